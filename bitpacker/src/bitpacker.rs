@@ -113,10 +113,13 @@ impl BitUnpacker {
     #[inline(never)]
     fn get_slow_path(&self, addr: usize, bit_shift: u32, data: &[u8]) -> u64 {
         let mut bytes: [u8; 8] = [0u8; 8];
-        let available_bytes = data.len() - addr;
+        // Readers check that `data` holds all of their values when they are opened, so reading
+        // past its end is a bug.
+        let available_data = &data[addr..];
+        let available_bytes = available_data.len();
         // This function is meant to only be called if we did not have 8 bytes to load.
         debug_assert!(available_bytes < 8);
-        bytes[..available_bytes].copy_from_slice(&data[addr..]);
+        bytes[..available_bytes].copy_from_slice(available_data);
         let val_unshifted_unmasked: u64 = u64::from_le_bytes(bytes);
         let val_shifted = val_unshifted_unmasked >> bit_shift;
         val_shifted & self.mask
@@ -348,6 +351,13 @@ mod test {
         bitunpacker.get_batch_u32s(8 * 4 - 2, &[0u8, 0u8, 0u8, 0u8], &mut output[..]);
     }
 
+    #[test]
+    #[should_panic]
+    fn test_get_panics_past_the_end() {
+        let bitunpacker = BitUnpacker::new(8);
+        bitunpacker.get(5, &[0u8, 0u8, 0u8, 0u8]);
+    }
+
     proptest::proptest! {
         #[test]
         fn test_get_batch_u32s_proptest(num_bits in 0u8..=32u8) {
@@ -1,7 +1,8 @@
-use columnar::ColumnarReader;
-use common::file_slice::{FileSlice, WrapFile};
 use std::io;
 use std::path::Path;
+
+use columnar::ColumnarReader;
+use common::file_slice::{FileSlice, WrapFile};
 use tantivy::directory::footer::Footer;
 
 fn main() -> io::Result<()> {
@@ -29,12 +30,16 @@ pub fn validate_columnar_reader(reader: &ColumnarReader) {
             | columnar::DynamicColumn::F64(_)
             | columnar::DynamicColumn::IpAddr(_)
             | columnar::DynamicColumn::DateTime(_)
+            | columnar::DynamicColumn::Uuid(_)
             | columnar::DynamicColumn::Bytes(_) => {}
             columnar::DynamicColumn::Str(str_column) => {
                 let num_vals = str_column.ords().values.num_vals();
                 let num_terms_dict = str_column.num_terms() as u64;
                 let max_ord = str_column.ords().values.iter().max().unwrap_or_default();
-                println!("{col_name:35}  num_vals {num_vals:10} \t num_terms_dict {num_terms_dict:8} max_ord: {max_ord:8}",);
+                println!(
+                    "{col_name:35}  num_vals {num_vals:10} \t num_terms_dict {num_terms_dict:8} \
+                     max_ord: {max_ord:8}",
+                );
                 for ord in str_column.ords().values.iter() {
                     assert!(ord < num_terms_dict);
                 }
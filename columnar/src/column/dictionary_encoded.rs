@@ -5,7 +5,7 @@ use std::{fmt, io};
 use sstable::{Dictionary, VoidSSTable};
 
 use crate::column::Column;
-use crate::RowId;
+use crate::{RowId, Streamer};
 
 /// Dictionary encoded column.
 ///
@@ -66,6 +66,14 @@ impl BytesColumn {
     pub fn dictionary(&self) -> &Dictionary<VoidSSTable> {
         self.dictionary.as_ref()
     }
+
+    /// Streams the distinct values of the column, in increasing order.
+    ///
+    /// The ordinal of the current term of the stream is the one stored in the column of
+    /// ordinals. The values of a [`StrColumn`] are valid utf-8.
+    pub fn dictionary_stream(&self) -> io::Result<Streamer<'_>> {
+        self.dictionary.stream()
+    }
 }
 
 #[derive(Clone)]
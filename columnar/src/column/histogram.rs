@@ -0,0 +1,216 @@
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+
+use super::{cmp_column_values, Column};
+use crate::RowId;
+
+/// Maximum number of values read to compute a histogram. Above that, the histogram is computed
+/// on a sample of the values.
+const MAX_NUM_SAMPLES: u32 = 10_000;
+
+/// A bucket of a [`ValueHistogram`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistogramBucket<T> {
+    /// Smallest value of the bucket.
+    pub lower: T,
+    /// Largest value of the bucket.
+    pub upper: T,
+    /// Number of values within the bucket. It is an estimate if the histogram is sampled.
+    pub count: u64,
+}
+
+/// Equi-depth histogram of the values of a column.
+///
+/// The buckets hold approximately the same number of values, so that dense ranges of values get
+/// narrow buckets. Buckets do not overlap: all the occurrences of a given value belong to the same
+/// bucket, which can therefore hold more values than the others.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValueHistogram<T> {
+    /// Buckets, in increasing order of values.
+    pub buckets: Vec<HistogramBucket<T>>,
+    /// True if the histogram was computed on a sample of the values of the column.
+    pub is_sampled: bool,
+}
+
+impl<T: PartialOrd + Copy> ValueHistogram<T> {
+    /// Returns the number of values of the column, or an estimate of it if the histogram is
+    /// sampled.
+    pub fn num_vals(&self) -> u64 {
+        self.buckets.iter().map(|bucket| bucket.count).sum()
+    }
+
+    /// Estimates the number of values of the column within the given range.
+    ///
+    /// The values of the buckets partially overlapping the range are assumed to be evenly split
+    /// between the inside and the outside of the range.
+    pub fn estimate_count(&self, range: RangeInclusive<T>) -> u64 {
+        let mut count = 0;
+        for bucket in &self.buckets {
+            if bucket.upper < *range.start() || bucket.lower > *range.end() {
+                continue;
+            }
+            if range.contains(&bucket.lower) && range.contains(&bucket.upper) {
+                count += bucket.count;
+            } else {
+                count += bucket.count / 2;
+            }
+        }
+        count
+    }
+}
+
+/// Returns the positions of the values read to compute the histogram.
+fn sample_positions(num_vals: RowId) -> impl Iterator<Item = RowId> {
+    let num_samples = num_vals.min(MAX_NUM_SAMPLES);
+    (0..num_samples as u64).map(move |i| (i * num_vals as u64 / num_samples as u64) as RowId)
+}
+
+pub(super) fn compute_histogram<T: PartialOrd + Copy + std::fmt::Debug + Send + Sync + 'static>(
+    column: &Column<T>,
+    num_buckets: usize,
+) -> ValueHistogram<T> {
+    let num_vals = column.values.num_vals();
+    let mut samples: Vec<T> = sample_positions(num_vals)
+        .map(|position| column.values.get_val(position))
+        .collect();
+    samples.sort_by(cmp_column_values);
+    let num_samples = samples.len() as u64;
+    let mut buckets: Vec<HistogramBucket<T>> = Vec::with_capacity(num_buckets);
+    let mut start = 0;
+    for bucket_ord in 1..=num_buckets as u64 {
+        let mut end = (bucket_ord * num_samples / num_buckets as u64) as usize;
+        if end <= start {
+            continue;
+        }
+        // The occurrences of the last value of the bucket are all kept in the bucket.
+        while end < samples.len()
+            && cmp_column_values(&samples[end], &samples[end - 1]) == Ordering::Equal
+        {
+            end += 1;
+        }
+        buckets.push(HistogramBucket {
+            lower: samples[start],
+            upper: samples[end - 1],
+            count: (end - start) as u64,
+        });
+        start = end;
+    }
+    let is_sampled = num_samples < num_vals as u64;
+    if is_sampled {
+        for bucket in &mut buckets {
+            bucket.count = (bucket.count * num_vals as u64 + num_samples / 2) / num_samples;
+        }
+    }
+    ValueHistogram {
+        buckets,
+        is_sampled,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::HistogramBucket;
+    use crate::column_values::VecColumn;
+    use crate::{Column, ColumnIndex};
+
+    fn column(values: Vec<u64>) -> Column<u64> {
+        Column {
+            index: ColumnIndex::Full,
+            values: Arc::new(VecColumn::from(values)),
+        }
+    }
+
+    #[test]
+    fn test_value_histogram_exact() {
+        let histogram = column((0..100).rev().collect()).value_histogram(4);
+        assert!(!histogram.is_sampled);
+        assert_eq!(
+            histogram.buckets,
+            vec![
+                HistogramBucket {
+                    lower: 0,
+                    upper: 24,
+                    count: 25,
+                },
+                HistogramBucket {
+                    lower: 25,
+                    upper: 49,
+                    count: 25,
+                },
+                HistogramBucket {
+                    lower: 50,
+                    upper: 74,
+                    count: 25,
+                },
+                HistogramBucket {
+                    lower: 75,
+                    upper: 99,
+                    count: 25,
+                },
+            ]
+        );
+        assert_eq!(histogram.num_vals(), 100);
+        assert_eq!(histogram.estimate_count(25..=74), 50);
+        assert_eq!(histogram.estimate_count(30..=74), 37);
+        assert_eq!(histogram.estimate_count(100..=200), 0);
+    }
+
+    #[test]
+    fn test_value_histogram_merges_repeated_values() {
+        let values: Vec<u64> = (0..100).map(|val| if val < 80 { 7 } else { val }).collect();
+        let histogram = column(values).value_histogram(4);
+        assert_eq!(
+            histogram.buckets,
+            vec![
+                HistogramBucket {
+                    lower: 7,
+                    upper: 7,
+                    count: 80,
+                },
+                HistogramBucket {
+                    lower: 80,
+                    upper: 99,
+                    count: 20,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_value_histogram_sampled() {
+        let histogram = column((0..1_000_000).collect()).value_histogram(10);
+        assert!(histogram.is_sampled);
+        assert_eq!(histogram.buckets.len(), 10);
+        assert_eq!(histogram.num_vals(), 1_000_000);
+        assert_eq!(histogram.buckets[0].lower, 0);
+        assert_eq!(histogram.buckets[0].count, 100_000);
+        let estimate = histogram.estimate_count(0..=499_999);
+        assert_eq!(estimate, 500_000);
+    }
+
+    #[test]
+    fn test_value_histogram_nan() {
+        let values = vec![f64::NAN, 2.0, 1.0, f64::NAN, 3.0, 4.0];
+        let column = Column {
+            index: ColumnIndex::Full,
+            values: Arc::new(VecColumn::from(values)),
+        };
+        let histogram = column.value_histogram(2);
+        assert_eq!(histogram.num_vals(), 6);
+        assert_eq!(histogram.buckets.len(), 2);
+        assert_eq!(histogram.buckets[0].lower, 1.0);
+        assert_eq!(histogram.buckets[0].upper, 3.0);
+        assert_eq!(histogram.buckets[0].count, 3);
+        assert_eq!(histogram.buckets[1].lower, 4.0);
+        assert!(histogram.buckets[1].upper.is_nan());
+        assert_eq!(histogram.buckets[1].count, 3);
+    }
+
+    #[test]
+    fn test_value_histogram_empty() {
+        assert!(column(Vec::new()).value_histogram(10).buckets.is_empty());
+        assert!(column(vec![1, 2]).value_histogram(0).buckets.is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 mod dictionary_encoded;
+mod histogram;
 mod serialize;
 
+use std::cmp::Ordering;
 use std::fmt::{self, Debug};
 use std::io::Write;
 use std::ops::{Range, RangeInclusive};
@@ -8,9 +10,11 @@ use std::sync::Arc;
 
 use common::BinarySerializable;
 pub use dictionary_encoded::{BytesColumn, StrColumn};
+pub use histogram::{HistogramBucket, ValueHistogram};
 pub use serialize::{
     open_column_bytes, open_column_str, open_column_u128, open_column_u128_as_compact_u64,
     open_column_u64, serialize_column_mappable_to_u128, serialize_column_mappable_to_u64,
+    u64_codec_types,
 };
 
 use crate::column_index::{ColumnIndex, Set};
@@ -56,6 +60,19 @@ impl<T: MonotonicallyMappableToU64> Column<T> {
     }
 }
 
+/// Total order over the values of a column.
+///
+/// f64 columns may hold NaN values, which are not comparable to any value. They are ordered after
+/// all of the other values, and are equal to each other.
+pub fn cmp_column_values<T: PartialOrd>(left: &T, right: &T) -> Ordering {
+    left.partial_cmp(right)
+        .unwrap_or_else(|| is_nan(left).cmp(&is_nan(right)))
+}
+
+fn is_nan<T: PartialOrd>(val: &T) -> bool {
+    val.partial_cmp(val).is_none()
+}
+
 impl<T: PartialOrd + Copy + Debug + Send + Sync + 'static> Column<T> {
     #[inline]
     pub fn get_cardinality(&self) -> Cardinality {
@@ -83,6 +100,24 @@ impl<T: PartialOrd + Copy + Debug + Send + Sync + 'static> Column<T> {
         self.values.max_value()
     }
 
+    /// Computes an equi-depth histogram of the values of the column, with at most `num_buckets`
+    /// buckets.
+    ///
+    /// Above 10,000 values, the histogram is computed on an evenly spaced sample of the values,
+    /// so that it is cheap enough to be computed at query time, e.g. to estimate the selectivity
+    /// of a range or to render a slider.
+    pub fn value_histogram(&self, num_buckets: usize) -> ValueHistogram<T> {
+        histogram::compute_histogram(self, num_buckets)
+    }
+
+    /// Returns the distinct values of the column, in increasing order.
+    pub fn distinct_values(&self) -> Vec<T> {
+        let mut values: Vec<T> = self.values.iter().collect();
+        values.sort_unstable_by(cmp_column_values);
+        values.dedup_by(|left, right| cmp_column_values(left, right) == Ordering::Equal);
+        values
+    }
+
     #[inline]
     pub fn first(&self, row_id: RowId) -> Option<T> {
         self.values_for_doc(row_id).next()
@@ -11,7 +11,8 @@ use crate::column_values::{
     load_u64_based_column_values, serialize_u64_based_column_values, CodecType, ColumnValues,
 };
 use crate::iterable::Iterable;
-use crate::{DocId, RowId, Version};
+use crate::utils::{try_rsplit, try_split};
+use crate::{DocId, RowId, Version, CURRENT_VERSION};
 
 pub struct SerializableMultivalueIndex<'a> {
     pub doc_ids_with_values: SerializableOptionalIndex<'a>,
@@ -55,13 +56,13 @@ pub fn open_multivalued_index(
                 start_index_column,
             }))
         }
-        Version::V2 => {
-            let (body_bytes, optional_index_len) = bytes.rsplit(4);
+        Version::V2 | Version::V3 => {
+            let (body_bytes, optional_index_len) = try_rsplit(bytes, 4)?;
             let optional_index_len =
                 u32::from_le_bytes(optional_index_len.as_slice().try_into().unwrap());
             let (optional_index_bytes, start_index_bytes) =
-                body_bytes.split(optional_index_len as usize);
-            let optional_index = open_optional_index(optional_index_bytes)?;
+                try_split(body_bytes, optional_index_len as usize)?;
+            let optional_index = open_optional_index(optional_index_bytes, format_version)?;
             let start_index_column: Arc<dyn ColumnValues<RowId>> =
                 load_u64_based_column_values(start_index_bytes)?;
             Ok(MultiValueIndex::MultiValueIndexV2(MultiValueIndexV2 {
@@ -186,7 +187,7 @@ impl MultiValueIndex {
         let mut buffer = Vec::new();
         serialize_multivalued_index(&serializable_multivalued_index, &mut buffer).unwrap();
         let bytes = OwnedBytes::new(buffer);
-        open_multivalued_index(bytes, Version::V2).unwrap()
+        open_multivalued_index(bytes, CURRENT_VERSION).unwrap()
     }
 
     pub fn get_start_index_column(&self) -> &Arc<dyn crate::ColumnValues<RowId>> {
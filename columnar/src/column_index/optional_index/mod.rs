@@ -3,15 +3,20 @@ use std::sync::Arc;
 
 mod set;
 mod set_block;
+mod sparse_index;
 
 use common::{BinarySerializable, OwnedBytes, VInt};
 pub use set::{SelectCursor, Set, SetCodec};
 use set_block::{
     DenseBlock, DenseBlockCodec, SparseBlock, SparseBlockCodec, DENSE_BLOCK_NUM_BYTES,
 };
+use sparse_index::{
+    open_sparse_index, serialize_sparse_index, sparse_index_num_bytes, SparseIndex,
+};
 
 use crate::iterable::Iterable;
-use crate::{DocId, InvalidData, RowId};
+use crate::utils::try_rsplit;
+use crate::{DocId, InvalidData, RowId, Version, CURRENT_VERSION};
 
 /// The threshold for for number of elements after which we switch to dense block encoding.
 ///
@@ -79,11 +84,30 @@ impl BlockVariant {
 /// When opening the data layout, the data is expanded to `Vec<SparseCodecBlockVariant>`, where the
 /// index is the block index. For each block `byte_start` and `offset` is computed.
 #[derive(Clone)]
+struct BlockwiseIndex {
+    block_data: OwnedBytes,
+    block_metas: Arc<[BlockMeta]>,
+}
+
+/// Index of the rows that have a value, in an optional column.
+///
+/// Depending on the density of the column, the index is either encoded blockwise (see
+/// `BlockwiseIndex`), or as the plain list of the non-null row ids (see `SparseIndex`).
+///
+/// # Serialized Data Layout
+/// Since `Version::V3`, the index starts with the code of its codec:
+/// [u8; Desc: codec][VInt; Desc: num rows][codec specific data]
+#[derive(Clone)]
 pub struct OptionalIndex {
     num_docs: RowId,
     num_non_null_docs: RowId,
-    block_data: OwnedBytes,
-    block_metas: Arc<[BlockMeta]>,
+    repr: OptionalIndexRepr,
+}
+
+#[derive(Clone)]
+enum OptionalIndexRepr {
+    Blockwise(BlockwiseIndex),
+    Sparse(SparseIndex),
 }
 
 impl Iterable<u32> for &OptionalIndex {
@@ -131,17 +155,17 @@ impl BlockSelectCursor<'_> {
         }
     }
 }
-pub struct OptionalIndexSelectCursor<'a> {
+struct BlockwiseSelectCursor<'a> {
     current_block_cursor: BlockSelectCursor<'a>,
     current_block_id: u16,
     // The current block is guaranteed to contain ranks < end_rank.
     current_block_end_rank: RowId,
-    optional_index: &'a OptionalIndex,
+    optional_index: &'a BlockwiseIndex,
     block_doc_idx_start: RowId,
     num_null_rows_before_block: RowId,
 }
 
-impl OptionalIndexSelectCursor<'_> {
+impl BlockwiseSelectCursor<'_> {
     fn search_and_load_block(&mut self, rank: RowId) {
         if rank < self.current_block_end_rank {
             // we are already in the right block
@@ -165,7 +189,7 @@ impl OptionalIndexSelectCursor<'_> {
     }
 }
 
-impl SelectCursor<RowId> for OptionalIndexSelectCursor<'_> {
+impl SelectCursor<RowId> for BlockwiseSelectCursor<'_> {
     fn select(&mut self, rank: RowId) -> RowId {
         self.search_and_load_block(rank);
         let index_in_block = (rank - self.num_null_rows_before_block) as u16;
@@ -173,9 +197,9 @@ impl SelectCursor<RowId> for OptionalIndexSelectCursor<'_> {
     }
 }
 
-impl Set<RowId> for OptionalIndex {
+impl Set<RowId> for BlockwiseIndex {
     type SelectCursor<'b>
-        = OptionalIndexSelectCursor<'b>
+        = BlockwiseSelectCursor<'b>
     where Self: 'b;
     // Check if value at position is not null.
     #[inline]
@@ -191,13 +215,9 @@ impl Set<RowId> for OptionalIndex {
         }
     }
 
-    /// Any value doc_id is allowed.
-    /// In particular, doc_id = num_rows.
+    /// The doc_id needs to be lower than num_rows.
     #[inline]
     fn rank(&self, doc_id: DocId) -> RowId {
-        if doc_id >= self.num_docs() {
-            return self.num_non_nulls();
-        }
         let RowAddr {
             block_id,
             in_block_row_id,
@@ -243,8 +263,8 @@ impl Set<RowId> for OptionalIndex {
         block_doc_idx_start + in_block_rank as u32
     }
 
-    fn select_cursor(&self) -> OptionalIndexSelectCursor<'_> {
-        OptionalIndexSelectCursor {
+    fn select_cursor(&self) -> BlockwiseSelectCursor<'_> {
+        BlockwiseSelectCursor {
             current_block_cursor: BlockSelectCursor::Sparse(
                 SparseBlockCodec::open(b"").select_cursor(),
             ),
@@ -267,7 +287,7 @@ impl OptionalIndex {
         let mut buffer = Vec::new();
         serialize_optional_index(&row_ids, num_rows, &mut buffer).unwrap();
         let bytes = OwnedBytes::new(buffer);
-        open_optional_index(bytes).unwrap()
+        open_optional_index(bytes, CURRENT_VERSION).unwrap()
     }
 
     pub fn num_docs(&self) -> RowId {
@@ -290,6 +310,14 @@ impl OptionalIndex {
         }
     }
 
+    /// Returns true if the index is encoded as the list of the non-null row ids.
+    #[cfg(test)]
+    pub(crate) fn is_sparse(&self) -> bool {
+        matches!(self.repr, OptionalIndexRepr::Sparse(_))
+    }
+}
+
+impl BlockwiseIndex {
     #[inline]
     fn block(&self, block_meta: BlockMeta) -> Block<'_> {
         let BlockMeta {
@@ -326,15 +354,88 @@ impl OptionalIndex {
     // The Iterator API is a probably a bad idea
 }
 
+enum SelectCursorRepr<'a> {
+    Blockwise(BlockwiseSelectCursor<'a>),
+    Sparse(&'a SparseIndex),
+}
+
+pub struct OptionalIndexSelectCursor<'a>(SelectCursorRepr<'a>);
+
+impl SelectCursor<RowId> for OptionalIndexSelectCursor<'_> {
+    #[inline]
+    fn select(&mut self, rank: RowId) -> RowId {
+        match &mut self.0 {
+            SelectCursorRepr::Blockwise(select_cursor) => select_cursor.select(rank),
+            SelectCursorRepr::Sparse(select_cursor) => select_cursor.select(rank),
+        }
+    }
+}
+
+impl Set<RowId> for OptionalIndex {
+    type SelectCursor<'b>
+        = OptionalIndexSelectCursor<'b>
+    where Self: 'b;
+    // Check if value at position is not null.
+    #[inline]
+    fn contains(&self, row_id: RowId) -> bool {
+        match &self.repr {
+            OptionalIndexRepr::Blockwise(blockwise_index) => blockwise_index.contains(row_id),
+            OptionalIndexRepr::Sparse(sparse_index) => sparse_index.contains(row_id),
+        }
+    }
+
+    /// Any value doc_id is allowed.
+    /// In particular, doc_id = num_rows.
+    #[inline]
+    fn rank(&self, doc_id: DocId) -> RowId {
+        if doc_id >= self.num_docs() {
+            return self.num_non_nulls();
+        }
+        match &self.repr {
+            OptionalIndexRepr::Blockwise(blockwise_index) => blockwise_index.rank(doc_id),
+            OptionalIndexRepr::Sparse(sparse_index) => sparse_index.rank(doc_id),
+        }
+    }
+
+    /// Any value doc_id is allowed.
+    /// In particular, doc_id = num_rows.
+    #[inline]
+    fn rank_if_exists(&self, doc_id: DocId) -> Option<RowId> {
+        match &self.repr {
+            OptionalIndexRepr::Blockwise(blockwise_index) => blockwise_index.rank_if_exists(doc_id),
+            OptionalIndexRepr::Sparse(sparse_index) => sparse_index.rank_if_exists(doc_id),
+        }
+    }
+
+    #[inline]
+    fn select(&self, rank: RowId) -> RowId {
+        match &self.repr {
+            OptionalIndexRepr::Blockwise(blockwise_index) => blockwise_index.select(rank),
+            OptionalIndexRepr::Sparse(sparse_index) => sparse_index.select(rank),
+        }
+    }
+
+    fn select_cursor(&self) -> OptionalIndexSelectCursor<'_> {
+        OptionalIndexSelectCursor(match &self.repr {
+            OptionalIndexRepr::Blockwise(blockwise_index) => {
+                SelectCursorRepr::Blockwise(blockwise_index.select_cursor())
+            }
+            OptionalIndexRepr::Sparse(sparse_index) => {
+                SelectCursorRepr::Sparse(sparse_index.select_cursor())
+            }
+        })
+    }
+}
+
 #[derive(Copy, Clone)]
 enum Block<'a> {
     Dense(DenseBlock<'a>),
     Sparse(SparseBlock<'a>),
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum OptionalIndexCodec {
-    Dense = 0,
+    Blockwise = 0,
     Sparse = 1,
 }
 
@@ -345,7 +446,7 @@ impl OptionalIndexCodec {
 
     fn try_from_code(code: u8) -> Result<Self, InvalidData> {
         match code {
-            0 => Ok(Self::Dense),
+            0 => Ok(Self::Blockwise),
             1 => Ok(Self::Sparse),
             _ => Err(InvalidData),
         }
@@ -374,13 +475,80 @@ fn serialize_optional_index_block(block_els: &[u16], out: &mut impl io::Write) -
     Ok(())
 }
 
+/// Picks the codec with the smallest footprint.
+///
+/// The footprint of the blockwise codec includes the block metas it allocates when opened,
+/// one per block of `ELEMENTS_PER_BLOCK` rows, even for empty blocks. In practice, the
+/// sparse codec is picked for columns where well below 1% of the rows have a value.
+///
+/// Also returns the number of non-null rows and the last non-null row id.
+fn choose_codec(
+    non_null_rows: &dyn Iterable<RowId>,
+    num_rows: RowId,
+) -> (OptionalIndexCodec, RowId, RowId) {
+    let mut num_non_null_rows = 0u32;
+    let mut last_row_id = 0u32;
+    let mut blockwise_num_bytes = 0u64;
+    let mut current_block: Option<(u16, u32)> = None;
+    let block_num_bytes = |num_rows_in_block: u32| {
+        let block_variant = if is_sparse(num_rows_in_block) {
+            BlockVariant::Sparse {
+                num_vals: num_rows_in_block as u16,
+            }
+        } else {
+            BlockVariant::Dense
+        };
+        (SERIALIZED_BLOCK_META_NUM_BYTES as u32 + block_variant.num_bytes_in_block()) as u64
+    };
+    for row_id in non_null_rows.boxed_iter() {
+        num_non_null_rows += 1;
+        last_row_id = row_id;
+        let block_id = row_addr_from_row_id(row_id).block_id;
+        match &mut current_block {
+            Some((current_block_id, num_rows_in_block)) if *current_block_id == block_id => {
+                *num_rows_in_block += 1;
+            }
+            _ => {
+                if let Some((_, num_rows_in_block)) = current_block {
+                    blockwise_num_bytes += block_num_bytes(num_rows_in_block);
+                }
+                current_block = Some((block_id, 1));
+            }
+        }
+    }
+    if let Some((_, num_rows_in_block)) = current_block {
+        blockwise_num_bytes += block_num_bytes(num_rows_in_block);
+    }
+    let num_blocks = num_rows.div_ceil(ELEMENTS_PER_BLOCK) as u64;
+    blockwise_num_bytes += num_blocks * std::mem::size_of::<BlockMeta>() as u64;
+    let codec = if sparse_index_num_bytes(num_non_null_rows, last_row_id) <= blockwise_num_bytes {
+        OptionalIndexCodec::Sparse
+    } else {
+        OptionalIndexCodec::Blockwise
+    };
+    (codec, num_non_null_rows, last_row_id)
+}
+
 pub fn serialize_optional_index<W: io::Write>(
     non_null_rows: &dyn Iterable<RowId>,
     num_rows: RowId,
     output: &mut W,
 ) -> io::Result<()> {
+    let (codec, num_non_null_rows, last_row_id) = choose_codec(non_null_rows, num_rows);
+    codec.serialize(output)?;
     VInt(num_rows as u64).serialize(output)?;
+    match codec {
+        OptionalIndexCodec::Blockwise => serialize_blockwise_index(non_null_rows, output),
+        OptionalIndexCodec::Sparse => {
+            serialize_sparse_index(non_null_rows, num_non_null_rows, last_row_id, output)
+        }
+    }
+}
 
+fn serialize_blockwise_index<W: io::Write>(
+    non_null_rows: &dyn Iterable<RowId>,
+    output: &mut W,
+) -> io::Result<()> {
     let mut rows_it = non_null_rows.boxed_iter();
     let mut block_metadata: Vec<SerializedBlockMeta> = Vec::new();
     let mut current_block = Vec::new();
@@ -515,21 +683,58 @@ fn deserialize_optional_index_block_metadatas(
     (block_metas.into_boxed_slice(), non_null_rows_before_block)
 }
 
-pub fn open_optional_index(bytes: OwnedBytes) -> io::Result<OptionalIndex> {
-    let (mut bytes, num_non_empty_blocks_bytes) = bytes.rsplit(2);
+pub fn open_optional_index(
+    mut bytes: OwnedBytes,
+    format_version: Version,
+) -> io::Result<OptionalIndex> {
+    let codec = match format_version {
+        Version::V1 | Version::V2 => OptionalIndexCodec::Blockwise,
+        Version::V3 => OptionalIndexCodec::deserialize(&mut bytes)?,
+    };
+    let num_docs = VInt::deserialize_u64(&mut bytes)? as u32;
+    match codec {
+        OptionalIndexCodec::Blockwise => open_blockwise_index(bytes, num_docs),
+        OptionalIndexCodec::Sparse => {
+            let sparse_index = open_sparse_index(bytes)?;
+            Ok(OptionalIndex {
+                num_docs,
+                num_non_null_docs: sparse_index.num_non_nulls(),
+                repr: OptionalIndexRepr::Sparse(sparse_index),
+            })
+        }
+    }
+}
+
+fn open_blockwise_index(bytes: OwnedBytes, num_docs: RowId) -> io::Result<OptionalIndex> {
+    let (bytes, num_non_empty_blocks_bytes) = try_rsplit(bytes, 2)?;
     let num_non_empty_block_bytes =
         u16::from_le_bytes(num_non_empty_blocks_bytes.as_slice().try_into().unwrap());
-    let num_docs = VInt::deserialize_u64(&mut bytes)? as u32;
     let block_metas_num_bytes =
         num_non_empty_block_bytes as usize * SERIALIZED_BLOCK_META_NUM_BYTES;
-    let (block_data, block_metas) = bytes.rsplit(block_metas_num_bytes);
+    let (block_data, block_metas) = try_rsplit(bytes, block_metas_num_bytes)?;
     let (block_metas, num_non_null_docs) =
         deserialize_optional_index_block_metadatas(block_metas.as_slice(), num_docs);
+    let block_data_num_bytes = block_metas
+        .iter()
+        .map(|block_meta| {
+            block_meta.start_byte_offset as usize
+                + block_meta.block_variant.num_bytes_in_block() as usize
+        })
+        .max()
+        .unwrap_or(0);
+    if block_data_num_bytes > block_data.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Optional index blocks exceed the optional index data.",
+        ));
+    }
     let optional_index = OptionalIndex {
         num_docs,
         num_non_null_docs,
-        block_data,
-        block_metas: block_metas.into(),
+        repr: OptionalIndexRepr::Blockwise(BlockwiseIndex {
+            block_data,
+            block_metas: block_metas.into(),
+        }),
     };
     Ok(optional_index)
 }
@@ -0,0 +1,139 @@
+use std::io;
+
+use common::{BinarySerializable, OwnedBytes, VInt};
+use tantivy_bitpacker::{compute_num_bits, BitPacker, BitUnpacker};
+
+use super::{SelectCursor, Set};
+use crate::iterable::Iterable;
+use crate::RowId;
+
+/// Optional index for very sparse columns, stored as the sorted list of the non-null row ids.
+///
+/// Contrary to the blockwise optional index, opening it does not require any allocation
+/// proportional to the number of rows.
+///
+/// # Serialized Data Layout
+/// [VInt; Desc: number of non-null rows][u8; Desc: number of bits per row id]
+/// [bitpacked row ids, sorted]
+#[derive(Clone)]
+pub(crate) struct SparseIndex {
+    num_non_null_rows: RowId,
+    bit_unpacker: BitUnpacker,
+    row_ids: OwnedBytes,
+}
+
+/// Returns the number of bytes used by the row ids of a sparse index.
+pub(crate) fn sparse_index_num_bytes(num_non_null_rows: RowId, last_row_id: RowId) -> u64 {
+    let num_bits = compute_num_bits(last_row_id as u64) as u64;
+    (num_non_null_rows as u64 * num_bits).div_ceil(8)
+}
+
+pub(crate) fn serialize_sparse_index(
+    non_null_rows: &dyn Iterable<RowId>,
+    num_non_null_rows: RowId,
+    last_row_id: RowId,
+    output: &mut impl io::Write,
+) -> io::Result<()> {
+    VInt(num_non_null_rows as u64).serialize(output)?;
+    let num_bits = compute_num_bits(last_row_id as u64);
+    num_bits.serialize(output)?;
+    let mut bit_packer = BitPacker::new();
+    for row_id in non_null_rows.boxed_iter() {
+        bit_packer.write(row_id as u64, num_bits, output)?;
+    }
+    bit_packer.close(output)?;
+    Ok(())
+}
+
+pub(crate) fn open_sparse_index(mut bytes: OwnedBytes) -> io::Result<SparseIndex> {
+    let num_non_null_rows = VInt::deserialize_u64(&mut bytes)?;
+    let num_bits = u8::deserialize(&mut bytes)?;
+    if num_bits > 32 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid number of bits for row ids: {num_bits}."),
+        ));
+    }
+    let num_non_null_rows = RowId::try_from(num_non_null_rows)
+        .ok()
+        .filter(|&num_non_null_rows| {
+            (num_non_null_rows as u64 * num_bits as u64).div_ceil(8) <= bytes.len() as u64
+        })
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Sparse optional index row ids are truncated.",
+            )
+        })?;
+    Ok(SparseIndex {
+        num_non_null_rows,
+        bit_unpacker: BitUnpacker::new(num_bits),
+        row_ids: bytes,
+    })
+}
+
+impl SparseIndex {
+    pub fn num_non_nulls(&self) -> RowId {
+        self.num_non_null_rows
+    }
+
+    #[inline]
+    fn row_id(&self, rank: RowId) -> RowId {
+        self.bit_unpacker.get(rank, &self.row_ids) as RowId
+    }
+
+    /// Looks for the row id. Returns its rank if found, or the rank of the next row id
+    /// otherwise.
+    #[inline]
+    fn binary_search(&self, target: RowId) -> Result<RowId, RowId> {
+        let mut left = 0;
+        let mut right = self.num_non_null_rows;
+        while left < right {
+            let mid = left + (right - left) / 2;
+            let mid_row_id = self.row_id(mid);
+            match mid_row_id.cmp(&target) {
+                std::cmp::Ordering::Less => left = mid + 1,
+                std::cmp::Ordering::Greater => right = mid,
+                std::cmp::Ordering::Equal => return Ok(mid),
+            }
+        }
+        Err(left)
+    }
+}
+
+impl SelectCursor<RowId> for &SparseIndex {
+    #[inline]
+    fn select(&mut self, rank: RowId) -> RowId {
+        self.row_id(rank)
+    }
+}
+
+impl Set<RowId> for SparseIndex {
+    type SelectCursor<'b>
+        = &'b SparseIndex
+    where Self: 'b;
+
+    #[inline]
+    fn contains(&self, row_id: RowId) -> bool {
+        self.binary_search(row_id).is_ok()
+    }
+
+    #[inline]
+    fn rank(&self, row_id: RowId) -> RowId {
+        self.binary_search(row_id).unwrap_or_else(|rank| rank)
+    }
+
+    #[inline]
+    fn rank_if_exists(&self, row_id: RowId) -> Option<RowId> {
+        self.binary_search(row_id).ok()
+    }
+
+    #[inline]
+    fn select(&self, rank: RowId) -> RowId {
+        self.row_id(rank)
+    }
+
+    fn select_cursor(&self) -> &SparseIndex {
+        self
+    }
+}
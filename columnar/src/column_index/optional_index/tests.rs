@@ -59,7 +59,7 @@ fn test_with_random_sets_simple() {
     let vals = 10..ELEMENTS_PER_BLOCK * 2;
     let mut out: Vec<u8> = Vec::new();
     serialize_optional_index(&vals, 100, &mut out).unwrap();
-    let null_index = open_optional_index(OwnedBytes::new(out)).unwrap();
+    let null_index = open_optional_index(OwnedBytes::new(out), CURRENT_VERSION).unwrap();
     let ranks: Vec<u32> = (65_472u32..65_473u32).collect();
     let els: Vec<u32> = ranks.iter().copied().map(|rank| rank + 10).collect();
     let mut select_cursor = null_index.select_cursor();
@@ -68,6 +68,51 @@ fn test_with_random_sets_simple() {
     }
 }
 
+#[test]
+fn test_optional_index_codec_selection() {
+    let sparse_row_ids: Vec<RowId> = (0..10).map(|i| i * 100_000).collect();
+    assert!(OptionalIndex::for_test(1_000_000, &sparse_row_ids).is_sparse());
+    let dense_row_ids: Vec<RowId> = (0..100_000).map(|i| i * 10).collect();
+    assert!(!OptionalIndex::for_test(1_000_000, &dense_row_ids).is_sparse());
+    assert!(OptionalIndex::for_test(1_000_000, &[]).is_sparse());
+}
+
+#[test]
+fn test_sparse_optional_index() {
+    let row_ids = [3, 70_000, 70_001, 999_999];
+    let optional_index = OptionalIndex::for_test(1_000_000, &row_ids);
+    assert!(optional_index.is_sparse());
+    assert_eq!(optional_index.num_docs(), 1_000_000);
+    assert_eq!(optional_index.num_non_nulls(), 4);
+    assert!(optional_index.contains(70_001));
+    assert!(!optional_index.contains(4));
+    let ranks: Vec<RowId> = [0, 3, 4, 70_001, 999_999, 1_000_000]
+        .into_iter()
+        .map(|doc_id| optional_index.rank(doc_id))
+        .collect();
+    assert_eq!(ranks, [0, 0, 1, 2, 3, 4]);
+    assert_eq!(optional_index.rank_if_exists(70_000), Some(1));
+    assert_eq!(optional_index.rank_if_exists(70_002), None);
+    assert_eq!(optional_index.select(2), 70_001);
+    assert_eq!(optional_index.iter_docs().collect::<Vec<_>>(), row_ids);
+    let mut ranks = vec![1, 3];
+    optional_index.select_batch(&mut ranks);
+    assert_eq!(ranks, [70_000, 999_999]);
+}
+
+#[test]
+fn test_open_optional_index_v2() {
+    // Before `Version::V3`, optional indexes were always blockwise, without a codec.
+    let mut out: Vec<u8> = Vec::new();
+    VInt(10).serialize(&mut out).unwrap();
+    serialize_blockwise_index(&&[1u32, 5][..], &mut out).unwrap();
+    let optional_index = open_optional_index(OwnedBytes::new(out), Version::V2).unwrap();
+    assert!(!optional_index.is_sparse());
+    assert_eq!(optional_index.num_docs(), 10);
+    assert_eq!(optional_index.iter_docs().collect::<Vec<_>>(), [1, 5]);
+    assert_eq!(optional_index.rank(3), 1);
+}
+
 #[test]
 fn test_optional_index_trailing_empty_blocks() {
     test_null_index(&[false]);
@@ -102,7 +147,7 @@ impl<'a> Iterable<RowId> for &'a [bool] {
 fn test_null_index(data: &[bool]) {
     let mut out: Vec<u8> = Vec::new();
     serialize_optional_index(&data, data.len() as RowId, &mut out).unwrap();
-    let null_index = open_optional_index(OwnedBytes::new(out)).unwrap();
+    let null_index = open_optional_index(OwnedBytes::new(out), CURRENT_VERSION).unwrap();
     let orig_idx_with_value: Vec<u32> = data
         .iter()
         .enumerate()
@@ -241,7 +286,7 @@ mod bench {
             .collect();
         serialize_optional_index(&&vals[..], TOTAL_NUM_VALUES, &mut out).unwrap();
 
-        open_optional_index(OwnedBytes::new(out)).unwrap()
+        open_optional_index(OwnedBytes::new(out), CURRENT_VERSION).unwrap()
     }
 
     fn random_range_iterator(
@@ -80,7 +80,7 @@ pub fn open_column_index(
     match cardinality {
         Cardinality::Full => Ok(ColumnIndex::Full),
         Cardinality::Optional => {
-            let optional_index = super::optional_index::open_optional_index(bytes)?;
+            let optional_index = super::optional_index::open_optional_index(bytes, format_version)?;
             Ok(ColumnIndex::Optional(optional_index))
         }
         Cardinality::Multivalued => {
@@ -1,6 +1,8 @@
 use std::fmt::Debug;
 use std::net::Ipv6Addr;
 
+use uuid::Uuid;
+
 /// Montonic maps a value to u128 value space
 /// Monotonic mapping enables `PartialOrd` on u128 space without conversion to original space.
 pub trait MonotonicallyMappableToU128: 'static + PartialOrd + Copy + Debug + Send + Sync {
@@ -36,6 +38,16 @@ impl MonotonicallyMappableToU128 for Ipv6Addr {
     }
 }
 
+impl MonotonicallyMappableToU128 for Uuid {
+    fn to_u128(self) -> u128 {
+        self.as_u128()
+    }
+
+    fn from_u128(val: u128) -> Self {
+        Uuid::from_u128(val)
+    }
+}
+
 fn ip_to_u128(ip_addr: Ipv6Addr) -> u128 {
     u128::from_be_bytes(ip_addr.octets())
 }
@@ -41,8 +41,13 @@ impl BinarySerializable for ColumnStats {
         let gcd = VInt::deserialize(reader)?.0;
         let gcd = NonZeroU64::new(gcd)
             .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "GCD of 0 is forbidden"))?;
-        let amplitude = VInt::deserialize(reader)?.0 * gcd.get();
-        let max_value = min_value + amplitude;
+        let max_value = VInt::deserialize(reader)?
+            .0
+            .checked_mul(gcd.get())
+            .and_then(|amplitude| amplitude.checked_add(min_value))
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "Column amplitude overflows u64")
+            })?;
         let num_rows = VInt::deserialize(reader)?.0 as RowId;
         Ok(ColumnStats {
             min_value,
@@ -0,0 +1,193 @@
+//! The bitpacked codec bitpacks the delta of each value to the minimum value of the column.
+//!
+//! It is well suited for values clustered in a single dense range, like the ip addresses of a
+//! subnet.
+use std::io::{self, Write};
+use std::ops::{Range, RangeInclusive};
+
+use common::{BinarySerializable, CountingWriter, OwnedBytes, VIntU128};
+use tantivy_bitpacker::{compute_num_bits, BitPacker, BitUnpacker};
+
+use crate::column_values::u64_based::{check_bitpacked_len, open_bit_unpacker};
+use crate::column_values::ColumnValues;
+use crate::RowId;
+
+pub struct BitpackedCompressor {
+    min_value: u128,
+    max_value: u128,
+    num_vals: RowId,
+    num_bits: u8,
+}
+
+/// Returns the number of bits required to bitpack the deltas to `min_value`, or `None` if the
+/// deltas don't fit in a `u32`, which is required to use them as compact values.
+fn num_bits(min_value: u128, max_value: u128) -> Option<u8> {
+    let amplitude = u32::try_from(max_value - min_value).ok()?;
+    Some(compute_num_bits(amplitude as u64))
+}
+
+impl BitpackedCompressor {
+    /// Returns `None` if the amplitude of the column is too large to be bitpacked.
+    pub fn train_from(
+        min_value: u128,
+        max_value: u128,
+        num_vals: RowId,
+    ) -> Option<BitpackedCompressor> {
+        let num_bits = num_bits(min_value, max_value)?;
+        Some(BitpackedCompressor {
+            min_value,
+            max_value,
+            num_vals,
+            num_bits,
+        })
+    }
+
+    fn serialize_params(&self, writer: &mut impl Write) -> io::Result<()> {
+        VIntU128(self.min_value).serialize(writer)?;
+        VIntU128(self.max_value - self.min_value).serialize(writer)?;
+        Ok(())
+    }
+
+    /// Returns the exact number of bytes required to serialize the column with this codec.
+    pub fn num_bytes(&self) -> u64 {
+        let mut counting_writer = CountingWriter::wrap(io::sink());
+        // Writing to a sink cannot fail.
+        self.serialize_params(&mut counting_writer).unwrap();
+        counting_writer.written_bytes() + (self.num_vals as u64 * self.num_bits as u64).div_ceil(8)
+    }
+
+    pub fn compress_into(
+        self,
+        vals: impl Iterator<Item = u128>,
+        write: &mut impl Write,
+    ) -> io::Result<()> {
+        self.serialize_params(write)?;
+        let mut bitpacker = BitPacker::default();
+        for val in vals {
+            if !(self.min_value..=self.max_value).contains(&val) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Value is out of the bitpacked range. This is a bug.",
+                ));
+            }
+            bitpacker.write((val - self.min_value) as u64, self.num_bits, write)?;
+        }
+        bitpacker.close(write)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BitpackedDecompressor {
+    data: OwnedBytes,
+    bit_unpacker: BitUnpacker,
+    min_value: u128,
+    max_value: u128,
+    num_vals: RowId,
+}
+
+impl BitpackedDecompressor {
+    pub fn open(mut data: OwnedBytes, num_vals: RowId) -> io::Result<BitpackedDecompressor> {
+        let min_value = VIntU128::deserialize(&mut data)?.0;
+        let amplitude = VIntU128::deserialize(&mut data)?.0;
+        let max_value = min_value
+            .checked_add(amplitude)
+            .filter(|&max_value| num_bits(min_value, max_value).is_some())
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "Invalid u128 bitpacked range")
+            })?;
+        let num_bits = compute_num_bits(amplitude as u64);
+        check_bitpacked_len(&data, num_bits, num_vals)?;
+        Ok(BitpackedDecompressor {
+            data,
+            bit_unpacker: open_bit_unpacker(num_bits)?,
+            min_value,
+            max_value,
+            num_vals,
+        })
+    }
+
+    #[inline]
+    pub fn get_compact(&self, idx: u32) -> u32 {
+        self.bit_unpacker.get(idx, &self.data) as u32
+    }
+
+    #[inline]
+    pub fn compact_to_u128(&self, compact: u32) -> u128 {
+        self.min_value.wrapping_add(compact as u128)
+    }
+
+    pub fn compact_min_value(&self) -> u32 {
+        0
+    }
+
+    pub fn compact_max_value(&self) -> u32 {
+        (self.max_value - self.min_value) as u32
+    }
+
+    #[inline]
+    pub fn iter_compact(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.num_vals).map(move |idx| self.get_compact(idx))
+    }
+
+    pub fn get_positions_for_compact_value_range(
+        &self,
+        value_range: RangeInclusive<u32>,
+        position_range: Range<u32>,
+        positions: &mut Vec<u32>,
+    ) {
+        let position_range = position_range.start..position_range.end.min(self.num_vals);
+        self.bit_unpacker.get_ids_for_value_range(
+            *value_range.start() as u64..=*value_range.end() as u64,
+            position_range,
+            &self.data,
+            positions,
+        );
+    }
+}
+
+impl ColumnValues<u128> for BitpackedDecompressor {
+    #[inline]
+    fn get_val(&self, idx: u32) -> u128 {
+        self.compact_to_u128(self.get_compact(idx))
+    }
+
+    fn min_value(&self) -> u128 {
+        self.min_value
+    }
+
+    fn max_value(&self) -> u128 {
+        self.max_value
+    }
+
+    fn num_vals(&self) -> u32 {
+        self.num_vals
+    }
+
+    #[inline]
+    fn iter(&self) -> Box<dyn Iterator<Item = u128> + '_> {
+        Box::new(
+            self.iter_compact()
+                .map(|compact| self.compact_to_u128(compact)),
+        )
+    }
+
+    fn get_row_ids_for_value_range(
+        &self,
+        value_range: RangeInclusive<u128>,
+        position_range: Range<u32>,
+        positions: &mut Vec<u32>,
+    ) {
+        let start = (*value_range.start()).max(self.min_value);
+        let end = (*value_range.end()).min(self.max_value);
+        if start > end {
+            positions.clear();
+            return;
+        }
+        self.get_positions_for_compact_value_range(
+            (start - self.min_value) as u32..=(end - self.min_value) as u32,
+            position_range,
+            positions,
+        );
+    }
+}
@@ -24,6 +24,7 @@ use build_compact_space::get_compact_space;
 use common::{BinarySerializable, CountingWriter, OwnedBytes, VInt, VIntU128};
 use tantivy_bitpacker::{BitPacker, BitUnpacker};
 
+use crate::column_values::u64_based::{check_bitpacked_len, open_bit_unpacker};
 use crate::column_values::ColumnValues;
 use crate::RowId;
 
@@ -82,22 +83,34 @@ impl BinarySerializable for CompactSpace {
         let mut ranges_mapping: Vec<RangeMapping> = vec![];
         let mut value = 0u128;
         let mut compact_start = 1u32; // 0 is reserved for `null`
+        let invalid_compact_space =
+            || io::Error::new(io::ErrorKind::InvalidData, "Invalid compact space ranges");
         for _ in 0..num_ranges {
             let blank_delta_start = VIntU128::deserialize(reader)?.0;
-            value += blank_delta_start;
+            value = value
+                .checked_add(blank_delta_start)
+                .ok_or_else(invalid_compact_space)?;
             let blank_start = value;
 
             let blank_delta_end = VIntU128::deserialize(reader)?.0;
-            value += blank_delta_end;
+            value = value
+                .checked_add(blank_delta_end)
+                .ok_or_else(invalid_compact_space)?;
             let blank_end = value;
 
+            // The compact space needs to fit in a u32.
+            let range_length = u32::try_from(blank_delta_end)
+                .ok()
+                .and_then(|delta| delta.checked_add(1))
+                .ok_or_else(invalid_compact_space)?;
             let range_mapping = RangeMapping {
                 value_range: blank_start..=blank_end,
                 compact_start,
             };
-            let range_length = range_mapping.range_length();
             ranges_mapping.push(range_mapping);
-            compact_start += range_length;
+            compact_start = compact_start
+                .checked_add(range_length)
+                .ok_or_else(invalid_compact_space)?;
         }
 
         Ok(Self { ranges_mapping })
@@ -147,12 +160,15 @@ impl CompactSpace {
             .ranges_mapping
             .binary_search_by_key(&compact, |range_mapping| range_mapping.compact_start)
             // Correctness: Overflow. The first range starts at compact space 0, the error from
-            // binary search can never be 0
-            .unwrap_or_else(|e| e - 1);
+            // binary search can never be 0, unless the data is corrupted.
+            .unwrap_or_else(|e| e.saturating_sub(1));
 
-        let range_mapping = &self.ranges_mapping[pos];
-        let diff = compact - range_mapping.compact_start;
-        range_mapping.value_range.start() + diff as u128
+        let Some(range_mapping) = self.ranges_mapping.get(pos) else {
+            // Only reachable with corrupted data.
+            return 0;
+        };
+        let diff = compact.saturating_sub(range_mapping.compact_start);
+        range_mapping.value_range.start().wrapping_add(diff as u128)
     }
 }
 
@@ -171,24 +187,13 @@ pub struct IPCodecParams {
 }
 
 impl CompactSpaceCompressor {
-    pub fn num_vals(&self) -> RowId {
-        self.params.num_vals
-    }
-
-    /// Taking the vals as Vec may cost a lot of memory. It is used to sort the vals.
-    pub fn train_from(iter: impl Iterator<Item = u128>) -> Self {
-        let mut values_sorted = BTreeSet::new();
-        // Total number of values, with their redundancy.
-        let mut total_num_values = 0u32;
-        for val in iter {
-            total_num_values += 1u32;
-            values_sorted.insert(val);
-        }
+    /// `total_num_values` is the number of values, with their redundancy.
+    pub fn train_from_sorted(values_sorted: &BTreeSet<u128>, total_num_values: u32) -> Self {
         let min_value = *values_sorted.iter().next().unwrap_or(&0);
         let max_value = *values_sorted.iter().last().unwrap_or(&0);
 
         let compact_space =
-            get_compact_space(&values_sorted, total_num_values, COST_PER_BLANK_IN_BITS);
+            get_compact_space(values_sorted, total_num_values, COST_PER_BLANK_IN_BITS);
         let amplitude_compact_space = compact_space.amplitude_compact_space();
 
         assert!(
@@ -216,6 +221,15 @@ impl CompactSpaceCompressor {
         }
     }
 
+    /// Returns the exact number of bytes required to serialize the column with this codec.
+    pub fn num_bytes(&self) -> u64 {
+        let mut counting_writer = CountingWriter::wrap(io::sink());
+        // Writing to a sink cannot fail.
+        self.params.serialize(&mut counting_writer).unwrap();
+        let footer_len = counting_writer.written_bytes() + 4;
+        footer_len + (self.params.num_vals as u64 * self.params.num_bits as u64).div_ceil(8)
+    }
+
     fn write_footer(self, writer: &mut impl Write) -> io::Result<()> {
         let writer = &mut CountingWriter::wrap(writer);
         self.params.serialize(writer)?;
@@ -283,7 +297,7 @@ impl BinarySerializable for IPCodecParams {
 
         Ok(Self {
             compact_space,
-            bit_unpacker: BitUnpacker::new(num_bits),
+            bit_unpacker: open_bit_unpacker(num_bits)?,
             min_value,
             max_value,
             num_vals,
@@ -292,63 +306,6 @@ impl BinarySerializable for IPCodecParams {
     }
 }
 
-/// Exposes the compact space compressed values as u64.
-///
-/// This allows faster access to the values, as u64 is faster to work with than u128.
-/// It also allows to handle u128 values like u64, via the `open_u64_lenient` as a uniform
-/// access interface.
-///
-/// When converting from the internal u64 to u128 `compact_to_u128` can be used.
-pub struct CompactSpaceU64Accessor(CompactSpaceDecompressor);
-impl CompactSpaceU64Accessor {
-    pub(crate) fn open(data: OwnedBytes) -> io::Result<CompactSpaceU64Accessor> {
-        let decompressor = CompactSpaceU64Accessor(CompactSpaceDecompressor::open(data)?);
-        Ok(decompressor)
-    }
-    /// Convert a compact space value to u128
-    pub fn compact_to_u128(&self, compact: u32) -> u128 {
-        self.0.compact_to_u128(compact)
-    }
-}
-
-impl ColumnValues<u64> for CompactSpaceU64Accessor {
-    #[inline]
-    fn get_val(&self, doc: u32) -> u64 {
-        let compact = self.0.get_compact(doc);
-        compact as u64
-    }
-
-    fn min_value(&self) -> u64 {
-        self.0.u128_to_compact(self.0.min_value()).unwrap() as u64
-    }
-
-    fn max_value(&self) -> u64 {
-        self.0.u128_to_compact(self.0.max_value()).unwrap() as u64
-    }
-
-    fn num_vals(&self) -> u32 {
-        self.0.params.num_vals
-    }
-
-    #[inline]
-    fn iter(&self) -> Box<dyn Iterator<Item = u64> + '_> {
-        Box::new(self.0.iter_compact().map(|el| el as u64))
-    }
-
-    #[inline]
-    fn get_row_ids_for_value_range(
-        &self,
-        value_range: RangeInclusive<u64>,
-        position_range: Range<u32>,
-        positions: &mut Vec<u32>,
-    ) {
-        let value_range = self.0.compact_to_u128(*value_range.start() as u32)
-            ..=self.0.compact_to_u128(*value_range.end() as u32);
-        self.0
-            .get_row_ids_for_value_range(value_range, position_range, positions)
-    }
-}
-
 impl ColumnValues<u128> for CompactSpaceDecompressor {
     #[inline]
     fn get_val(&self, doc: u32) -> u128 {
@@ -420,11 +377,25 @@ impl ColumnValues<u128> for CompactSpaceDecompressor {
 
 impl CompactSpaceDecompressor {
     pub fn open(data: OwnedBytes) -> io::Result<CompactSpaceDecompressor> {
-        let (data_slice, footer_len_bytes) = data.split_at(data.len() - 4);
+        let invalid_data = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        let footer_len_offset = data
+            .len()
+            .checked_sub(4)
+            .ok_or_else(|| invalid_data("Compact space column is truncated"))?;
+        let (data_slice, footer_len_bytes) = data.split_at(footer_len_offset);
         let footer_len = u32::deserialize(&mut &footer_len_bytes[..])?;
 
-        let data_footer = &data_slice[data_slice.len() - footer_len as usize..];
+        let footer_offset = data_slice
+            .len()
+            .checked_sub(footer_len as usize)
+            .ok_or_else(|| invalid_data("Compact space column footer is truncated"))?;
+        let data_footer = &data_slice[footer_offset..];
         let params = IPCodecParams::deserialize(&mut &data_footer[..])?;
+        check_bitpacked_len(
+            &data_slice[..footer_offset],
+            params.num_bits,
+            params.num_vals,
+        )?;
         let decompressor = CompactSpaceDecompressor { data, params };
 
         Ok(decompressor)
@@ -437,16 +408,16 @@ impl CompactSpaceDecompressor {
     ///
     /// and we want a mapping for 1005, there is no equivalent compact space. We instead return an
     /// error with the index of the next range.
-    fn u128_to_compact(&self, value: u128) -> Result<u32, usize> {
+    pub(crate) fn u128_to_compact(&self, value: u128) -> Result<u32, usize> {
         self.params.compact_space.u128_to_compact(value)
     }
 
-    fn compact_to_u128(&self, compact: u32) -> u128 {
+    pub(crate) fn compact_to_u128(&self, compact: u32) -> u128 {
         self.params.compact_space.compact_to_u128(compact)
     }
 
     #[inline]
-    fn iter_compact(&self) -> impl Iterator<Item = u32> + '_ {
+    pub(crate) fn iter_compact(&self) -> impl Iterator<Item = u32> + '_ {
         (0..self.params.num_vals)
             .map(move |idx| self.params.bit_unpacker.get(idx, &self.data) as u32)
     }
@@ -499,7 +470,9 @@ mod tests {
     use itertools::Itertools;
 
     use super::*;
-    use crate::column_values::u128_based::U128Header;
+    use crate::column_values::u128_based::{
+        serialize_column_values_u128_with_codecs, U128FastFieldCodecType, U128Header,
+    };
     use crate::column_values::{open_u128_mapped, serialize_column_values_u128};
 
     #[test]
@@ -576,7 +549,12 @@ mod tests {
 
     fn test_aux_vals(u128_vals: &[u128]) -> OwnedBytes {
         let mut out = Vec::new();
-        serialize_column_values_u128(&u128_vals, &mut out).unwrap();
+        serialize_column_values_u128_with_codecs(
+            &u128_vals,
+            &[U128FastFieldCodecType::CompactSpace],
+            &mut out,
+        )
+        .unwrap();
         let data = OwnedBytes::new(out);
         test_all(data.clone(), u128_vals);
         data
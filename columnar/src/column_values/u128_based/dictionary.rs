@@ -0,0 +1,208 @@
+//! The dictionary codec stores the distinct values of the column in a sorted dictionary, and
+//! bitpacks for each row the ordinal of its value in the dictionary.
+//!
+//! It is well suited for low cardinality columns, like ip addresses in access logs, where a few
+//! distinct values are repeated over many rows.
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+use std::ops::{Range, RangeInclusive};
+
+use common::{BinarySerializable, CountingWriter, OwnedBytes, VInt, VIntU128};
+use tantivy_bitpacker::{compute_num_bits, BitPacker, BitUnpacker};
+
+use crate::column_values::u64_based::{check_bitpacked_len, open_bit_unpacker};
+use crate::column_values::ColumnValues;
+use crate::RowId;
+
+/// Ordinals of the dictionary are bitpacked, but the bitpacked ordinal has to be usable as a `u32`
+/// compact value.
+const MAX_NUM_TERMS: usize = u32::MAX as usize;
+
+pub struct DictionaryCompressor {
+    terms: Vec<u128>,
+    num_vals: RowId,
+    num_bits: u8,
+}
+
+impl DictionaryCompressor {
+    /// Returns `None` if the column has too many distinct values to be dictionary encoded.
+    pub fn train_from_sorted(
+        values_sorted: &BTreeSet<u128>,
+        num_vals: RowId,
+    ) -> Option<DictionaryCompressor> {
+        if values_sorted.len() > MAX_NUM_TERMS {
+            return None;
+        }
+        let terms: Vec<u128> = values_sorted.iter().copied().collect();
+        let num_bits = compute_num_bits(terms.len().saturating_sub(1) as u64);
+        Some(DictionaryCompressor {
+            terms,
+            num_vals,
+            num_bits,
+        })
+    }
+
+    fn serialize_dictionary(&self, writer: &mut impl Write) -> io::Result<()> {
+        VInt(self.terms.len() as u64).serialize(writer)?;
+        let mut prev_term = 0u128;
+        for &term in &self.terms {
+            VIntU128(term - prev_term).serialize(writer)?;
+            prev_term = term;
+        }
+        self.num_bits.serialize(writer)?;
+        Ok(())
+    }
+
+    /// Returns the exact number of bytes required to serialize the column with this codec.
+    pub fn num_bytes(&self) -> u64 {
+        let mut counting_writer = CountingWriter::wrap(io::sink());
+        // Writing to a sink cannot fail.
+        self.serialize_dictionary(&mut counting_writer).unwrap();
+        counting_writer.written_bytes() + (self.num_vals as u64 * self.num_bits as u64).div_ceil(8)
+    }
+
+    pub fn compress_into(
+        self,
+        vals: impl Iterator<Item = u128>,
+        write: &mut impl Write,
+    ) -> io::Result<()> {
+        self.serialize_dictionary(write)?;
+        let mut bitpacker = BitPacker::default();
+        for val in vals {
+            let term_ord = self.terms.binary_search(&val).map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Could not find value in the dictionary. This is a bug.",
+                )
+            })?;
+            bitpacker.write(term_ord as u64, self.num_bits, write)?;
+        }
+        bitpacker.close(write)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DictionaryDecompressor {
+    data: OwnedBytes,
+    terms: Vec<u128>,
+    bit_unpacker: BitUnpacker,
+    num_vals: RowId,
+}
+
+impl DictionaryDecompressor {
+    pub fn open(mut data: OwnedBytes, num_vals: RowId) -> io::Result<DictionaryDecompressor> {
+        let invalid_dictionary =
+            || io::Error::new(io::ErrorKind::InvalidData, "Invalid u128 dictionary");
+        let num_terms = VInt::deserialize(&mut data)?.0;
+        if num_terms > MAX_NUM_TERMS as u64 {
+            return Err(invalid_dictionary());
+        }
+        let mut terms = Vec::new();
+        let mut term = 0u128;
+        for _ in 0..num_terms {
+            let delta = VIntU128::deserialize(&mut data)?.0;
+            term = term.checked_add(delta).ok_or_else(invalid_dictionary)?;
+            terms.push(term);
+        }
+        let num_bits = u8::deserialize(&mut data)?;
+        check_bitpacked_len(&data, num_bits, num_vals)?;
+        if num_vals > 0 && (num_terms == 0 || compute_num_bits(num_terms - 1) > num_bits) {
+            return Err(invalid_dictionary());
+        }
+        Ok(DictionaryDecompressor {
+            data,
+            terms,
+            bit_unpacker: open_bit_unpacker(num_bits)?,
+            num_vals,
+        })
+    }
+
+    #[inline]
+    pub fn get_compact(&self, idx: u32) -> u32 {
+        self.bit_unpacker.get(idx, &self.data) as u32
+    }
+
+    /// Returns the value of the dictionary for the given ordinal.
+    #[inline]
+    pub fn compact_to_u128(&self, term_ord: u32) -> u128 {
+        // Only out of bounds with corrupted data.
+        self.terms.get(term_ord as usize).copied().unwrap_or(0)
+    }
+
+    pub fn compact_min_value(&self) -> u32 {
+        0
+    }
+
+    pub fn compact_max_value(&self) -> u32 {
+        self.terms.len().saturating_sub(1) as u32
+    }
+
+    #[inline]
+    pub fn iter_compact(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.num_vals).map(move |idx| self.get_compact(idx))
+    }
+
+    pub fn get_positions_for_compact_value_range(
+        &self,
+        value_range: RangeInclusive<u32>,
+        position_range: Range<u32>,
+        positions: &mut Vec<u32>,
+    ) {
+        let position_range = position_range.start..position_range.end.min(self.num_vals);
+        self.bit_unpacker.get_ids_for_value_range(
+            *value_range.start() as u64..=*value_range.end() as u64,
+            position_range,
+            &self.data,
+            positions,
+        );
+    }
+}
+
+impl ColumnValues<u128> for DictionaryDecompressor {
+    #[inline]
+    fn get_val(&self, idx: u32) -> u128 {
+        self.compact_to_u128(self.get_compact(idx))
+    }
+
+    fn min_value(&self) -> u128 {
+        self.terms.first().copied().unwrap_or(0)
+    }
+
+    fn max_value(&self) -> u128 {
+        self.terms.last().copied().unwrap_or(0)
+    }
+
+    fn num_vals(&self) -> u32 {
+        self.num_vals
+    }
+
+    #[inline]
+    fn iter(&self) -> Box<dyn Iterator<Item = u128> + '_> {
+        Box::new(
+            self.iter_compact()
+                .map(|term_ord| self.compact_to_u128(term_ord)),
+        )
+    }
+
+    fn get_row_ids_for_value_range(
+        &self,
+        value_range: RangeInclusive<u128>,
+        position_range: Range<u32>,
+        positions: &mut Vec<u32>,
+    ) {
+        let start_ord = self
+            .terms
+            .partition_point(|term| term < value_range.start());
+        let end_ord = self.terms.partition_point(|term| term <= value_range.end());
+        if start_ord >= end_ord {
+            positions.clear();
+            return;
+        }
+        self.get_positions_for_compact_value_range(
+            start_ord as u32..=(end_ord - 1) as u32,
+            position_range,
+            positions,
+        );
+    }
+}
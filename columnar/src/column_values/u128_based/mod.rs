@@ -1,14 +1,18 @@
+use std::collections::BTreeSet;
 use std::fmt::Debug;
 use std::io;
 use std::io::Write;
+use std::ops::{Range, RangeInclusive};
 use std::sync::Arc;
 
+mod bitpacked;
 mod compact_space;
+mod dictionary;
 
+pub use bitpacked::{BitpackedCompressor, BitpackedDecompressor};
 use common::{BinarySerializable, OwnedBytes, VInt};
-pub use compact_space::{
-    CompactSpaceCompressor, CompactSpaceDecompressor, CompactSpaceU64Accessor,
-};
+pub use compact_space::{CompactSpaceCompressor, CompactSpaceDecompressor};
+pub use dictionary::{DictionaryCompressor, DictionaryDecompressor};
 
 use crate::column_values::monotonic_map_column;
 use crate::column_values::monotonic_mapping::{
@@ -40,28 +44,104 @@ impl BinarySerializable for U128Header {
     }
 }
 
-/// Serializes u128 values with the compact space codec.
+/// Serializes u128 values with the codec that gives the smallest column.
 pub fn serialize_column_values_u128<T: MonotonicallyMappableToU128>(
     iterable: &dyn Iterable<T>,
     output: &mut impl io::Write,
 ) -> io::Result<()> {
-    let compressor = CompactSpaceCompressor::train_from(
-        iterable
-            .boxed_iter()
-            .map(MonotonicallyMappableToU128::to_u128),
-    );
+    serialize_column_values_u128_with_codecs(iterable, &ALL_U128_CODEC_TYPES, output)
+}
+
+/// Serializes u128 values with the codec among `codec_types` that gives the smallest column.
+///
+/// Falls back to the compact space codec, which can encode any column, if none of the
+/// `codec_types` can encode the values.
+pub(crate) fn serialize_column_values_u128_with_codecs<T: MonotonicallyMappableToU128>(
+    iterable: &dyn Iterable<T>,
+    codec_types: &[U128FastFieldCodecType],
+    output: &mut impl io::Write,
+) -> io::Result<()> {
+    // Taking the vals as a BTreeSet may cost a lot of memory. It is shared by the estimation of
+    // all codecs.
+    let mut values_sorted = BTreeSet::new();
+    let mut num_vals = 0u32;
+    for val in iterable.boxed_iter() {
+        num_vals += 1;
+        values_sorted.insert(val.to_u128());
+    }
+    let compressor = codec_types
+        .iter()
+        .flat_map(|&codec_type| U128Compressor::train_from(codec_type, &values_sorted, num_vals))
+        // In case of a tie, the first codec wins.
+        .min_by_key(U128Compressor::num_bytes)
+        .unwrap_or_else(|| {
+            U128Compressor::CompactSpace(CompactSpaceCompressor::train_from_sorted(
+                &values_sorted,
+                num_vals,
+            ))
+        });
     let header = U128Header {
-        num_vals: compressor.num_vals(),
-        codec_type: U128FastFieldCodecType::CompactSpace,
+        num_vals,
+        codec_type: compressor.codec_type(),
     };
     header.serialize(output)?;
-    compressor.compress_into(
-        iterable
-            .boxed_iter()
-            .map(MonotonicallyMappableToU128::to_u128),
-        output,
-    )?;
-    Ok(())
+    let vals = iterable
+        .boxed_iter()
+        .map(MonotonicallyMappableToU128::to_u128);
+    match compressor {
+        U128Compressor::CompactSpace(compressor) => compressor.compress_into(vals, output),
+        U128Compressor::Dictionary(compressor) => compressor.compress_into(vals, output),
+        U128Compressor::Bitpacked(compressor) => compressor.compress_into(vals, output),
+    }
+}
+
+/// A trained compressor, for one of the u128 codecs.
+enum U128Compressor {
+    CompactSpace(CompactSpaceCompressor),
+    Dictionary(DictionaryCompressor),
+    Bitpacked(BitpackedCompressor),
+}
+
+impl U128Compressor {
+    /// Returns `None` if the codec can't encode the values.
+    fn train_from(
+        codec_type: U128FastFieldCodecType,
+        values_sorted: &BTreeSet<u128>,
+        num_vals: u32,
+    ) -> Option<U128Compressor> {
+        match codec_type {
+            U128FastFieldCodecType::CompactSpace => Some(U128Compressor::CompactSpace(
+                CompactSpaceCompressor::train_from_sorted(values_sorted, num_vals),
+            )),
+            U128FastFieldCodecType::Dictionary => {
+                DictionaryCompressor::train_from_sorted(values_sorted, num_vals)
+                    .map(U128Compressor::Dictionary)
+            }
+            U128FastFieldCodecType::Bitpacked => {
+                let min_value = values_sorted.first().copied().unwrap_or(0);
+                let max_value = values_sorted.last().copied().unwrap_or(0);
+                BitpackedCompressor::train_from(min_value, max_value, num_vals)
+                    .map(U128Compressor::Bitpacked)
+            }
+        }
+    }
+
+    fn codec_type(&self) -> U128FastFieldCodecType {
+        match self {
+            U128Compressor::CompactSpace(_) => U128FastFieldCodecType::CompactSpace,
+            U128Compressor::Dictionary(_) => U128FastFieldCodecType::Dictionary,
+            U128Compressor::Bitpacked(_) => U128FastFieldCodecType::Bitpacked,
+        }
+    }
+
+    /// Returns the number of bytes of the column, header excluded.
+    fn num_bytes(&self) -> u64 {
+        match self {
+            U128Compressor::CompactSpace(compressor) => compressor.num_bytes(),
+            U128Compressor::Dictionary(compressor) => compressor.num_bytes(),
+            U128Compressor::Bitpacked(compressor) => compressor.num_bytes(),
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
@@ -71,8 +151,21 @@ pub(crate) enum U128FastFieldCodecType {
     /// This codec takes a large number space (u128) and reduces it to a compact number space, by
     /// removing the holes.
     CompactSpace = 1,
+    /// Stores the distinct values in a dictionary and bitpacks the ordinals of the values.
+    Dictionary = 2,
+    /// Bitpacks the delta of the values to the minimum value.
+    Bitpacked = 3,
 }
 
+/// List of all available u128 codecs.
+///
+/// The compact space codec comes first, so that it is picked in case of a tie.
+pub(crate) const ALL_U128_CODEC_TYPES: [U128FastFieldCodecType; 3] = [
+    U128FastFieldCodecType::CompactSpace,
+    U128FastFieldCodecType::Dictionary,
+    U128FastFieldCodecType::Bitpacked,
+];
+
 impl BinarySerializable for U128FastFieldCodecType {
     fn serialize<W: Write + ?Sized>(&self, wrt: &mut W) -> io::Result<()> {
         self.to_code().serialize(wrt)
@@ -94,21 +187,41 @@ impl U128FastFieldCodecType {
     pub(crate) fn from_code(code: u8) -> Option<Self> {
         match code {
             1 => Some(Self::CompactSpace),
+            2 => Some(Self::Dictionary),
+            3 => Some(Self::Bitpacked),
             _ => None,
         }
     }
 }
 
+fn map_u128_column<T, C>(reader: C) -> Arc<dyn ColumnValues<T>>
+where
+    T: MonotonicallyMappableToU128 + Debug,
+    C: ColumnValues<u128> + 'static,
+{
+    let inverted: StrictlyMonotonicMappingInverter<StrictlyMonotonicMappingToInternal<T>> =
+        StrictlyMonotonicMappingToInternal::<T>::new().into();
+    Arc::new(monotonic_map_column(reader, inverted))
+}
+
 /// Returns the correct codec reader wrapped in the `Arc` for the data.
 pub fn open_u128_mapped<T: MonotonicallyMappableToU128 + Debug>(
     mut bytes: OwnedBytes,
 ) -> io::Result<Arc<dyn ColumnValues<T>>> {
     let header = U128Header::deserialize(&mut bytes)?;
-    assert_eq!(header.codec_type, U128FastFieldCodecType::CompactSpace);
-    let reader = CompactSpaceDecompressor::open(bytes)?;
-    let inverted: StrictlyMonotonicMappingInverter<StrictlyMonotonicMappingToInternal<T>> =
-        StrictlyMonotonicMappingToInternal::<T>::new().into();
-    Ok(Arc::new(monotonic_map_column(reader, inverted)))
+    match header.codec_type {
+        U128FastFieldCodecType::CompactSpace => {
+            Ok(map_u128_column(CompactSpaceDecompressor::open(bytes)?))
+        }
+        U128FastFieldCodecType::Dictionary => Ok(map_u128_column(DictionaryDecompressor::open(
+            bytes,
+            header.num_vals,
+        )?)),
+        U128FastFieldCodecType::Bitpacked => Ok(map_u128_column(BitpackedDecompressor::open(
+            bytes,
+            header.num_vals,
+        )?)),
+    }
 }
 
 /// Returns the u64 representation of the u128 data.
@@ -118,15 +231,161 @@ pub fn open_u128_mapped<T: MonotonicallyMappableToU128 + Debug>(
 /// `compact_to_u128`.
 ///
 /// # Notice
-/// In case there are new codecs added, check for usages of `CompactSpaceDecompressorU64` and
-/// also handle the new codecs.
+/// In case there are new codecs added, they also need to be handled by
+/// `CompactSpaceU64Accessor`.
 pub fn open_u128_as_compact_u64(mut bytes: OwnedBytes) -> io::Result<Arc<dyn ColumnValues<u64>>> {
     let header = U128Header::deserialize(&mut bytes)?;
-    assert_eq!(header.codec_type, U128FastFieldCodecType::CompactSpace);
-    let reader = CompactSpaceU64Accessor::open(bytes)?;
+    let reader = match header.codec_type {
+        U128FastFieldCodecType::CompactSpace => CompactSpaceU64Accessor::open(bytes)?,
+        U128FastFieldCodecType::Dictionary => CompactSpaceU64Accessor(
+            CompactU128Reader::Dictionary(DictionaryDecompressor::open(bytes, header.num_vals)?),
+        ),
+        U128FastFieldCodecType::Bitpacked => CompactSpaceU64Accessor(CompactU128Reader::Bitpacked(
+            BitpackedDecompressor::open(bytes, header.num_vals)?,
+        )),
+    };
     Ok(Arc::new(reader))
 }
 
+enum CompactU128Reader {
+    CompactSpace(CompactSpaceDecompressor),
+    Dictionary(DictionaryDecompressor),
+    Bitpacked(BitpackedDecompressor),
+}
+
+/// Exposes the compressed values as u64.
+///
+/// All u128 codecs map the values to a compact, order preserving, `u32` space: the compact space,
+/// the ordinals of the dictionary, or the deltas to the minimum value.
+///
+/// This allows faster access to the values, as u64 is faster to work with than u128.
+/// It also allows to handle u128 values like u64, via the `open_u64_lenient` as a uniform
+/// access interface.
+///
+/// When converting from the internal u64 to u128 `compact_to_u128` can be used.
+pub struct CompactSpaceU64Accessor(CompactU128Reader);
+
+impl CompactSpaceU64Accessor {
+    fn open(data: OwnedBytes) -> io::Result<CompactSpaceU64Accessor> {
+        let decompressor = CompactSpaceDecompressor::open(data)?;
+        // `min_value` and `max_value` expect them to be in the compact space.
+        if decompressor.num_vals() > 0
+            && (decompressor
+                .u128_to_compact(decompressor.min_value())
+                .is_err()
+                || decompressor
+                    .u128_to_compact(decompressor.max_value())
+                    .is_err())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Column min and max values are not in the compact space",
+            ));
+        }
+        Ok(CompactSpaceU64Accessor(CompactU128Reader::CompactSpace(
+            decompressor,
+        )))
+    }
+
+    /// Convert a compact space value to u128
+    pub fn compact_to_u128(&self, compact: u32) -> u128 {
+        match &self.0 {
+            CompactU128Reader::CompactSpace(reader) => reader.compact_to_u128(compact),
+            CompactU128Reader::Dictionary(reader) => reader.compact_to_u128(compact),
+            CompactU128Reader::Bitpacked(reader) => reader.compact_to_u128(compact),
+        }
+    }
+}
+
+impl ColumnValues<u64> for CompactSpaceU64Accessor {
+    #[inline]
+    fn get_val(&self, doc: u32) -> u64 {
+        let compact = match &self.0 {
+            CompactU128Reader::CompactSpace(reader) => reader.get_compact(doc),
+            CompactU128Reader::Dictionary(reader) => reader.get_compact(doc),
+            CompactU128Reader::Bitpacked(reader) => reader.get_compact(doc),
+        };
+        compact as u64
+    }
+
+    fn min_value(&self) -> u64 {
+        let compact = match &self.0 {
+            CompactU128Reader::CompactSpace(reader) => {
+                reader.u128_to_compact(reader.min_value()).unwrap()
+            }
+            CompactU128Reader::Dictionary(reader) => reader.compact_min_value(),
+            CompactU128Reader::Bitpacked(reader) => reader.compact_min_value(),
+        };
+        compact as u64
+    }
+
+    fn max_value(&self) -> u64 {
+        let compact = match &self.0 {
+            CompactU128Reader::CompactSpace(reader) => {
+                reader.u128_to_compact(reader.max_value()).unwrap()
+            }
+            CompactU128Reader::Dictionary(reader) => reader.compact_max_value(),
+            CompactU128Reader::Bitpacked(reader) => reader.compact_max_value(),
+        };
+        compact as u64
+    }
+
+    fn num_vals(&self) -> u32 {
+        match &self.0 {
+            CompactU128Reader::CompactSpace(reader) => reader.num_vals(),
+            CompactU128Reader::Dictionary(reader) => reader.num_vals(),
+            CompactU128Reader::Bitpacked(reader) => reader.num_vals(),
+        }
+    }
+
+    #[inline]
+    fn iter(&self) -> Box<dyn Iterator<Item = u64> + '_> {
+        match &self.0 {
+            CompactU128Reader::CompactSpace(reader) => {
+                Box::new(reader.iter_compact().map(|el| el as u64))
+            }
+            CompactU128Reader::Dictionary(reader) => {
+                Box::new(reader.iter_compact().map(|el| el as u64))
+            }
+            CompactU128Reader::Bitpacked(reader) => {
+                Box::new(reader.iter_compact().map(|el| el as u64))
+            }
+        }
+    }
+
+    #[inline]
+    fn get_row_ids_for_value_range(
+        &self,
+        value_range: RangeInclusive<u64>,
+        position_range: Range<u32>,
+        positions: &mut Vec<u32>,
+    ) {
+        if *value_range.start() > u32::MAX as u64 {
+            positions.clear();
+            return;
+        }
+        let compact_range =
+            *value_range.start() as u32..=(*value_range.end()).min(u32::MAX as u64) as u32;
+        match &self.0 {
+            CompactU128Reader::CompactSpace(reader) => {
+                let value_range = reader.compact_to_u128(*compact_range.start())
+                    ..=reader.compact_to_u128(*compact_range.end());
+                reader.get_row_ids_for_value_range(value_range, position_range, positions)
+            }
+            CompactU128Reader::Dictionary(reader) => reader.get_positions_for_compact_value_range(
+                compact_range,
+                position_range,
+                positions,
+            ),
+            CompactU128Reader::Bitpacked(reader) => reader.get_positions_for_compact_value_range(
+                compact_range,
+                position_range,
+                positions,
+            ),
+        }
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
@@ -194,4 +453,102 @@ pub(crate) mod tests {
         // Values are stored over 3 bits.
         assert_eq!(buffer.len(), 6 + (3 * 80 / 8));
     }
+
+    fn serialized_codec_type(vals: &[u128]) -> U128FastFieldCodecType {
+        let mut out = Vec::new();
+        serialize_column_values_u128(&vals, &mut out).unwrap();
+        U128Header::deserialize(&mut &out[..]).unwrap().codec_type
+    }
+
+    #[test]
+    fn test_u128_codecs() {
+        let vals: Vec<u128> = (0..1_000u128)
+            .map(|i| (1u128 << 100) + (i * 7) % 300)
+            .collect();
+        let range = (1u128 << 100) + 100..=(1u128 << 100) + 150;
+        let expected_positions: Vec<u32> = (0..vals.len() as u32)
+            .filter(|&pos| range.contains(&vals[pos as usize]))
+            .collect();
+        for codec_type in ALL_U128_CODEC_TYPES {
+            let mut out = Vec::new();
+            serialize_column_values_u128_with_codecs(&&vals[..], &[codec_type], &mut out).unwrap();
+            let header = U128Header::deserialize(&mut &out[..]).unwrap();
+            assert_eq!(header.codec_type, codec_type);
+            assert_eq!(header.num_vals, 1_000);
+
+            let column = open_u128_mapped::<u128>(OwnedBytes::new(out.clone())).unwrap();
+            assert_eq!(column.iter().collect::<Vec<u128>>(), vals);
+            assert_eq!(column.min_value(), 1u128 << 100);
+            assert_eq!(column.max_value(), (1u128 << 100) + 299);
+            let mut positions = Vec::new();
+            column.get_row_ids_for_value_range(range.clone(), 0..1_000, &mut positions);
+            assert_eq!(positions, expected_positions);
+            positions.clear();
+            column.get_row_ids_for_value_range(0..=1_000, 0..1_000, &mut positions);
+            assert!(positions.is_empty());
+
+            let compact_column = open_u128_as_compact_u64(OwnedBytes::new(out)).unwrap();
+            let accessor = compact_column
+                .clone()
+                .downcast_arc::<CompactSpaceU64Accessor>()
+                .ok()
+                .unwrap();
+            let compact_vals: Vec<u64> = compact_column.iter().collect();
+            for (&compact, &val) in compact_vals.iter().zip(&vals) {
+                assert_eq!(accessor.compact_to_u128(compact as u32), val);
+            }
+            assert_eq!(
+                accessor.compact_to_u128(compact_column.min_value() as u32),
+                1u128 << 100
+            );
+            assert_eq!(
+                accessor.compact_to_u128(compact_column.max_value() as u32),
+                (1u128 << 100) + 299
+            );
+            let compact_range =
+                compact_vals[3].min(compact_vals[100])..=compact_vals[3].max(compact_vals[100]);
+            let expected_compact_positions: Vec<u32> = (0..vals.len() as u32)
+                .filter(|&pos| compact_range.contains(&compact_vals[pos as usize]))
+                .collect();
+            positions.clear();
+            compact_column.get_row_ids_for_value_range(compact_range, 0..1_000, &mut positions);
+            assert_eq!(positions, expected_compact_positions);
+        }
+    }
+
+    #[test]
+    fn test_u128_codec_selection() {
+        // A dense range of 256 values: the compact space needs an extra bit, as it reserves a
+        // value for null.
+        let dense: Vec<u128> = (0..10_000u128).map(|i| (1u128 << 100) + i % 256).collect();
+        assert_eq!(
+            serialized_codec_type(&dense),
+            U128FastFieldCodecType::Bitpacked
+        );
+        // A few values spread over the whole u128 space.
+        let sparse: Vec<u128> = (0..10_000u128)
+            .map(|i| (i % 100) * (u128::MAX / 100))
+            .collect();
+        assert_eq!(
+            serialized_codec_type(&sparse),
+            U128FastFieldCodecType::Dictionary
+        );
+        // Dense clusters of values, far from each other.
+        let clustered: Vec<u128> = (0..10_000u128)
+            .map(|i| (i / 1_000) * 1_000_000 + i % 1_000)
+            .collect();
+        assert_eq!(
+            serialized_codec_type(&clustered),
+            U128FastFieldCodecType::CompactSpace
+        );
+        // The dictionary of an empty column is empty.
+        assert_eq!(
+            serialized_codec_type(&[]),
+            U128FastFieldCodecType::Dictionary
+        );
+        let mut out = Vec::new();
+        serialize_column_values_u128::<u128>(&&[][..], &mut out).unwrap();
+        let column = open_u128_mapped::<u128>(OwnedBytes::new(out)).unwrap();
+        assert_eq!(column.num_vals(), 0);
+    }
 }
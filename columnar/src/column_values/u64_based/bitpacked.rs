@@ -6,7 +6,9 @@ use common::{BinarySerializable, OwnedBytes};
 use fastdivide::DividerU64;
 use tantivy_bitpacker::{compute_num_bits, BitPacker, BitUnpacker};
 
-use crate::column_values::u64_based::{ColumnCodec, ColumnCodecEstimator, ColumnStats};
+use crate::column_values::u64_based::{
+    check_bitpacked_len, ColumnCodec, ColumnCodecEstimator, ColumnStats,
+};
 use crate::{ColumnValues, RowId};
 
 /// Depending on the field type, a different
@@ -61,7 +63,13 @@ fn transform_range_before_linear_transformation(
 impl ColumnValues for BitpackedReader {
     #[inline(always)]
     fn get_val(&self, doc: u32) -> u64 {
-        self.stats.min_value + self.stats.gcd.get() * self.bit_unpacker.get(doc, &self.data)
+        // Wrapping operations, so that corrupted data does not make us panic.
+        self.stats.min_value.wrapping_add(
+            self.stats
+                .gcd
+                .get()
+                .wrapping_mul(self.bit_unpacker.get(doc, &self.data)),
+        )
     }
     #[inline]
     fn min_value(&self) -> u64 {
@@ -140,6 +148,7 @@ impl ColumnCodec for BitpackedCodec {
     fn load(mut data: OwnedBytes) -> io::Result<Self::ColumnValues> {
         let stats = ColumnStats::deserialize(&mut data)?;
         let num_bits = num_bits(&stats);
+        check_bitpacked_len(&data, num_bits, stats.num_rows)?;
         let bit_unpacker = BitUnpacker::new(num_bits);
         Ok(BitpackedReader {
             data,
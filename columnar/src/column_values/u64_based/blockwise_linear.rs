@@ -7,8 +7,11 @@ use fastdivide::DividerU64;
 use tantivy_bitpacker::{compute_num_bits, BitPacker, BitUnpacker};
 
 use crate::column_values::u64_based::line::Line;
-use crate::column_values::u64_based::{ColumnCodec, ColumnCodecEstimator, ColumnStats};
+use crate::column_values::u64_based::{
+    check_bitpacked_len, open_bit_unpacker, ColumnCodec, ColumnCodecEstimator, ColumnStats,
+};
 use crate::column_values::{ColumnValues, VecColumn};
+use crate::utils::try_rsplit;
 use crate::MonotonicallyMappableToU64;
 
 const BLOCK_SIZE: u32 = 512u32;
@@ -32,7 +35,7 @@ impl BinarySerializable for Block {
         let bit_width = u8::deserialize(reader)?;
         Ok(Block {
             line,
-            bit_unpacker: BitUnpacker::new(bit_width),
+            bit_unpacker: open_bit_unpacker(bit_width)?,
             data_start_offset: 0,
         })
     }
@@ -174,16 +177,29 @@ impl ColumnCodec<u64> for BlockwiseLinearCodec {
 
     fn load(mut bytes: OwnedBytes) -> io::Result<Self::ColumnValues> {
         let stats = ColumnStats::deserialize(&mut bytes)?;
-        let footer_len: u32 = (&bytes[bytes.len() - 4..]).deserialize()?;
-        let footer_offset = bytes.len() - 4 - footer_len as usize;
-        let (data, mut footer) = bytes.split(footer_offset);
+        let (bytes, footer_len_bytes) = try_rsplit(bytes, 4)?;
+        let footer_len: u32 = footer_len_bytes.as_slice().deserialize()?;
+        let (data, mut footer) = try_rsplit(bytes, footer_len as usize)?;
         let num_blocks = compute_num_blocks(stats.num_rows);
+        // Each block takes at least 3 bytes in the footer.
+        if num_blocks as usize > footer.len() / 3 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Blockwise linear footer is truncated.",
+            ));
+        }
         let mut blocks: Vec<Block> = iter::repeat_with(|| Block::deserialize(&mut footer))
             .take(num_blocks as usize)
             .collect::<io::Result<_>>()?;
         let mut start_offset = 0;
-        for block in &mut blocks {
+        for (block_id, block) in blocks.iter_mut().enumerate() {
             block.data_start_offset = start_offset;
+            let num_vals_in_block = (stats.num_rows - block_id as u32 * BLOCK_SIZE).min(BLOCK_SIZE);
+            check_bitpacked_len(
+                data.get(start_offset..).unwrap_or(&[]),
+                block.bit_unpacker.bit_width(),
+                num_vals_in_block,
+            )?;
             start_offset += (block.bit_unpacker.bit_width() as usize) * BLOCK_SIZE as usize / 8;
         }
         Ok(BlockwiseLinearReader {
@@ -206,9 +222,13 @@ impl ColumnValues for BlockwiseLinearReader {
     fn get_val(&self, idx: u32) -> u64 {
         let block_id = (idx / BLOCK_SIZE) as usize;
         let idx_within_block = idx % BLOCK_SIZE;
-        let block = &self.blocks[block_id];
+        // Out of bounds reads can only happen with corrupted data: we return 0 rather than
+        // panicking, like the bit unpacker does.
+        let Some(block) = self.blocks.get(block_id) else {
+            return 0;
+        };
         let interpoled_val: u64 = block.line.eval(idx_within_block);
-        let block_bytes = &self.data[block.data_start_offset..];
+        let block_bytes = self.data.get(block.data_start_offset..).unwrap_or(&[]);
         let bitpacked_diff = block.bit_unpacker.get(idx_within_block, block_bytes);
         // TODO optimize me! the line parameters could be tweaked to include the multiplication and
         // remove the dependency.
@@ -0,0 +1,516 @@
+use std::io::Write;
+use std::ops::{Range, RangeInclusive};
+use std::sync::Arc;
+use std::{io, iter};
+
+use common::{BinarySerializable, CountingWriter, DeserializeFrom, OwnedBytes, VInt};
+use fastdivide::DividerU64;
+use tantivy_bitpacker::{compute_num_bits, BitPacker, BitUnpacker};
+
+use crate::column_values::u64_based::{
+    check_bitpacked_len, open_bit_unpacker, ColumnCodec, ColumnCodecEstimator, ColumnStats,
+};
+use crate::column_values::ColumnValues;
+use crate::utils::try_rsplit;
+use crate::RowId;
+
+// Blocks are small, as reading a value requires decoding the values preceding it in its block.
+const BLOCK_SIZE: u32 = 128u32;
+
+fn zigzag_encode(val: u64) -> u64 {
+    let val = val as i64;
+    ((val << 1) ^ (val >> 63)) as u64
+}
+
+fn zigzag_decode(val: u64) -> u64 {
+    (val >> 1) ^ (val & 1).wrapping_neg()
+}
+
+fn vint_num_bytes(val: u64) -> u64 {
+    (64 - val.leading_zeros() as u64).div_ceil(7).max(1)
+}
+
+fn compute_num_blocks(num_vals: u32) -> u32 {
+    num_vals.div_ceil(BLOCK_SIZE)
+}
+
+/// The values of a block are rebuilt from its first value and its first delta, and from the
+/// zigzag encoded deltas of the deltas, which are bitpacked.
+///
+/// The first two bitpacked values of a block are always 0.
+#[derive(Debug, Default)]
+struct Block {
+    first_value: u64,
+    first_delta: u64,
+    bit_unpacker: BitUnpacker,
+    data_start_offset: usize,
+}
+
+impl BinarySerializable for Block {
+    fn serialize<W: Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        VInt(self.first_value).serialize(writer)?;
+        VInt(zigzag_encode(self.first_delta)).serialize(writer)?;
+        self.bit_unpacker.bit_width().serialize(writer)?;
+        Ok(())
+    }
+
+    fn deserialize<R: io::Read>(reader: &mut R) -> io::Result<Self> {
+        let first_value = VInt::deserialize_u64(reader)?;
+        let first_delta = zigzag_decode(VInt::deserialize_u64(reader)?);
+        let bit_width = u8::deserialize(reader)?;
+        Ok(Block {
+            first_value,
+            first_delta,
+            bit_unpacker: open_bit_unpacker(bit_width)?,
+            data_start_offset: 0,
+        })
+    }
+}
+
+/// Summary of a block of raw values, from which its size is estimated once the stats of the
+/// column are known.
+struct BlockEstimate {
+    first_value: u64,
+    first_delta: u64,
+    max_delta_of_delta: u64,
+    num_vals: u64,
+}
+
+#[derive(Default)]
+pub struct DeltaOfDeltaEstimator {
+    block: Vec<u64>,
+    block_estimates: Vec<BlockEstimate>,
+}
+
+impl DeltaOfDeltaEstimator {
+    fn flush_block_estimate(&mut self) {
+        let Some(&first_value) = self.block.first() else {
+            return;
+        };
+        let first_delta = match self.block.get(1) {
+            Some(second_value) => zigzag_encode(second_value.wrapping_sub(first_value)),
+            None => 0,
+        };
+        let max_delta_of_delta = self
+            .block
+            .windows(3)
+            .map(|vals| {
+                let delta = vals[2].wrapping_sub(vals[1]);
+                let previous_delta = vals[1].wrapping_sub(vals[0]);
+                zigzag_encode(delta.wrapping_sub(previous_delta))
+            })
+            .max()
+            .unwrap_or(0);
+        self.block_estimates.push(BlockEstimate {
+            first_value,
+            first_delta,
+            max_delta_of_delta,
+            num_vals: self.block.len() as u64,
+        });
+        self.block.clear();
+    }
+}
+
+impl ColumnCodecEstimator for DeltaOfDeltaEstimator {
+    fn collect(&mut self, value: u64) {
+        self.block.push(value);
+        if self.block.len() == BLOCK_SIZE as usize {
+            self.flush_block_estimate();
+        }
+    }
+
+    fn finalize(&mut self) {
+        self.flush_block_estimate();
+    }
+
+    fn estimate(&self, stats: &ColumnStats) -> Option<u64> {
+        let gcd = stats.gcd.get();
+        // Dividing the values by the gcd divides their deltas, and zigzag encoded deltas, by the
+        // gcd as well.
+        let divide_zigzag = |val: u64| val.saturating_add(1) / gcd;
+        let mut meta_num_bytes = 0u64;
+        let mut values_num_bits = 0u64;
+        for block_estimate in &self.block_estimates {
+            meta_num_bytes += vint_num_bytes((block_estimate.first_value - stats.min_value) / gcd)
+                + vint_num_bytes(divide_zigzag(block_estimate.first_delta))
+                + 1;
+            let bit_width = compute_num_bits(divide_zigzag(block_estimate.max_delta_of_delta));
+            values_num_bits += bit_width as u64 * block_estimate.num_vals;
+        }
+        Some(4 + stats.num_bytes() + meta_num_bytes + values_num_bits.div_ceil(8))
+    }
+
+    fn serialize(
+        &self,
+        stats: &ColumnStats,
+        vals: &mut dyn Iterator<Item = u64>,
+        wrt: &mut dyn Write,
+    ) -> io::Result<()> {
+        stats.serialize(wrt)?;
+        let num_blocks = compute_num_blocks(stats.num_rows) as usize;
+        let mut blocks = Vec::with_capacity(num_blocks);
+        let mut buffer = Vec::with_capacity(BLOCK_SIZE as usize);
+        let mut bit_packer = BitPacker::new();
+        let gcd_divider = DividerU64::divide_by(stats.gcd.get());
+
+        for _ in 0..num_blocks {
+            buffer.clear();
+            buffer.extend(
+                (&mut *vals)
+                    .take(BLOCK_SIZE as usize)
+                    .map(|val| gcd_divider.divide(val - stats.min_value)),
+            );
+            assert!(!buffer.is_empty());
+            let first_value = buffer[0];
+            let first_delta = buffer.get(1).map_or(0, |val| val.wrapping_sub(first_value));
+            // Computed in place from the end, so that the previous values are still available.
+            for i in (0..buffer.len()).rev() {
+                buffer[i] = if i < 2 {
+                    0
+                } else {
+                    let delta = buffer[i].wrapping_sub(buffer[i - 1]);
+                    let previous_delta = buffer[i - 1].wrapping_sub(buffer[i - 2]);
+                    zigzag_encode(delta.wrapping_sub(previous_delta))
+                };
+            }
+            let bit_width = buffer.iter().copied().map(compute_num_bits).max().unwrap();
+            for &buffer_val in &buffer {
+                bit_packer.write(buffer_val, bit_width, wrt)?;
+            }
+            blocks.push(Block {
+                first_value,
+                first_delta,
+                bit_unpacker: BitUnpacker::new(bit_width),
+                data_start_offset: 0,
+            });
+        }
+        bit_packer.close(wrt)?;
+
+        let mut counting_wrt = CountingWriter::wrap(wrt);
+        for block in &blocks {
+            block.serialize(&mut counting_wrt)?;
+        }
+        let footer_len = counting_wrt.written_bytes();
+        (footer_len as u32).serialize(&mut counting_wrt)?;
+        Ok(())
+    }
+}
+
+/// Codec encoding the deltas between the deltas of consecutive values.
+///
+/// It targets slowly changing columns, and in particular the timestamps of indexes sorted by
+/// time: values sampled at a regular interval only need a couple of bits per value, even if
+/// the interval drifts over time.
+///
+/// Reading a single value decodes the values preceding it in its block of 128 values, but
+/// reading values in increasing order decodes each block only once.
+pub struct DeltaOfDeltaCodec;
+
+impl ColumnCodec<u64> for DeltaOfDeltaCodec {
+    type ColumnValues = DeltaOfDeltaReader;
+
+    type Estimator = DeltaOfDeltaEstimator;
+
+    fn load(mut bytes: OwnedBytes) -> io::Result<Self::ColumnValues> {
+        let stats = ColumnStats::deserialize(&mut bytes)?;
+        let (bytes, footer_len_bytes) = try_rsplit(bytes, 4)?;
+        let footer_len: u32 = footer_len_bytes.as_slice().deserialize()?;
+        let (data, mut footer) = try_rsplit(bytes, footer_len as usize)?;
+        let num_blocks = compute_num_blocks(stats.num_rows);
+        // Each block takes at least 3 bytes in the footer.
+        if num_blocks as usize > footer.len() / 3 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Delta of delta footer is truncated.",
+            ));
+        }
+        let mut blocks: Vec<Block> = iter::repeat_with(|| Block::deserialize(&mut footer))
+            .take(num_blocks as usize)
+            .collect::<io::Result<_>>()?;
+        let mut start_offset = 0;
+        for (block_id, block) in blocks.iter_mut().enumerate() {
+            block.data_start_offset = start_offset;
+            let num_vals_in_block = (stats.num_rows - block_id as u32 * BLOCK_SIZE).min(BLOCK_SIZE);
+            check_bitpacked_len(
+                data.get(start_offset..).unwrap_or(&[]),
+                block.bit_unpacker.bit_width(),
+                num_vals_in_block,
+            )?;
+            start_offset += (block.bit_unpacker.bit_width() as usize) * BLOCK_SIZE as usize / 8;
+        }
+        Ok(DeltaOfDeltaReader {
+            blocks: blocks.into_boxed_slice().into(),
+            data,
+            stats,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct DeltaOfDeltaReader {
+    blocks: Arc<[Block]>,
+    data: OwnedBytes,
+    stats: ColumnStats,
+}
+
+/// Position of a sequential decoding of the values.
+///
+/// Accessing values in increasing order only decodes each block once.
+struct Cursor<'a> {
+    reader: &'a DeltaOfDeltaReader,
+    block: Option<&'a Block>,
+    idx: u32,
+    value: u64,
+    delta: u64,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(reader: &'a DeltaOfDeltaReader) -> Self {
+        let mut cursor = Cursor {
+            reader,
+            block: None,
+            idx: 0,
+            value: 0,
+            delta: 0,
+        };
+        cursor.reset(0);
+        cursor
+    }
+
+    fn reset(&mut self, block_id: u32) {
+        self.idx = block_id * BLOCK_SIZE;
+        // Out of bounds reads can only happen with corrupted data: we return 0 rather than
+        // panicking, like the bit unpacker does.
+        self.block = self.reader.blocks.get(block_id as usize);
+        (self.value, self.delta) = self
+            .block
+            .map_or((0, 0), |block| (block.first_value, block.first_delta));
+    }
+
+    fn advance(&mut self) {
+        let idx = self.idx + 1;
+        let idx_within_block = idx % BLOCK_SIZE;
+        if idx_within_block == 0 {
+            self.reset(idx / BLOCK_SIZE);
+            return;
+        }
+        self.idx = idx;
+        let Some(block) = self.block else {
+            return;
+        };
+        if idx_within_block >= 2 {
+            let block_bytes = self
+                .reader
+                .data
+                .get(block.data_start_offset..)
+                .unwrap_or(&[]);
+            let delta_of_delta = block.bit_unpacker.get(idx_within_block, block_bytes);
+            self.delta = self.delta.wrapping_add(zigzag_decode(delta_of_delta));
+        }
+        self.value = self.value.wrapping_add(self.delta);
+    }
+
+    fn seek(&mut self, idx: u32) -> u64 {
+        if idx < self.idx || idx / BLOCK_SIZE != self.idx / BLOCK_SIZE {
+            self.reset(idx / BLOCK_SIZE);
+        }
+        while self.idx < idx {
+            self.advance();
+        }
+        self.val()
+    }
+
+    fn val(&self) -> u64 {
+        self.reader
+            .stats
+            .min_value
+            .wrapping_add(self.reader.stats.gcd.get().wrapping_mul(self.value))
+    }
+}
+
+impl ColumnValues for DeltaOfDeltaReader {
+    fn get_val(&self, idx: u32) -> u64 {
+        Cursor::new(self).seek(idx)
+    }
+
+    fn get_vals(&self, indexes: &[u32], output: &mut [u64]) {
+        assert!(indexes.len() == output.len());
+        let mut cursor = Cursor::new(self);
+        for (out, &idx) in output.iter_mut().zip(indexes) {
+            *out = cursor.seek(idx);
+        }
+    }
+
+    fn get_vals_opt(&self, indexes: &[u32], output: &mut [Option<u64>]) {
+        assert!(indexes.len() == output.len());
+        let mut cursor = Cursor::new(self);
+        for (out, &idx) in output.iter_mut().zip(indexes) {
+            *out = Some(cursor.seek(idx));
+        }
+    }
+
+    fn get_range(&self, start: u64, output: &mut [u64]) {
+        let mut cursor = Cursor::new(self);
+        for (out, idx) in output.iter_mut().zip(start as u32..) {
+            *out = cursor.seek(idx);
+        }
+    }
+
+    fn get_row_ids_for_value_range(
+        &self,
+        value_range: RangeInclusive<u64>,
+        row_id_range: Range<RowId>,
+        row_id_hits: &mut Vec<RowId>,
+    ) {
+        let row_id_range = row_id_range.start..row_id_range.end.min(self.num_vals());
+        let mut cursor = Cursor::new(self);
+        for idx in row_id_range {
+            if value_range.contains(&cursor.seek(idx)) {
+                row_id_hits.push(idx);
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn min_value(&self) -> u64 {
+        self.stats.min_value
+    }
+
+    #[inline(always)]
+    fn max_value(&self) -> u64 {
+        self.stats.max_value
+    }
+
+    #[inline(always)]
+    fn num_vals(&self) -> u32 {
+        self.stats.num_rows
+    }
+
+    fn iter<'a>(&'a self) -> Box<dyn Iterator<Item = u64> + 'a> {
+        let mut cursor = Cursor::new(self);
+        Box::new((0..self.num_vals()).map(move |idx| cursor.seek(idx)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::column_values::u64_based::tests::create_and_validate;
+    use crate::column_values::u64_based::{
+        serialize_and_load_u64_based_column_values, serialize_u64_based_column_values, CodecType,
+    };
+
+    #[test]
+    fn test_zigzag() {
+        for val in [0i64, 1, -1, 2, -2, i64::MAX, i64::MIN] {
+            assert_eq!(zigzag_decode(zigzag_encode(val as u64)), val as u64);
+        }
+        assert_eq!(zigzag_encode(-1i64 as u64), 1);
+        assert_eq!(zigzag_encode(1), 2);
+    }
+
+    #[test]
+    fn test_with_codec_data_sets() {
+        let data_sets = crate::column_values::u64_based::tests::get_codec_test_datasets();
+        for (mut data, name) in data_sets {
+            create_and_validate::<DeltaOfDeltaCodec>(&data, name);
+            data.reverse();
+            create_and_validate::<DeltaOfDeltaCodec>(&data, name);
+        }
+    }
+
+    #[test]
+    fn test_delta_of_delta_timestamps() {
+        // Timestamps in nanoseconds of events logged about every second, at a pace drifting by
+        // a millisecond from one event to the next.
+        let mut timestamp = 1_700_000_000_000_000_000u64;
+        let data: Vec<u64> = (0..10_000u64)
+            .map(|i| {
+                timestamp += 1_000_000_000 + (i % 1_000) * 1_000_000;
+                timestamp
+            })
+            .collect();
+        create_and_validate::<DeltaOfDeltaCodec>(&data, "timestamps").unwrap();
+        let num_bytes = |codec_type: CodecType| {
+            let mut buffer = Vec::new();
+            serialize_u64_based_column_values(&&data[..], &[codec_type], &mut buffer).unwrap();
+            buffer.len()
+        };
+        assert!(num_bytes(CodecType::DeltaOfDelta) * 4 < num_bytes(CodecType::BlockwiseLinear));
+
+        let reader = serialize_and_load_u64_based_column_values::<u64>(
+            &&data[..],
+            &[CodecType::DeltaOfDelta],
+        );
+        assert!(reader.iter().eq(data.iter().copied()));
+        let mut output = vec![0u64; 300];
+        reader.get_range(100, &mut output);
+        assert_eq!(&output[..], &data[100..400]);
+        let mut row_ids = Vec::new();
+        reader.get_row_ids_for_value_range(data[200]..=data[300], 150..1_000, &mut row_ids);
+        assert_eq!(row_ids, (200..=300).collect::<Vec<u32>>());
+        // Unsorted accesses.
+        let indexes = [9_999, 3, 130, 129, 5_000];
+        let mut output = [0u64; 5];
+        reader.get_vals(&indexes, &mut output);
+        for (idx, val) in indexes.iter().zip(output) {
+            assert_eq!(data[*idx as usize], val);
+        }
+    }
+
+    #[test]
+    fn test_delta_of_delta_requires_large_size_win() {
+        let serialize = |data: &[u64], codec_types: &[CodecType]| {
+            let mut buffer = Vec::new();
+            serialize_u64_based_column_values(&data, codec_types, &mut buffer).unwrap();
+            buffer
+        };
+        let codec_types = [CodecType::BlockwiseLinear, CodecType::DeltaOfDelta];
+        // A random walk with small steps: the deltas of deltas are small, but not that much
+        // smaller than the deviations from a line.
+        let mut state = 42u64;
+        let mut value = 0u64;
+        let random_walk: Vec<u64> = (0..10_000)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                value += state >> 60;
+                value
+            })
+            .collect();
+        let delta_of_delta_num_bytes = serialize(&random_walk, &[CodecType::DeltaOfDelta]).len();
+        let linear_num_bytes = serialize(&random_walk, &[CodecType::BlockwiseLinear]).len();
+        assert!(delta_of_delta_num_bytes < linear_num_bytes);
+        assert!(delta_of_delta_num_bytes * 4 > linear_num_bytes);
+        assert_eq!(
+            serialize(&random_walk, &codec_types)[0],
+            CodecType::BlockwiseLinear.to_code()
+        );
+
+        // Timestamps at a drifting pace.
+        let mut timestamp = 1_700_000_000_000_000_000u64;
+        let timestamps: Vec<u64> = (0..10_000u64)
+            .map(|i| {
+                timestamp += 1_000_000_000 + (i % 1_000) * 1_000_000;
+                timestamp
+            })
+            .collect();
+        assert_eq!(
+            serialize(&timestamps, &codec_types)[0],
+            CodecType::DeltaOfDelta.to_code()
+        );
+    }
+
+    #[test]
+    fn test_delta_of_delta_fast_field_rand() {
+        for _ in 0..500 {
+            let mut data = (0..1 + rand::random::<u16>() as usize % 1_000)
+                .map(|_| rand::random::<u64>())
+                .collect::<Vec<_>>();
+            create_and_validate::<DeltaOfDeltaCodec>(&data, "rand");
+            data.sort_unstable();
+            create_and_validate::<DeltaOfDeltaCodec>(&data, "rand sorted");
+        }
+    }
+}
@@ -5,7 +5,9 @@ use tantivy_bitpacker::{compute_num_bits, BitPacker, BitUnpacker};
 
 use super::line::Line;
 use super::ColumnValues;
-use crate::column_values::u64_based::{ColumnCodec, ColumnCodecEstimator, ColumnStats};
+use crate::column_values::u64_based::{
+    check_bitpacked_len, open_bit_unpacker, ColumnCodec, ColumnCodecEstimator, ColumnStats,
+};
 use crate::column_values::VecColumn;
 use crate::RowId;
 
@@ -67,7 +69,7 @@ impl BinarySerializable for LinearParams {
         let bit_width = u8::deserialize(reader)?;
         Ok(Self {
             line,
-            bit_unpacker: BitUnpacker::new(bit_width),
+            bit_unpacker: open_bit_unpacker(bit_width)?,
         })
     }
 }
@@ -193,6 +195,11 @@ impl ColumnCodec for LinearCodec {
     fn load(mut data: OwnedBytes) -> io::Result<Self::ColumnValues> {
         let stats = ColumnStats::deserialize(&mut data)?;
         let linear_params = LinearParams::deserialize(&mut data)?;
+        check_bitpacked_len(
+            &data,
+            linear_params.bit_unpacker.bit_width(),
+            stats.num_rows,
+        )?;
         Ok(LinearReader {
             stats,
             linear_params,
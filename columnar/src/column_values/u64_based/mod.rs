@@ -1,5 +1,6 @@
 mod bitpacked;
 mod blockwise_linear;
+mod delta_of_delta;
 mod line;
 mod linear;
 mod stats_collector;
@@ -9,18 +10,48 @@ use std::io::Write;
 use std::sync::Arc;
 
 use common::{BinarySerializable, OwnedBytes};
+use tantivy_bitpacker::BitUnpacker;
 
 use crate::column_values::monotonic_mapping::{
     StrictlyMonotonicMappingInverter, StrictlyMonotonicMappingToInternal,
 };
 pub use crate::column_values::u64_based::bitpacked::BitpackedCodec;
 pub use crate::column_values::u64_based::blockwise_linear::BlockwiseLinearCodec;
+pub use crate::column_values::u64_based::delta_of_delta::DeltaOfDeltaCodec;
 pub use crate::column_values::u64_based::linear::LinearCodec;
 pub use crate::column_values::u64_based::stats_collector::StatsCollector;
 use crate::column_values::{monotonic_map_column, ColumnStats};
 use crate::iterable::Iterable;
 use crate::{ColumnValues, MonotonicallyMappableToU64};
 
+/// Creates a `BitUnpacker` for a bit width read from a serialized column.
+///
+/// Returns an error rather than panicking if the bit width is not supported.
+pub(crate) fn open_bit_unpacker(num_bits: u8) -> io::Result<BitUnpacker> {
+    if num_bits > 56 && num_bits != 64 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unsupported bit width {num_bits}."),
+        ));
+    }
+    Ok(BitUnpacker::new(num_bits))
+}
+
+/// Checks that `data` is large enough to hold `num_vals` values bitpacked over `num_bits` bits.
+pub(crate) fn check_bitpacked_len(data: &[u8], num_bits: u8, num_vals: u32) -> io::Result<()> {
+    let num_bytes = (num_bits as u64 * num_vals as u64).div_ceil(8);
+    if num_bytes > data.len() as u64 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Bitpacked column values are truncated: expected {num_bytes} bytes, got {}.",
+                data.len()
+            ),
+        ));
+    }
+    Ok(())
+}
+
 /// A `ColumnCodecEstimator` is in charge of gathering all
 /// data required to serialize a column.
 ///
@@ -86,13 +117,18 @@ pub enum CodecType {
     Linear = 1u8,
     /// Same as [`CodecType::Linear`], but encodes in blocks of 512 elements.
     BlockwiseLinear = 2u8,
+    /// Bitpacks the zigzag encoded differences between consecutive deltas, in blocks of 128
+    /// elements. The number of bits is defined by how much the deltas vary within a block, which
+    /// suits timestamps increasing at a roughly regular pace.
+    DeltaOfDelta = 3u8,
 }
 
 /// List of all available u64-base codecs.
-pub const ALL_U64_CODEC_TYPES: [CodecType; 3] = [
+pub const ALL_U64_CODEC_TYPES: [CodecType; 4] = [
     CodecType::Bitpacked,
     CodecType::Linear,
     CodecType::BlockwiseLinear,
+    CodecType::DeltaOfDelta,
 ];
 
 impl CodecType {
@@ -105,6 +141,7 @@ impl CodecType {
             0u8 => Some(CodecType::Bitpacked),
             1u8 => Some(CodecType::Linear),
             2u8 => Some(CodecType::BlockwiseLinear),
+            3u8 => Some(CodecType::DeltaOfDelta),
             _ => None,
         }
     }
@@ -117,6 +154,7 @@ impl CodecType {
             CodecType::Bitpacked => load_specific_codec::<BitpackedCodec, T>(bytes),
             CodecType::Linear => load_specific_codec::<LinearCodec, T>(bytes),
             CodecType::BlockwiseLinear => load_specific_codec::<BlockwiseLinearCodec, T>(bytes),
+            CodecType::DeltaOfDelta => load_specific_codec::<DeltaOfDeltaCodec, T>(bytes),
         }
     }
 }
@@ -133,12 +171,24 @@ fn load_specific_codec<C: ColumnCodec, T: MonotonicallyMappableToU64>(
 }
 
 impl CodecType {
+    /// Factor applied to the estimated size of the codec when picking the codec of a column.
+    ///
+    /// Reading a random value from a delta of delta encoded column requires decoding up to a whole
+    /// block, so that the codec is only picked if it is several times smaller than the others.
+    fn size_penalty(&self) -> u64 {
+        match self {
+            CodecType::DeltaOfDelta => 4,
+            CodecType::Bitpacked | CodecType::Linear | CodecType::BlockwiseLinear => 1,
+        }
+    }
+
     /// Returns a boxed codec estimator associated to a given `CodecType`.
     pub fn estimator(&self) -> Box<dyn ColumnCodecEstimator> {
         match self {
             CodecType::Bitpacked => BitpackedCodec::boxed_estimator(),
             CodecType::Linear => LinearCodec::boxed_estimator(),
             CodecType::BlockwiseLinear => BlockwiseLinearCodec::boxed_estimator(),
+            CodecType::DeltaOfDelta => DeltaOfDeltaCodec::boxed_estimator(),
         }
     }
 }
@@ -172,7 +222,9 @@ pub fn serialize_u64_based_column_values<T: MonotonicallyMappableToU64>(
             let num_bytes = estimator.estimate(&stats)?;
             Some((num_bytes, codec_type, estimator))
         })
-        .min_by_key(|(num_bytes, _, _)| *num_bytes)
+        .min_by_key(|(num_bytes, codec_type, _)| {
+            num_bytes.saturating_mul(codec_type.size_penalty())
+        })
         .ok_or_else(|| {
             io::Error::new(io::ErrorKind::InvalidData, "No available applicable codec.")
         })?;
@@ -173,6 +173,11 @@ proptest! {
     fn test_proptest_small_blockwise_linear(data in proptest::collection::vec(num_strategy(), 1..10)) {
         create_and_validate::<BlockwiseLinearCodec>(&data, "proptest multilinearinterpol");
     }
+
+    #[test]
+    fn test_proptest_small_delta_of_delta(data in proptest::collection::vec(num_strategy(), 1..10)) {
+        create_and_validate::<DeltaOfDeltaCodec>(&data, "proptest delta of delta");
+    }
 }
 
 #[test]
@@ -200,6 +205,11 @@ proptest! {
     fn test_proptest_large_blockwise_linear(data in proptest::collection::vec(num_strategy(), 1..6000)) {
         create_and_validate::<BlockwiseLinearCodec>(&data, "proptest multilinearinterpol");
     }
+
+    #[test]
+    fn test_proptest_large_delta_of_delta(data in proptest::collection::vec(num_strategy(), 1..6000)) {
+        create_and_validate::<DeltaOfDeltaCodec>(&data, "proptest delta of delta");
+    }
 }
 
 fn num_strategy() -> impl Strategy<Value = u64> {
@@ -256,6 +266,10 @@ fn test_codec_interpolation() {
 fn test_codec_multi_interpolation() {
     test_codec::<BlockwiseLinearCodec>();
 }
+#[test]
+fn test_codec_delta_of_delta() {
+    test_codec::<DeltaOfDeltaCodec>();
+}
 
 use super::*;
 
@@ -314,7 +328,7 @@ fn test_fast_field_codec_type_to_code() {
             count_codec += 1;
         }
     }
-    assert_eq!(count_codec, 3);
+    assert_eq!(count_codec, 4);
 }
 
 fn test_fastfield_gcd_i64_with_codec(codec_type: CodecType, num_vals: usize) -> io::Result<()> {
@@ -354,6 +368,7 @@ fn test_fastfield_gcd_i64() -> io::Result<()> {
         CodecType::Bitpacked,
         CodecType::BlockwiseLinear,
         CodecType::Linear,
+        CodecType::DeltaOfDelta,
     ] {
         test_fastfield_gcd_i64_with_codec(codec_type, 5500)?;
     }
@@ -396,6 +411,7 @@ fn test_fastfield_gcd_u64() -> io::Result<()> {
         CodecType::Bitpacked,
         CodecType::BlockwiseLinear,
         CodecType::Linear,
+        CodecType::DeltaOfDelta,
     ] {
         test_fastfield_gcd_u64_with_codec(codec_type, 5500)?;
     }
@@ -3,6 +3,7 @@ use std::fmt::Debug;
 use std::net::Ipv6Addr;
 
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use crate::value::NumericalType;
 use crate::InvalidData;
@@ -20,6 +21,7 @@ pub enum ColumnType {
     Bool = 5u8,
     IpAddr = 6u8,
     DateTime = 7u8,
+    Uuid = 8u8,
 }
 
 impl fmt::Display for ColumnType {
@@ -33,13 +35,14 @@ impl fmt::Display for ColumnType {
             ColumnType::Bool => "bool",
             ColumnType::IpAddr => "ip",
             ColumnType::DateTime => "datetime",
+            ColumnType::Uuid => "uuid",
         };
         write!(f, "{short_str}")
     }
 }
 
 // The order needs to match _exactly_ the order in the enum
-const COLUMN_TYPES: [ColumnType; 8] = [
+const COLUMN_TYPES: [ColumnType; 9] = [
     ColumnType::I64,
     ColumnType::U64,
     ColumnType::F64,
@@ -48,6 +51,7 @@ const COLUMN_TYPES: [ColumnType; 8] = [
     ColumnType::Bool,
     ColumnType::IpAddr,
     ColumnType::DateTime,
+    ColumnType::Uuid,
 ];
 
 impl ColumnType {
@@ -83,7 +87,8 @@ impl ColumnType {
             | ColumnType::Str
             | ColumnType::Bool
             | ColumnType::IpAddr
-            | ColumnType::DateTime => None,
+            | ColumnType::DateTime
+            | ColumnType::Uuid => None,
         }
     }
 }
@@ -152,6 +157,16 @@ impl HasAssociatedColumnType for Ipv6Addr {
     }
 }
 
+impl HasAssociatedColumnType for Uuid {
+    fn column_type() -> ColumnType {
+        ColumnType::Uuid
+    }
+
+    fn default_value() -> Self {
+        Uuid::nil()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
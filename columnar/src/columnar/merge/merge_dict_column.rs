@@ -4,10 +4,10 @@ use common::{BitSet, CountingWriter, ReadOnlyBitSet};
 use sstable::{SSTable, Streamer, TermOrdinal, VoidSSTable};
 
 use super::term_merger::{TermMerger, TermsWithSegmentOrd};
-use crate::column::serialize_column_mappable_to_u64;
+use crate::column::{serialize_column_mappable_to_u64, u64_codec_types};
 use crate::column_index::SerializableColumnIndex;
 use crate::iterable::Iterable;
-use crate::{BytesColumn, MergeRowOrder, ShuffleMergeOrder};
+use crate::{BytesColumn, ColumnType, MergeRowOrder, ShuffleMergeOrder};
 
 // Serialize [Dictionary, Column, dictionary num bytes U32::LE]
 // Column: [Column Index, Column Values, column index num bytes U32::LE]
@@ -28,7 +28,12 @@ pub fn merge_bytes_or_str_column(
         term_ord_mapping: &term_ord_mapping,
         merge_row_order,
     };
-    serialize_column_mappable_to_u64(column_index, &remapped_term_ordinals_values, output)?;
+    serialize_column_mappable_to_u64(
+        column_index,
+        &remapped_term_ordinals_values,
+        u64_codec_types(ColumnType::U64),
+        output,
+    )?;
     output.write_all(&dictionary_num_bytes.to_le_bytes())?;
     Ok(())
 }
@@ -3,18 +3,21 @@ mod merge_mapping;
 mod term_merger;
 
 use std::collections::{BTreeMap, HashSet};
-use std::io;
+use std::io::{self, Write};
 use std::net::Ipv6Addr;
 use std::sync::Arc;
 
 pub use merge_mapping::{MergeRowOrder, ShuffleMergeOrder, StackMergeOrder};
+use uuid::Uuid;
 
 use super::writer::ColumnarSerializer;
-use crate::column::{serialize_column_mappable_to_u128, serialize_column_mappable_to_u64};
-use crate::column_values::MergedColumnValues;
+use crate::column::{
+    serialize_column_mappable_to_u128, serialize_column_mappable_to_u64, u64_codec_types,
+};
+use crate::column_values::{MergedColumnValues, MonotonicallyMappableToU128};
 use crate::columnar::merge::merge_dict_column::merge_bytes_or_str_column;
 use crate::columnar::writer::CompatibleNumericalTypes;
-use crate::columnar::ColumnarReader;
+use crate::columnar::{ColumnarReader, CURRENT_VERSION};
 use crate::dynamic_column::DynamicColumn;
 use crate::{
     BytesColumn, Column, ColumnIndex, ColumnType, ColumnValues, DynamicColumnHandle, NumericalType,
@@ -38,6 +41,7 @@ pub(crate) enum ColumnTypeCategory {
     Bool,
     IpAddr,
     DateTime,
+    Uuid,
 }
 
 impl From<ColumnType> for ColumnTypeCategory {
@@ -51,6 +55,7 @@ impl From<ColumnType> for ColumnTypeCategory {
             ColumnType::Bool => ColumnTypeCategory::Bool,
             ColumnType::IpAddr => ColumnTypeCategory::IpAddr,
             ColumnType::DateTime => ColumnTypeCategory::DateTime,
+            ColumnType::Uuid => ColumnTypeCategory::Uuid,
         }
     }
 }
@@ -78,6 +83,28 @@ pub fn merge_columnar(
     required_columns: &[(String, ColumnType)],
     merge_row_order: MergeRowOrder,
     output: &mut impl io::Write,
+) -> io::Result<()> {
+    merge_columnar_with_column_mapper(
+        columnar_readers,
+        required_columns,
+        merge_row_order,
+        &|_columnar_ord, _column_name, column| Ok(column),
+        output,
+    )
+}
+
+/// Same as [`merge_columnar`], except that `map_column` is applied to the columns of the input
+/// columnar tables before they get merged.
+///
+/// `map_column` is called with the ordinal of the columnar table in `columnar_readers`, the name
+/// of the column, and the column coerced to its type after merge. It should not change the
+/// type of the column, nor its index.
+pub fn merge_columnar_with_column_mapper(
+    columnar_readers: &[&ColumnarReader],
+    required_columns: &[(String, ColumnType)],
+    merge_row_order: MergeRowOrder,
+    map_column: &dyn Fn(usize, &str, DynamicColumn) -> io::Result<DynamicColumn>,
+    output: &mut impl io::Write,
 ) -> io::Result<()> {
     let mut serializer = ColumnarSerializer::new(output);
     let num_docs_per_columnar = columnar_readers
@@ -99,6 +126,11 @@ pub fn merge_columnar(
         // Or num_docs_per_columnar would be incorrect.
         assert_eq!(columns.len(), columnar_readers.len());
         coerce_columns(column_type_after_merge, &mut columns)?;
+        for (columnar_ord, column_opt) in columns.iter_mut().enumerate() {
+            if let Some(column) = column_opt.take() {
+                *column_opt = Some(map_column(columnar_ord, &column_name, column)?);
+            }
+        }
 
         let mut column_serializer =
             serializer.start_serialize_column(column_name.as_bytes(), column_type_after_merge);
@@ -116,6 +148,60 @@ pub fn merge_columnar(
     Ok(())
 }
 
+/// Writes the columns of `columnar`, in which the columns whose name is selected by
+/// `is_replaced` are replaced by the columns of `replacement` selected by `is_replaced`.
+///
+/// Contrary to [`merge_columnar`], the columns are not decoded: their bytes are copied as is.
+/// Both columnar tables must have the same number of rows, and be written in the current format
+/// version.
+pub fn replace_columns(
+    columnar: &ColumnarReader,
+    replacement: &ColumnarReader,
+    is_replaced: &dyn Fn(&str) -> bool,
+    output: &mut impl io::Write,
+) -> io::Result<()> {
+    if columnar.num_docs() != replacement.num_docs() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "The replacement columns have {} rows instead of {}.",
+                replacement.num_docs(),
+                columnar.num_docs()
+            ),
+        ));
+    }
+    if columnar.format_version() != CURRENT_VERSION
+        || replacement.format_version() != CURRENT_VERSION
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "The columns of an older format version cannot be copied as is.",
+        ));
+    }
+    let mut columns: Vec<(String, DynamicColumnHandle)> = columnar
+        .iter_columns()?
+        .filter(|(column_name, _)| !is_replaced(column_name))
+        .collect();
+    columns.extend(
+        replacement
+            .iter_columns()?
+            .filter(|(column_name, _)| is_replaced(column_name)),
+    );
+    // The column dictionary is sorted by column name, and then by column type.
+    columns.sort_by_key(|(column_name, column_handle)| {
+        (column_name.clone(), column_handle.column_type().to_code())
+    });
+    let mut serializer = ColumnarSerializer::new(output);
+    for (column_name, column_handle) in &columns {
+        let column_bytes = column_handle.file_slice().read_bytes()?;
+        let mut column_serializer =
+            serializer.start_serialize_column(column_name.as_bytes(), column_handle.column_type());
+        column_serializer.write_all(column_bytes.as_slice())?;
+        column_serializer.finalize()?;
+    }
+    serializer.finalize(columnar.num_docs())
+}
+
 fn dynamic_column_to_u64_monotonic(dynamic_column: DynamicColumn) -> Option<Column<u64>> {
     match dynamic_column {
         DynamicColumn::Bool(column) => Some(column.to_u64_monotonic()),
@@ -123,8 +209,47 @@ fn dynamic_column_to_u64_monotonic(dynamic_column: DynamicColumn) -> Option<Colu
         DynamicColumn::U64(column) => Some(column.to_u64_monotonic()),
         DynamicColumn::F64(column) => Some(column.to_u64_monotonic()),
         DynamicColumn::DateTime(column) => Some(column.to_u64_monotonic()),
-        DynamicColumn::IpAddr(_) | DynamicColumn::Bytes(_) | DynamicColumn::Str(_) => None,
+        DynamicColumn::IpAddr(_)
+        | DynamicColumn::Uuid(_)
+        | DynamicColumn::Bytes(_)
+        | DynamicColumn::Str(_) => None,
+    }
+}
+
+fn merge_column_u128<T: MonotonicallyMappableToU128>(
+    num_docs_per_column: &[u32],
+    columns_to_merge: Vec<Option<DynamicColumn>>,
+    merge_row_order: &MergeRowOrder,
+    wrt: &mut impl io::Write,
+) -> io::Result<()>
+where
+    Option<Column<T>>: From<DynamicColumn>,
+{
+    let mut column_indexes: Vec<ColumnIndex> = Vec::with_capacity(columns_to_merge.len());
+    let mut column_values: Vec<Option<Arc<dyn ColumnValues<T>>>> =
+        Vec::with_capacity(columns_to_merge.len());
+    for (i, dynamic_column_opt) in columns_to_merge.into_iter().enumerate() {
+        if let Some(Column { index: idx, values }) =
+            dynamic_column_opt.and_then(|dynamic_column| dynamic_column.into())
+        {
+            column_indexes.push(idx);
+            column_values.push(Some(values));
+        } else {
+            column_indexes.push(ColumnIndex::Empty {
+                num_docs: num_docs_per_column[i],
+            });
+            column_values.push(None);
+        }
     }
+
+    let merged_column_index =
+        crate::column_index::merge_column_index(&column_indexes[..], merge_row_order);
+    let merge_column_values = MergedColumnValues {
+        column_indexes: &column_indexes[..],
+        column_values: &column_values,
+        merge_row_order,
+    };
+    serialize_column_mappable_to_u128(merged_column_index, &merge_column_values, wrt)
 }
 
 fn merge_column(
@@ -163,35 +288,23 @@ fn merge_column(
                 column_values: &column_values[..],
                 merge_row_order,
             };
-            serialize_column_mappable_to_u64(merged_column_index, &merge_column_values, wrt)?;
+            serialize_column_mappable_to_u64(
+                merged_column_index,
+                &merge_column_values,
+                u64_codec_types(column_type),
+                wrt,
+            )?;
         }
         ColumnType::IpAddr => {
-            let mut column_indexes: Vec<ColumnIndex> = Vec::with_capacity(columns_to_merge.len());
-            let mut column_values: Vec<Option<Arc<dyn ColumnValues<Ipv6Addr>>>> =
-                Vec::with_capacity(columns_to_merge.len());
-            for (i, dynamic_column_opt) in columns_to_merge.into_iter().enumerate() {
-                if let Some(DynamicColumn::IpAddr(Column { index: idx, values })) =
-                    dynamic_column_opt
-                {
-                    column_indexes.push(idx);
-                    column_values.push(Some(values));
-                } else {
-                    column_indexes.push(ColumnIndex::Empty {
-                        num_docs: num_docs_per_column[i],
-                    });
-                    column_values.push(None);
-                }
-            }
-
-            let merged_column_index =
-                crate::column_index::merge_column_index(&column_indexes[..], merge_row_order);
-            let merge_column_values = MergedColumnValues {
-                column_indexes: &column_indexes[..],
-                column_values: &column_values,
+            merge_column_u128::<Ipv6Addr>(
+                num_docs_per_column,
+                columns_to_merge,
                 merge_row_order,
-            };
-
-            serialize_column_mappable_to_u128(merged_column_index, &merge_column_values, wrt)?;
+                wrt,
+            )?;
+        }
+        ColumnType::Uuid => {
+            merge_column_u128::<Uuid>(num_docs_per_column, columns_to_merge, merge_row_order, wrt)?;
         }
         ColumnType::Bytes | ColumnType::Str => {
             let mut column_indexes: Vec<ColumnIndex> = Vec::with_capacity(columns_to_merge.len());
@@ -465,6 +578,7 @@ fn min_max_if_numerical(column: &DynamicColumn) -> Option<(NumericalValue, Numer
         DynamicColumn::Bool(_)
         | DynamicColumn::IpAddr(_)
         | DynamicColumn::DateTime(_)
+        | DynamicColumn::Uuid(_)
         | DynamicColumn::Bytes(_)
         | DynamicColumn::Str(_) => None,
     }
@@ -471,6 +471,61 @@ fn test_merge_columnar_different_empty_cardinality() {
     assert_eq!(dynamic_column.get_cardinality(), Cardinality::Optional);
 }
 
+#[test]
+fn test_replace_columns() {
+    let columnar = make_text_columnar_multiple_columns(&[
+        ("body", &[&["Hello World"], &[]]),
+        ("id", &[&["a"], &["b"]]),
+        ("title", &[&["x"], &["y"]]),
+    ]);
+    // Only the selected columns of the replacement are kept.
+    let replacement = make_text_columnar_multiple_columns(&[
+        ("body", &[&["hello"], &["bye"]]),
+        ("other", &[&["z"], &["z"]]),
+    ]);
+    let mut buffer = Vec::new();
+    crate::columnar::replace_columns(
+        &columnar,
+        &replacement,
+        &|column_name| column_name == "body",
+        &mut buffer,
+    )
+    .unwrap();
+    let columnar_reader = ColumnarReader::open(buffer).unwrap();
+    assert_eq!(columnar_reader.num_docs(), 2);
+    let column_names: Vec<String> = columnar_reader
+        .list_columns()
+        .unwrap()
+        .into_iter()
+        .map(|(column_name, _)| column_name)
+        .collect();
+    assert_eq!(column_names, ["body", "id", "title"]);
+    let get_str = |column_name: &str, row_id: RowId| {
+        let column = columnar_reader.read_columns(column_name).unwrap()[0]
+            .open()
+            .unwrap();
+        let DynamicColumn::Str(str_column) = column else {
+            panic!()
+        };
+        let mut values = Vec::new();
+        for term_ord in str_column.term_ords(row_id) {
+            let mut value = String::new();
+            str_column.ord_to_str(term_ord, &mut value).unwrap();
+            values.push(value);
+        }
+        values
+    };
+    assert_eq!(get_str("body", 1), ["bye"]);
+    assert_eq!(get_str("title", 1), ["y"]);
+
+    // Both columnar tables must have the same number of rows.
+    let replacement = make_text_columnar_multiple_columns(&[("body", &[&["hello"]])]);
+    let mut buffer = Vec::new();
+    let err = crate::columnar::replace_columns(&columnar, &replacement, &|_| true, &mut buffer)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
 #[derive(Debug, Clone)]
 struct ColumnSpec {
     column_name: String,
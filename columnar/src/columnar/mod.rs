@@ -8,6 +8,9 @@ pub use column_type::{ColumnType, HasAssociatedColumnType};
 pub use format_version::{Version, CURRENT_VERSION};
 #[cfg(test)]
 pub(crate) use merge::ColumnTypeCategory;
-pub use merge::{merge_columnar, MergeRowOrder, ShuffleMergeOrder, StackMergeOrder};
+pub use merge::{
+    merge_columnar, merge_columnar_with_column_mapper, replace_columns, MergeRowOrder,
+    ShuffleMergeOrder, StackMergeOrder,
+};
 pub use reader::ColumnarReader;
 pub use writer::ColumnarWriter;
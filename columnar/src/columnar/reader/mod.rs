@@ -2,7 +2,7 @@ use std::{fmt, io, mem};
 
 use common::file_slice::FileSlice;
 use common::json_path_writer::JSON_PATH_SEGMENT_SEP;
-use common::BinarySerializable;
+use common::{BinarySerializable, HasLen};
 use sstable::{Dictionary, RangeSSTable};
 
 use crate::columnar::{format_version, ColumnType};
@@ -66,6 +66,11 @@ fn read_all_columns_in_stream(
         let column_type = ColumnType::try_from_code(column_code)
             .map_err(|_| io_invalid_data(format!("Unknown column code `{column_code}`")))?;
         let range = stream.value();
+        if range.start > range.end || range.end > column_data.len() as u64 {
+            return Err(io_invalid_data(format!(
+                "Column range {range:?} exceeds the column data."
+            )));
+        }
         let file_slice = column_data.slice(range.start as usize..range.end as usize);
         let dynamic_column_handle = DynamicColumnHandle {
             file_slice,
@@ -97,18 +102,50 @@ impl ColumnarReader {
         Self::open_inner(file_slice.into())
     }
 
+    /// Opens a Columnar file coming from an untrusted source.
+    ///
+    /// On top of what [`ColumnarReader::open`] does, this validates the whole column
+    /// dictionary (see [`Dictionary::open_untrusted`]) and checks that all of the columns
+    /// are well-formed and stored within the file, so that listing columns does not panic,
+    /// whatever the content of the file.
+    pub fn open_untrusted<F>(file_slice: F) -> io::Result<ColumnarReader>
+    where FileSlice: From<F> {
+        let columnar_reader =
+            Self::open_with_dictionary(file_slice.into(), Dictionary::open_untrusted)?;
+        columnar_reader.validate_columns()?;
+        Ok(columnar_reader)
+    }
+
     fn open_inner(file_slice: FileSlice) -> io::Result<ColumnarReader> {
-        let (file_slice_without_sstable_len, footer_slice) = file_slice
-            .split_from_end(mem::size_of::<u64>() + 4 + format_version::VERSION_FOOTER_NUM_BYTES);
+        Self::open_with_dictionary(file_slice, Dictionary::open)
+    }
+
+    fn open_with_dictionary(
+        file_slice: FileSlice,
+        open_dictionary: impl FnOnce(FileSlice) -> io::Result<Dictionary<RangeSSTable>>,
+    ) -> io::Result<ColumnarReader> {
+        let footer_len = mem::size_of::<u64>() + 4 + format_version::VERSION_FOOTER_NUM_BYTES;
+        if file_slice.len() < footer_len {
+            return Err(io_invalid_data(format!(
+                "Columnar file is smaller than its footer ({} bytes).",
+                file_slice.len()
+            )));
+        }
+        let (file_slice_without_sstable_len, footer_slice) = file_slice.split_from_end(footer_len);
         let footer_bytes = footer_slice.read_bytes()?;
         let sstable_len = u64::deserialize(&mut &footer_bytes[0..8])?;
         let num_rows = u32::deserialize(&mut &footer_bytes[8..12])?;
         let version_footer_bytes: [u8; format_version::VERSION_FOOTER_NUM_BYTES] =
             footer_bytes[12..].try_into().unwrap();
         let format_version = format_version::parse_footer(version_footer_bytes)?;
+        if sstable_len > file_slice_without_sstable_len.len() as u64 {
+            return Err(io_invalid_data(format!(
+                "Column dictionary length {sstable_len} exceeds the columnar file length."
+            )));
+        }
         let (column_data, sstable) =
             file_slice_without_sstable_len.split_from_end(sstable_len as usize);
-        let column_dictionary = Dictionary::open(sstable)?;
+        let column_dictionary = open_dictionary(sstable)?;
         Ok(ColumnarReader {
             column_dictionary,
             column_data,
@@ -120,20 +157,23 @@ impl ColumnarReader {
     pub fn num_docs(&self) -> RowId {
         self.num_docs
     }
+
+    pub(crate) fn format_version(&self) -> Version {
+        self.format_version
+    }
     // Iterate over the columns in a sorted way
     pub fn iter_columns(
         &self,
     ) -> io::Result<impl Iterator<Item = (String, DynamicColumnHandle)> + '_> {
+        // The API gets quite ugly when returning errors from the iterator, so instead we check
+        // all of the columns upfront.
+        self.validate_columns()?;
         let mut stream = self.column_dictionary.stream()?;
         Ok(std::iter::from_fn(move || {
             if stream.advance() {
                 let key_bytes: &[u8] = stream.key();
-                let column_code: u8 = key_bytes.last().cloned().unwrap();
-                // TODO Error Handling. The API gets quite ugly when returning the error here, so
-                // instead we could just check the first N columns upfront.
-                let column_type: ColumnType = ColumnType::try_from_code(column_code)
-                    .map_err(|_| io_invalid_data(format!("Unknown column code `{column_code}`")))
-                    .unwrap();
+                let column_code: u8 = key_bytes.last().cloned()?;
+                let column_type: ColumnType = ColumnType::try_from_code(column_code).ok()?;
                 let range = stream.value().clone();
                 let column_name =
                 // The last two bytes are respectively the 0u8 separator and the column_type.
@@ -153,11 +193,79 @@ impl ColumnarReader {
         }))
     }
 
+    /// Checks that the keys and the ranges of all of the columns are valid.
+    fn validate_columns(&self) -> io::Result<()> {
+        let mut stream = self.column_dictionary.stream()?;
+        while stream.advance() {
+            let key_bytes: &[u8] = stream.key();
+            let [.., separator, column_code] = key_bytes else {
+                return Err(io_invalid_data("Column key is too short.".to_string()));
+            };
+            if *separator != 0u8 {
+                return Err(io_invalid_data(
+                    "Column key is missing its separator.".to_string(),
+                ));
+            }
+            ColumnType::try_from_code(*column_code)
+                .map_err(|_| io_invalid_data(format!("Unknown column code `{column_code}`")))?;
+            let range = stream.value();
+            if range.start > range.end || range.end > self.column_data.len() as u64 {
+                return Err(io_invalid_data(format!(
+                    "Column range {range:?} exceeds the column data."
+                )));
+            }
+        }
+        Ok(())
+    }
+
     // TODO Add unit tests
     pub fn list_columns(&self) -> io::Result<Vec<(String, DynamicColumnHandle)>> {
         Ok(self.iter_columns()?.collect())
     }
 
+    /// Lists the columns whose name starts with the given prefix, sorted by name.
+    ///
+    /// Unlike [`ColumnarReader::list_columns`], only the part of the column dictionary matching
+    /// the prefix is read.
+    pub fn list_columns_with_prefix(
+        &self,
+        prefix: &str,
+    ) -> io::Result<Vec<(String, DynamicColumnHandle)>> {
+        let mut stream = self
+            .column_dictionary
+            .prefix_range(prefix.as_bytes())
+            .into_stream()?;
+        let mut columns = Vec::new();
+        while stream.advance() {
+            let key_bytes: &[u8] = stream.key();
+            let [column_name @ .., 0u8, column_code] = key_bytes else {
+                return Err(io_invalid_data(
+                    "Column key is missing its separator.".to_string(),
+                ));
+            };
+            let column_type = ColumnType::try_from_code(*column_code)
+                .map_err(|_| io_invalid_data(format!("Unknown column code `{column_code}`")))?;
+            let range = stream.value();
+            if range.start > range.end || range.end > self.column_data.len() as u64 {
+                return Err(io_invalid_data(format!(
+                    "Column range {range:?} exceeds the column data."
+                )));
+            }
+            let column_handle = DynamicColumnHandle {
+                file_slice: self
+                    .column_data
+                    .slice(range.start as usize..range.end as usize),
+                column_type,
+                format_version: self.format_version,
+            };
+            columns.push((
+                String::from_utf8_lossy(column_name).to_string(),
+                column_handle,
+            ));
+        }
+        Ok(columns)
+    }
+
     pub async fn read_columns_async(
         &self,
         column_name: &str,
@@ -308,6 +416,78 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_list_columns_with_prefix() {
+        let mut columnar_writer = ColumnarWriter::default();
+        columnar_writer.record_str(0, "attr\u{1}host", "a");
+        columnar_writer.record_numerical(0, "attr\u{1}http\u{1}status", 200u64);
+        columnar_writer.record_str(1, "attr\u{1}http\u{1}status", "ok");
+        columnar_writer.record_str(0, "body", "hello");
+        let mut buffer = Vec::new();
+        columnar_writer.serialize(2, &mut buffer).unwrap();
+        let columnar = ColumnarReader::open(buffer).unwrap();
+        let columns = columnar.list_columns_with_prefix("attr\u{1}h").unwrap();
+        let column_names_and_types: Vec<(&str, ColumnType)> = columns
+            .iter()
+            .map(|(column_name, handle)| (column_name.as_str(), handle.column_type()))
+            .collect();
+        assert_eq!(
+            column_names_and_types,
+            [
+                ("attr\u{1}host", ColumnType::Str),
+                ("attr\u{1}http\u{1}status", ColumnType::I64),
+                ("attr\u{1}http\u{1}status", ColumnType::Str),
+            ]
+        );
+        assert!(columnar
+            .list_columns_with_prefix("other")
+            .unwrap()
+            .is_empty());
+    }
+
+    fn make_columnar() -> Vec<u8> {
+        let mut columnar_writer = ColumnarWriter::default();
+        for row_id in 0..100u32 {
+            columnar_writer.record_numerical(row_id, "num", u64::from(row_id));
+            columnar_writer.record_str(row_id, "str", &format!("str{row_id}"));
+            columnar_writer.record_bool(row_id, "bool", row_id % 2 == 0);
+        }
+        let mut buffer = Vec::new();
+        columnar_writer.serialize(100, &mut buffer).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn test_open_untrusted() {
+        let columnar = ColumnarReader::open_untrusted(make_columnar()).unwrap();
+        assert_eq!(columnar.num_docs(), 100);
+        let columns = columnar.list_columns().unwrap();
+        assert_eq!(columns.len(), 3);
+        assert_eq!(&columns[0].0, "bool");
+        assert_eq!(&columns[1].0, "num");
+        assert_eq!(&columns[2].0, "str");
+    }
+
+    #[test]
+    fn test_open_untrusted_corrupted() {
+        let buffer = make_columnar();
+        for len in 0..buffer.len() {
+            // Truncated columnars either fail to open, or at least do not panic.
+            if let Ok(columnar) = ColumnarReader::open_untrusted(buffer[..len].to_vec()) {
+                columnar.list_columns().unwrap();
+            }
+        }
+        for pos in 0..buffer.len() {
+            for mask in [0x01, 0xff] {
+                let mut corrupted = buffer.clone();
+                corrupted[pos] ^= mask;
+                if let Ok(columnar) = ColumnarReader::open_untrusted(corrupted) {
+                    columnar.list_columns().unwrap();
+                }
+            }
+        }
+    }
+
     #[test]
     #[should_panic(expected = "Input type forbidden")]
     fn test_list_columns_strict_typing_panics_on_wrong_types() {
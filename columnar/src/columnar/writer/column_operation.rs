@@ -1,5 +1,7 @@
 use std::net::Ipv6Addr;
 
+use uuid::Uuid;
+
 use crate::dictionary::UnorderedId;
 use crate::utils::{place_bits, pop_first_byte, select_bits};
 use crate::value::NumericalValue;
@@ -155,6 +157,18 @@ impl SymbolValue for Ipv6Addr {
     }
 }
 
+impl SymbolValue for Uuid {
+    fn serialize(self, buffer: &mut [u8]) -> u8 {
+        buffer[0..16].copy_from_slice(self.as_bytes());
+        16
+    }
+
+    fn deserialize(bytes: &[u8]) -> Self {
+        let bytes: [u8; 16] = bytes[0..16].try_into().unwrap();
+        Uuid::from_bytes(bytes)
+    }
+}
+
 #[derive(Default)]
 struct MiniBuffer {
     pub bytes: [u8; 17],
@@ -12,9 +12,11 @@ use common::json_path_writer::JSON_END_OF_PATH;
 use common::CountingWriter;
 pub(crate) use serializer::ColumnarSerializer;
 use stacker::{Addr, ArenaHashMap, MemoryArena};
+use uuid::Uuid;
 
+use crate::column::u64_codec_types;
 use crate::column_index::{SerializableColumnIndex, SerializableOptionalIndex};
-use crate::column_values::{MonotonicallyMappableToU128, MonotonicallyMappableToU64};
+use crate::column_values::{CodecType, MonotonicallyMappableToU128, MonotonicallyMappableToU64};
 use crate::columnar::column_type::ColumnType;
 use crate::columnar::writer::column_writers::{
     ColumnWriter, NumericalColumnWriter, StrOrBytesColumnWriter,
@@ -31,6 +33,7 @@ struct SpareBuffers {
     value_index_builders: PreallocatedIndexBuilders,
     u64_values: Vec<u64>,
     ip_addr_values: Vec<Ipv6Addr>,
+    uuid_values: Vec<Uuid>,
 }
 
 /// Makes it possible to create a new columnar.
@@ -52,6 +55,7 @@ pub struct ColumnarWriter {
     datetime_field_hash_map: ArenaHashMap,
     bool_field_hash_map: ArenaHashMap,
     ip_addr_field_hash_map: ArenaHashMap,
+    uuid_field_hash_map: ArenaHashMap,
     bytes_field_hash_map: ArenaHashMap,
     str_field_hash_map: ArenaHashMap,
     arena: MemoryArena,
@@ -68,6 +72,7 @@ impl ColumnarWriter {
             + self.bytes_field_hash_map.mem_usage()
             + self.str_field_hash_map.mem_usage()
             + self.ip_addr_field_hash_map.mem_usage()
+            + self.uuid_field_hash_map.mem_usage()
             + self.datetime_field_hash_map.mem_usage()
             + self
                 .dictionaries
@@ -145,6 +150,10 @@ impl ColumnarWriter {
                 column_name.as_bytes(),
                 |column_opt: Option<ColumnWriter>| column_opt.unwrap_or_default(),
             ),
+            ColumnType::Uuid => self.uuid_field_hash_map.mutate_or_create(
+                column_name.as_bytes(),
+                |column_opt: Option<ColumnWriter>| column_opt.unwrap_or_default(),
+            ),
         }
     }
 
@@ -177,6 +186,18 @@ impl ColumnarWriter {
         );
     }
 
+    pub fn record_uuid(&mut self, doc: RowId, column_name: &str, uuid: Uuid) {
+        let (hash_map, arena) = (&mut self.uuid_field_hash_map, &mut self.arena);
+        hash_map.mutate_or_create(
+            column_name.as_bytes(),
+            |column_opt: Option<ColumnWriter>| {
+                let mut column: ColumnWriter = column_opt.unwrap_or_default();
+                column.record(doc, uuid, arena);
+                column
+            },
+        );
+    }
+
     pub fn record_bool(&mut self, doc: RowId, column_name: &str, val: bool) {
         let (hash_map, arena) = (&mut self.bool_field_hash_map, &mut self.arena);
         hash_map.mutate_or_create(
@@ -279,6 +300,11 @@ impl ColumnarWriter {
                 .iter()
                 .map(|(column_name, addr)| (column_name, ColumnType::IpAddr, addr)),
         );
+        columns.extend(
+            self.uuid_field_hash_map
+                .iter()
+                .map(|(column_name, addr)| (column_name, ColumnType::Uuid, addr)),
+        );
         columns.extend(
             self.datetime_field_hash_map
                 .iter()
@@ -323,6 +349,20 @@ impl ColumnarWriter {
                     )?;
                     column_serializer.finalize()?;
                 }
+                ColumnType::Uuid => {
+                    let column_writer: ColumnWriter = self.uuid_field_hash_map.read(addr);
+                    let cardinality = column_writer.get_cardinality(num_docs);
+                    let mut column_serializer =
+                        serializer.start_serialize_column(column_name, ColumnType::Uuid);
+                    serialize_uuid_column(
+                        cardinality,
+                        num_docs,
+                        column_writer.operation_iterator(arena, &mut symbol_byte_buffer),
+                        buffers,
+                        &mut column_serializer,
+                    )?;
+                    column_serializer.finalize()?;
+                }
                 ColumnType::Bytes | ColumnType::Str => {
                     let str_or_bytes_column_writer: StrOrBytesColumnWriter =
                         if column_type == ColumnType::Bytes {
@@ -361,6 +401,7 @@ impl ColumnarWriter {
                         cardinality,
                         num_docs,
                         numerical_type,
+                        u64_codec_types(column_type),
                         numerical_column_writer.operation_iterator(arena, &mut symbol_byte_buffer),
                         buffers,
                         &mut column_serializer,
@@ -376,6 +417,7 @@ impl ColumnarWriter {
                         cardinality,
                         num_docs,
                         NumericalType::I64,
+                        u64_codec_types(ColumnType::DateTime),
                         column_writer.operation_iterator(arena, &mut symbol_byte_buffer),
                         buffers,
                         &mut column_serializer,
@@ -427,6 +469,7 @@ fn serialize_bytes_or_str_column(
         cardinality,
         num_docs,
         sort_values_within_row,
+        u64_codec_types(ColumnType::Str),
         value_index_builders,
         u64_values,
         &mut wrt,
@@ -439,6 +482,7 @@ fn serialize_numerical_column(
     cardinality: Cardinality,
     num_docs: RowId,
     numerical_type: NumericalType,
+    codec_types: &[CodecType],
     op_iterator: impl Iterator<Item = ColumnOperation<NumericalValue>>,
     buffers: &mut SpareBuffers,
     wrt: &mut impl io::Write,
@@ -455,6 +499,7 @@ fn serialize_numerical_column(
                 cardinality,
                 num_docs,
                 false,
+                codec_types,
                 value_index_builders,
                 u64_values,
                 wrt,
@@ -466,6 +511,7 @@ fn serialize_numerical_column(
                 cardinality,
                 num_docs,
                 false,
+                codec_types,
                 value_index_builders,
                 u64_values,
                 wrt,
@@ -477,6 +523,7 @@ fn serialize_numerical_column(
                 cardinality,
                 num_docs,
                 false,
+                codec_types,
                 value_index_builders,
                 u64_values,
                 wrt,
@@ -506,6 +553,7 @@ fn serialize_bool_column(
         cardinality,
         num_docs,
         false,
+        u64_codec_types(ColumnType::Bool),
         value_index_builders,
         u64_values,
         wrt,
@@ -536,6 +584,29 @@ fn serialize_ip_addr_column(
     Ok(())
 }
 
+fn serialize_uuid_column(
+    cardinality: Cardinality,
+    num_docs: RowId,
+    column_operations_it: impl Iterator<Item = ColumnOperation<Uuid>>,
+    buffers: &mut SpareBuffers,
+    wrt: &mut impl io::Write,
+) -> io::Result<()> {
+    let SpareBuffers {
+        value_index_builders,
+        uuid_values,
+        ..
+    } = buffers;
+    send_to_serialize_column_mappable_to_u128(
+        column_operations_it,
+        cardinality,
+        num_docs,
+        value_index_builders,
+        uuid_values,
+        wrt,
+    )?;
+    Ok(())
+}
+
 fn send_to_serialize_column_mappable_to_u128<
     T: Copy + Ord + std::fmt::Debug + Send + Sync + MonotonicallyMappableToU128 + PartialOrd,
 >(
@@ -581,11 +652,13 @@ fn send_to_serialize_column_mappable_to_u128<
     Ok(())
 }
 
+#[expect(clippy::too_many_arguments)]
 fn send_to_serialize_column_mappable_to_u64(
     op_iterator: impl Iterator<Item = ColumnOperation<u64>>,
     cardinality: Cardinality,
     num_rows: RowId,
     sort_values_within_row: bool,
+    codec_types: &[CodecType],
     value_index_builders: &mut PreallocatedIndexBuilders,
     values: &mut Vec<u64>,
     mut wrt: impl io::Write,
@@ -625,6 +698,7 @@ fn send_to_serialize_column_mappable_to_u64(
     crate::column::serialize_column_mappable_to_u64(
         serializable_column_index,
         &&values[..],
+        codec_types,
         &mut wrt,
     )?;
     Ok(())
@@ -4,6 +4,7 @@ use std::{fmt, io};
 
 use common::file_slice::FileSlice;
 use common::{ByteCount, DateTime, HasLen, OwnedBytes};
+use uuid::Uuid;
 
 use crate::column::{BytesColumn, Column, StrColumn};
 use crate::column_values::{monotonic_map_column, StrictlyMonotonicFn};
@@ -18,6 +19,7 @@ pub enum DynamicColumn {
     F64(Column<f64>),
     IpAddr(Column<Ipv6Addr>),
     DateTime(Column<DateTime>),
+    Uuid(Column<Uuid>),
     Bytes(BytesColumn),
     Str(StrColumn),
 }
@@ -32,6 +34,7 @@ impl fmt::Debug for DynamicColumn {
             DynamicColumn::F64(col) => write!(f, "{col:?}")?,
             DynamicColumn::IpAddr(col) => write!(f, "{col:?}")?,
             DynamicColumn::DateTime(col) => write!(f, "{col:?}")?,
+            DynamicColumn::Uuid(col) => write!(f, "{col:?}")?,
             DynamicColumn::Bytes(col) => write!(f, "{col:?}")?,
             DynamicColumn::Str(col) => write!(f, "{col:?}")?,
         }
@@ -48,6 +51,7 @@ impl DynamicColumn {
             DynamicColumn::F64(c) => &c.index,
             DynamicColumn::IpAddr(c) => &c.index,
             DynamicColumn::DateTime(c) => &c.index,
+            DynamicColumn::Uuid(c) => &c.index,
             DynamicColumn::Bytes(c) => &c.ords().index,
             DynamicColumn::Str(c) => &c.ords().index,
         }
@@ -65,6 +69,7 @@ impl DynamicColumn {
             DynamicColumn::F64(c) => c.values.num_vals(),
             DynamicColumn::IpAddr(c) => c.values.num_vals(),
             DynamicColumn::DateTime(c) => c.values.num_vals(),
+            DynamicColumn::Uuid(c) => c.values.num_vals(),
             DynamicColumn::Bytes(c) => c.ords().values.num_vals(),
             DynamicColumn::Str(c) => c.ords().values.num_vals(),
         }
@@ -78,6 +83,7 @@ impl DynamicColumn {
             DynamicColumn::F64(_) => ColumnType::F64,
             DynamicColumn::IpAddr(_) => ColumnType::IpAddr,
             DynamicColumn::DateTime(_) => ColumnType::DateTime,
+            DynamicColumn::Uuid(_) => ColumnType::Uuid,
             DynamicColumn::Bytes(_) => ColumnType::Bytes,
             DynamicColumn::Str(_) => ColumnType::Str,
         }
@@ -227,6 +233,7 @@ static_dynamic_conversions!(Column<DateTime>, DateTime);
 static_dynamic_conversions!(StrColumn, Str);
 static_dynamic_conversions!(BytesColumn, Bytes);
 static_dynamic_conversions!(Column<Ipv6Addr>, IpAddr);
+static_dynamic_conversions!(Column<Uuid>, Uuid);
 
 #[derive(Clone, Debug)]
 pub struct DynamicColumnHandle {
@@ -248,10 +255,10 @@ impl DynamicColumnHandle {
     }
 
     /// Returns the `u64` fast field reader reader associated with `fields` of types
-    /// Str, u64, i64, f64, bool, ip, or datetime.
+    /// Str, u64, i64, f64, bool, ip, datetime or uuid.
     ///
-    /// Notice that for IpAddr, the fastfield reader will return the u64 representation of the
-    /// IpAddr.
+    /// Notice that for IpAddr and Uuid, the fastfield reader will return the u64 representation
+    /// of the value.
     /// In order to convert to u128 back cast to `CompactSpaceU64Accessor` and call
     /// `compact_to_u128`.
     ///
@@ -265,7 +272,7 @@ impl DynamicColumnHandle {
                     crate::column::open_column_bytes(column_bytes, self.format_version)?;
                 Ok(Some(column.term_ord_column))
             }
-            ColumnType::IpAddr => {
+            ColumnType::IpAddr | ColumnType::Uuid => {
                 let column = crate::column::open_column_u128_as_compact_u64(
                     column_bytes,
                     self.format_version,
@@ -312,6 +319,9 @@ impl DynamicColumnHandle {
                 crate::column::open_column_u64::<DateTime>(column_bytes, self.format_version)?
                     .into()
             }
+            ColumnType::Uuid => {
+                crate::column::open_column_u128::<Uuid>(column_bytes, self.format_version)?.into()
+            }
         };
         Ok(dynamic_column)
     }
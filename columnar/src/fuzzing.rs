@@ -0,0 +1,89 @@
+//! Generators of arbitrary, possibly corrupted, columnar files.
+//!
+//! This module is only available with the `arbitrary` feature.
+
+use std::net::Ipv6Addr;
+
+use arbitrary::Arbitrary;
+use common::fuzzing::{corrupt, Corruption};
+use common::DateTime;
+use uuid::Uuid;
+
+use crate::{ColumnarWriter, RowId};
+
+/// Number of distinct column names used by [`ArbitraryColumnar`].
+///
+/// Keeping it small makes sure columns hold several values, and that different
+/// value types get recorded in the same column.
+const NUM_COLUMN_NAMES: u8 = 4;
+
+/// A value recorded in an [`ArbitraryColumnar`].
+#[derive(Arbitrary, Clone, Debug)]
+#[allow(missing_docs)]
+pub enum ArbitraryValue {
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
+    DateTime(i64),
+    IpAddr(u128),
+    Uuid(u128),
+    Str(String),
+    Bytes(Vec<u8>),
+}
+
+/// An arbitrary columnar file.
+///
+/// The file is obtained by serializing a valid columnar with a [`ColumnarWriter`],
+/// and then applying the corruptions to it.
+#[derive(Arbitrary, Clone, Debug)]
+pub struct ArbitraryColumnar {
+    /// The values of each row, along with the id of the column they are recorded in.
+    pub rows: Vec<Vec<(u8, ArbitraryValue)>>,
+    /// The corruptions applied to the serialized file.
+    pub corruptions: Vec<Corruption>,
+}
+
+impl ArbitraryColumnar {
+    /// Serializes the columnar, before any corruption.
+    pub fn to_valid_bytes(&self) -> Vec<u8> {
+        let mut writer = ColumnarWriter::default();
+        for (row_id, row) in self.rows.iter().enumerate() {
+            let row_id = row_id as RowId;
+            for (column_id, value) in row {
+                let column_name = format!("col{}", column_id % NUM_COLUMN_NAMES);
+                match value {
+                    ArbitraryValue::I64(val) => writer.record_numerical(row_id, &column_name, *val),
+                    ArbitraryValue::U64(val) => writer.record_numerical(row_id, &column_name, *val),
+                    ArbitraryValue::F64(val) => writer.record_numerical(row_id, &column_name, *val),
+                    ArbitraryValue::Bool(val) => writer.record_bool(row_id, &column_name, *val),
+                    ArbitraryValue::DateTime(timestamp_nanos) => writer.record_datetime(
+                        row_id,
+                        &column_name,
+                        DateTime::from_timestamp_nanos(*timestamp_nanos),
+                    ),
+                    ArbitraryValue::IpAddr(val) => {
+                        writer.record_ip_addr(row_id, &column_name, Ipv6Addr::from(*val))
+                    }
+                    ArbitraryValue::Uuid(val) => {
+                        writer.record_uuid(row_id, &column_name, Uuid::from_u128(*val))
+                    }
+                    ArbitraryValue::Str(val) => writer.record_str(row_id, &column_name, val),
+                    ArbitraryValue::Bytes(val) => writer.record_bytes(row_id, &column_name, val),
+                }
+            }
+        }
+        let mut bytes = Vec::new();
+        writer
+            .serialize(self.rows.len() as RowId, &mut bytes)
+            .expect("Writing in a Vec<u8> should never fail");
+        bytes
+    }
+
+    /// Serializes the columnar, and applies the corruptions to it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.to_valid_bytes();
+        corrupt(&mut bytes, &self.corruptions);
+        bytes
+    }
+}
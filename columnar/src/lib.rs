@@ -36,19 +36,24 @@ pub mod column_values;
 mod columnar;
 mod dictionary;
 mod dynamic_column;
+#[cfg(feature = "arbitrary")]
+pub mod fuzzing;
 mod iterable;
 pub(crate) mod utils;
 mod value;
 
 pub use block_accessor::ColumnBlockAccessor;
-pub use column::{BytesColumn, Column, StrColumn};
+pub use column::{
+    cmp_column_values, BytesColumn, Column, HistogramBucket, StrColumn, ValueHistogram,
+};
 pub use column_index::ColumnIndex;
 pub use column_values::{
     ColumnValues, EmptyColumnValues, MonotonicallyMappableToU128, MonotonicallyMappableToU64,
 };
 pub use columnar::{
-    merge_columnar, ColumnType, ColumnarReader, ColumnarWriter, HasAssociatedColumnType,
-    MergeRowOrder, ShuffleMergeOrder, StackMergeOrder, Version, CURRENT_VERSION,
+    merge_columnar, merge_columnar_with_column_mapper, replace_columns, ColumnType, ColumnarReader,
+    ColumnarWriter, HasAssociatedColumnType, MergeRowOrder, ShuffleMergeOrder, StackMergeOrder,
+    Version, CURRENT_VERSION,
 };
 use sstable::VoidSSTable;
 pub use value::{NumericalType, NumericalValue};
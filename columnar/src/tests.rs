@@ -2,9 +2,10 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::net::Ipv6Addr;
 
-use common::DateTime;
+use common::{DateTime, HasLen};
 use proptest::prelude::*;
 use proptest::sample::subsequence;
+use uuid::Uuid;
 
 use crate::column_values::MonotonicallyMappableToU128;
 use crate::columnar::{ColumnType, ColumnTypeCategory};
@@ -26,7 +27,7 @@ fn test_dataframe_writer_str() {
     assert_eq!(columnar.num_columns(), 1);
     let cols: Vec<DynamicColumnHandle> = columnar.read_columns("my_string").unwrap();
     assert_eq!(cols.len(), 1);
-    assert_eq!(cols[0].num_bytes(), 73);
+    assert_eq!(cols[0].num_bytes(), 67);
 }
 
 #[test]
@@ -40,7 +41,7 @@ fn test_dataframe_writer_bytes() {
     assert_eq!(columnar.num_columns(), 1);
     let cols: Vec<DynamicColumnHandle> = columnar.read_columns("my_string").unwrap();
     assert_eq!(cols.len(), 1);
-    assert_eq!(cols[0].num_bytes(), 73);
+    assert_eq!(cols[0].num_bytes(), 67);
 }
 
 #[test]
@@ -54,7 +55,7 @@ fn test_dataframe_writer_bool() {
     assert_eq!(columnar.num_columns(), 1);
     let cols: Vec<DynamicColumnHandle> = columnar.read_columns("bool.value").unwrap();
     assert_eq!(cols.len(), 1);
-    assert_eq!(cols[0].num_bytes(), 22);
+    assert_eq!(cols[0].num_bytes(), 16);
     assert_eq!(cols[0].column_type(), ColumnType::Bool);
     let dyn_bool_col = cols[0].open().unwrap();
     let DynamicColumn::Bool(bool_col) = dyn_bool_col else {
@@ -64,6 +65,42 @@ fn test_dataframe_writer_bool() {
     assert_eq!(&vals, &[None, Some(false), None, Some(true), None,]);
 }
 
+#[test]
+fn test_dynamic_column_handle_open_truncated() {
+    let mut dataframe_writer = ColumnarWriter::default();
+    for row_id in 0u32..200 {
+        dataframe_writer.record_numerical(row_id, "full", u64::from(row_id) * 1_000);
+        if row_id % 3 == 0 {
+            dataframe_writer.record_numerical(row_id, "optional", i64::from(row_id) - 100);
+            dataframe_writer.record_numerical(row_id, "multi", 0.5f64);
+            dataframe_writer.record_numerical(row_id, "multi", 1.5f64);
+        }
+        dataframe_writer.record_ip_addr(row_id, "ip", Ipv6Addr::from_u128(u128::from(row_id % 7)));
+        dataframe_writer.record_bool(row_id, "bool", row_id % 2 == 0);
+        dataframe_writer.record_str(row_id, "str", &format!("term{}", row_id % 11));
+    }
+    let mut buffer: Vec<u8> = Vec::new();
+    dataframe_writer.serialize(200, &mut buffer).unwrap();
+    let columnar = ColumnarReader::open(buffer).unwrap();
+    for (column_name, handle) in columnar.list_columns().unwrap() {
+        assert!(handle.open().is_ok());
+        for len in 0..handle.file_slice().len() {
+            let truncated_handle = DynamicColumnHandle {
+                file_slice: handle.file_slice().slice_to(len),
+                column_type: handle.column_type,
+                format_version: handle.format_version,
+            };
+            // Truncated columns may or may not be detected, but they should never panic.
+            let _ = truncated_handle.open();
+            let _ = truncated_handle.open_u64_lenient();
+            assert!(
+                len > 0 || truncated_handle.open().is_err(),
+                "empty column {column_name} should not open"
+            );
+        }
+    }
+}
+
 #[test]
 fn test_dataframe_writer_u64_multivalued() {
     let mut dataframe_writer = ColumnarWriter::default();
@@ -79,7 +116,7 @@ fn test_dataframe_writer_u64_multivalued() {
     assert_eq!(columnar.num_columns(), 1);
     let cols: Vec<DynamicColumnHandle> = columnar.read_columns("divisor").unwrap();
     assert_eq!(cols.len(), 1);
-    assert_eq!(cols[0].num_bytes(), 50);
+    assert_eq!(cols[0].num_bytes(), 39);
     let dyn_i64_col = cols[0].open().unwrap();
     let DynamicColumn::I64(divisor_col) = dyn_i64_col else {
         panic!();
@@ -102,7 +139,7 @@ fn test_dataframe_writer_ip_addr() {
     assert_eq!(columnar.num_columns(), 1);
     let cols: Vec<DynamicColumnHandle> = columnar.read_columns("ip_addr").unwrap();
     assert_eq!(cols.len(), 1);
-    assert_eq!(cols[0].num_bytes(), 42);
+    assert_eq!(cols[0].num_bytes(), 17);
     assert_eq!(cols[0].column_type(), ColumnType::IpAddr);
     let dyn_bool_col = cols[0].open().unwrap();
     let DynamicColumn::IpAddr(ip_col) = dyn_bool_col else {
@@ -121,6 +158,28 @@ fn test_dataframe_writer_ip_addr() {
     );
 }
 
+#[test]
+fn test_dataframe_writer_uuid() {
+    let mut dataframe_writer = ColumnarWriter::default();
+    let uuid1 = Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+    let uuid2 = Uuid::from_u128(0x936d_a01f_9abd_4d9d_80c7_02af_85c8_22a8);
+    dataframe_writer.record_uuid(1, "id", uuid2);
+    dataframe_writer.record_uuid(3, "id", uuid1);
+    let mut buffer: Vec<u8> = Vec::new();
+    dataframe_writer.serialize(5, &mut buffer).unwrap();
+    let columnar = ColumnarReader::open(buffer).unwrap();
+    let cols: Vec<DynamicColumnHandle> = columnar.read_columns("id").unwrap();
+    assert_eq!(cols.len(), 1);
+    assert_eq!(cols[0].column_type(), ColumnType::Uuid);
+    let DynamicColumn::Uuid(uuid_col) = cols[0].open().unwrap() else {
+        panic!();
+    };
+    let vals: Vec<Option<Uuid>> = (0..5).map(|row_id| uuid_col.first(row_id)).collect();
+    assert_eq!(&vals, &[None, Some(uuid2), None, Some(uuid1), None]);
+    assert_eq!(uuid_col.min_value(), uuid1);
+    assert_eq!(uuid_col.max_value(), uuid2);
+}
+
 #[test]
 fn test_dataframe_writer_numerical() {
     let mut dataframe_writer = ColumnarWriter::default();
@@ -138,7 +197,7 @@ fn test_dataframe_writer_numerical() {
     // - header 14 bytes
     // - vals  8 //< due to padding? could have been 1byte?.
     // - null footer 6 bytes
-    assert_eq!(cols[0].num_bytes(), 33);
+    assert_eq!(cols[0].num_bytes(), 26);
     let column = cols[0].open().unwrap();
     let DynamicColumn::I64(column_i64) = column else {
         panic!();
@@ -153,6 +212,43 @@ fn test_dataframe_writer_numerical() {
     assert_eq!(column_i64.first(6), None); //< we can change the spec for that one.
 }
 
+#[test]
+fn test_dataframe_writer_datetime_delta_of_delta() {
+    // Timestamps of events logged about every second, at a slowly drifting pace.
+    let mut timestamp_secs = 1_700_000_000i64;
+    let timestamps: Vec<DateTime> = (0..10_000i64)
+        .map(|i| {
+            timestamp_secs += 1 + (i / 1_000) % 3;
+            DateTime::from_timestamp_millis(timestamp_secs * 1_000 + (i % 1_000))
+        })
+        .collect();
+    let mut dataframe_writer = ColumnarWriter::default();
+    for (doc, timestamp) in timestamps.iter().enumerate() {
+        dataframe_writer.record_datetime(doc as u32, "date", *timestamp);
+        dataframe_writer.record_numerical(
+            doc as u32,
+            "nanos",
+            NumericalValue::I64(timestamp.into_timestamp_nanos()),
+        );
+    }
+    let mut buffer: Vec<u8> = Vec::new();
+    dataframe_writer
+        .serialize(timestamps.len() as u32, &mut buffer)
+        .unwrap();
+    let columnar = ColumnarReader::open(buffer).unwrap();
+    let date_col = columnar.read_columns("date").unwrap().pop().unwrap();
+    let nanos_col = columnar.read_columns("nanos").unwrap().pop().unwrap();
+    // Unlike the other numerical columns, date columns can use the delta of delta codec.
+    assert!(date_col.num_bytes().get_bytes() * 4 < nanos_col.num_bytes().get_bytes());
+    let DynamicColumn::DateTime(date_column) = date_col.open().unwrap() else {
+        panic!();
+    };
+    for (doc, timestamp) in timestamps.iter().enumerate() {
+        assert_eq!(date_column.first(doc as u32), Some(*timestamp));
+    }
+    assert!(date_column.values.iter().eq(timestamps.iter().copied()));
+}
+
 #[test]
 fn test_dictionary_encoded_str() {
     let mut buffer = Vec::new();
@@ -254,6 +350,7 @@ enum ColumnValue {
     IpAddr(Ipv6Addr),
     Bool(bool),
     DateTime(DateTime),
+    Uuid(Uuid),
 }
 
 impl<T: Into<NumericalValue>> From<T> for ColumnValue {
@@ -271,6 +368,7 @@ impl ColumnValue {
             ColumnValue::IpAddr(_) => ColumnTypeCategory::IpAddr,
             ColumnValue::Bool(_) => ColumnTypeCategory::Bool,
             ColumnValue::DateTime(_) => ColumnTypeCategory::DateTime,
+            ColumnValue::Uuid(_) => ColumnTypeCategory::Uuid,
         }
     }
 }
@@ -305,7 +403,8 @@ fn column_value_strategy() -> impl Strategy<Value = ColumnValue> {
         ))),
         1 => any::<bool>().prop_map(ColumnValue::Bool),
         1 => (679_723_993i64..1_679_723_995i64)
-            .prop_map(|val| { ColumnValue::DateTime(DateTime::from_timestamp_secs(val)) })
+            .prop_map(|val| { ColumnValue::DateTime(DateTime::from_timestamp_secs(val)) }),
+        1 => (1u128..3u128).prop_map(|val| ColumnValue::Uuid(Uuid::from_u128(val)))
     ]
 }
 
@@ -359,6 +458,9 @@ fn build_columnar_with_mapping(docs: &[Vec<(&'static str, ColumnValue)>]) -> Col
                 ColumnValue::DateTime(date_time) => {
                     columnar_writer.record_datetime(doc_id as u32, column_name, date_time);
                 }
+                ColumnValue::Uuid(uuid) => {
+                    columnar_writer.record_uuid(doc_id as u32, column_name, uuid);
+                }
             }
         }
     }
@@ -461,6 +563,9 @@ fn assert_dyn_column_eq(
         (DynamicColumn::IpAddr(left_col), DynamicColumn::IpAddr(right_col)) => {
             assert_column_eq(left_col, right_col);
         }
+        (DynamicColumn::Uuid(left_col), DynamicColumn::Uuid(right_col)) => {
+            assert_column_eq(left_col, right_col);
+        }
         (DynamicColumn::Bytes(left_col), DynamicColumn::Bytes(right_col)) => {
             assert_bytes_column_eq(left_col, right_col);
         }
@@ -506,6 +611,15 @@ impl AssertEqualToColumnValue for Ipv6Addr {
     }
 }
 
+impl AssertEqualToColumnValue for Uuid {
+    fn assert_equal_to_column_value(&self, column_value: &ColumnValue) {
+        let ColumnValue::Uuid(val) = column_value else {
+            panic!()
+        };
+        assert_eq!(self, val);
+    }
+}
+
 impl<T: Coerce + PartialEq + Debug + Into<NumericalValue>> AssertEqualToColumnValue for T {
     fn assert_equal_to_column_value(&self, column_value: &ColumnValue) {
         let ColumnValue::Numerical(num) = column_value else {
@@ -619,6 +733,8 @@ proptest! {
                     assert_column_values(col, expected_col_values),
                 DynamicColumn::DateTime(col) =>
                     assert_column_values(col, expected_col_values),
+                DynamicColumn::Uuid(col) =>
+                    assert_column_values(col, expected_col_values),
                 DynamicColumn::Bytes(col) =>
                     assert_bytes_column_values(col, expected_col_values, false),
                 DynamicColumn::Str(col) =>
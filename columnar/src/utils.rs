@@ -1,3 +1,7 @@
+use std::io;
+
+use common::OwnedBytes;
+
 const fn compute_mask(num_bits: u8) -> u8 {
     if num_bits == 8 {
         u8::MAX
@@ -27,6 +31,37 @@ pub(crate) fn place_bits<const START: u8, const END: u8>(code: u8) -> u8 {
     code << START
 }
 
+/// Splits `bytes` at `split_len`, returning an error if `bytes` is too short.
+///
+/// Lengths are read from the columnar file itself, and may be invalid if it is corrupted.
+pub(crate) fn try_split(
+    bytes: OwnedBytes,
+    split_len: usize,
+) -> io::Result<(OwnedBytes, OwnedBytes)> {
+    if split_len > bytes.len() {
+        return Err(truncated_data_error(split_len, bytes.len()));
+    }
+    Ok(bytes.split(split_len))
+}
+
+/// Splits the last `split_len` bytes off `bytes`, returning an error if `bytes` is too short.
+pub(crate) fn try_rsplit(
+    bytes: OwnedBytes,
+    split_len: usize,
+) -> io::Result<(OwnedBytes, OwnedBytes)> {
+    if split_len > bytes.len() {
+        return Err(truncated_data_error(split_len, bytes.len()));
+    }
+    Ok(bytes.rsplit(split_len))
+}
+
+fn truncated_data_error(expected_len: usize, len: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Column data is truncated: expected at least {expected_len} bytes, got {len}."),
+    )
+}
+
 /// Pop-front one bytes from a slice of bytes.
 #[inline(always)]
 pub fn pop_first_byte(bytes: &mut &[u8]) -> Option<u8> {
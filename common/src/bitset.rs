@@ -239,6 +239,32 @@ impl BitSet {
         self.intersect_update_with_iter(other.iter_tinysets());
     }
 
+    /// Intersect with a bitset of the same max value.
+    pub fn intersect_update_with_bitset(&mut self, other: &BitSet) {
+        assert_eq!(self.max_value, other.max_value);
+        self.intersect_update_with_iter(other.tinysets.iter().cloned());
+    }
+
+    /// Union with a bitset of the same max value.
+    pub fn union_update(&mut self, other: &BitSet) {
+        assert_eq!(self.max_value, other.max_value);
+        self.len = 0;
+        for (left, right) in self.tinysets.iter_mut().zip(other.tinysets.iter().cloned()) {
+            *left = left.union(right);
+            self.len += left.len() as u64;
+        }
+    }
+
+    /// Removes the elements of a bitset of the same max value.
+    pub fn difference_update(&mut self, other: &BitSet) {
+        assert_eq!(self.max_value, other.max_value);
+        self.len = 0;
+        for (left, right) in self.tinysets.iter_mut().zip(other.tinysets.iter().cloned()) {
+            *left = left.intersect(TinySet(!right.0));
+            self.len += left.len() as u64;
+        }
+    }
+
     /// Intersect with tinysets
     fn intersect_update_with_iter(&mut self, other: impl Iterator<Item = TinySet>) {
         self.len = 0;
@@ -487,6 +513,39 @@ mod tests {
         assert_eq!(bitset.len(), 0);
     }
 
+    #[test]
+    fn test_bitset_combinations() {
+        let bitset_from = |els: &[u32]| {
+            let mut bitset = BitSet::with_max_value(130);
+            for &el in els {
+                bitset.insert(el);
+            }
+            bitset
+        };
+        let elements = |bitset: &BitSet| {
+            (0..130)
+                .filter(|&el| bitset.contains(el))
+                .collect::<Vec<u32>>()
+        };
+        let left = bitset_from(&[1, 64, 65, 129]);
+        let right = bitset_from(&[2, 65, 129]);
+
+        let mut union = left.clone();
+        union.union_update(&right);
+        assert_eq!(elements(&union), vec![1, 2, 64, 65, 129]);
+        assert_eq!(union.len(), 5);
+
+        let mut intersection = left.clone();
+        intersection.intersect_update_with_bitset(&right);
+        assert_eq!(elements(&intersection), vec![65, 129]);
+        assert_eq!(intersection.len(), 2);
+
+        let mut difference = left.clone();
+        difference.difference_update(&right);
+        assert_eq!(elements(&difference), vec![1, 64]);
+        assert_eq!(difference.len(), 2);
+    }
+
     #[test]
     fn test_read_serialized_bitset_empty() {
         let mut bitset = BitSet::with_max_value(5);
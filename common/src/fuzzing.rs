@@ -0,0 +1,168 @@
+//! Generators of corrupted files, used to fuzz the readers of tantivy's binary formats.
+//!
+//! Fuzz targets typically serialize a valid file out of an arbitrary input, and then
+//! apply a list of arbitrary [`Corruption`]s to it, so that the fuzzer explores files
+//! that are almost valid rather than random bytes.
+//!
+//! This module is only available with the `arbitrary` feature.
+
+use arbitrary::Arbitrary;
+
+/// A position in a file, relative to its start or to its end.
+///
+/// Positions past the end of the file wrap around.
+#[derive(Arbitrary, Clone, Copy, Debug)]
+pub struct Position {
+    /// The offset of the position.
+    pub offset: u32,
+    /// If true, the offset is counted backwards from the end of the file.
+    ///
+    /// Footers usually hold the most sensitive part of a file.
+    pub from_end: bool,
+}
+
+impl Position {
+    /// Resolves the position in a file of `len` bytes.
+    ///
+    /// `len` needs to be strictly positive.
+    fn resolve(self, len: usize) -> usize {
+        let offset = self.offset as usize % len;
+        if self.from_end {
+            len - 1 - offset
+        } else {
+            offset
+        }
+    }
+}
+
+/// A corruption applied to a file.
+#[derive(Arbitrary, Clone, Debug)]
+pub enum Corruption {
+    /// Overwrites a byte.
+    SetByte {
+        /// Position of the byte.
+        pos: Position,
+        /// New value of the byte.
+        byte: u8,
+    },
+    /// Flips some of the bits of a byte.
+    FlipBits {
+        /// Position of the byte.
+        pos: Position,
+        /// Mask of the bits to flip.
+        mask: u8,
+    },
+    /// Inserts bytes.
+    Insert {
+        /// Position of the insertion.
+        pos: Position,
+        /// Inserted bytes.
+        bytes: Vec<u8>,
+    },
+    /// Removes bytes.
+    Remove {
+        /// Position of the first removed byte.
+        pos: Position,
+        /// Number of removed bytes.
+        num_bytes: u16,
+    },
+    /// Truncates the file.
+    Truncate {
+        /// Number of bytes removed from the end of the file.
+        num_bytes: u16,
+    },
+}
+
+impl Corruption {
+    /// Applies the corruption to the given file.
+    pub fn apply(&self, data: &mut Vec<u8>) {
+        if data.is_empty() {
+            if let Corruption::Insert { bytes, .. } = self {
+                data.extend_from_slice(bytes);
+            }
+            return;
+        }
+        match self {
+            Corruption::SetByte { pos, byte } => {
+                let pos = pos.resolve(data.len());
+                data[pos] = *byte;
+            }
+            Corruption::FlipBits { pos, mask } => {
+                let pos = pos.resolve(data.len());
+                data[pos] ^= *mask;
+            }
+            Corruption::Insert { pos, bytes } => {
+                let pos = pos.resolve(data.len());
+                data.splice(pos..pos, bytes.iter().copied());
+            }
+            Corruption::Remove { pos, num_bytes } => {
+                let start = pos.resolve(data.len());
+                let end = (start + *num_bytes as usize).min(data.len());
+                data.drain(start..end);
+            }
+            Corruption::Truncate { num_bytes } => {
+                data.truncate(data.len().saturating_sub(*num_bytes as usize));
+            }
+        }
+    }
+}
+
+/// Applies the corruptions to the given file, in order.
+pub fn corrupt(data: &mut Vec<u8>, corruptions: &[Corruption]) {
+    for corruption in corruptions {
+        corruption.apply(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{corrupt, Corruption, Position};
+
+    fn pos(offset: u32, from_end: bool) -> Position {
+        Position { offset, from_end }
+    }
+
+    #[test]
+    fn test_corrupt() {
+        let mut data = b"abcdef".to_vec();
+        corrupt(
+            &mut data,
+            &[
+                Corruption::SetByte {
+                    pos: pos(0, true),
+                    byte: b'z',
+                },
+                Corruption::FlipBits {
+                    pos: pos(6, false),
+                    mask: 1,
+                },
+                Corruption::Insert {
+                    pos: pos(1, false),
+                    bytes: b"xy".to_vec(),
+                },
+                Corruption::Remove {
+                    pos: pos(3, false),
+                    num_bytes: 2,
+                },
+                Corruption::Truncate { num_bytes: 1 },
+            ],
+        );
+        assert_eq!(&data, b"`xyde");
+        corrupt(&mut data, &[Corruption::Truncate { num_bytes: 100 }]);
+        assert!(data.is_empty());
+        corrupt(
+            &mut data,
+            &[
+                Corruption::SetByte {
+                    pos: pos(0, false),
+                    byte: 1,
+                },
+                Corruption::Insert {
+                    pos: pos(3, true),
+                    bytes: vec![2],
+                },
+            ],
+        );
+        assert_eq!(data, vec![2]);
+    }
+}
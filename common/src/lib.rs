@@ -9,6 +9,8 @@ pub mod bounds;
 mod byte_count;
 mod datetime;
 pub mod file_slice;
+#[cfg(feature = "arbitrary")]
+pub mod fuzzing;
 mod group_by;
 pub mod json_path_writer;
 mod serialize;
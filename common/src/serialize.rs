@@ -6,6 +6,18 @@ use byteorder::{ReadBytesExt, WriteBytesExt};
 
 use crate::{Endianness, VInt};
 
+/// Maximum number of bytes preallocated when deserializing a collection.
+///
+/// The length of a collection is read from the data, so a corrupted length could otherwise
+/// trigger a huge allocation. Larger collections are still deserialized, growing as usual.
+const MAX_PREALLOCATION_NUM_BYTES: u64 = 1 << 16;
+
+/// Returns the capacity to preallocate for a collection of `num_items` elements of type `T`.
+fn cautious_capacity<T>(num_items: u64) -> usize {
+    let max_num_items = MAX_PREALLOCATION_NUM_BYTES / std::mem::size_of::<T>().max(1) as u64;
+    num_items.min(max_num_items) as usize
+}
+
 #[derive(Default)]
 struct Counter(u64);
 
@@ -82,7 +94,7 @@ impl<T: BinarySerializable> BinarySerializable for Vec<T> {
     }
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Vec<T>> {
         let num_items = <VInt as BinarySerializable>::deserialize(reader)?.val();
-        let mut items: Vec<T> = Vec::with_capacity(num_items as usize);
+        let mut items: Vec<T> = Vec::with_capacity(cautious_capacity::<T>(num_items));
         for _ in 0..num_items {
             let item = T::deserialize(reader)?;
             items.push(item);
@@ -241,11 +253,9 @@ impl BinarySerializable for String {
     }
 
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<String> {
-        let string_length = <VInt as BinarySerializable>::deserialize(reader)?.val() as usize;
-        let mut result = String::with_capacity(string_length);
-        reader
-            .take(string_length as u64)
-            .read_to_string(&mut result)?;
+        let string_length = <VInt as BinarySerializable>::deserialize(reader)?.val();
+        let mut result = String::with_capacity(cautious_capacity::<u8>(string_length));
+        reader.take(string_length).read_to_string(&mut result)?;
         Ok(result)
     }
 }
@@ -258,11 +268,9 @@ impl<'a> BinarySerializable for Cow<'a, str> {
     }
 
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Cow<'a, str>> {
-        let string_length = <VInt as BinarySerializable>::deserialize(reader)?.val() as usize;
-        let mut result = String::with_capacity(string_length);
-        reader
-            .take(string_length as u64)
-            .read_to_string(&mut result)?;
+        let string_length = <VInt as BinarySerializable>::deserialize(reader)?.val();
+        let mut result = String::with_capacity(cautious_capacity::<u8>(string_length));
+        reader.take(string_length).read_to_string(&mut result)?;
         Ok(Cow::Owned(result))
     }
 }
@@ -278,7 +286,7 @@ impl<'a> BinarySerializable for Cow<'a, [u8]> {
 
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Cow<'a, [u8]>> {
         let num_items = <VInt as BinarySerializable>::deserialize(reader)?.val();
-        let mut items: Vec<u8> = Vec::with_capacity(num_items as usize);
+        let mut items: Vec<u8> = Vec::with_capacity(cautious_capacity::<u8>(num_items));
         for _ in 0..num_items {
             let item = <u8 as BinarySerializable>::deserialize(reader)?;
             items.push(item);
@@ -353,6 +361,15 @@ pub mod test {
         assert_eq!(serialize_test(vec![1u32, 3u32]), 1 + 4 * 2);
     }
 
+    #[test]
+    fn test_deserialize_corrupted_len() {
+        let mut buffer = Vec::new();
+        VInt(u64::MAX).serialize(&mut buffer).unwrap();
+        buffer.extend_from_slice(&[1u8, 2u8]);
+        assert!(Vec::<u32>::deserialize(&mut &buffer[..]).is_err());
+        assert_eq!(String::deserialize(&mut &buffer[..]).unwrap(), "\u{1}\u{2}");
+    }
+
     #[test]
     fn test_serialize_vint() {
         for i in 0..10_000 {
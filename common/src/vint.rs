@@ -35,6 +35,12 @@ impl BinarySerializable for VIntU128 {
         loop {
             match bytes.next() {
                 Some(Ok(b)) => {
+                    if shift >= 128 {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "VInt is too long to fit in a u128",
+                        ));
+                    }
                     result |= u128::from(b % 128u8) << shift;
                     if b >= STOP_BIT {
                         return Ok(VIntU128(result));
@@ -202,6 +208,12 @@ impl BinarySerializable for VInt {
         loop {
             match bytes.next() {
                 Some(Ok(b)) => {
+                    if shift >= 64 {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "VInt is too long to fit in a u64",
+                        ));
+                    }
                     result |= u64::from(b % 128u8) << shift;
                     if b >= STOP_BIT {
                         return Ok(VInt(result));
@@ -256,6 +268,11 @@ mod tests {
         aux_test_vint(10);
     }
 
+    #[test]
+    fn test_vint_too_long() {
+        assert!(VInt::deserialize(&mut &[0u8; 11][..]).is_err());
+    }
+
     fn aux_test_serialize_vint_u32(val: u32) {
         let mut buffer = [0u8; 10];
         let mut buffer2 = [0u8; 8];
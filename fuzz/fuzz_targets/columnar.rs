@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tantivy_columnar::fuzzing::ArbitraryColumnar;
+use tantivy_columnar::ColumnarReader;
+
+fuzz_target!(|columnar: ArbitraryColumnar| {
+    let bytes = columnar.to_bytes();
+    let Ok(columnar_reader) = ColumnarReader::open_untrusted(bytes) else {
+        return;
+    };
+    let Ok(columns) = columnar_reader.list_columns() else {
+        return;
+    };
+    for (_column_name, column_handle) in columns {
+        let _ = column_handle.open();
+        if let Ok(Some(column)) = column_handle.open_u64_lenient() {
+            for row_id in 0..columnar_reader.num_docs().min(1_000) {
+                column.values_for_doc(row_id).for_each(drop);
+            }
+        }
+    }
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tantivy::directory::FileSlice;
+use tantivy::fuzzing::ArbitraryDocStore;
+use tantivy::store::StoreReader;
+use tantivy::TantivyDocument;
+
+fuzz_target!(|doc_store: ArbitraryDocStore| {
+    let bytes = doc_store.to_bytes();
+    let Ok(store_reader) = StoreReader::open_untrusted(FileSlice::from(bytes), 1) else {
+        return;
+    };
+    for doc in store_reader.iter::<TantivyDocument>(None) {
+        let _ = doc;
+    }
+    for doc_id in 0..doc_store.docs.len() as u32 + 1 {
+        let _ = store_reader.get::<TantivyDocument>(doc_id);
+    }
+});
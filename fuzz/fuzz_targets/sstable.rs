@@ -0,0 +1,27 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tantivy_common::file_slice::FileSlice;
+use tantivy_sstable::fuzzing::ArbitrarySSTable;
+use tantivy_sstable::{Dictionary, MonotonicU64SSTable};
+
+fuzz_target!(|sstable: ArbitrarySSTable| {
+    let bytes = sstable.to_bytes();
+    let Ok(dictionary) = Dictionary::<MonotonicU64SSTable>::open_untrusted(FileSlice::from(bytes))
+    else {
+        return;
+    };
+    if let Ok(mut stream) = dictionary.stream() {
+        while stream.advance() {}
+    }
+    let mut term = Vec::new();
+    for ord in 0..dictionary.num_terms() as u64 + 1 {
+        let _ = dictionary.ord_to_term(ord, &mut term);
+        let _ = dictionary.term_info_from_ord(ord);
+    }
+    for key in sstable.entries.keys() {
+        let _ = dictionary.get(key);
+        let _ = dictionary.term_ord(key);
+        let _ = dictionary.term_ord_or_next(key);
+    }
+});
@@ -7,7 +7,7 @@ mod user_input_ast;
 
 pub use crate::infallible::LenientError;
 pub use crate::occur::Occur;
-use crate::query_grammar::{parse_to_ast, parse_to_ast_lenient};
+use crate::query_grammar::{parse_to_ast, parse_to_ast_lenient, with_extension_names};
 pub use crate::user_input_ast::{
     Delimiter, UserInputAst, UserInputBound, UserInputLeaf, UserInputLiteral,
 };
@@ -24,3 +24,24 @@ pub fn parse_query(query: &str) -> Result<UserInputAst, Error> {
 pub fn parse_query_lenient(query: &str) -> (UserInputAst, Vec<LenientError>) {
     parse_to_ast_lenient(query)
 }
+
+/// Parse a query, recognizing the clauses `name:(...)` of the given syntax extensions.
+///
+/// The text within the parentheses of such a clause is not parsed, but kept as is in a
+/// [`UserInputLeaf::Extension`]. It may contain nested parentheses as long as they are balanced.
+pub fn parse_query_with_extensions(
+    query: &str,
+    extension_names: &[String],
+) -> Result<UserInputAst, Error> {
+    with_extension_names(extension_names, || parse_query(query))
+}
+
+/// Parse a query leniently, recognizing the clauses `name:(...)` of the given syntax extensions.
+///
+/// See [`parse_query_with_extensions`] and [`parse_query_lenient`].
+pub fn parse_query_lenient_with_extensions(
+    query: &str,
+    extension_names: &[String],
+) -> (UserInputAst, Vec<LenientError>) {
+    with_extension_names(extension_names, || parse_query_lenient(query))
+}
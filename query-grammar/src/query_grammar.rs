@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::iter::once;
 
 use nom::branch::alt;
@@ -223,10 +224,46 @@ fn simple_term_infallible(
     }
 }
 
+/// Splits the fuzzy operator out of an unquoted word, e.g. `wolf~1` into `wolf` and `1`.
+///
+/// `~` is a valid character within words, the operator is only recognized if it is not escaped,
+/// and is followed by digits only.
+fn split_fuzzy_distance(word: String) -> (String, u32) {
+    let Some(tilde_pos) = word.rfind('~') else {
+        return (word, 0);
+    };
+    let (text, distance) = (&word[..tilde_pos], &word[tilde_pos + 1..]);
+    if text.is_empty() || text.ends_with('\\') {
+        return (word, 0);
+    }
+    if !distance.bytes().all(|b| b.is_ascii_digit()) {
+        return (word, 0);
+    }
+    match distance.parse::<u32>() {
+        Ok(distance) => (text.to_string(), distance),
+        Err(_) => (word, 0),
+    }
+}
+
+/// Applies [`split_fuzzy_distance`] to unquoted words with no explicit slop or prefix.
+fn with_fuzzy_distance(
+    delimiter: Delimiter,
+    phrase: String,
+    slop: u32,
+    prefix: bool,
+) -> (String, u32) {
+    if delimiter == Delimiter::None && slop == 0 && !prefix {
+        split_fuzzy_distance(phrase)
+    } else {
+        (phrase, slop)
+    }
+}
+
 fn term_or_phrase(inp: &str) -> IResult<&str, UserInputLeaf> {
     map(
         tuple((simple_term, fallible(slop_or_prefix_val))),
         |((delimiter, phrase), (slop, prefix))| {
+            let (phrase, slop) = with_fuzzy_distance(delimiter, phrase, slop, prefix);
             UserInputLiteral {
                 field_name: None,
                 phrase,
@@ -245,6 +282,7 @@ fn term_or_phrase_infallible(inp: &str) -> JResult<&str, Option<UserInputLeaf>>
         tuple_infallible((simple_term_infallible(")^"), slop_or_prefix_val)),
         |((delimiter_phrase, (slop, prefix)), errors)| {
             let leaf = if let Some((delimiter, phrase)) = delimiter_phrase {
+                let (phrase, slop) = with_fuzzy_distance(delimiter, phrase, slop, prefix);
                 Some(
                     UserInputLiteral {
                         field_name: None,
@@ -316,6 +354,106 @@ fn term_group_infallible(inp: &str) -> JResult<&str, UserInputAst> {
     res
 }
 
+thread_local! {
+    /// Names of the syntax extensions recognized by the parser running on this thread.
+    static EXTENSION_NAMES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Restores the extension names in use before the current parse on drop.
+struct ExtensionNamesGuard(Vec<String>);
+
+impl Drop for ExtensionNamesGuard {
+    fn drop(&mut self) {
+        let previous = std::mem::take(&mut self.0);
+        EXTENSION_NAMES.with(|names| *names.borrow_mut() = previous);
+    }
+}
+
+/// Runs `parse` with the given syntax extensions recognized.
+pub(crate) fn with_extension_names<R>(extension_names: &[String], parse: impl FnOnce() -> R) -> R {
+    let previous = EXTENSION_NAMES.with(|names| names.replace(extension_names.to_vec()));
+    let _guard = ExtensionNamesGuard(previous);
+    parse()
+}
+
+/// consume the name of a registered syntax extension followed by colon.
+fn extension_name(inp: &str) -> IResult<&str, String> {
+    verify(field_name, |name: &str| {
+        EXTENSION_NAMES.with(|names| names.borrow().iter().any(|extension| extension == name))
+    })(inp)
+}
+
+/// consume the text of an extension clause, up to the parenthesis closing it. Parentheses must
+/// be balanced, except within double quotes or when escaped.
+fn extension_content(inp: &str) -> IResult<&str, &str> {
+    let mut depth = 0usize;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (pos, c) in inp.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '(' if !in_quotes => depth += 1,
+            ')' if !in_quotes => {
+                if depth == 0 {
+                    return Ok((&inp[pos..], &inp[..pos]));
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+    }
+    Err(nom::Err::Error(Error::new(inp, ErrorKind::Char)))
+}
+
+fn extension(inp: &str) -> IResult<&str, UserInputAst> {
+    map(
+        tuple((
+            extension_name,
+            multispace0,
+            char('('),
+            extension_content,
+            char(')'),
+        )),
+        |(name, _, _, content, _)| {
+            UserInputLeaf::Extension {
+                name,
+                content: content.trim().to_string(),
+            }
+            .into()
+        },
+    )(inp)
+}
+
+// this is a precondition for extension_infallible. It does not consume its input.
+fn extension_precond(inp: &str) -> IResult<&str, (), ()> {
+    value((), peek(tuple((extension_name, multispace0, char('(')))))(inp).map_err(|e| e.map(|_| ()))
+}
+
+fn extension_infallible(inp: &str) -> JResult<&str, UserInputAst> {
+    let (inp, (name, _, _)) =
+        tuple((extension_name, multispace0, char('(')))(inp).expect("precondition failed");
+    let (inp, content, errors) = match extension_content(inp) {
+        Ok((inp, content)) => (&inp[1..], content, Vec::new()),
+        Err(_) => {
+            let errors = vec![LenientErrorInternal {
+                pos: 0,
+                message: "expected ')'".to_string(),
+            }];
+            ("", inp, errors)
+        }
+    };
+    let extension = UserInputLeaf::Extension {
+        name,
+        content: content.trim().to_string(),
+    };
+    Ok((inp, (extension.into(), errors)))
+}
+
 fn exists(inp: &str) -> IResult<&str, UserInputLeaf> {
     value(
         UserInputLeaf::Exists {
@@ -366,6 +504,7 @@ fn literal(inp: &str) -> IResult<&str, UserInputAst> {
     // * alone is already parsed by our caller, so if `exists` succeed, we can be confident
     // something (a field name) got parsed before
     alt((
+        extension,
         map(
             tuple((opt(field_name), alt((range, set, exists, term_or_phrase)))),
             |(field_name, leaf): (Option<String>, UserInputLeaf)| leaf.set_field(field_name).into(),
@@ -416,6 +555,12 @@ fn literal_no_group_infallible(inp: &str) -> JResult<&str, Option<UserInputAst>>
 fn literal_infallible(inp: &str) -> JResult<&str, Option<UserInputAst>> {
     alt_infallible(
         (
+            (
+                extension_precond,
+                map(extension_infallible, |(extension, errs)| {
+                    (Some(extension), errs)
+                }),
+            ),
             (
                 term_group_precond,
                 map(term_group_infallible, |(group, errs)| (Some(group), errs)),
@@ -744,31 +889,55 @@ fn positive_float_number(inp: &str) -> IResult<&str, f64> {
     )(inp)
 }
 
-fn boost(inp: &str) -> JResult<&str, Option<f64>> {
-    opt_i(preceded(char('^'), positive_float_number))(inp)
+/// Modifier of the score of a leaf: `^2.5` boosts its score, `^=2.5` replaces its score by a
+/// constant.
+#[derive(Clone, Copy)]
+enum ScoreModifier {
+    Boost(f64),
+    ConstScore(f64),
 }
 
-fn boosted_leaf(inp: &str) -> IResult<&str, UserInputAst> {
-    map(
-        tuple((leaf, fallible(boost))),
-        |(leaf, boost_opt)| match boost_opt {
-            Some(boost) if (boost - 1.0).abs() > f64::EPSILON => {
+impl ScoreModifier {
+    fn apply(self, leaf: UserInputAst) -> UserInputAst {
+        match self {
+            ScoreModifier::Boost(boost) if (boost - 1.0).abs() > f64::EPSILON => {
                 UserInputAst::Boost(Box::new(leaf), boost)
             }
-            _ => leaf,
+            ScoreModifier::Boost(_) => leaf,
+            ScoreModifier::ConstScore(score) => UserInputAst::ConstScore(Box::new(leaf), score),
+        }
+    }
+}
+
+fn score_modifier(inp: &str) -> JResult<&str, Option<ScoreModifier>> {
+    opt_i(alt((
+        map(
+            preceded(tag("^="), positive_float_number),
+            ScoreModifier::ConstScore,
+        ),
+        map(
+            preceded(char('^'), positive_float_number),
+            ScoreModifier::Boost,
+        ),
+    )))(inp)
+}
+
+fn boosted_leaf(inp: &str) -> IResult<&str, UserInputAst> {
+    map(
+        tuple((leaf, fallible(score_modifier))),
+        |(leaf, modifier_opt)| match modifier_opt {
+            Some(modifier) => modifier.apply(leaf),
+            None => leaf,
         },
     )(inp)
 }
 
 fn boosted_leaf_infallible(inp: &str) -> JResult<&str, Option<UserInputAst>> {
     map(
-        tuple_infallible((leaf_infallible, boost)),
-        |((leaf, boost_opt), error)| match boost_opt {
-            Some(boost) if (boost - 1.0).abs() > f64::EPSILON => (
-                leaf.map(|leaf| UserInputAst::Boost(Box::new(leaf), boost)),
-                error,
-            ),
-            _ => (leaf, error),
+        tuple_infallible((leaf_infallible, score_modifier)),
+        |((leaf, modifier_opt), error)| match modifier_opt {
+            Some(modifier) => (leaf.map(|leaf| modifier.apply(leaf)), error),
+            None => (leaf, error),
         },
     )(inp)
 }
@@ -1161,6 +1330,17 @@ mod test {
         test_parse_query_to_ast_helper("a^3", "(a)^3");
         test_parse_query_to_ast_helper("a^3 b^2", "(*(a)^3 *(b)^2)");
         test_parse_query_to_ast_helper("a^1", "a");
+        test_parse_query_to_ast_helper("title:a^2.5", "(\"title\":a)^2.5");
+        test_parse_query_to_ast_helper("(a b)^2", "((*a *b))^2");
+    }
+
+    #[test]
+    fn test_const_score() {
+        test_is_parse_err("a^=", "a");
+        test_parse_query_to_ast_helper("a^=3", "(a)^=3");
+        test_parse_query_to_ast_helper("a^=1", "(a)^=1");
+        test_parse_query_to_ast_helper("a^=0.5 b^2", "(*(a)^=0.5 *(b)^2)");
+        test_parse_query_to_ast_helper("(a b)^=2", "((*a *b))^=2");
     }
 
     #[test]
@@ -1514,6 +1694,24 @@ mod test {
         test_is_parse_err(r#"field:(+a -"b c""#, r#"(+"field":a -"field":"b c")"#);
     }
 
+    #[test]
+    fn test_parse_query_extension() {
+        let extension_names = vec!["geo".to_string(), "has_child".to_string()];
+        with_extension_names(&extension_names, || {
+            test_parse_query_to_ast_helper(
+                r#"geo:( 48.85, 2.35 (10km) )"#,
+                r#"$extension("geo", "48.85, 2.35 (10km)")"#,
+            );
+            test_parse_query_to_ast_helper(
+                r#"+title:a has_child: (kind:"a)" AND \) b)"#,
+                r#"(+"title":a *$extension("has_child", "kind:"a)" AND \) b"))"#,
+            );
+            test_parse_query_to_ast_helper(r#"field:(abc)"#, r#""field":abc"#);
+            test_is_parse_err(r#"a geo:(1 (2)"#, r#"(*a *$extension("geo", "1 (2)"))"#);
+        });
+        test_parse_query_to_ast_helper(r#"geo:(abc)"#, r#""geo":abc"#);
+    }
+
     #[test]
     fn field_re_specification() {
         test_parse_query_to_ast_helper(r#"field:(abc AND b:cde)"#, r#"(+"field":abc +"b":cde)"#);
@@ -1629,6 +1827,28 @@ mod test {
         test_parse_query_to_ast_helper("\"a b\"~300^2", "(\"a b\"~300)^2");
     }
 
+    #[test]
+    fn test_fuzzy_distance() {
+        let parse_literal = |query: &str| {
+            let UserInputAst::Leaf(leaf) = parse_to_ast(query).unwrap().1 else {
+                panic!("expected a leaf");
+            };
+            let UserInputLeaf::Literal(literal) = *leaf else {
+                panic!("expected a literal");
+            };
+            (literal.phrase, literal.slop)
+        };
+        assert_eq!(parse_literal("wolf~1"), ("wolf".to_string(), 1));
+        assert_eq!(parse_literal("title:wolf~2"), ("wolf".to_string(), 2));
+        assert_eq!(parse_literal("wolf~"), ("wolf~".to_string(), 0));
+        assert_eq!(parse_literal("wolf~a"), ("wolf~a".to_string(), 0));
+        assert_eq!(parse_literal("~1"), ("~1".to_string(), 0));
+        assert_eq!(parse_literal(r"wolf\~1"), (r"wolf\~1".to_string(), 0));
+        assert_eq!(parse_literal("a~b~1"), ("a~b".to_string(), 1));
+        test_parse_query_to_ast_helper("wolf~1^2", "(wolf~1)^2");
+        test_parse_query_to_ast_helper("title:wolf~1", "\"title\":wolf~1");
+    }
+
     #[test]
     fn test_phrase_prefix() {
         test_parse_query_to_ast_helper("\"a b\"*", "\"a b\"*");
@@ -1649,6 +1869,9 @@ mod test {
             "(?(+hello +$exists(\"toto\")) ?happy)",
         );
         test_parse_query_to_ast_helper("(a:*)", "$exists(\"a\")");
+        test_parse_query_to_ast_helper("_exists_:a", "$exists(\"a\")");
+        test_parse_query_to_ast_helper("_exists_:a.b", "$exists(\"a.b\")");
+        test_parse_query_to_ast_helper("hello -_exists_:toto", "(*hello -$exists(\"toto\"))");
 
         // these are term/wildcard query (not a phrase prefix)
         test_parse_query_to_ast_helper("a:b*", "\"a\":b*");
@@ -19,11 +19,25 @@ pub enum UserInputLeaf {
     Exists {
         field: String,
     },
+    /// Clause `name:(content)` of a syntax extension. The content is kept as is, and
+    /// interpreted by the extension.
+    Extension {
+        name: String,
+        content: String,
+    },
 }
 
+/// Pseudo field name of the `_exists_:field` syntax, which is equivalent to `field:*`.
+const EXISTS_FIELD_NAME: &str = "_exists_";
+
 impl UserInputLeaf {
     pub(crate) fn set_field(self, field: Option<String>) -> Self {
         match self {
+            UserInputLeaf::Literal(literal) if field.as_deref() == Some(EXISTS_FIELD_NAME) => {
+                UserInputLeaf::Exists {
+                    field: literal.phrase,
+                }
+            }
             UserInputLeaf::Literal(mut literal) => {
                 literal.field_name = field;
                 UserInputLeaf::Literal(literal)
@@ -42,6 +56,9 @@ impl UserInputLeaf {
             UserInputLeaf::Exists { field: _ } => UserInputLeaf::Exists {
                 field: field.expect("Exist query without a field isn't allowed"),
             },
+            UserInputLeaf::Extension { name, content } => {
+                UserInputLeaf::Extension { name, content }
+            }
         }
     }
 
@@ -103,6 +120,9 @@ impl Debug for UserInputLeaf {
             UserInputLeaf::Exists { field } => {
                 write!(formatter, "$exists(\"{field}\")")
             }
+            UserInputLeaf::Extension { name, content } => {
+                write!(formatter, "$extension(\"{name}\", \"{content}\")")
+            }
         }
     }
 }
@@ -192,6 +212,8 @@ pub enum UserInputAst {
     Clause(Vec<(Option<Occur>, UserInputAst)>),
     Leaf(Box<UserInputLeaf>),
     Boost(Box<UserInputAst>, f64),
+    /// Replaces the score of the documents matching the inner ast by a constant.
+    ConstScore(Box<UserInputAst>, f64),
 }
 
 impl UserInputAst {
@@ -232,7 +254,9 @@ impl UserInputAst {
                 .iter_mut()
                 .for_each(|(_, ast)| ast.set_default_field(field.clone())),
             UserInputAst::Leaf(leaf) => leaf.set_default_field(field),
-            UserInputAst::Boost(ref mut ast, _) => ast.set_default_field(field),
+            UserInputAst::Boost(ref mut ast, _) | UserInputAst::ConstScore(ref mut ast, _) => {
+                ast.set_default_field(field)
+            }
         }
     }
 }
@@ -282,6 +306,7 @@ impl fmt::Debug for UserInputAst {
             }
             UserInputAst::Leaf(ref subquery) => write!(formatter, "{subquery:?}"),
             UserInputAst::Boost(ref leaf, boost) => write!(formatter, "({leaf:?})^{boost}"),
+            UserInputAst::ConstScore(ref leaf, score) => write!(formatter, "({leaf:?})^={score}"),
         }
     }
 }
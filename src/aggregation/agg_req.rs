@@ -26,12 +26,14 @@
 //! let _agg_req: Aggregations = serde_json::from_str(elasticsearch_compatible_json_req).unwrap();
 //! ```
 
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 
 use serde::{Deserialize, Serialize};
 
 use super::bucket::{
-    DateHistogramAggregationReq, HistogramAggregation, RangeAggregation, TermsAggregation,
+    DateHistogramAggregationReq, FilterAggregation, FiltersAggregation, GeoDistanceAggregation,
+    GlobalAggregation, HistogramAggregation, RangeAggregation, TermsAggregation,
 };
 use super::metric::{
     AverageAggregation, CardinalityAggregationReq, CountAggregation, ExtendedStatsAggregation,
@@ -120,6 +122,9 @@ pub enum AggregationVariants {
     /// Put data into buckets of user-defined ranges.
     #[serde(rename = "range")]
     Range(RangeAggregation),
+    /// Put geo points into buckets of user-defined distance ranges.
+    #[serde(rename = "geo_distance")]
+    GeoDistance(GeoDistanceAggregation),
     /// Put data into a histogram.
     #[serde(rename = "histogram")]
     Histogram(HistogramAggregation),
@@ -129,6 +134,15 @@ pub enum AggregationVariants {
     /// Put data into buckets of terms.
     #[serde(rename = "terms")]
     Terms(TermsAggregation),
+    /// Put the documents matching a query into a bucket.
+    #[serde(rename = "filter")]
+    Filter(FilterAggregation),
+    /// Put the documents matching each of several queries into buckets.
+    #[serde(rename = "filters")]
+    Filters(FiltersAggregation),
+    /// Put all of the documents into a bucket, regardless of the main query.
+    #[serde(rename = "global")]
+    Global(GlobalAggregation),
 
     // Metric aggregation types
     /// Computes the average of the extracted values.
@@ -172,8 +186,12 @@ impl AggregationVariants {
         match self {
             AggregationVariants::Terms(terms) => vec![terms.field.as_str()],
             AggregationVariants::Range(range) => vec![range.field.as_str()],
+            AggregationVariants::GeoDistance(geo_distance) => vec![geo_distance.field.as_str()],
             AggregationVariants::Histogram(histogram) => vec![histogram.field.as_str()],
             AggregationVariants::DateHistogram(histogram) => vec![histogram.field.as_str()],
+            AggregationVariants::Filter(_)
+            | AggregationVariants::Filters(_)
+            | AggregationVariants::Global(_) => vec![],
             AggregationVariants::Average(avg) => vec![avg.field_name()],
             AggregationVariants::Count(count) => vec![count.field_name()],
             AggregationVariants::Max(max) => vec![max.field_name()],
@@ -187,9 +205,12 @@ impl AggregationVariants {
         }
     }
 
-    pub(crate) fn as_range(&self) -> Option<&RangeAggregation> {
+    pub(crate) fn as_range(&self) -> Option<Cow<'_, RangeAggregation>> {
         match &self {
-            AggregationVariants::Range(range) => Some(range),
+            AggregationVariants::Range(range) => Some(Cow::Borrowed(range)),
+            AggregationVariants::GeoDistance(geo_distance) => {
+                Some(Cow::Owned(geo_distance.to_range_req()))
+            }
             _ => None,
         }
     }
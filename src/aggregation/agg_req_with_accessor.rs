@@ -4,17 +4,19 @@ use std::collections::HashMap;
 use std::io;
 
 use columnar::{Column, ColumnBlockAccessor, ColumnType, DynamicColumn, StrColumn};
+use common::BitSet;
 
 use super::agg_req::{Aggregation, AggregationVariants, Aggregations};
 use super::bucket::{
-    DateHistogramAggregationReq, HistogramAggregation, RangeAggregation, TermsAggregation,
+    DateHistogramAggregationReq, GeoDistanceAggregation, HistogramAggregation, RangeAggregation,
+    TermsAggregation,
 };
 use super::metric::{
     AverageAggregation, CardinalityAggregationReq, CountAggregation, ExtendedStatsAggregation,
     MaxAggregation, MinAggregation, StatsAggregation, SumAggregation,
 };
 use super::segment_agg_result::AggregationLimitsGuard;
-use super::VecWithNames;
+use super::{AggregationError, VecWithNames};
 use crate::aggregation::{f64_to_fastfield_u64, Key};
 use crate::index::SegmentReader;
 use crate::SegmentOrdinal;
@@ -58,6 +60,9 @@ pub struct AggregationWithAccessor {
     /// Map field names to all associated column accessors.
     /// This field is used for `docvalue_fields`, which is currently only supported for `top_hits`.
     pub(crate) value_accessors: HashMap<String, Vec<DynamicColumn>>,
+    /// The documents of the segment in each bucket of the `filter`, `filters` and `global`
+    /// aggregations, which do not rely on fast fields.
+    pub(crate) docsets: Vec<BitSet>,
     pub(crate) agg: Aggregation,
 }
 
@@ -72,6 +77,16 @@ impl AggregationWithAccessor {
     ) -> crate::Result<Vec<AggregationWithAccessor>> {
         let mut agg = agg.clone();
 
+        if sub_aggregation
+            .values()
+            .any(|sub_agg| matches!(sub_agg.agg, AggregationVariants::Global(_)))
+        {
+            return Err(AggregationError::InvalidRequest(
+                "global aggregation can only be used as a top-level aggregation".to_string(),
+            )
+            .into());
+        }
+
         let add_agg_with_accessor = |agg: &Aggregation,
                                      accessor: Column<u64>,
                                      column_type: ColumnType,
@@ -82,6 +97,7 @@ impl AggregationWithAccessor {
                 accessor,
                 accessors: Default::default(),
                 value_accessors: Default::default(),
+                docsets: Default::default(),
                 field_type: column_type,
                 sub_aggregation: get_aggs_with_segment_accessor_and_validate(
                     sub_aggregation,
@@ -111,6 +127,7 @@ impl AggregationWithAccessor {
                 // TODO: We should do away with the `accessor` field altogether
                 accessor: accessor.clone(),
                 value_accessors,
+                docsets: Default::default(),
                 field_type: *field_type,
                 accessors,
                 sub_aggregation: get_aggs_with_segment_accessor_and_validate(
@@ -141,6 +158,14 @@ impl AggregationWithAccessor {
                     get_ff_reader(reader, field_name, Some(get_numeric_or_date_column_types()))?;
                 add_agg_with_accessor(&agg, accessor, column_type, &mut res)?;
             }
+            GeoDistance(GeoDistanceAggregation {
+                field: ref field_name,
+                ..
+            }) => {
+                let (accessor, column_type) =
+                    get_ff_reader(reader, field_name, Some(&[ColumnType::U64]))?;
+                add_agg_with_accessor(&agg, accessor, column_type, &mut res)?;
+            }
             Histogram(HistogramAggregation {
                 field: ref field_name,
                 ..
@@ -158,6 +183,40 @@ impl AggregationWithAccessor {
                     get_ff_reader(reader, field_name, Some(&[ColumnType::DateTime]))?;
                 add_agg_with_accessor(&agg, accessor, column_type, &mut res)?;
             }
+            Filter(_) | Filters(_) | Global(_) => {
+                let docsets = match agg.agg {
+                    Filter(ref filter) => vec![filter.query.docset(reader)?],
+                    Filters(ref filters) => filters
+                        .filters
+                        .values()
+                        .map(|query| query.docset(reader))
+                        .collect::<crate::Result<_>>()?,
+                    _ => vec![global_docset(reader)?],
+                };
+                let mut limits = limits.clone();
+                limits.add_memory_consumed(
+                    docsets.len() as u64 * (u64::from(reader.max_doc()) / 8 + 8),
+                )?;
+                res.push(AggregationWithAccessor {
+                    segment_ordinal,
+                    accessor: Column::build_empty_column(reader.max_doc()),
+                    accessors: Default::default(),
+                    value_accessors: Default::default(),
+                    docsets,
+                    field_type: ColumnType::U64,
+                    sub_aggregation: get_aggs_with_segment_accessor_and_validate(
+                        sub_aggregation,
+                        reader,
+                        segment_ordinal,
+                        &limits,
+                    )?,
+                    agg: agg.clone(),
+                    limits,
+                    missing_value_for_accessor: None,
+                    str_dict_column: None,
+                    column_block_accessor: Default::default(),
+                });
+            }
             Terms(TermsAggregation {
                 field: ref field_name,
                 ref missing,
@@ -252,6 +311,7 @@ impl AggregationWithAccessor {
                         accessor,
                         accessors: Default::default(),
                         value_accessors: Default::default(),
+                        docsets: Default::default(),
                         field_type: column_type,
                         sub_aggregation: get_aggs_with_segment_accessor_and_validate(
                             sub_aggregation,
@@ -379,10 +439,13 @@ fn get_missing_val_as_u64_lenient(
             f64_to_fastfield_u64(*val as f64, &column_type)
         }
         _ => {
-            return Err(crate::TantivyError::InvalidArgument(format!(
-                "Missing value {missing:?} for field {field_name} is not supported for column \
-                 type {column_type:?}"
-            )));
+            return Err(crate::TantivyError::InvalidArgument(
+                format!(
+                    "Missing value {missing:?} for field {field_name} is not supported for column \
+                     type {column_type:?}"
+                )
+                .into(),
+            ));
         }
     };
     Ok(missing_val)
@@ -422,6 +485,26 @@ pub(crate) fn get_aggs_with_segment_accessor_and_validate(
 }
 
 /// Get fast field reader or empty as default.
+/// Returns the documents of the `global` aggregation: the alive documents of the segment, within
+/// the [scope](SegmentReader::scope) of the segment reader if any.
+fn global_docset(reader: &SegmentReader) -> crate::Result<BitSet> {
+    let mut docset = if let Some(scope) = reader.scope() {
+        let mut scope_docs = BitSet::with_max_value(reader.max_doc());
+        scope.for_each_no_score(reader, &mut |docs| {
+            for &doc in docs {
+                scope_docs.insert(doc);
+            }
+        })?;
+        scope_docs
+    } else {
+        BitSet::with_max_value_and_full(reader.max_doc())
+    };
+    if let Some(alive_bitset) = reader.alive_bitset() {
+        docset.intersect_update(alive_bitset.bitset());
+    }
+    Ok(docset)
+}
+
 fn get_ff_reader(
     reader: &SegmentReader,
     field_name: &str,
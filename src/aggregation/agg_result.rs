@@ -27,13 +27,21 @@ impl AggregationResults {
             .sum::<u64>()
     }
 
+    /// Returns the value of the metric `agg_property` of the aggregation `name`.
+    ///
+    /// `name` can be a path of aggregation names separated by `>`, where all aggregations but the
+    /// last one are single bucket aggregations.
     pub(crate) fn get_value_from_aggregation(
         &self,
         name: &str,
         agg_property: &str,
     ) -> crate::Result<Option<f64>> {
-        if let Some(agg) = self.0.get(name) {
-            agg.get_value_from_aggregation(name, agg_property)
+        let (agg_name, sub_agg_path) = match name.split_once('>') {
+            Some((agg_name, sub_agg_path)) => (agg_name, Some(sub_agg_path)),
+            None => (name, None),
+        };
+        if let Some(agg) = self.0.get(agg_name) {
+            agg.get_value_from_aggregation(sub_agg_path, agg_property)
         } else {
             // Validation is be done during request parsing, so we can't reach this state.
             Err(TantivyError::InternalError(format!(
@@ -63,16 +71,24 @@ impl AggregationResult {
 
     pub(crate) fn get_value_from_aggregation(
         &self,
-        _name: &str,
+        sub_agg_path: Option<&str>,
         agg_property: &str,
     ) -> crate::Result<Option<f64>> {
-        match self {
-            AggregationResult::BucketResult(_bucket) => Err(TantivyError::InternalError(
+        match (self, sub_agg_path) {
+            (AggregationResult::BucketResult(BucketResult::Filter(bucket)), Some(sub_agg_path)) => {
+                bucket
+                    .sub_aggregation
+                    .get_value_from_aggregation(sub_agg_path, agg_property)
+            }
+            (AggregationResult::BucketResult(BucketResult::Filter(bucket)), None) => {
+                Ok(Some(bucket.doc_count as f64))
+            }
+            (AggregationResult::MetricResult(metric), None) => metric.get_value(agg_property),
+            _ => Err(TantivyError::InternalError(
                 "Tried to retrieve value from bucket aggregation. This is not supported and \
                  should not happen during collection phase, but should be caught during validation"
                     .to_string(),
             )),
-            AggregationResult::MetricResult(metric) => metric.get_value(agg_property),
         }
     }
 }
@@ -104,7 +120,7 @@ pub enum MetricResult {
 }
 
 impl MetricResult {
-    fn get_value(&self, agg_property: &str) -> crate::Result<Option<f64>> {
+    pub(crate) fn get_value(&self, agg_property: &str) -> crate::Result<Option<f64>> {
         match self {
             MetricResult::Average(avg) => Ok(avg.value),
             MetricResult::Count(count) => Ok(count.value),
@@ -113,9 +129,7 @@ impl MetricResult {
             MetricResult::Stats(stats) => stats.get_value(agg_property),
             MetricResult::ExtendedStats(extended_stats) => extended_stats.get_value(agg_property),
             MetricResult::Sum(sum) => Ok(sum.value),
-            MetricResult::Percentiles(_) => Err(TantivyError::AggregationError(
-                AggregationError::InvalidRequest("percentiles can't be used to order".to_string()),
-            )),
+            MetricResult::Percentiles(percentiles) => percentiles.get_value(agg_property),
             MetricResult::TopHits(_) => Err(TantivyError::AggregationError(
                 AggregationError::InvalidRequest("top_hits can't be used to order".to_string()),
             )),
@@ -156,6 +170,18 @@ pub enum BucketResult {
         /// The upper bound error for the doc count of each term.
         doc_count_error_upper_bound: Option<u64>,
     },
+    /// This is the filters result, with a bucket per filter.
+    Filters {
+        /// The buckets, by filter name.
+        ///
+        /// See [`FiltersAggregation`](super::bucket::FiltersAggregation)
+        buckets: FxHashMap<String, FilterBucketEntry>,
+    },
+    /// This is the filter or global result, which is a single bucket.
+    ///
+    /// See [`FilterAggregation`](super::bucket::FilterAggregation) and
+    /// [`GlobalAggregation`](super::bucket::GlobalAggregation)
+    Filter(FilterBucketEntry),
 }
 
 impl BucketResult {
@@ -172,6 +198,11 @@ impl BucketResult {
                 sum_other_doc_count: _,
                 doc_count_error_upper_bound: _,
             } => buckets.iter().map(|bucket| bucket.get_bucket_count()).sum(),
+            BucketResult::Filters { buckets } => buckets
+                .values()
+                .map(|bucket| bucket.get_bucket_count())
+                .sum(),
+            BucketResult::Filter(bucket) => bucket.get_bucket_count(),
         }
     }
 }
@@ -251,6 +282,36 @@ impl GetDocCount for BucketEntry {
     }
 }
 
+/// This is the bucket of a filter or global aggregation, or one of the buckets of a filters
+/// aggregation, which contains a count and optionally sub-aggregations.
+///
+/// # JSON Format
+/// ```json
+/// {
+///   ...
+///     "books": {
+///       "doc_count": 3,
+///       "avg_price": {
+///         "value": 23.3
+///       }
+///    }
+///    ...
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FilterBucketEntry {
+    /// Number of documents in the bucket.
+    pub doc_count: u64,
+    #[serde(flatten)]
+    /// Sub-aggregations in this bucket.
+    pub sub_aggregation: AggregationResults,
+}
+impl FilterBucketEntry {
+    pub(crate) fn get_bucket_count(&self) -> u64 {
+        1 + self.sub_aggregation.get_bucket_count()
+    }
+}
+
 /// This is the range entry for a bucket, which contains a key, count, and optionally
 /// sub-aggregations.
 ///
@@ -0,0 +1,604 @@
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use common::BitSet;
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::aggregation::agg_req::AggregationVariants;
+use crate::aggregation::agg_req_with_accessor::AggregationsWithAccessor;
+use crate::aggregation::intermediate_agg_result::{
+    IntermediateAggregationResult, IntermediateAggregationResults, IntermediateBucketResult,
+    IntermediateFilterBucketEntry,
+};
+use crate::aggregation::segment_agg_result::{
+    build_segment_agg_collector, SegmentAggregationCollector,
+};
+#[cfg(feature = "query-parser")]
+use crate::query::QueryParser;
+use crate::query::{EnableScoring, Query};
+#[cfg(feature = "query-parser")]
+use crate::tokenizer::TokenizerManager;
+use crate::{DocId, SegmentReader, COLLECT_BLOCK_BUFFER_LEN};
+
+/// The query defining the documents of a filter bucket.
+///
+/// In a JSON request, the query is a string in the `QueryParser` syntax.
+/// Queries are parsed without default fields, so every term needs to be prefixed with its
+/// field, and with the default tokenizers, as the tokenizers registered on the index
+/// are not available to aggregations. Parsing query strings requires the `query-parser`
+/// feature: without it, the collection of requests containing a query string fails.
+///
+/// Any [`Query`] can be used when building the request programmatically, but such requests
+/// cannot be serialized.
+#[derive(Clone, Debug)]
+pub enum FilterQuery {
+    /// A query in the `QueryParser` syntax, e.g. `category:book`.
+    QueryString(String),
+    /// A query object.
+    Query(Arc<dyn Query>),
+}
+
+impl FilterQuery {
+    /// Returns the set of documents of the segment matching the query, deleted documents
+    /// included.
+    pub(crate) fn docset(&self, reader: &SegmentReader) -> crate::Result<BitSet> {
+        let schema = reader.schema();
+        let enable_scoring = EnableScoring::disabled_from_schema(schema);
+        let weight = match self {
+            #[cfg(feature = "query-parser")]
+            FilterQuery::QueryString(query) => {
+                QueryParser::new(schema.clone(), Vec::new(), TokenizerManager::default())
+                    .parse_query(query)?
+                    .weight(enable_scoring)?
+            }
+            #[cfg(not(feature = "query-parser"))]
+            FilterQuery::QueryString(query) => {
+                return Err(crate::TantivyError::InvalidArgument(
+                    format!(
+                        "Filter query {query:?} is a query string, which requires the \
+                         `query-parser` feature"
+                    )
+                    .into(),
+                ));
+            }
+            FilterQuery::Query(query) => query.weight(enable_scoring)?,
+        };
+        let mut docset = BitSet::with_max_value(reader.max_doc());
+        weight.for_each_no_score(reader, &mut |docs| {
+            for &doc in docs {
+                docset.insert(doc);
+            }
+        })?;
+        Ok(docset)
+    }
+}
+
+impl PartialEq for FilterQuery {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (FilterQuery::QueryString(left), FilterQuery::QueryString(right)) => left == right,
+            (FilterQuery::Query(left), FilterQuery::Query(right)) => Arc::ptr_eq(left, right),
+            _ => false,
+        }
+    }
+}
+
+impl From<&str> for FilterQuery {
+    fn from(query: &str) -> Self {
+        FilterQuery::QueryString(query.to_string())
+    }
+}
+
+impl From<String> for FilterQuery {
+    fn from(query: String) -> Self {
+        FilterQuery::QueryString(query)
+    }
+}
+
+impl From<Box<dyn Query>> for FilterQuery {
+    fn from(query: Box<dyn Query>) -> Self {
+        FilterQuery::Query(Arc::from(query))
+    }
+}
+
+impl Serialize for FilterQuery {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        match self {
+            FilterQuery::QueryString(query) => serializer.serialize_str(query),
+            FilterQuery::Query(query) => Err(ser::Error::custom(format!(
+                "filter query {query:?} is not a query string and cannot be serialized"
+            ))),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for FilterQuery {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let query = String::deserialize(deserializer)
+            .map_err(|err| de::Error::custom(format!("filter query must be a string: {err}")))?;
+        Ok(FilterQuery::QueryString(query))
+    }
+}
+
+/// A single bucket containing the documents matching a query.
+///
+/// The sub-aggregations are computed on the documents matching both the main query and the
+/// filter query.
+///
+/// Result type is [`BucketResult::Filter`](crate::aggregation::agg_result::BucketResult::Filter)
+/// on the `AggregationCollector`.
+///
+/// # Request JSON Format
+/// ```json
+/// {
+///     "books": {
+///         "filter": { "query": "category:book" },
+///         "aggs": {
+///             "avg_price": { "avg": { "field": "price" } }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FilterAggregation {
+    /// The query documents need to match to be in the bucket.
+    pub query: FilterQuery,
+}
+
+/// One bucket per named query.
+///
+/// This is equivalent to one [`FilterAggregation`] per query, with the same sub-aggregations,
+/// but the documents of each segment are only visited once.
+///
+/// Result type is [`BucketResult::Filters`](crate::aggregation::agg_result::BucketResult::Filters)
+/// on the `AggregationCollector`.
+///
+/// # Request JSON Format
+/// ```json
+/// {
+///     "messages": {
+///         "filters": {
+///             "filters": {
+///                 "errors": "body:error",
+///                 "warnings": "body:warning"
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FiltersAggregation {
+    /// The queries defining the buckets, by bucket name.
+    pub filters: BTreeMap<String, FilterQuery>,
+}
+
+/// A single bucket containing all of the documents of the index, regardless of the main query.
+///
+/// This makes it possible to compare statistics on the documents matching a query with
+/// statistics on the whole index in a single search.
+/// It can only be used as a top-level aggregation. When searching through a
+/// [`ScopedSearcher`](crate::ScopedSearcher), the bucket only contains the documents of the
+/// tenant.
+///
+/// Result type is [`BucketResult::Filter`](crate::aggregation::agg_result::BucketResult::Filter)
+/// on the `AggregationCollector`.
+///
+/// # Request JSON Format
+/// ```json
+/// {
+///     "all_products": {
+///         "global": {},
+///         "aggs": {
+///             "avg_price": { "avg": { "field": "price" } }
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GlobalAggregation {}
+
+#[derive(Clone, Debug)]
+struct SegmentFilterBucketEntry {
+    doc_count: u64,
+    sub_aggregation: Option<Box<dyn SegmentAggregationCollector>>,
+}
+
+impl SegmentFilterBucketEntry {
+    fn collect_block(
+        &mut self,
+        docs: &[DocId],
+        sub_aggregation_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        self.doc_count += docs.len() as u64;
+        if let Some(sub_aggregation) = self.sub_aggregation.as_mut() {
+            sub_aggregation.collect_block(docs, sub_aggregation_accessor)?;
+        }
+        Ok(())
+    }
+
+    fn into_intermediate_bucket_entry(
+        self,
+        sub_aggregation_accessor: &AggregationsWithAccessor,
+    ) -> crate::Result<IntermediateFilterBucketEntry> {
+        let mut sub_aggregation_res = IntermediateAggregationResults::default();
+        if let Some(sub_aggregation) = self.sub_aggregation {
+            sub_aggregation.add_intermediate_aggregation_result(
+                sub_aggregation_accessor,
+                &mut sub_aggregation_res,
+            )?;
+        }
+        Ok(IntermediateFilterBucketEntry {
+            doc_count: self.doc_count,
+            sub_aggregation: sub_aggregation_res,
+        })
+    }
+}
+
+/// The collector for the `filter`, `filters` and `global` aggregations.
+///
+/// The docsets of the buckets are computed when opening the segment, and stored on the
+/// [`AggregationWithAccessor`](crate::aggregation::agg_req_with_accessor::AggregationWithAccessor).
+/// For `global`, the documents collected by the main query are ignored, and all of the alive
+/// documents of the segment within its [scope](SegmentReader::scope) are collected on flush
+/// instead.
+#[derive(Clone, Debug)]
+pub(crate) struct SegmentFilterCollector {
+    buckets: Vec<SegmentFilterBucketEntry>,
+    is_global: bool,
+    collected_all_docs: bool,
+    accessor_idx: usize,
+    matching_docs: Vec<DocId>,
+}
+
+impl SegmentFilterCollector {
+    pub(crate) fn from_req(
+        agg_variant: &AggregationVariants,
+        sub_aggregation: &mut AggregationsWithAccessor,
+        accessor_idx: usize,
+    ) -> crate::Result<Self> {
+        let num_buckets = match agg_variant {
+            AggregationVariants::Filters(filters) => filters.filters.len(),
+            _ => 1,
+        };
+        let buckets = (0..num_buckets)
+            .map(|_| {
+                let sub_aggregation = if sub_aggregation.is_empty() {
+                    None
+                } else {
+                    Some(build_segment_agg_collector(sub_aggregation)?)
+                };
+                Ok(SegmentFilterBucketEntry {
+                    doc_count: 0,
+                    sub_aggregation,
+                })
+            })
+            .collect::<crate::Result<_>>()?;
+        Ok(SegmentFilterCollector {
+            buckets,
+            is_global: matches!(agg_variant, AggregationVariants::Global(_)),
+            collected_all_docs: false,
+            accessor_idx,
+            matching_docs: Vec::new(),
+        })
+    }
+}
+
+impl SegmentAggregationCollector for SegmentFilterCollector {
+    fn add_intermediate_aggregation_result(
+        self: Box<Self>,
+        agg_with_accessor: &AggregationsWithAccessor,
+        results: &mut IntermediateAggregationResults,
+    ) -> crate::Result<()> {
+        let name = agg_with_accessor.aggs.keys[self.accessor_idx].to_string();
+        let agg_with_accessor = &agg_with_accessor.aggs.values[self.accessor_idx];
+        let sub_aggregation_accessor = &agg_with_accessor.sub_aggregation;
+
+        let bucket = if let AggregationVariants::Filters(filters) = &agg_with_accessor.agg.agg {
+            let buckets = filters
+                .filters
+                .keys()
+                .zip(self.buckets)
+                .map(|(key, bucket)| {
+                    Ok((
+                        key.to_string(),
+                        bucket.into_intermediate_bucket_entry(sub_aggregation_accessor)?,
+                    ))
+                })
+                .collect::<crate::Result<_>>()?;
+            IntermediateBucketResult::Filters { buckets }
+        } else {
+            let bucket = self
+                .buckets
+                .into_iter()
+                .next()
+                .expect("filter collector must have a bucket");
+            IntermediateBucketResult::Filter(
+                bucket.into_intermediate_bucket_entry(sub_aggregation_accessor)?,
+            )
+        };
+
+        results.push(name, IntermediateAggregationResult::Bucket(bucket))?;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn collect(
+        &mut self,
+        doc: DocId,
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        self.collect_block(&[doc], agg_with_accessor)
+    }
+
+    #[inline]
+    fn collect_block(
+        &mut self,
+        docs: &[DocId],
+        agg_with_accessor: &mut AggregationsWithAccessor,
+    ) -> crate::Result<()> {
+        if self.is_global {
+            return Ok(());
+        }
+        let agg_with_accessor = &mut agg_with_accessor.aggs.values[self.accessor_idx];
+        for (bucket, docset) in self.buckets.iter_mut().zip(&agg_with_accessor.docsets) {
+            self.matching_docs.clear();
+            self.matching_docs
+                .extend(docs.iter().copied().filter(|&doc| docset.contains(doc)));
+            if !self.matching_docs.is_empty() {
+                bucket
+                    .collect_block(&self.matching_docs, &mut agg_with_accessor.sub_aggregation)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self, agg_with_accessor: &mut AggregationsWithAccessor) -> crate::Result<()> {
+        let agg_with_accessor = &mut agg_with_accessor.aggs.values[self.accessor_idx];
+
+        if self.is_global && !self.collected_all_docs {
+            self.collected_all_docs = true;
+            let alive_docs = &agg_with_accessor.docsets[0];
+            let bucket = &mut self.buckets[0];
+            self.matching_docs.clear();
+            for doc in (0..alive_docs.max_value()).filter(|&doc| alive_docs.contains(doc)) {
+                self.matching_docs.push(doc);
+                if self.matching_docs.len() == COLLECT_BLOCK_BUFFER_LEN {
+                    bucket.collect_block(
+                        &self.matching_docs,
+                        &mut agg_with_accessor.sub_aggregation,
+                    )?;
+                    self.matching_docs.clear();
+                }
+            }
+            bucket.collect_block(&self.matching_docs, &mut agg_with_accessor.sub_aggregation)?;
+        }
+
+        for bucket in self.buckets.iter_mut() {
+            if let Some(sub_aggregation) = bucket.sub_aggregation.as_mut() {
+                sub_aggregation.flush(&mut agg_with_accessor.sub_aggregation)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::aggregation::agg_req::{Aggregation, Aggregations};
+    use crate::aggregation::agg_result::AggregationResults;
+    use crate::aggregation::bucket::{FilterAggregation, FilterQuery};
+    use crate::aggregation::{AggregationCollector, DistributedAggregationCollector};
+    #[cfg(feature = "query-parser")]
+    use crate::query::QueryParser;
+    use crate::query::{AllQuery, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, STRING};
+    use crate::{Index, IndexWriter, Term};
+
+    fn get_test_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let price = schema_builder.add_u64_field("price", INDEXED | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(category => "book", price => 10u64))?;
+        index_writer.add_document(doc!(category => "book", price => 20u64))?;
+        index_writer.add_document(doc!(category => "dvd", price => 35u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(category => "book", price => 40u64))?;
+        index_writer.add_document(doc!(category => "cd", price => 50u64))?;
+        index_writer.add_document(doc!(category => "cd", price => 60u64))?;
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_u64(price, 60));
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    fn exec_request(index: &Index, query: &dyn Query, agg_req: Aggregations) -> Value {
+        let collector = AggregationCollector::from_aggs(agg_req, Default::default());
+        let searcher = index.reader().unwrap().searcher();
+        let agg_res: AggregationResults = searcher.search(query, &collector).unwrap();
+        serde_json::to_value(agg_res).unwrap()
+    }
+
+    #[cfg(feature = "query-parser")]
+    #[test]
+    fn test_filter_aggregation() -> crate::Result<()> {
+        let index = get_test_index()?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "books": {
+                "filter": { "query": "category:book" },
+                "aggs": {
+                    "max_price": { "max": { "field": "price" } },
+                    "by_price": { "range": { "field": "price", "ranges": [{ "to": 15.0 }] } }
+                }
+            },
+            "no_match": { "filter": { "query": "category:vinyl" } }
+        }))
+        .unwrap();
+        let res = exec_request(&index, &AllQuery, agg_req.clone());
+        assert_eq!(res["books"]["doc_count"], 3);
+        assert_eq!(res["books"]["max_price"]["value"], 40.0);
+        assert_eq!(res["books"]["by_price"]["buckets"][0]["doc_count"], 1);
+        assert_eq!(res["no_match"], json!({ "doc_count": 0 }));
+
+        // The filter is applied on top of the main query.
+        let query_parser = QueryParser::for_index(&index, Vec::new());
+        let query = query_parser.parse_query("price:[15 TO 100]")?;
+        let res = exec_request(&index, &*query, agg_req);
+        assert_eq!(res["books"]["doc_count"], 2);
+        assert_eq!(res["books"]["by_price"]["buckets"][0]["doc_count"], 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_aggregation_with_query_object() -> crate::Result<()> {
+        let index = get_test_index()?;
+        let category = index.schema().get_field("category").unwrap();
+        let term_query: Box<dyn Query> = Box::new(TermQuery::new(
+            Term::from_field_text(category, "dvd"),
+            IndexRecordOption::Basic,
+        ));
+        let filter = Aggregation {
+            agg: crate::aggregation::agg_req::AggregationVariants::Filter(FilterAggregation {
+                query: FilterQuery::from(term_query),
+            }),
+            sub_aggregation: Default::default(),
+        };
+        assert!(serde_json::to_string(&filter).is_err());
+        let agg_req: Aggregations = [("dvds".to_string(), filter)].into_iter().collect();
+        let res = exec_request(&index, &AllQuery, agg_req);
+        assert_eq!(res["dvds"]["doc_count"], 1);
+        Ok(())
+    }
+
+    #[cfg(feature = "query-parser")]
+    #[test]
+    fn test_filters_aggregation() -> crate::Result<()> {
+        let index = get_test_index()?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "categories": {
+                "filters": {
+                    "filters": {
+                        "books": "category:book",
+                        "music": "category:cd OR category:vinyl"
+                    }
+                },
+                "aggs": {
+                    "sum_price": { "sum": { "field": "price" } }
+                }
+            }
+        }))
+        .unwrap();
+        let res = exec_request(&index, &AllQuery, agg_req.clone());
+        assert_eq!(
+            res["categories"],
+            json!({
+                "buckets": {
+                    "books": { "doc_count": 3, "sum_price": { "value": 70.0 } },
+                    "music": { "doc_count": 1, "sum_price": { "value": 50.0 } }
+                }
+            })
+        );
+
+        // The intermediate results of several searches can be merged.
+        let collector = DistributedAggregationCollector::from_aggs(agg_req, Default::default());
+        let searcher = index.reader()?.searcher();
+        let mut intermediate_res = searcher.search(&AllQuery, &collector)?;
+        intermediate_res.merge_fruits(searcher.search(&AllQuery, &collector)?)?;
+        let res: AggregationResults = intermediate_res.into_final_result(
+            serde_json::from_value(json!({
+                "categories": {
+                    "filters": { "filters": { "books": "category:book", "music": "category:cd" } },
+                    "aggs": { "sum_price": { "sum": { "field": "price" } } }
+                }
+            }))?,
+            Default::default(),
+        )?;
+        let res = serde_json::to_value(res)?;
+        assert_eq!(res["categories"]["buckets"]["books"]["doc_count"], 6);
+        assert_eq!(
+            res["categories"]["buckets"]["music"]["sum_price"]["value"],
+            100.0
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "query-parser")]
+    #[test]
+    fn test_global_aggregation() -> crate::Result<()> {
+        let index = get_test_index()?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "all": {
+                "global": {},
+                "aggs": {
+                    "avg_price": { "avg": { "field": "price" } },
+                    "books": { "filter": { "query": "category:book" } }
+                }
+            },
+            "avg_price": { "avg": { "field": "price" } }
+        }))
+        .unwrap();
+        let query_parser = QueryParser::for_index(&index, Vec::new());
+        let query = query_parser.parse_query("category:dvd")?;
+        let res = exec_request(&index, &*query, agg_req);
+        assert_eq!(res["avg_price"]["value"], 35.0);
+        assert_eq!(res["all"]["doc_count"], 5);
+        assert_eq!(res["all"]["avg_price"]["value"], 31.0);
+        assert_eq!(res["all"]["books"]["doc_count"], 3);
+        Ok(())
+    }
+
+    #[cfg(feature = "query-parser")]
+    #[test]
+    fn test_global_aggregation_is_top_level_only() -> crate::Result<()> {
+        let index = get_test_index()?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "books": {
+                "filter": { "query": "category:book" },
+                "aggs": { "all": { "global": {} } }
+            }
+        }))
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(agg_req, Default::default());
+        let searcher = index.reader()?.searcher();
+        let err = searcher.search(&AllQuery, &collector).unwrap_err();
+        assert!(err.to_string().contains("top-level"));
+        Ok(())
+    }
+
+    #[cfg(feature = "query-parser")]
+    #[test]
+    fn test_filter_aggregation_invalid_query() -> crate::Result<()> {
+        let index = get_test_index()?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "books": { "filter": { "query": "unknown_field:book" } }
+        }))
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(agg_req, Default::default());
+        let searcher = index.reader()?.searcher();
+        assert!(searcher.search(&AllQuery, &collector).is_err());
+        Ok(())
+    }
+
+    #[cfg(not(feature = "query-parser"))]
+    #[test]
+    fn test_filter_aggregation_query_string_requires_query_parser() -> crate::Result<()> {
+        let index = get_test_index()?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "books": { "filter": { "query": "category:book" } }
+        }))
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(agg_req, Default::default());
+        let searcher = index.reader()?.searcher();
+        let err = searcher.search(&AllQuery, &collector).unwrap_err();
+        assert!(err.to_string().contains("query-parser"));
+        Ok(())
+    }
+}
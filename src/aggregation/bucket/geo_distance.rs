@@ -0,0 +1,302 @@
+use columnar::MonotonicallyMappableToU64;
+use serde::{Deserialize, Serialize};
+
+use super::{RangeAggregation, RangeAggregationRange};
+use crate::TantivyError;
+
+/// Mean radius of the earth, in meters.
+const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+/// Provide user-defined buckets of distances to an origin, to aggregate geo points on.
+///
+/// The geo points are read from the `u64` fast field `field`, in which they are encoded with
+/// [`GeoPoint::to_u64`]. The distance of each document to the `origin` is computed, in the given
+/// `unit`, and the document is put in the range bucket containing this distance.
+///
+/// Buckets behave like the ones of the [`RangeAggregation`]: they include the from value and
+/// exclude the to value, extra buckets are created to cover all of the distances, and overlapping
+/// ranges are not supported.
+///
+/// Result type is [`BucketResult::Range`](crate::aggregation::agg_result::BucketResult::Range).
+///
+/// # Request JSON Format
+/// ```json
+/// {
+///     "rings_around_paris": {
+///         "field": "location",
+///         "origin": { "lat": 48.8566, "lon": 2.3522 },
+///         "unit": "km",
+///         "ranges": [
+///             { "to": 100.0 },
+///             { "from": 100.0, "to": 300.0 },
+///             { "from": 300.0 }
+///         ]
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoDistanceAggregation {
+    /// The field to aggregate on, holding encoded geo points.
+    pub field: String,
+    /// The point distances are computed from.
+    pub origin: GeoPoint,
+    /// The unit of the distances of the ranges.
+    #[serde(default)]
+    pub unit: DistanceUnit,
+    /// Note that this aggregation includes the from value and excludes the to value for each
+    /// range. Extra buckets will be created until the first to, and last from, if necessary.
+    pub ranges: Vec<RangeAggregationRange>,
+    /// Whether to return the buckets as a hash map
+    #[serde(default)]
+    pub keyed: bool,
+}
+
+impl GeoDistanceAggregation {
+    /// Returns the range aggregation of the distances.
+    pub(crate) fn to_range_req(&self) -> RangeAggregation {
+        RangeAggregation {
+            field: self.field.clone(),
+            ranges: self.ranges.clone(),
+            keyed: self.keyed,
+        }
+    }
+
+    pub(crate) fn distance_mapping(&self) -> crate::Result<GeoDistanceMapping> {
+        if !self.origin.is_valid() {
+            return Err(TantivyError::InvalidArgument(
+                format!(
+                    "Invalid origin {:?}, the latitude needs to be within [-90, 90] and the \
+                     longitude within [-180, 180]",
+                    self.origin
+                )
+                .into(),
+            ));
+        }
+        Ok(GeoDistanceMapping {
+            origin: self.origin,
+            unit: self.unit,
+        })
+    }
+}
+
+/// A point on earth.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GeoPoint {
+    /// Latitude, in degrees.
+    pub lat: f64,
+    /// Longitude, in degrees.
+    pub lon: f64,
+}
+
+impl GeoPoint {
+    /// Creates a geo point.
+    pub fn new(lat: f64, lon: f64) -> GeoPoint {
+        GeoPoint { lat, lon }
+    }
+
+    fn is_valid(&self) -> bool {
+        (-90.0..=90.0).contains(&self.lat) && (-180.0..=180.0).contains(&self.lon)
+    }
+
+    /// Encodes the geo point, to be indexed in a `u64` fast field.
+    ///
+    /// The latitude and the longitude are quantized on 32 bits each, which is precise to about a
+    /// centimeter.
+    pub fn to_u64(&self) -> u64 {
+        let lat_bits = quantize(self.lat.clamp(-90.0, 90.0), 90.0);
+        let lon_bits = quantize(self.lon.clamp(-180.0, 180.0), 180.0);
+        (u64::from(lat_bits) << 32) | u64::from(lon_bits)
+    }
+
+    /// Decodes a geo point encoded with [`GeoPoint::to_u64`].
+    pub fn from_u64(val: u64) -> GeoPoint {
+        GeoPoint {
+            lat: dequantize((val >> 32) as u32, 90.0),
+            lon: dequantize(val as u32, 180.0),
+        }
+    }
+
+    /// Returns the great-circle distance between two points, in meters.
+    pub fn distance(&self, other: &GeoPoint) -> f64 {
+        let lat = self.lat.to_radians();
+        let other_lat = other.lat.to_radians();
+        let half_delta_lat = (other_lat - lat) / 2.0;
+        let half_delta_lon = (other.lon - self.lon).to_radians() / 2.0;
+        let haversine = half_delta_lat.sin().powi(2)
+            + lat.cos() * other_lat.cos() * half_delta_lon.sin().powi(2);
+        2.0 * EARTH_RADIUS_METERS * haversine.sqrt().min(1.0).asin()
+    }
+}
+
+fn quantize(val: f64, max: f64) -> u32 {
+    ((val + max) / (2.0 * max) * u32::MAX as f64).round() as u32
+}
+
+fn dequantize(bits: u32, max: f64) -> f64 {
+    bits as f64 / u32::MAX as f64 * (2.0 * max) - max
+}
+
+/// Unit of the distances of a [`GeoDistanceAggregation`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DistanceUnit {
+    /// Meters
+    #[serde(rename = "m")]
+    #[default]
+    Meters,
+    /// Kilometers
+    #[serde(rename = "km")]
+    Kilometers,
+    /// Miles
+    #[serde(rename = "mi")]
+    Miles,
+}
+
+impl DistanceUnit {
+    fn convert_meters(&self, meters: f64) -> f64 {
+        match self {
+            DistanceUnit::Meters => meters,
+            DistanceUnit::Kilometers => meters / 1_000.0,
+            DistanceUnit::Miles => meters / 1_609.344,
+        }
+    }
+}
+
+/// Maps the encoded geo points to their distance to the origin, in the `f64` fast field value
+/// space.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct GeoDistanceMapping {
+    origin: GeoPoint,
+    unit: DistanceUnit,
+}
+
+impl GeoDistanceMapping {
+    #[inline]
+    pub(crate) fn map(&self, encoded_geo_point: u64) -> u64 {
+        let distance = self.origin.distance(&GeoPoint::from_u64(encoded_geo_point));
+        self.unit.convert_meters(distance).to_u64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::aggregation::agg_req::Aggregations;
+    use crate::aggregation::tests::exec_request;
+    use crate::schema::{Schema, FAST};
+    use crate::{Index, IndexWriter};
+
+    const PARIS: GeoPoint = GeoPoint {
+        lat: 48.8566,
+        lon: 2.3522,
+    };
+    const LONDON: GeoPoint = GeoPoint {
+        lat: 51.5074,
+        lon: -0.1278,
+    };
+    const BERLIN: GeoPoint = GeoPoint {
+        lat: 52.52,
+        lon: 13.405,
+    };
+    const NEW_YORK: GeoPoint = GeoPoint {
+        lat: 40.7128,
+        lon: -74.006,
+    };
+
+    #[test]
+    fn test_geo_point_encoding() {
+        for geo_point in [PARIS, NEW_YORK, GeoPoint::new(-90.0, 180.0)] {
+            let decoded = GeoPoint::from_u64(geo_point.to_u64());
+            assert!((decoded.lat - geo_point.lat).abs() < 1e-7);
+            assert!((decoded.lon - geo_point.lon).abs() < 1e-7);
+        }
+        assert_eq!(PARIS.distance(&PARIS), 0.0);
+        let paris_london_km = PARIS.distance(&LONDON) / 1_000.0;
+        assert!((paris_london_km - 343.5).abs() < 1.0);
+    }
+
+    fn get_test_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let location = schema_builder.add_u64_field("location", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for geo_point in [PARIS, LONDON, BERLIN, NEW_YORK] {
+            index_writer.add_document(doc!(location => geo_point.to_u64()))?;
+        }
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn test_geo_distance_agg() -> crate::Result<()> {
+        let index = get_test_index()?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "rings": {
+                "geo_distance": {
+                    "field": "location",
+                    "origin": { "lat": 48.8566, "lon": 2.3522 },
+                    "unit": "km",
+                    "ranges": [
+                        { "to": 100.0 },
+                        { "from": 100.0, "to": 1000.0 },
+                        { "from": 1000.0 }
+                    ]
+                },
+                "aggs": {
+                    "count": { "value_count": { "field": "location" } }
+                }
+            }
+        }))
+        .unwrap();
+
+        let res = exec_request(agg_req, &index)?;
+        let buckets = &res["rings"]["buckets"];
+        assert_eq!(buckets[0]["key"], "*-100");
+        assert_eq!(buckets[0]["doc_count"], 1);
+        assert_eq!(buckets[1]["key"], "100-1000");
+        assert_eq!(buckets[1]["doc_count"], 2);
+        assert_eq!(buckets[1]["count"]["value"], 2.0);
+        assert_eq!(buckets[2]["key"], "1000-*");
+        assert_eq!(buckets[2]["doc_count"], 1);
+        assert_eq!(buckets[3], Value::Null);
+        Ok(())
+    }
+
+    #[test]
+    fn test_geo_distance_agg_keyed() -> crate::Result<()> {
+        let index = get_test_index()?;
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "rings": {
+                "geo_distance": {
+                    "field": "location",
+                    "origin": { "lat": 51.5074, "lon": -0.1278 },
+                    "ranges": [
+                        { "key": "near", "from": 300000.0, "to": 1000000.0 }
+                    ],
+                    "keyed": true
+                }
+            }
+        }))
+        .unwrap();
+
+        let res = exec_request(agg_req, &index)?;
+        let buckets = &res["rings"]["buckets"];
+        assert_eq!(buckets["*-300000"]["doc_count"], 1);
+        assert_eq!(buckets["near"]["doc_count"], 2);
+        assert_eq!(buckets["1000000-*"]["doc_count"], 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_geo_distance_agg_invalid_origin() {
+        let agg_req = GeoDistanceAggregation {
+            field: "location".to_string(),
+            origin: GeoPoint::new(91.0, 0.0),
+            unit: DistanceUnit::Meters,
+            ranges: vec![(0.0..10.0).into()],
+            keyed: false,
+        };
+        assert!(agg_req.distance_mapping().is_err());
+    }
+}
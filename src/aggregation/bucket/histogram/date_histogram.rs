@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::{HistogramAggregation, HistogramBounds};
+pub use crate::aggregation::error::DateHistogramParseError;
 use crate::aggregation::*;
 
 /// DateHistogramAggregation is similar to `HistogramAggregation`, but it can only be used with date
@@ -138,26 +139,32 @@ impl DateHistogramAggregationReq {
 
     fn validate(&self) -> crate::Result<()> {
         if let Some(interval) = self.interval.as_ref() {
-            return Err(crate::TantivyError::InvalidArgument(format!(
-                "`interval` parameter {interval:?} in date histogram is unsupported, only \
-                 `fixed_interval` is supported"
-            )));
+            return Err(crate::TantivyError::InvalidArgument(
+                format!(
+                    "`interval` parameter {interval:?} in date histogram is unsupported, only \
+                     `fixed_interval` is supported"
+                )
+                .into(),
+            ));
         }
         if let Some(interval) = self.calendar_interval.as_ref() {
-            return Err(crate::TantivyError::InvalidArgument(format!(
-                "`calendar_interval` parameter {interval:?} in date histogram is unsupported, \
-                 only `fixed_interval` is supported"
-            )));
+            return Err(crate::TantivyError::InvalidArgument(
+                format!(
+                    "`calendar_interval` parameter {interval:?} in date histogram is unsupported, \
+                     only `fixed_interval` is supported"
+                )
+                .into(),
+            ));
         }
         if self.format.is_some() {
             return Err(crate::TantivyError::InvalidArgument(
-                "format parameter on date_histogram is unsupported".to_string(),
+                "format parameter on date_histogram is unsupported".into(),
             ));
         }
 
         if self.fixed_interval.is_none() {
             return Err(crate::TantivyError::InvalidArgument(
-                "fixed_interval in date histogram is missing".to_string(),
+                "fixed_interval in date histogram is missing".into(),
             ));
         }
 
@@ -167,26 +174,6 @@ impl DateHistogramAggregationReq {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Error)]
-/// Errors when parsing the fixed interval for `DateHistogramAggregationReq`.
-pub enum DateHistogramParseError {
-    /// Unit not recognized in passed String
-    #[error("Unit not recognized in passed String {0:?}")]
-    UnitNotRecognized(String),
-    /// Number not found in passed String
-    #[error("Number not found in passed String {0:?}")]
-    NumberMissing(String),
-    /// Unit not found in passed String
-    #[error("Unit not found in passed String {0:?}")]
-    UnitMissing(String),
-    /// Offset invalid
-    #[error("passed offset is invalid {0:?}")]
-    InvalidOffset(String),
-    /// Value out of bounds
-    #[error("passed value is out of bounds: {0:?}")]
-    OutOfBounds(String),
-}
-
 fn parse_offset_into_milliseconds(input: &str) -> Result<i64, AggregationError> {
     let is_sign = |byte| &[byte] == b"-" || &[byte] == b"+";
     if input.is_empty() {
@@ -147,23 +147,26 @@ impl HistogramAggregation {
     fn validate(&self) -> crate::Result<()> {
         if self.interval <= 0.0f64 {
             return Err(TantivyError::InvalidArgument(
-                "interval must be a positive value".to_string(),
+                "interval must be a positive value".into(),
             ));
         }
 
         if self.min_doc_count.unwrap_or(0) > 0 && self.extended_bounds.is_some() {
             return Err(TantivyError::InvalidArgument(
-                "Cannot set min_doc_count and extended_bounds at the same time".to_string(),
+                "Cannot set min_doc_count and extended_bounds at the same time".into(),
             ));
         }
 
         if let (Some(hard_bounds), Some(extended_bounds)) = (self.hard_bounds, self.extended_bounds)
         {
             if extended_bounds.min < hard_bounds.min || extended_bounds.max > hard_bounds.max {
-                return Err(TantivyError::InvalidArgument(format!(
-                    "extended_bounds have to be inside hard_bounds, extended_bounds: \
-                     {extended_bounds}, hard_bounds {hard_bounds}"
-                )));
+                return Err(TantivyError::InvalidArgument(
+                    format!(
+                        "extended_bounds have to be inside hard_bounds, extended_bounds: \
+                         {extended_bounds}, hard_bounds {hard_bounds}"
+                    )
+                    .into(),
+                ));
             }
         }
 
@@ -20,8 +20,14 @@
 //! - [Histogram](HistogramAggregation)
 //! - [DateHistogram](DateHistogramAggregationReq)
 //! - [Range](RangeAggregation)
+//! - [GeoDistance](GeoDistanceAggregation)
 //! - [Terms](TermsAggregation)
+//! - [Filter](FilterAggregation)
+//! - [Filters](FiltersAggregation)
+//! - [Global](GlobalAggregation)
 
+mod filter;
+mod geo_distance;
 mod histogram;
 mod range;
 mod term_agg;
@@ -30,6 +36,8 @@ mod term_missing_agg;
 use std::collections::HashMap;
 use std::fmt;
 
+pub use filter::*;
+pub use geo_distance::*;
 pub use histogram::*;
 pub use range::*;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
@@ -59,7 +67,8 @@ pub enum OrderTarget {
     Count,
     /// Order by value of the sub aggregation metric with identified by given `String`.
     ///
-    /// Only single value metrics are supported currently
+    /// The `String` is a path of aggregation names separated by `>`, optionally followed by the
+    /// property of a multi value metric, e.g. `in_stock>price_stats.avg`.
     SubAggregation(String),
 }
 
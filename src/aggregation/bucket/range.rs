@@ -4,6 +4,7 @@ use std::ops::Range;
 use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 
+use super::geo_distance::GeoDistanceMapping;
 use crate::aggregation::agg_req_with_accessor::AggregationsWithAccessor;
 use crate::aggregation::intermediate_agg_result::{
     IntermediateAggregationResult, IntermediateAggregationResults, IntermediateBucketResult,
@@ -133,6 +134,8 @@ pub struct SegmentRangeCollector {
     /// The buckets containing the aggregation data.
     buckets: Vec<SegmentRangeAndBucketEntry>,
     column_type: ColumnType,
+    /// Maps the encoded geo points to their distance, for the geo distance aggregation.
+    geo_distance_mapping: Option<GeoDistanceMapping>,
     pub(crate) accessor_idx: usize,
 }
 
@@ -239,6 +242,10 @@ impl SegmentAggregationCollector for SegmentRangeCollector {
             .column_block_accessor
             .iter_docid_vals(docs, &bucket_agg_accessor.accessor)
         {
+            let val = match &self.geo_distance_mapping {
+                Some(geo_distance_mapping) => geo_distance_mapping.map(val),
+                None => val,
+            };
             let bucket_pos = self.get_bucket_pos(val);
 
             let bucket = &mut self.buckets[bucket_pos];
@@ -321,10 +328,17 @@ impl SegmentRangeCollector {
         Ok(SegmentRangeCollector {
             buckets,
             column_type: field_type,
+            geo_distance_mapping: None,
             accessor_idx,
         })
     }
 
+    /// Buckets the distance of the encoded geo points of the column, instead of its values.
+    pub(crate) fn with_geo_distance(mut self, geo_distance_mapping: GeoDistanceMapping) -> Self {
+        self.geo_distance_mapping = Some(geo_distance_mapping);
+        self
+    }
+
     #[inline]
     fn get_bucket_pos(&self, val: u64) -> usize {
         let pos = self
@@ -354,14 +368,14 @@ fn to_u64_range(
 ) -> crate::Result<InternalRangeAggregationRange> {
     let start = if let Some(from) = range.from {
         f64_to_fastfield_u64(from, field_type)
-            .ok_or_else(|| TantivyError::InvalidArgument("invalid field type".to_string()))?
+            .ok_or_else(|| TantivyError::InvalidArgument("invalid field type".into()))?
     } else {
         u64::MIN
     };
 
     let end = if let Some(to) = range.to {
         f64_to_fastfield_u64(to, field_type)
-            .ok_or_else(|| TantivyError::InvalidArgument("invalid field type".to_string()))?
+            .ok_or_else(|| TantivyError::InvalidArgument("invalid field type".into()))?
     } else {
         u64::MAX
     };
@@ -397,10 +411,13 @@ fn extend_validate_ranges(
     let find_hole = |converted_buckets: &[InternalRangeAggregationRange]| {
         for (pos, ranges) in converted_buckets.windows(2).enumerate() {
             if ranges[0].range.end > ranges[1].range.start {
-                return Err(TantivyError::InvalidArgument(format!(
-                    "Overlapping ranges not supported range {:?}, range+1 {:?}",
-                    ranges[0], ranges[1]
-                )));
+                return Err(TantivyError::InvalidArgument(
+                    format!(
+                        "Overlapping ranges not supported range {:?}, range+1 {:?}",
+                        ranges[0], ranges[1]
+                    )
+                    .into(),
+                ));
             }
             if ranges[0].range.end != ranges[1].range.start {
                 return Ok(Some(pos));
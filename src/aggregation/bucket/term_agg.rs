@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 
 use super::{CustomOrder, Order, OrderTarget};
 use crate::aggregation::agg_limits::MemoryConsumption;
+use crate::aggregation::agg_req::{Aggregation, AggregationVariants};
 use crate::aggregation::agg_req_with_accessor::{
     AggregationWithAccessor, AggregationsWithAccessor,
 };
@@ -21,7 +22,7 @@ use crate::aggregation::intermediate_agg_result::{
 use crate::aggregation::segment_agg_result::{
     build_segment_agg_collector, SegmentAggregationCollector,
 };
-use crate::aggregation::{format_date, Key};
+use crate::aggregation::{format_date, AggregationError, Key};
 use crate::error::DataCorruption;
 use crate::TantivyError;
 
@@ -54,6 +55,12 @@ use crate::TantivyError;
 /// each segment. It’s the sum of the size of the largest bucket on each segment that didn’t fit
 /// into segment_size.
 ///
+/// This also holds when ordering by a sub-aggregation: a segment with more than `segment_size`
+/// terms computes the sub-aggregations of all of its terms, and only keeps the `segment_size` best
+/// ones. The upper bound of the error is then the sum, over the segments, of the largest doc count
+/// among the terms that were cut off, as these are not necessarily the ones with the lowest doc
+/// count.
+///
 /// Result type is [`BucketResult`](crate::aggregation::agg_result::BucketResult) with
 /// [`BucketEntry`](crate::aggregation::agg_result::BucketEntry) on the
 /// `AggregationCollector`.
@@ -134,12 +141,17 @@ pub struct TermsAggregation {
     ///
     /// Single value metrics like average can be addressed by its name.
     /// Multi value metrics like stats are required to address their field by name e.g.
-    /// "stats.avg"
+    /// "stats.avg", and percentiles by the percent e.g. "load_time_percentiles.99".
+    ///
+    /// Metrics nested in single bucket aggregations (`filter` and `global`) are addressed with a
+    /// path of aggregation names separated by `>`, e.g. "in_stock>average_price". Addressing a
+    /// single bucket aggregation itself orders by its doc count.
     ///
     /// Examples in JSON format:
     /// { "_count": "asc" }
     /// { "_key": "asc" }
     /// { "average_price": "asc" }
+    /// { "in_stock>price_stats.max": "desc" }
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub order: Option<CustomOrder>,
 
@@ -256,9 +268,90 @@ pub struct SegmentTermCollector {
     accessor_idx: usize,
 }
 
+/// Splits an order target into the path of the aggregation and the property of the metric, e.g.
+/// "in_stock>stats.avg" into ("in_stock>stats", "avg").
 pub(crate) fn get_agg_name_and_property(name: &str) -> (&str, &str) {
-    let (agg_name, agg_property) = name.split_once('.').unwrap_or((name, ""));
-    (agg_name, agg_property)
+    let last_agg_start = name.rfind('>').map(|pos| pos + 1).unwrap_or(0);
+    match name[last_agg_start..].find('.') {
+        Some(pos) => (
+            &name[..last_agg_start + pos],
+            &name[last_agg_start + pos + 1..],
+        ),
+        None => (name, ""),
+    }
+}
+
+/// Validates that an order target addresses a metric, or the doc count of a single bucket
+/// aggregation, only traversing single bucket aggregations.
+fn validate_order_target(
+    name: &str,
+    sub_aggregations: &AggregationsWithAccessor,
+) -> crate::Result<()> {
+    let (agg_path, agg_property) = get_agg_name_and_property(name);
+    let not_found = |agg_name: &str| {
+        TantivyError::InvalidArgument(
+            format!("could not find aggregation with name {agg_name} in metric sub_aggregations")
+                .into(),
+        )
+    };
+    let invalid_order = |reason: &str| {
+        TantivyError::AggregationError(AggregationError::InvalidRequest(format!(
+            "can't order by {name:?}: {reason}"
+        )))
+    };
+    let mut agg_names = agg_path.split('>');
+    let first_agg_name = agg_names.next().unwrap_or(agg_path);
+    let mut agg: &Aggregation = &sub_aggregations
+        .aggs
+        .get(first_agg_name)
+        .ok_or_else(|| not_found(first_agg_name))?
+        .agg;
+    for agg_name in agg_names {
+        if !is_single_bucket_aggregation(agg) {
+            return Err(invalid_order(
+                "only single bucket aggregations can be part of the path",
+            ));
+        }
+        agg = agg
+            .sub_aggregation()
+            .get(agg_name)
+            .ok_or_else(|| not_found(agg_name))?;
+    }
+    match &agg.agg {
+        AggregationVariants::Filter(_) | AggregationVariants::Global(_)
+            if !matches!(agg_property, "" | "_count") =>
+        {
+            Err(invalid_order(
+                "single bucket aggregations can only be ordered by their doc count",
+            ))
+        }
+        AggregationVariants::Percentiles(percentiles)
+            if !agg_property
+                .parse::<f64>()
+                .is_ok_and(|percent| percentiles.percents_or_default().contains(&percent)) =>
+        {
+            Err(invalid_order(
+                "the percentile is not part of the requested ones",
+            ))
+        }
+        AggregationVariants::TopHits(_) => Err(invalid_order("top_hits can't be used to order")),
+        AggregationVariants::Range(_)
+        | AggregationVariants::GeoDistance(_)
+        | AggregationVariants::Histogram(_)
+        | AggregationVariants::DateHistogram(_)
+        | AggregationVariants::Terms(_)
+        | AggregationVariants::Filters(_) => Err(invalid_order(
+            "multi bucket aggregations can't be used to order",
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn is_single_bucket_aggregation(agg: &Aggregation) -> bool {
+    matches!(
+        agg.agg,
+        AggregationVariants::Filter(_) | AggregationVariants::Global(_)
+    )
 }
 
 impl SegmentAggregationCollector for SegmentTermCollector {
@@ -357,23 +450,15 @@ impl SegmentTermCollector {
         accessor_idx: usize,
     ) -> crate::Result<Self> {
         if field_type == ColumnType::Bytes {
-            return Err(TantivyError::InvalidArgument(format!(
-                "terms aggregation is not supported for column type {field_type:?}"
-            )));
+            return Err(TantivyError::InvalidArgument(
+                format!("terms aggregation is not supported for column type {field_type:?}").into(),
+            ));
         }
         let term_buckets = TermBuckets::default();
 
         if let Some(custom_order) = req.order.as_ref() {
-            // Validate sub aggregation exists
             if let OrderTarget::SubAggregation(sub_agg_name) = &custom_order.target {
-                let (agg_name, _agg_property) = get_agg_name_and_property(sub_agg_name);
-
-                sub_aggregations.aggs.get(agg_name).ok_or_else(|| {
-                    TantivyError::InvalidArgument(format!(
-                        "could not find aggregation with name {agg_name} in metric \
-                         sub_aggregations"
-                    ))
-                })?;
+                validate_order_target(sub_agg_name, sub_aggregations)?;
             }
         }
 
@@ -400,11 +485,11 @@ impl SegmentTermCollector {
         agg_with_accessor: &AggregationWithAccessor,
     ) -> crate::Result<IntermediateBucketResult> {
         let mut entries: Vec<(u64, u32)> = self.term_buckets.entries.into_iter().collect();
+        // Sub-aggregation results computed to order the buckets.
+        let mut sub_aggregation_results: FxHashMap<u64, IntermediateAggregationResults> =
+            FxHashMap::default();
 
-        let order_by_sub_aggregation =
-            matches!(self.req.order.target, OrderTarget::SubAggregation(_));
-
-        match self.req.order.target {
+        match &self.req.order.target {
             OrderTarget::Key => {
                 // We rely on the fact, that term ordinals match the order of the strings
                 // TODO: We could have a special collector, that keeps only TOP n results at any
@@ -415,10 +500,37 @@ impl SegmentTermCollector {
                     entries.sort_unstable_by_key(|bucket| bucket.0);
                 }
             }
-            OrderTarget::SubAggregation(_name) => {
-                // don't sort and cut off since it's hard to make assumptions on the quality of the
-                // results when cutting off du to unknown nature of the sub_aggregation (possible
-                // to check).
+            OrderTarget::SubAggregation(name) => {
+                // Sorting requires the sub-aggregation results of every bucket, so we only do it
+                // when some buckets have to be cut off.
+                if entries.len() > self.req.segment_size as usize {
+                    let (agg_name, agg_property) = get_agg_name_and_property(name);
+                    let sub_aggregation_req = agg_with_accessor.agg.sub_aggregation();
+                    let mut entries_with_val = Vec::with_capacity(entries.len());
+                    for (term_id, doc_count) in entries.drain(..) {
+                        let mut sub_aggregation_res = IntermediateAggregationResults::default();
+                        if let Some(sub_aggregation) = self.term_buckets.sub_aggs.remove(&term_id) {
+                            sub_aggregation.add_intermediate_aggregation_result(
+                                &agg_with_accessor.sub_aggregation,
+                                &mut sub_aggregation_res,
+                            )?;
+                        }
+                        let val = sub_aggregation_res
+                            .get_value_from_aggregation(
+                                agg_name,
+                                agg_property,
+                                sub_aggregation_req,
+                            )?
+                            .unwrap_or(f64::MIN);
+                        sub_aggregation_results.insert(term_id, sub_aggregation_res);
+                        entries_with_val.push(((term_id, doc_count), val));
+                    }
+                    entries_with_val.sort_by(|(_, val1), (_, val2)| match self.req.order.order {
+                        Order::Desc => val2.total_cmp(val1),
+                        Order::Asc => val1.total_cmp(val2),
+                    });
+                    entries.extend(entries_with_val.into_iter().map(|(entry, _val)| entry));
+                }
             }
             OrderTarget::Count => {
                 if self.req.order.order == Order::Desc {
@@ -429,40 +541,43 @@ impl SegmentTermCollector {
             }
         }
 
-        let (term_doc_count_before_cutoff, sum_other_doc_count) = if order_by_sub_aggregation {
-            (0, 0)
-        } else {
-            cut_off_buckets(&mut entries, self.req.segment_size as usize)
-        };
+        let (term_doc_count_before_cutoff, sum_other_doc_count) =
+            cut_off_buckets(&mut entries, self.req.segment_size as usize);
 
         let mut dict: FxHashMap<IntermediateKey, IntermediateTermBucketEntry> = Default::default();
         dict.reserve(entries.len());
 
         let mut into_intermediate_bucket_entry =
             |id, doc_count| -> crate::Result<IntermediateTermBucketEntry> {
-                let intermediate_entry = if self.blueprint.as_ref().is_some() {
-                    let mut sub_aggregation_res = IntermediateAggregationResults::default();
-                    self.term_buckets
-                        .sub_aggs
-                        .remove(&id)
-                        .unwrap_or_else(|| {
-                            panic!("Internal Error: could not find subaggregation for id {id}")
-                        })
-                        .add_intermediate_aggregation_result(
-                            &agg_with_accessor.sub_aggregation,
-                            &mut sub_aggregation_res,
-                        )?;
-
-                    IntermediateTermBucketEntry {
-                        doc_count,
-                        sub_aggregation: sub_aggregation_res,
-                    }
-                } else {
-                    IntermediateTermBucketEntry {
-                        doc_count,
-                        sub_aggregation: Default::default(),
-                    }
-                };
+                let intermediate_entry =
+                    if let Some(sub_aggregation_res) = sub_aggregation_results.remove(&id) {
+                        IntermediateTermBucketEntry {
+                            doc_count,
+                            sub_aggregation: sub_aggregation_res,
+                        }
+                    } else if self.blueprint.as_ref().is_some() {
+                        let mut sub_aggregation_res = IntermediateAggregationResults::default();
+                        self.term_buckets
+                            .sub_aggs
+                            .remove(&id)
+                            .unwrap_or_else(|| {
+                                panic!("Internal Error: could not find subaggregation for id {id}")
+                            })
+                            .add_intermediate_aggregation_result(
+                                &agg_with_accessor.sub_aggregation,
+                                &mut sub_aggregation_res,
+                            )?;
+
+                        IntermediateTermBucketEntry {
+                            doc_count,
+                            sub_aggregation: sub_aggregation_res,
+                        }
+                    } else {
+                        IntermediateTermBucketEntry {
+                            doc_count,
+                            sub_aggregation: Default::default(),
+                        }
+                    };
                 Ok(intermediate_entry)
             };
 
@@ -638,13 +753,17 @@ impl GetDocCount for (String, IntermediateTermBucketEntry) {
     }
 }
 
+/// Truncates `entries` to `num_elem` entries, and returns the largest doc count and the sum of the
+/// doc counts of the entries that were cut off.
+///
+/// The entries are not necessarily sorted by doc count, e.g. when ordering by key.
 pub(crate) fn cut_off_buckets<T: GetDocCount + Debug>(
     entries: &mut Vec<T>,
     num_elem: usize,
 ) -> (u64, u64) {
     let term_doc_count_before_cutoff = entries
-        .get(num_elem)
-        .map(|entry| entry.doc_count())
+        .get(num_elem..)
+        .and_then(|cut_off_range| cut_off_range.iter().map(|entry| entry.doc_count()).max())
         .unwrap_or(0);
 
     let sum_other_doc_count = entries
@@ -1058,6 +1177,138 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn terms_aggregation_test_order_nested_sub_agg() -> crate::Result<()> {
+        let segment_and_terms = vec![
+            vec![
+                (1.0, "terma".to_string()),
+                (9.0, "terma".to_string()),
+                (5.0, "termb".to_string()),
+                (6.0, "termb".to_string()),
+            ],
+            vec![
+                (2.0, "termc".to_string()),
+                (2.0, "termc".to_string()),
+                (3.0, "termc".to_string()),
+                (1.0, "terma".to_string()),
+            ],
+        ];
+        let index = get_test_index_from_values_and_terms(false, &segment_and_terms)?;
+
+        let order_by = |target: &str, order: &str| -> crate::Result<Vec<String>> {
+            let agg_req: Aggregations = serde_json::from_value(json!({
+                "my_texts": {
+                    "terms": {
+                        "field": "string_id",
+                        "order": { target: order }
+                    },
+                    "aggs": {
+                        "high": {
+                            "filter": { "query": "score:[5 TO 100]" },
+                            "aggs": { "avg_score": { "avg": { "field": "score" } } }
+                        },
+                        "score_percentiles": {
+                            "percentiles": { "field": "score", "percents": [50.0, 99.0] }
+                        },
+                        "top": {
+                            "top_hits": { "size": 1, "sort": [{ "score": "desc" }] }
+                        }
+                    }
+                }
+            }))
+            .unwrap();
+            let res = exec_request(agg_req, &index)?;
+            Ok(res["my_texts"]["buckets"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|bucket| bucket["key"].as_str().unwrap().to_string())
+                .collect())
+        };
+
+        // terma: high avg 9, termb: high avg 5.5, termc: no high values.
+        assert_eq!(
+            order_by("high>avg_score", "desc")?,
+            vec!["terma", "termb", "termc"]
+        );
+        assert_eq!(
+            order_by("high>avg_score", "asc")?,
+            vec!["termc", "termb", "terma"]
+        );
+        // Single bucket aggregations are ordered by doc count.
+        assert_eq!(order_by("high", "asc")?, vec!["termc", "terma", "termb"]);
+        assert_eq!(
+            order_by("high._count", "asc")?,
+            vec!["termc", "terma", "termb"]
+        );
+        assert_eq!(
+            order_by("score_percentiles.50", "desc")?,
+            vec!["termb", "termc", "terma"]
+        );
+        assert_eq!(
+            order_by("score_percentiles.50", "asc")?,
+            vec!["terma", "termc", "termb"]
+        );
+
+        // Invalid order targets are rejected when validating the request.
+        assert!(order_by("score_percentiles.75", "asc").is_err());
+        assert!(order_by("top", "asc").is_err());
+        assert!(order_by("high>doesnotexist", "asc").is_err());
+        assert!(order_by("high.avg_score", "asc").is_err());
+        assert!(order_by("score_percentiles>high", "asc").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn terms_aggregation_test_order_sub_agg_segment_cut_off() -> crate::Result<()> {
+        let segment_and_terms = vec![
+            vec![
+                (1.0, "terma".to_string()),
+                (1.0, "terma".to_string()),
+                (1.0, "terma".to_string()),
+                (10.0, "termb".to_string()),
+                (5.0, "termc".to_string()),
+                (5.0, "termc".to_string()),
+            ],
+            vec![
+                (7.0, "termc".to_string()),
+                (2.0, "terma".to_string()),
+                (2.0, "terma".to_string()),
+                (2.0, "terma".to_string()),
+                (2.0, "terma".to_string()),
+            ],
+        ];
+        let index = get_test_index_from_values_and_terms(false, &segment_and_terms)?;
+        assert_eq!(index.searchable_segments()?.len(), 2);
+
+        let agg_req: Aggregations = serde_json::from_value(json!({
+            "my_texts": {
+                "terms": {
+                    "field": "string_id",
+                    "order": { "avg_score": "desc" },
+                    "size": 1,
+                    "segment_size": 1,
+                    "show_term_doc_count_error": true
+                },
+                "aggs": { "avg_score": { "avg": { "field": "score" } } }
+            }
+        }))
+        .unwrap();
+        let res = exec_request(agg_req, &index)?;
+
+        // The first segment only keeps termb, the second one termc.
+        assert_eq!(res["my_texts"]["buckets"][0]["key"], "termb");
+        assert_eq!(res["my_texts"]["buckets"][0]["avg_score"]["value"], 10.0);
+        assert_eq!(
+            res["my_texts"]["buckets"][1]["key"],
+            serde_json::Value::Null
+        );
+        assert_eq!(res["my_texts"]["sum_other_doc_count"], 10);
+        // terma is cut off in both segments, with 3 and 4 documents.
+        assert_eq!(res["my_texts"]["doc_count_error_upper_bound"], 7);
+        Ok(())
+    }
+
     #[test]
     fn terms_aggregation_test_order_key_single_segment() -> crate::Result<()> {
         terms_aggregation_test_order_key_merge_segment(true)
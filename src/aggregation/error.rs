@@ -1,7 +1,5 @@
 use common::ByteCount;
 
-use super::bucket::DateHistogramParseError;
-
 /// Error that may occur when opening a directory
 #[derive(Debug, Clone, PartialEq, Eq, Error)]
 pub enum AggregationError {
@@ -37,3 +35,23 @@ pub enum AggregationError {
         current: u32,
     },
 }
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+/// Errors when parsing the fixed interval for `DateHistogramAggregationReq`.
+pub enum DateHistogramParseError {
+    /// Unit not recognized in passed String
+    #[error("Unit not recognized in passed String {0:?}")]
+    UnitNotRecognized(String),
+    /// Number not found in passed String
+    #[error("Number not found in passed String {0:?}")]
+    NumberMissing(String),
+    /// Unit not found in passed String
+    #[error("Unit not found in passed String {0:?}")]
+    UnitMissing(String),
+    /// Offset invalid
+    #[error("passed offset is invalid {0:?}")]
+    InvalidOffset(String),
+    /// Value out of bounds
+    #[error("passed value is out of bounds: {0:?}")]
+    OutOfBounds(String),
+}
@@ -13,7 +13,9 @@ use rustc_hash::FxHashMap;
 use serde::{Deserialize, Serialize};
 
 use super::agg_req::{Aggregation, AggregationVariants, Aggregations};
-use super::agg_result::{AggregationResult, BucketResult, MetricResult, RangeBucketEntry};
+use super::agg_result::{
+    AggregationResult, BucketResult, FilterBucketEntry, MetricResult, RangeBucketEntry,
+};
 use super::bucket::{
     cut_off_buckets, get_agg_name_and_property, intermediate_histogram_buckets_to_final_buckets,
     GetDocCount, Order, OrderTarget, RangeAggregation, TermsAggregation,
@@ -138,6 +140,48 @@ impl IntermediateAggregationResults {
     }
 
     /// Convert intermediate result and its aggregation request to the final result.
+    /// Returns the value of the metric `agg_property` of the aggregation `name`, like
+    /// [`AggregationResults::get_value_from_aggregation`] does on the final results.
+    ///
+    /// This is used to order buckets by a sub-aggregation before the final results are computed.
+    pub(crate) fn get_value_from_aggregation(
+        &self,
+        name: &str,
+        agg_property: &str,
+        req: &Aggregations,
+    ) -> crate::Result<Option<f64>> {
+        let (agg_name, sub_agg_path) = match name.split_once('>') {
+            Some((agg_name, sub_agg_path)) => (agg_name, Some(sub_agg_path)),
+            None => (name, None),
+        };
+        let (Some(agg_res), Some(agg_req)) = (self.aggs_res.get(agg_name), req.get(agg_name))
+        else {
+            return Ok(None);
+        };
+        match (agg_res, sub_agg_path) {
+            (
+                IntermediateAggregationResult::Bucket(IntermediateBucketResult::Filter(bucket)),
+                Some(sub_agg_path),
+            ) => bucket.sub_aggregation.get_value_from_aggregation(
+                sub_agg_path,
+                agg_property,
+                agg_req.sub_aggregation(),
+            ),
+            (
+                IntermediateAggregationResult::Bucket(IntermediateBucketResult::Filter(bucket)),
+                None,
+            ) => Ok(Some(bucket.doc_count as f64)),
+            (IntermediateAggregationResult::Metric(metric), None) => metric
+                .clone()
+                .into_final_metric_result(agg_req)
+                .get_value(agg_property),
+            _ => Err(TantivyError::InternalError(format!(
+                "Can't retrieve an order value from aggregation {agg_name:?}. This should have \
+                 been caught during validation"
+            ))),
+        }
+    }
+
     pub(crate) fn into_final_result_internal(
         self,
         req: &Aggregations,
@@ -196,9 +240,9 @@ pub(crate) fn empty_from_req(req: &Aggregation) -> IntermediateAggregationResult
         Terms(_) => IntermediateAggregationResult::Bucket(IntermediateBucketResult::Terms {
             buckets: Default::default(),
         }),
-        Range(_) => IntermediateAggregationResult::Bucket(IntermediateBucketResult::Range(
-            Default::default(),
-        )),
+        Range(_) | GeoDistance(_) => IntermediateAggregationResult::Bucket(
+            IntermediateBucketResult::Range(Default::default()),
+        ),
         Histogram(_) => {
             IntermediateAggregationResult::Bucket(IntermediateBucketResult::Histogram {
                 buckets: Vec::new(),
@@ -211,6 +255,18 @@ pub(crate) fn empty_from_req(req: &Aggregation) -> IntermediateAggregationResult
                 is_date_agg: true,
             })
         }
+        Filter(_) | Global(_) => IntermediateAggregationResult::Bucket(
+            IntermediateBucketResult::Filter(Default::default()),
+        ),
+        Filters(ref filters) => {
+            IntermediateAggregationResult::Bucket(IntermediateBucketResult::Filters {
+                buckets: filters
+                    .filters
+                    .keys()
+                    .map(|key| (key.to_string(), Default::default()))
+                    .collect(),
+            })
+        }
         Average(_) => IntermediateAggregationResult::Metric(IntermediateMetricResult::Average(
             IntermediateAverage::default(),
         )),
@@ -426,6 +482,13 @@ pub enum IntermediateBucketResult {
         /// The term buckets
         buckets: IntermediateTermBucketResult,
     },
+    /// Filter or global aggregation, which have a single bucket
+    Filter(IntermediateFilterBucketEntry),
+    /// Filters aggregation
+    Filters {
+        /// The buckets, by filter name
+        buckets: FxHashMap<String, IntermediateFilterBucketEntry>,
+    },
 }
 
 impl IntermediateBucketResult {
@@ -436,15 +499,17 @@ impl IntermediateBucketResult {
     ) -> crate::Result<BucketResult> {
         match self {
             IntermediateBucketResult::Range(range_res) => {
+                let range_req = req
+                    .agg
+                    .as_range()
+                    .expect("unexpected aggregation, expected range aggregation");
                 let mut buckets: Vec<RangeBucketEntry> = range_res
                     .buckets
                     .into_values()
                     .map(|bucket| {
                         bucket.into_final_bucket_entry(
                             req.sub_aggregation(),
-                            req.agg
-                                .as_range()
-                                .expect("unexpected aggregation, expected histogram aggregation"),
+                            &range_req,
                             range_res.column_type,
                             limits,
                         )
@@ -457,12 +522,7 @@ impl IntermediateBucketResult {
                         .total_cmp(&right.from.unwrap_or(f64::MIN))
                 });
 
-                let is_keyed = req
-                    .agg
-                    .as_range()
-                    .expect("unexpected aggregation, expected range aggregation")
-                    .keyed;
-                let buckets = if is_keyed {
+                let buckets = if range_req.keyed {
                     let mut bucket_map =
                         FxHashMap::with_capacity_and_hasher(buckets.len(), Default::default());
                     for bucket in buckets {
@@ -509,6 +569,21 @@ impl IntermediateBucketResult {
                 req.sub_aggregation(),
                 limits,
             ),
+            IntermediateBucketResult::Filter(bucket) => Ok(BucketResult::Filter(
+                bucket.into_final_bucket_entry(req.sub_aggregation(), limits)?,
+            )),
+            IntermediateBucketResult::Filters { buckets } => {
+                let buckets = buckets
+                    .into_iter()
+                    .map(|(key, bucket)| {
+                        Ok((
+                            key,
+                            bucket.into_final_bucket_entry(req.sub_aggregation(), limits)?,
+                        ))
+                    })
+                    .collect::<crate::Result<_>>()?;
+                Ok(BucketResult::Filters { buckets })
+            }
         }
     }
 
@@ -562,6 +637,22 @@ impl IntermediateBucketResult {
 
                 *buckets_left = buckets?;
             }
+            (
+                IntermediateBucketResult::Filter(bucket_left),
+                IntermediateBucketResult::Filter(bucket_right),
+            ) => {
+                bucket_left.merge_fruits(bucket_right)?;
+            }
+            (
+                IntermediateBucketResult::Filters {
+                    buckets: buckets_left,
+                },
+                IntermediateBucketResult::Filters {
+                    buckets: buckets_right,
+                },
+            ) => {
+                merge_maps(buckets_left, buckets_right)?;
+            }
             (IntermediateBucketResult::Range(_), _) => {
                 panic!("try merge on different types")
             }
@@ -571,6 +662,12 @@ impl IntermediateBucketResult {
             (IntermediateBucketResult::Terms { .. }, _) => {
                 panic!("try merge on different types")
             }
+            (IntermediateBucketResult::Filter(_), _) => {
+                panic!("try merge on different types")
+            }
+            (IntermediateBucketResult::Filters { .. }, _) => {
+                panic!("try merge on different types")
+            }
         }
         Ok(())
     }
@@ -799,6 +896,39 @@ pub struct IntermediateTermBucketEntry {
     pub sub_aggregation: IntermediateAggregationResults,
 }
 
+/// This is the bucket of a filter or global aggregation, or one of the buckets of a filters
+/// aggregation.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IntermediateFilterBucketEntry {
+    /// The number of documents in the bucket.
+    pub doc_count: u64,
+    /// The sub_aggregation in this bucket.
+    pub sub_aggregation: IntermediateAggregationResults,
+}
+
+impl IntermediateFilterBucketEntry {
+    pub(crate) fn into_final_bucket_entry(
+        self,
+        req: &Aggregations,
+        limits: &mut AggregationLimitsGuard,
+    ) -> crate::Result<FilterBucketEntry> {
+        Ok(FilterBucketEntry {
+            doc_count: self.doc_count,
+            sub_aggregation: self
+                .sub_aggregation
+                .into_final_result_internal(req, limits)?,
+        })
+    }
+}
+
+impl MergeFruits for IntermediateFilterBucketEntry {
+    fn merge_fruits(&mut self, other: IntermediateFilterBucketEntry) -> crate::Result<()> {
+        self.doc_count += other.doc_count;
+        self.sub_aggregation.merge_fruits(other.sub_aggregation)?;
+        Ok(())
+    }
+}
+
 impl MergeFruits for IntermediateTermBucketEntry {
     fn merge_fruits(&mut self, other: IntermediateTermBucketEntry) -> crate::Result<()> {
         self.doc_count += other.doc_count;
@@ -158,9 +158,9 @@ impl ExtendedStats {
                 .std_deviation_bounds
                 .as_ref()
                 .map(|bounds| bounds.upper_sampling)),
-            _ => Err(TantivyError::InvalidArgument(format!(
-                "Unknown property {agg_property} on stats metric aggregation"
-            ))),
+            _ => Err(TantivyError::InvalidArgument(
+                format!("Unknown property {agg_property} on stats metric aggregation").into(),
+            )),
         }
     }
 }
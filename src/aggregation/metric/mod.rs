@@ -42,7 +42,9 @@ pub use stats::*;
 pub use sum::*;
 pub use top_hits::*;
 
+use super::AggregationError;
 use crate::schema::OwnedValue;
+use crate::TantivyError;
 
 /// Single-metric aggregations use this common result structure.
 ///
@@ -92,6 +94,32 @@ pub struct PercentilesMetricResult {
     pub values: PercentileValues,
 }
 
+impl PercentilesMetricResult {
+    /// Returns the value of the percentile given as `agg_property`, e.g. "99" or "99.9".
+    ///
+    /// `None` is returned if there were no values to compute the percentile on.
+    pub(crate) fn get_value(&self, agg_property: &str) -> crate::Result<Option<f64>> {
+        let not_requested = || {
+            TantivyError::AggregationError(AggregationError::InvalidRequest(format!(
+                "percentile {agg_property:?} is not part of the requested percentiles"
+            )))
+        };
+        let percent: f64 = agg_property.parse().map_err(|_| not_requested())?;
+        let value = match &self.values {
+            PercentileValues::Vec(entries) => entries
+                .iter()
+                .find(|entry| entry.key == percent)
+                .map(|entry| entry.value),
+            PercentileValues::HashMap(entries) => entries
+                .iter()
+                .find(|(key, _)| key.parse::<f64>().ok() == Some(percent))
+                .map(|(_, value)| *value),
+        }
+        .ok_or_else(not_requested)?;
+        Ok(Some(value).filter(|value| !value.is_nan()))
+    }
+}
+
 /// The top_hits metric results entry
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TopHitsVecEntry {
@@ -112,6 +112,14 @@ impl PercentilesAggregationReq {
         &self.field
     }
 
+    /// Returns the requested percents, or the default ones if none are set.
+    pub(crate) fn percents_or_default(&self) -> &[f64] {
+        self.percents
+            .as_ref()
+            .map(|el| el.as_ref())
+            .unwrap_or(default_percentiles())
+    }
+
     fn validate(&self) -> crate::Result<()> {
         if let Some(percents) = self.percents.as_ref() {
             let all_in_range = percents
@@ -176,11 +184,7 @@ impl PercentilesCollector {
     /// Convert result into final result. This will query the quantils from the underlying quantil
     /// collector.
     pub fn into_final_result(self, req: &PercentilesAggregationReq) -> PercentilesMetricResult {
-        let percentiles: &[f64] = req
-            .percents
-            .as_ref()
-            .map(|el| el.as_ref())
-            .unwrap_or(default_percentiles());
+        let percentiles: &[f64] = req.percents_or_default();
         let iter_quantile_and_values = percentiles.iter().cloned().map(|percentile| {
             (
                 percentile,
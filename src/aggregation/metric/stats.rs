@@ -75,9 +75,9 @@ impl Stats {
             "min" => Ok(self.min),
             "max" => Ok(self.max),
             "avg" => Ok(self.avg),
-            _ => Err(TantivyError::InvalidArgument(format!(
-                "Unknown property {agg_property} on stats metric aggregation"
-            ))),
+            _ => Err(TantivyError::InvalidArgument(
+                format!("Unknown property {agg_property} on stats metric aggregation").into(),
+            )),
         }
     }
 }
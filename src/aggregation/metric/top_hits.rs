@@ -7,6 +7,7 @@ use common::DateTime;
 use regex::Regex;
 use serde::ser::SerializeMap;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use uuid::Uuid;
 
 use super::{TopHitsMetricResult, TopHitsVecEntry};
 use crate::aggregation::bucket::Order;
@@ -299,6 +300,10 @@ impl TopHitsAggregationReq {
                             .values_for_doc(doc_id)
                             .map(FastFieldValue::IpAddr)
                             .collect::<Vec<_>>(),
+                        DynamicColumn::Uuid(accessor) => accessor
+                            .values_for_doc(doc_id)
+                            .map(FastFieldValue::Uuid)
+                            .collect::<Vec<_>>(),
                         DynamicColumn::DateTime(accessor) => accessor
                             .values_for_doc(doc_id)
                             .map(FastFieldValue::Date)
@@ -332,6 +337,8 @@ pub enum FastFieldValue {
     Bytes(Vec<u8>),
     /// IpV6 Address. Internally there is no IpV4, it needs to be converted to `Ipv6Addr`.
     IpAddr(Ipv6Addr),
+    /// Uuid
+    Uuid(Uuid),
     /// A list of values.
     Array(Vec<Self>),
 }
@@ -347,6 +354,7 @@ impl From<FastFieldValue> for OwnedValue {
             FastFieldValue::Date(d) => OwnedValue::Date(d),
             FastFieldValue::Bytes(b) => OwnedValue::Bytes(b),
             FastFieldValue::IpAddr(ip) => OwnedValue::IpAddr(ip),
+            FastFieldValue::Uuid(uuid) => OwnedValue::Uuid(uuid),
             FastFieldValue::Array(a) => {
                 OwnedValue::Array(a.into_iter().map(OwnedValue::from).collect())
             }
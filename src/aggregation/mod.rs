@@ -40,6 +40,7 @@
 //!     - [Histogram](bucket::HistogramAggregation)
 //!     - [DateHistogram](bucket::DateHistogramAggregationReq)
 //!     - [Range](bucket::RangeAggregation)
+//!     - [GeoDistance](bucket::GeoDistanceAggregation)
 //!     - [Terms](bucket::TermsAggregation)
 //! - [Metric](metric)
 //!     - [Average](metric::AverageAggregation)
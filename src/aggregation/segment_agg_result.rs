@@ -5,10 +5,14 @@
 
 use std::fmt::Debug;
 
+use columnar::ColumnType;
+
 pub(crate) use super::agg_limits::AggregationLimitsGuard;
 use super::agg_req::AggregationVariants;
 use super::agg_req_with_accessor::{AggregationWithAccessor, AggregationsWithAccessor};
-use super::bucket::{SegmentHistogramCollector, SegmentRangeCollector, SegmentTermCollector};
+use super::bucket::{
+    SegmentFilterCollector, SegmentHistogramCollector, SegmentRangeCollector, SegmentTermCollector,
+};
 use super::intermediate_agg_result::IntermediateAggregationResults;
 use super::metric::{
     AverageAggregation, CountAggregation, ExtendedStatsAggregation, MaxAggregation, MinAggregation,
@@ -107,6 +111,16 @@ pub(crate) fn build_single_agg_segment_collector(
             req.field_type,
             accessor_idx,
         )?)),
+        GeoDistance(geo_distance_req) => Ok(Box::new(
+            SegmentRangeCollector::from_req_and_validate(
+                &geo_distance_req.to_range_req(),
+                &mut req.sub_aggregation,
+                &mut req.limits,
+                ColumnType::F64,
+                accessor_idx,
+            )?
+            .with_geo_distance(geo_distance_req.distance_mapping()?),
+        )),
         Histogram(histogram) => Ok(Box::new(SegmentHistogramCollector::from_req_and_validate(
             histogram.clone(),
             &mut req.sub_aggregation,
@@ -119,6 +133,11 @@ pub(crate) fn build_single_agg_segment_collector(
             req.field_type,
             accessor_idx,
         )?)),
+        Filter(_) | Filters(_) | Global(_) => Ok(Box::new(SegmentFilterCollector::from_req(
+            &req.agg.agg,
+            &mut req.sub_aggregation,
+            accessor_idx,
+        )?)),
         Average(AverageAggregation { missing, .. }) => {
             Ok(Box::new(SegmentStatsCollector::from_req(
                 req.field_type,
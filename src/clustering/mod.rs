@@ -0,0 +1,552 @@
+//! [`ResultClusterer`]
+//! Groups the top hits of a search by topic.
+//!
+//! The hits are clustered using spherical k-means, either over term vectors computed from their
+//! stored text fields, or over embeddings provided by the caller. Each cluster is labeled with
+//! its most characteristic terms, which makes it possible to display "search results grouped by
+//! topic".
+//!
+//! Clustering is deterministic: the same hits always yield the same clusters.
+//!
+//! ## Example
+//!
+//! ```rust
+//! # use tantivy::collector::TopDocs;
+//! # use tantivy::query::QueryParser;
+//! # use tantivy::schema::{Schema, STORED, TEXT};
+//! # use tantivy::{doc, Index};
+//! use tantivy::clustering::ResultClusterer;
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! # let mut schema_builder = Schema::builder();
+//! # let title = schema_builder.add_text_field("title", TEXT | STORED);
+//! # let index = Index::create_in_ram(schema_builder.build());
+//! # let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+//! # index_writer.add_document(doc!(title => "jaguar speed of the big cat"))?;
+//! # index_writer.add_document(doc!(title => "jaguar car engine speed"))?;
+//! # index_writer.add_document(doc!(title => "the jaguar is a big cat"))?;
+//! # index_writer.add_document(doc!(title => "jaguar car dealership"))?;
+//! # index_writer.commit()?;
+//! # let searcher = index.reader()?.searcher();
+//! let query = QueryParser::for_index(&index, vec![title]).parse_query("jaguar")?;
+//! let top_docs = searcher.search(&query, &TopDocs::with_limit(100))?;
+//! let mut clusterer = ResultClusterer::create(&searcher, vec![title])?;
+//! clusterer.set_num_clusters(2);
+//! let clusters = clusterer.cluster(&top_docs)?;
+//! assert_eq!(clusters.len(), 2);
+//! for cluster in &clusters {
+//!     assert_eq!(cluster.hits.len(), 2);
+//! }
+//! let labels: Vec<&[String]> = clusters.iter().map(|cluster| &cluster.labels[..]).collect();
+//! assert!(labels.iter().any(|labels| labels.contains(&"cat".to_string())));
+//! assert!(labels.iter().any(|labels| labels.contains(&"car".to_string())));
+//! #     Ok(())
+//! # }
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use crate::schema::document::Value;
+use crate::schema::{Field, FieldType};
+use crate::tokenizer::TextAnalyzer;
+use crate::{DocAddress, Searcher, TantivyDocument, TantivyError, Term};
+
+const DEFAULT_NUM_CLUSTERS: usize = 5;
+const DEFAULT_NUM_LABELS: usize = 3;
+const DEFAULT_MAX_ITERATIONS: usize = 20;
+
+/// A group of hits, returned by [`ResultClusterer`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResultCluster {
+    /// Most characteristic terms of the cluster, by decreasing weight.
+    pub labels: Vec<String>,
+    /// Positions of the hits of the cluster in the clustered hits, in increasing order.
+    pub hits: Vec<usize>,
+}
+
+/// Clusters the top hits of a search.
+///
+/// See the [module documentation](crate::clustering) for more detail.
+pub struct ResultClusterer {
+    searcher: Searcher,
+    fields: Vec<(Field, TextAnalyzer)>,
+    num_clusters: usize,
+    num_labels: usize,
+    max_iterations: usize,
+}
+
+impl ResultClusterer {
+    /// Creates a new result clusterer.
+    ///
+    /// Term vectors are computed from the values of `fields`, which need to be stored and
+    /// indexed text fields.
+    pub fn create(searcher: &Searcher, fields: Vec<Field>) -> crate::Result<ResultClusterer> {
+        let schema = searcher.schema();
+        let fields = fields
+            .into_iter()
+            .map(|field| {
+                let field_entry = schema.get_field_entry(field);
+                if !matches!(field_entry.field_type(), FieldType::Str(_))
+                    || !field_entry.is_stored()
+                {
+                    return Err(TantivyError::SchemaError(format!(
+                        "{:?} is not a stored text field.",
+                        field_entry.name()
+                    )));
+                }
+                let tokenizer = searcher.index().tokenizer_for_field(field)?;
+                Ok((field, tokenizer))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(ResultClusterer {
+            searcher: searcher.clone(),
+            fields,
+            num_clusters: DEFAULT_NUM_CLUSTERS,
+            num_labels: DEFAULT_NUM_LABELS,
+            max_iterations: DEFAULT_MAX_ITERATIONS,
+        })
+    }
+
+    /// Sets the maximum number of clusters. Default is 5.
+    ///
+    /// Fewer clusters are returned if there are fewer distinct hits.
+    pub fn set_num_clusters(&mut self, num_clusters: usize) {
+        assert!(num_clusters > 0, "At least one cluster is required.");
+        self.num_clusters = num_clusters;
+    }
+
+    /// Sets the number of labels of each cluster. Default is 3.
+    pub fn set_num_labels(&mut self, num_labels: usize) {
+        self.num_labels = num_labels;
+    }
+
+    /// Sets the maximum number of k-means iterations. Default is 20.
+    pub fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    /// Clusters the hits by their term vectors.
+    ///
+    /// Hits without any term are grouped in a last cluster, with no labels.
+    pub fn cluster<S>(&self, hits: &[(S, DocAddress)]) -> crate::Result<Vec<ResultCluster>> {
+        let term_vectors = self.term_vectors(hits)?;
+        let assignments = spherical_kmeans(
+            &term_vectors.vectors,
+            term_vectors.vocabulary.len(),
+            self.num_clusters,
+            self.max_iterations,
+        );
+        Ok(self.labeled_clusters(&assignments, &term_vectors))
+    }
+
+    /// Clusters the hits by the given embeddings, one per hit, using the cosine similarity.
+    ///
+    /// The clusters are labeled using the term vectors of the hits. Hits with a zero embedding
+    /// are grouped in a last cluster.
+    pub fn cluster_embeddings<S>(
+        &self,
+        hits: &[(S, DocAddress)],
+        embeddings: &[Vec<f32>],
+    ) -> crate::Result<Vec<ResultCluster>> {
+        if embeddings.len() != hits.len() {
+            return Err(TantivyError::InvalidArgument(
+                format!(
+                    "Expected one embedding per hit, got {} embeddings for {} hits.",
+                    embeddings.len(),
+                    hits.len()
+                )
+                .into(),
+            ));
+        }
+        let dimension = embeddings.first().map(Vec::len).unwrap_or(0);
+        if embeddings
+            .iter()
+            .any(|embedding| embedding.len() != dimension)
+        {
+            return Err(TantivyError::InvalidArgument(
+                "All of the embeddings need to have the same dimension.".into(),
+            ));
+        }
+        let vectors: Vec<SparseVector> = embeddings
+            .iter()
+            .map(|embedding| SparseVector::normalized(embedding.iter().copied().enumerate()))
+            .collect();
+        let assignments =
+            spherical_kmeans(&vectors, dimension, self.num_clusters, self.max_iterations);
+        let term_vectors = self.term_vectors(hits)?;
+        Ok(self.labeled_clusters(&assignments, &term_vectors))
+    }
+
+    fn term_vectors<S>(&self, hits: &[(S, DocAddress)]) -> crate::Result<TermVectors> {
+        let mut vocabulary: Vec<Term> = Vec::new();
+        let mut term_ords: HashMap<Term, usize> = HashMap::new();
+        let mut term_freqs_per_hit: Vec<HashMap<usize, u32>> = Vec::with_capacity(hits.len());
+        for (_, doc_address) in hits {
+            let mut term_freqs: HashMap<usize, u32> = HashMap::new();
+            if !self.fields.is_empty() {
+                let doc: TantivyDocument = self.searcher.doc(*doc_address)?;
+                for (field, tokenizer) in &self.fields {
+                    let mut tokenizer = tokenizer.clone();
+                    for value in doc.get_all(*field) {
+                        let Some(text) = value.as_str() else {
+                            continue;
+                        };
+                        let mut token_stream = tokenizer.token_stream(text);
+                        token_stream.process(&mut |token| {
+                            let term = Term::from_field_text(*field, &token.text);
+                            let term_ord = *term_ords.entry(term.clone()).or_insert_with(|| {
+                                vocabulary.push(term);
+                                vocabulary.len() - 1
+                            });
+                            *term_freqs.entry(term_ord).or_default() += 1;
+                        });
+                    }
+                }
+            }
+            term_freqs_per_hit.push(term_freqs);
+        }
+        let num_docs = self.searcher.num_docs() as f32;
+        let idfs = vocabulary
+            .iter()
+            .map(|term| {
+                let doc_freq = self.searcher.doc_freq(term)? as f32;
+                Ok((1.0 + num_docs / (1.0 + doc_freq)).ln())
+            })
+            .collect::<crate::Result<Vec<f32>>>()?;
+        let vectors = term_freqs_per_hit
+            .into_iter()
+            .map(|term_freqs| {
+                SparseVector::normalized(term_freqs.into_iter().map(|(term_ord, term_freq)| {
+                    (term_ord, (1.0 + (term_freq as f32).ln()) * idfs[term_ord])
+                }))
+            })
+            .collect();
+        let vocabulary = vocabulary
+            .iter()
+            .map(|term| term.value().as_str().unwrap_or_default().to_string())
+            .collect();
+        Ok(TermVectors {
+            vocabulary,
+            vectors,
+        })
+    }
+
+    fn labeled_clusters(
+        &self,
+        assignments: &[Option<usize>],
+        term_vectors: &TermVectors,
+    ) -> Vec<ResultCluster> {
+        let num_clusters = assignments.iter().flatten().max().map_or(0, |max| max + 1);
+        let mut clusters_hits: Vec<Vec<usize>> = vec![Vec::new(); num_clusters];
+        let mut unassigned_hits = Vec::new();
+        for (hit, assignment) in assignments.iter().enumerate() {
+            match assignment {
+                Some(cluster) => clusters_hits[*cluster].push(hit),
+                None => unassigned_hits.push(hit),
+            }
+        }
+        clusters_hits.retain(|hits| !hits.is_empty());
+        // Larger clusters first, then clusters containing the best hits.
+        clusters_hits.sort_by(|left, right| {
+            right
+                .len()
+                .cmp(&left.len())
+                .then_with(|| left[0].cmp(&right[0]))
+        });
+        let mut clusters: Vec<ResultCluster> = clusters_hits
+            .into_iter()
+            .map(|hits| ResultCluster {
+                labels: self.labels(&hits, term_vectors),
+                hits,
+            })
+            .collect();
+        if !unassigned_hits.is_empty() {
+            clusters.push(ResultCluster {
+                labels: Vec::new(),
+                hits: unassigned_hits,
+            });
+        }
+        clusters
+    }
+
+    fn labels(&self, hits: &[usize], term_vectors: &TermVectors) -> Vec<String> {
+        let mut term_weights: HashMap<usize, f32> = HashMap::new();
+        for &hit in hits {
+            for &(term_ord, weight) in &term_vectors.vectors[hit].0 {
+                *term_weights.entry(term_ord).or_default() += weight;
+            }
+        }
+        let mut term_weights: Vec<(usize, f32)> = term_weights.into_iter().collect();
+        term_weights.sort_by(|(left_ord, left_weight), (right_ord, right_weight)| {
+            right_weight
+                .partial_cmp(left_weight)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| {
+                    term_vectors.vocabulary[*left_ord].cmp(&term_vectors.vocabulary[*right_ord])
+                })
+        });
+        term_weights
+            .into_iter()
+            .take(self.num_labels)
+            .map(|(term_ord, _)| term_vectors.vocabulary[term_ord].clone())
+            .collect()
+    }
+}
+
+struct TermVectors {
+    vocabulary: Vec<String>,
+    vectors: Vec<SparseVector>,
+}
+
+/// A vector with unit norm, or the zero vector.
+struct SparseVector(Vec<(usize, f32)>);
+
+impl SparseVector {
+    fn normalized(components: impl Iterator<Item = (usize, f32)>) -> SparseVector {
+        let mut components: Vec<(usize, f32)> =
+            components.filter(|(_, value)| *value != 0.0).collect();
+        components.sort_by_key(|(dimension, _)| *dimension);
+        let norm = components
+            .iter()
+            .map(|(_, value)| value * value)
+            .sum::<f32>()
+            .sqrt();
+        if !norm.is_normal() {
+            return SparseVector(Vec::new());
+        }
+        for (_, value) in &mut components {
+            *value /= norm;
+        }
+        SparseVector(components)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn dot(&self, centroid: &[f32]) -> f32 {
+        self.0
+            .iter()
+            .map(|&(dimension, value)| value * centroid[dimension])
+            .sum()
+    }
+
+    fn add_to(&self, centroid: &mut [f32]) {
+        for &(dimension, value) in &self.0 {
+            centroid[dimension] += value;
+        }
+    }
+}
+
+fn normalize(centroid: &mut [f32]) {
+    let norm = centroid
+        .iter()
+        .map(|value| value * value)
+        .sum::<f32>()
+        .sqrt();
+    if norm.is_normal() {
+        for value in centroid.iter_mut() {
+            *value /= norm;
+        }
+    }
+}
+
+fn closest_centroid(vector: &SparseVector, centroids: &[Vec<f32>]) -> (usize, f32) {
+    centroids
+        .iter()
+        .map(|centroid| vector.dot(centroid))
+        .enumerate()
+        .fold((0, f32::NEG_INFINITY), |best, (cluster, similarity)| {
+            if similarity > best.1 {
+                (cluster, similarity)
+            } else {
+                best
+            }
+        })
+}
+
+/// Returns the cluster of each vector, or `None` for zero vectors.
+///
+/// Centroids are initialized with the farthest-first traversal of the vectors, starting from the
+/// first one: this makes the clustering deterministic, and favors clusters around the best hits.
+fn spherical_kmeans(
+    vectors: &[SparseVector],
+    dimension: usize,
+    num_clusters: usize,
+    max_iterations: usize,
+) -> Vec<Option<usize>> {
+    let mut centroids: Vec<Vec<f32>> = Vec::new();
+    while centroids.len() < num_clusters {
+        // The vector least similar to the current centroids.
+        let farthest = vectors
+            .iter()
+            .enumerate()
+            .filter(|(_, vector)| !vector.is_zero())
+            .map(|(ord, vector)| {
+                let similarity = if centroids.is_empty() {
+                    0.0
+                } else {
+                    closest_centroid(vector, &centroids).1
+                };
+                (ord, similarity)
+            })
+            .fold(
+                None,
+                |farthest: Option<(usize, f32)>, (ord, similarity)| match farthest {
+                    Some((_, farthest_similarity)) if farthest_similarity <= similarity => farthest,
+                    _ => Some((ord, similarity)),
+                },
+            );
+        let Some((farthest_ord, farthest_similarity)) = farthest else {
+            break;
+        };
+        if !centroids.is_empty() && farthest_similarity >= 1.0 - 1e-6 {
+            // All of the remaining vectors are duplicates of the centroids.
+            break;
+        }
+        let mut centroid = vec![0.0; dimension];
+        vectors[farthest_ord].add_to(&mut centroid);
+        centroids.push(centroid);
+    }
+    let mut assignments: Vec<Option<usize>> = vec![None; vectors.len()];
+    for _ in 0..max_iterations.max(1) {
+        let new_assignments: Vec<Option<usize>> = vectors
+            .iter()
+            .map(|vector| {
+                if vector.is_zero() {
+                    None
+                } else {
+                    Some(closest_centroid(vector, &centroids).0)
+                }
+            })
+            .collect();
+        if new_assignments == assignments {
+            break;
+        }
+        assignments = new_assignments;
+        for centroid in &mut centroids {
+            centroid.fill(0.0);
+        }
+        for (vector, assignment) in vectors.iter().zip(&assignments) {
+            if let Some(cluster) = assignment {
+                vector.add_to(&mut centroids[*cluster]);
+            }
+        }
+        for centroid in &mut centroids {
+            normalize(centroid);
+        }
+    }
+    assignments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ResultCluster, ResultClusterer};
+    use crate::collector::TopDocs;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, STORED, TEXT};
+    use crate::{DocAddress, Index, IndexWriter, Searcher, TantivyError};
+
+    const TITLES: [&str; 7] = [
+        "jaguar big cat of the jungle",
+        "the jaguar car engine",
+        "a big cat hunting in the jungle",
+        "second hand jaguar car",
+        "",
+        "jungle cat cubs",
+        "car engine repair",
+    ];
+
+    fn searcher_and_hits() -> crate::Result<(Searcher, Vec<(f32, DocAddress)>)> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for text in TITLES {
+            index_writer.add_document(doc!(title => text))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let mut hits = searcher.search(&AllQuery, &TopDocs::with_limit(10))?;
+        hits.sort_by_key(|(_, doc_address)| *doc_address);
+        Ok((searcher, hits))
+    }
+
+    fn clusterer(searcher: &Searcher) -> crate::Result<ResultClusterer> {
+        let title = searcher.schema().get_field("title")?;
+        let mut clusterer = ResultClusterer::create(searcher, vec![title])?;
+        clusterer.set_num_clusters(2);
+        clusterer.set_num_labels(2);
+        Ok(clusterer)
+    }
+
+    #[test]
+    fn test_cluster_by_terms() -> crate::Result<()> {
+        let (searcher, hits) = searcher_and_hits()?;
+        let clusters = clusterer(&searcher)?.cluster(&hits)?;
+        assert_eq!(
+            clusters,
+            vec![
+                ResultCluster {
+                    labels: vec!["cat".to_string(), "jungle".to_string()],
+                    hits: vec![0, 2, 5],
+                },
+                ResultCluster {
+                    labels: vec!["car".to_string(), "engine".to_string()],
+                    hits: vec![1, 3, 6],
+                },
+                ResultCluster {
+                    labels: Vec::new(),
+                    hits: vec![4],
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cluster_embeddings() -> crate::Result<()> {
+        let (searcher, hits) = searcher_and_hits()?;
+        let embeddings: Vec<Vec<f32>> = vec![
+            vec![1.0, 0.1],
+            vec![0.1, 1.0],
+            vec![2.0, 0.0],
+            vec![0.0, 0.5],
+            vec![0.0, 0.0],
+            vec![0.9, 0.2],
+            vec![0.3, 3.0],
+        ];
+        let clusters = clusterer(&searcher)?.cluster_embeddings(&hits, &embeddings)?;
+        let cluster_hits: Vec<&[usize]> =
+            clusters.iter().map(|cluster| &cluster.hits[..]).collect();
+        assert_eq!(cluster_hits, vec![&[0, 2, 5][..], &[1, 3, 6], &[4]]);
+        assert_eq!(clusters[0].labels, vec!["cat", "jungle"]);
+
+        let res = clusterer(&searcher)?.cluster_embeddings(&hits, &embeddings[1..]);
+        assert!(matches!(res, Err(TantivyError::InvalidArgument(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cluster_duplicates() -> crate::Result<()> {
+        let (searcher, hits) = searcher_and_hits()?;
+        let duplicated_hits = vec![hits[1], hits[1], hits[1]];
+        let clusters = clusterer(&searcher)?.cluster(&duplicated_hits)?;
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].hits, vec![0, 1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cluster_requires_stored_text_fields() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader().unwrap().searcher();
+        assert!(matches!(
+            ResultClusterer::create(&searcher, vec![title]),
+            Err(TantivyError::SchemaError(_))
+        ));
+    }
+}
@@ -0,0 +1,286 @@
+use crate::collector::top_collector::{TopCollector, TopSegmentCollector};
+use crate::collector::{Collector, SegmentCollector, TopNComputer};
+use crate::query::Weight;
+use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// Best scoring documents found by [`ApproximateTopDocs`], along with the bound on the score of
+/// the documents that were missed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApproximateTopDocsResult {
+    /// Scores and addresses of the documents, by decreasing score.
+    pub docs: Vec<(Score, DocAddress)>,
+    /// Upper bound of the score of the matching documents which are not part of `docs`.
+    ///
+    /// `None` if all of the matching documents are part of `docs`.
+    pub max_missed_score: Option<Score>,
+}
+
+impl ApproximateTopDocsResult {
+    /// Returns true if no document scoring strictly more than the last document of `docs` can
+    /// have been missed, i.e. if the result is the one [`TopDocs`](crate::collector::TopDocs)
+    /// would have returned, up to ties.
+    pub fn is_exact(&self) -> bool {
+        match (self.max_missed_score, self.docs.last()) {
+            (Some(max_missed_score), Some((last_score, _))) => max_missed_score <= *last_score,
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+    }
+
+    fn new(
+        docs: Vec<(Score, DocAddress)>,
+        heap_len: usize,
+        max_missed_score: Option<Score>,
+    ) -> ApproximateTopDocsResult {
+        // Documents can only have been missed if the heap was full.
+        let max_missed_score =
+            docs.last()
+                .filter(|_| docs.len() >= heap_len)
+                .map(|(last_score, _)| {
+                    max_missed_score.map_or(*last_score, |score| score.max(*last_score))
+                });
+        ApproximateTopDocsResult {
+            docs,
+            max_missed_score,
+        }
+    }
+}
+
+/// Collects the best scoring documents, trading exactness for speed.
+///
+/// See [`TopDocs::approximate()`](crate::collector::TopDocs::approximate).
+pub struct ApproximateTopDocs {
+    top_collector: TopCollector<Score>,
+    recall_target: Score,
+}
+
+impl ApproximateTopDocs {
+    pub(crate) fn new(top_collector: TopCollector<Score>, recall_target: Score) -> Self {
+        assert!(
+            recall_target > 0.0 && recall_target <= 1.0,
+            "Recall target must be within ]0, 1]."
+        );
+        ApproximateTopDocs {
+            top_collector,
+            recall_target,
+        }
+    }
+
+    fn heap_len(&self) -> usize {
+        self.top_collector.limit + self.top_collector.offset
+    }
+
+    /// Raises the threshold passed to the scorer, so that documents whose score upper bound is
+    /// within `recall_target` of the current threshold get skipped.
+    fn pruning_threshold(&self, threshold: Score) -> Score {
+        if threshold > 0.0 {
+            threshold / self.recall_target
+        } else {
+            threshold
+        }
+    }
+}
+
+impl Collector for ApproximateTopDocs {
+    type Fruit = ApproximateTopDocsResult;
+
+    type Child = ApproximateTopScoreSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        reader: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        Ok(ApproximateTopScoreSegmentCollector {
+            collector: self.top_collector.for_segment(segment_local_id, reader),
+            heap_len: self.heap_len(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        child_fruits: Vec<ApproximateTopDocsResult>,
+    ) -> crate::Result<ApproximateTopDocsResult> {
+        let mut max_missed_score: Option<Score> = None;
+        let mut top_n: TopNComputer<_, _> = TopNComputer::new(self.heap_len());
+        for child_fruit in child_fruits {
+            if let Some(child_max_missed_score) = child_fruit.max_missed_score {
+                max_missed_score = Some(max_missed_score.map_or(child_max_missed_score, |score| {
+                    score.max(child_max_missed_score)
+                }));
+            }
+            for (score, doc) in child_fruit.docs {
+                top_n.push(score, doc);
+            }
+        }
+        let docs: Vec<(Score, DocAddress)> = top_n
+            .into_sorted_vec()
+            .into_iter()
+            .map(|cdoc| (cdoc.feature, cdoc.doc))
+            .collect();
+        let mut result = ApproximateTopDocsResult::new(docs, self.heap_len(), max_missed_score);
+        result
+            .docs
+            .drain(..self.top_collector.offset.min(result.docs.len()));
+        Ok(result)
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+    ) -> crate::Result<ApproximateTopDocsResult> {
+        let mut top_n: TopNComputer<_, _> = TopNComputer::new(self.heap_len());
+        // Every document the scorer skipped scores at most the last threshold it was given.
+        let mut pruning_threshold = Score::MIN;
+        let alive_bitset_opt = reader.alive_bitset();
+        weight.for_each_pruning(Score::MIN, reader, &mut |doc, score| {
+            if alive_bitset_opt.map_or(true, |alive_bitset| alive_bitset.is_alive(doc)) {
+                top_n.push(score, doc);
+                pruning_threshold = self.pruning_threshold(top_n.threshold.unwrap_or(Score::MIN));
+            }
+            pruning_threshold
+        })?;
+        let docs = top_n
+            .into_sorted_vec()
+            .into_iter()
+            .map(|cdoc| {
+                (
+                    cdoc.feature,
+                    DocAddress {
+                        segment_ord,
+                        doc_id: cdoc.doc,
+                    },
+                )
+            })
+            .collect();
+        let max_missed_score = Some(pruning_threshold).filter(|&score| score > Score::MIN);
+        Ok(ApproximateTopDocsResult::new(
+            docs,
+            self.heap_len(),
+            max_missed_score,
+        ))
+    }
+}
+
+/// Segment Collector associated with [`ApproximateTopDocs`].
+///
+/// Documents collected one by one are all scored, so that the result of the segment is exact.
+pub struct ApproximateTopScoreSegmentCollector {
+    collector: TopSegmentCollector<Score>,
+    heap_len: usize,
+}
+
+impl SegmentCollector for ApproximateTopScoreSegmentCollector {
+    type Fruit = ApproximateTopDocsResult;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.collector.collect(doc, score);
+    }
+
+    fn harvest(self) -> ApproximateTopDocsResult {
+        ApproximateTopDocsResult::new(self.collector.harvest(), self.heap_len, None)
+    }
+}
+
+#[cfg(all(test, feature = "query-parser"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::collector::TopDocs;
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, TEXT};
+    use crate::{DocAddress, Index, IndexWriter, Score};
+
+    fn make_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..5_000usize {
+            let mut body = String::new();
+            for _ in 0..(i * 7) % 13 {
+                body.push_str(" apple");
+            }
+            for _ in 0..(i * 11) % 5 {
+                body.push_str(" banana");
+            }
+            for _ in 0..(i % 17) {
+                body.push_str(" cherry");
+            }
+            index_writer.add_document(doc!(text => body))?;
+        }
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn test_approximate_top_docs() -> crate::Result<()> {
+        let index = make_index()?;
+        let text = index.schema().get_field("text")?;
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("apple banana")?;
+        let searcher = index.reader()?.searcher();
+
+        let exact = searcher.search(&query, &TopDocs::with_limit(10))?;
+        let result = searcher.search(&query, &TopDocs::with_limit(10).approximate(1.0))?;
+        assert_eq!(result.docs, exact);
+        assert!(result.is_exact());
+
+        let result = searcher.search(&query, &TopDocs::with_limit(10).approximate(0.5))?;
+        assert_eq!(result.docs.len(), 10);
+        let max_missed_score = result.max_missed_score.unwrap();
+        assert!(max_missed_score >= result.docs[9].0);
+        assert!(!result.is_exact());
+        let all_scores: HashMap<DocAddress, Score> = searcher
+            .search(&query, &TopDocs::with_limit(5_000))?
+            .into_iter()
+            .map(|(score, doc)| (doc, score))
+            .collect();
+        for (score, doc) in &result.docs {
+            assert_eq!(all_scores[doc], *score);
+        }
+        for (score, doc) in &exact {
+            if !result.docs.iter().any(|(_, result_doc)| result_doc == doc) {
+                assert!(*score <= max_missed_score);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_approximate_top_docs_all_matches() -> crate::Result<()> {
+        let index = make_index()?;
+        let text = index.schema().get_field("text")?;
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("cherry")?;
+        let searcher = index.reader()?.searcher();
+        let num_matches = searcher.search(&query, &crate::collector::Count)?;
+        let result = searcher.search(
+            &query,
+            &TopDocs::with_limit(num_matches + 1).approximate(0.1),
+        )?;
+        assert_eq!(result.docs.len(), num_matches);
+        assert_eq!(result.max_missed_score, None);
+        assert!(result.is_exact());
+        Ok(())
+    }
+
+    #[test]
+    fn test_approximate_top_docs_with_offset() -> crate::Result<()> {
+        let index = make_index()?;
+        let text = index.schema().get_field("text")?;
+        let query = QueryParser::for_index(&index, vec![text]).parse_query("apple cherry")?;
+        let searcher = index.reader()?.searcher();
+        let exact = searcher.search(&query, &TopDocs::with_limit(5).and_offset(3))?;
+        let result = searcher.search(
+            &query,
+            &TopDocs::with_limit(5).and_offset(3).approximate(1.0),
+        )?;
+        assert_eq!(result.docs, exact);
+        Ok(())
+    }
+}
@@ -0,0 +1,439 @@
+use std::fmt::Debug;
+use std::io;
+
+use columnar::{Column, DynamicColumn, HasAssociatedColumnType, StrColumn};
+
+use crate::collector::top_collector::{TopCollector, TopSegmentCollector};
+use crate::collector::{Collector, SegmentCollector};
+use crate::{DateTime, DocAddress, DocId, Result, Score, SegmentReader, COLLECT_BLOCK_BUFFER_LEN};
+
+/// Typed access to the fast field columns of a segment, handed to a [`ColumnarScoreTweaker`]
+/// when it prepares the tweaking of the scores of a segment.
+///
+/// Columns are meant to be opened once per segment. Accessing a field that has no fast field
+/// values in the segment returns an empty column, for which all documents are missing a value.
+pub struct SegmentColumns<'a> {
+    segment_reader: &'a SegmentReader,
+}
+
+impl<'a> SegmentColumns<'a> {
+    pub(crate) fn new(segment_reader: &'a SegmentReader) -> SegmentColumns<'a> {
+        SegmentColumns { segment_reader }
+    }
+
+    /// Returns the reader of the segment.
+    pub fn segment_reader(&self) -> &'a SegmentReader {
+        self.segment_reader
+    }
+
+    fn column<T>(&self, field_name: &str) -> Result<BlockColumn<T>>
+    where
+        T: HasAssociatedColumnType + PartialOrd + Default + Copy + Debug + Send + Sync + 'static,
+        DynamicColumn: Into<Option<Column<T>>>,
+    {
+        let column = self
+            .segment_reader
+            .fast_fields()
+            .column_opt(field_name)?
+            .unwrap_or_else(|| Column::build_empty_column(self.segment_reader.max_doc()));
+        Ok(BlockColumn::new(column))
+    }
+
+    /// Returns the `u64` column of a field.
+    pub fn u64(&self, field_name: &str) -> Result<BlockColumn<u64>> {
+        self.column(field_name)
+    }
+
+    /// Returns the `i64` column of a field.
+    pub fn i64(&self, field_name: &str) -> Result<BlockColumn<i64>> {
+        self.column(field_name)
+    }
+
+    /// Returns the `f64` column of a field.
+    pub fn f64(&self, field_name: &str) -> Result<BlockColumn<f64>> {
+        self.column(field_name)
+    }
+
+    /// Returns the `bool` column of a field.
+    pub fn bool(&self, field_name: &str) -> Result<BlockColumn<bool>> {
+        self.column(field_name)
+    }
+
+    /// Returns the date column of a field.
+    pub fn date(&self, field_name: &str) -> Result<BlockColumn<DateTime>> {
+        self.column(field_name)
+    }
+
+    /// Returns the str column of a field.
+    pub fn str(&self, field_name: &str) -> Result<BlockStrColumn> {
+        let str_column_opt = self.segment_reader.fast_fields().str(field_name)?;
+        let term_ords = match &str_column_opt {
+            Some(str_column) => str_column.ords().clone(),
+            None => Column::build_empty_column(self.segment_reader.max_doc()),
+        };
+        Ok(BlockStrColumn {
+            str_column_opt,
+            term_ords: BlockColumn::new(term_ords),
+        })
+    }
+}
+
+/// A column, along with a buffer to fetch the values of a block of documents at once.
+pub struct BlockColumn<T> {
+    column: Column<T>,
+    values: Vec<Option<T>>,
+}
+
+impl<T: PartialOrd + Copy + Debug + Send + Sync + 'static> BlockColumn<T> {
+    fn new(column: Column<T>) -> BlockColumn<T> {
+        BlockColumn {
+            column,
+            values: Vec::with_capacity(COLLECT_BLOCK_BUFFER_LEN),
+        }
+    }
+
+    /// Fetches the first value of each of the documents, or `None` if a document has no value.
+    pub fn fetch(&mut self, docs: &[DocId]) -> &[Option<T>] {
+        self.values.clear();
+        self.values.resize(docs.len(), None);
+        self.column.first_vals(docs, &mut self.values);
+        &self.values
+    }
+
+    /// Returns all of the values of a document, e.g. for multivalued fields.
+    pub fn values_for_doc(&self, doc: DocId) -> impl Iterator<Item = T> + '_ {
+        self.column.values_for_doc(doc)
+    }
+
+    /// Returns the underlying column.
+    pub fn column(&self) -> &Column<T> {
+        &self.column
+    }
+}
+
+/// A str column, whose values are fetched as term ordinals.
+///
+/// Term ordinals follow the lexicographical order of the terms of the segment. Comparing
+/// ordinals, e.g. with the ordinal of a given term, is much cheaper than comparing terms.
+pub struct BlockStrColumn {
+    str_column_opt: Option<StrColumn>,
+    term_ords: BlockColumn<u64>,
+}
+
+impl BlockStrColumn {
+    /// Fetches the first term ordinal of each of the documents, or `None` if a document has no
+    /// value.
+    pub fn fetch_ords(&mut self, docs: &[DocId]) -> &[Option<u64>] {
+        self.term_ords.fetch(docs)
+    }
+
+    /// Returns all of the term ordinals of a document.
+    pub fn ords_for_doc(&self, doc: DocId) -> impl Iterator<Item = u64> + '_ {
+        self.term_ords.values_for_doc(doc)
+    }
+
+    /// Returns the term ordinal of `term`, or `None` if no document of the segment has it.
+    pub fn term_ord(&self, term: &str) -> io::Result<Option<u64>> {
+        match &self.str_column_opt {
+            Some(str_column) => str_column.dictionary().term_ord(term),
+            None => Ok(None),
+        }
+    }
+
+    /// Writes the term associated with `term_ord` into `output`.
+    ///
+    /// Returns false if the term ordinal does not exist.
+    pub fn ord_to_str(&self, term_ord: u64, output: &mut String) -> io::Result<bool> {
+        match &self.str_column_opt {
+            Some(str_column) => str_column.ord_to_str(term_ord, output),
+            None => Ok(false),
+        }
+    }
+}
+
+/// A `ColumnarScoreSegmentTweaker` tweaks the scores of the documents of a segment, a block of
+/// documents at a time.
+///
+/// It is the segment local version of the [`ColumnarScoreTweaker`].
+pub trait ColumnarScoreSegmentTweaker<TScore>: 'static {
+    /// Pushes the tweaked score of each of the documents `docs` to `tweaked_scores`, given their
+    /// raw `scores`.
+    ///
+    /// `tweaked_scores` is empty when the method is called.
+    fn score_block(&mut self, docs: &[DocId], scores: &[Score], tweaked_scores: &mut Vec<TScore>);
+}
+
+/// `ColumnarScoreTweaker` tweaks the scores emitted by the scorer, given the fast field values
+/// of the documents.
+///
+/// Unlike a [`ScoreTweaker`](crate::collector::ScoreTweaker), it is handed the
+/// [`SegmentColumns`] of each segment, and its segment tweakers score blocks of documents, which
+/// makes it possible to fetch their column values in batch.
+pub trait ColumnarScoreTweaker<TScore>: Sync {
+    /// Type of the associated [`ColumnarScoreSegmentTweaker`].
+    type Child: ColumnarScoreSegmentTweaker<TScore>;
+
+    /// Builds the tweaker of a segment, opening the columns it needs.
+    fn segment_tweaker(&self, segment_columns: &SegmentColumns) -> Result<Self::Child>;
+}
+
+impl<F, TScore, TSegmentTweaker> ColumnarScoreTweaker<TScore> for F
+where
+    F: 'static + Send + Sync + Fn(&SegmentColumns) -> Result<TSegmentTweaker>,
+    TSegmentTweaker: ColumnarScoreSegmentTweaker<TScore>,
+{
+    type Child = TSegmentTweaker;
+
+    fn segment_tweaker(&self, segment_columns: &SegmentColumns) -> Result<Self::Child> {
+        (self)(segment_columns)
+    }
+}
+
+impl<F, TScore> ColumnarScoreSegmentTweaker<TScore> for F
+where F: 'static + FnMut(&[DocId], &[Score], &mut Vec<TScore>)
+{
+    fn score_block(&mut self, docs: &[DocId], scores: &[Score], tweaked_scores: &mut Vec<TScore>) {
+        (self)(docs, scores, tweaked_scores)
+    }
+}
+
+pub(crate) struct ColumnarScoreTopCollector<TScoreTweaker, TScore> {
+    score_tweaker: TScoreTweaker,
+    collector: TopCollector<TScore>,
+}
+
+impl<TScoreTweaker, TScore> ColumnarScoreTopCollector<TScoreTweaker, TScore>
+where TScore: Clone + PartialOrd
+{
+    pub fn new(
+        score_tweaker: TScoreTweaker,
+        collector: TopCollector<TScore>,
+    ) -> ColumnarScoreTopCollector<TScoreTweaker, TScore> {
+        ColumnarScoreTopCollector {
+            score_tweaker,
+            collector,
+        }
+    }
+}
+
+impl<TScoreTweaker, TScore> Collector for ColumnarScoreTopCollector<TScoreTweaker, TScore>
+where
+    TScoreTweaker: ColumnarScoreTweaker<TScore> + Send + Sync,
+    TScore: 'static + PartialOrd + Clone + Send + Sync,
+{
+    type Fruit = Vec<(TScore, DocAddress)>;
+
+    type Child = ColumnarScoreTopSegmentCollector<TScoreTweaker::Child, TScore>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: u32,
+        segment_reader: &SegmentReader,
+    ) -> Result<Self::Child> {
+        let segment_tweaker = self
+            .score_tweaker
+            .segment_tweaker(&SegmentColumns::new(segment_reader))?;
+        let segment_collector = self.collector.for_segment(segment_local_id, segment_reader);
+        Ok(ColumnarScoreTopSegmentCollector {
+            segment_collector,
+            segment_tweaker,
+            docs: Vec::with_capacity(COLLECT_BLOCK_BUFFER_LEN),
+            scores: Vec::with_capacity(COLLECT_BLOCK_BUFFER_LEN),
+            tweaked_scores: Vec::with_capacity(COLLECT_BLOCK_BUFFER_LEN),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<Self::Fruit>) -> Result<Self::Fruit> {
+        self.collector.merge_fruits(segment_fruits)
+    }
+}
+
+/// Segment collector of the collector returned by
+/// [`TopDocs::tweak_score_with_columns()`](crate::collector::TopDocs::tweak_score_with_columns).
+///
+/// Documents are buffered, and their scores are tweaked a block at a time.
+pub struct ColumnarScoreTopSegmentCollector<TSegmentScoreTweaker, TScore>
+where TScore: 'static + PartialOrd + Clone + Send + Sync + Sized
+{
+    segment_collector: TopSegmentCollector<TScore>,
+    segment_tweaker: TSegmentScoreTweaker,
+    docs: Vec<DocId>,
+    scores: Vec<Score>,
+    tweaked_scores: Vec<TScore>,
+}
+
+impl<TSegmentScoreTweaker, TScore> ColumnarScoreTopSegmentCollector<TSegmentScoreTweaker, TScore>
+where
+    TScore: 'static + PartialOrd + Clone + Send + Sync,
+    TSegmentScoreTweaker: ColumnarScoreSegmentTweaker<TScore>,
+{
+    fn flush_block(&mut self) {
+        if self.docs.is_empty() {
+            return;
+        }
+        self.tweaked_scores.clear();
+        self.segment_tweaker
+            .score_block(&self.docs, &self.scores, &mut self.tweaked_scores);
+        assert_eq!(
+            self.tweaked_scores.len(),
+            self.docs.len(),
+            "The score tweaker is expected to push one score per document."
+        );
+        for (&doc, tweaked_score) in self.docs.iter().zip(self.tweaked_scores.drain(..)) {
+            self.segment_collector.collect(doc, tweaked_score);
+        }
+        self.docs.clear();
+        self.scores.clear();
+    }
+}
+
+impl<TSegmentScoreTweaker, TScore> SegmentCollector
+    for ColumnarScoreTopSegmentCollector<TSegmentScoreTweaker, TScore>
+where
+    TScore: 'static + PartialOrd + Clone + Send + Sync,
+    TSegmentScoreTweaker: 'static + ColumnarScoreSegmentTweaker<TScore>,
+{
+    type Fruit = Vec<(TScore, DocAddress)>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.docs.push(doc);
+        self.scores.push(score);
+        if self.docs.len() == COLLECT_BLOCK_BUFFER_LEN {
+            self.flush_block();
+        }
+    }
+
+    fn harvest(mut self) -> Vec<(TScore, DocAddress)> {
+        self.flush_block();
+        self.segment_collector.harvest()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::{SegmentColumns, TopDocs};
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING, TEXT};
+    use crate::{DateTime, DocAddress, DocId, Index, IndexWriter, Score, Term};
+
+    #[test]
+    fn test_tweak_score_with_columns() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let popularity = schema_builder.add_u64_field("popularity", FAST);
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let published = schema_builder.add_date_field("published", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0..200u64 {
+            let mut doc = doc!(
+                body => "hello",
+                popularity => i,
+                published => DateTime::from_timestamp_secs(i as i64),
+            );
+            if i % 2 == 0 {
+                doc.add_text(category, "news");
+            } else {
+                doc.add_text(category, "blog");
+                doc.add_text(category, "sports");
+            }
+            index_writer.add_document(doc)?;
+            if i == 100 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let query = TermQuery::new(
+            Term::from_field_text(body, "hello"),
+            IndexRecordOption::WithFreqs,
+        );
+        let collector =
+            TopDocs::with_limit(3).tweak_score_with_columns(|segment_columns: &SegmentColumns| {
+                let mut popularity = segment_columns.u64("popularity")?;
+                let mut published = segment_columns.date("published")?;
+                let category = segment_columns.str("category")?;
+                let sports_ord = category.term_ord("sports")?;
+                let mut missing = segment_columns.f64("missing")?;
+                Ok(
+                    move |docs: &[DocId], scores: &[Score], tweaked_scores: &mut Vec<Score>| {
+                        let popularities = popularity.fetch(docs).to_vec();
+                        let published_dates = published.fetch(docs);
+                        assert!(missing.fetch(docs).iter().all(Option::is_none));
+                        for (i, (&doc, &score)) in docs.iter().zip(scores).enumerate() {
+                            assert_eq!(
+                                published_dates[i].unwrap().into_timestamp_secs() as u64,
+                                popularities[i].unwrap()
+                            );
+                            let is_sports = category
+                                .ords_for_doc(doc)
+                                .any(|term_ord| Some(term_ord) == sports_ord);
+                            let boost = if is_sports { 2.0 } else { 1.0 };
+                            tweaked_scores.push(score * boost * popularities[i].unwrap() as Score);
+                        }
+                    },
+                )
+            });
+        let top_docs = searcher.search(&query, &collector)?;
+        let popularities: Vec<u64> = top_docs
+            .iter()
+            .map(|(_, doc_address)| {
+                let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+                let popularity_column = segment_reader.fast_fields().u64("popularity").unwrap();
+                popularity_column.first(doc_address.doc_id).unwrap()
+            })
+            .collect();
+        assert_eq!(popularities, vec![199, 197, 195]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tweak_score_with_columns_str_terms() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let category = schema_builder.add_text_field("category", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(category => "b"))?;
+        index_writer.add_document(doc!(category => "c"))?;
+        index_writer.add_document(doc!())?;
+        index_writer.add_document(doc!(category => "a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let collector =
+            TopDocs::with_limit(4).tweak_score_with_columns(|segment_columns: &SegmentColumns| {
+                let mut category = segment_columns.str("category")?;
+                Ok(
+                    move |docs: &[DocId], _scores: &[Score], tweaked_scores: &mut Vec<String>| {
+                        let term_ords = category.fetch_ords(docs).to_vec();
+                        for term_ord_opt in term_ords {
+                            let mut term = String::new();
+                            if let Some(term_ord) = term_ord_opt {
+                                assert!(category.ord_to_str(term_ord, &mut term).unwrap());
+                            }
+                            tweaked_scores.push(term);
+                        }
+                    },
+                )
+            });
+        let top_docs = searcher.search(&AllQuery, &collector)?;
+        assert_eq!(
+            top_docs,
+            vec![
+                ("c".to_string(), DocAddress::new(0, 1)),
+                ("b".to_string(), DocAddress::new(0, 0)),
+                ("a".to_string(), DocAddress::new(0, 3)),
+                (String::new(), DocAddress::new(0, 2)),
+            ]
+        );
+        Ok(())
+    }
+}
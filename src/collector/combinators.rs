@@ -0,0 +1,235 @@
+use crate::collector::{Collector, Fruit, SegmentCollector};
+use crate::query::Weight;
+use crate::{SegmentOrdinal, SegmentReader};
+
+/// Adapters composing existing collectors, without implementing [`Collector`] and
+/// [`SegmentCollector`] from scratch.
+///
+/// The trait is implemented for all of the collectors.
+///
+/// ```rust
+/// use tantivy::collector::{CollectorExt, Count, TopDocs};
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(title => "The Name of the Wind"))?;
+/// index_writer.add_document(doc!(title => "The Diary of Muadib"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let collector = TopDocs::with_limit(1)
+///     .zip(Count)
+///     .map_fruit(|(top_docs, count)| (top_docs.len(), count));
+/// assert_eq!(searcher.search(&AllQuery, &collector)?, (1, 2));
+///
+/// let no_segment_collector =
+///     Count.filter_segments(|segment_reader| segment_reader.num_docs() > 10);
+/// assert_eq!(searcher.search(&AllQuery, &no_segment_collector)?, 0);
+/// # Ok(())
+/// # }
+/// ```
+pub trait CollectorExt: Collector + Sized {
+    /// Returns a collector running both `self` and `other`, whose fruit is the pair of their
+    /// fruits.
+    fn zip<TOther: Collector>(self, other: TOther) -> (Self, TOther) {
+        (self, other)
+    }
+
+    /// Returns a collector transforming the fruit of `self` with `map`, once the fruits of all
+    /// of the segments have been merged.
+    fn map_fruit<TFruit, TMap>(self, map: TMap) -> MapFruitCollector<Self, TMap>
+    where
+        TFruit: Fruit,
+        TMap: Fn(Self::Fruit) -> TFruit + Send + Sync,
+    {
+        MapFruitCollector {
+            collector: self,
+            map,
+        }
+    }
+
+    /// Returns a collector only running `self` on the segments for which `predicate` returns
+    /// true.
+    ///
+    /// The other segments are skipped wholesale: the query is not even evaluated on them.
+    fn filter_segments<TPredicate>(
+        self,
+        predicate: TPredicate,
+    ) -> SegmentFilterCollector<Self, TPredicate>
+    where
+        TPredicate: Fn(&SegmentReader) -> bool + Send + Sync,
+    {
+        SegmentFilterCollector {
+            collector: self,
+            predicate,
+        }
+    }
+}
+
+impl<TCollector: Collector> CollectorExt for TCollector {}
+
+/// Collector transforming the fruit of another collector.
+///
+/// See [`CollectorExt::map_fruit()`].
+pub struct MapFruitCollector<TCollector, TMap> {
+    collector: TCollector,
+    map: TMap,
+}
+
+impl<TCollector, TFruit, TMap> Collector for MapFruitCollector<TCollector, TMap>
+where
+    TCollector: Collector,
+    TFruit: Fruit,
+    TMap: Fn(TCollector::Fruit) -> TFruit + Send + Sync,
+{
+    type Fruit = TFruit;
+
+    type Child = TCollector::Child;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        self.collector.for_segment(segment_local_id, segment)
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.collector.requires_scoring()
+    }
+
+    fn should_collect_segment(&self, segment: &SegmentReader) -> bool {
+        self.collector.should_collect_segment(segment)
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+    ) -> crate::Result<TFruit> {
+        let fruit = self.collector.merge_fruits(segment_fruits)?;
+        Ok((self.map)(fruit))
+    }
+
+    // Delegated, so that the specialized collection of the wrapped collector is preserved.
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+    ) -> crate::Result<<Self::Child as SegmentCollector>::Fruit> {
+        self.collector.collect_segment(weight, segment_ord, reader)
+    }
+}
+
+/// Collector skipping the segments which do not match a predicate.
+///
+/// See [`CollectorExt::filter_segments()`].
+pub struct SegmentFilterCollector<TCollector, TPredicate> {
+    collector: TCollector,
+    predicate: TPredicate,
+}
+
+impl<TCollector, TPredicate> Collector for SegmentFilterCollector<TCollector, TPredicate>
+where
+    TCollector: Collector,
+    TPredicate: Fn(&SegmentReader) -> bool + Send + Sync,
+{
+    type Fruit = TCollector::Fruit;
+
+    type Child = Option<TCollector::Child>;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        if !(self.predicate)(segment) {
+            return Ok(None);
+        }
+        Ok(Some(self.collector.for_segment(segment_local_id, segment)?))
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.collector.requires_scoring()
+    }
+
+    fn should_collect_segment(&self, segment: &SegmentReader) -> bool {
+        (self.predicate)(segment) && self.collector.should_collect_segment(segment)
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+    ) -> crate::Result<Self::Fruit> {
+        self.collector
+            .merge_fruits(segment_fruits.into_iter().flatten().collect())
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: u32,
+        reader: &SegmentReader,
+    ) -> crate::Result<<Self::Child as SegmentCollector>::Fruit> {
+        if !(self.predicate)(reader) {
+            return Ok(None);
+        }
+        Ok(Some(self.collector.collect_segment(
+            weight,
+            segment_ord,
+            reader,
+        )?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CollectorExt;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, FAST};
+    use crate::{DocAddress, Index, IndexWriter};
+
+    #[test]
+    fn test_collector_combinators() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let num = schema_builder.add_u64_field("num", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(num => 1u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(num => 2u64))?;
+        index_writer.add_document(doc!(num => 3u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let count_times_two = Count.map_fruit(|count| count * 2);
+        assert_eq!(searcher.search(&AllQuery, &count_times_two)?, 6);
+
+        let large_segments_count = Count
+            .filter_segments(|segment_reader| segment_reader.num_docs() > 1)
+            .zip(Count);
+        assert_eq!(searcher.search(&AllQuery, &large_segments_count)?, (2, 3));
+
+        let small_segment_top_docs = TopDocs::with_limit(10)
+            .filter_segments(|segment_reader| segment_reader.num_docs() == 1)
+            .map_fruit(|top_docs| {
+                top_docs
+                    .into_iter()
+                    .map(|(_, doc_address)| doc_address)
+                    .collect::<Vec<DocAddress>>()
+            });
+        let doc_addresses = searcher.search(&AllQuery, &small_segment_top_docs)?;
+        assert_eq!(doc_addresses.len(), 1);
+        let segment_reader = searcher.segment_reader(doc_addresses[0].segment_ord);
+        assert_eq!(segment_reader.num_docs(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,423 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::BuildHasherDefault;
+
+use columnar::{Column, ColumnType, StrColumn};
+use common::BitSet;
+use hyperloglogplus::{HyperLogLog, HyperLogLogPlus};
+use rustc_hash::FxHashSet;
+
+use crate::collector::{Collector, SegmentCollector};
+use crate::fastfield::FastFieldNotAvailableError;
+use crate::{DocId, Score, SegmentOrdinal, SegmentReader};
+
+type Sketch = HyperLogLogPlus<u64, BuildHasherDefault<DefaultHasher>>;
+
+/// Precision of the sketch, the relative error of the approximate counts is about 0.4%.
+const SKETCH_PRECISION: u8 = 16;
+
+/// Numeric column types whose `u64` values are consistent across segments.
+const NUMERIC_COLUMN_TYPES: [ColumnType; 5] = [
+    ColumnType::U64,
+    ColumnType::I64,
+    ColumnType::F64,
+    ColumnType::DateTime,
+    ColumnType::Bool,
+];
+
+/// How a [`DistinctCount`] was computed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DistinctCountMode {
+    /// The distinct values were all kept in memory, the count is exact.
+    Exact,
+    /// The number of distinct values exceeded the threshold, the count is an estimate.
+    Approximate,
+}
+
+/// Number of distinct values of a fast field, as computed by the [`DistinctCountCollector`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DistinctCount {
+    /// The number of distinct values.
+    pub count: u64,
+    /// Whether `count` is exact or an estimate.
+    pub mode: DistinctCountMode,
+}
+
+/// Counts the distinct values of a fast field over the matching documents.
+///
+/// The count is exact as long as the number of distinct values does not exceed the
+/// [exact threshold](DistinctCountCollector::with_exact_threshold). Above it, the collector
+/// switches to a HyperLogLog++ sketch, which bounds the memory usage, and the count becomes an
+/// estimate. The returned [`DistinctCount`] reports which of the two modes was used.
+///
+/// The field can be a `u64`, `i64`, `f64`, date, bool or str fast field. Documents without a
+/// value are ignored, and every value of multivalued documents is counted.
+///
+/// ```rust
+/// use tantivy::collector::{DistinctCountCollector, DistinctCountMode};
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let country = schema_builder.add_text_field("country", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(country => "fr"))?;
+/// index_writer.add_document(doc!(country => "de"))?;
+/// index_writer.add_document(doc!(country => "fr"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let distinct_count = searcher.search(&AllQuery, &DistinctCountCollector::for_field("country"))?;
+/// assert_eq!(distinct_count.count, 2);
+/// assert_eq!(distinct_count.mode, DistinctCountMode::Exact);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct DistinctCountCollector {
+    field: String,
+    exact_threshold: usize,
+}
+
+impl DistinctCountCollector {
+    /// Creates a collector counting the distinct values of the fast field `field`.
+    ///
+    /// By default, the count is exact up to 3,000 distinct values.
+    pub fn for_field(field: impl ToString) -> DistinctCountCollector {
+        DistinctCountCollector {
+            field: field.to_string(),
+            exact_threshold: 3_000,
+        }
+    }
+
+    /// Sets the number of distinct values up to which the count is exact.
+    ///
+    /// Exact counting keeps every distinct value in memory.
+    pub fn with_exact_threshold(mut self, exact_threshold: usize) -> DistinctCountCollector {
+        self.exact_threshold = exact_threshold;
+        self
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+enum DistinctValue {
+    Numeric(u64),
+    Bytes(Vec<u8>),
+}
+
+impl DistinctValue {
+    fn insert_into(&self, sketch: &mut Sketch) {
+        match self {
+            DistinctValue::Numeric(val) => sketch.insert_any(val),
+            DistinctValue::Bytes(bytes) => sketch.insert_any(&bytes[..]),
+        }
+    }
+}
+
+enum DistinctValuesRepr {
+    Exact(FxHashSet<DistinctValue>),
+    Sketch(Box<Sketch>),
+}
+
+/// The distinct values of a segment, kept either exactly or in a sketch.
+pub struct DistinctValues {
+    repr: DistinctValuesRepr,
+    exact_threshold: usize,
+}
+
+impl DistinctValues {
+    fn new(exact_threshold: usize) -> DistinctValues {
+        DistinctValues {
+            repr: DistinctValuesRepr::Exact(FxHashSet::default()),
+            exact_threshold,
+        }
+    }
+
+    fn insert(&mut self, value: DistinctValue) {
+        match &mut self.repr {
+            DistinctValuesRepr::Exact(values) => {
+                values.insert(value);
+                if values.len() > self.exact_threshold {
+                    self.switch_to_sketch();
+                }
+            }
+            DistinctValuesRepr::Sketch(sketch) => value.insert_into(sketch),
+        }
+    }
+
+    fn insert_bytes(&mut self, bytes: &[u8]) {
+        if let DistinctValuesRepr::Sketch(sketch) = &mut self.repr {
+            sketch.insert_any(bytes);
+        } else {
+            self.insert(DistinctValue::Bytes(bytes.to_vec()));
+        }
+    }
+
+    fn switch_to_sketch(&mut self) {
+        let DistinctValuesRepr::Exact(values) = &self.repr else {
+            return;
+        };
+        let mut sketch = Sketch::new(SKETCH_PRECISION, BuildHasherDefault::default())
+            .expect("the sketch precision should be valid");
+        for value in values {
+            value.insert_into(&mut sketch);
+        }
+        self.repr = DistinctValuesRepr::Sketch(Box::new(sketch));
+    }
+
+    fn merge(&mut self, other: DistinctValues) {
+        match other.repr {
+            DistinctValuesRepr::Exact(other_values) => {
+                for value in other_values {
+                    self.insert(value);
+                }
+            }
+            DistinctValuesRepr::Sketch(other_sketch) => {
+                self.switch_to_sketch();
+                if let DistinctValuesRepr::Sketch(sketch) = &mut self.repr {
+                    sketch
+                        .merge(&other_sketch)
+                        .expect("sketches with the same precision should be mergeable");
+                }
+            }
+        }
+    }
+
+    fn count(self) -> DistinctCount {
+        match self.repr {
+            DistinctValuesRepr::Exact(values) => DistinctCount {
+                count: values.len() as u64,
+                mode: DistinctCountMode::Exact,
+            },
+            DistinctValuesRepr::Sketch(mut sketch) => DistinctCount {
+                count: sketch.count().round() as u64,
+                mode: DistinctCountMode::Approximate,
+            },
+        }
+    }
+}
+
+enum SegmentColumn {
+    Numeric(Column<u64>),
+    Str {
+        column: StrColumn,
+        term_ords: BitSet,
+    },
+    Missing,
+}
+
+/// Segment collector of the [`DistinctCountCollector`].
+pub struct SegmentDistinctCountCollector {
+    column: SegmentColumn,
+    distinct_values: DistinctValues,
+}
+
+impl SegmentCollector for SegmentDistinctCountCollector {
+    type Fruit = crate::Result<DistinctValues>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        match &mut self.column {
+            SegmentColumn::Numeric(column) => {
+                for val in column.values_for_doc(doc) {
+                    self.distinct_values.insert(DistinctValue::Numeric(val));
+                }
+            }
+            SegmentColumn::Str { column, term_ords } => {
+                for term_ord in column.term_ords(doc) {
+                    term_ords.insert(term_ord as u32);
+                }
+            }
+            SegmentColumn::Missing => {}
+        }
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        let mut distinct_values = self.distinct_values;
+        if let SegmentColumn::Str { column, term_ords } = self.column {
+            if term_ords.len() > distinct_values.exact_threshold {
+                distinct_values.switch_to_sketch();
+            }
+            let sorted_term_ords = (0..term_ords.max_value())
+                .filter(|&term_ord| term_ords.contains(term_ord))
+                .map(u64::from);
+            column
+                .dictionary()
+                .sorted_ords_to_term_cb(sorted_term_ords, |term_bytes| {
+                    distinct_values.insert_bytes(term_bytes);
+                    Ok(())
+                })?;
+        }
+        Ok(distinct_values)
+    }
+}
+
+impl Collector for DistinctCountCollector {
+    type Fruit = DistinctCount;
+    type Child = SegmentDistinctCountCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        segment_reader: &SegmentReader,
+    ) -> crate::Result<SegmentDistinctCountCollector> {
+        let fast_fields = segment_reader.fast_fields();
+        let column = if let Some(column) = fast_fields.str(&self.field)? {
+            let term_ords = BitSet::with_max_value(column.num_terms() as u32);
+            SegmentColumn::Str { column, term_ords }
+        } else if let Some((column, _column_type)) =
+            fast_fields.u64_lenient_for_type(Some(&NUMERIC_COLUMN_TYPES), &self.field)?
+        {
+            SegmentColumn::Numeric(column)
+        } else if let Some((field, _path)) = segment_reader.schema().find_field(&self.field) {
+            if !segment_reader.schema().get_field_entry(field).is_fast() {
+                return Err(FastFieldNotAvailableError {
+                    field_name: self.field.clone(),
+                }
+                .into());
+            }
+            // The field is fast, but none of the documents of the segment has a value.
+            SegmentColumn::Missing
+        } else {
+            return Err(FastFieldNotAvailableError {
+                field_name: self.field.clone(),
+            }
+            .into());
+        };
+        Ok(SegmentDistinctCountCollector {
+            column,
+            distinct_values: DistinctValues::new(self.exact_threshold),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<crate::Result<DistinctValues>>,
+    ) -> crate::Result<DistinctCount> {
+        let mut distinct_values = DistinctValues::new(self.exact_threshold);
+        for segment_distinct_values in segment_fruits {
+            distinct_values.merge(segment_distinct_values?);
+        }
+        Ok(distinct_values.count())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DistinctCountCollector, DistinctCountMode};
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{AllQuery, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING, TEXT};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_distinct_count_exact() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let val = schema_builder.add_i64_field("val", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0..100i64 {
+            index_writer.add_document(doc!(val => i % 10 - 5))?;
+            if i % 30 == 0 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.add_document(doc!())?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert!(searcher.segment_readers().len() > 1);
+        let distinct_count =
+            searcher.search(&AllQuery, &DistinctCountCollector::for_field("val"))?;
+        assert_eq!(distinct_count.count, 10);
+        assert_eq!(distinct_count.mode, DistinctCountMode::Exact);
+        Ok(())
+    }
+
+    #[test]
+    fn test_distinct_count_approximate() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let val = schema_builder.add_u64_field("val", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0..10_000u64 {
+            index_writer.add_document(doc!(val => i % 5_000))?;
+            if i % 3_000 == 0 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let collector = DistinctCountCollector::for_field("val").with_exact_threshold(1_000);
+        let distinct_count = searcher.search(&AllQuery, &collector)?;
+        assert_eq!(distinct_count.mode, DistinctCountMode::Approximate);
+        assert!((4_900..=5_100).contains(&distinct_count.count));
+
+        // Each segment is below the threshold, but their union is not.
+        let collector = DistinctCountCollector::for_field("val").with_exact_threshold(4_000);
+        let distinct_count = searcher.search(&AllQuery, &collector)?;
+        assert_eq!(distinct_count.mode, DistinctCountMode::Approximate);
+        assert!((4_900..=5_100).contains(&distinct_count.count));
+
+        let collector = DistinctCountCollector::for_field("val").with_exact_threshold(5_000);
+        let distinct_count = searcher.search(&AllQuery, &collector)?;
+        assert_eq!(distinct_count.count, 5_000);
+        assert_eq!(distinct_count.mode, DistinctCountMode::Exact);
+        Ok(())
+    }
+
+    #[test]
+    fn test_distinct_count_str() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let tag = schema_builder.add_text_field("tag", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(text => "a", tag => "red", tag => "blue"))?;
+        index_writer.add_document(doc!(text => "b", tag => "green"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text => "a", tag => "blue"))?;
+        index_writer.add_document(doc!(text => "a", tag => "yellow"))?;
+        index_writer.add_document(doc!(text => "a"))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let collector = DistinctCountCollector::for_field("tag");
+        assert_eq!(searcher.search(&AllQuery, &collector)?.count, 4);
+        let query = TermQuery::new(Term::from_field_text(text, "a"), IndexRecordOption::Basic);
+        let distinct_count = searcher.search(&query, &collector)?;
+        assert_eq!(distinct_count.count, 3);
+        assert_eq!(distinct_count.mode, DistinctCountMode::Exact);
+
+        let collector = collector.with_exact_threshold(2);
+        let distinct_count = searcher.search(&query, &collector)?;
+        assert_eq!(distinct_count.count, 3);
+        assert_eq!(distinct_count.mode, DistinctCountMode::Approximate);
+        Ok(())
+    }
+
+    #[test]
+    fn test_distinct_count_not_fast() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let collector = DistinctCountCollector::for_field("text");
+        assert!(searcher.search(&AllQuery, &collector).is_err());
+        let collector = DistinctCountCollector::for_field("missing");
+        assert!(searcher.search(&AllQuery, &collector).is_err());
+        Ok(())
+    }
+}
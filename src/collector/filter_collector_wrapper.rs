@@ -127,6 +127,10 @@ where
         self.collector.requires_scoring()
     }
 
+    fn should_collect_segment(&self, segment: &SegmentReader) -> bool {
+        self.collector.should_collect_segment(segment)
+    }
+
     fn merge_fruits(
         &self,
         segment_fruits: Vec<<TCollector::Child as SegmentCollector>::Fruit>,
@@ -281,6 +285,10 @@ where
         self.collector.requires_scoring()
     }
 
+    fn should_collect_segment(&self, segment: &SegmentReader) -> bool {
+        self.collector.should_collect_segment(segment)
+    }
+
     fn merge_fruits(
         &self,
         segment_fruits: Vec<<TCollector::Child as SegmentCollector>::Fruit>,
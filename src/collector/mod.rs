@@ -76,6 +76,11 @@
 //! If on the other hand, the collectors depend on some query parameter,
 //! you can rely on [`MultiCollector`]'s.
 //!
+//! # Adapting collectors
+//!
+//! [`CollectorExt`] transforms the fruit of a collector, or restricts it to some of the
+//! segments, without implementing a new collector.
+//!
 //!
 //! # Implementing your own collectors.
 //!
@@ -88,9 +93,25 @@ use crate::{DocId, Score, SegmentOrdinal, SegmentReader};
 mod count_collector;
 pub use self::count_collector::Count;
 
+#[cfg(feature = "columnar-analytics")]
 mod histogram_collector;
+#[cfg(feature = "columnar-analytics")]
 pub use histogram_collector::HistogramCollector;
 
+#[cfg(feature = "columnar-analytics")]
+mod distinct_count_collector;
+#[cfg(feature = "columnar-analytics")]
+pub use self::distinct_count_collector::{
+    DistinctCount, DistinctCountCollector, DistinctCountMode, DistinctValues,
+    SegmentDistinctCountCollector,
+};
+
+mod near_duplicate_collector;
+pub use self::near_duplicate_collector::{NearDuplicateCollector, NearDuplicateGroup};
+
+mod combinators;
+pub use self::combinators::{CollectorExt, MapFruitCollector, SegmentFilterCollector};
+
 mod multi_collector;
 pub use self::multi_collector::{FruitHandle, MultiCollector, MultiFruit};
 
@@ -100,11 +121,22 @@ mod top_score_collector;
 pub use self::top_collector::ComparableDoc;
 pub use self::top_score_collector::{TopDocs, TopNComputer};
 
+mod approximate_top_docs;
+pub use self::approximate_top_docs::{
+    ApproximateTopDocs, ApproximateTopDocsResult, ApproximateTopScoreSegmentCollector,
+};
+
 mod custom_score_top_collector;
 pub use self::custom_score_top_collector::{CustomScorer, CustomSegmentScorer};
 
 mod tweak_score_top_collector;
 pub use self::tweak_score_top_collector::{ScoreSegmentTweaker, ScoreTweaker};
+
+mod columnar_score_tweaker;
+pub use self::columnar_score_tweaker::{
+    BlockColumn, BlockStrColumn, ColumnarScoreSegmentTweaker, ColumnarScoreTopSegmentCollector,
+    ColumnarScoreTweaker, SegmentColumns,
+};
 mod facet_collector;
 pub use self::facet_collector::{FacetCollector, FacetCounts};
 use crate::query::Weight;
@@ -156,6 +188,17 @@ pub trait Collector: Sync + Send {
     /// Returns true iff the collector requires to compute scores for documents.
     fn requires_scoring(&self) -> bool;
 
+    /// Returns false if the collector does not need to collect the documents of the segment at
+    /// all, e.g. because the attributes or the column statistics of the segment show that none
+    /// of its documents could be relevant.
+    ///
+    /// Skipped segments are not searched, and do not contribute any fruit to
+    /// [`Collector::merge_fruits()`]. The hook is a hint: a collector combined with others, e.g. in
+    /// a tuple, still collects the segments required by any of them.
+    fn should_collect_segment(&self, _segment: &SegmentReader) -> bool {
+        true
+    }
+
     /// Combines the fruit associated with the collection of each segments
     /// into one fruit.
     fn merge_fruits(
@@ -243,6 +286,12 @@ impl<TCollector: Collector> Collector for Option<TCollector> {
             .unwrap_or(false)
     }
 
+    fn should_collect_segment(&self, segment: &SegmentReader) -> bool {
+        self.as_ref()
+            .map(|inner| inner.should_collect_segment(segment))
+            .unwrap_or(false)
+    }
+
     fn merge_fruits(
         &self,
         segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
@@ -313,6 +362,10 @@ where
         self.0.requires_scoring() || self.1.requires_scoring()
     }
 
+    fn should_collect_segment(&self, segment: &SegmentReader) -> bool {
+        self.0.should_collect_segment(segment) || self.1.should_collect_segment(segment)
+    }
+
     fn merge_fruits(
         &self,
         segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
@@ -373,6 +426,12 @@ where
         self.0.requires_scoring() || self.1.requires_scoring() || self.2.requires_scoring()
     }
 
+    fn should_collect_segment(&self, segment: &SegmentReader) -> bool {
+        self.0.should_collect_segment(segment)
+            || self.1.should_collect_segment(segment)
+            || self.2.should_collect_segment(segment)
+    }
+
     fn merge_fruits(
         &self,
         children: Vec<<Self::Child as SegmentCollector>::Fruit>,
@@ -443,6 +502,13 @@ where
             || self.3.requires_scoring()
     }
 
+    fn should_collect_segment(&self, segment: &SegmentReader) -> bool {
+        self.0.should_collect_segment(segment)
+            || self.1.should_collect_segment(segment)
+            || self.2.should_collect_segment(segment)
+            || self.3.should_collect_segment(segment)
+    }
+
     fn merge_fruits(
         &self,
         children: Vec<<Self::Child as SegmentCollector>::Fruit>,
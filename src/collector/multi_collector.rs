@@ -29,6 +29,10 @@ impl<TCollector: Collector> Collector for CollectorWrapper<TCollector> {
         self.0.requires_scoring()
     }
 
+    fn should_collect_segment(&self, segment: &SegmentReader) -> bool {
+        self.0.should_collect_segment(segment)
+    }
+
     fn merge_fruits(
         &self,
         children: Vec<<Self::Child as SegmentCollector>::Fruit>,
@@ -40,7 +44,7 @@ impl<TCollector: Collector> Collector for CollectorWrapper<TCollector> {
                     .downcast::<<TCollector::Child as SegmentCollector>::Fruit>()
                     .map(|boxed_but_typed| *boxed_but_typed)
                     .map_err(|_| {
-                        TantivyError::InvalidArgument("Failed to cast child fruit.".to_string())
+                        TantivyError::InvalidArgument("Failed to cast child fruit.".into())
                     })
             })
             .collect::<crate::Result<_>>()?;
@@ -214,6 +218,12 @@ impl Collector for MultiCollector<'_> {
             .any(Collector::requires_scoring)
     }
 
+    fn should_collect_segment(&self, segment: &SegmentReader) -> bool {
+        self.collector_wrappers
+            .iter()
+            .any(|collector_wrapper| collector_wrapper.should_collect_segment(segment))
+    }
+
     fn merge_fruits(&self, segments_multifruits: Vec<MultiFruit>) -> crate::Result<MultiFruit> {
         let mut segment_fruits_list: Vec<Vec<Box<dyn Fruit>>> = (0..self.collector_wrappers.len())
             .map(|_| Vec::with_capacity(segments_multifruits.len()))
@@ -0,0 +1,373 @@
+use std::cmp::Ordering;
+
+use columnar::Column;
+
+use crate::collector::{Collector, SegmentCollector, TopNComputer};
+use crate::indexer::near_duplicates::{
+    signature_similarity, MINHASH_FIELD_NAME, SIMHASH_FIELD_NAME,
+};
+use crate::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// A document, along with the documents found to be its near-duplicates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NearDuplicateGroup {
+    /// Score of the document.
+    pub score: Score,
+    /// Address of the document.
+    pub doc: DocAddress,
+    /// Scores and addresses of the near-duplicates of the document, by decreasing score.
+    pub duplicates: Vec<(Score, DocAddress)>,
+}
+
+/// Collects the best scoring documents, grouping their near-duplicates together.
+///
+/// The index needs to be created with
+/// [`IndexSettings::near_duplicate_signature`](crate::IndexSettings::near_duplicate_signature)
+/// set, so that every document has a near-duplicate signature.
+///
+/// The fruit is the list of the `limit` best scoring documents that are not near-duplicates of a
+/// better scoring document, each of them along with its near-duplicates. Suppressing
+/// near-duplicates is just a matter of ignoring the `duplicates` of the groups.
+///
+/// Grouping is approximate: only the `num_candidates` best scoring documents of every segment
+/// are considered, so that documents that would have been grouped can be missed.
+///
+/// ```rust
+/// use tantivy::collector::NearDuplicateCollector;
+/// use tantivy::indexer::near_duplicates::NearDuplicateSignatureSettings;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, IndexSettings, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let body = schema_builder.add_text_field("body", TEXT);
+/// let index = Index::builder()
+///     .schema(schema_builder.build())
+///     .settings(IndexSettings {
+///         near_duplicate_signature: Some(NearDuplicateSignatureSettings::for_fields(&["body"])),
+///         ..Default::default()
+///     })
+///     .create_in_ram()?;
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(body => "breaking news: the river flooded the old town"))?;
+/// index_writer.add_document(doc!(body => "Breaking news: the river flooded the old town!"))?;
+/// index_writer.add_document(doc!(body => "a recipe for a delicious apple pie"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let groups = searcher.search(&AllQuery, &NearDuplicateCollector::with_limit(10))?;
+/// assert_eq!(groups.len(), 2);
+/// assert_eq!(groups[0].duplicates.len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct NearDuplicateCollector {
+    limit: usize,
+    num_candidates: usize,
+    similarity_threshold: f32,
+}
+
+impl NearDuplicateCollector {
+    /// Creates a collector returning at most `limit` groups.
+    ///
+    /// By default, `4 * limit` candidates are considered per segment, and documents are
+    /// near-duplicates if the similarity of their signatures is at least 0.9.
+    ///
+    /// # Panics
+    /// The method panics if limit is 0
+    pub fn with_limit(limit: usize) -> NearDuplicateCollector {
+        assert!(limit >= 1, "Limit must be strictly greater than 0.");
+        NearDuplicateCollector {
+            limit,
+            num_candidates: limit * 4,
+            similarity_threshold: 0.9,
+        }
+    }
+
+    /// Sets the number of best scoring documents considered per segment.
+    ///
+    /// It cannot be lower than the limit.
+    #[must_use]
+    pub fn and_num_candidates(mut self, num_candidates: usize) -> NearDuplicateCollector {
+        self.num_candidates = num_candidates.max(self.limit);
+        self
+    }
+
+    /// Sets the similarity, between 0 and 1, from which documents are near-duplicates.
+    ///
+    /// See [`signature_similarity`](crate::indexer::near_duplicates::signature_similarity).
+    #[must_use]
+    pub fn and_similarity_threshold(mut self, similarity_threshold: f32) -> NearDuplicateCollector {
+        self.similarity_threshold = similarity_threshold;
+        self
+    }
+}
+
+/// A candidate document, along with its signature.
+pub struct NearDuplicateCandidate {
+    score: Score,
+    doc: DocAddress,
+    signature: Vec<u64>,
+}
+
+impl Collector for NearDuplicateCollector {
+    type Fruit = Vec<NearDuplicateGroup>;
+
+    type Child = NearDuplicateSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_local_id: SegmentOrdinal,
+        reader: &SegmentReader,
+    ) -> crate::Result<NearDuplicateSegmentCollector> {
+        let schema = reader.schema();
+        let field_name = [SIMHASH_FIELD_NAME, MINHASH_FIELD_NAME]
+            .into_iter()
+            .find(|field_name| schema.get_field(field_name).is_ok())
+            .ok_or_else(|| {
+                TantivyError::SchemaError(
+                    "The index does not have near-duplicate signatures.".to_string(),
+                )
+            })?;
+        let signatures = reader.fast_fields().u64(field_name)?;
+        Ok(NearDuplicateSegmentCollector {
+            segment_ord: segment_local_id,
+            top_n: TopNComputer::new(self.num_candidates),
+            signatures,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_candidates: Vec<Vec<NearDuplicateCandidate>>,
+    ) -> crate::Result<Vec<NearDuplicateGroup>> {
+        let mut candidates: Vec<NearDuplicateCandidate> =
+            segment_candidates.into_iter().flatten().collect();
+        candidates.sort_by(|left, right| {
+            right
+                .score
+                .partial_cmp(&left.score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| left.doc.cmp(&right.doc))
+        });
+        let mut groups: Vec<(NearDuplicateGroup, Vec<u64>)> = Vec::with_capacity(self.limit);
+        for candidate in candidates {
+            let original = groups.iter_mut().find(|(_, signature)| {
+                signature_similarity(signature, &candidate.signature) >= self.similarity_threshold
+            });
+            if let Some((group, _)) = original {
+                group.duplicates.push((candidate.score, candidate.doc));
+            } else if groups.len() < self.limit {
+                let group = NearDuplicateGroup {
+                    score: candidate.score,
+                    doc: candidate.doc,
+                    duplicates: Vec::new(),
+                };
+                groups.push((group, candidate.signature));
+            }
+        }
+        Ok(groups.into_iter().map(|(group, _)| group).collect())
+    }
+}
+
+/// Segment collector associated with the [`NearDuplicateCollector`].
+pub struct NearDuplicateSegmentCollector {
+    segment_ord: SegmentOrdinal,
+    top_n: TopNComputer<Score, DocId>,
+    signatures: Column<u64>,
+}
+
+impl SegmentCollector for NearDuplicateSegmentCollector {
+    type Fruit = Vec<NearDuplicateCandidate>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        self.top_n.push(score, doc);
+    }
+
+    fn harvest(self) -> Vec<NearDuplicateCandidate> {
+        self.top_n
+            .into_vec()
+            .into_iter()
+            .map(|candidate| NearDuplicateCandidate {
+                score: candidate.feature,
+                doc: DocAddress::new(self.segment_ord, candidate.doc),
+                signature: self.signatures.values_for_doc(candidate.doc).collect(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NearDuplicateCollector;
+    use crate::collector::TopDocs;
+    use crate::indexer::near_duplicates::{
+        NearDuplicateSignatureSettings, SignatureAlgorithm, MINHASH_FIELD_NAME,
+    };
+    use crate::indexer::NoMergePolicy;
+    use crate::query::AllQuery;
+    #[cfg(feature = "query-parser")]
+    use crate::query::QueryParser;
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::{Index, IndexSettings, IndexWriter, TantivyError};
+
+    const ARTICLE: &str = "the city council approved on monday a new budget for public transport \
+                           with more buses on the northern lines and cheaper tickets for students \
+                           and retired people starting next year";
+
+    fn create_index(algorithm: SignatureAlgorithm) -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT);
+        schema_builder.add_text_field("body", TEXT);
+        let settings = NearDuplicateSignatureSettings {
+            algorithm,
+            ..NearDuplicateSignatureSettings::for_fields(&["body"])
+        };
+        Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                near_duplicate_signature: Some(settings),
+                ..Default::default()
+            })
+            .create_in_ram()
+    }
+
+    fn index_articles(index: &Index) -> crate::Result<()> {
+        let schema = index.schema();
+        let title = schema.get_field("title")?;
+        let body = schema.get_field("body")?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(title => "budget", body => ARTICLE))?;
+        index_writer.add_document(doc!(
+            title => "other",
+            body => "the football team lost the final after a penalty shootout on sunday evening"
+        ))?;
+        index_writer.commit()?;
+        // A syndicated copy, with a different title.
+        index_writer.add_document(doc!(title => "syndicated budget", body => ARTICLE))?;
+        // A lightly edited copy.
+        index_writer.add_document(doc!(
+            title => "edited budget",
+            body => ARTICLE.replace("monday", "tuesday")
+        ))?;
+        index_writer.commit()?;
+        Ok(())
+    }
+
+    #[cfg(feature = "query-parser")]
+    #[test]
+    fn test_near_duplicate_collector_simhash() -> crate::Result<()> {
+        let index = create_index(SignatureAlgorithm::SimHash)?;
+        index_articles(&index)?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![index.schema().get_field("title")?]);
+        let other_query = query_parser.parse_query("other")?;
+        let other_doc = searcher.search(&other_query, &TopDocs::with_limit(1))?[0].1;
+        let query = query_parser.parse_query("budget other")?;
+        // SimHash signatures of short texts are noisy, hence the low threshold.
+        let collector = NearDuplicateCollector::with_limit(10).and_similarity_threshold(0.8);
+        let groups = searcher.search(&query, &collector)?;
+        assert_eq!(groups.len(), 2);
+        let budget_group = groups.iter().find(|group| group.doc != other_doc).unwrap();
+        assert_eq!(budget_group.duplicates.len(), 2);
+        assert!(budget_group
+            .duplicates
+            .iter()
+            .all(|(score, _)| *score <= budget_group.score));
+
+        // Suppressing near-duplicates leaves room for other documents.
+        let collector = NearDuplicateCollector::with_limit(2).and_similarity_threshold(0.8);
+        let groups = searcher.search(&AllQuery, &collector)?;
+        assert_eq!(groups.len(), 2);
+        assert!(groups.iter().any(|group| group.doc == other_doc));
+
+        // With a threshold of 1, only exact duplicates are grouped.
+        let collector = NearDuplicateCollector::with_limit(10).and_similarity_threshold(1.0);
+        let groups = searcher.search(&AllQuery, &collector)?;
+        let mut num_duplicates: Vec<usize> =
+            groups.iter().map(|group| group.duplicates.len()).collect();
+        num_duplicates.sort();
+        assert_eq!(num_duplicates, vec![0, 0, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_near_duplicate_collector_minhash() -> crate::Result<()> {
+        let index = create_index(SignatureAlgorithm::MinHash { num_hashes: 32 })?;
+        index_articles(&index)?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let signatures = searcher
+            .segment_reader(0)
+            .fast_fields()
+            .u64(MINHASH_FIELD_NAME)?;
+        let doc_signatures: Vec<Vec<u64>> = (0..4)
+            .map(|doc| signatures.values_for_doc(doc).collect())
+            .collect();
+        assert!(doc_signatures.iter().all(|signature| signature.len() == 32));
+        // Values keep the order of the hash functions.
+        let mut exact_copies = doc_signatures.clone();
+        exact_copies.sort();
+        exact_copies.dedup();
+        assert_eq!(exact_copies.len(), 3);
+
+        let collector = NearDuplicateCollector::with_limit(10).and_similarity_threshold(0.5);
+        let groups = searcher.search(&AllQuery, &collector)?;
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].duplicates.len(), 2);
+        assert!(groups[1].duplicates.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_near_duplicate_signature_validation() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("id", STRING);
+        let schema = schema_builder.build();
+        let create_index = |fields: &[&str], shingle_size: usize| {
+            Index::builder()
+                .schema(schema.clone())
+                .settings(IndexSettings {
+                    near_duplicate_signature: Some(NearDuplicateSignatureSettings {
+                        shingle_size,
+                        ..NearDuplicateSignatureSettings::for_fields(fields)
+                    }),
+                    ..Default::default()
+                })
+                .create_in_ram()
+        };
+        assert!(create_index(&["id"], 3).is_ok());
+        assert!(matches!(
+            create_index(&["missing"], 3),
+            Err(TantivyError::FieldNotFound(_))
+        ));
+        assert!(matches!(
+            create_index(&["id"], 0),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+
+        let id = schema.get_field("id")?;
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert!(matches!(
+            searcher.search(&AllQuery, &NearDuplicateCollector::with_limit(1)),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+}
@@ -304,3 +304,57 @@ fn test_option_collector_none() -> crate::Result<()> {
     assert_eq!(counts, None);
     Ok(())
 }
+
+/// Counts the segments it collects, skipping the segments with less than `min_num_docs`
+/// documents.
+struct CollectedSegmentsCollector {
+    min_num_docs: u32,
+}
+
+impl Collector for CollectedSegmentsCollector {
+    type Fruit = usize;
+    type Child = <Count as Collector>::Child;
+
+    fn for_segment(
+        &self,
+        segment_ord: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> crate::Result<Self::Child> {
+        Count.for_segment(segment_ord, segment)
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn should_collect_segment(&self, segment: &SegmentReader) -> bool {
+        segment.num_docs() >= self.min_num_docs
+    }
+
+    fn merge_fruits(&self, segment_fruits: Vec<usize>) -> crate::Result<usize> {
+        Ok(segment_fruits.len())
+    }
+}
+
+#[test]
+fn test_collector_should_collect_segment() -> crate::Result<()> {
+    let mut schema_builder = Schema::builder();
+    let text = schema_builder.add_text_field("text", TEXT);
+    let index = Index::create_in_ram(schema_builder.build());
+    let mut index_writer = index.writer_for_tests()?;
+    index_writer.add_document(doc!(text => "a"))?;
+    index_writer.commit()?;
+    index_writer.add_document(doc!(text => "b"))?;
+    index_writer.add_document(doc!(text => "c"))?;
+    index_writer.commit()?;
+    let searcher = index.reader()?.searcher();
+    assert_eq!(searcher.segment_readers().len(), 2);
+
+    let collector = CollectedSegmentsCollector { min_num_docs: 2 };
+    assert_eq!(searcher.search(&AllQuery, &collector)?, 1);
+    let collector = CollectedSegmentsCollector { min_num_docs: 3 };
+    assert_eq!(searcher.search(&AllQuery, &collector)?, 0);
+    // Combined with a collector requiring all of the segments, no segment is skipped.
+    assert_eq!(searcher.search(&AllQuery, &(collector, Count))?, (2, 3));
+    Ok(())
+}
@@ -6,11 +6,13 @@ use columnar::ColumnValues;
 use serde::{Deserialize, Serialize};
 
 use super::Collector;
+use crate::collector::columnar_score_tweaker::ColumnarScoreTopCollector;
 use crate::collector::custom_score_top_collector::CustomScoreTopCollector;
 use crate::collector::top_collector::{ComparableDoc, TopCollector, TopSegmentCollector};
 use crate::collector::tweak_score_top_collector::TweakedScoreTopCollector;
 use crate::collector::{
-    CustomScorer, CustomSegmentScorer, ScoreSegmentTweaker, ScoreTweaker, SegmentCollector,
+    ApproximateTopDocs, ColumnarScoreSegmentTweaker, ColumnarScoreTweaker, CustomScorer,
+    CustomSegmentScorer, ScoreSegmentTweaker, ScoreTweaker, SegmentCollector,
 };
 use crate::fastfield::{FastFieldNotAvailableError, FastValue};
 use crate::query::Weight;
@@ -239,6 +241,61 @@ impl TopDocs {
         TopDocs(self.0.and_offset(offset))
     }
 
+    /// Collects the top documents approximately, for when latency matters more than exactness.
+    ///
+    /// While collecting, the scorers of the query use the upper bounds of the scores of their
+    /// blocks of documents to skip those which cannot make it to the top documents (see
+    /// BlockWAND). In approximate mode, the score threshold they are given is divided by
+    /// `recall_target`, so that blocks are also skipped when their documents can only beat the
+    /// current top documents by a small margin.
+    ///
+    /// `recall_target` is a ratio of scores rather than a probability: every matching document
+    /// missing from the result scores at most the score of the last returned document divided by
+    /// `recall_target`. The achieved bound is returned as
+    /// [`ApproximateTopDocsResult::max_missed_score`](crate::collector::ApproximateTopDocsResult::max_missed_score),
+    /// and is usually much tighter. A `recall_target` of `1.0` gives the same result as
+    /// `TopDocs`.
+    ///
+    /// # Panics
+    /// The method panics if `recall_target` is not within `]0, 1]`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use tantivy::collector::TopDocs;
+    /// use tantivy::query::QueryParser;
+    /// use tantivy::schema::{Schema, TEXT};
+    /// use tantivy::{doc, Index};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let title = schema_builder.add_text_field("title", TEXT);
+    /// let schema = schema_builder.build();
+    /// let index = Index::create_in_ram(schema);
+    ///
+    /// let mut index_writer = index.writer_with_num_threads(1, 20_000_000)?;
+    /// index_writer.add_document(doc!(title => "The Name of the Wind"))?;
+    /// index_writer.add_document(doc!(title => "The Diary of Muadib"))?;
+    /// index_writer.add_document(doc!(title => "A Dairy Cow"))?;
+    /// index_writer.add_document(doc!(title => "The Diary of a Young Girl"))?;
+    /// index_writer.commit()?;
+    ///
+    /// let searcher = index.reader()?.searcher();
+    /// let query_parser = QueryParser::for_index(&index, vec![title]);
+    /// let query = query_parser.parse_query("diary cow")?;
+    /// let result = searcher.search(&query, &TopDocs::with_limit(2).approximate(0.8))?;
+    ///
+    /// assert_eq!(result.docs.len(), 2);
+    /// if let Some(max_missed_score) = result.max_missed_score {
+    ///     assert!(max_missed_score >= result.docs[1].0);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn approximate(self, recall_target: Score) -> ApproximateTopDocs {
+        ApproximateTopDocs::new(self.0, recall_target)
+    }
+
     /// Set top-K to rank documents by a given fast field.
     ///
     /// If the field is not a fast or does not exist, this method returns successfully (it is not
@@ -518,6 +575,63 @@ impl TopDocs {
         TweakedScoreTopCollector::new(score_tweaker, self.0.into_tscore())
     }
 
+    /// Ranks the documents using a score tweaked given the fast field values of the documents.
+    ///
+    /// This is the same as [`tweak_score(...)`](TopDocs::tweak_score), except that:
+    /// - the tweaker is handed the [`SegmentColumns`](crate::collector::SegmentColumns) of each
+    ///   segment, with typed accessors to their columns, including multivalued, str and date
+    ///   columns.
+    /// - documents are scored a block at a time, so that the values of their columns can be fetched
+    ///   in batch rather than with per-document random accesses.
+    ///
+    /// ```rust
+    /// use tantivy::collector::{SegmentColumns, TopDocs};
+    /// use tantivy::query::AllQuery;
+    /// use tantivy::schema::{Schema, FAST, TEXT};
+    /// use tantivy::{doc, DocId, Index, IndexWriter, Score};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let title = schema_builder.add_text_field("title", TEXT);
+    /// let popularity = schema_builder.add_u64_field("popularity", FAST);
+    /// let index = Index::create_in_ram(schema_builder.build());
+    /// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+    /// index_writer.add_document(doc!(title => "The Diary of Muadib", popularity => 1u64))?;
+    /// index_writer.add_document(doc!(title => "A Dairy Cow", popularity => 10u64))?;
+    /// index_writer.commit()?;
+    ///
+    /// let top_docs_by_popularity = TopDocs::with_limit(10).tweak_score_with_columns(
+    ///     |segment_columns: &SegmentColumns| {
+    ///         // Columns are opened once per segment.
+    ///         let mut popularity = segment_columns.u64("popularity")?;
+    ///         Ok(move |docs: &[DocId], scores: &[Score], tweaked_scores: &mut Vec<Score>| {
+    ///             // The popularity of the whole block of documents is fetched at once.
+    ///             let popularities = popularity.fetch(docs);
+    ///             for (popularity, score) in popularities.iter().zip(scores) {
+    ///                 let popularity_boost = ((2 + popularity.unwrap_or(0)) as Score).log2();
+    ///                 tweaked_scores.push(popularity_boost * score);
+    ///             }
+    ///         })
+    ///     },
+    /// );
+    /// let searcher = index.reader()?.searcher();
+    /// let top_docs = searcher.search(&AllQuery, &top_docs_by_popularity)?;
+    /// assert_eq!(top_docs[0].1.doc_id, 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tweak_score_with_columns<TScore, TScoreSegmentTweaker, TScoreTweaker>(
+        self,
+        score_tweaker: TScoreTweaker,
+    ) -> impl Collector<Fruit = Vec<(TScore, DocAddress)>>
+    where
+        TScore: 'static + Send + Sync + Clone + PartialOrd,
+        TScoreSegmentTweaker: ColumnarScoreSegmentTweaker<TScore> + 'static,
+        TScoreTweaker: ColumnarScoreTweaker<TScore, Child = TScoreSegmentTweaker> + Send + Sync,
+    {
+        ColumnarScoreTopCollector::new(score_tweaker, self.0.into_tscore())
+    }
+
     /// Ranks the documents using a custom score.
     ///
     /// This method offers a convenient way to use a different score.
@@ -8,12 +8,27 @@ fn create_index(path: &str) {
     let mut schema_builder = Schema::builder();
     let label = schema_builder.add_text_field("label", TEXT | STORED);
     let date = schema_builder.add_date_field("date", INDEXED | STORED);
+    let category_options = TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default().set_postings_codec(PostingsCodec::Bitmap),
+    );
+    let category = schema_builder.add_text_field("category", category_options);
+    let body_options = TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+            .set_positions_checkpoint_interval(2),
+    );
+    let body = schema_builder.add_text_field("body", body_options);
     let schema = schema_builder.build();
     std::fs::create_dir_all(path).unwrap();
     let index = Index::create_in_dir(path, schema).unwrap();
     let mut index_writer = index.writer_with_num_threads(1, 20_000_000).unwrap();
     index_writer
-        .add_document(doc!(label => "dateformat", date => DateTime::from_timestamp_nanos(123456)))
+        .add_document(doc!(
+            label => "dateformat",
+            date => DateTime::from_timestamp_nanos(123456),
+            category => "books",
+            body => "a b c a b c a b c",
+        ))
         .unwrap();
     index_writer.commit().unwrap();
 }
@@ -55,6 +70,65 @@ fn test_format_7() {
     assert_date_time_precision(&index, DateTimePrecision::Nanoseconds);
 }
 
+/// feature flag quickwit uses a different dictionary type
+#[test]
+#[cfg(not(feature = "quickwit"))]
+fn test_format_8() {
+    let path = path_for_version("8");
+
+    let index = Index::open_in_dir(path).expect("Failed to open index");
+    assert_date_time_precision(&index, DateTimePrecision::Nanoseconds);
+    // the posting lists of `category` are encoded as bitmaps in v8
+    assert_term_doc_freq(&index, "category", "books", 1);
+}
+
+/// feature flag quickwit uses a different dictionary type
+#[test]
+#[cfg(not(feature = "quickwit"))]
+fn test_format_9() {
+    let path = path_for_version("9");
+
+    let index = Index::open_in_dir(path).expect("Failed to open index");
+    assert_date_time_precision(&index, DateTimePrecision::Nanoseconds);
+    assert_term_doc_freq(&index, "category", "books", 1);
+    // the positions of `body` are preceded by checkpoints in v9
+    assert_phrase_doc_freq(&index, "body", &["c", "a"], 1);
+    assert_phrase_doc_freq(&index, "body", &["c", "b"], 0);
+}
+
+#[cfg(not(feature = "quickwit"))]
+fn assert_phrase_doc_freq(index: &Index, field_name: &str, texts: &[&str], expected: usize) {
+    use collector::Count;
+    use query::PhraseQuery;
+    let searcher = index.reader().expect("Failed to create reader").searcher();
+    let field = index
+        .schema()
+        .get_field(field_name)
+        .expect("Field not found");
+    let terms = texts
+        .iter()
+        .map(|text| Term::from_field_text(field, text))
+        .collect();
+    let count = searcher
+        .search(&PhraseQuery::new(terms), &Count)
+        .expect("Search failed");
+    assert_eq!(count, expected);
+}
+
+#[cfg(not(feature = "quickwit"))]
+fn assert_term_doc_freq(index: &Index, field_name: &str, text: &str, expected_doc_freq: u32) {
+    use collector::Count;
+    use query::TermQuery;
+    let searcher = index.reader().expect("Failed to create reader").searcher();
+    let field = index
+        .schema()
+        .get_field(field_name)
+        .expect("Field not found");
+    let query = TermQuery::new(Term::from_field_text(field, text), IndexRecordOption::Basic);
+    let count = searcher.search(&query, &Count).expect("Search failed");
+    assert_eq!(count, expected_doc_freq as usize);
+}
+
 #[cfg(not(feature = "quickwit"))]
 fn assert_date_time_precision(index: &Index, doc_store_precision: DateTimePrecision) {
     use collector::TopDocs;
@@ -0,0 +1,257 @@
+//! Dense matrix of fast field values, fetched with
+//! [`Searcher::fetch_features()`](crate::Searcher::fetch_features).
+use columnar::{ColumnType, MonotonicallyMappableToU64};
+use common::DateTime;
+
+use crate::fastfield::FastFieldNotAvailableError;
+use crate::schema::{Field, FieldType};
+use crate::{DocAddress, DocId, Searcher, TantivyError};
+
+const FEATURE_COLUMN_TYPES: [ColumnType; 5] = [
+    ColumnType::U64,
+    ColumnType::I64,
+    ColumnType::F64,
+    ColumnType::DateTime,
+    ColumnType::Bool,
+];
+
+/// Dense matrix of fast field values, with one row per document and one column per field.
+///
+/// Values are stored column by column, so that each feature is a contiguous `&[f64]` slice that
+/// can be handed over as is to a reranker.
+///
+/// All values are converted to `f64`:
+/// - booleans are `0.0` or `1.0`,
+/// - dates are timestamps in seconds.
+///
+/// Missing values are `f64::NAN`. For multivalued fields, only the first value of each document
+/// is kept.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeatureMatrix {
+    num_docs: usize,
+    fields: Vec<Field>,
+    values: Vec<f64>,
+}
+
+impl FeatureMatrix {
+    pub(crate) fn fetch(
+        searcher: &Searcher,
+        doc_addresses: &[DocAddress],
+        fields: &[Field],
+    ) -> crate::Result<FeatureMatrix> {
+        let schema = searcher.schema();
+        let field_names = fields
+            .iter()
+            .map(|&field| {
+                let field_entry = schema.get_field_entry(field);
+                if !field_entry.is_fast() {
+                    return Err(FastFieldNotAvailableError::new(field_entry).into());
+                }
+                match field_entry.field_type() {
+                    FieldType::U64(_)
+                    | FieldType::I64(_)
+                    | FieldType::F64(_)
+                    | FieldType::Bool(_)
+                    | FieldType::Date(_) => Ok(field_entry.name()),
+                    _ => Err(TantivyError::SchemaError(format!(
+                        "field {:?} can't be used as a feature, only numeric, bool and date \
+                         fields are supported",
+                        field_entry.name()
+                    ))),
+                }
+            })
+            .collect::<crate::Result<Vec<&str>>>()?;
+
+        let num_docs = doc_addresses.len();
+        let mut values = vec![f64::NAN; num_docs * fields.len()];
+
+        // Documents are processed segment by segment, in doc id order.
+        let mut rows: Vec<(DocAddress, usize)> = doc_addresses
+            .iter()
+            .copied()
+            .enumerate()
+            .map(|(row, doc_address)| (doc_address, row))
+            .collect();
+        rows.sort_unstable();
+        let mut doc_ids: Vec<DocId> = Vec::new();
+        let mut first_vals: Vec<Option<u64>> = Vec::new();
+        let mut segment_start = 0;
+        while segment_start < rows.len() {
+            let segment_ord = rows[segment_start].0.segment_ord;
+            let segment_end = rows[segment_start..]
+                .iter()
+                .position(|(doc_address, _)| doc_address.segment_ord != segment_ord)
+                .map(|len| segment_start + len)
+                .unwrap_or(rows.len());
+            let segment_rows = &rows[segment_start..segment_end];
+            segment_start = segment_end;
+
+            doc_ids.clear();
+            doc_ids.extend(
+                segment_rows
+                    .iter()
+                    .map(|(doc_address, _)| doc_address.doc_id),
+            );
+            let fast_fields = searcher.segment_reader(segment_ord).fast_fields();
+            for (feature_ord, field_name) in field_names.iter().enumerate() {
+                let Some((column, column_type)) =
+                    fast_fields.u64_lenient_for_type(Some(&FEATURE_COLUMN_TYPES), field_name)?
+                else {
+                    continue;
+                };
+                first_vals.clear();
+                first_vals.resize(doc_ids.len(), None);
+                column.first_vals(&doc_ids, &mut first_vals);
+                let feature_values = &mut values[feature_ord * num_docs..][..num_docs];
+                for (&(_, row), val) in segment_rows.iter().zip(&first_vals) {
+                    if let Some(val) = val {
+                        feature_values[row] = to_f64(*val, column_type);
+                    }
+                }
+            }
+        }
+
+        Ok(FeatureMatrix {
+            num_docs,
+            fields: fields.to_vec(),
+            values,
+        })
+    }
+
+    /// Returns the number of documents, i.e. the number of rows.
+    pub fn num_docs(&self) -> usize {
+        self.num_docs
+    }
+
+    /// Returns the number of features, i.e. the number of columns.
+    pub fn num_features(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Returns the fields of the features, in the order of the columns.
+    pub fn fields(&self) -> &[Field] {
+        &self.fields
+    }
+
+    /// Returns the values of the feature at `feature_ord`, in the order of the documents.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `feature_ord` is out of bounds.
+    pub fn column(&self, feature_ord: usize) -> &[f64] {
+        assert!(feature_ord < self.num_features());
+        &self.values[feature_ord * self.num_docs..][..self.num_docs]
+    }
+
+    /// Returns the value of the feature at `feature_ord` for the document at `doc_ord`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `doc_ord` or `feature_ord` is out of bounds.
+    pub fn get(&self, doc_ord: usize, feature_ord: usize) -> f64 {
+        self.column(feature_ord)[doc_ord]
+    }
+
+    /// Returns all of the values, column after column.
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// Consumes the matrix and returns all of the values, column after column.
+    pub fn into_values(self) -> Vec<f64> {
+        self.values
+    }
+}
+
+fn to_f64(val: u64, column_type: ColumnType) -> f64 {
+    match column_type {
+        ColumnType::I64 => i64::from_u64(val) as f64,
+        ColumnType::F64 => f64::from_u64(val),
+        ColumnType::DateTime => DateTime::from_u64(val).into_timestamp_secs() as f64,
+        ColumnType::Bool => bool::from_u64(val) as u64 as f64,
+        _ => val as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::TopDocs;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, FAST, INDEXED, STRING};
+    use crate::{DateTime, DocAddress, Index, IndexWriter, Order, TantivyError};
+
+    #[test]
+    fn test_fetch_features() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST);
+        let popularity = schema_builder.add_f64_field("popularity", FAST);
+        let delta = schema_builder.add_i64_field("delta", FAST);
+        let in_stock = schema_builder.add_bool_field("in_stock", FAST);
+        let published = schema_builder.add_date_field("published", FAST);
+        let not_fast = schema_builder.add_u64_field("not_fast", INDEXED);
+        let text = schema_builder.add_text_field("text", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            id => 0u64,
+            popularity => 0.5f64,
+            delta => -3i64,
+            in_stock => true,
+            published => DateTime::from_timestamp_secs(1_000),
+        ))?;
+        index_writer.add_document(doc!(id => 1u64, popularity => 2.0f64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(id => 2u64, delta => 7i64, in_stock => false))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let mut doc_addresses: Vec<DocAddress> = searcher
+            .search(
+                &AllQuery,
+                &TopDocs::with_limit(10).order_by_u64_field("id", Order::Desc),
+            )?
+            .into_iter()
+            .map(|(_, doc_address)| doc_address)
+            .collect();
+        // Documents can be requested several times.
+        doc_addresses.push(doc_addresses[0]);
+
+        let features = searcher.fetch_features(
+            &doc_addresses,
+            &[popularity, delta, in_stock, published, id],
+        )?;
+        assert_eq!(features.num_docs(), 4);
+        assert_eq!(features.num_features(), 5);
+        assert_eq!(
+            features.fields(),
+            &[popularity, delta, in_stock, published, id]
+        );
+        assert_eq!(features.column(4), &[2.0, 1.0, 0.0, 2.0]);
+        assert!(features.column(0)[0].is_nan());
+        assert_eq!(&features.column(0)[1..3], &[2.0, 0.5]);
+        assert!(features.column(0)[3].is_nan());
+        assert_eq!(features.get(0, 1), 7.0);
+        assert!(features.get(1, 1).is_nan());
+        assert_eq!(features.get(2, 1), -3.0);
+        assert_eq!(features.get(0, 2), 0.0);
+        assert_eq!(features.get(2, 2), 1.0);
+        assert_eq!(features.get(2, 3), 1_000.0);
+        assert!(features.get(3, 3).is_nan());
+        assert_eq!(features.values().len(), 20);
+
+        let empty = searcher.fetch_features(&[], &[popularity])?;
+        assert_eq!(empty.num_docs(), 0);
+        assert!(empty.column(0).is_empty());
+
+        assert!(matches!(
+            searcher.fetch_features(&doc_addresses, &[not_fast]),
+            Err(TantivyError::SchemaError(_))
+        ));
+        assert!(matches!(
+            searcher.fetch_features(&doc_addresses, &[text]),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+}
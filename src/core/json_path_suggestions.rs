@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use common::json_path_writer::{JSON_END_OF_PATH, JSON_PATH_SEGMENT_SEP};
+
+use crate::core::json_utils::split_json_path;
+use crate::schema::{Field, Type};
+use crate::{Searcher, SegmentReader};
+
+/// A JSON path of an index, as suggested by [`Searcher::json_path_suggestions()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonPathSuggestion {
+    /// The full path, including the name of the JSON field, as accepted by the query parser.
+    ///
+    /// Dots within the keys of the JSON objects are escaped.
+    pub path: String,
+    /// The types of the values found at the path, in increasing order.
+    pub types: Vec<Type>,
+    /// Is the path indexed for search
+    pub indexed: bool,
+    /// Is the path stored in the columnar storage
+    pub fast: bool,
+    /// The number of documents having a value at the path.
+    pub num_docs: u64,
+}
+
+/// Types and options of a path, gathered over the segments.
+#[derive(Default)]
+struct PathInfo {
+    types: BTreeSet<Type>,
+    indexed: bool,
+    fast: bool,
+}
+
+/// Returns the encoded prefix of the JSON paths of `field_name` which can match `prefix`, or
+/// `None` if none of them can.
+fn encoded_json_path_prefix(field_name: &str, prefix: &str) -> Option<String> {
+    if field_name.starts_with(prefix) {
+        return Some(String::new());
+    }
+    let json_path_prefix = prefix.strip_prefix(field_name)?.strip_prefix('.')?;
+    let segments = split_json_path(json_path_prefix);
+    Some(segments.join(&(JSON_PATH_SEGMENT_SEP as char).to_string()))
+}
+
+/// Returns the path accepted by the query parser for an encoded JSON path.
+fn display_json_path(field_name: &str, encoded_json_path: &[u8]) -> String {
+    let mut path = field_name.to_string();
+    for segment in encoded_json_path.split(|&byte| byte == JSON_PATH_SEGMENT_SEP) {
+        path.push('.');
+        path.push_str(&String::from_utf8_lossy(segment).replace('.', "\\."));
+    }
+    path
+}
+
+/// Collects the JSON paths of `field` starting with `encoded_prefix` in a segment.
+fn collect_segment_json_paths(
+    segment_reader: &SegmentReader,
+    field: Field,
+    field_name: &str,
+    encoded_prefix: &str,
+    paths: &mut BTreeMap<String, PathInfo>,
+) -> crate::Result<()> {
+    let field_entry = segment_reader.schema().get_field_entry(field);
+    if field_entry.is_indexed() {
+        let inverted_index = segment_reader.inverted_index(field)?;
+        let term_dictionary = inverted_index.terms();
+        let mut lower_bound = encoded_prefix.as_bytes().to_vec();
+        loop {
+            let mut stream = term_dictionary.range().ge(&lower_bound).into_stream()?;
+            if !stream.advance() || !stream.key().starts_with(encoded_prefix.as_bytes()) {
+                break;
+            }
+            let key = stream.key();
+            let Some(end_of_path) = key.iter().position(|&byte| byte == JSON_END_OF_PATH) else {
+                break;
+            };
+            let Some(typ) = key
+                .get(end_of_path + 1)
+                .and_then(|&code| Type::from_code(code))
+            else {
+                break;
+            };
+            let path_info = paths
+                .entry(display_json_path(field_name, &key[..end_of_path]))
+                .or_default();
+            path_info.types.insert(typ);
+            path_info.indexed = true;
+            // Skips the other terms of the path having the same type.
+            lower_bound.clear();
+            lower_bound.extend_from_slice(&key[..end_of_path + 1]);
+            lower_bound.push(typ.to_code() + 1);
+        }
+    }
+    if field_entry.is_fast() {
+        let column_prefix = format!(
+            "{field_name}{}{encoded_prefix}",
+            JSON_PATH_SEGMENT_SEP as char
+        );
+        let columns = segment_reader
+            .fast_fields()
+            .columnar()
+            .list_columns_with_prefix(&column_prefix)?;
+        for (column_name, column_handle) in columns {
+            let encoded_json_path = &column_name.as_bytes()[field_name.len() + 1..];
+            let path_info = paths
+                .entry(display_json_path(field_name, encoded_json_path))
+                .or_default();
+            path_info
+                .types
+                .insert(Type::from(column_handle.column_type()));
+            path_info.fast = true;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn json_path_suggestions(
+    searcher: &Searcher,
+    prefix: &str,
+    limit: usize,
+) -> crate::Result<Vec<JsonPathSuggestion>> {
+    let mut paths: BTreeMap<String, PathInfo> = BTreeMap::new();
+    for (field, field_entry) in searcher.schema().fields() {
+        if field_entry.field_type().value_type() != Type::Json {
+            continue;
+        }
+        let Some(encoded_prefix) = encoded_json_path_prefix(field_entry.name(), prefix) else {
+            continue;
+        };
+        for segment_reader in searcher.segment_readers() {
+            collect_segment_json_paths(
+                segment_reader,
+                field,
+                field_entry.name(),
+                &encoded_prefix,
+                &mut paths,
+            )?;
+        }
+    }
+    paths
+        .into_iter()
+        .filter(|(path, _)| path.starts_with(prefix))
+        .take(limit)
+        .map(|(path, path_info)| {
+            let num_docs = searcher.num_docs_with_field(&path)?;
+            Ok(JsonPathSuggestion {
+                path,
+                types: path_info.types.into_iter().collect(),
+                indexed: path_info.indexed,
+                fast: path_info.fast,
+                num_docs,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JsonPathSuggestion;
+    use crate::schema::{JsonObjectOptions, Schema, Type, FAST, STORED, TEXT};
+    use crate::{Index, IndexWriter, TantivyDocument};
+
+    #[test]
+    fn test_json_path_suggestions() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_json_field("attributes", TEXT | FAST);
+        schema_builder.add_json_field(
+            "labels",
+            JsonObjectOptions::from(TEXT | STORED).set_expand_dots_enabled(),
+        );
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema.clone());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for json in [
+            r#"{"attributes": {"http": {"status": 200, "method": "GET"}, "host": "a"}}"#,
+            r#"{"attributes": {"http": {"status": "ok"}, "host.name": "b"}}"#,
+            r#"{"labels": {"http.route": "home", "env": "prod"}}"#,
+        ] {
+            index_writer.add_document(TantivyDocument::parse_json(&schema, json)?)?;
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+
+        let suggestions = searcher.json_path_suggestions("attributes.h", 10)?;
+        let paths: Vec<&str> = suggestions
+            .iter()
+            .map(|suggestion| suggestion.path.as_str())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "attributes.host",
+                "attributes.host\\.name",
+                "attributes.http.method",
+                "attributes.http.status",
+            ]
+        );
+        assert_eq!(
+            suggestions[3],
+            JsonPathSuggestion {
+                path: "attributes.http.status".to_string(),
+                types: vec![Type::I64, Type::Str],
+                indexed: true,
+                fast: true,
+                num_docs: 2,
+            }
+        );
+        assert_eq!(suggestions[1].num_docs, 1);
+
+        let suggestions = searcher.json_path_suggestions("attributes.http.", 1)?;
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].path, "attributes.http.method");
+
+        // Dots within keys are expanded, and paths of all of the JSON fields matching the prefix
+        // are suggested.
+        let suggestions = searcher.json_path_suggestions("lab", 10)?;
+        let paths: Vec<&str> = suggestions
+            .iter()
+            .map(|suggestion| suggestion.path.as_str())
+            .collect();
+        assert_eq!(paths, ["labels.env", "labels.http.route"]);
+        assert_eq!(
+            suggestions[1],
+            JsonPathSuggestion {
+                path: "labels.http.route".to_string(),
+                types: vec![Type::Str],
+                indexed: true,
+                fast: false,
+                num_docs: 1,
+            }
+        );
+        assert!(searcher.json_path_suggestions("other", 10)?.is_empty());
+        Ok(())
+    }
+}
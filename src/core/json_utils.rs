@@ -120,10 +120,21 @@ pub(crate) fn index_json_value<'a, V: Value<'a>>(
         term_buffer.append_bytes(&[typ.to_code()]);
     };
 
+    let uuid_str;
     match json_value.as_value() {
         ReferenceValue::Leaf(leaf) => match leaf {
             ReferenceValueLeaf::Null => {}
-            ReferenceValueLeaf::Str(val) => {
+            ReferenceValueLeaf::Str(_) | ReferenceValueLeaf::Uuid(_) => {
+                // Dynamic fields have no uuid type: uuids are indexed as their string
+                // representation, the way they are serialized in JSON.
+                let val = match leaf {
+                    ReferenceValueLeaf::Uuid(uuid) => {
+                        uuid_str = uuid.to_string();
+                        uuid_str.as_str()
+                    }
+                    ReferenceValueLeaf::Str(val) => val,
+                    _ => unreachable!(),
+                };
                 let mut token_stream = text_analyzer.token_stream(val);
                 let unordered_id = ctx
                     .path_to_unordered_id
@@ -1,6 +1,9 @@
 mod executor;
+mod feature_matrix;
+mod json_path_suggestions;
 #[doc(hidden)]
 pub mod json_utils;
+mod scoped_searcher;
 pub mod searcher;
 
 use std::path::Path;
@@ -8,12 +11,25 @@ use std::path::Path;
 use once_cell::sync::Lazy;
 
 pub use self::executor::Executor;
+pub use self::feature_matrix::FeatureMatrix;
+pub use self::json_path_suggestions::JsonPathSuggestion;
+pub use self::scoped_searcher::ScopedSearcher;
 pub use self::searcher::{Searcher, SearcherGeneration};
 
 /// The meta file contains all the information about the list of segments and the schema
 /// of the index.
 pub static META_FILEPATH: Lazy<&'static Path> = Lazy::new(|| Path::new("meta.json"));
 
+/// The merge statistics file contains the statistics of the last merges of the index.
+///
+/// See [`MergeStatsHistory`](crate::indexer::MergeStatsHistory).
+pub static MERGE_STATS_FILEPATH: Lazy<&'static Path> = Lazy::new(|| Path::new(".merge_stats.json"));
+
+/// The bulk checkpoint file contains the checkpoint of the last batch committed by a
+/// [`BulkLoader`](crate::indexer::BulkLoader).
+pub static BULK_CHECKPOINT_FILEPATH: Lazy<&'static Path> =
+    Lazy::new(|| Path::new(".bulk_checkpoint.json"));
+
 /// The managed file contains a list of files that were created by the tantivy
 /// and will therefore be garbage collected when they are deemed useless by tantivy.
 ///
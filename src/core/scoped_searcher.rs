@@ -0,0 +1,281 @@
+use std::sync::Arc;
+
+use crate::collector::Collector;
+use crate::core::{Executor, Searcher};
+use crate::query::{AllQuery, EnableScoring, Query, TenantQuery, Weight};
+use crate::schema::document::DocumentDeserialize;
+use crate::schema::{Field, Schema, Term};
+use crate::{DocAddress, SegmentReader, TantivyError};
+
+/// A [`Searcher`] restricted to the documents of a single tenant.
+///
+/// Every query run through a `ScopedSearcher` is wrapped in a [`TenantQuery`], and the collectors
+/// are handed segment readers whose [scope](SegmentReader::scope) is the tenant.
+/// The collectors of tantivy, aggregations included, only see the documents of the tenant.
+/// Custom collectors which collect documents not matched by the query need to restrict themselves
+/// to the scope of the segment reader.
+/// Fetching a document of another tenant from the doc store returns an error.
+///
+/// The underlying searcher is deliberately not exposed.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, STRING};
+/// use tantivy::{doc, Index, IndexWriter, ScopedSearcher, Term};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let tenant = schema_builder.add_text_field("tenant", STRING);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(tenant => "acme"))?;
+/// index_writer.add_document(doc!(tenant => "initech"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let scoped_searcher =
+///     ScopedSearcher::for_tenant(searcher, Term::from_field_text(tenant, "acme"))?;
+/// assert_eq!(scoped_searcher.search(&AllQuery, &Count)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ScopedSearcher {
+    searcher: Searcher,
+    tenant_field: Field,
+    tenant: String,
+    scope: Arc<dyn Weight>,
+    segment_readers: Vec<SegmentReader>,
+}
+
+impl ScopedSearcher {
+    /// Creates a `ScopedSearcher` restricted to the documents containing the `tenant` term.
+    ///
+    /// # Errors
+    /// Returns `TantivyError::InvalidArgument` if the term is not a text term, and
+    /// `TantivyError::SchemaError` if its field is not indexed.
+    pub fn for_tenant(searcher: Searcher, tenant: Term) -> crate::Result<ScopedSearcher> {
+        let tenant_field = tenant.field();
+        let field_entry = searcher.schema().get_field_entry(tenant_field);
+        if !field_entry.is_indexed() {
+            return Err(TantivyError::SchemaError(format!(
+                "The tenant field `{}` needs to be indexed.",
+                field_entry.name()
+            )));
+        }
+        let tenant = tenant
+            .value()
+            .as_str()
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument(
+                    format!("The tenant term {tenant:?} is not a text term.").into(),
+                )
+            })?
+            .to_string();
+        let scope: Arc<dyn Weight> = TenantQuery::new(tenant_field, &tenant, Box::new(AllQuery))
+            .weight(EnableScoring::disabled_from_searcher(&searcher))?
+            .into();
+        let segment_readers = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.with_scope(scope.clone()))
+            .collect();
+        Ok(ScopedSearcher {
+            searcher,
+            tenant_field,
+            tenant,
+            scope,
+            segment_readers,
+        })
+    }
+
+    /// Returns the tenant the searcher is restricted to.
+    pub fn tenant(&self) -> &str {
+        &self.tenant
+    }
+
+    /// Access the schema associated with the index of this searcher.
+    pub fn schema(&self) -> &Schema {
+        self.searcher.schema()
+    }
+
+    /// Returns the number of documents of the tenant.
+    pub fn num_docs(&self) -> crate::Result<u64> {
+        let mut num_docs = 0u64;
+        for segment_reader in &self.segment_readers {
+            num_docs += u64::from(self.scope.count(segment_reader)?);
+        }
+        Ok(num_docs)
+    }
+
+    /// Fetches a document of the tenant from tantivy's store given a [`DocAddress`].
+    ///
+    /// # Errors
+    /// Returns `TantivyError::InvalidArgument` if the document does not belong to the tenant.
+    pub fn doc<D: DocumentDeserialize>(&self, doc_address: DocAddress) -> crate::Result<D> {
+        let segment_reader = &self.segment_readers[doc_address.segment_ord as usize];
+        let mut scorer = self.scope.scorer(segment_reader, 1.0)?;
+        if scorer.seek(doc_address.doc_id) != doc_address.doc_id {
+            return Err(TantivyError::InvalidArgument(
+                format!(
+                    "Document {doc_address:?} does not belong to tenant {:?}.",
+                    self.tenant
+                )
+                .into(),
+            ));
+        }
+        self.searcher.doc(doc_address)
+    }
+
+    /// Runs a query restricted to the documents of the tenant.
+    ///
+    /// See [`Searcher::search()`].
+    pub fn search<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+    ) -> crate::Result<C::Fruit> {
+        let enabled_scoring = if collector.requires_scoring() {
+            EnableScoring::enabled_from_searcher(&self.searcher)
+        } else {
+            EnableScoring::disabled_from_searcher(&self.searcher)
+        };
+        let executor = self.searcher.index().search_executor();
+        self.search_with_executor(query, collector, executor, enabled_scoring)
+    }
+
+    /// Same as [`search(...)`](ScopedSearcher::search) but multithreaded.
+    ///
+    /// See [`Searcher::search_with_executor()`].
+    pub fn search_with_executor<C: Collector>(
+        &self,
+        query: &dyn Query,
+        collector: &C,
+        executor: &Executor,
+        enabled_scoring: EnableScoring,
+    ) -> crate::Result<C::Fruit> {
+        let weight = TenantQuery::new(self.tenant_field, &self.tenant, query.box_clone())
+            .weight(enabled_scoring)?;
+        let fruits = executor.map(
+            |(segment_ord, segment_reader)| {
+                collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)
+            },
+            self.segment_readers
+                .iter()
+                .enumerate()
+                .filter(|(_, segment_reader)| collector.should_collect_segment(segment_reader)),
+        )?;
+        collector.merge_fruits(fruits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ScopedSearcher;
+    #[cfg(feature = "aggregation")]
+    use crate::aggregation::agg_req::Aggregations;
+    #[cfg(feature = "aggregation")]
+    use crate::aggregation::agg_result::AggregationResults;
+    #[cfg(feature = "aggregation")]
+    use crate::aggregation::AggregationCollector;
+    use crate::collector::{Count, TopDocs};
+    use crate::query::AllQuery;
+    #[cfg(feature = "aggregation")]
+    use crate::query::TermQuery;
+    #[cfg(feature = "aggregation")]
+    use crate::schema::{IndexRecordOption, INDEXED};
+    use crate::schema::{Schema, Value, FAST, STORED, STRING};
+    use crate::{Index, IndexWriter, TantivyDocument, Term};
+
+    #[test]
+    fn test_scoped_searcher() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING | STORED);
+        let price = schema_builder.add_u64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tenant => "acme", price => 10u64))?;
+        index_writer.add_document(doc!(tenant => "initech", price => 1000u64))?;
+        index_writer.add_document(doc!(tenant => "acme", price => 20u64))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let scoped_searcher =
+            ScopedSearcher::for_tenant(searcher, Term::from_field_text(tenant, "acme"))?;
+        assert_eq!(scoped_searcher.tenant(), "acme");
+        assert_eq!(scoped_searcher.num_docs()?, 2);
+        assert_eq!(scoped_searcher.search(&AllQuery, &Count)?, 2);
+
+        let top_docs = scoped_searcher.search(&AllQuery, &TopDocs::with_limit(10))?;
+        assert_eq!(top_docs.len(), 2);
+        for (_, doc_address) in top_docs {
+            let doc: TantivyDocument = scoped_searcher.doc(doc_address)?;
+            assert_eq!(doc.get_first(tenant).unwrap().as_str(), Some("acme"));
+        }
+        assert!(scoped_searcher
+            .doc::<TantivyDocument>(crate::DocAddress::new(0, 1))
+            .is_err());
+
+        #[cfg(feature = "aggregation")]
+        {
+            let aggregations: Aggregations =
+                serde_json::from_str(r#"{ "max_price": { "max": { "field": "price" } } }"#)
+                    .unwrap();
+            let collector = AggregationCollector::from_aggs(aggregations, Default::default());
+            let aggregation_results: AggregationResults =
+                scoped_searcher.search(&AllQuery, &collector)?;
+            let aggregation_results = serde_json::to_value(aggregation_results).unwrap();
+            assert_eq!(aggregation_results["max_price"]["value"], 20.0);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "aggregation")]
+    #[test]
+    fn test_scoped_searcher_global_aggregation() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let price = schema_builder.add_u64_field("price", FAST | INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tenant => "acme", price => 10u64))?;
+        index_writer.add_document(doc!(tenant => "initech", price => 1000u64))?;
+        index_writer.add_document(doc!(tenant => "acme", price => 20u64))?;
+        index_writer.commit()?;
+        // A segment without any document of the tenant.
+        index_writer.add_document(doc!(tenant => "initech", price => 2000u64))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let scoped_searcher =
+            ScopedSearcher::for_tenant(searcher, Term::from_field_text(tenant, "acme"))?;
+        let aggregations: Aggregations = serde_json::from_str(
+            r#"{ "all": { "global": {}, "aggs": { "max_price": { "max": { "field": "price" } } } } }"#,
+        )
+        .unwrap();
+        let collector = AggregationCollector::from_aggs(aggregations, Default::default());
+        let query = TermQuery::new(Term::from_field_u64(price, 10), IndexRecordOption::Basic);
+        let (count, aggregation_results): (usize, AggregationResults) =
+            scoped_searcher.search(&query, &(Count, collector))?;
+        assert_eq!(count, 1);
+        let aggregation_results = serde_json::to_value(aggregation_results).unwrap();
+        assert_eq!(aggregation_results["all"]["doc_count"], 2);
+        assert_eq!(aggregation_results["all"]["max_price"]["value"], 20.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_scoped_searcher_invalid_tenant() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", FAST);
+        let tenant = schema_builder.add_text_field("tenant", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader()?.searcher();
+        assert!(ScopedSearcher::for_tenant(searcher.clone(), Term::from_field_u64(id, 1)).is_err());
+        assert!(
+            ScopedSearcher::for_tenant(searcher, Term::from_field_text(tenant, "acme")).is_err()
+        );
+        Ok(())
+    }
+}
@@ -2,12 +2,19 @@ use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::{fmt, io};
 
+use columnar::{Column, DynamicColumn, HasAssociatedColumnType};
+
 use crate::collector::Collector;
-use crate::core::Executor;
+use crate::core::json_path_suggestions::json_path_suggestions;
+use crate::core::{Executor, FeatureMatrix, JsonPathSuggestion};
+use crate::fastfield::distinct_values::{self, DistinctStrValues};
 use crate::index::{SegmentId, SegmentReader};
-use crate::query::{Bm25StatisticsProvider, EnableScoring, Query};
+use crate::query::non_match_explanation::explain_non_match;
+use crate::query::{
+    Bm25StatisticsProvider, EnableScoring, ExistsQuery, NonMatchExplanation, Query,
+};
 use crate::schema::document::DocumentDeserialize;
-use crate::schema::{Schema, Term};
+use crate::schema::{Field, Schema, Term};
 use crate::space_usage::SearcherSpaceUsage;
 use crate::store::{CacheStats, StoreReader};
 use crate::{DocAddress, Index, Opstamp, TrackedObject};
@@ -65,6 +72,9 @@ impl SearcherGeneration {
 ///
 /// It guarantees that the `Segment` will not be removed before
 /// the destruction of the `Searcher`.
+///
+/// A `Searcher` is `Send` and `Sync`, and cloning it is cheap. It can be moved to, or shared
+/// with, other threads, e.g. to search its segments in parallel.
 #[derive(Clone)]
 pub struct Searcher {
     inner: Arc<SearcherInner>,
@@ -81,6 +91,14 @@ impl Searcher {
         self.inner.generation.as_ref()
     }
 
+    /// Returns the opstamp of the commit this `Searcher` was opened on.
+    ///
+    /// The changes of that commit and of the previous ones are visible to the `Searcher`.
+    /// See [`IndexReader::wait_for()`](crate::IndexReader::wait_for).
+    pub fn opstamp(&self) -> Opstamp {
+        self.inner.opstamp
+    }
+
     /// Fetches a document from tantivy's store given a [`DocAddress`].
     ///
     /// The searcher uses the segment ordinal to route the
@@ -90,6 +108,35 @@ impl Searcher {
         store_reader.get(doc_address.doc_id)
     }
 
+    /// Fetches the values of the given fields of a document from tantivy's store.
+    ///
+    /// Only the requested fields are decoded, so this is cheaper than [`Searcher::doc()`]
+    /// when retrieving, say, a small id field of documents with a large stored body.
+    pub fn doc_fields<D: DocumentDeserialize>(
+        &self,
+        doc_address: DocAddress,
+        fields: &[Field],
+    ) -> crate::Result<D> {
+        let store_reader = &self.inner.store_readers[doc_address.segment_ord as usize];
+        store_reader.get_fields(doc_address.doc_id, fields)
+    }
+
+    /// Fetches the fast field values of `fields` for the given documents, as a dense
+    /// [`FeatureMatrix`] with one row per document, in the order of `doc_addresses`.
+    ///
+    /// This is meant to hand the top candidates of a search over to an external reranker: the
+    /// documents are read segment by segment in doc id order, and each field ends up as a
+    /// contiguous column of `f64` values.
+    ///
+    /// The fields are required to be numeric, bool or date fast fields.
+    pub fn fetch_features(
+        &self,
+        doc_addresses: &[DocAddress],
+        fields: &[Field],
+    ) -> crate::Result<FeatureMatrix> {
+        FeatureMatrix::fetch(self, doc_addresses, fields)
+    }
+
     /// The cache stats for the underlying store reader.
     ///
     /// Aggregates the sum for each segment store reader.
@@ -128,6 +175,84 @@ impl Searcher {
             .sum::<u64>()
     }
 
+    /// Returns the overall number of documents having at least one value in the given field.
+    ///
+    /// The field must be fast or indexed, and can be a JSON path. For indexed fields that are not
+    /// fast, this requires going through all of the terms of the field, unless the index records
+    /// the presence of fields (see
+    /// [`IndexSettings::field_presence`](crate::IndexSettings::field_presence)).
+    pub fn num_docs_with_field(&self, field_name: &str) -> crate::Result<u64> {
+        let weight = ExistsQuery::new(field_name.to_string(), false)
+            .weight(EnableScoring::disabled_from_searcher(self))?;
+        let mut num_docs = 0;
+        for segment_reader in &self.inner.segment_readers {
+            num_docs += u64::from(weight.count(segment_reader)?);
+        }
+        Ok(num_docs)
+    }
+
+    /// Returns the JSON paths of the index starting with `prefix`, e.g. to autocomplete field
+    /// names in a query builder for documents with a dynamic schema.
+    ///
+    /// The prefix is matched against the full paths, e.g. `attributes.http.st` or `attr`. At
+    /// most `limit` paths are returned, sorted by path, along with the types of their values and
+    /// the number of documents having a value at the path.
+    ///
+    /// The paths are listed from the term dictionaries of the indexed JSON fields and from the
+    /// column dictionaries of the fast JSON fields, only visiting one term per path and type.
+    /// Counting the documents of a path which is indexed but not fast requires going through all
+    /// of its terms though (see [`Searcher::num_docs_with_field()`]).
+    pub fn json_path_suggestions(
+        &self,
+        prefix: &str,
+        limit: usize,
+    ) -> crate::Result<Vec<JsonPathSuggestion>> {
+        json_path_suggestions(self, prefix, limit)
+    }
+
+    /// Explains why the query does not match the document, e.g. to debug relevance or access
+    /// control issues. Returns `None` if the query matches the document.
+    ///
+    /// The query tree is walked down from the root: for each boolean query, the explanation
+    /// lists the required clauses not matching the document, the excluded clauses matching it,
+    /// or the optional clauses not matching it if too few of them match. Each clause is then
+    /// explained in turn, down to the leaf queries, e.g. a term absent from the document or a
+    /// value out of the bounds of a range query.
+    pub fn explain_non_match(
+        &self,
+        query: &dyn Query,
+        doc_address: DocAddress,
+    ) -> crate::Result<Option<NonMatchExplanation>> {
+        explain_non_match(self, query, doc_address)
+    }
+
+    /// Returns the distinct values of a string fast field, over all of the segments.
+    ///
+    /// Unlike a terms aggregation, the values are streamed in increasing order straight from the
+    /// dictionaries of the columns, e.g. to export the list of values of a field. The values
+    /// only held by deleted documents are skipped. A field missing from the schema has no values.
+    pub fn distinct_str_values(&self, field_name: &str) -> crate::Result<DistinctStrValues> {
+        DistinctStrValues::open(self, field_name)
+    }
+
+    /// Returns the distinct values of a numeric, date, bool or ip address fast field, over all of
+    /// the segments, in increasing order.
+    ///
+    /// The values of each segment are deduplicated and sorted before being merged. The values
+    /// only held by deleted documents are skipped. A field missing from the schema, or which
+    /// does not have the type `T`, has no values. NaN values are ordered after all of the other
+    /// values.
+    pub fn distinct_numeric_values<T>(
+        &self,
+        field_name: &str,
+    ) -> crate::Result<impl Iterator<Item = T>>
+    where
+        T: HasAssociatedColumnType + PartialOrd + Copy + fmt::Debug + Send + Sync + 'static,
+        DynamicColumn: Into<Option<Column<T>>>,
+    {
+        distinct_values::distinct_numeric_values(self, field_name)
+    }
+
     /// Return the overall number of documents containing
     /// the given term.
     pub fn doc_freq(&self, term: &Term) -> crate::Result<u64> {
@@ -230,7 +355,10 @@ impl Searcher {
             |(segment_ord, segment_reader)| {
                 collector.collect_segment(weight.as_ref(), segment_ord as u32, segment_reader)
             },
-            segment_readers.iter().enumerate(),
+            segment_readers
+                .iter()
+                .enumerate()
+                .filter(|(_, segment_reader)| collector.should_collect_segment(segment_reader)),
         )?;
         collector.merge_fruits(fruits)
     }
@@ -258,6 +386,7 @@ impl From<Arc<SearcherInner>> for Searcher {
 pub(crate) struct SearcherInner {
     schema: Schema,
     index: Index,
+    opstamp: Opstamp,
     segment_readers: Vec<SegmentReader>,
     store_readers: Vec<StoreReader>,
     generation: TrackedObject<SearcherGeneration>,
@@ -268,6 +397,7 @@ impl SearcherInner {
     pub(crate) fn new(
         schema: Schema,
         index: Index,
+        opstamp: Opstamp,
         segment_readers: Vec<SegmentReader>,
         generation: TrackedObject<SearcherGeneration>,
         doc_store_cache_num_blocks: usize,
@@ -288,6 +418,7 @@ impl SearcherInner {
         Ok(SearcherInner {
             schema,
             index,
+            opstamp,
             segment_readers,
             store_readers,
             generation,
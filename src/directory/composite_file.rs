@@ -105,14 +105,26 @@ impl std::fmt::Debug for CompositeFile {
     }
 }
 
+fn corrupted_composite_file(msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Corrupted composite file: {msg}."),
+    )
+}
+
 impl CompositeFile {
     /// Opens a composite file stored in a given
     /// `FileSlice`.
     pub fn open(data: &FileSlice) -> io::Result<CompositeFile> {
         let end = data.len();
-        let footer_len_data = data.slice_from(end - 4).read_bytes()?;
+        let footer_len_offset = end
+            .checked_sub(4)
+            .ok_or_else(|| corrupted_composite_file("The file is too short"))?;
+        let footer_len_data = data.slice_from(footer_len_offset).read_bytes()?;
         let footer_len = u32::deserialize(&mut footer_len_data.as_slice())? as usize;
-        let footer_start = end - 4 - footer_len;
+        let footer_start = footer_len_offset
+            .checked_sub(footer_len)
+            .ok_or_else(|| corrupted_composite_file("The footer length exceeds the file"))?;
         let footer_data = data
             .slice(footer_start..footer_start + footer_len)
             .read_bytes()?;
@@ -123,9 +135,14 @@ impl CompositeFile {
         let mut offsets = vec![];
         let mut field_index = HashMap::new();
 
-        let mut offset = 0;
+        let mut offset = 0usize;
         for _ in 0..num_fields {
-            offset += VInt::deserialize(&mut footer_buffer)?.0 as usize;
+            let delta = VInt::deserialize(&mut footer_buffer)?.0;
+            offset = usize::try_from(delta)
+                .ok()
+                .and_then(|delta| offset.checked_add(delta))
+                .filter(|&offset| offset <= footer_start)
+                .ok_or_else(|| corrupted_composite_file("A field offset exceeds the file"))?;
             let file_addr = FileAddr::deserialize(&mut footer_buffer)?;
             offsets.push(offset);
             file_addrs.push(file_addr);
@@ -167,6 +184,19 @@ impl CompositeFile {
             .map(|byte_range| self.data.slice(byte_range.clone()))
     }
 
+    /// Returns all of the `FileSlice`s associated with a given `Field`, along with their index,
+    /// in increasing index order.
+    pub fn open_read_all(&self, field: Field) -> Vec<(usize, FileSlice)> {
+        let mut file_slices: Vec<(usize, FileSlice)> = self
+            .offsets_index
+            .iter()
+            .filter(|(file_addr, _)| file_addr.field == field)
+            .map(|(file_addr, byte_range)| (file_addr.idx, self.data.slice(byte_range.clone())))
+            .collect();
+        file_slices.sort_by_key(|(idx, _)| *idx);
+        file_slices
+    }
+
     pub fn space_usage(&self) -> PerFieldSpaceUsage {
         let mut fields = Vec::new();
         for (&field_addr, byte_range) in &self.offsets_index {
@@ -187,7 +217,7 @@ mod test {
     use common::{BinarySerializable, VInt};
 
     use super::{CompositeFile, CompositeWrite};
-    use crate::directory::{Directory, RamDirectory};
+    use crate::directory::{Directory, FileSlice, RamDirectory};
     use crate::schema::Field;
 
     #[test]
@@ -283,4 +313,27 @@ mod test {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_composite_file_corrupted() -> crate::Result<()> {
+        let mut buffer = Vec::new();
+        {
+            let mut composite_write = CompositeWrite::wrap(&mut buffer);
+            for field_id in 0u32..3 {
+                let mut write = composite_write.for_field(Field::from_field_id(field_id));
+                VInt(u64::from(field_id) * 1_000).serialize(&mut write)?;
+                write.flush()?;
+            }
+            composite_write.close()?;
+        }
+        for len in 0..buffer.len() {
+            // Truncated files may or may not be detected, but they should never panic.
+            let _ = CompositeFile::open(&FileSlice::from(buffer[..len].to_vec()));
+        }
+        let mut corrupted = buffer.clone();
+        let footer_len_offset = corrupted.len() - 4;
+        corrupted[footer_len_offset..].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(CompositeFile::open(&FileSlice::from(corrupted)).is_err());
+        Ok(())
+    }
 }
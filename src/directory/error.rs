@@ -19,7 +19,7 @@ pub enum LockError {
     LockBusy,
     /// Trying to acquire a lock failed with an `IoError`
     #[error("Failed to acquire the lock due to an io:Error.")]
-    IoError(Arc<io::Error>),
+    IoError(#[source] Arc<io::Error>),
 }
 
 impl LockError {
@@ -40,11 +40,12 @@ pub enum OpenDirectoryError {
     NotADirectory(PathBuf),
     /// Failed to create a temp directory.
     #[error("Failed to create a temporary directory: '{0}'.")]
-    FailedToCreateTempDir(Arc<io::Error>),
+    FailedToCreateTempDir(#[source] Arc<io::Error>),
     /// IoError
     #[error("IoError '{io_error:?}' while create directory in: '{directory_path:?}'.")]
     IoError {
         /// underlying io Error.
+        #[source]
         io_error: Arc<io::Error>,
         /// directory we tried to open.
         directory_path: PathBuf,
@@ -73,6 +74,7 @@ pub enum OpenWriteError {
     #[error("IoError '{io_error:?}' while opening file for write: '{filepath}'.")]
     IoError {
         /// The underlying `io::Error`.
+        #[source]
         io_error: Arc<io::Error>,
         /// File path of the file that tantivy failed to open for write.
         filepath: PathBuf,
@@ -160,6 +162,7 @@ pub enum OpenReadError {
     )]
     IoError {
         /// The underlying `io::Error`.
+        #[source]
         io_error: Arc<io::Error>,
         /// File path of the file that tantivy failed to open for read.
         filepath: PathBuf,
@@ -189,6 +192,7 @@ pub enum DeleteError {
     #[error("The following IO error happened while deleting file '{filepath}': '{io_error:?}'.")]
     IoError {
         /// The underlying `io::Error`.
+        #[source]
         io_error: Arc<io::Error>,
         /// File path of the file that tantivy failed to delete.
         filepath: PathBuf,
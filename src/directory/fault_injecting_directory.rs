@@ -0,0 +1,433 @@
+use std::fmt;
+use std::io::{self, BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use common::HasLen;
+
+use crate::directory::error::{DeleteError, LockError, OpenReadError, OpenWriteError};
+use crate::directory::{
+    AntiCallToken, Directory, DirectoryLock, FileHandle, Lock, OwnedBytes, TerminatingWrite,
+    WatchCallback, WatchHandle, WritePtr,
+};
+
+/// Fault injected by a [`FaultInjectingDirectory`] in the files it matches.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// Opening the file for write, or writing it atomically, fails with an IO error.
+    WriteError,
+    /// Only the first `num_bytes` bytes written to the file are persisted, after which the
+    /// writes fail with an IO error, as if the disk got full or the process crashed.
+    ///
+    /// Atomic writes fail without changing the file.
+    PartialWrite {
+        /// Number of bytes persisted.
+        num_bytes: usize,
+    },
+    /// Reading the file fails with an IO error.
+    ReadError,
+    /// Reading the file is delayed by the given duration, as with a slow disk or a remote
+    /// storage.
+    ReadDelay(Duration),
+    /// The bits of the byte at `offset` in the file are flipped when it is read, so that the
+    /// checksum of the file no longer matches its content.
+    Corruption {
+        /// Offset of the corrupted byte in the file.
+        offset: usize,
+    },
+}
+
+type FileMatcher = Arc<dyn Fn(&Path) -> bool + Send + Sync>;
+
+#[derive(Clone, Default)]
+struct Faults(Arc<RwLock<Vec<(FileMatcher, Fault)>>>);
+
+impl Faults {
+    fn for_path(&self, path: &Path) -> Vec<Fault> {
+        self.0
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(file_matcher, _)| file_matcher(path))
+            .map(|(_, fault)| fault.clone())
+            .collect()
+    }
+}
+
+fn injected_error(path: &Path, fault: &Fault) -> io::Error {
+    io::Error::other(format!("Injected fault {fault:?} on {path:?}"))
+}
+
+/// Directory wrapping another directory, and injecting faults in some of its files.
+///
+/// It makes it possible for applications to test how they recover from realistic index
+/// failures: IO errors, partial writes, slow reads and corrupted files. The faults apply to the
+/// files matching a predicate on their path, from the moment they are injected until they
+/// are cleared, including to the files which are already open.
+///
+/// It is only available with the `test-utils` feature.
+///
+/// ```rust
+/// use tantivy::directory::{Fault, FaultInjectingDirectory, RamDirectory};
+/// use tantivy::schema::{Schema, STORED, STRING};
+/// use tantivy::{doc, DocAddress, Index, IndexWriter, TantivyDocument};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let directory = FaultInjectingDirectory::wrap(RamDirectory::create());
+/// let mut schema_builder = Schema::builder();
+/// let id = schema_builder.add_text_field("id", STRING | STORED);
+/// let index = Index::create(directory.clone(), schema_builder.build(), Default::default())?;
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(id => "a"))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// directory.inject_fault(
+///     |path| path.extension().is_some_and(|extension| extension == "store"),
+///     Fault::ReadError,
+/// );
+/// assert!(searcher.doc::<TantivyDocument>(DocAddress::new(0, 0)).is_err());
+/// directory.clear_faults();
+/// assert!(searcher.doc::<TantivyDocument>(DocAddress::new(0, 0)).is_ok());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct FaultInjectingDirectory {
+    underlying: Box<dyn Directory>,
+    faults: Faults,
+}
+
+impl FaultInjectingDirectory {
+    /// Wraps a directory, without injecting any fault yet.
+    pub fn wrap<D: Into<Box<dyn Directory>>>(directory: D) -> FaultInjectingDirectory {
+        FaultInjectingDirectory {
+            underlying: directory.into(),
+            faults: Faults::default(),
+        }
+    }
+
+    /// Injects a fault in the files whose path matches `file_matcher`.
+    ///
+    /// Paths are relative to the directory, e.g. `meta.json`. The files of a segment are
+    /// named after its id, and can be matched by extension, e.g. `store` or `idx`.
+    pub fn inject_fault(
+        &self,
+        file_matcher: impl Fn(&Path) -> bool + Send + Sync + 'static,
+        fault: Fault,
+    ) {
+        self.faults
+            .0
+            .write()
+            .unwrap()
+            .push((Arc::new(file_matcher), fault));
+    }
+
+    /// Removes all of the injected faults.
+    pub fn clear_faults(&self) {
+        self.faults.0.write().unwrap().clear();
+    }
+}
+
+impl fmt::Debug for FaultInjectingDirectory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FaultInjectingDirectory({:?})", self.underlying)
+    }
+}
+
+impl Directory for FaultInjectingDirectory {
+    fn get_file_handle(&self, path: &Path) -> Result<Arc<dyn FileHandle>, OpenReadError> {
+        let underlying = self.underlying.get_file_handle(path)?;
+        Ok(Arc::new(FaultInjectingFileHandle {
+            underlying,
+            path: path.to_path_buf(),
+            faults: self.faults.clone(),
+        }))
+    }
+
+    fn delete(&self, path: &Path) -> Result<(), DeleteError> {
+        self.underlying.delete(path)
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, OpenReadError> {
+        self.underlying.exists(path)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<WritePtr, OpenWriteError> {
+        let mut remaining_bytes_opt = None;
+        for fault in self.faults.for_path(path) {
+            match fault {
+                Fault::WriteError => {
+                    return Err(OpenWriteError::wrap_io_error(
+                        injected_error(path, &fault),
+                        path.to_path_buf(),
+                    ));
+                }
+                Fault::PartialWrite { num_bytes } => {
+                    remaining_bytes_opt =
+                        Some(num_bytes.min(remaining_bytes_opt.unwrap_or(num_bytes)));
+                }
+                Fault::ReadError | Fault::ReadDelay(_) | Fault::Corruption { .. } => {}
+            }
+        }
+        let underlying = self.underlying.open_write(path)?;
+        let Some(remaining_bytes) = remaining_bytes_opt else {
+            return Ok(underlying);
+        };
+        Ok(BufWriter::new(Box::new(PartialWriter {
+            underlying,
+            path: path.to_path_buf(),
+            remaining_bytes,
+            failed: false,
+        })))
+    }
+
+    fn atomic_read(&self, path: &Path) -> Result<Vec<u8>, OpenReadError> {
+        let mut data = self.underlying.atomic_read(path)?;
+        apply_read_faults(path, &self.faults, 0..data.len(), &mut data)
+            .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))?;
+        Ok(data)
+    }
+
+    fn atomic_write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let faults = self.faults.for_path(path);
+        let write_fault_opt = faults
+            .iter()
+            .find(|fault| matches!(fault, Fault::WriteError | Fault::PartialWrite { .. }));
+        if let Some(write_fault) = write_fault_opt {
+            return Err(injected_error(path, write_fault));
+        }
+        self.underlying.atomic_write(path, data)
+    }
+
+    fn sync_directory(&self) -> io::Result<()> {
+        self.underlying.sync_directory()
+    }
+
+    fn acquire_lock(&self, lock: &Lock) -> Result<DirectoryLock, LockError> {
+        self.underlying.acquire_lock(lock)
+    }
+
+    fn watch(&self, watch_callback: WatchCallback) -> crate::Result<WatchHandle> {
+        self.underlying.watch(watch_callback)
+    }
+}
+
+/// Applies the read faults of a file to the bytes read at `range`.
+fn apply_read_faults(
+    path: &Path,
+    faults: &Faults,
+    range: Range<usize>,
+    data: &mut [u8],
+) -> io::Result<()> {
+    for fault in faults.for_path(path) {
+        match fault {
+            Fault::ReadError => return Err(injected_error(path, &fault)),
+            Fault::ReadDelay(delay) => std::thread::sleep(delay),
+            Fault::Corruption { offset } => {
+                if range.contains(&offset) {
+                    data[offset - range.start] ^= 0xFF;
+                }
+            }
+            Fault::WriteError | Fault::PartialWrite { .. } => {}
+        }
+    }
+    Ok(())
+}
+
+struct FaultInjectingFileHandle {
+    underlying: Arc<dyn FileHandle>,
+    path: PathBuf,
+    faults: Faults,
+}
+
+impl fmt::Debug for FaultInjectingFileHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FaultInjectingFileHandle({:?})", self.path)
+    }
+}
+
+impl HasLen for FaultInjectingFileHandle {
+    fn len(&self) -> usize {
+        self.underlying.len()
+    }
+}
+
+impl FileHandle for FaultInjectingFileHandle {
+    fn read_bytes(&self, range: Range<usize>) -> io::Result<OwnedBytes> {
+        let bytes = self.underlying.read_bytes(range.clone())?;
+        let is_corrupted =
+            self.faults.for_path(&self.path).iter().any(
+                |fault| matches!(fault, Fault::Corruption { offset } if range.contains(offset)),
+            );
+        if !is_corrupted {
+            apply_read_faults(&self.path, &self.faults, range, &mut [])?;
+            return Ok(bytes);
+        }
+        let mut data = bytes.as_slice().to_vec();
+        apply_read_faults(&self.path, &self.faults, range, &mut data)?;
+        Ok(OwnedBytes::new(data))
+    }
+}
+
+/// Writer persisting the first `remaining_bytes` bytes written, and failing afterwards.
+struct PartialWriter {
+    underlying: WritePtr,
+    path: PathBuf,
+    remaining_bytes: usize,
+    failed: bool,
+}
+
+impl PartialWriter {
+    fn fail(&mut self) -> io::Error {
+        self.failed = true;
+        // The bytes written so far reach the disk.
+        if let Err(io_error) = self.underlying.flush() {
+            return io_error;
+        }
+        let fault = Fault::PartialWrite {
+            num_bytes: self.remaining_bytes,
+        };
+        injected_error(&self.path, &fault)
+    }
+}
+
+impl Write for PartialWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.remaining_bytes == 0 {
+            return Err(self.fail());
+        }
+        let len = buf.len().min(self.remaining_bytes);
+        self.underlying.write_all(&buf[..len])?;
+        self.remaining_bytes -= len;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.underlying.flush()?;
+        if self.failed {
+            return Err(self.fail());
+        }
+        Ok(())
+    }
+}
+
+impl TerminatingWrite for PartialWriter {
+    fn terminate_ref(&mut self, token: AntiCallToken) -> io::Result<()> {
+        if self.failed {
+            return Err(self.fail());
+        }
+        self.underlying.terminate_ref(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::path::Path;
+    use std::time::{Duration, Instant};
+
+    use super::{Fault, FaultInjectingDirectory};
+    use crate::directory::{Directory, RamDirectory, TerminatingWrite};
+    use crate::schema::{Schema, STORED, STRING};
+    use crate::{Index, IndexWriter};
+
+    #[test]
+    fn test_fault_injecting_directory_writes() {
+        let directory = FaultInjectingDirectory::wrap(RamDirectory::create());
+        directory.inject_fault(|path| path == Path::new("error"), Fault::WriteError);
+        directory.inject_fault(
+            |path| path == Path::new("partial"),
+            Fault::PartialWrite { num_bytes: 4 },
+        );
+        assert!(directory.open_write(Path::new("error")).is_err());
+        assert!(directory
+            .atomic_write(Path::new("partial"), b"data")
+            .is_err());
+
+        let mut write = directory.open_write(Path::new("partial")).unwrap();
+        write.write_all(b"abcdefgh").unwrap();
+        assert!(write.flush().is_err());
+        assert!(write.terminate().is_err());
+        let data = directory.open_read(Path::new("partial")).unwrap();
+        assert_eq!(data.read_bytes().unwrap().as_slice(), b"abcd");
+
+        directory.clear_faults();
+        let mut write = directory.open_write(Path::new("complete")).unwrap();
+        write.write_all(b"abcdefgh").unwrap();
+        write.terminate().unwrap();
+        let data = directory.open_read(Path::new("complete")).unwrap();
+        assert_eq!(data.read_bytes().unwrap().as_slice(), b"abcdefgh");
+    }
+
+    #[test]
+    fn test_fault_injecting_directory_reads() -> crate::Result<()> {
+        let directory = FaultInjectingDirectory::wrap(RamDirectory::create());
+        directory.atomic_write(Path::new("file"), b"abcd")?;
+        let file_slice = directory.open_read(Path::new("file"))?;
+
+        directory.inject_fault(|path| path == Path::new("file"), Fault::ReadError);
+        assert!(file_slice.read_bytes().is_err());
+        assert!(directory.atomic_read(Path::new("file")).is_err());
+        directory.clear_faults();
+
+        directory.inject_fault(
+            |path| path == Path::new("file"),
+            Fault::Corruption { offset: 1 },
+        );
+        assert_eq!(
+            file_slice.read_bytes()?.as_slice(),
+            &[b'a', !b'b', b'c', b'd']
+        );
+        assert_eq!(file_slice.slice(2..4).read_bytes()?.as_slice(), b"cd");
+        directory.clear_faults();
+
+        directory.inject_fault(|_| true, Fault::ReadDelay(Duration::from_millis(50)));
+        let start = Instant::now();
+        assert_eq!(directory.atomic_read(Path::new("file"))?, b"abcd");
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        Ok(())
+    }
+
+    #[test]
+    fn test_fault_injecting_directory_index() -> crate::Result<()> {
+        let directory = FaultInjectingDirectory::wrap(RamDirectory::create());
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING | STORED);
+        let index = Index::create(
+            directory.clone(),
+            schema_builder.build(),
+            Default::default(),
+        )?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.commit()?;
+
+        // A commit failing to write the meta file leaves the previous commit untouched.
+        directory.inject_fault(|path| path == Path::new("meta.json"), Fault::WriteError);
+        index_writer.add_document(doc!(id => "b"))?;
+        assert!(index_writer.commit().is_err());
+        directory.clear_faults();
+        let index = Index::open(directory.clone())?;
+        assert_eq!(index.reader()?.searcher().num_docs(), 1);
+
+        directory.inject_fault(
+            |path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "store")
+            },
+            Fault::Corruption { offset: 0 },
+        );
+        let corrupted_files = index.validate_checksum()?;
+        assert_eq!(corrupted_files.len(), 1);
+        assert!(corrupted_files
+            .iter()
+            .all(|path| path.extension().unwrap() == "store"));
+        Ok(())
+    }
+}
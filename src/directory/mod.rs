@@ -5,6 +5,8 @@ mod mmap_directory;
 
 mod directory;
 mod directory_lock;
+#[cfg(any(test, feature = "test-utils"))]
+mod fault_injecting_directory;
 mod file_watcher;
 pub mod footer;
 mod managed_directory;
@@ -25,6 +27,8 @@ pub use common::{AntiCallToken, OwnedBytes, TerminatingWrite};
 pub(crate) use self::composite_file::{CompositeFile, CompositeWrite};
 pub use self::directory::{Directory, DirectoryClone, DirectoryLock};
 pub use self::directory_lock::{Lock, INDEX_WRITER_LOCK, META_LOCK};
+#[cfg(any(test, feature = "test-utils"))]
+pub use self::fault_injecting_directory::{Fault, FaultInjectingDirectory};
 pub use self::ram_directory::RamDirectory;
 pub use self::watch_event_router::{WatchCallback, WatchCallbackList, WatchHandle};
 
@@ -11,8 +11,10 @@ use crate::directory::error::{
     Incompatibility, LockError, OpenDirectoryError, OpenReadError, OpenWriteError,
 };
 use crate::fastfield::FastFieldNotAvailableError;
+#[cfg(feature = "query-parser")]
+use crate::query;
+use crate::schema;
 use crate::schema::document::DeserializeError;
-use crate::{query, schema};
 
 /// Represents a `DataCorruption` error.
 ///
@@ -41,6 +43,63 @@ impl DataCorruption {
     }
 }
 
+/// Payload of a [`TantivyError::InvalidArgument`] error.
+///
+/// It is built from the message with `From<String>` or `From<&str>`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct InvalidArgument {
+    message: String,
+    kind: InvalidArgumentKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum InvalidArgumentKind {
+    Argument,
+    Query,
+}
+
+impl InvalidArgument {
+    /// Returns the message of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl From<String> for InvalidArgument {
+    fn from(message: String) -> InvalidArgument {
+        InvalidArgument {
+            message,
+            kind: InvalidArgumentKind::Argument,
+        }
+    }
+}
+
+impl From<&str> for InvalidArgument {
+    fn from(message: &str) -> InvalidArgument {
+        InvalidArgument::from(message.to_string())
+    }
+}
+
+impl std::ops::Deref for InvalidArgument {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Debug for InvalidArgument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.message, f)
+    }
+}
+
+impl fmt::Display for InvalidArgument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
 impl fmt::Debug for DataCorruption {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(f, "Data corruption")?;
@@ -72,10 +131,10 @@ pub enum TantivyError {
     IndexAlreadyExists,
     /// Failed to acquire file lock.
     #[error("Failed to acquire Lockfile: {0:?}. {1:?}")]
-    LockFailure(LockError, Option<String>),
+    LockFailure(#[source] LockError, Option<String>),
     /// IO Error.
     #[error("An IO error occurred: '{0}'")]
-    IoError(Arc<io::Error>),
+    IoError(#[source] Arc<io::Error>),
     /// Data corruption.
     #[error("Data corrupted: '{0:?}'")]
     DataCorruption(DataCorruption),
@@ -87,7 +146,7 @@ pub enum TantivyError {
     FieldNotFound(String),
     /// Invalid argument was passed by the user.
     #[error("An invalid argument was passed: '{0}'")]
-    InvalidArgument(String),
+    InvalidArgument(InvalidArgument),
     /// An Error occurred in one of the threads.
     #[error("An error occurred in a thread: '{0}'")]
     ErrorInThread(String),
@@ -109,7 +168,322 @@ pub enum TantivyError {
     InternalError(String),
     #[error("Deserialize error: {0}")]
     /// An error occurred while attempting to deserialize a document.
-    DeserializeError(DeserializeError),
+    DeserializeError(#[source] DeserializeError),
+    /// A boolean query has more clauses than the
+    /// [`max_boolean_clauses`](crate::IndexSettings::max_boolean_clauses) limit of the index,
+    /// even after grouping its term clauses.
+    #[error("The boolean query has {num_clauses} clauses, more than the limit of {max_clauses}")]
+    TooManyClauses {
+        /// Number of clauses of the query, after grouping its term clauses.
+        num_clauses: usize,
+        /// Maximum number of clauses.
+        max_clauses: usize,
+    },
+    /// The search was rejected by the
+    /// [admission policy](crate::AdmissionPolicy) of the reader, because too many
+    /// searches are already running. It can be retried later.
+    #[error("The search was rejected: {0}")]
+    Overloaded(String),
+    /// An error, along with a description of the operation that failed.
+    ///
+    /// See [`TantivyError::context()`].
+    #[error("{context}: {source}")]
+    Context {
+        /// Description of the operation that failed.
+        context: String,
+        /// The underlying error.
+        source: Box<TantivyError>,
+    },
+}
+
+/// Broad category of a [`TantivyError`].
+///
+/// Categories are meant to decide how an error should be reported to the caller, e.g.
+/// which HTTP status code to return, without having to match on every variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// The schema does not allow the operation, e.g. the field does not exist or is not
+    /// indexed.
+    Schema,
+    /// The request is invalid: the query could not be parsed, or an argument is invalid.
+    Query,
+    /// The index cannot be read: its files are corrupted or were written by an incompatible
+    /// version of tantivy.
+    Corruption,
+    /// A resource is unavailable or exhausted: the index is locked, a limit was reached, or a
+    /// thread could not be spawned.
+    Resource,
+    /// An IO error occurred in the directory.
+    Io,
+    /// A bug in tantivy, or a thread that panicked.
+    Internal,
+}
+
+impl ErrorCategory {
+    /// Returns a stable, machine-readable name for the category.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCategory::Schema => "schema",
+            ErrorCategory::Query => "query",
+            ErrorCategory::Corruption => "corruption",
+            ErrorCategory::Resource => "resource",
+            ErrorCategory::Io => "io",
+            ErrorCategory::Internal => "internal",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Machine-readable code of a [`TantivyError`].
+///
+/// Contrary to the error messages, codes are stable and can be relied upon to identify an
+/// error. Each code belongs to a single [`ErrorCategory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// The directory could not be opened.
+    DirectoryNotFound,
+    /// A file of the index does not exist.
+    FileNotFound,
+    /// A file of the index could not be created because it already exists.
+    FileAlreadyExists,
+    /// An IO error occurred.
+    Io,
+    /// An index already exists in the directory.
+    IndexAlreadyExists,
+    /// The lock of the index is held by another process.
+    LockBusy,
+    /// The data of the index is corrupted.
+    CorruptedIndex,
+    /// The index was written by an incompatible version of tantivy.
+    IncompatibleIndex,
+    /// A document could not be deserialized from the doc store.
+    DocumentDeserialization,
+    /// A lock was poisoned by a thread that panicked.
+    Poisoned,
+    /// The field does not exist in the schema.
+    FieldNotFound,
+    /// The schema does not allow the operation.
+    SchemaError,
+    /// An invalid argument was passed.
+    InvalidArgument,
+    /// The query could not be parsed, which is reported as a
+    /// [`TantivyError::InvalidArgument`].
+    InvalidQuery,
+    /// A boolean query has too many clauses.
+    TooManyClauses,
+    /// The aggregation request is invalid.
+    InvalidAggregation,
+    /// The memory limit of the aggregation was exceeded.
+    AggregationMemoryLimitExceeded,
+    /// The bucket limit of the aggregation was exceeded.
+    AggregationBucketLimitExceeded,
+    /// A required argument of the index builder is missing.
+    MissingIndexBuilderArgument,
+    /// A system resource could not be acquired, e.g. a thread could not be spawned.
+    SystemError,
+    /// The search was rejected because too many searches are already running.
+    Overloaded,
+    /// An error occurred in a thread.
+    ErrorInThread,
+    /// An internal error occurred.
+    InternalError,
+}
+
+impl ErrorCode {
+    /// Returns the code, in snake case.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::DirectoryNotFound => "directory_not_found",
+            ErrorCode::FileNotFound => "file_not_found",
+            ErrorCode::FileAlreadyExists => "file_already_exists",
+            ErrorCode::Io => "io",
+            ErrorCode::IndexAlreadyExists => "index_already_exists",
+            ErrorCode::LockBusy => "lock_busy",
+            ErrorCode::CorruptedIndex => "corrupted_index",
+            ErrorCode::IncompatibleIndex => "incompatible_index",
+            ErrorCode::DocumentDeserialization => "document_deserialization",
+            ErrorCode::Poisoned => "poisoned",
+            ErrorCode::FieldNotFound => "field_not_found",
+            ErrorCode::SchemaError => "schema_error",
+            ErrorCode::InvalidArgument => "invalid_argument",
+            ErrorCode::InvalidQuery => "invalid_query",
+            ErrorCode::TooManyClauses => "too_many_clauses",
+            ErrorCode::InvalidAggregation => "invalid_aggregation",
+            ErrorCode::AggregationMemoryLimitExceeded => "aggregation_memory_limit_exceeded",
+            ErrorCode::AggregationBucketLimitExceeded => "aggregation_bucket_limit_exceeded",
+            ErrorCode::MissingIndexBuilderArgument => "missing_index_builder_argument",
+            ErrorCode::SystemError => "system_error",
+            ErrorCode::Overloaded => "overloaded",
+            ErrorCode::ErrorInThread => "error_in_thread",
+            ErrorCode::InternalError => "internal_error",
+        }
+    }
+
+    /// Returns the category of the code.
+    pub fn category(self) -> ErrorCategory {
+        match self {
+            ErrorCode::FieldNotFound | ErrorCode::SchemaError => ErrorCategory::Schema,
+            ErrorCode::InvalidArgument
+            | ErrorCode::InvalidQuery
+            | ErrorCode::TooManyClauses
+            | ErrorCode::InvalidAggregation
+            | ErrorCode::MissingIndexBuilderArgument => ErrorCategory::Query,
+            ErrorCode::CorruptedIndex
+            | ErrorCode::IncompatibleIndex
+            | ErrorCode::DocumentDeserialization => ErrorCategory::Corruption,
+            ErrorCode::IndexAlreadyExists
+            | ErrorCode::LockBusy
+            | ErrorCode::AggregationMemoryLimitExceeded
+            | ErrorCode::AggregationBucketLimitExceeded
+            | ErrorCode::SystemError
+            | ErrorCode::Overloaded => ErrorCategory::Resource,
+            ErrorCode::DirectoryNotFound
+            | ErrorCode::FileNotFound
+            | ErrorCode::FileAlreadyExists
+            | ErrorCode::Io => ErrorCategory::Io,
+            ErrorCode::Poisoned | ErrorCode::ErrorInThread | ErrorCode::InternalError => {
+                ErrorCategory::Internal
+            }
+        }
+    }
+
+    /// Returns true if the operation may succeed when retried later, without any change.
+    pub fn is_retryable(self) -> bool {
+        matches!(self, ErrorCode::LockBusy | ErrorCode::Overloaded)
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TantivyError {
+    /// Returns the machine-readable code of the error.
+    ///
+    /// Context added with [`TantivyError::context()`] is ignored.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            TantivyError::AggregationError(aggregation_error) => match aggregation_error {
+                AggregationError::InternalError(_) => ErrorCode::InternalError,
+                AggregationError::InvalidRequest(_)
+                | AggregationError::DateHistogramParseError(_) => ErrorCode::InvalidAggregation,
+                AggregationError::MemoryExceeded { .. } => {
+                    ErrorCode::AggregationMemoryLimitExceeded
+                }
+                AggregationError::BucketLimitExceeded { .. } => {
+                    ErrorCode::AggregationBucketLimitExceeded
+                }
+            },
+            TantivyError::OpenDirectoryError(open_directory_error) => match open_directory_error {
+                OpenDirectoryError::DoesNotExist(_) | OpenDirectoryError::NotADirectory(_) => {
+                    ErrorCode::DirectoryNotFound
+                }
+                OpenDirectoryError::FailedToCreateTempDir(_)
+                | OpenDirectoryError::IoError { .. } => ErrorCode::Io,
+            },
+            TantivyError::OpenReadError(open_read_error) => match open_read_error {
+                OpenReadError::FileDoesNotExist(_) => ErrorCode::FileNotFound,
+                OpenReadError::IoError { .. } => ErrorCode::Io,
+                OpenReadError::IncompatibleIndex(_) => ErrorCode::IncompatibleIndex,
+            },
+            TantivyError::OpenWriteError(open_write_error) => match open_write_error {
+                OpenWriteError::FileAlreadyExists(_) => ErrorCode::FileAlreadyExists,
+                OpenWriteError::IoError { .. } => ErrorCode::Io,
+            },
+            TantivyError::IndexAlreadyExists => ErrorCode::IndexAlreadyExists,
+            TantivyError::LockFailure(lock_error, _) => match lock_error {
+                LockError::LockBusy => ErrorCode::LockBusy,
+                LockError::IoError(_) => ErrorCode::Io,
+            },
+            TantivyError::IoError(_) => ErrorCode::Io,
+            TantivyError::DataCorruption(_) => ErrorCode::CorruptedIndex,
+            TantivyError::Poisoned => ErrorCode::Poisoned,
+            TantivyError::FieldNotFound(_) => ErrorCode::FieldNotFound,
+            TantivyError::InvalidArgument(invalid_argument) => match invalid_argument.kind {
+                InvalidArgumentKind::Argument => ErrorCode::InvalidArgument,
+                InvalidArgumentKind::Query => ErrorCode::InvalidQuery,
+            },
+            TantivyError::ErrorInThread(_) => ErrorCode::ErrorInThread,
+            TantivyError::IndexBuilderMissingArgument(_) => ErrorCode::MissingIndexBuilderArgument,
+            TantivyError::SchemaError(_) => ErrorCode::SchemaError,
+            TantivyError::SystemError(_) => ErrorCode::SystemError,
+            TantivyError::IncompatibleIndex(_) => ErrorCode::IncompatibleIndex,
+            TantivyError::InternalError(_) => ErrorCode::InternalError,
+            TantivyError::DeserializeError(_) => ErrorCode::DocumentDeserialization,
+            TantivyError::TooManyClauses { .. } => ErrorCode::TooManyClauses,
+            TantivyError::Overloaded(_) => ErrorCode::Overloaded,
+            TantivyError::Context { source, .. } => source.code(),
+        }
+    }
+
+    /// Returns the category of the error.
+    ///
+    /// This is a shortcut for `self.code().category()`.
+    pub fn category(&self) -> ErrorCategory {
+        self.code().category()
+    }
+
+    /// Wraps the error with a description of the operation that failed.
+    ///
+    /// The original error remains accessible through [`std::error::Error::source()`] and
+    /// [`TantivyError::root()`], and keeps its code.
+    pub fn context(self, context: impl Into<String>) -> TantivyError {
+        TantivyError::Context {
+            context: context.into(),
+            source: Box::new(self),
+        }
+    }
+
+    /// Returns the error, stripped of the context added with [`TantivyError::context()`].
+    pub fn root(&self) -> &TantivyError {
+        let mut error = self;
+        while let TantivyError::Context { source, .. } = error {
+            error = source;
+        }
+        error
+    }
+}
+
+/// Extension trait to add context to the error of a [`Result`].
+///
+/// ```rust
+/// use tantivy::error::{ErrorCategory, ResultExt};
+/// use tantivy::schema::Schema;
+/// use tantivy::TantivyError;
+///
+/// let schema = Schema::builder().build();
+/// let err: TantivyError = schema
+///     .get_field("title")
+///     .context("Failed to build the query")
+///     .unwrap_err();
+/// assert_eq!(err.category(), ErrorCategory::Schema);
+/// assert_eq!(err.code().as_str(), "field_not_found");
+/// assert!(matches!(err.root(), TantivyError::FieldNotFound(_)));
+/// ```
+pub trait ResultExt<T> {
+    /// Wraps the error, if any, with a description of the operation that failed.
+    fn context(self, context: impl Into<String>) -> crate::Result<T>;
+
+    /// Same as [`ResultExt::context()`], but the context is only computed on error.
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> crate::Result<T>;
+}
+
+impl<T, E: Into<TantivyError>> ResultExt<T> for Result<T, E> {
+    fn context(self, context: impl Into<String>) -> crate::Result<T> {
+        self.map_err(|err| err.into().context(context))
+    }
+
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> crate::Result<T> {
+        self.map_err(|err| err.into().context(context()))
+    }
 }
 
 impl From<io::Error> for TantivyError {
@@ -117,6 +491,20 @@ impl From<io::Error> for TantivyError {
         TantivyError::IoError(Arc::new(io_err))
     }
 }
+
+/// Converts an error returned while decoding the files of a segment.
+///
+/// The decoders report truncated or inconsistent data with the `InvalidData` and
+/// `UnexpectedEof` error kinds, which are converted to a [`TantivyError::DataCorruption`]. Other
+/// errors remain IO errors.
+pub(crate) fn corruption_from_io(io_err: io::Error) -> TantivyError {
+    match io_err.kind() {
+        io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => {
+            DataCorruption::comment_only(io_err.to_string()).into()
+        }
+        _ => io_err.into(),
+    }
+}
 impl From<DataCorruption> for TantivyError {
     fn from(data_corruption: DataCorruption) -> TantivyError {
         TantivyError::DataCorruption(data_corruption)
@@ -133,9 +521,13 @@ impl From<LockError> for TantivyError {
     }
 }
 
+#[cfg(feature = "query-parser")]
 impl From<query::QueryParserError> for TantivyError {
     fn from(parsing_error: query::QueryParserError) -> TantivyError {
-        TantivyError::InvalidArgument(format!("Query is invalid. {parsing_error:?}"))
+        TantivyError::InvalidArgument(InvalidArgument {
+            message: format!("Query is invalid. {parsing_error:?}"),
+            kind: InvalidArgumentKind::Query,
+        })
     }
 }
 
@@ -147,25 +539,25 @@ impl<Guard> From<PoisonError<Guard>> for TantivyError {
 
 impl From<time::error::Format> for TantivyError {
     fn from(err: time::error::Format) -> TantivyError {
-        TantivyError::InvalidArgument(format!("Date formatting error: {err}"))
+        TantivyError::InvalidArgument(format!("Date formatting error: {err}").into())
     }
 }
 
 impl From<time::error::Parse> for TantivyError {
     fn from(err: time::error::Parse) -> TantivyError {
-        TantivyError::InvalidArgument(format!("Date parsing error: {err}"))
+        TantivyError::InvalidArgument(format!("Date parsing error: {err}").into())
     }
 }
 
 impl From<time::error::ComponentRange> for TantivyError {
     fn from(err: time::error::ComponentRange) -> TantivyError {
-        TantivyError::InvalidArgument(format!("Date range error: {err}"))
+        TantivyError::InvalidArgument(format!("Date range error: {err}").into())
     }
 }
 
 impl From<schema::DocParsingError> for TantivyError {
     fn from(error: schema::DocParsingError) -> TantivyError {
-        TantivyError::InvalidArgument(format!("Failed to parse document {error:?}"))
+        TantivyError::InvalidArgument(format!("Failed to parse document {error:?}").into())
     }
 }
 
@@ -186,3 +578,80 @@ impl From<DeserializeError> for TantivyError {
         TantivyError::DeserializeError(error)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+    use std::io;
+
+    use super::{corruption_from_io, ErrorCategory, ErrorCode, ResultExt};
+    use crate::directory::error::OpenReadError;
+    #[cfg(feature = "query-parser")]
+    use crate::query::QueryParserError;
+    use crate::TantivyError;
+
+    #[test]
+    fn test_io_error_conversion() {
+        let err: TantivyError = io::Error::new(io::ErrorKind::InvalidData, "bad json").into();
+        assert!(matches!(err, TantivyError::IoError(_)));
+        let err = corruption_from_io(io::Error::new(io::ErrorKind::InvalidData, "bad block"));
+        assert!(matches!(err, TantivyError::DataCorruption(_)));
+        assert_eq!(
+            err.to_string(),
+            "Data corrupted: 'Data corruption: bad block.'"
+        );
+        let err = corruption_from_io(io::Error::from(io::ErrorKind::UnexpectedEof));
+        assert!(matches!(err, TantivyError::DataCorruption(_)));
+        let err = corruption_from_io(io::Error::from(io::ErrorKind::PermissionDenied));
+        assert!(matches!(err, TantivyError::IoError(_)));
+    }
+
+    #[test]
+    fn test_error_code_and_category() {
+        let err = TantivyError::FieldNotFound("title".to_string());
+        assert_eq!(err.code(), ErrorCode::FieldNotFound);
+        assert_eq!(err.category(), ErrorCategory::Schema);
+        #[cfg(feature = "query-parser")]
+        {
+            let err: TantivyError = QueryParserError::SyntaxError("title:".to_string()).into();
+            assert!(matches!(err, TantivyError::InvalidArgument(_)));
+            assert_eq!(err.code().as_str(), "invalid_query");
+            assert_eq!(err.category(), ErrorCategory::Query);
+        }
+        let err = TantivyError::InvalidArgument("Query is invalid. Negative limit".into());
+        assert_eq!(err.code(), ErrorCode::InvalidArgument);
+        assert_eq!(err.category(), ErrorCategory::Query);
+        let err = corruption_from_io(io::Error::from(io::ErrorKind::InvalidData));
+        assert_eq!(err.category(), ErrorCategory::Corruption);
+        let err: TantivyError = OpenReadError::FileDoesNotExist("meta.json".into()).into();
+        assert_eq!(err.code(), ErrorCode::FileNotFound);
+        assert_eq!(err.category().to_string(), "io");
+        assert!(!err.code().is_retryable());
+        let err = TantivyError::Overloaded("10 searches are queued".to_string());
+        assert_eq!(err.category(), ErrorCategory::Resource);
+        assert!(err.code().is_retryable());
+    }
+
+    #[test]
+    fn test_error_context() {
+        let res: Result<(), io::Error> = Err(io::Error::new(io::ErrorKind::InvalidData, "bad"));
+        let err = res
+            .context("Failed to open segment")
+            .with_context(|| format!("Failed to open index {}", "idx"))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to open index idx: Failed to open segment: An IO error occurred: 'bad'"
+        );
+        assert_eq!(err.code(), ErrorCode::Io);
+        assert!(matches!(err.root(), TantivyError::IoError(_)));
+        let mut chain = Vec::new();
+        let mut source: Option<&dyn Error> = Some(&err);
+        while let Some(err) = source {
+            chain.push(err.to_string());
+            source = err.source();
+        }
+        assert_eq!(chain.len(), 4);
+        assert_eq!(chain[3], "bad");
+    }
+}
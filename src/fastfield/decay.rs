@@ -0,0 +1,210 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use columnar::column_values::{monotonic_map_column, StrictlyMonotonicFn};
+use columnar::{Column, ColumnValues};
+
+use crate::schema::{FieldType, Schema};
+use crate::DateTime;
+
+/// Returns the factor by which a value decays between `from` and `to`.
+///
+/// The factor is always strictly positive, so that decaying a column preserves the order of its
+/// values.
+pub(crate) fn decay_factor(half_life: Duration, from: DateTime, to: DateTime) -> f64 {
+    let elapsed_secs =
+        (to.into_timestamp_nanos() as f64 - from.into_timestamp_nanos() as f64) / 1_000_000_000.0;
+    (-elapsed_secs / half_life.as_secs_f64())
+        .exp2()
+        .max(f64::MIN_POSITIVE)
+}
+
+/// Multiplies the values of a column by a strictly positive factor.
+struct Scale(f64);
+
+impl StrictlyMonotonicFn<f64, f64> for Scale {
+    #[inline]
+    fn mapping(&self, val: f64) -> f64 {
+        val * self.0
+    }
+
+    #[inline]
+    fn inverse(&self, val: f64) -> f64 {
+        val / self.0
+    }
+}
+
+/// Returns a view of the column, with its values multiplied by `factor`.
+pub(crate) fn scale_f64_column(column: Column<f64>, factor: f64) -> Column<f64> {
+    if factor == 1.0 {
+        return column;
+    }
+    let values: Arc<dyn ColumnValues<f64>> =
+        Arc::new(monotonic_map_column(column.values, Scale(factor)));
+    Column {
+        index: column.index,
+        values,
+    }
+}
+
+/// Returns the half-life of the decayed counters of the schema, along with their names.
+pub(crate) fn decayed_fields(schema: &Schema) -> Vec<(&str, Duration)> {
+    schema
+        .fields()
+        .filter_map(|(_, field_entry)| match field_entry.field_type() {
+            FieldType::F64(numeric_options) => numeric_options
+                .decay_half_life()
+                .map(|half_life| (field_entry.name(), half_life)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the current time if the schema contains decayed counters, and `None` otherwise.
+///
+/// This is the decay reference timestamp of the segments being created.
+pub(crate) fn new_decay_reference_timestamp(schema: &Schema) -> Option<DateTime> {
+    if decayed_fields(schema).is_empty() {
+        return None;
+    }
+    let now_nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos() as i64)
+        .unwrap_or(0);
+    Some(DateTime::from_timestamp_nanos(now_nanos))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::decay_factor;
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{NumericOptions, Schema, FAST};
+    use crate::{DateTime, Index, IndexWriter, TantivyError};
+
+    const HOUR: Duration = Duration::from_secs(3_600);
+
+    fn hours(num_hours: i64) -> DateTime {
+        DateTime::from_timestamp_secs(num_hours * 3_600)
+    }
+
+    #[test]
+    fn test_decay_factor() {
+        assert_eq!(decay_factor(HOUR, hours(1), hours(1)), 1.0);
+        assert_eq!(decay_factor(HOUR, hours(1), hours(2)), 0.5);
+        assert_eq!(decay_factor(HOUR, hours(1), hours(4)), 0.125);
+        assert_eq!(decay_factor(HOUR, hours(2), hours(1)), 2.0);
+        assert!(decay_factor(HOUR, hours(0), hours(1_000_000)) > 0.0);
+    }
+
+    #[test]
+    fn test_decayed_counter_merge() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_f64_field(
+            "popularity",
+            NumericOptions::default()
+                .set_fast()
+                .set_decay_half_life(HOUR),
+        );
+        let not_decayed = schema_builder.add_f64_field("not_decayed", FAST);
+        let schema = schema_builder.build();
+        let popularity = schema.get_field("popularity")?;
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(popularity => 8.0, not_decayed => 8.0))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(popularity => 2.0, not_decayed => 2.0))?;
+        index_writer.commit()?;
+
+        let decayed_values = |at_hours: i64| -> crate::Result<Vec<f64>> {
+            let searcher = index.reader()?.searcher();
+            let mut values = Vec::new();
+            for segment_reader in searcher.segment_readers() {
+                let fast_fields = segment_reader.fast_fields();
+                let reference_timestamp = fast_fields.decay_reference_timestamp().unwrap();
+                let at = DateTime::from_timestamp_nanos(
+                    reference_timestamp.into_timestamp_nanos() + at_hours * 3_600_000_000_000,
+                );
+                let column = fast_fields.decayed_f64("popularity", at)?;
+                values.extend((0..segment_reader.max_doc()).filter_map(|doc| column.first(doc)));
+            }
+            values.sort_by(f64::total_cmp);
+            Ok(values)
+        };
+        assert_eq!(decayed_values(0)?, vec![2.0, 8.0]);
+        assert_eq!(decayed_values(2)?, vec![0.5, 2.0]);
+
+        let searcher = index.reader()?.searcher();
+        let reference_timestamps: Vec<DateTime> = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| {
+                segment_reader
+                    .fast_fields()
+                    .decay_reference_timestamp()
+                    .unwrap()
+            })
+            .collect();
+        let latest_reference_timestamp = reference_timestamps.iter().max().unwrap();
+        let at = DateTime::from_timestamp_nanos(
+            latest_reference_timestamp.into_timestamp_nanos() + 2 * 3_600_000_000_000,
+        );
+        let read_values = |searcher: &crate::Searcher| -> crate::Result<Vec<f64>> {
+            let mut values = Vec::new();
+            for segment_reader in searcher.segment_readers() {
+                let column = segment_reader.fast_fields().decayed_f64("popularity", at)?;
+                values.extend((0..segment_reader.max_doc()).filter_map(|doc| column.first(doc)));
+            }
+            values.sort_by(f64::total_cmp);
+            Ok(values)
+        };
+        let values_before_merge = read_values(&searcher)?;
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let segment_reader = searcher.segment_reader(0);
+        let merge_timestamp = segment_reader
+            .fast_fields()
+            .decay_reference_timestamp()
+            .unwrap();
+        assert!(reference_timestamps
+            .iter()
+            .all(|reference_timestamp| *reference_timestamp <= merge_timestamp));
+        // Reading the values decayed to a given time is not affected by the merge.
+        let values_after_merge = read_values(&searcher)?;
+        for (before, after) in values_before_merge.iter().zip(&values_after_merge) {
+            assert!((before - after).abs() <= before.abs() * 1e-9);
+        }
+        // The raw values are decayed to the time of the merge.
+        let raw_values: Vec<f64> = segment_reader
+            .fast_fields()
+            .f64("popularity")?
+            .values_for_doc(0)
+            .chain(
+                segment_reader
+                    .fast_fields()
+                    .f64("popularity")?
+                    .values_for_doc(1),
+            )
+            .collect();
+        assert!(raw_values.iter().all(|value| *value <= 8.0));
+        // Other fields are not decayed.
+        let not_decayed_column = segment_reader.fast_fields().f64("not_decayed")?;
+        let mut not_decayed_values: Vec<f64> = (0..2)
+            .filter_map(|doc| not_decayed_column.first(doc))
+            .collect();
+        not_decayed_values.sort_by(f64::total_cmp);
+        assert_eq!(not_decayed_values, vec![2.0, 8.0]);
+        assert!(matches!(
+            segment_reader.fast_fields().decayed_f64("not_decayed", at),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+}
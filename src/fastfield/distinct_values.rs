@@ -0,0 +1,286 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt::Debug;
+use std::io;
+
+use columnar::{
+    cmp_column_values, Column, DynamicColumn, HasAssociatedColumnType, StrColumn, Streamer,
+};
+use common::BitSet;
+use itertools::Itertools;
+
+use crate::{Searcher, SegmentReader};
+
+/// The column of a segment, along with the ordinals of the values of its alive documents if the
+/// segment has deletes.
+struct SegmentStrValues {
+    column: StrColumn,
+    alive_ords: Option<BitSet>,
+}
+
+impl SegmentStrValues {
+    fn open(segment_reader: &SegmentReader, field_name: &str) -> crate::Result<Option<Self>> {
+        let Some(column) = segment_reader.fast_fields().str(field_name)? else {
+            return Ok(None);
+        };
+        let alive_ords = if segment_reader.has_deletes() {
+            let mut alive_ords = BitSet::with_max_value(column.num_terms() as u32);
+            for doc in segment_reader.doc_ids_alive() {
+                for term_ord in column.term_ords(doc) {
+                    alive_ords.insert(term_ord as u32);
+                }
+            }
+            Some(alive_ords)
+        } else {
+            None
+        };
+        Ok(Some(SegmentStrValues { column, alive_ords }))
+    }
+}
+
+/// The distinct values of a string fast field, over all of the segments of a
+/// [`Searcher`].
+///
+/// See [`Searcher::distinct_str_values()`].
+pub struct DistinctStrValues {
+    segments: Vec<SegmentStrValues>,
+}
+
+impl DistinctStrValues {
+    pub(crate) fn open(searcher: &Searcher, field_name: &str) -> crate::Result<Self> {
+        let mut segments = Vec::new();
+        for segment_reader in searcher.segment_readers() {
+            if let Some(segment) = SegmentStrValues::open(segment_reader, field_name)? {
+                segments.push(segment);
+            }
+        }
+        Ok(DistinctStrValues { segments })
+    }
+
+    /// Streams the values, in increasing order.
+    pub fn stream(&self) -> io::Result<DistinctStrStreamer<'_>> {
+        let mut streamers = Vec::with_capacity(self.segments.len());
+        for segment in &self.segments {
+            streamers.push(SegmentStreamer {
+                streamer: segment.column.dictionary_stream()?,
+                alive_ords: segment.alive_ords.as_ref(),
+            });
+        }
+        Ok(DistinctStrStreamer {
+            heap: BinaryHeap::new(),
+            current: streamers,
+        })
+    }
+}
+
+struct SegmentStreamer<'a> {
+    streamer: Streamer<'a>,
+    alive_ords: Option<&'a BitSet>,
+}
+
+impl SegmentStreamer<'_> {
+    /// Advances to the next value held by an alive document.
+    fn advance(&mut self) -> bool {
+        while self.streamer.advance() {
+            let is_alive = self.alive_ords.map_or(true, |alive_ords| {
+                alive_ords.contains(self.streamer.term_ord() as u32)
+            });
+            if is_alive {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl PartialEq for SegmentStreamer<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.streamer.key() == other.streamer.key()
+    }
+}
+
+impl Eq for SegmentStreamer<'_> {}
+
+impl PartialOrd for SegmentStreamer<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SegmentStreamer<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, so that the binary heap pops the smallest key first.
+        other.streamer.key().cmp(self.streamer.key())
+    }
+}
+
+/// Streams the distinct values of a string fast field over several segments, in increasing
+/// order.
+pub struct DistinctStrStreamer<'a> {
+    heap: BinaryHeap<SegmentStreamer<'a>>,
+    // Streamers positioned on the current value.
+    current: Vec<SegmentStreamer<'a>>,
+}
+
+impl DistinctStrStreamer<'_> {
+    /// Advances the stream to the next value.
+    ///
+    /// Returns false once all of the values have been streamed.
+    pub fn advance(&mut self) -> bool {
+        for mut streamer in self.current.drain(..) {
+            if streamer.advance() {
+                self.heap.push(streamer);
+            }
+        }
+        let Some(head) = self.heap.pop() else {
+            return false;
+        };
+        self.current.push(head);
+        while self
+            .heap
+            .peek()
+            .is_some_and(|next| next.streamer.key() == self.key())
+        {
+            let next = self.heap.pop().unwrap();
+            self.current.push(next);
+        }
+        true
+    }
+
+    /// Returns the bytes of the current value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `.advance()` has never returned true.
+    pub fn key(&self) -> &[u8] {
+        self.current[0].streamer.key()
+    }
+
+    /// Returns the current value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `.advance()` has never returned true.
+    pub fn value(&self) -> io::Result<&str> {
+        std::str::from_utf8(self.key())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Not valid utf-8"))
+    }
+}
+
+/// Returns the distinct values of a numeric fast field, over all of the segments of a
+/// searcher, in increasing order. NaN values come last.
+pub(crate) fn distinct_numeric_values<T>(
+    searcher: &Searcher,
+    field_name: &str,
+) -> crate::Result<impl Iterator<Item = T>>
+where
+    T: HasAssociatedColumnType + PartialOrd + Copy + Debug + Send + Sync + 'static,
+    DynamicColumn: Into<Option<Column<T>>>,
+{
+    let mut segment_values: Vec<Vec<T>> = Vec::new();
+    for segment_reader in searcher.segment_readers() {
+        let Some(column) = segment_reader.fast_fields().column_opt::<T>(field_name)? else {
+            continue;
+        };
+        if !segment_reader.has_deletes() {
+            segment_values.push(column.distinct_values());
+            continue;
+        }
+        let mut values: Vec<T> = segment_reader
+            .doc_ids_alive()
+            .flat_map(|doc| column.values_for_doc(doc))
+            .collect();
+        values.sort_unstable_by(cmp_column_values);
+        values.dedup_by(|left, right| cmp_column_values(left, right) == Ordering::Equal);
+        segment_values.push(values);
+    }
+    Ok(segment_values
+        .into_iter()
+        .kmerge_by(|left, right| cmp_column_values(left, right) == Ordering::Less)
+        .dedup_by(|left, right| cmp_column_values(left, right) == Ordering::Equal))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::{Schema, FAST, STRING};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_distinct_values() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING | FAST);
+        let price = schema_builder.add_i64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tag => "red", tag => "blue", price => 3i64))?;
+        index_writer.add_document(doc!(tag => "green", price => -1i64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(tag => "blue", price => 3i64, price => 10i64))?;
+        index_writer.add_document(doc!(tag => "yellow", price => 7i64))?;
+        index_writer.add_document(doc!(price => 5i64))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let distinct_tags = searcher.distinct_str_values("tag")?;
+        let mut stream = distinct_tags.stream()?;
+        let mut tags = Vec::new();
+        while stream.advance() {
+            tags.push(stream.value()?.to_string());
+        }
+        assert_eq!(tags, ["blue", "green", "red", "yellow"]);
+        let prices: Vec<i64> = searcher.distinct_numeric_values("price")?.collect();
+        assert_eq!(prices, [-1, 3, 5, 7, 10]);
+
+        // The values of deleted documents are not part of the distinct values.
+        index_writer.delete_term(Term::from_field_text(tag, "yellow"));
+        index_writer.delete_term(Term::from_field_text(tag, "green"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let distinct_tags = searcher.distinct_str_values("tag")?;
+        let mut stream = distinct_tags.stream()?;
+        let mut tags = Vec::new();
+        while stream.advance() {
+            tags.push(stream.value()?.to_string());
+        }
+        assert_eq!(tags, ["blue", "red"]);
+        let prices: Vec<i64> = searcher.distinct_numeric_values("price")?.collect();
+        assert_eq!(prices, [3, 5, 10]);
+
+        assert!(!searcher.distinct_str_values("missing")?.stream()?.advance());
+        assert_eq!(searcher.distinct_numeric_values::<u64>("price")?.count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_distinct_values_nan() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let score = schema_builder.add_f64_field("score", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tag => "a", score => f64::NAN, score => 2.0))?;
+        index_writer.add_document(doc!(tag => "b", score => 1.0, score => f64::NAN))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(tag => "c", score => f64::NAN))?;
+        index_writer.add_document(doc!(tag => "d", score => 0.5))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let scores: Vec<f64> = searcher.distinct_numeric_values("score")?.collect();
+        assert_eq!(scores.len(), 4);
+        assert_eq!(scores[..3], [0.5, 1.0, 2.0]);
+        assert!(scores[3].is_nan());
+
+        // Segments with deletes read the values of their alive documents.
+        index_writer.delete_term(Term::from_field_text(tag, "a"));
+        index_writer.delete_term(Term::from_field_text(tag, "d"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let scores: Vec<f64> = searcher.distinct_numeric_values("score")?.collect();
+        assert_eq!(scores.len(), 2);
+        assert_eq!(scores[0], 1.0);
+        assert!(scores[1].is_nan());
+        Ok(())
+    }
+}
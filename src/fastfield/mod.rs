@@ -19,10 +19,12 @@
 //!
 //! Read access performance is comparable to that of an array lookup.
 
-pub use columnar::Column;
 use columnar::MonotonicallyMappableToU64;
+pub use columnar::{Column, HistogramBucket, ValueHistogram};
+pub use common::BitSet;
 
 pub use self::alive_bitset::{intersect_alive_bitsets, write_alive_bitset, AliveBitSet};
+pub use self::distinct_values::{DistinctStrStreamer, DistinctStrValues};
 pub use self::error::{FastFieldNotAvailableError, Result};
 pub use self::facet_reader::FacetReader;
 pub use self::readers::FastFieldReaders;
@@ -31,6 +33,8 @@ use crate::schema::Type;
 use crate::DateTime;
 
 mod alive_bitset;
+pub(crate) mod decay;
+pub(crate) mod distinct_values;
 mod error;
 mod facet_reader;
 mod readers;
@@ -831,7 +835,7 @@ mod tests {
             write.terminate().unwrap();
         }
         let file = directory.open_read(path).unwrap();
-        assert_eq!(file.len(), 86);
+        assert_eq!(file.len(), 87);
         let fastfield_readers = FastFieldReaders::open(file, schema).unwrap();
         let col = fastfield_readers.bool("field_bool").unwrap();
         assert_eq!(col.first(0), None);
@@ -4,15 +4,17 @@ use std::sync::Arc;
 
 use columnar::{
     BytesColumn, Column, ColumnType, ColumnValues, ColumnarReader, DynamicColumn,
-    DynamicColumnHandle, HasAssociatedColumnType, StrColumn,
+    DynamicColumnHandle, HasAssociatedColumnType, StrColumn, ValueHistogram,
 };
-use common::ByteCount;
+use common::{BitSet, ByteCount};
 
 use crate::core::json_utils::encode_column_name;
 use crate::directory::FileSlice;
+use crate::error::corruption_from_io;
+use crate::fastfield::decay::{decay_factor, scale_f64_column};
 use crate::schema::{Field, FieldEntry, FieldType, Schema};
 use crate::space_usage::{FieldUsage, PerFieldSpaceUsage};
-use crate::TantivyError;
+use crate::{DateTime, TantivyError};
 
 /// Provides access to all of the BitpackedFastFieldReader.
 ///
@@ -22,12 +24,32 @@ use crate::TantivyError;
 pub struct FastFieldReaders {
     columnar: Arc<ColumnarReader>,
     schema: Schema,
+    decay_reference_timestamp: Option<DateTime>,
 }
 
 impl FastFieldReaders {
     pub(crate) fn open(fast_field_file: FileSlice, schema: Schema) -> io::Result<FastFieldReaders> {
         let columnar = Arc::new(ColumnarReader::open(fast_field_file)?);
-        Ok(FastFieldReaders { columnar, schema })
+        Ok(FastFieldReaders {
+            columnar,
+            schema,
+            decay_reference_timestamp: None,
+        })
+    }
+
+    pub(crate) fn with_decay_reference_timestamp(
+        mut self,
+        decay_reference_timestamp: Option<DateTime>,
+    ) -> FastFieldReaders {
+        self.decay_reference_timestamp = decay_reference_timestamp;
+        self
+    }
+
+    /// Returns the time as of which the values of the decayed counters are expressed.
+    ///
+    /// See [`SegmentMeta::decay_reference_timestamp()`](crate::index::SegmentMeta::decay_reference_timestamp).
+    pub fn decay_reference_timestamp(&self) -> Option<DateTime> {
+        self.decay_reference_timestamp
     }
 
     fn resolve_field(&self, column_name: &str) -> crate::Result<Option<String>> {
@@ -93,9 +115,9 @@ impl FastFieldReaders {
         };
         let field_entry: &FieldEntry = self.schema.get_field_entry(field);
         if !field_entry.is_fast() {
-            return Err(TantivyError::InvalidArgument(format!(
-                "Field {field_name:?} is not configured as fast field"
-            )));
+            return Err(TantivyError::InvalidArgument(
+                format!("Field {field_name:?} is not configured as fast field").into(),
+            ));
         }
         Ok(match (field_entry.field_type(), path) {
             (FieldType::JsonObject(json_options), path) if !path.is_empty() => {
@@ -125,10 +147,28 @@ impl FastFieldReaders {
         else {
             return Ok(None);
         };
-        let dynamic_column = dynamic_column_handle.open()?;
+        let dynamic_column = dynamic_column_handle.open().map_err(corruption_from_io)?;
         Ok(dynamic_column.into())
     }
 
+    /// Returns an equi-depth histogram of the values of the column associated to a given field
+    /// name, with at most `num_buckets` buckets.
+    ///
+    /// See [`Column::value_histogram()`]. Returns `None` if no column of the required type is
+    /// associated with that field name.
+    pub fn value_histogram<T>(
+        &self,
+        field_name: &str,
+        num_buckets: usize,
+    ) -> crate::Result<Option<ValueHistogram<T>>>
+    where
+        T: HasAssociatedColumnType,
+        DynamicColumn: Into<Option<Column<T>>>,
+    {
+        let column_opt: Option<Column<T>> = self.column_opt(field_name)?;
+        Ok(column_opt.map(|column| column.value_histogram(num_buckets)))
+    }
+
     /// Returns the number of `bytes` associated with a column.
     ///
     /// Returns 0 if the column does not exist.
@@ -138,7 +178,8 @@ impl FastFieldReaders {
         };
         Ok(self
             .columnar
-            .read_columns(&resolved_field_name)?
+            .read_columns(&resolved_field_name)
+            .map_err(corruption_from_io)?
             .into_iter()
             .map(|column_handle| column_handle.num_bytes())
             .sum())
@@ -202,7 +243,7 @@ impl FastFieldReaders {
         else {
             return Ok(None);
         };
-        let dynamic_column = dynamic_column_handle.open()?;
+        let dynamic_column = dynamic_column_handle.open().map_err(corruption_from_io)?;
         Ok(dynamic_column.into())
     }
 
@@ -213,7 +254,7 @@ impl FastFieldReaders {
         else {
             return Ok(None);
         };
-        let dynamic_column = dynamic_column_handle.open()?;
+        let dynamic_column = dynamic_column_handle.open().map_err(corruption_from_io)?;
         Ok(dynamic_column.into())
     }
 
@@ -228,7 +269,8 @@ impl FastFieldReaders {
         };
         let dynamic_column_handle_opt = self
             .columnar
-            .read_columns(&resolved_field_name)?
+            .read_columns(&resolved_field_name)
+            .map_err(corruption_from_io)?
             .into_iter()
             .find(|column| column.column_type() == column_type);
         Ok(dynamic_column_handle_opt)
@@ -244,7 +286,8 @@ impl FastFieldReaders {
         };
         let dynamic_column_handles = self
             .columnar
-            .read_columns(&resolved_field_name)?
+            .read_columns(&resolved_field_name)
+            .map_err(corruption_from_io)?
             .into_iter()
             .collect();
         Ok(dynamic_column_handles)
@@ -309,13 +352,17 @@ impl FastFieldReaders {
         let Some(resolved_field_name) = self.resolve_field(field_name)? else {
             return Ok(None);
         };
-        for col in self.columnar.read_columns(&resolved_field_name)? {
+        for col in self
+            .columnar
+            .read_columns(&resolved_field_name)
+            .map_err(corruption_from_io)?
+        {
             if let Some(type_white_list) = type_white_list_opt {
                 if !type_white_list.contains(&col.column_type()) {
                     continue;
                 }
             }
-            if let Some(col_u64) = col.open_u64_lenient()? {
+            if let Some(col_u64) = col.open_u64_lenient().map_err(corruption_from_io)? {
                 return Ok(Some((col_u64, col.column_type())));
             }
         }
@@ -337,13 +384,17 @@ impl FastFieldReaders {
         let Some(resolved_field_name) = self.resolve_field(field_name)? else {
             return Ok(columns_and_types);
         };
-        for col in self.columnar.read_columns(&resolved_field_name)? {
+        for col in self
+            .columnar
+            .read_columns(&resolved_field_name)
+            .map_err(corruption_from_io)?
+        {
             if let Some(type_white_list) = type_white_list_opt {
                 if !type_white_list.contains(&col.column_type()) {
                     continue;
                 }
             }
-            if let Some(col_u64) = col.open_u64_lenient()? {
+            if let Some(col_u64) = col.open_u64_lenient().map_err(corruption_from_io)? {
                 columns_and_types.push((col_u64, col.column_type()));
             }
         }
@@ -375,12 +426,52 @@ impl FastFieldReaders {
         self.column(field_name)
     }
 
+    /// Returns the values of a decayed counter, decayed to the time `at`.
+    ///
+    /// If `field` is not a decayed counter, this method returns an Error.
+    /// See [`NumericOptions::set_decay_half_life()`](crate::schema::NumericOptions::set_decay_half_life).
+    pub fn decayed_f64(&self, field_name: &str, at: DateTime) -> crate::Result<Column<f64>> {
+        let field = self.schema.get_field(field_name)?;
+        let field_entry = self.schema.get_field_entry(field);
+        let half_life = match field_entry.field_type() {
+            FieldType::F64(numeric_options) => numeric_options.decay_half_life(),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            TantivyError::SchemaError(format!("Field {field_name:?} is not a decayed counter"))
+        })?;
+        let column = self.f64(field_name)?;
+        let factor = decay_factor(half_life, self.decay_reference_timestamp.unwrap_or(at), at);
+        Ok(scale_f64_column(column, factor))
+    }
+
     /// Returns the `bool` fast field reader reader associated with `field`.
     ///
     /// If `field` is not a bool fast field, this method returns an Error.
     pub fn bool(&self, field_name: &str) -> crate::Result<Column<bool>> {
         self.column(field_name)
     }
+
+    /// Returns the documents having `value` in the `bool` fast field `field`, as a dense bitset.
+    ///
+    /// The bitset answers whether a document has the value in O(1), and bitsets of different
+    /// fields can be combined with bitwise operations, e.g.
+    /// [`BitSet::intersect_update_with_bitset()`]. Documents without any value are not in the
+    /// bitset, and multivalued documents are in it if any of their values is `value`. Deleted
+    /// documents are not excluded.
+    ///
+    /// If `field` is not a bool fast field, this method returns an Error.
+    pub fn bool_bitset(&self, field_name: &str, value: bool) -> crate::Result<BitSet> {
+        let column = self.bool(field_name)?;
+        let max_doc = self.columnar.num_docs();
+        let mut doc_bitset = BitSet::with_max_value(max_doc);
+        let mut docs = Vec::new();
+        column.get_docids_for_value_range(value..=value, 0..max_doc, &mut docs);
+        for doc in docs {
+            doc_bitset.insert(doc);
+        }
+        Ok(doc_bitset)
+    }
 }
 
 #[cfg(test)]
@@ -518,4 +609,33 @@ mod tests {
             .unwrap();
         assert_eq!(foo_subcolumns.len(), 0);
     }
+
+    #[test]
+    fn test_fast_field_reader_value_histogram() {
+        let mut schema_builder = Schema::builder();
+        let price = schema_builder.add_f64_field("price", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests().unwrap();
+        for i in 0..100 {
+            index_writer
+                .add_document(doc!(price => i as f64 * 0.5))
+                .unwrap();
+        }
+        index_writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+        let fast_fields = searcher.segment_reader(0u32).fast_fields();
+        let histogram = fast_fields
+            .value_histogram::<f64>("price", 2)
+            .unwrap()
+            .unwrap();
+        assert_eq!(histogram.buckets.len(), 2);
+        assert_eq!(histogram.buckets[0].lower, 0.0);
+        assert_eq!(histogram.buckets[0].upper, 24.5);
+        assert_eq!(histogram.buckets[1].upper, 49.5);
+        assert_eq!(histogram.estimate_count(0.0..=24.5), 50);
+        assert!(fast_fields
+            .value_histogram::<u64>("price", 2)
+            .unwrap()
+            .is_none());
+    }
 }
@@ -1,7 +1,7 @@
 use std::io;
 
 use columnar::{ColumnarWriter, NumericalValue};
-use common::{DateTimePrecision, JsonPathWriter};
+use common::{DateTime, DateTimePrecision, JsonPathWriter};
 use tokenizer_api::Token;
 
 use crate::schema::document::{Document, ReferenceValue, ReferenceValueLeaf, Value};
@@ -59,9 +59,9 @@ impl FastFieldsWriter {
             if let FieldType::JsonObject(json_object_options) = field_entry.field_type() {
                 if let Some(tokenizer_name) = json_object_options.get_fast_field_tokenizer_name() {
                     let text_analyzer = tokenizer_manager.get(tokenizer_name).ok_or_else(|| {
-                        TantivyError::InvalidArgument(format!(
-                            "Tokenizer {tokenizer_name:?} not found"
-                        ))
+                        TantivyError::InvalidArgument(
+                            format!("Tokenizer {tokenizer_name:?} not found").into(),
+                        )
                     })?;
                     per_field_tokenizer[field_id.field_id() as usize] = Some(text_analyzer);
                 }
@@ -72,9 +72,9 @@ impl FastFieldsWriter {
             if let FieldType::Str(text_options) = field_entry.field_type() {
                 if let Some(tokenizer_name) = text_options.get_fast_field_tokenizer_name() {
                     let text_analyzer = tokenizer_manager.get(tokenizer_name).ok_or_else(|| {
-                        TantivyError::InvalidArgument(format!(
-                            "Tokenizer {tokenizer_name:?} not found"
-                        ))
+                        TantivyError::InvalidArgument(
+                            format!("Tokenizer {tokenizer_name:?} not found").into(),
+                        )
                     })?;
                     per_field_tokenizer[field_id.field_id() as usize] = Some(text_analyzer);
                 }
@@ -117,6 +117,35 @@ impl FastFieldsWriter {
         Ok(())
     }
 
+    /// Records a string value for a fast text field of an already added document.
+    ///
+    /// The value is recorded as is, without going through the fast field tokenizer.
+    pub(crate) fn record_str(&mut self, doc_id: DocId, field: Field, value: &str) {
+        if let Some(field_name) = &self.fast_field_names[field.field_id() as usize] {
+            self.columnar_writer.record_str(doc_id, field_name, value);
+        }
+    }
+
+    /// Records a u64 value for a fast field of an already added document.
+    pub(crate) fn record_u64(&mut self, doc_id: DocId, field: Field, value: u64) {
+        if let Some(field_name) = &self.fast_field_names[field.field_id() as usize] {
+            self.columnar_writer
+                .record_numerical(doc_id, field_name, NumericalValue::from(value));
+        }
+    }
+
+    /// Records a date value for a fast field of an already added document.
+    pub(crate) fn record_datetime(&mut self, doc_id: DocId, field: Field, value: DateTime) {
+        if let Some(field_name) = &self.fast_field_names[field.field_id() as usize] {
+            let date_precision = self.date_precisions[field.field_id() as usize];
+            self.columnar_writer.record_datetime(
+                doc_id,
+                field_name,
+                value.truncate(date_precision),
+            );
+        }
+    }
+
     fn add_doc_value<'a, V: Value<'a>>(
         &mut self,
         doc_id: DocId,
@@ -180,6 +209,9 @@ impl FastFieldsWriter {
                 ReferenceValueLeaf::IpAddr(val) => {
                     self.columnar_writer.record_ip_addr(doc_id, field_name, val);
                 }
+                ReferenceValueLeaf::Uuid(val) => {
+                    self.columnar_writer.record_uuid(doc_id, field_name, val);
+                }
                 ReferenceValueLeaf::Bool(val) => {
                     self.columnar_writer.record_bool(doc_id, field_name, val);
                 }
@@ -264,10 +296,20 @@ fn record_json_value_to_columnar_writer<'a, V: Value<'a>>(
     }
     remaining_depth_limit -= 1;
 
+    let uuid_str;
     match json_val.as_value() {
         ReferenceValue::Leaf(leaf) => match leaf {
             ReferenceValueLeaf::Null => {} // TODO: Handle null
-            ReferenceValueLeaf::Str(val) => {
+            ReferenceValueLeaf::Str(_) | ReferenceValueLeaf::Uuid(_) => {
+                // Like in the inverted index, uuids are recorded as their string representation.
+                let val = match leaf {
+                    ReferenceValueLeaf::Uuid(uuid) => {
+                        uuid_str = uuid.to_string();
+                        uuid_str.as_str()
+                    }
+                    ReferenceValueLeaf::Str(val) => val,
+                    _ => unreachable!(),
+                };
                 if let Some(text_analyzer) = tokenizer.as_mut() {
                     let mut token_stream = text_analyzer.token_stream(val);
                     token_stream.process(&mut |token| {
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use super::{fieldnorm_to_id, id_to_fieldnorm};
 use crate::directory::{CompositeFile, FileSlice, OwnedBytes};
+use crate::error::corruption_from_io;
 use crate::schema::Field;
 use crate::space_usage::PerFieldSpaceUsage;
 use crate::DocId;
@@ -20,7 +21,7 @@ pub struct FieldNormReaders {
 impl FieldNormReaders {
     /// Creates a field norm reader.
     pub fn open(file: FileSlice) -> crate::Result<FieldNormReaders> {
-        let data = CompositeFile::open(&file)?;
+        let data = CompositeFile::open(&file).map_err(corruption_from_io)?;
         Ok(FieldNormReaders {
             data: Arc::new(data),
         })
@@ -0,0 +1,117 @@
+//! Generators of arbitrary, possibly corrupted, index files.
+//!
+//! Fuzz targets build a valid file out of an arbitrary input, apply arbitrary corruptions
+//! to it, and then check that opening and reading it with the untrusted open modes
+//! returns errors rather than panicking.
+//!
+//! The generators of the columnar and sstable files live in `tantivy_columnar::fuzzing`
+//! and `tantivy_sstable::fuzzing`.
+//!
+//! This module is only available with the `arbitrary` feature.
+
+use std::path::Path;
+
+use arbitrary::Arbitrary;
+use common::fuzzing::{corrupt, Corruption};
+
+use crate::directory::{Directory, RamDirectory};
+use crate::schema::{Field, Schema, STORED};
+use crate::store::{Compressor, StoreWriter};
+use crate::TantivyDocument;
+
+/// A value stored in an [`ArbitraryDocStore`].
+#[derive(Arbitrary, Clone, Debug)]
+#[allow(missing_docs)]
+pub enum ArbitraryStoredValue {
+    Str(String),
+    U64(u64),
+    I64(i64),
+    F64(f64),
+    Bool(bool),
+    Bytes(Vec<u8>),
+}
+
+/// An arbitrary doc store file.
+///
+/// The file is obtained by writing the documents with a [`StoreWriter`],
+/// and then applying the corruptions to it.
+#[derive(Arbitrary, Clone, Debug)]
+pub struct ArbitraryDocStore {
+    /// The values of each document.
+    pub docs: Vec<Vec<ArbitraryStoredValue>>,
+    /// The size of the blocks of the doc store.
+    ///
+    /// Small blocks make it possible to have skip indexes with several layers.
+    pub block_size: u16,
+    /// If true, blocks are compressed with the default compressor.
+    pub compress: bool,
+    /// The corruptions applied to the serialized file.
+    pub corruptions: Vec<Corruption>,
+}
+
+impl ArbitraryDocStore {
+    /// The schema of the documents of the doc store.
+    ///
+    /// It holds one stored field per type of [`ArbitraryStoredValue`].
+    pub fn schema() -> Schema {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("str", STORED);
+        schema_builder.add_u64_field("u64", STORED);
+        schema_builder.add_i64_field("i64", STORED);
+        schema_builder.add_f64_field("f64", STORED);
+        schema_builder.add_bool_field("bool", STORED);
+        schema_builder.add_bytes_field("bytes", STORED);
+        schema_builder.build()
+    }
+
+    /// Serializes the doc store, before any corruption.
+    pub fn to_valid_bytes(&self) -> Vec<u8> {
+        let schema = Self::schema();
+        let directory = RamDirectory::create();
+        let path = Path::new("store");
+        let compressor = if self.compress {
+            Compressor::default()
+        } else {
+            Compressor::None
+        };
+        let write_ptr = directory
+            .open_write(path)
+            .expect("Opening a file in a RamDirectory should never fail");
+        let mut store_writer = StoreWriter::new(
+            write_ptr,
+            compressor,
+            usize::from(self.block_size).max(1),
+            false,
+        )
+        .expect("Writing in a RamDirectory should never fail");
+        for values in &self.docs {
+            let mut doc = TantivyDocument::default();
+            for value in values {
+                match value {
+                    ArbitraryStoredValue::Str(val) => doc.add_text(Field::from_field_id(0), val),
+                    ArbitraryStoredValue::U64(val) => doc.add_u64(Field::from_field_id(1), *val),
+                    ArbitraryStoredValue::I64(val) => doc.add_i64(Field::from_field_id(2), *val),
+                    ArbitraryStoredValue::F64(val) => doc.add_f64(Field::from_field_id(3), *val),
+                    ArbitraryStoredValue::Bool(val) => doc.add_bool(Field::from_field_id(4), *val),
+                    ArbitraryStoredValue::Bytes(val) => doc.add_bytes(Field::from_field_id(5), val),
+                }
+            }
+            store_writer
+                .store(&doc, &schema)
+                .expect("Writing in a RamDirectory should never fail");
+        }
+        store_writer
+            .close()
+            .expect("Writing in a RamDirectory should never fail");
+        directory
+            .atomic_read(path)
+            .expect("Reading a file written in a RamDirectory should never fail")
+    }
+
+    /// Serializes the doc store, and applies the corruptions to it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.to_valid_bytes();
+        corrupt(&mut bytes, &self.corruptions);
+        bytes
+    }
+}
@@ -0,0 +1,363 @@
+//! Incremental backups of an index, deduplicated at the file level.
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use common::HasLen;
+use crc32fast::Hasher;
+use serde::{Deserialize, Serialize};
+
+use super::replica_syncer::{parse_manifest, segment_files};
+use crate::core::META_FILEPATH;
+use crate::directory::error::OpenReadError;
+use crate::directory::footer::Footer;
+use crate::directory::{Directory, FileSlice, ManagedDirectory, INDEX_WRITER_LOCK, META_LOCK};
+use crate::error::DataCorruption;
+use crate::{Index, Opstamp, TantivyError};
+
+/// Key of the list of the backup generations, in the backup target.
+const CATALOG_KEY: &str = "backups.json";
+
+/// Storage receiving the backups of an index, e.g. an object store.
+///
+/// The target is a flat key-value store. The backups of an index only ever add new keys, except
+/// for the catalog of the backup generations, which is overwritten by each backup. Writes are
+/// expected to be atomic.
+///
+/// Any [`Directory`] can be used as a backup target.
+pub trait BackupTarget: Send + Sync {
+    /// Returns true if the target holds a value for the given key.
+    fn exists(&self, key: &str) -> io::Result<bool>;
+
+    /// Reads the value of the given key.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::NotFound`] if the key does not exist.
+    fn read(&self, key: &str) -> io::Result<Vec<u8>>;
+
+    /// Atomically writes the value of the given key, replacing the former value if any.
+    fn write(&self, key: &str, data: &[u8]) -> io::Result<()>;
+}
+
+impl<D: Directory> BackupTarget for D {
+    fn exists(&self, key: &str) -> io::Result<bool> {
+        Directory::exists(self, Path::new(key)).map_err(io::Error::other)
+    }
+
+    fn read(&self, key: &str) -> io::Result<Vec<u8>> {
+        self.atomic_read(Path::new(key)).map_err(|err| match err {
+            OpenReadError::FileDoesNotExist(_) => io::Error::new(io::ErrorKind::NotFound, err),
+            _ => io::Error::other(err),
+        })
+    }
+
+    fn write(&self, key: &str, data: &[u8]) -> io::Result<()> {
+        self.atomic_write(Path::new(key), data)
+    }
+}
+
+/// A file of a backup generation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupFile {
+    /// Path of the file in the index directory.
+    pub path: PathBuf,
+    /// Checksum recorded in the footer of the file.
+    pub checksum: u32,
+    /// Size of the file, footer included.
+    pub num_bytes: u64,
+}
+
+impl BackupFile {
+    /// Returns the key of the file in the backup target.
+    ///
+    /// Segment files are immutable, so that a file with the same name and checksum is only copied
+    /// once across all of the backup generations.
+    pub fn key(&self) -> String {
+        format!("{}.{:08x}", self.path.display(), self.checksum)
+    }
+}
+
+/// Manifest of a backup generation, i.e. of a commit of the index copied to a backup target.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// Generation of the backup. Generations are numbered from 1, in the order of the backups.
+    pub generation: u64,
+    /// Opstamp of the backed up commit.
+    pub opstamp: Opstamp,
+    /// Files of the backed up commit.
+    pub files: Vec<BackupFile>,
+    // Content of the `meta.json` file of the backed up commit.
+    meta_json: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct BackupCatalog {
+    generations: Vec<u64>,
+}
+
+fn manifest_key(generation: u64) -> String {
+    format!("backup-{generation}.json")
+}
+
+fn load_catalog(target: &dyn BackupTarget) -> crate::Result<BackupCatalog> {
+    match target.read(CATALOG_KEY) {
+        Ok(catalog_json) => Ok(serde_json::from_slice(&catalog_json)?),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(BackupCatalog::default()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+impl BackupManifest {
+    /// Returns the generations of the backups stored in the target, in increasing order.
+    pub fn list_generations(target: &dyn BackupTarget) -> crate::Result<Vec<u64>> {
+        Ok(load_catalog(target)?.generations)
+    }
+
+    /// Loads the manifest of the given backup generation.
+    pub fn load(target: &dyn BackupTarget, generation: u64) -> crate::Result<BackupManifest> {
+        let manifest_json = target.read(&manifest_key(generation)).map_err(|err| {
+            if err.kind() == io::ErrorKind::NotFound {
+                TantivyError::InvalidArgument(
+                    format!("Backup generation {generation} does not exist.").into(),
+                )
+            } else {
+                err.into()
+            }
+        })?;
+        Ok(serde_json::from_slice(&manifest_json)?)
+    }
+}
+
+/// Outcome of an [`Index::backup()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BackupReport {
+    /// Generation of the new backup.
+    pub generation: u64,
+    /// Opstamp of the backed up commit.
+    pub opstamp: Opstamp,
+    /// Number of files copied to the target.
+    pub num_files_copied: usize,
+    /// Number of bytes copied to the target.
+    pub num_bytes_copied: u64,
+    /// Number of files which were already present in the target.
+    pub num_files_reused: usize,
+}
+
+fn footer_checksum(path: &Path, file: FileSlice) -> crate::Result<u32> {
+    let (footer, _) = Footer::extract_footer(file)
+        .map_err(|io_error| OpenReadError::wrap_io_error(io_error, path.to_path_buf()))?;
+    Ok(footer.crc())
+}
+
+pub(crate) fn backup(index: &Index, target: &dyn BackupTarget) -> crate::Result<BackupReport> {
+    let directory = index.directory();
+    // Opening the files while holding the meta lock prevents the garbage collection from removing
+    // them. Once opened, they can be read even if they get deleted.
+    let (meta_json, index_meta, opened_files) = {
+        let _meta_lock = directory.acquire_lock(&META_LOCK)?;
+        let meta_json = directory.atomic_read(&META_FILEPATH)?;
+        let index_meta = parse_manifest(&meta_json)?;
+        let mut opened_files = Vec::new();
+        for path in index_meta.segments.iter().flat_map(segment_files) {
+            let file = directory.open_read_with_footer(&path)?;
+            opened_files.push((path, file));
+        }
+        (meta_json, index_meta, opened_files)
+    };
+    let meta_json = String::from_utf8(meta_json).map_err(|_| {
+        TantivyError::InvalidArgument("Manifest does not contain valid utf8.".into())
+    })?;
+    let mut catalog = load_catalog(target)?;
+    let generation = catalog.generations.last().copied().unwrap_or(0) + 1;
+    let mut report = BackupReport {
+        generation,
+        opstamp: index_meta.opstamp,
+        num_files_copied: 0,
+        num_bytes_copied: 0,
+        num_files_reused: 0,
+    };
+    let mut files = Vec::with_capacity(opened_files.len());
+    for (path, file) in opened_files {
+        let backup_file = BackupFile {
+            checksum: footer_checksum(&path, file.clone())?,
+            num_bytes: file.len() as u64,
+            path,
+        };
+        let key = backup_file.key();
+        if target.exists(&key)? {
+            report.num_files_reused += 1;
+        } else {
+            target.write(&key, file.read_bytes()?.as_slice())?;
+            report.num_files_copied += 1;
+            report.num_bytes_copied += backup_file.num_bytes;
+        }
+        files.push(backup_file);
+    }
+    let manifest = BackupManifest {
+        generation,
+        opstamp: index_meta.opstamp,
+        files,
+        meta_json,
+    };
+    // The generation only becomes visible once all of its files and its manifest are written.
+    target.write(&manifest_key(generation), &serde_json::to_vec(&manifest)?)?;
+    catalog.generations.push(generation);
+    target.write(CATALOG_KEY, &serde_json::to_vec(&catalog)?)?;
+    Ok(report)
+}
+
+/// Returns true if the directory holds a complete copy of the file.
+fn has_file(directory: &ManagedDirectory, backup_file: &BackupFile) -> crate::Result<bool> {
+    let file = match directory.open_read_with_footer(&backup_file.path) {
+        Ok(file) => file,
+        Err(OpenReadError::FileDoesNotExist(_)) => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+    if file.len() as u64 != backup_file.num_bytes {
+        return Ok(false);
+    }
+    // A file left incomplete by a failed restore does not have a valid footer.
+    Ok(footer_checksum(&backup_file.path, file).ok() == Some(backup_file.checksum))
+}
+
+fn check_checksum(backup_file: &BackupFile, data: &[u8]) -> crate::Result<()> {
+    let corrupted = || {
+        DataCorruption::new(
+            backup_file.path.clone(),
+            format!("Backup file {} is corrupted.", backup_file.key()),
+        )
+    };
+    let (footer, payload) =
+        Footer::extract_footer(FileSlice::from(data.to_vec())).map_err(|_| corrupted())?;
+    let mut hasher = Hasher::new();
+    hasher.update(payload.read_bytes()?.as_slice());
+    if data.len() as u64 != backup_file.num_bytes
+        || footer.crc() != backup_file.checksum
+        || hasher.finalize() != backup_file.checksum
+    {
+        return Err(corrupted().into());
+    }
+    Ok(())
+}
+
+pub(crate) fn restore(
+    target: &dyn BackupTarget,
+    generation: u64,
+    directory: Box<dyn Directory>,
+) -> crate::Result<Index> {
+    let manifest = BackupManifest::load(target, generation)?;
+    {
+        let managed_directory = ManagedDirectory::wrap(directory.box_clone())?;
+        let _index_writer_lock =
+            managed_directory
+                .acquire_lock(&INDEX_WRITER_LOCK)
+                .map_err(|err| {
+                    TantivyError::LockFailure(
+                        err,
+                        Some(
+                            "Failed to acquire index lock. An index cannot be restored while an \
+                             `IndexWriter` is working on its `Directory`."
+                                .to_string(),
+                        ),
+                    )
+                })?;
+        let mut restored_files = HashSet::new();
+        for backup_file in &manifest.files {
+            if !restored_files.insert(&backup_file.path)
+                || has_file(&managed_directory, backup_file)?
+            {
+                continue;
+            }
+            let data = target.read(&backup_file.key())?;
+            check_checksum(backup_file, &data)?;
+            // Registers the file as managed, so that it gets garbage collected once it is not
+            // used anymore.
+            managed_directory.atomic_write(&backup_file.path, &data)?;
+        }
+        // Readers of the directory switch to the restored commit at once.
+        managed_directory.atomic_write(&META_FILEPATH, manifest.meta_json.as_bytes())?;
+    }
+    Index::open(directory)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BackupManifest, BackupTarget};
+    use crate::directory::RamDirectory;
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, STRING};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_backup_and_restore() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.commit()?;
+
+        let target = RamDirectory::create();
+        let first_report = index.backup(&target)?;
+        assert_eq!(first_report.generation, 1);
+        assert!(first_report.num_files_copied > 0);
+        assert_eq!(first_report.num_files_reused, 0);
+
+        // Only the delete bitset of the new commit is copied.
+        index_writer.delete_term(Term::from_field_text(id, "a"));
+        index_writer.commit()?;
+        let second_report = index.backup(&target)?;
+        assert_eq!(second_report.generation, 2);
+        assert_eq!(second_report.num_files_copied, 1);
+        assert_eq!(
+            second_report.num_files_reused,
+            first_report.num_files_copied
+        );
+        assert_eq!(BackupManifest::list_generations(&target)?, vec![1, 2]);
+
+        let restored_directory = RamDirectory::create();
+        let restored = Index::restore(&target, 1, restored_directory.clone())?;
+        assert_eq!(restored.load_metas()?.opstamp, first_report.opstamp);
+        assert_eq!(restored.reader()?.searcher().num_docs(), 2);
+        assert!(restored.validate_checksum()?.is_empty());
+
+        // Restoring another generation over the restored index.
+        let restored = Index::restore(&target, 2, restored_directory)?;
+        assert_eq!(restored.reader()?.searcher().num_docs(), 1);
+        let mut restored_writer: IndexWriter = restored.writer_for_tests()?;
+        restored_writer.add_document(doc!(id => "c"))?;
+        restored_writer.commit()?;
+        assert_eq!(restored.reader()?.searcher().num_docs(), 2);
+
+        assert!(Index::restore(&target, 3, RamDirectory::create()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_corrupted_backup() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.commit()?;
+
+        let target = RamDirectory::create();
+        index.backup(&target)?;
+        let manifest = BackupManifest::load(&target, 1)?;
+        let key = manifest.files[0].key();
+        let mut data = target.read(&key)?;
+        data[0] ^= 1;
+        target.write(&key, &data)?;
+
+        let restored_directory = RamDirectory::create();
+        assert!(matches!(
+            Index::restore(&target, 1, restored_directory.clone()),
+            Err(crate::TantivyError::DataCorruption(_))
+        ));
+        // The failed restore did not create an index.
+        assert!(!Index::exists(&restored_directory)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,364 @@
+//! Health report of an index, for operations dashboards.
+use std::fmt;
+
+use common::ByteCount;
+
+use crate::index::{SegmentId, SegmentReader};
+use crate::indexer::{LogMergePolicy, MergeCandidate, MergePolicy};
+use crate::schema::Field;
+use crate::Index;
+
+/// Thresholds used by [`Index::health_report_with_settings()`] to detect issues.
+#[derive(Debug)]
+pub struct HealthCheckSettings {
+    /// Ratio of deleted documents above which a segment should be merged to purge them.
+    ///
+    /// Defaults to `0.2`.
+    pub max_deleted_docs_ratio: f32,
+    /// Number of documents under which a segment is considered small.
+    ///
+    /// Defaults to `10_000`.
+    pub small_segment_max_docs: u32,
+    /// Number of small segments above which they should be merged together.
+    ///
+    /// Defaults to `8`.
+    pub max_num_small_segments: usize,
+    /// Size of the term dictionary of a field in a segment above which it is reported as
+    /// oversized.
+    ///
+    /// Defaults to 512MB.
+    pub max_termdict_num_bytes: u64,
+    /// Merge policy expected to be running on the index.
+    ///
+    /// Defaults to the default [`LogMergePolicy`].
+    pub merge_policy: Box<dyn MergePolicy>,
+}
+
+impl Default for HealthCheckSettings {
+    fn default() -> Self {
+        HealthCheckSettings {
+            max_deleted_docs_ratio: 0.2,
+            small_segment_max_docs: 10_000,
+            max_num_small_segments: 8,
+            max_termdict_num_bytes: 512 * 1024 * 1024,
+            merge_policy: Box::<LogMergePolicy>::default(),
+        }
+    }
+}
+
+/// Summary of a segment in a [`HealthReport`].
+#[derive(Clone, Debug)]
+pub struct SegmentHealth {
+    /// The segment.
+    pub segment_id: SegmentId,
+    /// Number of documents of the segment, deleted documents included.
+    pub max_doc: u32,
+    /// Number of deleted documents of the segment.
+    pub num_deleted_docs: u32,
+    /// Space used by the segment.
+    pub num_bytes: ByteCount,
+}
+
+impl SegmentHealth {
+    /// Ratio of deleted documents in the segment.
+    pub fn deleted_docs_ratio(&self) -> f32 {
+        if self.max_doc == 0 {
+            return 0.0;
+        }
+        self.num_deleted_docs as f32 / self.max_doc as f32
+    }
+}
+
+/// An issue detected by a [`HealthReport`].
+#[derive(Clone, Debug)]
+pub enum HealthIssue {
+    /// The ratio of deleted documents of a segment exceeds
+    /// [`HealthCheckSettings::max_deleted_docs_ratio`].
+    TooManyDeletedDocs {
+        /// The segment.
+        segment_id: SegmentId,
+        /// Ratio of deleted documents of the segment.
+        deleted_docs_ratio: f32,
+    },
+    /// The number of small segments exceeds [`HealthCheckSettings::max_num_small_segments`].
+    TooManySmallSegments {
+        /// The small segments.
+        segment_ids: Vec<SegmentId>,
+    },
+    /// The field is neither indexed nor fast, so that it cannot be searched.
+    UnsearchableField {
+        /// The field.
+        field: Field,
+        /// Name of the field.
+        field_name: String,
+    },
+    /// The term dictionary of a field in a segment exceeds
+    /// [`HealthCheckSettings::max_termdict_num_bytes`].
+    OversizedTermDictionary {
+        /// The segment.
+        segment_id: SegmentId,
+        /// The field.
+        field: Field,
+        /// Name of the field.
+        field_name: String,
+        /// Size of the term dictionary.
+        num_bytes: ByteCount,
+    },
+    /// The merge policy would merge some segments, which means that the merges are lagging
+    /// behind, or that no index writer is running on the index.
+    PendingMerges {
+        /// The merges suggested by the merge policy.
+        candidates: Vec<MergeCandidate>,
+    },
+}
+
+impl HealthIssue {
+    /// Returns an actionable recommendation to solve the issue.
+    pub fn recommendation(&self) -> String {
+        match self {
+            HealthIssue::TooManyDeletedDocs {
+                segment_id,
+                deleted_docs_ratio,
+            } => format!(
+                "Segment {} has {:.0}% of deleted documents: merge it to purge them, or lower the \
+                 deleted documents ratio of the merge policy.",
+                segment_id.short_uuid_string(),
+                deleted_docs_ratio * 100.0
+            ),
+            HealthIssue::TooManySmallSegments { segment_ids } => format!(
+                "{} small segments are not merged: merge them together, or commit less often.",
+                segment_ids.len()
+            ),
+            HealthIssue::UnsearchableField { field_name, .. } => format!(
+                "Field {field_name:?} is neither indexed nor fast: it cannot be searched, set it \
+                 as indexed or fast in the schema if it should be."
+            ),
+            HealthIssue::OversizedTermDictionary {
+                segment_id,
+                field_name,
+                num_bytes,
+                ..
+            } => format!(
+                "The term dictionary of field {field_name:?} in segment {} uses {}: check the \
+                 tokenizer of the field, or index it as a fast field only if it holds identifiers.",
+                segment_id.short_uuid_string(),
+                num_bytes.human_readable()
+            ),
+            HealthIssue::PendingMerges { candidates } => format!(
+                "The merge policy suggests {} merges: make sure an index writer is running, with \
+                 enough merge threads to keep up with the commits.",
+                candidates.len()
+            ),
+        }
+    }
+}
+
+impl fmt::Display for HealthIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.recommendation())
+    }
+}
+
+/// Outcome of an [`Index::health_report()`].
+#[derive(Clone, Debug)]
+pub struct HealthReport {
+    /// Searchable segments of the index.
+    pub segments: Vec<SegmentHealth>,
+    /// Issues detected in the index.
+    pub issues: Vec<HealthIssue>,
+}
+
+impl HealthReport {
+    /// Number of searchable segments.
+    pub fn num_segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Number of alive documents.
+    pub fn num_docs(&self) -> u64 {
+        self.segments
+            .iter()
+            .map(|segment| u64::from(segment.max_doc - segment.num_deleted_docs))
+            .sum()
+    }
+
+    /// Number of deleted documents.
+    pub fn num_deleted_docs(&self) -> u64 {
+        self.segments
+            .iter()
+            .map(|segment| u64::from(segment.num_deleted_docs))
+            .sum()
+    }
+
+    /// Ratio of deleted documents in the index.
+    pub fn deleted_docs_ratio(&self) -> f32 {
+        let max_doc: u64 = self
+            .segments
+            .iter()
+            .map(|segment| u64::from(segment.max_doc))
+            .sum();
+        if max_doc == 0 {
+            return 0.0;
+        }
+        self.num_deleted_docs() as f32 / max_doc as f32
+    }
+
+    /// Returns true if no issue was detected.
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+pub(crate) fn health_report(
+    index: &Index,
+    settings: &HealthCheckSettings,
+) -> crate::Result<HealthReport> {
+    let schema = index.schema();
+    let segment_metas = index.searchable_segment_metas()?;
+    let mut segments = Vec::with_capacity(segment_metas.len());
+    let mut issues = Vec::new();
+
+    for (field, field_entry) in schema.fields() {
+        if !field_entry.is_indexed() && !field_entry.is_fast() {
+            issues.push(HealthIssue::UnsearchableField {
+                field,
+                field_name: field_entry.name().to_string(),
+            });
+        }
+    }
+
+    let mut small_segment_ids = Vec::new();
+    for segment_meta in &segment_metas {
+        let segment_reader = SegmentReader::open(&index.segment(segment_meta.clone()))?;
+        let space_usage = segment_reader.space_usage()?;
+        let segment = SegmentHealth {
+            segment_id: segment_meta.id(),
+            max_doc: segment_meta.max_doc(),
+            num_deleted_docs: segment_meta.num_deleted_docs(),
+            num_bytes: space_usage.total(),
+        };
+        let deleted_docs_ratio = segment.deleted_docs_ratio();
+        if deleted_docs_ratio > settings.max_deleted_docs_ratio {
+            issues.push(HealthIssue::TooManyDeletedDocs {
+                segment_id: segment.segment_id,
+                deleted_docs_ratio,
+            });
+        }
+        if segment.max_doc < settings.small_segment_max_docs {
+            small_segment_ids.push(segment.segment_id);
+        }
+        for (&field, field_usage) in space_usage.termdict().fields() {
+            let num_bytes = field_usage.total();
+            if num_bytes.get_bytes() > settings.max_termdict_num_bytes {
+                issues.push(HealthIssue::OversizedTermDictionary {
+                    segment_id: segment.segment_id,
+                    field,
+                    field_name: schema.get_field_name(field).to_string(),
+                    num_bytes,
+                });
+            }
+        }
+        segments.push(segment);
+    }
+    if small_segment_ids.len() > settings.max_num_small_segments {
+        issues.push(HealthIssue::TooManySmallSegments {
+            segment_ids: small_segment_ids,
+        });
+    }
+
+    let candidates = settings
+        .merge_policy
+        .compute_merge_candidates(&segment_metas);
+    if !candidates.is_empty() {
+        issues.push(HealthIssue::PendingMerges { candidates });
+    }
+
+    Ok(HealthReport { segments, issues })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HealthCheckSettings, HealthIssue};
+    use crate::indexer::merge_policy::tests::MergeWheneverPossible;
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, STORED, STRING, TEXT};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_health_report() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let body = schema_builder.add_text_field("body", TEXT);
+        schema_builder.add_text_field("payload", STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0..3 {
+            index_writer.add_document(doc!(id => format!("a{i}"), body => "hello"))?;
+            index_writer.add_document(doc!(id => format!("b{i}"), body => "hello world"))?;
+            index_writer.commit()?;
+        }
+        index_writer.delete_term(Term::from_field_text(id, "a0"));
+        index_writer.commit()?;
+
+        let report = index.health_report()?;
+        assert_eq!(report.num_segments(), 3);
+        assert_eq!(report.num_docs(), 5);
+        assert_eq!(report.num_deleted_docs(), 1);
+        assert!(!report.is_healthy());
+        let unsearchable_fields: Vec<&str> = report
+            .issues
+            .iter()
+            .filter_map(|issue| match issue {
+                HealthIssue::UnsearchableField { field_name, .. } => Some(field_name.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(unsearchable_fields, vec!["payload"]);
+        let deleted_segment_ids: Vec<_> = report
+            .issues
+            .iter()
+            .filter_map(|issue| match issue {
+                HealthIssue::TooManyDeletedDocs { segment_id, .. } => Some(*segment_id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(deleted_segment_ids.len(), 1);
+        assert!(report
+            .issues
+            .iter()
+            .all(|issue| !matches!(issue, HealthIssue::PendingMerges { .. })));
+
+        let settings = HealthCheckSettings {
+            max_num_small_segments: 2,
+            max_termdict_num_bytes: 0,
+            merge_policy: Box::new(MergeWheneverPossible),
+            ..HealthCheckSettings::default()
+        };
+        let report = index.health_report_with_settings(&settings)?;
+        assert!(report.issues.iter().any(|issue| matches!(
+            issue,
+            HealthIssue::TooManySmallSegments { segment_ids } if segment_ids.len() == 3
+        )));
+        let oversized_fields: Vec<(crate::index::SegmentId, crate::schema::Field)> = report
+            .issues
+            .iter()
+            .filter_map(|issue| match issue {
+                HealthIssue::OversizedTermDictionary {
+                    segment_id, field, ..
+                } => Some((*segment_id, *field)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(oversized_fields.len(), 6);
+        assert!(report.issues.iter().any(|issue| matches!(
+            issue,
+            HealthIssue::PendingMerges { candidates } if candidates.len() == 1
+        )));
+        assert!(oversized_fields.iter().any(|(_, field)| *field == body));
+        for issue in &report.issues {
+            assert!(!issue.recommendation().is_empty());
+        }
+        Ok(())
+    }
+}
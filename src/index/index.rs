@@ -5,8 +5,11 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::thread::available_parallelism;
 
+use super::backup::{self, BackupReport, BackupTarget};
+use super::health::{self, HealthCheckSettings, HealthReport};
 use super::segment::Segment;
 use super::segment_reader::merge_field_meta_data;
+use super::settings_preset::SettingsPreset;
 use super::{FieldMetadata, IndexSettings};
 use crate::core::{Executor, META_FILEPATH};
 use crate::directory::error::OpenReadError;
@@ -18,8 +21,10 @@ use crate::index::{IndexMeta, SegmentId, SegmentMeta, SegmentMetaInventory};
 use crate::indexer::index_writer::{
     IndexWriterOptions, MAX_NUM_THREAD, MEMORY_BUDGET_NUM_BYTES_MIN,
 };
+use crate::indexer::merge_stats::load_merge_stats_history;
 use crate::indexer::segment_updater::save_metas;
-use crate::indexer::{IndexWriter, SingleSegmentIndexWriter};
+use crate::indexer::writer_fields::with_writer_fields;
+use crate::indexer::{IndexWriter, MergeStatsHistory, SingleSegmentIndexWriter};
 use crate::reader::{IndexReader, IndexReaderBuilder};
 use crate::schema::document::Document;
 use crate::schema::{Field, FieldType, Schema};
@@ -130,6 +135,16 @@ impl IndexBuilder {
         self
     }
 
+    /// Set the settings chosen by a preset.
+    ///
+    /// See [`SettingsPreset::settings()`] for the index settings of the preset, and
+    /// [`Index::writer_with_preset()`] to open an index writer with the rest of them.
+    #[must_use]
+    pub fn settings_preset(mut self, preset: SettingsPreset) -> Self {
+        self.index_settings = preset.settings().index_settings;
+        self
+    }
+
     /// Set the schema
     #[must_use]
     pub fn schema(mut self, schema: Schema) -> Self {
@@ -208,10 +223,12 @@ impl IndexBuilder {
     }
 
     fn get_expect_schema(&self) -> crate::Result<Schema> {
-        self.schema
+        let schema = self
+            .schema
             .as_ref()
             .cloned()
-            .ok_or(TantivyError::IndexBuilderMissingArgument("schema"))
+            .ok_or(TantivyError::IndexBuilderMissingArgument("schema"))?;
+        with_writer_fields(schema, &self.index_settings)
     }
 
     /// Opens or creates a new index in the provided directory
@@ -235,9 +252,7 @@ impl IndexBuilder {
         if let Some(_schema) = self.schema.as_ref() {
             Ok(())
         } else {
-            Err(TantivyError::InvalidArgument(
-                "no schema passed".to_string(),
-            ))
+            Err(TantivyError::InvalidArgument("no schema passed".into()))
         }
     }
 
@@ -430,17 +445,17 @@ impl Index {
             }
         };
         let indexing_options = indexing_options_opt.ok_or_else(|| {
-            TantivyError::InvalidArgument(format!(
-                "No indexing options set for field {field_entry:?}"
-            ))
+            TantivyError::InvalidArgument(
+                format!("No indexing options set for field {field_entry:?}").into(),
+            )
         })?;
 
         tokenizer_manager
             .get(indexing_options.tokenizer())
             .ok_or_else(|| {
-                TantivyError::InvalidArgument(format!(
-                    "No Tokenizer found for field {field_entry:?}"
-                ))
+                TantivyError::InvalidArgument(
+                    format!("No Tokenizer found for field {field_entry:?}").into(),
+                )
             })
     }
 
@@ -571,7 +586,9 @@ impl Index {
     /// - `overall_memory_budget_in_bytes` sets the amount of memory allocated for all indexing
     ///   thread.
     ///
-    /// Each thread will receive a budget of `overall_memory_budget_in_bytes / num_threads`.
+    /// The budget is shared among the threads: when it is reached, the thread holding
+    /// the largest in-memory segment flushes it.
+    /// `overall_memory_budget_in_bytes / num_threads` is required to be at least 15 MB.
     ///
     /// # Errors
     /// If the lockfile already exists, returns `Error::DirectoryLockBusy` or an `Error::IoError`.
@@ -590,6 +607,24 @@ impl Index {
         self.writer_with_options(options)
     }
 
+    /// Open a new index writer with the memory budget, threads and merge policy chosen by a
+    /// preset. Attempts to acquire a lockfile.
+    ///
+    /// The index settings of the preset are set upon index creation, with
+    /// [`IndexBuilder::settings_preset()`].
+    ///
+    /// # Errors
+    /// If the lockfile already exists, returns `TantivyError::LockFailure`.
+    pub fn writer_with_preset<D: Document>(
+        &self,
+        preset: SettingsPreset,
+    ) -> crate::Result<IndexWriter<D>> {
+        let preset_settings = preset.settings();
+        let index_writer = self.writer_with_options(preset_settings.writer_options())?;
+        index_writer.set_merge_policy(Box::new(preset_settings.merge_policy));
+        Ok(index_writer)
+    }
+
     /// Helper to create an index writer for tests.
     ///
     /// That index writer only simply has a single thread and a memory budget of 15 MB.
@@ -686,6 +721,59 @@ impl Index {
             .collect())
     }
 
+    /// Backs up the last commit of the index to the target, as a new backup generation.
+    ///
+    /// Only the files which are not already in the target, identified by their name and the
+    /// checksum of their footer, are copied. The files of the commit are protected from the
+    /// garbage collection of the index while they are being copied, so that the index can keep
+    /// being written and merged during the backup.
+    ///
+    /// Only one backup should be written to a given target at a time.
+    pub fn backup(&self, target: &dyn BackupTarget) -> crate::Result<BackupReport> {
+        backup::backup(self, target)
+    }
+
+    /// Restores a backup generation to the given directory, and opens the restored index.
+    ///
+    /// The directory may be empty, or hold a former version of the index. The files which are
+    /// already in the directory are not copied again. The restored commit becomes visible
+    /// atomically, once all of its files have been copied and checked against their checksums.
+    ///
+    /// Returns an error if an `IndexWriter` is working on the directory.
+    pub fn restore<T: Into<Box<dyn Directory>>>(
+        target: &dyn BackupTarget,
+        generation: u64,
+        directory: T,
+    ) -> crate::Result<Index> {
+        backup::restore(target, generation, directory.into())
+    }
+
+    /// Checks the last commit of the index for operational issues, using the default
+    /// [`HealthCheckSettings`].
+    ///
+    /// The report lists the searchable segments, along with the issues detected and a
+    /// recommendation to solve each of them. It opens all of the segments, and is meant to be
+    /// called from time to time, e.g. to feed an operations dashboard.
+    pub fn health_report(&self) -> crate::Result<HealthReport> {
+        self.health_report_with_settings(&HealthCheckSettings::default())
+    }
+
+    /// Checks the last commit of the index for operational issues, using the given thresholds.
+    pub fn health_report_with_settings(
+        &self,
+        settings: &HealthCheckSettings,
+    ) -> crate::Result<HealthReport> {
+        health::health_report(self, settings)
+    }
+
+    /// Returns the statistics of the last merges of the index, as recorded by the index writers.
+    ///
+    /// Only the last [`MERGE_STATS_HISTORY_CAPACITY`](crate::indexer::MERGE_STATS_HISTORY_CAPACITY)
+    /// merges are kept.
+    pub fn merge_stats_history(&self) -> crate::Result<MergeStatsHistory> {
+        load_merge_stats_history(&self.directory)
+    }
+
     /// Returns the set of corrupted files
     pub fn validate_checksum(&self) -> crate::Result<HashSet<PathBuf>> {
         let managed_files = self.directory.list_managed_files();
@@ -8,9 +8,39 @@ use serde::{Deserialize, Serialize};
 
 use super::SegmentComponent;
 use crate::index::SegmentId;
+use crate::indexer::near_duplicates::NearDuplicateSignatureSettings;
+use crate::indexer::routing::RoutingSettings;
 use crate::schema::Schema;
 use crate::store::Compressor;
-use crate::{Inventory, Opstamp, TrackedObject};
+use crate::{DateTime, Inventory, Opstamp, TrackedObject};
+
+/// The partition the documents of a segment belong to, along with the field they were
+/// partitioned on.
+///
+/// See [`SegmentMeta::partition()`].
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct SegmentPartition {
+    /// Name of the field the documents were partitioned on.
+    pub field: String,
+    /// The partition, given the value of the field.
+    pub key: PartitionKey,
+}
+
+/// Identifies a partition of the documents, given their value for the partitioning field.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PartitionKey {
+    /// The documents all have this value, e.g. their tenant.
+    Value(String),
+    /// The values of the documents all hash to `partition`, out of `num_partitions`
+    /// (see [`RoutingSettings::partition()`]).
+    Hash {
+        /// The partition of the values.
+        partition: u32,
+        /// The number of partitions the values are hashed to.
+        num_partitions: u32,
+    },
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct DeleteMeta {
@@ -39,6 +69,8 @@ impl SegmentMetaInventory {
             max_doc,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
             deletes: None,
+            partition: None,
+            decay_reference_timestamp: None,
         };
         SegmentMeta::from(self.inventory.track(inner))
     }
@@ -169,6 +201,26 @@ impl SegmentMeta {
             .map(|delete_meta| delete_meta.opstamp)
     }
 
+    /// Returns the partition of the documents of this segment.
+    ///
+    /// Segments written by an `IndexWriter` partitioning documents by tenant
+    /// (see [`IndexWriterOptions`](crate::indexer::IndexWriterOptions)), or written to an index
+    /// with a [`routing_field`](crate::IndexSettings::routing_field), only contain documents of a
+    /// single partition (see [`indexer::routing`](crate::indexer::routing)). `None` means the
+    /// segment may contain documents of any partition.
+    pub fn partition(&self) -> Option<&SegmentPartition> {
+        self.tracked.partition.as_ref()
+    }
+
+    /// Returns the time as of which the values of the decayed counters of the segment are
+    /// expressed.
+    ///
+    /// See [`NumericOptions::set_decay_half_life()`](crate::schema::NumericOptions::set_decay_half_life).
+    /// `None` means the segment does not contain any decayed counter.
+    pub fn decay_reference_timestamp(&self) -> Option<DateTime> {
+        self.tracked.decay_reference_timestamp
+    }
+
     /// Returns true iff the segment meta contains
     /// delete information.
     pub fn has_deletes(&self) -> bool {
@@ -184,6 +236,42 @@ impl SegmentMeta {
             max_doc,
             deletes: None,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
+            partition: inner_meta.partition.clone(),
+            decay_reference_timestamp: inner_meta.decay_reference_timestamp,
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Tags the segment as only containing documents of the given partition.
+    #[doc(hidden)]
+    #[must_use]
+    pub fn with_partition(self, partition: Option<SegmentPartition>) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            deletes: inner_meta.deletes.clone(),
+            include_temp_doc_store: inner_meta.include_temp_doc_store.clone(),
+            partition,
+            decay_reference_timestamp: inner_meta.decay_reference_timestamp,
+        });
+        SegmentMeta { tracked }
+    }
+
+    /// Sets the time as of which the values of the decayed counters of the segment are
+    /// expressed.
+    #[doc(hidden)]
+    #[must_use]
+    pub fn with_decay_reference_timestamp(
+        self,
+        decay_reference_timestamp: Option<DateTime>,
+    ) -> SegmentMeta {
+        let tracked = self.tracked.map(move |inner_meta| InnerSegmentMeta {
+            segment_id: inner_meta.segment_id,
+            max_doc: inner_meta.max_doc,
+            deletes: inner_meta.deletes.clone(),
+            include_temp_doc_store: inner_meta.include_temp_doc_store.clone(),
+            partition: inner_meta.partition.clone(),
+            decay_reference_timestamp,
         });
         SegmentMeta { tracked }
     }
@@ -204,6 +292,8 @@ impl SegmentMeta {
             max_doc: inner_meta.max_doc,
             include_temp_doc_store: Arc::new(AtomicBool::new(true)),
             deletes: Some(delete_meta),
+            partition: inner_meta.partition.clone(),
+            decay_reference_timestamp: inner_meta.decay_reference_timestamp,
         });
         SegmentMeta { tracked }
     }
@@ -219,6 +309,12 @@ struct InnerSegmentMeta {
     #[serde(skip)]
     #[serde(default = "default_temp_store")]
     pub(crate) include_temp_doc_store: Arc<AtomicBool>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    partition: Option<SegmentPartition>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decay_reference_timestamp: Option<DateTime>,
 }
 fn default_temp_store() -> Arc<AtomicBool> {
     Arc::new(AtomicBool::new(false))
@@ -240,6 +336,10 @@ fn is_true(val: &bool) -> bool {
     *val
 }
 
+fn is_false(val: &bool) -> bool {
+    !val
+}
+
 /// Search Index Settings.
 ///
 /// Contains settings which are applied on the whole
@@ -258,6 +358,43 @@ pub struct IndexSettings {
     #[serde(default = "default_docstore_blocksize")]
     /// The size of each block that will be compressed and written to disk
     pub docstore_blocksize: usize,
+    /// If set to true, the index writer populates the `_ingest_timestamp` and `_opstamp` fast
+    /// fields of every document.
+    ///
+    /// See [`indexer::ingest_metadata`](crate::indexer::ingest_metadata).
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub ingest_metadata: bool,
+    /// If set, the index writer computes a near-duplicate signature for every document, and
+    /// stores it in the `_simhash` or `_minhash` fast field.
+    ///
+    /// See [`indexer::near_duplicates`](crate::indexer::near_duplicates).
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub near_duplicate_signature: Option<NearDuplicateSignatureSettings>,
+    /// If set to true, the index writer records the indexed, non-fast fields each document has a
+    /// value for in the `_field_presence` field.
+    ///
+    /// See [`indexer::field_presence`](crate::indexer::field_presence).
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub field_presence: bool,
+    /// If set, the index writer routes documents to partitions given the value of the routing
+    /// field, and writes the documents of each partition to their own segments.
+    ///
+    /// See [`indexer::routing`](crate::indexer::routing).
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub routing_field: Option<RoutingSettings>,
+    /// If set, boolean queries may have at most this number of clauses.
+    ///
+    /// The `Should` term clauses of larger boolean queries are grouped into a single
+    /// [`TermSetQuery`](crate::query::TermSetQuery) clause, which gives a constant score to the
+    /// terms. If the query still has too many clauses,
+    /// [`TantivyError::TooManyClauses`](crate::TantivyError::TooManyClauses) is returned.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_boolean_clauses: Option<usize>,
 }
 
 /// Must be a function to be compatible with serde defaults
@@ -271,6 +408,11 @@ impl Default for IndexSettings {
             docstore_compression: Compressor::default(),
             docstore_blocksize: default_docstore_blocksize(),
             docstore_compress_dedicated_thread: true,
+            ingest_metadata: false,
+            near_duplicate_signature: None,
+            field_presence: false,
+            routing_field: None,
+            max_boolean_clauses: None,
         }
     }
 }
@@ -436,6 +578,11 @@ mod tests {
                 }),
                 docstore_blocksize: 1_000_000,
                 docstore_compress_dedicated_thread: true,
+                ingest_metadata: false,
+                near_duplicate_signature: None,
+                field_presence: false,
+                routing_field: None,
+                max_boolean_clauses: None,
             },
             segments: Vec::new(),
             schema,
@@ -499,7 +646,12 @@ mod tests {
             IndexSettings {
                 docstore_compression: Compressor::default(),
                 docstore_compress_dedicated_thread: true,
-                docstore_blocksize: 16_384
+                docstore_blocksize: 16_384,
+                ingest_metadata: false,
+                near_duplicate_signature: None,
+                field_presence: false,
+                routing_field: None,
+                max_boolean_clauses: None,
             }
         );
         {
@@ -0,0 +1,311 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::Arc;
+
+use common::ByteCount;
+
+use crate::index::{InvertedIndexReader, SegmentId};
+use crate::schema::Field;
+use crate::space_usage::{PerFieldSpaceUsage, SearcherSpaceUsage};
+use crate::termdict::TermMerger;
+use crate::{Searcher, Term};
+
+/// Exposes statistics about the content of an index, for capacity planning and relevance
+/// debugging.
+///
+/// The statistics are computed over the segments of a [`Searcher`], and include deleted
+/// documents.
+///
+/// Computing the statistics of a field requires a full scan of its term dictionaries, and is
+/// therefore **expensive**.
+///
+/// ```rust
+/// use tantivy::index::IndexInspector;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(50_000_000)?;
+/// index_writer.add_document(doc!(title => "the old man and the sea"))?;
+/// index_writer.add_document(doc!(title => "the sea wolf"))?;
+/// index_writer.commit()?;
+///
+/// let inspector = IndexInspector::new(index.reader()?.searcher());
+/// let field_statistics = inspector.field_statistics(title, 1)?;
+/// assert_eq!(field_statistics.num_unique_terms, 6);
+/// assert_eq!(field_statistics.total_term_freq, 9);
+/// assert_eq!(field_statistics.top_terms[0].term.value().as_str(), Some("sea"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct IndexInspector {
+    searcher: Searcher,
+}
+
+/// Statistics about an indexed field, aggregated over all of the segments.
+#[derive(Clone, Debug)]
+pub struct FieldStatistics {
+    /// The field.
+    pub field: Field,
+    /// Number of distinct terms of the field.
+    pub num_unique_terms: u64,
+    /// Total number of tokens indexed for the field, that is the sum of the term frequencies
+    /// of all of its terms.
+    pub total_term_freq: u64,
+    /// Distribution of the document frequencies of the terms.
+    ///
+    /// The bucket `i` counts the terms whose document frequency is within `[2^i, 2^(i+1))`.
+    pub doc_freq_histogram: Vec<u64>,
+    /// The terms with the highest document frequencies, in decreasing order.
+    ///
+    /// Ties are broken by term order.
+    pub top_terms: Vec<TermStatistics>,
+    /// Statistics of the field in each segment, in the order of
+    /// [`Searcher::segment_readers()`].
+    pub segments: Vec<SegmentFieldStatistics>,
+}
+
+/// Statistics about a term.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TermStatistics {
+    /// The term.
+    pub term: Term,
+    /// Number of documents containing the term.
+    pub doc_freq: u64,
+}
+
+/// Statistics about a field in a given segment.
+#[derive(Clone, Debug)]
+pub struct SegmentFieldStatistics {
+    /// The segment.
+    pub segment_id: SegmentId,
+    /// Number of terms of the field in the segment.
+    pub num_terms: u64,
+    /// Total number of tokens indexed for the field in the segment.
+    pub total_term_freq: u64,
+    /// Space used by the field in the segment.
+    pub space_usage: FieldSpaceUsage,
+}
+
+/// Space used by a field in a segment, per component.
+///
+/// The doc store is not broken down per field, see [`IndexInspector::space_usage()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FieldSpaceUsage {
+    /// Space used by the term dictionary.
+    pub termdict: ByteCount,
+    /// Space used by the postings lists.
+    pub postings: ByteCount,
+    /// Space used by the positions.
+    pub positions: ByteCount,
+    /// Space used by the fast field.
+    pub fast_fields: ByteCount,
+    /// Space used by the fieldnorms.
+    pub fieldnorms: ByteCount,
+}
+
+impl FieldSpaceUsage {
+    /// Total space used by the field.
+    pub fn total(&self) -> ByteCount {
+        self.termdict + self.postings + self.positions + self.fast_fields + self.fieldnorms
+    }
+}
+
+fn field_num_bytes(per_field_space_usage: &PerFieldSpaceUsage, field: Field) -> ByteCount {
+    per_field_space_usage
+        .field_usage(field)
+        .map(|field_usage| field_usage.total())
+        .unwrap_or_default()
+}
+
+impl IndexInspector {
+    /// Creates an inspector for the segments of the given searcher.
+    pub fn new(searcher: Searcher) -> IndexInspector {
+        IndexInspector { searcher }
+    }
+
+    /// Returns the space used by each component of each segment.
+    ///
+    /// Segments are listed in the order of [`Searcher::segment_readers()`].
+    pub fn space_usage(&self) -> crate::Result<SearcherSpaceUsage> {
+        Ok(self.searcher.space_usage()?)
+    }
+
+    /// Computes the statistics of an indexed field, along with its `top_k` most frequent terms.
+    pub fn field_statistics(&self, field: Field, top_k: usize) -> crate::Result<FieldStatistics> {
+        let field_type = self.searcher.schema().get_field_entry(field).field_type();
+        let inverted_indexes: Vec<Arc<InvertedIndexReader>> = self
+            .searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| segment_reader.inverted_index(field))
+            .collect::<crate::Result<_>>()?;
+
+        let mut segments = Vec::with_capacity(inverted_indexes.len());
+        for (segment_reader, inverted_index) in self
+            .searcher
+            .segment_readers()
+            .iter()
+            .zip(&inverted_indexes)
+        {
+            let space_usage = segment_reader.space_usage()?;
+            segments.push(SegmentFieldStatistics {
+                segment_id: segment_reader.segment_id(),
+                num_terms: inverted_index.terms().num_terms() as u64,
+                total_term_freq: inverted_index.total_num_tokens(),
+                space_usage: FieldSpaceUsage {
+                    termdict: field_num_bytes(space_usage.termdict(), field),
+                    postings: field_num_bytes(space_usage.postings(), field),
+                    positions: field_num_bytes(space_usage.positions(), field),
+                    fast_fields: field_num_bytes(space_usage.fast_fields(), field),
+                    fieldnorms: field_num_bytes(space_usage.fieldnorms(), field),
+                },
+            });
+        }
+
+        let term_streams = inverted_indexes
+            .iter()
+            .map(|inverted_index| inverted_index.terms().stream())
+            .collect::<std::io::Result<Vec<_>>>()?;
+        let mut term_merger = TermMerger::new(term_streams);
+        let mut num_unique_terms = 0u64;
+        let mut doc_freq_histogram: Vec<u64> = Vec::new();
+        // Min-heap of the best terms so far. On ties, the greatest term is evicted first.
+        let mut top_terms: BinaryHeap<Reverse<(u64, Reverse<Vec<u8>>)>> = BinaryHeap::new();
+        while term_merger.advance() {
+            num_unique_terms += 1;
+            let doc_freq: u64 = term_merger
+                .current_segment_ords_and_term_infos()
+                .map(|(_, term_info)| u64::from(term_info.doc_freq))
+                .sum();
+            if doc_freq > 0 {
+                let bucket = (u64::BITS - 1 - doc_freq.leading_zeros()) as usize;
+                if doc_freq_histogram.len() <= bucket {
+                    doc_freq_histogram.resize(bucket + 1, 0);
+                }
+                doc_freq_histogram[bucket] += 1;
+            }
+            if top_k == 0 {
+                continue;
+            }
+            if top_terms.len() == top_k {
+                let Some(Reverse((min_doc_freq, _))) = top_terms.peek() else {
+                    continue;
+                };
+                // Terms are visited in increasing order, so the current term loses ties.
+                if doc_freq <= *min_doc_freq {
+                    continue;
+                }
+                top_terms.pop();
+            }
+            top_terms.push(Reverse((doc_freq, Reverse(term_merger.key().to_vec()))));
+        }
+
+        let top_terms = top_terms
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((doc_freq, Reverse(term_bytes)))| {
+                let mut term = Term::with_type_and_field(field_type.value_type(), field);
+                term.append_bytes(&term_bytes);
+                TermStatistics { term, doc_freq }
+            })
+            .collect();
+
+        Ok(FieldStatistics {
+            field,
+            num_unique_terms,
+            total_term_freq: segments
+                .iter()
+                .map(|segment_statistics| segment_statistics.total_term_freq)
+                .sum(),
+            doc_freq_histogram,
+            top_terms,
+            segments,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IndexInspector;
+    use crate::schema::{Schema, FAST, INDEXED, STORED, TEXT};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_index_inspector_field_statistics() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let num = schema_builder.add_u64_field("num", INDEXED | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(body => "a b c", num => 1u64))?;
+        index_writer.add_document(doc!(body => "a b", num => 1u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(body => "a d d d", num => 2u64))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let inspector = IndexInspector::new(searcher.clone());
+
+        let body_statistics = inspector.field_statistics(body, 2)?;
+        assert_eq!(body_statistics.num_unique_terms, 4);
+        assert_eq!(body_statistics.total_term_freq, 9);
+        // a: 3, b: 2, c: 1, d: 1
+        assert_eq!(body_statistics.doc_freq_histogram, vec![2, 2]);
+        let top_terms: Vec<(Option<String>, u64)> = body_statistics
+            .top_terms
+            .iter()
+            .map(|term_statistics| {
+                (
+                    term_statistics.term.value().as_str().map(str::to_string),
+                    term_statistics.doc_freq,
+                )
+            })
+            .collect();
+        assert_eq!(
+            top_terms,
+            vec![(Some("a".to_string()), 3), (Some("b".to_string()), 2)]
+        );
+        assert_eq!(body_statistics.segments.len(), 2);
+        let num_terms: Vec<u64> = body_statistics
+            .segments
+            .iter()
+            .map(|segment_statistics| segment_statistics.num_terms)
+            .collect();
+        assert!(num_terms == vec![3, 2] || num_terms == vec![2, 3]);
+        for segment_statistics in &body_statistics.segments {
+            let space_usage = segment_statistics.space_usage;
+            assert!(space_usage.termdict.get_bytes() > 0);
+            assert!(space_usage.postings.get_bytes() > 0);
+            assert!(space_usage.positions.get_bytes() > 0);
+            assert_eq!(space_usage.fast_fields.get_bytes(), 0);
+        }
+
+        let num_statistics = inspector.field_statistics(num, 10)?;
+        assert_eq!(num_statistics.num_unique_terms, 2);
+        assert_eq!(
+            num_statistics.top_terms[0].term,
+            Term::from_field_u64(num, 1u64)
+        );
+        assert_eq!(num_statistics.top_terms[0].doc_freq, 2);
+        assert!(
+            num_statistics.segments[0]
+                .space_usage
+                .fast_fields
+                .get_bytes()
+                > 0
+        );
+
+        assert!(inspector.field_statistics(body, 0)?.top_terms.is_empty());
+        assert_eq!(
+            inspector.space_usage()?.total(),
+            searcher.space_usage()?.total()
+        );
+        Ok(())
+    }
+}
@@ -1,10 +1,12 @@
 use std::io;
+use std::ops::Range;
+use std::sync::Arc;
 
 use common::json_path_writer::JSON_END_OF_PATH;
-use common::BinarySerializable;
+use common::{BinarySerializable, HasLen, OwnedBytes};
 use fnv::FnvHashSet;
 #[cfg(feature = "quickwit")]
-use futures_util::{FutureExt, StreamExt, TryStreamExt};
+use futures_util::{StreamExt, TryStreamExt};
 #[cfg(feature = "quickwit")]
 use itertools::Itertools;
 #[cfg(feature = "quickwit")]
@@ -13,9 +15,56 @@ use tantivy_fst::automaton::{AlwaysMatch, Automaton};
 use crate::directory::FileSlice;
 use crate::positions::PositionReader;
 use crate::postings::{BlockSegmentPostings, SegmentPostings, TermInfo};
-use crate::schema::{IndexRecordOption, Term, Type};
+use crate::schema::{IndexRecordOption, PostingsCodec, Term, Type};
+use crate::termdict::bloom_filter::BloomFilter;
 use crate::termdict::TermDictionary;
 
+/// Checks that a range read from the term dictionary fits in the file it points to.
+fn check_range(file_slice: &FileSlice, range: &Range<usize>, file_name: &str) -> io::Result<()> {
+    if range.start > range.end || range.end > file_slice.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "The {file_name} range {range:?} exceeds the {file_name} file ({} bytes).",
+                file_slice.len()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Reads of ranges of a file separated by less than this number of bytes are merged into a
+/// single read.
+const MERGE_READS_UNDER_GAP_BYTES: usize = 16_384;
+
+/// Reads several ranges of a file, merging the reads of neighbouring ranges.
+///
+/// Returns the bytes of the ranges, in the order of the ranges.
+fn read_ranges(file_slice: &FileSlice, ranges: &[Range<usize>]) -> io::Result<Vec<OwnedBytes>> {
+    let mut range_ords: Vec<usize> = (0..ranges.len()).collect();
+    range_ords.sort_by_key(|&range_ord| ranges[range_ord].start);
+    let mut range_bytes = vec![OwnedBytes::empty(); ranges.len()];
+    let mut group_start = 0;
+    while group_start < range_ords.len() {
+        let start = ranges[range_ords[group_start]].start;
+        let mut end = ranges[range_ords[group_start]].end;
+        let mut group_end = group_start + 1;
+        while group_end < range_ords.len()
+            && ranges[range_ords[group_end]].start <= end + MERGE_READS_UNDER_GAP_BYTES
+        {
+            end = end.max(ranges[range_ords[group_end]].end);
+            group_end += 1;
+        }
+        let group_bytes = file_slice.read_bytes_slice(start..end)?;
+        for &range_ord in &range_ords[group_start..group_end] {
+            let range = &ranges[range_ord];
+            range_bytes[range_ord] = group_bytes.slice(range.start - start..range.end - start);
+        }
+        group_start = group_end;
+    }
+    Ok(range_bytes)
+}
+
 /// The inverted index reader is in charge of accessing
 /// the inverted index associated with a specific field.
 ///
@@ -33,6 +82,9 @@ pub struct InvertedIndexReader {
     postings_file_slice: FileSlice,
     positions_file_slice: FileSlice,
     record_option: IndexRecordOption,
+    postings_codec: PostingsCodec,
+    has_positions_checkpoints: bool,
+    bloom_filter_opt: Option<BloomFilter>,
     total_num_tokens: u64,
 }
 
@@ -42,7 +94,16 @@ impl InvertedIndexReader {
         postings_file_slice: FileSlice,
         positions_file_slice: FileSlice,
         record_option: IndexRecordOption,
+        postings_codec: PostingsCodec,
+        has_positions_checkpoints: bool,
+        bloom_filter_opt: Option<BloomFilter>,
     ) -> io::Result<InvertedIndexReader> {
+        if postings_file_slice.len() < 8 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "The postings file is truncated.",
+            ));
+        }
         let (total_num_tokens_slice, postings_body) = postings_file_slice.split(8);
         let total_num_tokens = u64::deserialize(&mut total_num_tokens_slice.read_bytes()?)?;
         Ok(InvertedIndexReader {
@@ -50,6 +111,9 @@ impl InvertedIndexReader {
             postings_file_slice: postings_body,
             positions_file_slice,
             record_option,
+            postings_codec,
+            has_positions_checkpoints,
+            bloom_filter_opt,
             total_num_tokens,
         })
     }
@@ -62,15 +126,33 @@ impl InvertedIndexReader {
             postings_file_slice: FileSlice::empty(),
             positions_file_slice: FileSlice::empty(),
             record_option,
+            postings_codec: PostingsCodec::Block,
+            has_positions_checkpoints: false,
+            bloom_filter_opt: None,
             total_num_tokens: 0u64,
         }
     }
 
     /// Returns the term info associated with the term.
     pub fn get_term_info(&self, term: &Term) -> io::Result<Option<TermInfo>> {
+        if !self.may_contain_term(term) {
+            return Ok(None);
+        }
         self.termdict.get(term.serialized_value_bytes())
     }
 
+    /// Returns false if the term is definitely not part of the inverted index, without looking
+    /// it up in the term dictionary.
+    ///
+    /// This always returns true, unless the field has a
+    /// [bloom filter](crate::schema::TextFieldIndexing::set_bloom_filter). A bloom filter has
+    /// false positives: the term may be missing even if this returns true.
+    pub fn may_contain_term(&self, term: &Term) -> bool {
+        self.bloom_filter_opt.as_ref().map_or(true, |bloom_filter| {
+            bloom_filter.may_contain(term.serialized_value_bytes())
+        })
+    }
+
     /// Return the term dictionary datastructure.
     pub fn terms(&self) -> &TermDictionary {
         &self.termdict
@@ -89,7 +171,15 @@ impl InvertedIndexReader {
             if let Some(index) = term.iter().position(|&byte| byte == JSON_END_OF_PATH) {
                 if !fields_set.contains(&term[..index + 2]) {
                     fields_set.insert(term[..index + 2].to_vec());
-                    let typ = Type::from_code(term[index + 1]).unwrap();
+                    let typ = term
+                        .get(index + 1)
+                        .and_then(|&code| Type::from_code(code))
+                        .ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidData,
+                                "Invalid type code in JSON term.",
+                            )
+                        })?;
                     fields.push((String::from_utf8_lossy(&term[..index]).to_string(), typ));
                 }
             }
@@ -113,9 +203,7 @@ impl InvertedIndexReader {
         term_info: &TermInfo,
         block_postings: &mut BlockSegmentPostings,
     ) -> io::Result<()> {
-        let postings_slice = self
-            .postings_file_slice
-            .slice(term_info.postings_range.clone());
+        let postings_slice = self.postings_slice(term_info)?;
         let postings_bytes = postings_slice.read_bytes()?;
         block_postings.reset(term_info.doc_freq, postings_bytes)?;
         Ok(())
@@ -144,12 +232,11 @@ impl InvertedIndexReader {
         term_info: &TermInfo,
         requested_option: IndexRecordOption,
     ) -> io::Result<BlockSegmentPostings> {
-        let postings_data = self
-            .postings_file_slice
-            .slice(term_info.postings_range.clone());
+        let postings_data = self.postings_slice(term_info)?;
         BlockSegmentPostings::open(
             term_info.doc_freq,
             postings_data,
+            self.postings_codec,
             self.record_option,
             requested_option,
         )
@@ -169,10 +256,15 @@ impl InvertedIndexReader {
         let block_postings = self.read_block_postings_from_terminfo(term_info, option)?;
         let position_reader = {
             if option.has_positions() {
+                check_range(
+                    &self.positions_file_slice,
+                    &term_info.positions_range,
+                    "positions",
+                )?;
                 let positions_data = self
                     .positions_file_slice
                     .read_bytes_slice(term_info.positions_range.clone())?;
-                let position_reader = PositionReader::open(positions_data)?;
+                let position_reader = self.open_position_reader(positions_data)?;
                 Some(position_reader)
             } else {
                 None
@@ -184,6 +276,86 @@ impl InvertedIndexReader {
         ))
     }
 
+    /// Returns the posting objects of several `term_info`s.
+    ///
+    /// The postings (and positions) of the terms are read together: the reads of the ranges of
+    /// the file that are close to each other are merged, which saves random reads on disk-bound
+    /// indexes. This is especially efficient for terms that are close in the dictionary.
+    ///
+    /// This method is for an advanced usage only.
+    /// Most users should prefer using [`Self::read_postings_many()`] instead.
+    pub fn read_postings_many_from_terminfos(
+        &self,
+        term_infos: &[TermInfo],
+        option: IndexRecordOption,
+    ) -> io::Result<Vec<SegmentPostings>> {
+        let option = option.downgrade(self.record_option);
+        let mut postings_ranges = Vec::with_capacity(term_infos.len());
+        let mut positions_ranges = Vec::new();
+        for term_info in term_infos {
+            check_range(
+                &self.postings_file_slice,
+                &term_info.postings_range,
+                "postings",
+            )?;
+            postings_ranges.push(term_info.postings_range.clone());
+            if option.has_positions() {
+                check_range(
+                    &self.positions_file_slice,
+                    &term_info.positions_range,
+                    "positions",
+                )?;
+                positions_ranges.push(term_info.positions_range.clone());
+            }
+        }
+        let postings_data = read_ranges(&self.postings_file_slice, &postings_ranges)?;
+        let mut positions_data = read_ranges(&self.positions_file_slice, &positions_ranges)?
+            .into_iter()
+            .map(Some)
+            .chain(std::iter::repeat_with(|| None));
+        term_infos
+            .iter()
+            .zip(postings_data)
+            .map(|(term_info, postings_bytes)| {
+                let block_postings = BlockSegmentPostings::open(
+                    term_info.doc_freq,
+                    FileSlice::new(Arc::new(postings_bytes)),
+                    self.postings_codec,
+                    self.record_option,
+                    option,
+                )?;
+                let position_reader = positions_data
+                    .next()
+                    .flatten()
+                    .map(|positions_data| self.open_position_reader(positions_data))
+                    .transpose()?;
+                Ok(SegmentPostings::from_block_postings(
+                    block_postings,
+                    position_reader,
+                ))
+            })
+            .collect()
+    }
+
+    fn open_position_reader(&self, positions_data: OwnedBytes) -> io::Result<PositionReader> {
+        if self.has_positions_checkpoints {
+            PositionReader::open_with_checkpoints(positions_data)
+        } else {
+            PositionReader::open(positions_data)
+        }
+    }
+
+    fn postings_slice(&self, term_info: &TermInfo) -> io::Result<FileSlice> {
+        check_range(
+            &self.postings_file_slice,
+            &term_info.postings_range,
+            "postings",
+        )?;
+        Ok(self
+            .postings_file_slice
+            .slice(term_info.postings_range.clone()))
+    }
+
     /// Returns the total number of tokens recorded for all documents
     /// (including deleted documents).
     pub fn total_num_tokens(&self) -> u64 {
@@ -210,6 +382,31 @@ impl InvertedIndexReader {
             .transpose()
     }
 
+    /// Returns the segment postings associated with each of the terms, and with the given
+    /// option, or `None` for the terms that have never been encountered and indexed.
+    ///
+    /// The dictionary entries of the terms are resolved in a single sorted pass, and their
+    /// postings are read together. See [`Self::read_postings_many_from_terminfos()`].
+    pub fn read_postings_many(
+        &self,
+        terms: &[Term],
+        option: IndexRecordOption,
+    ) -> io::Result<Vec<Option<SegmentPostings>>> {
+        let keys: Vec<&[u8]> = terms
+            .iter()
+            .map(|term| term.serialized_value_bytes())
+            .collect();
+        let term_info_opts = self.termdict.get_many(&keys)?;
+        let term_infos: Vec<TermInfo> = term_info_opts.iter().flatten().cloned().collect();
+        let mut postings = self
+            .read_postings_many_from_terminfos(&term_infos, option)?
+            .into_iter();
+        Ok(term_info_opts
+            .iter()
+            .map(|term_info_opt| term_info_opt.as_ref().and_then(|_| postings.next()))
+            .collect())
+    }
+
     /// Returns the number of documents containing the term.
     pub fn doc_freq(&self, term: &Term) -> io::Result<u32> {
         Ok(self
@@ -222,6 +419,9 @@ impl InvertedIndexReader {
 #[cfg(feature = "quickwit")]
 impl InvertedIndexReader {
     pub(crate) async fn get_term_info_async(&self, term: &Term) -> io::Result<Option<TermInfo>> {
+        if !self.may_contain_term(term) {
+            return Ok(None);
+        }
         self.termdict.get_async(term.serialized_value_bytes()).await
     }
 
@@ -274,6 +474,18 @@ impl InvertedIndexReader {
     pub async fn warm_postings(&self, term: &Term, with_positions: bool) -> io::Result<bool> {
         let term_info_opt: Option<TermInfo> = self.get_term_info_async(term).await?;
         if let Some(term_info) = term_info_opt {
+            check_range(
+                &self.postings_file_slice,
+                &term_info.postings_range,
+                "postings",
+            )?;
+            if with_positions {
+                check_range(
+                    &self.positions_file_slice,
+                    &term_info.positions_range,
+                    "positions",
+                )?;
+            }
             let postings = self
                 .postings_file_slice
                 .read_bytes_slice_async(term_info.postings_range.clone());
@@ -315,6 +527,10 @@ impl InvertedIndexReader {
         let postings_range = first_terminfo.postings_range.start..last_terminfo.postings_range.end;
         let positions_range =
             first_terminfo.positions_range.start..last_terminfo.positions_range.end;
+        check_range(&self.postings_file_slice, &postings_range, "postings")?;
+        if with_positions {
+            check_range(&self.positions_file_slice, &positions_range, "positions")?;
+        }
 
         let postings = self
             .postings_file_slice
@@ -394,10 +610,12 @@ impl InvertedIndexReader {
         let task_handle = executor(Box::new(cpu_bound_task));
 
         let posting_downloader = posting_ranges_to_load_stream
-            .map(|posting_slice| {
+            .map(|posting_slice| async move {
+                check_range(&self.postings_file_slice, &posting_slice, "postings")?;
                 self.postings_file_slice
                     .read_bytes_slice_async(posting_slice)
-                    .map(|result| result.map(|_slice| ()))
+                    .await
+                    .map(|_slice| ())
             })
             .buffer_unordered(5)
             .try_collect::<Vec<()>>();
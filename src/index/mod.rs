@@ -2,19 +2,33 @@
 //!
 //! It contains `Index` and `Segment`, where a `Index` consists of one or more `Segment`s.
 
+mod backup;
+mod health;
 mod index;
 mod index_meta;
+mod inspector;
 mod inverted_index_reader;
+mod replica_syncer;
 mod segment;
 mod segment_component;
 mod segment_id;
 mod segment_reader;
+mod settings_preset;
 
+pub use self::backup::{BackupFile, BackupManifest, BackupReport, BackupTarget};
+pub use self::health::{HealthCheckSettings, HealthIssue, HealthReport, SegmentHealth};
 pub use self::index::{Index, IndexBuilder};
 pub(crate) use self::index_meta::SegmentMetaInventory;
-pub use self::index_meta::{IndexMeta, IndexSettings, Order, SegmentMeta};
+pub use self::index_meta::{
+    IndexMeta, IndexSettings, Order, PartitionKey, SegmentMeta, SegmentPartition,
+};
+pub use self::inspector::{
+    FieldSpaceUsage, FieldStatistics, IndexInspector, SegmentFieldStatistics, TermStatistics,
+};
 pub use self::inverted_index_reader::InvertedIndexReader;
+pub use self::replica_syncer::{ReplicaSyncer, ReplicationMetrics, SyncReport};
 pub use self::segment::Segment;
 pub use self::segment_component::SegmentComponent;
 pub use self::segment_id::SegmentId;
 pub use self::segment_reader::{FieldMetadata, SegmentReader};
+pub use self::settings_preset::{PresetSettings, SettingsPreset};
@@ -0,0 +1,372 @@
+//! Warm standby replicas, kept up to date by applying the commits of a primary index.
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::core::META_FILEPATH;
+use crate::directory::error::{DeleteError, OpenReadError};
+use crate::directory::{Directory, TerminatingWrite, META_LOCK};
+use crate::index::{IndexMeta, SegmentComponent, SegmentMeta, SegmentMetaInventory};
+use crate::{Opstamp, TantivyError};
+
+/// Replication metrics of a [`ReplicaSyncer`].
+#[derive(Clone, Debug, Default)]
+pub struct ReplicationMetrics {
+    /// Opstamp of the last commit of the primary seen by the syncer.
+    pub primary_opstamp: Option<Opstamp>,
+    /// Opstamp of the commit the replica is at.
+    pub replica_opstamp: Option<Opstamp>,
+    /// Number of commits applied to the replica.
+    pub num_commits_applied: u64,
+    /// Number of files copied from the primary.
+    pub num_files_copied: u64,
+    /// Number of bytes copied from the primary.
+    pub num_bytes_copied: u64,
+    /// Last time the replica was found to be up to date with the primary.
+    pub last_in_sync: Option<Instant>,
+}
+
+impl ReplicationMetrics {
+    /// Returns the number of operations the replica is behind the last commit seen on the
+    /// primary.
+    pub fn opstamp_lag(&self) -> u64 {
+        self.primary_opstamp
+            .unwrap_or(0)
+            .saturating_sub(self.replica_opstamp.unwrap_or(0))
+    }
+
+    /// Returns the time elapsed since the replica was last found to be up to date with the
+    /// primary, or `None` if it never was.
+    pub fn time_lag(&self) -> Option<Duration> {
+        self.last_in_sync.map(|last_in_sync| last_in_sync.elapsed())
+    }
+}
+
+/// Outcome of a [`ReplicaSyncer::sync()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyncReport {
+    /// Opstamp of the commit the replica is at after the sync.
+    pub opstamp: Opstamp,
+    /// Number of files copied from the primary.
+    pub num_files_copied: usize,
+    /// Number of bytes copied from the primary.
+    pub num_bytes_copied: u64,
+}
+
+struct SyncState {
+    replica_opstamp: Option<Opstamp>,
+    // Files of the commit the replica is at. They are known to be complete.
+    replica_files: HashSet<PathBuf>,
+    // Files of former commits which could not be deleted yet.
+    pending_deletes: HashSet<PathBuf>,
+    metrics: ReplicationMetrics,
+}
+
+/// Keeps a read replica of an index up to date with its primary.
+///
+/// On each [`sync()`](ReplicaSyncer::sync), the syncer reads the `meta.json` manifest of the
+/// primary directory, copies the segment files and delete bitsets the replica does not have yet,
+/// and then atomically switches the `meta.json` of the replica. As segment files are immutable,
+/// and delete bitsets are written in a new file on every commit, only the files created since the
+/// last sync are copied. The files which are not used anymore are removed from the replica
+/// afterwards.
+///
+/// The manifest can also be pushed by the primary, with
+/// [`apply_manifest()`](ReplicaSyncer::apply_manifest).
+///
+/// The replica can be searched as a regular index, with
+/// [`Index::open()`](crate::Index::open). Its readers pick up the new commits like they would on
+/// the primary. It should not be written to.
+///
+/// Both directories should be raw directories, e.g.
+/// [`MmapDirectory`](crate::directory::MmapDirectory)
+/// or [`RamDirectory`](crate::directory::RamDirectory), so that files are copied as is.
+///
+/// ```rust
+/// use tantivy::directory::RamDirectory;
+/// use tantivy::index::ReplicaSyncer;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let primary_directory = RamDirectory::create();
+/// let primary = Index::create(
+///     primary_directory.clone(),
+///     schema_builder.build(),
+///     Default::default(),
+/// )?;
+/// let mut index_writer: IndexWriter = primary.writer(15_000_000)?;
+/// index_writer.add_document(doc!(title => "The Old Man and the Sea"))?;
+/// index_writer.commit()?;
+///
+/// let replica_directory = RamDirectory::create();
+/// let syncer = ReplicaSyncer::new(primary_directory, replica_directory.clone())?;
+/// syncer.sync()?;
+/// assert_eq!(syncer.metrics().opstamp_lag(), 0);
+///
+/// let replica = Index::open(replica_directory)?;
+/// assert_eq!(replica.reader()?.searcher().num_docs(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct ReplicaSyncer {
+    primary: Box<dyn Directory>,
+    replica: Box<dyn Directory>,
+    state: Mutex<SyncState>,
+}
+
+/// Returns the files required by the segment.
+pub(super) fn segment_files(segment_meta: &SegmentMeta) -> impl Iterator<Item = PathBuf> + '_ {
+    SegmentComponent::iterator()
+        .filter(|component| match component {
+            SegmentComponent::TempStore => false,
+            SegmentComponent::Delete => segment_meta.has_deletes(),
+            _ => true,
+        })
+        .map(|&component| segment_meta.relative_path(component))
+}
+
+pub(super) fn parse_manifest(meta_json: &[u8]) -> crate::Result<IndexMeta> {
+    let meta_string = std::str::from_utf8(meta_json).map_err(|_| {
+        TantivyError::InvalidArgument("Manifest does not contain valid utf8.".into())
+    })?;
+    Ok(IndexMeta::deserialize(
+        meta_string,
+        &SegmentMetaInventory::default(),
+    )?)
+}
+
+impl ReplicaSyncer {
+    /// Creates a syncer copying the commits of the index in `primary` to `replica`.
+    ///
+    /// The replica directory may be empty, or hold a replica created by a previous syncer.
+    pub fn new<P: Into<Box<dyn Directory>>, R: Into<Box<dyn Directory>>>(
+        primary: P,
+        replica: R,
+    ) -> crate::Result<ReplicaSyncer> {
+        let replica = replica.into();
+        let (replica_opstamp, replica_files) = match replica.atomic_read(&META_FILEPATH) {
+            Ok(meta_json) => {
+                let index_meta = parse_manifest(&meta_json)?;
+                let replica_files = index_meta.segments.iter().flat_map(segment_files).collect();
+                (Some(index_meta.opstamp), replica_files)
+            }
+            Err(OpenReadError::FileDoesNotExist(_)) => (None, HashSet::new()),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(ReplicaSyncer {
+            primary: primary.into(),
+            replica,
+            state: Mutex::new(SyncState {
+                replica_opstamp,
+                replica_files,
+                pending_deletes: HashSet::new(),
+                metrics: ReplicationMetrics {
+                    replica_opstamp,
+                    ..Default::default()
+                },
+            }),
+        })
+    }
+
+    /// Reads the manifest of the last commit of the primary, and applies it to the replica.
+    ///
+    /// If the primary garbage collects the files of that commit while they are being copied,
+    /// an error is returned and the sync can simply be retried.
+    pub fn sync(&self) -> crate::Result<SyncReport> {
+        let meta_json = self.primary.atomic_read(&META_FILEPATH)?;
+        self.apply_manifest(&meta_json)
+    }
+
+    /// Applies a commit manifest, i.e. the content of the `meta.json` file of the primary, to the
+    /// replica.
+    ///
+    /// The files of the commit are copied from the primary directory.
+    pub fn apply_manifest(&self, meta_json: &[u8]) -> crate::Result<SyncReport> {
+        let index_meta = parse_manifest(meta_json)?;
+        let mut state = self.state.lock().unwrap();
+        state.metrics.primary_opstamp = Some(index_meta.opstamp);
+        let mut report = SyncReport {
+            opstamp: index_meta.opstamp,
+            num_files_copied: 0,
+            num_bytes_copied: 0,
+        };
+        let files: HashSet<PathBuf> = index_meta.segments.iter().flat_map(segment_files).collect();
+        // Merges change the segments without changing the opstamp.
+        if state.replica_opstamp == Some(index_meta.opstamp) && state.replica_files == files {
+            self.mark_in_sync(&mut state);
+            return Ok(report);
+        }
+        for path in &files {
+            // Files which are not part of the current commit of the replica may have been left
+            // incomplete by a failed sync.
+            if state.replica_files.contains(path) {
+                continue;
+            }
+            report.num_bytes_copied += self.copy_file(path)?;
+            report.num_files_copied += 1;
+        }
+        self.replica.atomic_write(&META_FILEPATH, meta_json)?;
+
+        let former_files = std::mem::replace(&mut state.replica_files, files);
+        let obsolete_files: Vec<PathBuf> = former_files
+            .difference(&state.replica_files)
+            .cloned()
+            .collect();
+        state.pending_deletes.extend(obsolete_files);
+        state.replica_opstamp = Some(index_meta.opstamp);
+        state.metrics.replica_opstamp = Some(index_meta.opstamp);
+        state.metrics.num_commits_applied += 1;
+        state.metrics.num_files_copied += report.num_files_copied as u64;
+        state.metrics.num_bytes_copied += report.num_bytes_copied;
+        self.mark_in_sync(&mut state);
+        self.delete_obsolete_files(&mut state);
+        Ok(report)
+    }
+
+    /// Returns the replication metrics.
+    pub fn metrics(&self) -> ReplicationMetrics {
+        self.state.lock().unwrap().metrics.clone()
+    }
+
+    fn mark_in_sync(&self, state: &mut SyncState) {
+        if state.metrics.opstamp_lag() == 0 {
+            state.metrics.last_in_sync = Some(Instant::now());
+        }
+    }
+
+    fn copy_file(&self, path: &Path) -> crate::Result<u64> {
+        let bytes = self.primary.open_read(path)?.read_bytes()?;
+        if self.replica.exists(path)? {
+            self.replica
+                .delete(path)
+                .map_err(|err| TantivyError::SystemError(err.to_string()))?;
+        }
+        let mut writer = self.replica.open_write(path)?;
+        writer.write_all(bytes.as_slice())?;
+        writer.terminate()?;
+        Ok(bytes.len() as u64)
+    }
+
+    fn delete_obsolete_files(&self, state: &mut SyncState) {
+        // Prevents the files from getting deleted while a reader of the replica is opening them.
+        let _meta_lock = match self.replica.acquire_lock(&META_LOCK) {
+            Ok(meta_lock) => meta_lock,
+            Err(err) => {
+                warn!("Failed to acquire the meta lock of the replica: {err:?}");
+                return;
+            }
+        };
+        let replica_files = &state.replica_files;
+        state.pending_deletes.retain(|path| {
+            if replica_files.contains(path) {
+                return false;
+            }
+            match self.replica.delete(path) {
+                Ok(()) | Err(DeleteError::FileDoesNotExist(_)) => false,
+                Err(err) => {
+                    warn!("Failed to delete {path:?} from the replica: {err:?}");
+                    true
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReplicaSyncer;
+    use crate::directory::{Directory, RamDirectory};
+    use crate::index::SegmentComponent;
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, STRING};
+    use crate::{Index, IndexWriter, ReloadPolicy, Term};
+
+    #[test]
+    fn test_replica_syncer() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let primary_directory = RamDirectory::create();
+        let primary = Index::create(
+            primary_directory.clone(),
+            schema_builder.build(),
+            Default::default(),
+        )?;
+        let mut index_writer: IndexWriter = primary.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.commit()?;
+
+        let replica_directory = RamDirectory::create();
+        let syncer = ReplicaSyncer::new(primary_directory.clone(), replica_directory.clone())?;
+        assert_eq!(syncer.metrics().replica_opstamp, None);
+        assert!(syncer.sync()?.num_files_copied > 0);
+        let replica = Index::open(replica_directory.clone())?;
+        let reader = replica
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+
+        // Only the new delete bitset is copied.
+        index_writer.delete_term(Term::from_field_text(id, "a"));
+        index_writer.commit()?;
+        let report = syncer.sync()?;
+        assert_eq!(report.num_files_copied, 1);
+        assert_eq!(report.opstamp, primary.load_metas()?.opstamp);
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 1);
+        assert_eq!(syncer.sync()?.num_files_copied, 0);
+
+        // Merged segments are removed from the replica.
+        index_writer.add_document(doc!(id => "c"))?;
+        index_writer.commit()?;
+        syncer.sync()?;
+        let segment_ids = primary.searchable_segment_ids()?;
+        let merged_segment_files = primary.searchable_segments()?[0].meta().list_files();
+        index_writer.merge(&segment_ids).wait()?;
+        assert!(syncer.sync()?.num_files_copied > 0);
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        assert_eq!(searcher.num_docs(), 2);
+        for path in merged_segment_files {
+            assert!(!replica_directory.exists(&path)?);
+        }
+
+        let metrics = syncer.metrics();
+        assert_eq!(metrics.num_commits_applied, 4);
+        assert_eq!(metrics.opstamp_lag(), 0);
+        assert!(metrics.time_lag().is_some());
+
+        // A new syncer resumes from the replica.
+        let syncer = ReplicaSyncer::new(primary_directory.clone(), replica_directory)?;
+        assert_eq!(
+            syncer.metrics().replica_opstamp,
+            Some(primary.load_metas()?.opstamp)
+        );
+        assert_eq!(syncer.sync()?.num_files_copied, 0);
+
+        // The replica stays at its commit if the files of the primary are gone.
+        index_writer.add_document(doc!(id => "d"))?;
+        index_writer.commit()?;
+        let new_segment_meta = primary
+            .searchable_segment_metas()?
+            .into_iter()
+            .find(|segment_meta| segment_meta.num_docs() == 1)
+            .unwrap();
+        primary_directory
+            .delete(&new_segment_meta.relative_path(SegmentComponent::Store))
+            .unwrap();
+        assert!(syncer.sync().is_err());
+        assert!(syncer.metrics().opstamp_lag() > 0);
+        reader.reload()?;
+        assert_eq!(reader.searcher().num_docs(), 2);
+        Ok(())
+    }
+}
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use super::SegmentComponent;
 use crate::directory::error::{OpenReadError, OpenWriteError};
 use crate::directory::{Directory, FileSlice, WritePtr};
+use crate::fastfield::decay::new_decay_reference_timestamp;
 use crate::index::{Index, SegmentId, SegmentMeta};
 use crate::schema::Schema;
 use crate::Opstamp;
@@ -47,9 +48,13 @@ impl Segment {
     /// This method is only used when updating `max_doc` from 0
     /// as we finalize a fresh new segment.
     pub(crate) fn with_max_doc(self, max_doc: u32) -> Segment {
+        let decay_reference_timestamp = new_decay_reference_timestamp(&self.index.schema());
         Segment {
             index: self.index,
-            meta: self.meta.with_max_doc(max_doc),
+            meta: self
+                .meta
+                .with_max_doc(max_doc)
+                .with_decay_reference_timestamp(decay_reference_timestamp),
         }
     }
 
@@ -7,14 +7,16 @@ use fnv::FnvHashMap;
 use itertools::Itertools;
 
 use crate::directory::{CompositeFile, FileSlice};
-use crate::error::DataCorruption;
+use crate::error::{corruption_from_io, DataCorruption};
 use crate::fastfield::{intersect_alive_bitsets, AliveBitSet, FacetReader, FastFieldReaders};
 use crate::fieldnorm::{FieldNormReader, FieldNormReaders};
-use crate::index::{InvertedIndexReader, Segment, SegmentComponent, SegmentId};
+use crate::index::{InvertedIndexReader, Segment, SegmentComponent, SegmentId, SegmentPartition};
 use crate::json_utils::json_path_sep_to_dot;
+use crate::query::Weight;
 use crate::schema::{Field, IndexRecordOption, Schema, Type};
 use crate::space_usage::SegmentSpaceUsage;
 use crate::store::StoreReader;
+use crate::termdict::bloom_filter::BloomFilter;
 use crate::termdict::TermDictionary;
 use crate::{DocId, Opstamp};
 
@@ -34,6 +36,7 @@ pub struct SegmentReader {
 
     segment_id: SegmentId,
     delete_opstamp: Option<Opstamp>,
+    partition: Option<SegmentPartition>,
 
     max_doc: DocId,
     num_docs: DocId,
@@ -46,6 +49,7 @@ pub struct SegmentReader {
 
     store_file: FileSlice,
     alive_bitset_opt: Option<AliveBitSet>,
+    scope: Option<Arc<dyn Weight>>,
     schema: Schema,
 }
 
@@ -150,18 +154,18 @@ impl SegmentReader {
         custom_bitset: Option<AliveBitSet>,
     ) -> crate::Result<SegmentReader> {
         let termdict_file = segment.open_read(SegmentComponent::Terms)?;
-        let termdict_composite = CompositeFile::open(&termdict_file)?;
+        let termdict_composite = CompositeFile::open(&termdict_file).map_err(corruption_from_io)?;
 
         let store_file = segment.open_read(SegmentComponent::Store)?;
 
         crate::fail_point!("SegmentReader::open#middle");
 
         let postings_file = segment.open_read(SegmentComponent::Postings)?;
-        let postings_composite = CompositeFile::open(&postings_file)?;
+        let postings_composite = CompositeFile::open(&postings_file).map_err(corruption_from_io)?;
 
         let positions_composite = {
             if let Ok(positions_file) = segment.open_read(SegmentComponent::Positions) {
-                CompositeFile::open(&positions_file)?
+                CompositeFile::open(&positions_file).map_err(corruption_from_io)?
             } else {
                 CompositeFile::empty()
             }
@@ -170,7 +174,9 @@ impl SegmentReader {
         let schema = segment.schema();
 
         let fast_fields_data = segment.open_read(SegmentComponent::FastFields)?;
-        let fast_fields_readers = FastFieldReaders::open(fast_fields_data, schema.clone())?;
+        let fast_fields_readers = FastFieldReaders::open(fast_fields_data, schema.clone())
+            .map_err(corruption_from_io)?
+            .with_decay_reference_timestamp(segment.meta().decay_reference_timestamp());
         let fieldnorm_data = segment.open_read(SegmentComponent::FieldNorms)?;
         let fieldnorm_readers = FieldNormReaders::open(fieldnorm_data)?;
 
@@ -200,8 +206,10 @@ impl SegmentReader {
             fieldnorm_readers,
             segment_id: segment.id(),
             delete_opstamp: segment.meta().delete_opstamp(),
+            partition: segment.meta().partition().cloned(),
             store_file,
             alive_bitset_opt,
+            scope: None,
             positions_composite,
             schema,
         })
@@ -268,12 +276,29 @@ impl SegmentReader {
             DataCorruption::comment_only(error_msg)
         })?;
 
-        let inv_idx_reader = Arc::new(InvertedIndexReader::new(
-            TermDictionary::open(termdict_file)?,
-            postings_file,
-            positions_file,
-            record_option,
-        )?);
+        // Segments written before the bloom filter of the field was enabled have none.
+        let bloom_filter_opt = if field_type.has_bloom_filter() {
+            self.termdict_composite
+                .open_read_with_idx(field, 1)
+                .map(|bloom_filter_file| BloomFilter::open(bloom_filter_file.read_bytes()?))
+                .transpose()
+                .map_err(corruption_from_io)?
+        } else {
+            None
+        };
+
+        let inv_idx_reader = Arc::new(
+            InvertedIndexReader::new(
+                TermDictionary::open(termdict_file).map_err(corruption_from_io)?,
+                postings_file,
+                positions_file,
+                record_option,
+                field_type.postings_codec(),
+                field_type.positions_checkpoint_interval().is_some(),
+                bloom_filter_opt,
+            )
+            .map_err(corruption_from_io)?,
+        );
 
         // by releasing the lock in between, we may end up opening the inverting index
         // twice, but this is fine.
@@ -392,11 +417,39 @@ impl SegmentReader {
         self.delete_opstamp
     }
 
+    /// Returns the partition of the documents of this segment, if the segment
+    /// only contains documents of a single partition.
+    ///
+    /// See [`SegmentMeta::partition()`](crate::index::SegmentMeta::partition).
+    pub fn partition(&self) -> Option<&SegmentPartition> {
+        self.partition.as_ref()
+    }
+
     /// Returns the bitset representing the alive `DocId`s.
     pub fn alive_bitset(&self) -> Option<&AliveBitSet> {
         self.alive_bitset_opt.as_ref()
     }
 
+    /// Returns the weight matching the documents searches on this segment are restricted to, if
+    /// any.
+    ///
+    /// The segment readers of a [`ScopedSearcher`](crate::ScopedSearcher) are restricted to the
+    /// documents of its tenant. The documents matched by the query of a search are always within
+    /// the scope, but collectors which collect documents regardless of the query, like the
+    /// `global` aggregation, need to restrict themselves to the documents matched by the scope.
+    pub fn scope(&self) -> Option<&dyn Weight> {
+        self.scope.as_deref()
+    }
+
+    /// Returns a copy of the segment reader, with searches restricted to the documents matched
+    /// by `scope`.
+    pub(crate) fn with_scope(&self, scope: Arc<dyn Weight>) -> SegmentReader {
+        SegmentReader {
+            scope: Some(scope),
+            ..self.clone()
+        }
+    }
+
     /// Returns true if the `doc` is marked
     /// as deleted.
     pub fn is_deleted(&self, doc: DocId) -> bool {
@@ -514,9 +567,12 @@ impl fmt::Debug for SegmentReader {
 
 #[cfg(test)]
 mod test {
+    use common::HasLen;
+
     use super::*;
+    use crate::directory::Directory;
     use crate::index::Index;
-    use crate::schema::{SchemaBuilder, Term, STORED, TEXT};
+    use crate::schema::{Schema, SchemaBuilder, Term, STORED, TEXT};
     use crate::IndexWriter;
 
     #[test]
@@ -693,4 +749,29 @@ mod test {
         assert_eq!(vec![0u32, 2u32], docs);
         Ok(())
     }
+
+    #[test]
+    fn test_segment_reader_corrupted_postings() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "hello"))?;
+        index_writer.commit()?;
+        let segment = index.searchable_segments()?.pop().unwrap();
+        let postings_path = segment.meta().relative_path(SegmentComponent::Postings);
+        // The raw file ends with the footer of the directory, after the composite file.
+        let composite_file_len = segment.open_read(SegmentComponent::Postings)?.len();
+        let mut postings_data = index.directory().atomic_read(&postings_path)?;
+        postings_data[composite_file_len - 4..composite_file_len]
+            .copy_from_slice(&u32::MAX.to_le_bytes());
+        index
+            .directory()
+            .atomic_write(&postings_path, &postings_data)?;
+        assert!(matches!(
+            SegmentReader::open(&segment),
+            Err(crate::TantivyError::DataCorruption(_))
+        ));
+        Ok(())
+    }
 }
@@ -0,0 +1,323 @@
+//! Named presets of settings, picking coherent defaults for typical workloads.
+use std::fmt;
+use std::str::FromStr;
+
+use crate::index::IndexSettings;
+use crate::indexer::index_writer::MEMORY_BUDGET_NUM_BYTES_MIN;
+use crate::indexer::{IndexWriterOptions, LogMergePolicy};
+use crate::schema::{NumericOptions, TextOptions, STORED, STRING, TEXT};
+use crate::store::Compressor;
+#[cfg(feature = "zstd-compression")]
+use crate::store::ZstdCompressor;
+use crate::TantivyError;
+
+/// A named preset of settings, for a typical workload.
+///
+/// A preset configures the index settings, the memory budget and threads of the index writer,
+/// its merge policy, and the options fields should be created with, so that they fit together.
+/// [`SettingsPreset::settings()`] returns what the preset chose, which can be inspected and
+/// tweaked before it is used.
+///
+/// ```rust
+/// use tantivy::index::SettingsPreset;
+/// use tantivy::schema::Schema;
+/// use tantivy::{Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let preset_settings = SettingsPreset::EcommerceSearch.settings();
+/// let mut schema_builder = Schema::builder();
+/// schema_builder.add_text_field("title", preset_settings.text_options());
+/// schema_builder.add_text_field("brand", preset_settings.keyword_options());
+/// schema_builder.add_f64_field("price", preset_settings.numeric_options());
+///
+/// let index = Index::builder()
+///     .schema(schema_builder.build())
+///     .settings_preset(SettingsPreset::EcommerceSearch)
+///     .create_in_ram()?;
+/// let index_writer: IndexWriter = index.writer_with_preset(SettingsPreset::EcommerceSearch)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SettingsPreset {
+    /// Append-only ingestion of large volumes of log lines, searched by time range and keywords,
+    /// and whose documents are rarely fetched.
+    Logging,
+    /// Product catalog with frequent updates, low latency full-text search, filters, sorting and
+    /// facet counts.
+    EcommerceSearch,
+    /// Large tables mostly queried through aggregations on their columns.
+    Analytics,
+    /// Small indexes embedded in an application, with a tight memory footprint.
+    Embedded,
+}
+
+impl SettingsPreset {
+    /// All of the presets.
+    pub const ALL: [SettingsPreset; 4] = [
+        SettingsPreset::Logging,
+        SettingsPreset::EcommerceSearch,
+        SettingsPreset::Analytics,
+        SettingsPreset::Embedded,
+    ];
+
+    /// Name of the preset, e.g. `ecommerce_search`.
+    pub fn name(self) -> &'static str {
+        match self {
+            SettingsPreset::Logging => "logging",
+            SettingsPreset::EcommerceSearch => "ecommerce_search",
+            SettingsPreset::Analytics => "analytics",
+            SettingsPreset::Embedded => "embedded",
+        }
+    }
+
+    /// Returns the settings chosen by the preset.
+    pub fn settings(self) -> PresetSettings {
+        match self {
+            SettingsPreset::Logging => PresetSettings {
+                preset: self,
+                index_settings: IndexSettings {
+                    docstore_compression: strong_compressor(3),
+                    docstore_blocksize: 65_536,
+                    ..IndexSettings::default()
+                },
+                memory_budget_per_thread: 128_000_000,
+                num_worker_threads: 4,
+                num_merge_threads: 2,
+                merge_policy: log_merge_policy(10, 20_000_000, 1.0),
+                fast_numeric_fields: true,
+                fast_keyword_fields: true,
+            },
+            SettingsPreset::EcommerceSearch => PresetSettings {
+                preset: self,
+                index_settings: IndexSettings {
+                    docstore_compression: fast_compressor(),
+                    docstore_blocksize: 16_384,
+                    ..IndexSettings::default()
+                },
+                memory_budget_per_thread: 64_000_000,
+                num_worker_threads: 2,
+                num_merge_threads: 4,
+                merge_policy: log_merge_policy(4, 5_000_000, 0.2),
+                fast_numeric_fields: true,
+                fast_keyword_fields: true,
+            },
+            SettingsPreset::Analytics => PresetSettings {
+                preset: self,
+                index_settings: IndexSettings {
+                    docstore_compression: strong_compressor(9),
+                    docstore_blocksize: 262_144,
+                    ..IndexSettings::default()
+                },
+                memory_budget_per_thread: 256_000_000,
+                num_worker_threads: 4,
+                num_merge_threads: 4,
+                merge_policy: log_merge_policy(8, 50_000_000, 0.5),
+                fast_numeric_fields: true,
+                fast_keyword_fields: true,
+            },
+            SettingsPreset::Embedded => PresetSettings {
+                preset: self,
+                index_settings: IndexSettings {
+                    docstore_compression: fast_compressor(),
+                    docstore_blocksize: 16_384,
+                    docstore_compress_dedicated_thread: false,
+                    ..IndexSettings::default()
+                },
+                memory_budget_per_thread: MEMORY_BUDGET_NUM_BYTES_MIN,
+                num_worker_threads: 1,
+                num_merge_threads: 1,
+                merge_policy: log_merge_policy(4, 1_000_000, 0.3),
+                fast_numeric_fields: false,
+                fast_keyword_fields: false,
+            },
+        }
+    }
+}
+
+impl fmt::Display for SettingsPreset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SettingsPreset {
+    type Err = TantivyError;
+
+    fn from_str(name: &str) -> crate::Result<SettingsPreset> {
+        SettingsPreset::ALL
+            .into_iter()
+            .find(|preset| preset.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = SettingsPreset::ALL.iter().map(|p| p.name()).collect();
+                TantivyError::InvalidArgument(
+                    format!("Unknown settings preset {name:?}, expected one of {names:?}").into(),
+                )
+            })
+    }
+}
+
+/// The settings chosen by a [`SettingsPreset`].
+///
+/// The fields are public, so that the settings can be inspected, or adjusted before being
+/// applied with [`IndexBuilder::settings()`](crate::IndexBuilder::settings),
+/// [`PresetSettings::writer_options()`] and
+/// [`IndexWriter::set_merge_policy()`](crate::IndexWriter::set_merge_policy).
+#[derive(Clone, Debug)]
+pub struct PresetSettings {
+    /// The preset the settings were chosen by.
+    pub preset: SettingsPreset,
+    /// Settings of the index, which include the docstore compression.
+    pub index_settings: IndexSettings,
+    /// Memory budget of each indexer thread.
+    pub memory_budget_per_thread: usize,
+    /// Number of indexer threads.
+    pub num_worker_threads: usize,
+    /// Number of merge threads.
+    pub num_merge_threads: usize,
+    /// Merge policy of the index writer.
+    pub merge_policy: LogMergePolicy,
+    /// Whether numeric fields should be fast fields, for range queries, sorting and
+    /// aggregations.
+    pub fast_numeric_fields: bool,
+    /// Whether keyword fields (e.g. tags, identifiers) should be fast fields, for term
+    /// aggregations and sorting.
+    pub fast_keyword_fields: bool,
+}
+
+impl PresetSettings {
+    /// Options of the index writer: memory budget and threads.
+    pub fn writer_options(&self) -> IndexWriterOptions {
+        IndexWriterOptions::builder()
+            .memory_budget_per_thread(self.memory_budget_per_thread)
+            .num_worker_threads(self.num_worker_threads)
+            .num_merge_threads(self.num_merge_threads)
+            .build()
+    }
+
+    /// Options for stored, indexed numeric fields, fast if
+    /// [`PresetSettings::fast_numeric_fields`] is set.
+    pub fn numeric_options(&self) -> NumericOptions {
+        let numeric_options = NumericOptions::default().set_indexed().set_stored();
+        if self.fast_numeric_fields {
+            return numeric_options.set_fast();
+        }
+        numeric_options
+    }
+
+    /// Options for stored keyword fields, indexed as a single token, and fast if
+    /// [`PresetSettings::fast_keyword_fields`] is set.
+    pub fn keyword_options(&self) -> TextOptions {
+        let keyword_options = STRING | STORED;
+        if self.fast_keyword_fields {
+            return keyword_options.set_fast(None);
+        }
+        keyword_options
+    }
+
+    /// Options for stored full-text fields.
+    ///
+    /// Full-text fields are never fast: their tokens are only useful in the inverted index.
+    pub fn text_options(&self) -> TextOptions {
+        TEXT | STORED
+    }
+}
+
+fn log_merge_policy(
+    min_num_segments: usize,
+    max_docs_before_merge: usize,
+    del_docs_ratio_before_merge: f32,
+) -> LogMergePolicy {
+    let mut merge_policy = LogMergePolicy::default();
+    merge_policy.set_min_num_segments(min_num_segments);
+    merge_policy.set_max_docs_before_merge(max_docs_before_merge);
+    merge_policy.set_del_docs_ratio_before_merge(del_docs_ratio_before_merge);
+    merge_policy
+}
+
+/// Compressor favoring the decompression speed, for docstores fetched at search time.
+fn fast_compressor() -> Compressor {
+    #[cfg(feature = "lz4-compression")]
+    {
+        Compressor::Lz4
+    }
+    #[cfg(not(feature = "lz4-compression"))]
+    {
+        Compressor::default()
+    }
+}
+
+/// Compressor favoring the compression ratio, for docstores which are rarely fetched.
+///
+/// Falls back to [`fast_compressor()`] if zstd is not enabled.
+#[cfg_attr(not(feature = "zstd-compression"), allow(unused_variables))]
+fn strong_compressor(compression_level: i32) -> Compressor {
+    #[cfg(feature = "zstd-compression")]
+    {
+        Compressor::Zstd(ZstdCompressor {
+            compression_level: Some(compression_level),
+        })
+    }
+    #[cfg(not(feature = "zstd-compression"))]
+    {
+        fast_compressor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SettingsPreset;
+    use crate::collector::Count;
+    use crate::query::AllQuery;
+    use crate::schema::Schema;
+    use crate::{Index, IndexWriter};
+
+    #[test]
+    fn test_settings_preset_names() {
+        for preset in SettingsPreset::ALL {
+            assert_eq!(preset.name().parse::<SettingsPreset>().unwrap(), preset);
+            assert_eq!(preset.settings().preset, preset);
+        }
+        assert!("unknown".parse::<SettingsPreset>().is_err());
+    }
+
+    #[test]
+    fn test_settings_preset() -> crate::Result<()> {
+        for preset in SettingsPreset::ALL {
+            let preset_settings = preset.settings();
+            let mut schema_builder = Schema::builder();
+            let body = schema_builder.add_text_field("body", preset_settings.text_options());
+            let tag = schema_builder.add_text_field("tag", preset_settings.keyword_options());
+            let num = schema_builder.add_u64_field("num", preset_settings.numeric_options());
+            let schema = schema_builder.build();
+            assert!(!schema.get_field_entry(body).is_fast());
+            assert_eq!(
+                schema.get_field_entry(tag).is_fast(),
+                preset_settings.fast_keyword_fields
+            );
+            assert_eq!(
+                schema.get_field_entry(num).is_fast(),
+                preset_settings.fast_numeric_fields
+            );
+
+            let index = Index::builder()
+                .schema(schema)
+                .settings_preset(preset)
+                .create_in_ram()?;
+            assert_eq!(
+                index.settings().docstore_compression,
+                preset_settings.index_settings.docstore_compression
+            );
+            assert_eq!(
+                index.settings().docstore_blocksize,
+                preset_settings.index_settings.docstore_blocksize
+            );
+            let mut index_writer: IndexWriter = index.writer_with_preset(preset)?;
+            index_writer.add_document(doc!(body => "hello world", tag => "a", num => 1u64))?;
+            index_writer.commit()?;
+            let searcher = index.reader()?.searcher();
+            assert_eq!(searcher.search(&AllQuery, &Count)?, 1);
+        }
+        Ok(())
+    }
+}
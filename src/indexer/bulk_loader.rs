@@ -0,0 +1,372 @@
+use serde::{Deserialize, Serialize};
+
+use super::operation::UserOperation;
+use super::IndexWriter;
+use crate::core::BULK_CHECKPOINT_FILEPATH;
+use crate::directory::error::OpenReadError;
+use crate::directory::Directory;
+use crate::error::TantivyError;
+use crate::schema::document::{Document, ReferenceValue, ReferenceValueLeaf, Value};
+use crate::schema::{Field, FieldType, Term};
+use crate::Opstamp;
+
+/// A checkpoint recorded by the [`BulkLoader`] in the payload of each of its commits.
+///
+/// It ties the state of the index (`opstamp`) to a position in the
+/// source the documents are read from (`source_offset`).
+/// The source offset is opaque to tantivy: it can be a line number, a byte offset,
+/// a message queue position, etc. as long as it increases monotonically.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BulkCheckpoint {
+    /// Opstamp of the commit that recorded this checkpoint.
+    pub opstamp: Opstamp,
+    /// Offset in the source, right after the last document of the committed batch.
+    ///
+    /// Resuming a load should start reading the source from this offset.
+    pub source_offset: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BulkCheckpointPayload {
+    bulk_checkpoint: BulkCheckpoint,
+}
+
+impl BulkCheckpoint {
+    /// Serializes the checkpoint into a commit payload.
+    pub fn to_payload(&self) -> String {
+        let payload = BulkCheckpointPayload {
+            bulk_checkpoint: *self,
+        };
+        serde_json::to_string(&payload).expect("Failed to serialize checkpoint. This is a bug.")
+    }
+
+    /// Extracts a checkpoint from a commit payload.
+    ///
+    /// Returns `None` if the payload was not written by a [`BulkLoader`].
+    pub fn from_payload(payload: &str) -> Option<BulkCheckpoint> {
+        serde_json::from_str::<BulkCheckpointPayload>(payload)
+            .ok()
+            .map(|payload| payload.bulk_checkpoint)
+    }
+}
+
+/// `BulkLoader` loads documents read from an external source into an index,
+/// batch by batch, in a way that can be resumed after an interruption.
+///
+/// Every batch is committed along with a [`BulkCheckpoint`] stored in the commit payload.
+/// The checkpoint is also saved in the `.bulk_checkpoint.json` file of the index directory,
+/// as later commits that do not go through the `BulkLoader` replace the payload.
+/// When a load is interrupted (crash, killed process, ...), creating a new `BulkLoader`
+/// on the same index reads back the last checkpoint and
+/// [`BulkLoader::resume_offset()`] tells where the source should be read from.
+///
+/// If the last commit of the index did not record a checkpoint (for instance if the writer was
+/// committed by some other means while a batch was being indexed), the documents past the
+/// resume offset are "in doubt": some of them might already be part of the index.
+/// There is no telling how far in the source these documents go, so every batch loaded by
+/// such a `BulkLoader` is loaded as a series of upserts keyed by the primary key field:
+/// any document sharing its primary key with a document of the batch is deleted first.
+///
+/// The primary key field is required to be indexed, and for text fields, to use the `raw`
+/// tokenizer, so that a primary key is a single term.
+pub struct BulkLoader<'a, D: Document> {
+    index_writer: &'a mut IndexWriter<D>,
+    primary_key: Field,
+    checkpoint: Option<BulkCheckpoint>,
+    deduplicate: bool,
+}
+
+impl<'a, D: Document> BulkLoader<'a, D> {
+    /// Creates a new `BulkLoader`, picking up the checkpoint recorded in the last commit, if any.
+    ///
+    /// # Errors
+    /// Returns `TantivyError::FieldNotFound` if the primary key field is not part of the
+    /// schema, and `TantivyError::SchemaError` if it is not indexed, or if it is a text field
+    /// that does not use the `raw` tokenizer.
+    pub fn new(
+        index_writer: &'a mut IndexWriter<D>,
+        primary_key: Field,
+    ) -> crate::Result<BulkLoader<'a, D>> {
+        let index = index_writer.index();
+        let schema = index.schema();
+        let field_entry = schema
+            .fields()
+            .nth(primary_key.field_id() as usize)
+            .map(|(_, field_entry)| field_entry)
+            .ok_or_else(|| TantivyError::FieldNotFound(format!("{primary_key:?}")))?;
+        if !field_entry.is_indexed() {
+            return Err(TantivyError::SchemaError(format!(
+                "The primary key field `{}` needs to be indexed.",
+                field_entry.name()
+            )));
+        }
+        if let FieldType::Str(text_options) = field_entry.field_type() {
+            let tokenizer = text_options
+                .get_indexing_options()
+                .map(|indexing_options| indexing_options.tokenizer());
+            if tokenizer != Some("raw") {
+                return Err(TantivyError::SchemaError(format!(
+                    "The primary key field `{}` needs to use the `raw` tokenizer.",
+                    field_entry.name()
+                )));
+            }
+        }
+        let metas = index.load_metas()?;
+        let payload_checkpoint = metas
+            .payload
+            .as_deref()
+            .and_then(BulkCheckpoint::from_payload);
+        // The checkpoint file is written right after the commit: the payload is more recent if
+        // the load was interrupted in between.
+        let checkpoint = load_checkpoint(index.directory())?
+            .into_iter()
+            .chain(payload_checkpoint)
+            .max_by_key(|checkpoint| checkpoint.opstamp);
+        // Documents were committed past the resume offset unless the last commit is the one
+        // that recorded the checkpoint.
+        let last_commit_is_checkpoint =
+            checkpoint.map(|checkpoint| checkpoint.opstamp) == Some(metas.opstamp);
+        let deduplicate = !last_commit_is_checkpoint && !metas.segments.is_empty();
+        Ok(BulkLoader {
+            index_writer,
+            primary_key,
+            checkpoint,
+            deduplicate,
+        })
+    }
+
+    /// Returns the checkpoint of the last committed batch, if any.
+    pub fn last_checkpoint(&self) -> Option<BulkCheckpoint> {
+        self.checkpoint
+    }
+
+    /// Returns the source offset the load should be resumed from.
+    ///
+    /// Returns 0 if no batch was ever committed by a `BulkLoader`.
+    pub fn resume_offset(&self) -> u64 {
+        self.checkpoint
+            .map(|checkpoint| checkpoint.source_offset)
+            .unwrap_or(0u64)
+    }
+
+    /// Indexes and commits a batch of documents.
+    ///
+    /// `source_offset` is the offset in the source right after the last document of
+    /// the batch. It is required to be greater or equal to the offset of the last checkpoint.
+    ///
+    /// Returns the checkpoint recorded in the commit.
+    pub fn load_batch<I>(&mut self, source_offset: u64, docs: I) -> crate::Result<BulkCheckpoint>
+    where I: IntoIterator<Item = D> {
+        if source_offset < self.resume_offset() {
+            return Err(TantivyError::InvalidArgument(
+                format!(
+                    "Source offset {source_offset} is behind the last checkpoint offset {}.",
+                    self.resume_offset()
+                )
+                .into(),
+            ));
+        }
+        let mut operations: Vec<UserOperation<D>> = Vec::new();
+        for doc in docs {
+            let primary_key_term = self.primary_key_term(&doc)?;
+            if self.deduplicate {
+                operations.push(UserOperation::Delete(primary_key_term));
+            }
+            operations.push(UserOperation::Add(doc));
+        }
+        self.index_writer.run(operations)?;
+        let mut prepared_commit = self.index_writer.prepare_commit()?;
+        let checkpoint = BulkCheckpoint {
+            opstamp: prepared_commit.opstamp(),
+            source_offset,
+        };
+        prepared_commit.set_payload(&checkpoint.to_payload());
+        prepared_commit.commit()?;
+        save_checkpoint(self.index_writer.index().directory(), &checkpoint)?;
+        self.checkpoint = Some(checkpoint);
+        Ok(checkpoint)
+    }
+
+    fn primary_key_term(&self, doc: &D) -> crate::Result<Term> {
+        let primary_key = self.primary_key;
+        let mut primary_key_terms = doc
+            .iter_fields_and_values()
+            .filter(|(field, _)| *field == primary_key)
+            .map(|(_, value)| leaf_to_term(primary_key, value.as_value()));
+        let term_opt = primary_key_terms.next();
+        if primary_key_terms.next().is_some() {
+            return Err(TantivyError::InvalidArgument(
+                "Document has more than one value for the primary key field.".into(),
+            ));
+        }
+        term_opt.flatten().ok_or_else(|| {
+            TantivyError::InvalidArgument(
+                "Document is missing a supported primary key value.".into(),
+            )
+        })
+    }
+}
+
+/// Loads the checkpoint saved in the directory, if any.
+fn load_checkpoint(directory: &dyn Directory) -> crate::Result<Option<BulkCheckpoint>> {
+    match directory.atomic_read(&BULK_CHECKPOINT_FILEPATH) {
+        Ok(checkpoint_json) => Ok(Some(serde_json::from_slice(&checkpoint_json)?)),
+        Err(OpenReadError::FileDoesNotExist(_)) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn save_checkpoint(directory: &dyn Directory, checkpoint: &BulkCheckpoint) -> crate::Result<()> {
+    let checkpoint_json = serde_json::to_vec(checkpoint)?;
+    directory.atomic_write(&BULK_CHECKPOINT_FILEPATH, &checkpoint_json)?;
+    Ok(())
+}
+
+fn leaf_to_term<'a, V: Value<'a>>(field: Field, value: ReferenceValue<'a, V>) -> Option<Term> {
+    let term = match value {
+        ReferenceValue::Leaf(ReferenceValueLeaf::Str(text)) => Term::from_field_text(field, text),
+        ReferenceValue::Leaf(ReferenceValueLeaf::U64(val)) => Term::from_field_u64(field, val),
+        ReferenceValue::Leaf(ReferenceValueLeaf::I64(val)) => Term::from_field_i64(field, val),
+        ReferenceValue::Leaf(ReferenceValueLeaf::Bool(val)) => Term::from_field_bool(field, val),
+        ReferenceValue::Leaf(ReferenceValueLeaf::Date(val)) => {
+            Term::from_field_date_for_search(field, val)
+        }
+        ReferenceValue::Leaf(ReferenceValueLeaf::IpAddr(val)) => {
+            Term::from_field_ip_addr(field, val)
+        }
+        ReferenceValue::Leaf(ReferenceValueLeaf::Bytes(bytes)) => {
+            Term::from_field_bytes(field, bytes)
+        }
+        _ => return None,
+    };
+    Some(term)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BulkCheckpoint, BulkLoader};
+    use crate::collector::Count;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::{Index, IndexWriter, TantivyError};
+
+    #[test]
+    fn test_bulk_checkpoint_payload() {
+        let checkpoint = BulkCheckpoint {
+            opstamp: 12,
+            source_offset: 1_000,
+        };
+        let payload = checkpoint.to_payload();
+        assert_eq!(BulkCheckpoint::from_payload(&payload), Some(checkpoint));
+        assert_eq!(BulkCheckpoint::from_payload("my payload"), None);
+    }
+
+    #[test]
+    fn test_bulk_loader_resume() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let source: Vec<String> = (0..10).map(|i| format!("doc{i}")).collect();
+        let make_doc = |i: usize| doc!(id => source[i].clone(), body => "hello");
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            let mut bulk_loader = BulkLoader::new(&mut index_writer, id)?;
+            assert_eq!(bulk_loader.resume_offset(), 0);
+            let checkpoint = bulk_loader.load_batch(4, (0..4).map(make_doc))?;
+            assert_eq!(checkpoint.source_offset, 4);
+            // The next batch gets partially committed without a checkpoint.
+            index_writer.add_document(make_doc(4))?;
+            index_writer.add_document(make_doc(5))?;
+            index_writer.commit()?;
+        }
+        let metas = index.load_metas()?;
+        assert_eq!(metas.payload, None);
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.prepare_commit()?.commit()?;
+            let mut bulk_loader = BulkLoader::new(&mut index_writer, id)?;
+            // The last commit had no checkpoint, but the checkpoint was kept aside.
+            assert_eq!(bulk_loader.resume_offset(), 4);
+            // The documents committed without a checkpoint span more than one batch.
+            bulk_loader.load_batch(5, (4..5).map(make_doc))?;
+            bulk_loader.load_batch(10, (5..10).map(make_doc))?;
+            assert_eq!(bulk_loader.resume_offset(), 10);
+        }
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_loader_resume_from_checkpoint() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", crate::schema::INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            let mut bulk_loader = BulkLoader::new(&mut index_writer, id)?;
+            bulk_loader.load_batch(3, (0..3u64).map(|i| doc!(id => i)))?;
+            // The following batch is indexed but never committed.
+            index_writer.add_document(doc!(id => 3u64))?;
+        }
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let mut bulk_loader = BulkLoader::new(&mut index_writer, id)?;
+        let resume_offset = bulk_loader.resume_offset();
+        assert_eq!(resume_offset, 3);
+        assert!(bulk_loader.load_batch(2, Vec::new()).is_err());
+        bulk_loader.load_batch(5, (resume_offset..5).map(|i| doc!(id => i)))?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_loader_resume_after_plain_commit() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", crate::schema::INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let checkpoint = {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            let checkpoint = {
+                let mut bulk_loader = BulkLoader::new(&mut index_writer, id)?;
+                bulk_loader.load_batch(3, (0..3u64).map(|i| doc!(id => i)))?
+            };
+            index_writer.commit()?;
+            checkpoint
+        };
+        assert_eq!(index.load_metas()?.payload, None);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let bulk_loader = BulkLoader::new(&mut index_writer, id)?;
+        assert_eq!(bulk_loader.last_checkpoint(), Some(checkpoint));
+        assert_eq!(bulk_loader.resume_offset(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_loader_requires_raw_tokenizer() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        assert!(matches!(
+            BulkLoader::new(&mut index_writer, id),
+            Err(TantivyError::SchemaError(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_loader_missing_primary_key() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let mut bulk_loader = BulkLoader::new(&mut index_writer, id)?;
+        assert!(bulk_loader
+            .load_batch(1, vec![doc!(body => "no id")])
+            .is_err());
+        Ok(())
+    }
+}
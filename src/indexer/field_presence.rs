@@ -0,0 +1,142 @@
+//! Field populated by the index writer when
+//! [`IndexSettings::field_presence`](crate::IndexSettings::field_presence) is enabled.
+//!
+//! `_field_presence` is an indexed, untokenized text field. For every document, the index writer
+//! indexes in it the name of each field which is indexed but not fast, and for which the
+//! document has at least one value. The postings of the term `_field_presence:<field name>`
+//! are therefore the documents having a value in that field.
+//!
+//! Fast fields do not need it, as the documents having a value are known from the null index of
+//! their columns. JSON fields are not recorded either, as their presence is usually checked for a
+//! given path.
+//!
+//! [`ExistsQuery`](crate::query::ExistsQuery) and
+//! [`Searcher::num_docs_with_field()`](crate::Searcher::num_docs_with_field) rely on these
+//! postings when available, instead of going through all of the terms of the field.
+//!
+//! Like the other [fields populated by the index writer](super::writer_fields), it is added to
+//! the schema upon index creation.
+use crate::schema::{
+    Field, FieldEntry, FieldType, IndexRecordOption, Schema, Term, TextFieldIndexing, TextOptions,
+};
+use crate::TantivyError;
+
+/// Name of the field holding the names of the fields each document has a value for.
+pub const FIELD_PRESENCE_FIELD_NAME: &str = "_field_presence";
+
+/// Returns the field presence field of a schema, or an error if it is missing or does not have
+/// the expected type.
+pub(crate) fn field_presence_field(schema: &Schema) -> crate::Result<Field> {
+    let field = schema.get_field(FIELD_PRESENCE_FIELD_NAME)?;
+    let field_entry = schema.get_field_entry(field);
+    let is_raw_text = match field_entry.field_type() {
+        FieldType::Str(text_options) => text_options
+            .get_indexing_options()
+            .map(|indexing_options| indexing_options.tokenizer() == "raw")
+            .unwrap_or(false),
+        _ => false,
+    };
+    if !is_raw_text {
+        return Err(TantivyError::SchemaError(format!(
+            "Field presence field {:?} has an unexpected type: {:?}",
+            field_entry.name(),
+            field_entry.field_type()
+        )));
+    }
+    Ok(field)
+}
+
+/// Returns true if the presence of the field is recorded in the field presence field.
+pub(crate) fn is_presence_recorded(field_entry: &FieldEntry) -> bool {
+    field_entry.is_indexed()
+        && !field_entry.is_fast()
+        && !matches!(field_entry.field_type(), FieldType::JsonObject(_))
+        && field_entry.name() != FIELD_PRESENCE_FIELD_NAME
+}
+
+/// Returns the term whose postings are the documents having a value in the given field, if the
+/// schema records the presence of that field.
+pub(crate) fn field_presence_term(schema: &Schema, field: Field) -> Option<Term> {
+    let field_presence_field = field_presence_field(schema).ok()?;
+    let field_entry = schema.get_field_entry(field);
+    if !is_presence_recorded(field_entry) {
+        return None;
+    }
+    Some(Term::from_field_text(
+        field_presence_field,
+        field_entry.name(),
+    ))
+}
+
+/// Returns the entry of the field presence field, as added to the schema.
+pub(crate) fn field_presence_field_entry() -> FieldEntry {
+    FieldEntry::new_text(
+        FIELD_PRESENCE_FIELD_NAME.to_string(),
+        TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer("raw")
+                .set_index_option(IndexRecordOption::Basic)
+                .set_fieldnorms(false),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FIELD_PRESENCE_FIELD_NAME;
+    use crate::collector::Count;
+    use crate::query::ExistsQuery;
+    use crate::schema::{Schema, FAST, TEXT};
+    use crate::{Index, IndexSettings, IndexWriter, Term};
+
+    #[test]
+    fn test_field_presence() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let id = schema_builder.add_u64_field("id", FAST);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                field_presence: true,
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        let field_presence = index.schema().get_field(FIELD_PRESENCE_FIELD_NAME)?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "hello", body => "happy tax payer", id => 1u64))?;
+        // No token is emitted for the title, but the document has a value.
+        index_writer.add_document(doc!(title => "", id => 2u64))?;
+        index_writer.add_document(doc!(body => "hello"))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(
+            searcher.doc_freq(&Term::from_field_text(field_presence, "title"))?,
+            2
+        );
+        assert_eq!(
+            searcher.doc_freq(&Term::from_field_text(field_presence, "body"))?,
+            2
+        );
+        // The presence of fast fields is not recorded.
+        assert_eq!(
+            searcher.doc_freq(&Term::from_field_text(field_presence, "id"))?,
+            0
+        );
+        assert_eq!(
+            searcher.search(&ExistsQuery::new("title".to_string(), false), &Count)?,
+            2
+        );
+        assert_eq!(searcher.num_docs_with_field("title")?, 2);
+        assert_eq!(searcher.num_docs_with_field("id")?, 2);
+
+        index_writer.delete_term(Term::from_field_text(body, "payer"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs_with_field("title")?, 1);
+        assert_eq!(searcher.num_docs_with_field("body")?, 1);
+        assert!(searcher.num_docs_with_field("missing").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,379 @@
+use std::io::Write;
+
+use columnar::ColumnarReader;
+use common::json_path_writer::JSON_PATH_SEGMENT_SEP;
+
+use crate::directory::{CompositeFile, CompositeWrite, Directory, TerminatingWrite};
+use crate::index::{Index, Segment, SegmentComponent, SegmentId};
+use crate::indexer::index_writer::{advance_deletes, MARGIN_IN_BYTES};
+use crate::indexer::operation::AddOperation;
+use crate::indexer::{SegmentEntry, SegmentWriter};
+use crate::schema::Field;
+use crate::{Opstamp, SegmentReader, TantivyDocument, TantivyError};
+
+/// Components of a segment holding one part per field.
+const PER_FIELD_COMPONENTS: [SegmentComponent; 4] = [
+    SegmentComponent::Terms,
+    SegmentComponent::Postings,
+    SegmentComponent::Positions,
+    SegmentComponent::FieldNorms,
+];
+
+/// Checks that `field` can be rebuilt from the doc store.
+pub(crate) fn check_rebuildable_field(index: &Index, field: Field) -> crate::Result<()> {
+    let schema = index.schema();
+    let field_entry = schema.get_field_entry(field);
+    if !field_entry.is_stored() {
+        return Err(TantivyError::InvalidArgument(
+            format!(
+                "The field `{}` is not stored, so that it cannot be rebuilt.",
+                field_entry.name()
+            )
+            .into(),
+        ));
+    }
+    if !field_entry.is_indexed() && !field_entry.is_fast() {
+        return Err(TantivyError::InvalidArgument(
+            format!(
+                "The field `{}` is neither indexed nor fast, so that there is nothing to rebuild.",
+                field_entry.name()
+            )
+            .into(),
+        ));
+    }
+    Ok(())
+}
+
+fn copy_file(
+    source_segment: &Segment,
+    target_segment: &mut Segment,
+    component: SegmentComponent,
+) -> crate::Result<()> {
+    let data = source_segment.open_read(component)?.read_bytes()?;
+    let mut write = target_segment.open_write(component)?;
+    write.write_all(data.as_slice())?;
+    write.terminate()?;
+    Ok(())
+}
+
+/// Writes the component of `target_segment`, copying the parts of `field` from
+/// `rebuilt_segment` and the parts of the other fields from `source_segment`.
+fn copy_composite_file(
+    source_segment: &Segment,
+    rebuilt_segment: &Segment,
+    target_segment: &mut Segment,
+    component: SegmentComponent,
+    field: Field,
+) -> crate::Result<()> {
+    let source_file = CompositeFile::open(&source_segment.open_read(component)?)?;
+    let rebuilt_file = CompositeFile::open(&rebuilt_segment.open_read(component)?)?;
+    let mut composite_write = CompositeWrite::wrap(target_segment.open_write(component)?);
+    for (schema_field, _) in target_segment.schema().fields() {
+        let composite_file = if schema_field == field {
+            &rebuilt_file
+        } else {
+            &source_file
+        };
+        for (idx, file_slice) in composite_file.open_read_all(schema_field) {
+            let data = file_slice.read_bytes()?;
+            composite_write
+                .for_field_with_idx(schema_field, idx)
+                .write_all(data.as_slice())?;
+        }
+    }
+    composite_write.close()?;
+    Ok(())
+}
+
+/// Writes the fast fields of `target_segment`, copying the columns of `field` from
+/// `rebuilt_segment` and the other columns from `source_segment`.
+fn copy_fast_fields(
+    source_segment: &Segment,
+    rebuilt_segment: &Segment,
+    target_segment: &mut Segment,
+    field_name: &str,
+) -> crate::Result<()> {
+    let source_columnar =
+        ColumnarReader::open(source_segment.open_read(SegmentComponent::FastFields)?)?;
+    let rebuilt_columnar =
+        ColumnarReader::open(rebuilt_segment.open_read(SegmentComponent::FastFields)?)?;
+    // The columns of a JSON field are named after their path within the field.
+    let is_column_of_field = |column_name: &str| {
+        column_name
+            .strip_prefix(field_name)
+            .is_some_and(|path| path.is_empty() || path.starts_with(JSON_PATH_SEGMENT_SEP as char))
+    };
+    let mut write = target_segment.open_write(SegmentComponent::FastFields)?;
+    columnar::replace_columns(
+        &source_columnar,
+        &rebuilt_columnar,
+        &is_column_of_field,
+        &mut write,
+    )?;
+    write.terminate()?;
+    Ok(())
+}
+
+/// Indexes the stored values of `field` in a new segment, with the same doc ids as the source
+/// segment.
+///
+/// The values all go to a single segment writer, so that an error is returned if they do not fit
+/// in `memory_budget`.
+fn index_stored_values(
+    index: &Index,
+    segment_reader: &SegmentReader,
+    field: Field,
+    memory_budget: usize,
+) -> crate::Result<Segment> {
+    let rebuilt_segment = index.new_segment();
+    let mut segment_writer = SegmentWriter::for_segment(memory_budget, rebuilt_segment.clone())?;
+    let store_reader = segment_reader.get_store_reader(1)?;
+    // Deleted documents are indexed too, so that doc ids are preserved.
+    for doc_id in 0..segment_reader.max_doc() {
+        let document: TantivyDocument = store_reader.get_fields(doc_id, &[field])?;
+        segment_writer.add_document(AddOperation {
+            opstamp: 0,
+            document,
+        })?;
+        if segment_writer.mem_usage() >= memory_budget - MARGIN_IN_BYTES {
+            return Err(TantivyError::InvalidArgument(
+                format!(
+                    "Rebuilding the field `{}` of segment {} needs more than the memory budget of \
+                     {memory_budget} bytes.",
+                    index.schema().get_field_name(field),
+                    segment_reader.segment_id().uuid_string()
+                )
+                .into(),
+            ));
+        }
+    }
+    segment_writer.finalize()?;
+    Ok(rebuilt_segment.with_max_doc(segment_reader.max_doc()))
+}
+
+/// Rebuilds the inverted index and the fast field columns of `field` in a segment, from the
+/// values of the field in the doc store.
+///
+/// The data of the other fields, the doc store and the deletes are copied as is to the new
+/// segment. The values of the field are indexed within `memory_budget`.
+pub(crate) fn rebuild_field(
+    index: &Index,
+    mut segment_entry: SegmentEntry,
+    target_opstamp: Opstamp,
+    field: Field,
+    memory_budget: usize,
+) -> crate::Result<Option<SegmentEntry>> {
+    let source_segment = index.segment(segment_entry.meta().clone());
+    advance_deletes(source_segment, &mut segment_entry, target_opstamp)?;
+    let source_meta = segment_entry.meta().clone();
+    if source_meta.num_docs() == 0 {
+        return Ok(None);
+    }
+    let source_segment = index.segment(source_meta.clone());
+    let segment_reader = SegmentReader::open(&source_segment)?;
+    let rebuilt_segment = index_stored_values(index, &segment_reader, field, memory_budget)?;
+
+    let mut target_meta = index
+        .new_segment_meta(SegmentId::generate_random(), source_meta.max_doc())
+        .with_partition(source_meta.partition().cloned())
+        .with_decay_reference_timestamp(source_meta.decay_reference_timestamp());
+    if let Some(delete_opstamp) = source_meta.delete_opstamp() {
+        target_meta = target_meta.with_delete_meta(source_meta.num_deleted_docs(), delete_opstamp);
+    }
+    let mut target_segment = index.segment(target_meta.clone());
+    let field_entry = index.schema().get_field_entry(field).clone();
+    for component in PER_FIELD_COMPONENTS {
+        copy_composite_file(
+            &source_segment,
+            &rebuilt_segment,
+            &mut target_segment,
+            component,
+            field,
+        )?;
+    }
+    if field_entry.is_fast() {
+        copy_fast_fields(
+            &source_segment,
+            &rebuilt_segment,
+            &mut target_segment,
+            field_entry.name(),
+        )?;
+    } else {
+        copy_file(
+            &source_segment,
+            &mut target_segment,
+            SegmentComponent::FastFields,
+        )?;
+    }
+    copy_file(
+        &source_segment,
+        &mut target_segment,
+        SegmentComponent::Store,
+    )?;
+    if source_meta.has_deletes() {
+        copy_file(
+            &source_segment,
+            &mut target_segment,
+            SegmentComponent::Delete,
+        )?;
+    }
+
+    // The rebuilt segment is never registered, its files are only kept until the rebuild is
+    // over.
+    for path in rebuilt_segment.meta().list_files() {
+        let _ = index.directory().delete(&path);
+    }
+    let delete_cursor = segment_entry.delete_cursor().clone();
+    Ok(Some(SegmentEntry::new(target_meta, delete_cursor, None)))
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::index_stored_values;
+    use crate::collector::Count;
+    use crate::indexer::index_writer::MEMORY_BUDGET_NUM_BYTES_MIN;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::TermQuery;
+    use crate::schema::{
+        IndexRecordOption, Schema, TextFieldIndexing, TextOptions, FAST, INDEXED, STORED, STRING,
+        TEXT,
+    };
+    use crate::tokenizer::{LowerCaser, SimpleTokenizer, TextAnalyzer};
+    use crate::{Index, IndexWriter, TantivyError, Term};
+
+    #[test]
+    fn test_rebuild_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title_options = TextOptions::default()
+            .set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("custom")
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+            )
+            .set_stored();
+        let title = schema_builder.add_text_field("title", title_options);
+        let tag = schema_builder.add_text_field("tag", STRING | STORED | FAST);
+        let id = schema_builder.add_u64_field("id", INDEXED | FAST);
+        let unstored = schema_builder.add_text_field("unstored", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        index
+            .tokenizers()
+            .register("custom", TextAnalyzer::from(SimpleTokenizer::default()));
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "Hello World", tag => "a", id => 1u64))?;
+        index_writer.add_document(doc!(title => "Hello Moon", tag => "b", id => 2u64))?;
+        index_writer.add_document(doc!(title => "Goodbye", id => 3u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(title => "HELLO", tag => "a", id => 4u64))?;
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_u64(id, 2));
+        index_writer.commit()?;
+
+        let count = |field, text: &str| -> crate::Result<usize> {
+            let searcher = index.reader()?.searcher();
+            let query =
+                TermQuery::new(Term::from_field_text(field, text), IndexRecordOption::Basic);
+            searcher.search(&query, &Count)
+        };
+        assert_eq!(count(title, "hello")?, 0);
+
+        index.tokenizers().register(
+            "custom",
+            TextAnalyzer::builder(SimpleTokenizer::default())
+                .filter(LowerCaser)
+                .build(),
+        );
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 2);
+        for segment_id in segment_ids {
+            index_writer.rebuild_field(segment_id, title).wait()?;
+        }
+        index_writer
+            .rebuild_field(index.searchable_segment_ids()?[0], tag)
+            .wait()?;
+        index_writer.commit()?;
+
+        assert_eq!(count(title, "hello")?, 2);
+        assert_eq!(count(title, "moon")?, 0);
+        assert_eq!(count(tag, "a")?, 2);
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 3);
+        assert_eq!(
+            searcher.search(
+                &TermQuery::new(Term::from_field_u64(id, 3), IndexRecordOption::Basic),
+                &Count
+            )?,
+            1
+        );
+        let mut tags = Vec::new();
+        for segment_reader in searcher.segment_readers() {
+            let ids = segment_reader.fast_fields().u64("id")?;
+            let tag_column = segment_reader.fast_fields().str("tag")?.unwrap();
+            for doc_id in segment_reader.doc_ids_alive() {
+                let mut tag_value = String::new();
+                if let Some(ord) = tag_column.term_ords(doc_id).next() {
+                    tag_column.ord_to_str(ord, &mut tag_value)?;
+                }
+                tags.push((ids.first(doc_id).unwrap(), tag_value));
+            }
+        }
+        tags.sort();
+        assert_eq!(
+            tags,
+            [
+                (1, "a".to_string()),
+                (3, String::new()),
+                (4, "a".to_string())
+            ]
+        );
+
+        assert!(matches!(
+            index_writer
+                .rebuild_field(index.searchable_segment_ids()?[0], unstored)
+                .wait(),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_rebuild_field_memory_budget() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(body => "small segment"))?;
+        index_writer.commit()?;
+        for i in 0..5_000 {
+            let text = (0..20).map(|j| format!("w{i}x{j}")).join(" ");
+            index_writer.add_document(doc!(body => text))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let (small_segment_reader, large_segment_reader) = searcher
+            .segment_readers()
+            .iter()
+            .sorted_by_key(|segment_reader| segment_reader.max_doc())
+            .collect_tuple()
+            .unwrap();
+        let memory_budget = 5_000_000;
+        let rebuilt_segment =
+            index_stored_values(&index, small_segment_reader, body, memory_budget)?;
+        assert_eq!(rebuilt_segment.meta().max_doc(), 1);
+        assert!(matches!(
+            index_stored_values(&index, large_segment_reader, body, memory_budget),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        let rebuilt_segment = index_stored_values(
+            &index,
+            large_segment_reader,
+            body,
+            MEMORY_BUDGET_NUM_BYTES_MIN,
+        )?;
+        assert_eq!(rebuilt_segment.meta().max_doc(), 5_000);
+        Ok(())
+    }
+}
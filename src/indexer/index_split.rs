@@ -0,0 +1,264 @@
+//! Resharding of indexes, without going back to the source data.
+//!
+//! [`split_index()`] splits an index into several indexes, given the value of a routing field,
+//! and [`merge_indices()`](crate::indexer::merge_indices) shrinks several indexes into one.
+use common::{BitSet, ReadOnlyBitSet};
+
+use crate::directory::Directory;
+use crate::fastfield::AliveBitSet;
+use crate::index::{Index, Segment, SegmentReader};
+use crate::indexer::index_writer::is_raw_text_field;
+use crate::indexer::merge_filtered_segments;
+use crate::indexer::routing::RoutingSettings;
+use crate::schema::{Field, IndexRecordOption};
+use crate::{DocSet, TantivyError, TERMINATED};
+
+/// Defines the index a document is copied to by [`split_index()`], given its routing value.
+#[derive(Clone, Copy)]
+pub enum SplitRouting<'a> {
+    /// The routing value is hashed to one of the output indexes, with the same hash function as
+    /// [`RoutingSettings::partition()`].
+    ///
+    /// Splitting an index into `N` indexes this way puts the documents of routing partition `i`
+    /// in the `i`-th index, if the index is routed with `N` partitions.
+    Hash,
+    /// The output index is the ordinal returned by the function, given the routing value.
+    Value(&'a (dyn Fn(&str) -> usize + Sync)),
+}
+
+/// Splits an index into as many indexes as there are output directories, given the value of
+/// `routing_field` of its documents.
+///
+/// Each output index is made of a single segment, and has the schema and the settings of the
+/// original index. The routing field is required to be a text field indexed with the `raw`
+/// tokenizer. Documents are routed given their smallest routing value, and documents without any
+/// routing value are copied to the first index. Deleted documents are not copied.
+///
+/// The output directories are assumed to be empty.
+///
+/// # Warning
+/// This function does NOT check or take the `IndexWriter` is running. It is not
+/// meant to work if you have an `IndexWriter` running for the origin index, or
+/// the destination indexes.
+pub fn split_index<T: Into<Box<dyn Directory>>>(
+    index: &Index,
+    routing_field: Field,
+    routing: SplitRouting<'_>,
+    output_directories: Vec<T>,
+) -> crate::Result<Vec<Index>> {
+    let num_indexes = output_directories.len();
+    if num_indexes == 0 {
+        return Err(TantivyError::InvalidArgument(
+            "No output directories given to split the index into".into(),
+        ));
+    }
+    let schema = index.schema();
+    let field_entry = schema.get_field_entry(routing_field);
+    if !is_raw_text_field(field_entry) {
+        let err_msg = format!(
+            "The routing field `{}` needs to be a text field indexed with the `raw` tokenizer.",
+            field_entry.name()
+        );
+        return Err(TantivyError::SchemaError(err_msg));
+    }
+    let routing_settings = RoutingSettings::new(field_entry.name(), num_indexes as u32);
+    let route = |routing_value: &str| -> crate::Result<usize> {
+        let ord = match routing {
+            SplitRouting::Hash => routing_settings.partition(routing_value) as usize,
+            SplitRouting::Value(route_fn) => route_fn(routing_value),
+        };
+        if ord >= num_indexes {
+            return Err(TantivyError::InvalidArgument(
+                format!(
+                    "Routing value {routing_value:?} was routed to index {ord}, but there are \
+                     only {num_indexes} output indexes"
+                )
+                .into(),
+            ));
+        }
+        Ok(ord)
+    };
+
+    let segments: Vec<Segment> = index.searchable_segments()?;
+    if segments.is_empty() {
+        return output_directories
+            .into_iter()
+            .map(|directory| Index::create(directory, schema.clone(), index.settings().clone()))
+            .collect();
+    }
+
+    // `filters[i][j]` holds the documents of the `j`-th segment copied to the `i`-th index.
+    let mut filters: Vec<Vec<Option<AliveBitSet>>> = (0..num_indexes)
+        .map(|_| Vec::with_capacity(segments.len()))
+        .collect();
+    for segment in &segments {
+        let segment_reader = SegmentReader::open(segment)?;
+        let mut doc_ords: Vec<Option<usize>> = vec![None; segment_reader.max_doc() as usize];
+        let inverted_index = segment_reader.inverted_index(routing_field)?;
+        let mut term_stream = inverted_index.terms().stream()?;
+        // Terms are streamed in increasing order, so that the first routing value seen for a
+        // document is its smallest one.
+        while term_stream.advance() {
+            let routing_value = std::str::from_utf8(term_stream.key()).map_err(|_| {
+                TantivyError::InvalidArgument("Routing value is not valid utf8.".into())
+            })?;
+            let ord = route(routing_value)?;
+            let mut postings = inverted_index
+                .read_postings_from_terminfo(term_stream.value(), IndexRecordOption::Basic)?;
+            let mut doc = postings.doc();
+            while doc != TERMINATED {
+                doc_ords[doc as usize].get_or_insert(ord);
+                doc = postings.advance();
+            }
+        }
+        let mut bitsets: Vec<BitSet> = (0..num_indexes)
+            .map(|_| BitSet::with_max_value(segment_reader.max_doc()))
+            .collect();
+        for (doc, doc_ord) in doc_ords.into_iter().enumerate() {
+            bitsets[doc_ord.unwrap_or(0)].insert(doc as u32);
+        }
+        for (filter, bitset) in filters.iter_mut().zip(&bitsets) {
+            filter.push(Some(AliveBitSet::from(ReadOnlyBitSet::from(bitset))));
+        }
+    }
+
+    output_directories
+        .into_iter()
+        .zip(filters)
+        .map(|(directory, filter)| {
+            merge_filtered_segments(&segments, index.settings().clone(), filter, directory)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_index, SplitRouting};
+    use crate::collector::Count;
+    use crate::directory::RamDirectory;
+    use crate::indexer::merge_indices;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, STORED, STRING, TEXT};
+    use crate::{Index, IndexWriter, Term};
+
+    fn routing_values(index: &Index) -> crate::Result<Vec<String>> {
+        let searcher = index.reader()?.searcher();
+        let routing_field = index.schema().get_field("tenant")?;
+        let mut routing_values = Vec::new();
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(routing_field)?;
+            let mut term_stream = inverted_index.terms().stream()?;
+            while term_stream.advance() {
+                routing_values.push(String::from_utf8(term_stream.key().to_vec()).unwrap());
+            }
+        }
+        Ok(routing_values)
+    }
+
+    #[test]
+    fn test_split_index_by_value() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING | STORED);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tenant => "a", body => "hello"))?;
+        index_writer.add_document(doc!(tenant => "b", body => "hello"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(tenant => "c", body => "hello"))?;
+        index_writer.add_document(doc!(tenant => "a", body => "world"))?;
+        index_writer.add_document(doc!(body => "no tenant"))?;
+        index_writer.commit()?;
+        index_writer.delete_term(Term::from_field_text(tenant, "c"));
+        index_writer.commit()?;
+
+        let route = |routing_value: &str| usize::from(routing_value != "a");
+        let indexes = split_index(
+            &index,
+            tenant,
+            SplitRouting::Value(&route),
+            vec![RamDirectory::default(), RamDirectory::default()],
+        )?;
+        assert_eq!(indexes.len(), 2);
+        let num_docs: Vec<usize> = indexes
+            .iter()
+            .map(|index| index.reader()?.searcher().search(&AllQuery, &Count))
+            .collect::<crate::Result<_>>()?;
+        assert_eq!(num_docs, vec![3, 1]);
+        assert_eq!(routing_values(&indexes[0])?, vec!["a".to_string()]);
+        assert_eq!(routing_values(&indexes[1])?, vec!["b".to_string()]);
+
+        let shrunk_index = merge_indices(&indexes, RamDirectory::default())?;
+        let searcher = shrunk_index.reader()?.searcher();
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_index_by_hash() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..20 {
+            index_writer.add_document(doc!(tenant => format!("tenant-{i}")))?;
+        }
+        index_writer.commit()?;
+
+        let indexes = split_index(
+            &index,
+            tenant,
+            SplitRouting::Hash,
+            vec![
+                RamDirectory::default(),
+                RamDirectory::default(),
+                RamDirectory::default(),
+            ],
+        )?;
+        let routing_settings = crate::indexer::routing::RoutingSettings::new("tenant", 3);
+        let mut num_docs = 0;
+        for (ord, index) in indexes.iter().enumerate() {
+            for routing_value in routing_values(index)? {
+                assert_eq!(routing_settings.partition(&routing_value) as usize, ord);
+                num_docs += 1;
+            }
+        }
+        assert_eq!(num_docs, 20);
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_index_invalid_routing() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tenant => "a"))?;
+        index_writer.commit()?;
+
+        assert!(split_index(
+            &index,
+            body,
+            SplitRouting::Hash,
+            vec![RamDirectory::default()]
+        )
+        .is_err());
+        let route = |_: &str| 1;
+        assert!(split_index(
+            &index,
+            tenant,
+            SplitRouting::Value(&route),
+            vec![RamDirectory::default()]
+        )
+        .is_err());
+        assert!(split_index(
+            &index,
+            tenant,
+            SplitRouting::Hash,
+            Vec::<RamDirectory>::new()
+        )
+        .is_err());
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 use std::ops::Range;
 use std::sync::Arc;
 use std::thread;
@@ -12,16 +14,22 @@ use super::{AddBatch, AddBatchReceiver, AddBatchSender, PreparedCommit};
 use crate::directory::{DirectoryLock, GarbageCollectionResult, TerminatingWrite};
 use crate::error::TantivyError;
 use crate::fastfield::write_alive_bitset;
-use crate::index::{Index, Segment, SegmentComponent, SegmentId, SegmentMeta, SegmentReader};
+use crate::index::{
+    Index, Segment, SegmentComponent, SegmentId, SegmentMeta, SegmentPartition, SegmentReader,
+};
 use crate::indexer::delete_queue::{DeleteCursor, DeleteQueue};
 use crate::indexer::doc_opstamp_mapping::DocToOpstampMapping;
+use crate::indexer::field_rebuild::check_rebuildable_field;
 use crate::indexer::index_writer_status::IndexWriterStatus;
+use crate::indexer::memory_accountant::{MemoryAccountant, WorkerMemoryHandle};
 use crate::indexer::operation::DeleteOperation;
+use crate::indexer::routing::Partitioner;
 use crate::indexer::stamper::Stamper;
-use crate::indexer::{MergePolicy, SegmentEntry, SegmentWriter};
+use crate::indexer::writer_fields::WriterFields;
+use crate::indexer::{MemoryUsage, MergePolicy, SegmentEntry, SegmentWriter};
 use crate::query::{EnableScoring, Query, TermQuery};
 use crate::schema::document::Document;
-use crate::schema::{IndexRecordOption, TantivyDocument, Term};
+use crate::schema::{Field, FieldEntry, FieldType, IndexRecordOption, TantivyDocument, Term};
 use crate::{FutureResult, Opstamp};
 
 // Size of the margin for the `memory_arena`. A segment is closed when the remaining memory
@@ -35,6 +43,11 @@ pub const MEMORY_BUDGET_NUM_BYTES_MAX: usize = u32::MAX as usize - MARGIN_IN_BYT
 // We impose the number of index writer threads to be at most this.
 pub const MAX_NUM_THREAD: usize = 8;
 
+// Maximum number of tenant or routing partition segments an indexer thread writes at the same
+// time. Beyond it, or beyond half of the memory budget of the thread, documents go to a segment
+// which is not tagged.
+const MAX_PARTITION_SEGMENT_WRITERS: usize = 16;
+
 // Add document will block if the number of docs waiting in the queue to be indexed
 // reaches `PIPELINE_MAX_SIZE_IN_DOCS`
 const PIPELINE_MAX_SIZE_IN_DOCS: usize = 10_000;
@@ -51,8 +64,10 @@ pub struct IndexWriterOptions {
     #[builder(default = MEMORY_BUDGET_NUM_BYTES_MIN)]
     /// The memory budget per indexer thread.
     ///
-    /// When an indexer thread has buffered this much data in memory
-    /// it will flush the segment to disk (although this is not searchable until commit is called.)
+    /// The budgets of all indexer threads are pooled: when the indexer threads have
+    /// buffered this much data in memory on average, the thread holding the largest
+    /// in-memory segment flushes it to disk (although this is not searchable until commit is
+    /// called.) See [`IndexWriter::memory_usage()`].
     memory_budget_per_thread: usize,
     #[builder(default = 1)]
     /// The number of indexer worker threads to use.
@@ -60,6 +75,24 @@ pub struct IndexWriterOptions {
     #[builder(default = 4)]
     /// Defines the number of merger threads to use.
     num_merge_threads: usize,
+    /// If set, documents are partitioned by tenant, i.e. by the value of this field.
+    ///
+    /// Each segment produced by the indexer threads then only contains documents of a
+    /// single tenant, and is tagged with it (see [`SegmentMeta::partition()`]). Merges only
+    /// happen between segments of the same tenant.
+    /// Queries wrapped in a [`TenantQuery`](crate::query::TenantQuery) skip the segments of other
+    /// tenants altogether.
+    ///
+    /// Each indexer thread writes up to 16 tenant segments at the same time, which share the
+    /// memory budget of the thread. A new tenant segment is only started while the thread uses
+    /// less than half of its budget. The documents of the other tenants, as well as the
+    /// documents with several values for the field, go to a segment which is not tagged.
+    ///
+    /// The field is required to be a text field indexed with the `raw` tokenizer, and cannot be
+    /// combined with a [`routing_field`](crate::IndexSettings::routing_field).
+    /// Note that documents of a same [`IndexWriter::run()`] batch may end up in different
+    /// segments if they belong to different tenants.
+    tenant_field: Option<Field>,
 }
 
 /// `IndexWriter` is the user entry-point to add document to an index.
@@ -84,11 +117,15 @@ pub struct IndexWriter<D: Document = TantivyDocument> {
 
     segment_updater: SegmentUpdater,
 
+    memory_accountant: Arc<MemoryAccountant>,
+
     worker_id: usize,
 
     delete_queue: DeleteQueue,
 
     stamper: Stamper,
+
+    writer_fields: WriterFields,
     committed_opstamp: Opstamp,
 }
 
@@ -184,15 +221,16 @@ fn index_documents<D: Document>(
     segment: Segment,
     grouped_document_iterator: &mut dyn Iterator<Item = AddBatch<D>>,
     segment_updater: &SegmentUpdater,
-    mut delete_cursor: DeleteCursor,
+    worker_memory: &WorkerMemoryHandle,
+    delete_cursor: DeleteCursor,
 ) -> crate::Result<()> {
     let mut segment_writer = SegmentWriter::for_segment(memory_budget, segment.clone())?;
-    for document_group in grouped_document_iterator {
+    while let Some(document_group) = next_document_group(grouped_document_iterator, worker_memory) {
         for doc in document_group {
             segment_writer.add_document(doc)?;
         }
-        let mem_usage = segment_writer.mem_usage();
-        if mem_usage >= memory_budget - MARGIN_IN_BYTES {
+        worker_memory.update(segment_writer.memory_usage());
+        if worker_memory.should_flush() {
             info!(
                 "Buffer limit reached, flushing segment with maxdoc={}.",
                 segment_writer.max_doc()
@@ -205,6 +243,102 @@ fn index_documents<D: Document>(
         return Ok(());
     }
 
+    finalize_segment(
+        segment,
+        segment_writer,
+        None,
+        segment_updater,
+        delete_cursor,
+    )
+}
+
+/// Waits for the next group of documents, reporting the worker as idle meanwhile.
+fn next_document_group<D: Document>(
+    grouped_document_iterator: &mut dyn Iterator<Item = AddBatch<D>>,
+    worker_memory: &WorkerMemoryHandle,
+) -> Option<AddBatch<D>> {
+    worker_memory.set_idle(true);
+    let document_group_opt = grouped_document_iterator.next();
+    worker_memory.set_idle(false);
+    document_group_opt
+}
+
+/// Same as `index_documents`, but routes each document to a segment dedicated
+/// to its partition, e.g. to its tenant.
+fn index_documents_partitioned<D: Document>(
+    memory_budget: usize,
+    index: &Index,
+    partitioner: &Partitioner,
+    grouped_document_iterator: &mut dyn Iterator<Item = AddBatch<D>>,
+    segment_updater: &SegmentUpdater,
+    worker_memory: &WorkerMemoryHandle,
+    delete_cursor: DeleteCursor,
+) -> crate::Result<()> {
+    // Partition segment writers start with a small hash table, which grows as
+    // their partition gets more documents.
+    let segment_writer_memory_budget = memory_budget / MAX_PARTITION_SEGMENT_WRITERS;
+    let mut segment_writers: HashMap<Option<SegmentPartition>, (Segment, SegmentWriter)> =
+        HashMap::new();
+    let mut memory_usage = MemoryUsage::default();
+    while let Some(document_group) = next_document_group(grouped_document_iterator, worker_memory) {
+        for doc in document_group {
+            let mut partition_opt = partitioner.partition(&doc.document);
+            // Rather than flushing ever more small segments when there are many tenants, the
+            // documents of the tenants beyond the first ones go to a segment which is not tagged.
+            if !segment_writers.contains_key(&partition_opt)
+                && (segment_writers.len() >= MAX_PARTITION_SEGMENT_WRITERS
+                    || memory_usage.total() >= memory_budget / 2)
+            {
+                partition_opt = None;
+            }
+            let (_, segment_writer) = match segment_writers.entry(partition_opt) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let segment = index.new_segment();
+                    let segment_writer =
+                        SegmentWriter::for_segment(segment_writer_memory_budget, segment.clone())?;
+                    entry.insert((segment, segment_writer))
+                }
+            };
+            segment_writer.add_document(doc)?;
+        }
+        memory_usage = MemoryUsage::default();
+        for (_, segment_writer) in segment_writers.values() {
+            memory_usage += segment_writer.memory_usage();
+        }
+        worker_memory.update(memory_usage);
+        if worker_memory.should_flush() {
+            info!(
+                "Buffer limit reached, flushing {} partition segments.",
+                segment_writers.len()
+            );
+            break;
+        }
+    }
+
+    if !segment_updater.is_alive() {
+        return Ok(());
+    }
+
+    for (partition_opt, (segment, segment_writer)) in segment_writers {
+        finalize_segment(
+            segment,
+            segment_writer,
+            partition_opt,
+            segment_updater,
+            delete_cursor.clone(),
+        )?;
+    }
+    Ok(())
+}
+
+fn finalize_segment(
+    segment: Segment,
+    segment_writer: SegmentWriter,
+    partition_opt: Option<SegmentPartition>,
+    segment_updater: &SegmentUpdater,
+    mut delete_cursor: DeleteCursor,
+) -> crate::Result<()> {
     let max_doc = segment_writer.max_doc();
 
     // this is ensured by the call to peek before starting
@@ -217,7 +351,10 @@ fn index_documents<D: Document>(
 
     let alive_bitset_opt = apply_deletes(&segment_with_max_doc, &mut delete_cursor, &doc_opstamps)?;
 
-    let meta = segment_with_max_doc.meta().clone();
+    let meta = segment_with_max_doc
+        .meta()
+        .clone()
+        .with_partition(partition_opt);
     meta.untrack_temp_docstore();
     // update segment_updater inventory to remove tempstore
     let segment_entry = SegmentEntry::new(meta, delete_cursor, alive_bitset_opt);
@@ -225,6 +362,16 @@ fn index_documents<D: Document>(
     Ok(())
 }
 
+pub(crate) fn is_raw_text_field(field_entry: &FieldEntry) -> bool {
+    match field_entry.field_type() {
+        FieldType::Str(text_options) => text_options
+            .get_indexing_options()
+            .map(|indexing_options| indexing_options.tokenizer() == "raw")
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
 /// `doc_opstamps` is required to be non-empty.
 fn apply_deletes(
     segment: &Segment,
@@ -287,18 +434,20 @@ impl<D: Document> IndexWriter<D> {
                 "The memory arena in bytes per thread needs to be at least \
                  {MEMORY_BUDGET_NUM_BYTES_MIN}."
             );
-            return Err(TantivyError::InvalidArgument(err_msg));
+            return Err(TantivyError::InvalidArgument(err_msg.into()));
         }
         if options.memory_budget_per_thread >= MEMORY_BUDGET_NUM_BYTES_MAX {
             let err_msg = format!(
                 "The memory arena in bytes per thread cannot exceed {MEMORY_BUDGET_NUM_BYTES_MAX}"
             );
-            return Err(TantivyError::InvalidArgument(err_msg));
+            return Err(TantivyError::InvalidArgument(err_msg.into()));
         }
         if options.num_worker_threads == 0 {
             let err_msg = "At least one worker thread is required, got 0".to_string();
-            return Err(TantivyError::InvalidArgument(err_msg));
+            return Err(TantivyError::InvalidArgument(err_msg.into()));
         }
+        Partitioner::for_index(index, options.tenant_field)?;
+        let writer_fields = WriterFields::for_schema(&index.schema(), index.settings())?;
 
         let (document_sender, document_receiver) =
             crossbeam_channel::bounded(PIPELINE_MAX_SIZE_IN_DOCS);
@@ -326,12 +475,18 @@ impl<D: Document> IndexWriter<D> {
 
             segment_updater,
 
+            memory_accountant: Arc::new(MemoryAccountant::new(
+                options.memory_budget_per_thread * options.num_worker_threads,
+                options.num_worker_threads,
+            )),
+
             workers_join_handle: vec![],
 
             delete_queue,
 
             committed_opstamp: current_opstamp,
             stamper,
+            writer_fields,
 
             worker_id: 0,
         };
@@ -349,6 +504,12 @@ impl<D: Document> IndexWriter<D> {
         &self.index
     }
 
+    /// Returns the memory currently used by the indexing threads to buffer
+    /// the documents that have not been flushed to a segment yet.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.memory_accountant.memory_usage()
+    }
+
     /// If there are some merging threads, blocks until they all finish their work and
     /// then drop the `IndexWriter`.
     pub fn wait_merging_threads(mut self) -> crate::Result<()> {
@@ -417,9 +578,12 @@ impl<D: Document> IndexWriter<D> {
 
         let segment_updater = self.segment_updater.clone();
 
+        let worker_memory = self.memory_accountant.register();
+
         let mut delete_cursor = self.delete_queue.cursor();
 
         let mem_budget = self.options.memory_budget_per_thread;
+        let partitioner_opt = Partitioner::for_index(&self.index, self.options.tenant_field)?;
         let index = self.index.clone();
         let join_handle: JoinHandle<crate::Result<()>> = thread::Builder::new()
             .name(format!("thrd-tantivy-index{}", self.worker_id))
@@ -447,13 +611,27 @@ impl<D: Document> IndexWriter<D> {
                         return Ok(());
                     }
 
-                    index_documents(
-                        mem_budget,
-                        index.new_segment(),
-                        &mut document_iterator,
-                        &segment_updater,
-                        delete_cursor.clone(),
-                    )?;
+                    if let Some(partitioner) = &partitioner_opt {
+                        index_documents_partitioned(
+                            mem_budget,
+                            &index,
+                            partitioner,
+                            &mut document_iterator,
+                            &segment_updater,
+                            &worker_memory,
+                            delete_cursor.clone(),
+                        )?;
+                    } else {
+                        index_documents(
+                            mem_budget,
+                            index.new_segment(),
+                            &mut document_iterator,
+                            &segment_updater,
+                            &worker_memory,
+                            delete_cursor.clone(),
+                        )?;
+                    }
+                    worker_memory.update(MemoryUsage::default());
                 }
             })?;
         self.worker_id += 1;
@@ -538,6 +716,58 @@ impl<D: Document> IndexWriter<D> {
         segment_updater.start_merge(merge_operation)
     }
 
+    /// Rebuilds the inverted index and the fast field columns of `field` in a segment, from the
+    /// values of the field in the doc store.
+    ///
+    /// This is typically useful after changing the tokenizer of a field: the postings of the field
+    /// are recomputed with the tokenizers currently registered in the index, while the data of the
+    /// other fields is copied as is. Like a merge, the rebuilt segment replaces the original one,
+    /// and the change is only visible after the next commit.
+    ///
+    /// The field is required to be stored, and to be indexed or fast. Values which are not
+    /// stored, e.g. the values of a field added with a different schema, cannot be recovered.
+    ///
+    /// The values of the field in the segment are indexed within the memory budget of a single
+    /// indexer thread (see [`IndexWriterOptions`]). The rebuild fails with an
+    /// [`InvalidArgument`](TantivyError::InvalidArgument) error if they do not fit, in which case
+    /// the segment is left untouched.
+    ///
+    /// ```rust
+    /// use tantivy::schema::{Schema, STORED, TEXT};
+    /// use tantivy::{doc, Index, IndexWriter};
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let mut schema_builder = Schema::builder();
+    /// let title = schema_builder.add_text_field("title", TEXT | STORED);
+    /// let index = Index::create_in_ram(schema_builder.build());
+    /// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+    /// index_writer.add_document(doc!(title => "The Old Man and the Sea"))?;
+    /// index_writer.commit()?;
+    ///
+    /// for segment_id in index.searchable_segment_ids()? {
+    ///     index_writer.rebuild_field(segment_id, title).wait()?;
+    /// }
+    /// index_writer.commit()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn rebuild_field(
+        &mut self,
+        segment_id: SegmentId,
+        field: Field,
+    ) -> FutureResult<Option<SegmentMeta>> {
+        if let Err(err) = check_rebuildable_field(&self.index, field) {
+            return err.into();
+        }
+        let merge_operation = self.segment_updater.make_merge_operation(&[segment_id]);
+        let segment_updater = self.segment_updater.clone();
+        segment_updater.start_field_rebuild(
+            merge_operation,
+            field,
+            self.options.memory_budget_per_thread,
+        )
+    }
+
     /// Closes the current document channel send.
     /// and replace all the channels by new ones.
     ///
@@ -660,7 +890,8 @@ impl<D: Document> IndexWriter<D> {
     /// to resume indexing from this point.
     ///
     /// Commit returns the `opstamp` of the last document
-    /// that made it in the commit.
+    /// that made it in the commit. Readers can wait for it to be
+    /// visible with [`IndexReader::wait_for()`](crate::IndexReader::wait_for).
     pub fn commit(&mut self) -> crate::Result<Opstamp> {
         self.prepare_commit()?.commit()
     }
@@ -688,13 +919,51 @@ impl<D: Document> IndexWriter<D> {
     /// Delete all documents matching a given query.
     /// Returns an `Err` if the query can't be executed.
     ///
+    /// The query is compiled into a [`Weight`](crate::query::Weight) right away and pushed
+    /// into the delete queue. It is evaluated upon commit, against the committed segments
+    /// as well as the segments being written.
+    ///
     /// Delete operation only affects documents that
     /// were added in previous commits, and documents
     /// that were added previously in the same commit.
+    /// Documents added after the call are left untouched, even
+    /// if they match the query.
     ///
     /// Like adds, the deletion itself will be visible
     /// only after calling `commit()`.
-    #[doc(hidden)]
+    ///
+    /// ```rust
+    /// use std::ops::Bound;
+    ///
+    /// use tantivy::collector::Count;
+    /// use tantivy::query::{AllQuery, RangeQuery};
+    /// use tantivy::schema::{Schema, FAST, INDEXED};
+    /// use tantivy::{doc, DateTime, Index, IndexWriter, Term};
+    ///
+    /// fn main() -> tantivy::Result<()> {
+    ///     let mut schema_builder = Schema::builder();
+    ///     let timestamp = schema_builder.add_date_field("timestamp", INDEXED | FAST);
+    ///     let index = Index::create_in_ram(schema_builder.build());
+    ///     let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 50_000_000)?;
+    ///     for day in 0..10 {
+    ///         let day = DateTime::from_timestamp_secs(day * 86_400);
+    ///         index_writer.add_document(doc!(timestamp => day))?;
+    ///     }
+    ///
+    ///     // Expire the documents older than 7 days.
+    ///     let cutoff = DateTime::from_timestamp_secs(3 * 86_400);
+    ///     let expired = RangeQuery::new(
+    ///         Bound::Unbounded,
+    ///         Bound::Excluded(Term::from_field_date(timestamp, cutoff)),
+    ///     );
+    ///     index_writer.delete_query(Box::new(expired))?;
+    ///     index_writer.commit()?;
+    ///
+    ///     let searcher = index.reader()?.searcher();
+    ///     assert_eq!(searcher.search(&AllQuery, &Count)?, 7);
+    ///     Ok(())
+    /// }
+    /// ```
     pub fn delete_query(&self, query: Box<dyn Query>) -> crate::Result<Opstamp> {
         let weight = query.weight(EnableScoring::disabled_from_schema(&self.index.schema()))?;
         let opstamp = self.stamper.stamp();
@@ -725,6 +994,7 @@ impl<D: Document> IndexWriter<D> {
     /// be used by the client to align commits with its own
     /// document queue.
     pub fn add_document(&self, document: D) -> crate::Result<Opstamp> {
+        self.writer_fields.check_document(&document)?;
         let opstamp = self.stamper.stamp();
         self.send_add_documents_batch(smallvec![AddOperation { opstamp, document }])?;
         Ok(opstamp)
@@ -772,25 +1042,30 @@ impl<D: Document> IndexWriter<D> {
         let (batch_opstamp, stamps) = self.get_batch_opstamps(count);
 
         let mut adds = AddBatch::default();
+        let mut deletes = Vec::new();
 
+        // Nothing is applied before all of the operations are validated.
         for (user_op, opstamp) in user_operations_it.zip(stamps) {
             match user_op {
                 UserOperation::Delete(term) => {
                     let query = TermQuery::new(term, IndexRecordOption::Basic);
                     let weight =
                         query.weight(EnableScoring::disabled_from_schema(&self.index.schema()))?;
-                    let delete_operation = DeleteOperation {
+                    deletes.push(DeleteOperation {
                         opstamp,
                         target: weight,
-                    };
-                    self.delete_queue.push(delete_operation);
+                    });
                 }
                 UserOperation::Add(document) => {
+                    self.writer_fields.check_document(&document)?;
                     let add_operation = AddOperation { opstamp, document };
                     adds.push(add_operation);
                 }
             }
         }
+        for delete_operation in deletes {
+            self.delete_queue.push(delete_operation);
+        }
         self.send_add_documents_batch(adds)?;
         Ok(batch_opstamp)
     }
@@ -818,6 +1093,7 @@ impl<D: Document> Drop for IndexWriter<D> {
 mod tests {
     use std::collections::{HashMap, HashSet};
     use std::net::Ipv6Addr;
+    use std::ops::Bound;
 
     use columnar::{Column, MonotonicallyMappableToU128};
     use itertools::Itertools;
@@ -827,9 +1103,15 @@ mod tests {
     use crate::collector::{Count, TopDocs};
     use crate::directory::error::LockError;
     use crate::error::*;
-    use crate::indexer::index_writer::MEMORY_BUDGET_NUM_BYTES_MIN;
-    use crate::indexer::{IndexWriterOptions, NoMergePolicy};
-    use crate::query::{QueryParser, TermQuery};
+    use crate::index::{PartitionKey, SegmentPartition};
+    use crate::indexer::index_writer::{
+        MAX_PARTITION_SEGMENT_WRITERS, MEMORY_BUDGET_NUM_BYTES_MIN,
+    };
+    use crate::indexer::routing::RoutingSettings;
+    use crate::indexer::{IndexWriterOptions, LogMergePolicy, MemoryUsage, NoMergePolicy};
+    #[cfg(feature = "query-parser")]
+    use crate::query::QueryParser;
+    use crate::query::{AllQuery, RangeQuery, TenantQuery, TermQuery};
     use crate::schema::{
         self, Facet, FacetOptions, IndexRecordOption, IpAddrOptions, JsonObjectOptions,
         NumericOptions, Schema, TextFieldIndexing, TextOptions, Value, FAST, INDEXED, STORED,
@@ -837,8 +1119,8 @@ mod tests {
     };
     use crate::store::DOCSTORE_CACHE_CAPACITY;
     use crate::{
-        DateTime, DocAddress, Index, IndexSettings, IndexWriter, ReloadPolicy, TantivyDocument,
-        Term,
+        DateTime, DocAddress, Index, IndexSettings, IndexWriter, ReloadPolicy, SegmentMeta,
+        TantivyDocument, Term,
     };
 
     const LOREM: &str = "Doc Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do \
@@ -1280,6 +1562,51 @@ mod tests {
         assert_eq!(num_docs_containing("a"), 0);
     }
 
+    #[test]
+    fn test_delete_query_opstamp_ordering() -> crate::Result<()> {
+        let mut schema_builder = schema::Schema::builder();
+        let date_field = schema_builder.add_date_field("date", INDEXED | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let day = |day: i64| DateTime::from_timestamp_secs(day * 86_400);
+        let expired_query = || {
+            Box::new(RangeQuery::new(
+                Bound::Unbounded,
+                Bound::Excluded(Term::from_field_date(date_field, day(5))),
+            ))
+        };
+        let num_docs = |index_writer: &mut IndexWriter| -> crate::Result<usize> {
+            index_writer.commit()?;
+            index.reader()?.searcher().search(&AllQuery, &Count)
+        };
+
+        // Committed segment.
+        for i in 0..4 {
+            index_writer.add_document(doc!(date_field => day(i)))?;
+        }
+        index_writer.commit()?;
+        // In-flight segment.
+        for i in 4..8 {
+            index_writer.add_document(doc!(date_field => day(i)))?;
+        }
+        let delete_opstamp = index_writer.delete_query(expired_query())?;
+        // Added after the delete: not deleted, even though it matches the query.
+        let add_opstamp = index_writer.add_document(doc!(date_field => day(0)))?;
+        assert!(add_opstamp > delete_opstamp);
+        assert_eq!(num_docs(&mut index_writer)?, 4);
+
+        // An update by query: the deletion only applies to the previous version.
+        index_writer.delete_query(expired_query())?;
+        index_writer.add_document(doc!(date_field => day(1)))?;
+        assert_eq!(num_docs(&mut index_writer)?, 4);
+
+        // Rolled back deletes are not applied.
+        index_writer.delete_query(expired_query())?;
+        index_writer.rollback()?;
+        assert_eq!(num_docs(&mut index_writer)?, 4);
+        Ok(())
+    }
+
     #[test]
     fn test_delete_all_documents_rollback_correct_stamp() {
         let mut schema_builder = schema::Schema::builder();
@@ -1625,6 +1952,7 @@ mod tests {
         id_list
     }
 
+    #[cfg(feature = "query-parser")]
     fn test_operation_strategy(ops: &[IndexingOp], force_end_merge: bool) -> crate::Result<Index> {
         let mut schema_builder = schema::Schema::builder();
         let json_field = schema_builder.add_json_field("json", FAST | TEXT | STORED);
@@ -2227,12 +2555,14 @@ mod tests {
         Ok(index)
     }
 
+    #[cfg(feature = "query-parser")]
     #[test]
     fn test_fast_field_range() {
         let ops: Vec<_> = (0..1000).map(IndexingOp::add).collect();
         assert!(test_operation_strategy(&ops, true).is_ok());
     }
 
+    #[cfg(feature = "query-parser")]
     #[test]
     fn test_ip_range_query_multivalue_bug() {
         assert!(test_operation_strategy(
@@ -2249,6 +2579,7 @@ mod tests {
         .is_ok());
     }
 
+    #[cfg(feature = "query-parser")]
     #[test]
     fn test_ff_num_ips_regression() {
         assert!(test_operation_strategy(
@@ -2265,6 +2596,7 @@ mod tests {
         .is_ok());
     }
 
+    #[cfg(feature = "query-parser")]
     #[test]
     fn test_minimal_sort_force_end_merge() {
         assert!(
@@ -2272,6 +2604,7 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "query-parser")]
     #[test]
     fn test_minimal_no_force_end_merge() {
         assert!(test_operation_strategy(
@@ -2287,6 +2620,7 @@ mod tests {
 
     use proptest::prelude::*;
 
+    #[cfg(feature = "query-parser")]
     proptest! {
 
         #![proptest_config(ProptestConfig::with_cases(20))]
@@ -2311,6 +2645,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "query-parser")]
     #[test]
     fn test_delete_bug_reproduction_ip_addr() {
         use IndexingOp::*;
@@ -2328,6 +2663,7 @@ mod tests {
         test_operation_strategy(&ops[..], true).unwrap();
     }
 
+    #[cfg(feature = "query-parser")]
     #[test]
     fn test_merge_regression_1() {
         use IndexingOp::*;
@@ -2341,6 +2677,7 @@ mod tests {
         test_operation_strategy(&ops[..], true).unwrap();
     }
 
+    #[cfg(feature = "query-parser")]
     #[test]
     fn test_range_query_bug_1() {
         use IndexingOp::*;
@@ -2353,6 +2690,7 @@ mod tests {
         test_operation_strategy(&ops[..], true).unwrap();
     }
 
+    #[cfg(feature = "query-parser")]
     #[test]
     fn test_range_query_bug_2() {
         let ops = &[
@@ -2376,6 +2714,7 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "query-parser")]
     #[test]
     fn test_bug_1617_3() {
         assert!(test_operation_strategy(
@@ -2393,6 +2732,7 @@ mod tests {
         .is_ok());
     }
 
+    #[cfg(feature = "query-parser")]
     #[test]
     fn test_bug_1617_2() {
         test_operation_strategy(
@@ -2581,4 +2921,284 @@ mod tests {
         );
         assert!(matches!(result, Err(TantivyError::InvalidArgument(_))));
     }
+
+    #[test]
+    fn test_writer_options_tenant_field_validation() {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let id = schema_builder.add_u64_field("id", INDEXED);
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        for invalid_tenant_field in [text, id] {
+            let options = IndexWriterOptions::builder()
+                .tenant_field(invalid_tenant_field)
+                .build();
+            let result = index.writer_with_options::<TantivyDocument>(options);
+            assert!(matches!(result, Err(TantivyError::SchemaError(_))));
+        }
+        let options = IndexWriterOptions::builder().tenant_field(tenant).build();
+        assert!(index
+            .writer_with_options::<TantivyDocument>(options)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_tenant_segments_merge() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let options = IndexWriterOptions::builder()
+            .num_worker_threads(1)
+            .tenant_field(tenant)
+            .build();
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for _ in 0..3 {
+            index_writer.add_document(doc!(tenant => "acme"))?;
+            index_writer.add_document(doc!(tenant => "initech"))?;
+            index_writer.add_document(doc!())?;
+            index_writer.commit()?;
+        }
+        let segment_metas = index.searchable_segment_metas()?;
+        assert_eq!(segment_metas.len(), 9);
+        let mut log_merge_policy = LogMergePolicy::default();
+        log_merge_policy.set_min_num_segments(2);
+        index_writer.set_merge_policy(Box::new(log_merge_policy));
+        index_writer.commit()?;
+        index_writer.wait_merging_threads()?;
+
+        let mut tenants: Vec<(Option<String>, u32)> = index
+            .searchable_segment_metas()?
+            .iter()
+            .map(|segment_meta| {
+                (
+                    segment_meta
+                        .partition()
+                        .map(|partition| match &partition.key {
+                            PartitionKey::Value(tenant) => tenant.clone(),
+                            PartitionKey::Hash { .. } => panic!("expected a tenant partition"),
+                        }),
+                    segment_meta.num_docs(),
+                )
+            })
+            .collect();
+        tenants.sort();
+        assert_eq!(
+            tenants,
+            vec![
+                (None, 3),
+                (Some("acme".to_string()), 3),
+                (Some("initech".to_string()), 3)
+            ]
+        );
+
+        // Explicitly merging segments of different tenants yields an untagged segment.
+        let segment_ids = index.searchable_segment_ids()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let merged_segment_meta = index_writer.merge(&segment_ids).wait()?.unwrap();
+        assert_eq!(merged_segment_meta.partition(), None);
+        assert_eq!(merged_segment_meta.num_docs(), 9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tenant_segments_many_tenants() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let options = IndexWriterOptions::builder()
+            .num_worker_threads(1)
+            .memory_budget_per_thread(200_000_000)
+            .tenant_field(tenant)
+            .build();
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        add_tenant_documents(&mut index_writer, tenant)?;
+
+        // The tenants beyond the first ones share a single segment, rather than each getting
+        // small segments flushed over and over.
+        let segment_metas = index.searchable_segment_metas()?;
+        assert_eq!(segment_metas.len(), MAX_PARTITION_SEGMENT_WRITERS + 1);
+        let untagged_segment_metas: Vec<&SegmentMeta> = segment_metas
+            .iter()
+            .filter(|segment_meta| segment_meta.partition().is_none())
+            .collect();
+        assert_eq!(untagged_segment_metas.len(), 1);
+        assert_eq!(
+            untagged_segment_metas[0].num_docs() as usize,
+            (100 - MAX_PARTITION_SEGMENT_WRITERS) * 10 + 1
+        );
+
+        let searcher = index.reader()?.searcher();
+        let count = |tenant_name: &str| {
+            let tenant_query = TenantQuery::new(tenant, tenant_name, Box::new(AllQuery));
+            searcher.search(&tenant_query, &Count).unwrap()
+        };
+        assert_eq!(count("tenant-0"), 11);
+        assert_eq!(count("tenant-1"), 11);
+        assert_eq!(count("tenant-99"), 10);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tenant_segments_small_memory_budget() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let options = IndexWriterOptions::builder()
+            .num_worker_threads(1)
+            .memory_budget_per_thread(MEMORY_BUDGET_NUM_BYTES_MIN)
+            .tenant_field(tenant)
+            .build();
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        add_tenant_documents(&mut index_writer, tenant)?;
+        // Fewer tenant segments fit in the memory budget, but the segments are not flushed
+        // after every batch either.
+        let segment_metas = index.searchable_segment_metas()?;
+        assert!(segment_metas.len() <= MAX_PARTITION_SEGMENT_WRITERS + 1);
+        let num_docs: u32 = segment_metas.iter().map(SegmentMeta::num_docs).sum();
+        assert_eq!(num_docs, 1001);
+        Ok(())
+    }
+
+    /// Adds 10 documents for each of 100 tenants, and a document with two tenants.
+    fn add_tenant_documents(
+        index_writer: &mut IndexWriter,
+        tenant: schema::Field,
+    ) -> crate::Result<()> {
+        for _ in 0..10 {
+            for tenant_id in 0..100 {
+                index_writer.add_document(doc!(tenant => format!("tenant-{tenant_id}")))?;
+            }
+        }
+        // Documents with several tenants are not tagged.
+        index_writer.add_document(doc!(tenant => "tenant-0", tenant => "tenant-1"))?;
+        index_writer.commit()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_routing_segments_merge() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let routing_settings = RoutingSettings::new("tenant", 2);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                routing_field: Some(routing_settings.clone()),
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        let tenants = ["acme", "initech", "umbrella", "hooli"];
+        for _ in 0..3 {
+            for tenant_name in tenants {
+                index_writer.add_document(doc!(tenant => tenant_name))?;
+            }
+            index_writer.commit()?;
+        }
+        let mut log_merge_policy = LogMergePolicy::default();
+        log_merge_policy.set_min_num_segments(2);
+        index_writer.set_merge_policy(Box::new(log_merge_policy));
+        index_writer.commit()?;
+        index_writer.wait_merging_threads()?;
+
+        let mut expected_num_docs_per_partition = HashMap::new();
+        for tenant_name in tenants {
+            let partition = SegmentPartition {
+                field: "tenant".to_string(),
+                key: PartitionKey::Hash {
+                    partition: routing_settings.partition(tenant_name),
+                    num_partitions: 2,
+                },
+            };
+            *expected_num_docs_per_partition
+                .entry(Some(partition))
+                .or_insert(0u32) += 3;
+        }
+        let num_docs_per_partition: HashMap<Option<SegmentPartition>, u32> = index
+            .searchable_segment_metas()?
+            .iter()
+            .map(|segment_meta| (segment_meta.partition().cloned(), segment_meta.num_docs()))
+            .collect();
+        assert_eq!(num_docs_per_partition, expected_num_docs_per_partition);
+        Ok(())
+    }
+
+    #[test]
+    fn test_routing_field_validation() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", TEXT);
+        schema_builder.add_text_field("tenant", STRING);
+        let schema = schema_builder.build();
+        for routing_settings in [
+            RoutingSettings::new("text", 4),
+            RoutingSettings::new("missing", 4),
+            RoutingSettings::new("tenant", 0),
+        ] {
+            let index = Index::builder()
+                .schema(schema.clone())
+                .settings(IndexSettings {
+                    routing_field: Some(routing_settings),
+                    ..Default::default()
+                })
+                .create_in_ram()
+                .unwrap();
+            assert!(index.writer_for_tests::<TantivyDocument>().is_err());
+        }
+    }
+
+    #[test]
+    fn test_routing_field_and_tenant_field_conflict() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                routing_field: Some(RoutingSettings::new("tenant", 4)),
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        let options = IndexWriterOptions::builder().tenant_field(tenant).build();
+        assert!(matches!(
+            index.writer_with_options::<TantivyDocument>(options),
+            Err(TantivyError::InvalidArgument(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_writer_memory_usage() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT | STORED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        assert_eq!(index_writer.memory_usage(), MemoryUsage::default());
+        index_writer.run([UserOperation::Add(doc!(text => "hello happy tax payer"))])?;
+        // Operations are processed asynchronously by the indexing thread.
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        let mut memory_usage = index_writer.memory_usage();
+        while memory_usage.postings == 0 {
+            assert!(
+                std::time::Instant::now() < deadline,
+                "The memory usage was never reported."
+            );
+            std::thread::sleep(std::time::Duration::from_millis(1));
+            memory_usage = index_writer.memory_usage();
+        }
+        assert!(memory_usage.store > 0);
+        assert!(memory_usage.fieldnorms > 0);
+        assert_eq!(
+            memory_usage.total(),
+            memory_usage.postings
+                + memory_usage.store
+                + memory_usage.fast_fields
+                + memory_usage.fieldnorms
+        );
+        index_writer.commit()?;
+        assert_eq!(index_writer.memory_usage(), MemoryUsage::default());
+        Ok(())
+    }
 }
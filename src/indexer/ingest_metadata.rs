@@ -0,0 +1,169 @@
+//! Fields populated by the index writer when
+//! [`IndexSettings::ingest_metadata`](crate::IndexSettings::ingest_metadata) is enabled.
+//!
+//! - `_ingest_timestamp` is a fast date field, holding the time at which the document was indexed.
+//!   Timestamps are taken from the clock of the indexing process, and are strictly increasing
+//!   within a process.
+//! - `_opstamp` is a fast u64 field, holding the opstamp of the operation that added the document.
+//!
+//! Like the other [fields populated by the index writer](super::writer_fields), both fields are
+//! added to the schema upon index creation.
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use common::DateTime;
+
+use crate::schema::{
+    DateOptions, DateTimePrecision, Field, FieldEntry, FieldType, NumericOptions, Schema,
+};
+use crate::TantivyError;
+
+/// Name of the field holding the time at which a document was indexed.
+pub const INGEST_TIMESTAMP_FIELD_NAME: &str = "_ingest_timestamp";
+/// Name of the field holding the opstamp of the operation that added a document.
+pub const OPSTAMP_FIELD_NAME: &str = "_opstamp";
+
+/// Last ingest timestamp handed out, in microseconds since the epoch.
+static LAST_INGEST_TIMESTAMP_MICROS: AtomicI64 = AtomicI64::new(i64::MIN);
+
+/// Returns the current time, or the successor of the previously returned timestamp if the
+/// clock did not move forward (or went backward) in the meantime.
+pub(crate) fn next_ingest_timestamp() -> DateTime {
+    let now_micros = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_micros() as i64)
+        .unwrap_or(0);
+    let previous_micros = LAST_INGEST_TIMESTAMP_MICROS
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |last_micros| {
+            Some(now_micros.max(last_micros.saturating_add(1)))
+        })
+        .unwrap_or_else(|last_micros| last_micros);
+    DateTime::from_timestamp_micros(now_micros.max(previous_micros.saturating_add(1)))
+}
+
+/// The ingest metadata fields of a schema.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct IngestMetadataFields {
+    pub ingest_timestamp: Field,
+    pub opstamp: Field,
+}
+
+impl IngestMetadataFields {
+    /// Returns the ingest metadata fields of a schema, or an error if they are missing or do not
+    /// have the expected type.
+    pub fn for_schema(schema: &Schema) -> crate::Result<IngestMetadataFields> {
+        let ingest_timestamp = schema.get_field(INGEST_TIMESTAMP_FIELD_NAME)?;
+        let opstamp = schema.get_field(OPSTAMP_FIELD_NAME)?;
+        check_field_entry(schema.get_field_entry(ingest_timestamp))?;
+        check_field_entry(schema.get_field_entry(opstamp))?;
+        Ok(IngestMetadataFields {
+            ingest_timestamp,
+            opstamp,
+        })
+    }
+}
+
+fn check_field_entry(field_entry: &FieldEntry) -> crate::Result<()> {
+    let has_expected_type = match field_entry.field_type() {
+        FieldType::Date(_) => field_entry.name() == INGEST_TIMESTAMP_FIELD_NAME,
+        FieldType::U64(_) => field_entry.name() == OPSTAMP_FIELD_NAME,
+        _ => false,
+    };
+    if !has_expected_type || !field_entry.is_fast() {
+        return Err(TantivyError::SchemaError(format!(
+            "Ingest metadata field {:?} has an unexpected type: {:?}",
+            field_entry.name(),
+            field_entry.field_type()
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the entries of the ingest metadata fields, as added to the schema.
+pub(crate) fn ingest_metadata_field_entries() -> [FieldEntry; 2] {
+    [
+        FieldEntry::new_date(
+            INGEST_TIMESTAMP_FIELD_NAME.to_string(),
+            DateOptions::default()
+                .set_fast()
+                .set_precision(DateTimePrecision::Microseconds),
+        ),
+        FieldEntry::new_u64(
+            OPSTAMP_FIELD_NAME.to_string(),
+            NumericOptions::default().set_fast(),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::{next_ingest_timestamp, INGEST_TIMESTAMP_FIELD_NAME, OPSTAMP_FIELD_NAME};
+    use crate::collector::Count;
+    use crate::query::RangeQuery;
+    use crate::schema::{Schema, TEXT};
+    use crate::{Index, IndexSettings, IndexWriter, Term};
+
+    #[test]
+    fn test_next_ingest_timestamp_is_strictly_increasing() {
+        let mut previous = next_ingest_timestamp();
+        for _ in 0..1_000 {
+            let timestamp = next_ingest_timestamp();
+            assert!(timestamp > previous);
+            previous = timestamp;
+        }
+    }
+
+    #[test]
+    fn test_ingest_metadata_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                ingest_metadata: true,
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        let schema = index.schema();
+        let ingest_timestamp = schema.get_field(INGEST_TIMESTAMP_FIELD_NAME)?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let first_opstamp = index_writer.add_document(doc!(text => "a"))?;
+        index_writer.commit()?;
+        let second_opstamp = index_writer.add_document(doc!(text => "b"))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(text => "c"))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let mut opstamps_and_timestamps = Vec::new();
+        for segment_reader in searcher.segment_readers() {
+            let fast_fields = segment_reader.fast_fields();
+            let opstamps = fast_fields.u64(OPSTAMP_FIELD_NAME)?;
+            let timestamps = fast_fields.date(INGEST_TIMESTAMP_FIELD_NAME)?;
+            for doc in 0..segment_reader.max_doc() {
+                opstamps_and_timestamps
+                    .push((opstamps.first(doc).unwrap(), timestamps.first(doc).unwrap()));
+            }
+        }
+        opstamps_and_timestamps.sort();
+        let opstamps: Vec<u64> = opstamps_and_timestamps
+            .iter()
+            .map(|(opstamp, _)| *opstamp)
+            .collect();
+        assert_eq!(opstamps[..2], [first_opstamp, second_opstamp]);
+        assert!(opstamps_and_timestamps
+            .windows(2)
+            .all(|pair| pair[0].1 < pair[1].1));
+
+        // What arrived after the second document?
+        let second_timestamp = opstamps_and_timestamps[1].1;
+        let query = RangeQuery::new(
+            Bound::Excluded(Term::from_field_date(ingest_timestamp, second_timestamp)),
+            Bound::Unbounded,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        Ok(())
+    }
+}
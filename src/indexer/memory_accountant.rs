@@ -0,0 +1,253 @@
+use std::ops::AddAssign;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+use super::index_writer::{MARGIN_IN_BYTES, MEMORY_BUDGET_NUM_BYTES_MAX};
+
+/// Memory used by the documents an [`IndexWriter`](super::IndexWriter) holds in memory,
+/// broken down by component.
+///
+/// All of the figures are in bytes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MemoryUsage {
+    /// Memory used by the postings hash maps and memory arenas.
+    pub postings: usize,
+    /// Memory used by the doc store buffers.
+    pub store: usize,
+    /// Memory used by the fast field buffers.
+    pub fast_fields: usize,
+    /// Memory used by the fieldnorm buffers.
+    pub fieldnorms: usize,
+}
+
+impl MemoryUsage {
+    /// Returns the total memory usage.
+    pub fn total(&self) -> usize {
+        self.postings + self.store + self.fast_fields + self.fieldnorms
+    }
+}
+
+impl AddAssign for MemoryUsage {
+    fn add_assign(&mut self, other: MemoryUsage) {
+        self.postings += other.postings;
+        self.store += other.store;
+        self.fast_fields += other.fast_fields;
+        self.fieldnorms += other.fieldnorms;
+    }
+}
+
+#[derive(Default)]
+struct WorkerMemory {
+    postings: AtomicUsize,
+    store: AtomicUsize,
+    fast_fields: AtomicUsize,
+    fieldnorms: AtomicUsize,
+    // True while the worker waits for documents, and can therefore not flush its segment.
+    idle: AtomicBool,
+}
+
+impl WorkerMemory {
+    fn store(&self, memory_usage: MemoryUsage) {
+        self.postings
+            .store(memory_usage.postings, Ordering::Relaxed);
+        self.store.store(memory_usage.store, Ordering::Relaxed);
+        self.fast_fields
+            .store(memory_usage.fast_fields, Ordering::Relaxed);
+        self.fieldnorms
+            .store(memory_usage.fieldnorms, Ordering::Relaxed);
+    }
+
+    fn load(&self) -> MemoryUsage {
+        MemoryUsage {
+            postings: self.postings.load(Ordering::Relaxed),
+            store: self.store.load(Ordering::Relaxed),
+            fast_fields: self.fast_fields.load(Ordering::Relaxed),
+            fieldnorms: self.fieldnorms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Keeps track of the memory used by the indexing workers of an `IndexWriter`.
+///
+/// The memory budget is global: rather than flushing its segment when reaching a fixed
+/// share of the budget, a worker flushes its segment when the whole budget is reached and
+/// its segment is the largest one. This way, workers indexing larger documents get a
+/// larger share of the budget.
+///
+/// Workers waiting for documents do not take part: they can only flush their segment after
+/// indexing more documents. Their memory is therefore left out of the budget check, as
+/// counting it would make the busy workers flush tiny segments while the idle ones keep
+/// holding the largest share of the budget.
+pub(crate) struct MemoryAccountant {
+    budget: usize,
+    workers: RwLock<Vec<Arc<WorkerMemory>>>,
+}
+
+impl MemoryAccountant {
+    /// Creates a new accountant for `num_workers` workers sharing `budget` bytes.
+    pub fn new(budget: usize, num_workers: usize) -> MemoryAccountant {
+        MemoryAccountant {
+            budget: budget.saturating_sub(MARGIN_IN_BYTES * num_workers),
+            workers: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Registers a new worker.
+    ///
+    /// The worker is unregistered when the returned handle is dropped.
+    pub fn register(self: &Arc<Self>) -> WorkerMemoryHandle {
+        let memory = Arc::new(WorkerMemory::default());
+        self.workers.write().unwrap().push(memory.clone());
+        WorkerMemoryHandle {
+            accountant: self.clone(),
+            memory,
+        }
+    }
+
+    /// Returns the memory used by all of the workers.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut memory_usage = MemoryUsage::default();
+        for worker in self.workers.read().unwrap().iter() {
+            memory_usage += worker.load();
+        }
+        memory_usage
+    }
+}
+
+/// Handle used by an indexing worker to report its memory usage.
+pub(crate) struct WorkerMemoryHandle {
+    accountant: Arc<MemoryAccountant>,
+    memory: Arc<WorkerMemory>,
+}
+
+impl WorkerMemoryHandle {
+    /// Records the memory currently used by the worker.
+    pub fn update(&self, memory_usage: MemoryUsage) {
+        self.memory.store(memory_usage);
+    }
+
+    /// Records whether the worker is waiting for documents.
+    pub fn set_idle(&self, idle: bool) {
+        self.memory.idle.store(idle, Ordering::Relaxed);
+    }
+
+    /// Returns true if the worker should flush its segment.
+    ///
+    /// This happens if the memory used by the busy workers reaches the budget and the worker
+    /// holds the largest of their segments, or if its segment is about to exceed what a single
+    /// memory arena can address.
+    pub fn should_flush(&self) -> bool {
+        let own_usage = self.memory.load().total();
+        if own_usage >= MEMORY_BUDGET_NUM_BYTES_MAX {
+            return true;
+        }
+        let workers = self.accountant.workers.read().unwrap();
+        let mut busy_usage = 0;
+        let mut max_busy_usage = 0;
+        for worker in workers.iter() {
+            if worker.idle.load(Ordering::Relaxed) {
+                continue;
+            }
+            let usage = worker.load().total();
+            busy_usage += usage;
+            max_busy_usage = max_busy_usage.max(usage);
+        }
+        busy_usage >= self.accountant.budget && own_usage >= max_busy_usage
+    }
+}
+
+impl Drop for WorkerMemoryHandle {
+    fn drop(&mut self) {
+        self.accountant
+            .workers
+            .write()
+            .unwrap()
+            .retain(|memory| !Arc::ptr_eq(memory, &self.memory));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{MemoryAccountant, MemoryUsage};
+    use crate::indexer::index_writer::MARGIN_IN_BYTES;
+
+    fn usage(postings: usize) -> MemoryUsage {
+        MemoryUsage {
+            postings,
+            store: 10,
+            ..MemoryUsage::default()
+        }
+    }
+
+    #[test]
+    fn test_memory_accountant_flushes_largest() {
+        let accountant = Arc::new(MemoryAccountant::new(100 + 2 * MARGIN_IN_BYTES, 2));
+        let small = accountant.register();
+        let large = accountant.register();
+        small.update(usage(20));
+        large.update(usage(50));
+        assert_eq!(accountant.memory_usage().total(), 90);
+        assert!(!small.should_flush());
+        assert!(!large.should_flush());
+        small.update(usage(40));
+        assert_eq!(accountant.memory_usage().postings, 90);
+        assert!(!small.should_flush());
+        assert!(large.should_flush());
+        large.update(MemoryUsage::default());
+        assert!(!small.should_flush());
+    }
+
+    #[test]
+    fn test_memory_accountant_skips_idle_workers() {
+        let accountant = Arc::new(MemoryAccountant::new(100 + 2 * MARGIN_IN_BYTES, 2));
+        let busy = accountant.register();
+        let idle = accountant.register();
+        busy.update(usage(20));
+        idle.update(usage(50));
+        idle.set_idle(true);
+        assert!(!busy.should_flush());
+        // The total usage is over the budget, but the largest segment is held by a worker
+        // waiting for documents: the busy worker keeps filling its own segment.
+        busy.update(usage(40));
+        assert_eq!(accountant.memory_usage().total(), 110);
+        assert!(!busy.should_flush());
+        busy.update(usage(90));
+        assert!(busy.should_flush());
+        idle.set_idle(false);
+        assert!(busy.should_flush());
+        assert!(!idle.should_flush());
+    }
+
+    #[test]
+    fn test_memory_accountant_idle_worker_does_not_shrink_segments() {
+        let accountant = Arc::new(MemoryAccountant::new(100 + 2 * MARGIN_IN_BYTES, 2));
+        let busy = accountant.register();
+        let idle = accountant.register();
+        idle.update(usage(80));
+        idle.set_idle(true);
+        let mut num_flushes = 0;
+        let mut segment_size = 0;
+        for _ in 0..100 {
+            segment_size += 5;
+            busy.update(usage(segment_size));
+            if busy.should_flush() {
+                assert!(usage(segment_size).total() >= 100);
+                num_flushes += 1;
+                segment_size = 0;
+            }
+        }
+        assert_eq!(num_flushes, 5);
+    }
+
+    #[test]
+    fn test_memory_accountant_unregister() {
+        let accountant = Arc::new(MemoryAccountant::new(100, 1));
+        let worker = accountant.register();
+        worker.update(usage(20));
+        assert_eq!(accountant.memory_usage().total(), 30);
+        drop(worker);
+        assert_eq!(accountant.memory_usage(), MemoryUsage::default());
+    }
+}
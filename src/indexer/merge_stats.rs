@@ -0,0 +1,254 @@
+//! History of the merges of an index, to quantify the write amplification of its settings.
+//!
+//! The statistics of the last [`MERGE_STATS_HISTORY_CAPACITY`] merges are persisted in the
+//! `.merge_stats.json` file of the index directory, by the index writer, as the merges end.
+use std::collections::VecDeque;
+use std::io;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use common::HasLen;
+use serde::{Deserialize, Serialize};
+
+use crate::core::MERGE_STATS_FILEPATH;
+use crate::directory::error::OpenReadError;
+use crate::directory::Directory;
+use crate::index::{Index, SegmentComponent, SegmentId, SegmentMeta};
+
+/// Number of merges kept in the merge statistics history of an index.
+pub const MERGE_STATS_HISTORY_CAPACITY: usize = 256;
+
+/// Statistics about a merge.
+///
+/// Field rebuilds (see [`IndexWriter::rebuild_field()`](crate::IndexWriter::rebuild_field)) are
+/// recorded as merges of a single segment.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeStats {
+    /// Time at which the merge ended, in seconds since the epoch.
+    pub timestamp_secs: u64,
+    /// The merged segments.
+    pub input_segment_ids: Vec<SegmentId>,
+    /// The segment resulting from the merge.
+    pub output_segment_id: SegmentId,
+    /// Size of the merged segments.
+    pub num_input_bytes: u64,
+    /// Size of the segment resulting from the merge.
+    pub num_output_bytes: u64,
+    /// Number of documents of the merged segments, deleted documents included.
+    pub num_input_docs: u64,
+    /// Number of documents of the segment resulting from the merge.
+    pub num_output_docs: u64,
+    /// Time spent merging.
+    pub duration: Duration,
+}
+
+impl MergeStats {
+    /// Number of deleted documents purged by the merge.
+    pub fn num_deleted_docs_reclaimed(&self) -> u64 {
+        self.num_input_docs.saturating_sub(self.num_output_docs)
+    }
+}
+
+/// The statistics of the last merges of an index, as returned by
+/// [`Index::merge_stats_history()`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeStatsHistory {
+    merges: VecDeque<MergeStats>,
+}
+
+impl MergeStatsHistory {
+    /// Returns the statistics of the last merges, from the oldest to the most recent one.
+    pub fn merges(&self) -> impl Iterator<Item = &MergeStats> + '_ {
+        self.merges.iter()
+    }
+
+    /// Number of merges in the history.
+    pub fn len(&self) -> usize {
+        self.merges.len()
+    }
+
+    /// Returns true if no merge was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.merges.is_empty()
+    }
+
+    /// Total number of bytes written by the merges of the history.
+    pub fn num_bytes_written(&self) -> u64 {
+        self.merges.iter().map(|merge| merge.num_output_bytes).sum()
+    }
+
+    /// Total number of deleted documents purged by the merges of the history.
+    pub fn num_deleted_docs_reclaimed(&self) -> u64 {
+        self.merges
+            .iter()
+            .map(MergeStats::num_deleted_docs_reclaimed)
+            .sum()
+    }
+
+    /// Total time spent merging by the merges of the history.
+    pub fn total_duration(&self) -> Duration {
+        self.merges.iter().map(|merge| merge.duration).sum()
+    }
+
+    fn push(&mut self, merge_stats: MergeStats) {
+        if self.merges.len() == MERGE_STATS_HISTORY_CAPACITY {
+            self.merges.pop_front();
+        }
+        self.merges.push_back(merge_stats);
+    }
+}
+
+/// Loads the merge statistics history of a directory, which is empty if no merge was recorded.
+pub(crate) fn load_merge_stats_history(
+    directory: &dyn Directory,
+) -> crate::Result<MergeStatsHistory> {
+    match directory.atomic_read(&MERGE_STATS_FILEPATH) {
+        Ok(history_json) => Ok(serde_json::from_slice(&history_json)?),
+        Err(OpenReadError::FileDoesNotExist(_)) => Ok(MergeStatsHistory::default()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Appends a merge to the history of the directory, evicting the oldest merge if the history
+/// is full.
+///
+/// This should only be called from the segment updater thread.
+pub(crate) fn record_merge_stats(
+    directory: &dyn Directory,
+    merge_stats: MergeStats,
+) -> crate::Result<()> {
+    let mut history = load_merge_stats_history(directory)?;
+    history.push(merge_stats);
+    let history_json = serde_json::to_vec(&history)?;
+    directory.atomic_write(&MERGE_STATS_FILEPATH, &history_json)?;
+    Ok(())
+}
+
+fn segment_num_bytes(index: &Index, segment_meta: &SegmentMeta) -> io::Result<u64> {
+    let mut num_bytes = 0u64;
+    for &component in SegmentComponent::iterator() {
+        let path = segment_meta.relative_path(component);
+        match index.directory().open_read(&path) {
+            Ok(file) => num_bytes += file.len() as u64,
+            Err(OpenReadError::FileDoesNotExist(_)) => {}
+            Err(err) => return Err(io::Error::other(err)),
+        }
+    }
+    Ok(num_bytes)
+}
+
+/// Measures a merge, from the moment its input segments are known.
+pub(crate) struct MergeStatsRecorder {
+    start: Instant,
+    input_segment_metas: Vec<SegmentMeta>,
+}
+
+impl MergeStatsRecorder {
+    pub fn start(input_segment_metas: Vec<SegmentMeta>) -> MergeStatsRecorder {
+        MergeStatsRecorder {
+            start: Instant::now(),
+            input_segment_metas,
+        }
+    }
+
+    /// Computes the statistics of the merge, once the output segment has been written.
+    pub fn finish(
+        self,
+        index: &Index,
+        output_segment_meta: &SegmentMeta,
+    ) -> io::Result<MergeStats> {
+        let duration = self.start.elapsed();
+        let mut num_input_bytes = 0u64;
+        for segment_meta in &self.input_segment_metas {
+            num_input_bytes += segment_num_bytes(index, segment_meta)?;
+        }
+        Ok(MergeStats {
+            timestamp_secs: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or(0),
+            input_segment_ids: self
+                .input_segment_metas
+                .iter()
+                .map(SegmentMeta::id)
+                .collect(),
+            output_segment_id: output_segment_meta.id(),
+            num_input_bytes,
+            num_output_bytes: segment_num_bytes(index, output_segment_meta)?,
+            num_input_docs: self
+                .input_segment_metas
+                .iter()
+                .map(|segment_meta| u64::from(segment_meta.max_doc()))
+                .sum(),
+            num_output_docs: u64::from(output_segment_meta.max_doc()),
+            duration,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MERGE_STATS_HISTORY_CAPACITY;
+    use crate::index::SegmentId;
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, STRING, TEXT};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_merge_stats_history() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        assert!(index.merge_stats_history()?.is_empty());
+
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0..4 {
+            index_writer.add_document(doc!(id => format!("a{i}"), body => "hello world"))?;
+            index_writer.add_document(doc!(id => format!("b{i}"), body => "hello"))?;
+            index_writer.commit()?;
+        }
+        index_writer.delete_term(Term::from_field_text(id, "a0"));
+        index_writer.commit()?;
+        let segment_ids: Vec<SegmentId> = index.searchable_segment_ids()?;
+        let merged_segment_meta = index_writer.merge(&segment_ids).wait()?.unwrap();
+        index_writer.wait_merging_threads()?;
+
+        let history = index.merge_stats_history()?;
+        assert_eq!(history.len(), 1);
+        let merge_stats = history.merges().next().unwrap();
+        assert_eq!(merge_stats.output_segment_id, merged_segment_meta.id());
+        assert_eq!(merge_stats.input_segment_ids.len(), 4);
+        assert_eq!(merge_stats.num_input_docs, 8);
+        assert_eq!(merge_stats.num_output_docs, 7);
+        assert_eq!(merge_stats.num_deleted_docs_reclaimed(), 1);
+        assert!(merge_stats.num_input_bytes > 0);
+        assert!(merge_stats.num_output_bytes > 0);
+        assert_eq!(history.num_bytes_written(), merge_stats.num_output_bytes);
+        assert_eq!(history.num_deleted_docs_reclaimed(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_stats_history_capacity() {
+        let mut history = super::MergeStatsHistory::default();
+        for num_docs in 0..MERGE_STATS_HISTORY_CAPACITY as u64 + 2 {
+            history.push(super::MergeStats {
+                timestamp_secs: 0,
+                input_segment_ids: Vec::new(),
+                output_segment_id: SegmentId::generate_random(),
+                num_input_bytes: 0,
+                num_output_bytes: 1,
+                num_input_docs: num_docs,
+                num_output_docs: num_docs,
+                duration: Default::default(),
+            });
+        }
+        assert_eq!(history.len(), MERGE_STATS_HISTORY_CAPACITY);
+        assert_eq!(history.merges().next().unwrap().num_input_docs, 2);
+        assert_eq!(
+            history.num_bytes_written(),
+            MERGE_STATS_HISTORY_CAPACITY as u64
+        );
+    }
+}
@@ -1,7 +1,8 @@
 use std::sync::Arc;
 
 use columnar::{
-    ColumnType, ColumnarReader, MergeRowOrder, RowAddr, ShuffleMergeOrder, StackMergeOrder,
+    ColumnType, ColumnarReader, DynamicColumn, MergeRowOrder, RowAddr, ShuffleMergeOrder,
+    StackMergeOrder,
 };
 use common::ReadOnlyBitSet;
 use itertools::Itertools;
@@ -10,6 +11,9 @@ use measure_time::debug_time;
 use crate::directory::WritePtr;
 use crate::docset::{DocSet, TERMINATED};
 use crate::error::DataCorruption;
+use crate::fastfield::decay::{
+    decay_factor, decayed_fields, new_decay_reference_timestamp, scale_f64_column,
+};
 use crate::fastfield::AliveBitSet;
 use crate::fieldnorm::{FieldNormReader, FieldNormReaders, FieldNormsSerializer, FieldNormsWriter};
 use crate::index::{Segment, SegmentComponent, SegmentReader};
@@ -17,9 +21,9 @@ use crate::indexer::doc_id_mapping::{MappingType, SegmentDocIdMapping};
 use crate::indexer::SegmentSerializer;
 use crate::postings::{InvertedIndexSerializer, Postings, SegmentPostings};
 use crate::schema::{value_type_to_column_type, Field, FieldType, Schema};
-use crate::store::StoreWriter;
+use crate::store::{StoreWriter, DOC_STORE_VERSION};
 use crate::termdict::{TermMerger, TermOrdinal};
-use crate::{DocAddress, DocId, InvertedIndexReader};
+use crate::{DateTime, DocAddress, DocId, InvertedIndexReader, TantivyDocument};
 
 /// Segment's max doc must be `< MAX_DOC_LIMIT`.
 ///
@@ -80,6 +84,7 @@ pub struct IndexMerger {
     schema: Schema,
     pub(crate) readers: Vec<SegmentReader>,
     max_doc: u32,
+    decay_reference_timestamp: Option<DateTime>,
 }
 
 struct DeltaComputer {
@@ -183,15 +188,24 @@ impl IndexMerger {
                 "The segment resulting from this merge would have {max_doc} docs,which exceeds \
                  the limit {MAX_DOC_LIMIT}."
             );
-            return Err(crate::TantivyError::InvalidArgument(err_msg));
+            return Err(crate::TantivyError::InvalidArgument(err_msg.into()));
         }
+        let decay_reference_timestamp = new_decay_reference_timestamp(&schema);
         Ok(IndexMerger {
             schema,
             readers,
             max_doc,
+            decay_reference_timestamp,
         })
     }
 
+    /// Returns the decay reference timestamp of the merged segment.
+    ///
+    /// The values of the decayed counters are decayed to this time upon merge.
+    pub(crate) fn decay_reference_timestamp(&self) -> Option<DateTime> {
+        self.decay_reference_timestamp
+    }
+
     fn write_fieldnorms(
         &self,
         mut fieldnorms_serializer: FieldNormsSerializer,
@@ -230,10 +244,38 @@ impl IndexMerger {
             .map(|reader| reader.fast_fields().columnar())
             .collect();
         let merge_row_order = convert_to_merge_order(&columnars[..], doc_id_mapping);
-        columnar::merge_columnar(
+        let Some(merge_timestamp) = self.decay_reference_timestamp else {
+            columnar::merge_columnar(
+                &columnars[..],
+                &required_columns,
+                merge_row_order,
+                fast_field_wrt,
+            )?;
+            return Ok(());
+        };
+        let decayed_fields = decayed_fields(&self.schema);
+        let decay_column = |columnar_ord: usize, column_name: &str, column: DynamicColumn| {
+            let Some((_, half_life)) = decayed_fields
+                .iter()
+                .find(|(field_name, _)| *field_name == column_name)
+            else {
+                return Ok(column);
+            };
+            let DynamicColumn::F64(column) = column else {
+                return Ok(column);
+            };
+            let segment_timestamp = self.readers[columnar_ord]
+                .fast_fields()
+                .decay_reference_timestamp()
+                .unwrap_or(merge_timestamp);
+            let factor = decay_factor(*half_life, segment_timestamp, merge_timestamp);
+            Ok(DynamicColumn::F64(scale_f64_column(column, factor)))
+        };
+        columnar::merge_columnar_with_column_mapper(
             &columnars[..],
             &required_columns,
             merge_row_order,
+            &decay_column,
             fast_field_wrt,
         )?;
         Ok(())
@@ -507,8 +549,17 @@ impl IndexMerger {
                     //
                     // take 7 in order to not walk over all checkpoints.
                     || store_reader.block_checkpoints().take(7).count() < 6
+                    || store_reader.doc_store_version() != DOC_STORE_VERSION
                     || store_reader.decompressor() != store_writer.compressor().into()
             {
+                if store_reader.doc_store_version() != DOC_STORE_VERSION {
+                    // Documents written with an older version of the doc store need to be
+                    // serialized again, as their raw bytes use the older format.
+                    for doc_res in store_reader.iter::<TantivyDocument>(reader.alive_bitset()) {
+                        store_writer.store(&doc_res?, &self.schema)?;
+                    }
+                    continue;
+                }
                 for doc_bytes_res in store_reader.iter_raw(reader.alive_bitset()) {
                     let doc_bytes = doc_bytes_res?;
                     store_writer.store_bytes(&doc_bytes)?;
@@ -4,21 +4,30 @@
 //! `IndexWriter` is the main entry point for that, which created from
 //! [`Index::writer`](crate::Index::writer).
 
+mod bulk_loader;
 pub(crate) mod delete_queue;
 pub(crate) mod path_to_unordered_id;
 
 pub(crate) mod doc_id_mapping;
 mod doc_opstamp_mapping;
+pub mod field_presence;
+mod field_rebuild;
 mod flat_map_with_buffer;
+mod index_split;
 pub(crate) mod index_writer;
 pub(crate) mod index_writer_status;
+pub mod ingest_metadata;
 mod log_merge_policy;
+mod memory_accountant;
 mod merge_index_test;
 mod merge_operation;
 pub(crate) mod merge_policy;
+pub(crate) mod merge_stats;
 pub(crate) mod merger;
+pub mod near_duplicates;
 pub(crate) mod operation;
 pub(crate) mod prepared_commit;
+pub mod routing;
 mod segment_entry;
 mod segment_manager;
 mod segment_register;
@@ -27,14 +36,19 @@ pub(crate) mod segment_updater;
 pub(crate) mod segment_writer;
 pub(crate) mod single_segment_index_writer;
 mod stamper;
+pub mod writer_fields;
 
 use crossbeam_channel as channel;
 use smallvec::SmallVec;
 
+pub use self::bulk_loader::{BulkCheckpoint, BulkLoader};
+pub use self::index_split::{split_index, SplitRouting};
 pub use self::index_writer::{IndexWriter, IndexWriterOptions};
 pub use self::log_merge_policy::LogMergePolicy;
+pub use self::memory_accountant::MemoryUsage;
 pub use self::merge_operation::MergeOperation;
 pub use self::merge_policy::{MergeCandidate, MergePolicy, NoMergePolicy};
+pub use self::merge_stats::{MergeStats, MergeStatsHistory, MERGE_STATS_HISTORY_CAPACITY};
 use self::operation::AddOperation;
 pub use self::operation::UserOperation;
 pub use self::prepared_commit::PreparedCommit;
@@ -0,0 +1,312 @@
+//! Near-duplicate signatures, populated by the index writer when
+//! [`IndexSettings::near_duplicate_signature`](crate::IndexSettings::near_duplicate_signature) is
+//! set.
+//!
+//! Signatures are computed over the shingles of the configured text fields, that is, the
+//! sequences of `shingle_size` consecutive tokens. They are stored in a fast field, which is
+//! [populated by the index writer](super::writer_fields):
+//! - `_simhash` holds a 64 bits SimHash. The signatures of near-duplicates only differ by a few
+//!   bits.
+//! - `_minhash` holds `num_hashes` MinHash values. The fraction of equal values estimates the
+//!   Jaccard similarity of the shingle sets.
+//!
+//! Use the [`NearDuplicateCollector`](crate::collector::NearDuplicateCollector) to group or
+//! suppress near-duplicates at search time.
+use std::hash::Hasher;
+
+use fnv::FnvHasher;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::document::{Document, Value};
+use crate::schema::{Field, FieldEntry, FieldType, NumericOptions, Schema};
+use crate::tokenizer::TextAnalyzer;
+use crate::TantivyError;
+
+/// Name of the field holding the SimHash signatures.
+pub const SIMHASH_FIELD_NAME: &str = "_simhash";
+/// Name of the field holding the MinHash signatures.
+pub const MINHASH_FIELD_NAME: &str = "_minhash";
+
+/// Defines how near-duplicate signatures are computed.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct NearDuplicateSignatureSettings {
+    /// Names of the text fields the signature is computed from.
+    pub fields: Vec<String>,
+    /// Number of consecutive tokens per shingle.
+    #[serde(default = "default_shingle_size")]
+    pub shingle_size: usize,
+    /// Signature algorithm.
+    #[serde(default)]
+    pub algorithm: SignatureAlgorithm,
+}
+
+fn default_shingle_size() -> usize {
+    3
+}
+
+impl NearDuplicateSignatureSettings {
+    /// Creates settings computing a SimHash over the shingles of 3 tokens of the given fields.
+    pub fn for_fields(fields: &[&str]) -> NearDuplicateSignatureSettings {
+        NearDuplicateSignatureSettings {
+            fields: fields.iter().map(|field| field.to_string()).collect(),
+            shingle_size: default_shingle_size(),
+            algorithm: SignatureAlgorithm::default(),
+        }
+    }
+}
+
+/// Algorithm used to compute near-duplicate signatures.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureAlgorithm {
+    /// A single 64 bits signature, stored in the `_simhash` field.
+    ///
+    /// Cheap to store and compare, this works best to detect documents differing by a few words.
+    #[default]
+    SimHash,
+    /// `num_hashes` signatures, stored in the `_minhash` field.
+    ///
+    /// The similarity of MinHash signatures is an unbiased estimate of the Jaccard similarity of
+    /// the shingles of the documents.
+    MinHash {
+        /// Number of hash functions, and therefore of values per document.
+        num_hashes: usize,
+    },
+}
+
+impl SignatureAlgorithm {
+    /// Returns the name of the field holding the signatures.
+    pub fn field_name(&self) -> &'static str {
+        match self {
+            SignatureAlgorithm::SimHash => SIMHASH_FIELD_NAME,
+            SignatureAlgorithm::MinHash { .. } => MINHASH_FIELD_NAME,
+        }
+    }
+}
+
+/// Returns the similarity of two signatures, between 0 and 1.
+///
+/// Signatures are either SimHash signatures, with a single value, or MinHash signatures.
+pub fn signature_similarity(left: &[u64], right: &[u64]) -> f32 {
+    match (left, right) {
+        ([], _) | (_, []) => 0.0,
+        ([left], [right]) => 1.0 - (left ^ right).count_ones() as f32 / 64.0,
+        _ => {
+            let num_hashes = left.len().max(right.len());
+            let num_equal_hashes = left
+                .iter()
+                .zip(right)
+                .filter(|(left, right)| left == right)
+                .count();
+            num_equal_hashes as f32 / num_hashes as f32
+        }
+    }
+}
+
+/// Finalizer of splitmix64, spreading the entropy of the input over all of the bits.
+fn mix(mut val: u64) -> u64 {
+    val = (val ^ (val >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    val = (val ^ (val >> 27)).wrapping_mul(0x94d049bb133111eb);
+    val ^ (val >> 31)
+}
+
+fn token_hash(token: &str) -> u64 {
+    let mut hasher = FnvHasher::default();
+    hasher.write(token.as_bytes());
+    hasher.finish()
+}
+
+/// Returns the hashes of the shingles of the tokens.
+///
+/// Documents with fewer tokens than `shingle_size` have a single shingle.
+fn shingle_hashes(token_hashes: &[u64], shingle_size: usize) -> Vec<u64> {
+    token_hashes
+        .windows(shingle_size.min(token_hashes.len()).max(1))
+        .map(|shingle| {
+            shingle.iter().fold(0u64, |hash, token_hash| {
+                mix(hash.rotate_left(17) ^ token_hash)
+            })
+        })
+        .collect()
+}
+
+fn simhash(shingle_hashes: &[u64]) -> u64 {
+    let mut bit_weights = [0i64; 64];
+    for &shingle_hash in shingle_hashes {
+        for (bit, bit_weight) in bit_weights.iter_mut().enumerate() {
+            if shingle_hash & (1u64 << bit) != 0 {
+                *bit_weight += 1;
+            } else {
+                *bit_weight -= 1;
+            }
+        }
+    }
+    bit_weights
+        .iter()
+        .enumerate()
+        .filter(|(_, bit_weight)| **bit_weight > 0)
+        .fold(0u64, |signature, (bit, _)| signature | (1u64 << bit))
+}
+
+fn minhash(shingle_hashes: &[u64], num_hashes: usize) -> Vec<u64> {
+    (0..num_hashes as u64)
+        .map(|seed| {
+            let seed = mix(seed.wrapping_add(0x9e3779b97f4a7c15));
+            shingle_hashes
+                .iter()
+                .map(|shingle_hash| mix(shingle_hash ^ seed))
+                .min()
+                .unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+/// Returns the entry of the signature field, as added to the schema.
+pub(crate) fn signature_field_entry(settings: &NearDuplicateSignatureSettings) -> FieldEntry {
+    FieldEntry::new_u64(
+        settings.algorithm.field_name().to_string(),
+        NumericOptions::default().set_fast(),
+    )
+}
+
+/// Computes the near-duplicate signatures of the documents.
+pub(crate) struct NearDuplicateSignatureWriter {
+    fields: Vec<Field>,
+    signature_field: Field,
+    shingle_size: usize,
+    algorithm: SignatureAlgorithm,
+    token_hashes: Vec<u64>,
+}
+
+impl NearDuplicateSignatureWriter {
+    /// Returns an error if the settings are invalid, or if the fields are missing or do not have
+    /// the expected type.
+    pub fn for_schema(
+        schema: &Schema,
+        settings: &NearDuplicateSignatureSettings,
+    ) -> crate::Result<NearDuplicateSignatureWriter> {
+        if settings.shingle_size == 0 {
+            return Err(TantivyError::InvalidArgument(
+                "The shingle size needs to be at least 1.".into(),
+            ));
+        }
+        if settings.algorithm == (SignatureAlgorithm::MinHash { num_hashes: 0 }) {
+            return Err(TantivyError::InvalidArgument(
+                "MinHash signatures need at least one hash.".into(),
+            ));
+        }
+        let fields = settings
+            .fields
+            .iter()
+            .map(|field_name| {
+                let field = schema.get_field(field_name)?;
+                let field_entry = schema.get_field_entry(field);
+                match field_entry.field_type() {
+                    FieldType::Str(text_options)
+                        if text_options.get_indexing_options().is_some() =>
+                    {
+                        Ok(field)
+                    }
+                    _ => Err(TantivyError::SchemaError(format!(
+                        "Near-duplicate signatures can only be computed from indexed text fields, \
+                         {field_name:?} is not one."
+                    ))),
+                }
+            })
+            .collect::<crate::Result<Vec<Field>>>()?;
+        let signature_field = schema.get_field(settings.algorithm.field_name())?;
+        let signature_field_entry = schema.get_field_entry(signature_field);
+        if !matches!(signature_field_entry.field_type(), FieldType::U64(_))
+            || !signature_field_entry.is_fast()
+        {
+            return Err(TantivyError::SchemaError(format!(
+                "Near-duplicate signature field {:?} is not a fast u64 field",
+                signature_field_entry.name()
+            )));
+        }
+        Ok(NearDuplicateSignatureWriter {
+            fields,
+            signature_field,
+            shingle_size: settings.shingle_size,
+            algorithm: settings.algorithm,
+            token_hashes: Vec::new(),
+        })
+    }
+
+    pub fn signature_field(&self) -> Field {
+        self.signature_field
+    }
+
+    /// Computes the signature of a document, tokenizing its fields with the given analyzers (one
+    /// per field of the schema).
+    pub fn signature<D: Document>(
+        &mut self,
+        document: &D,
+        per_field_text_analyzers: &mut [TextAnalyzer],
+    ) -> Vec<u64> {
+        self.token_hashes.clear();
+        for (field, value) in document.iter_fields_and_values() {
+            if !self.fields.contains(&field) {
+                continue;
+            }
+            let value = value as D::Value<'_>;
+            let Some(text) = value.as_str() else {
+                continue;
+            };
+            let text_analyzer = &mut per_field_text_analyzers[field.field_id() as usize];
+            let mut token_stream = text_analyzer.token_stream(text);
+            token_stream.process(&mut |token| {
+                self.token_hashes.push(token_hash(&token.text));
+            });
+        }
+        let shingle_hashes = shingle_hashes(&self.token_hashes, self.shingle_size);
+        match self.algorithm {
+            SignatureAlgorithm::SimHash => vec![simhash(&shingle_hashes)],
+            SignatureAlgorithm::MinHash { num_hashes } if !shingle_hashes.is_empty() => {
+                minhash(&shingle_hashes, num_hashes)
+            }
+            SignatureAlgorithm::MinHash { .. } => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{minhash, shingle_hashes, signature_similarity, simhash, token_hash};
+
+    fn shingles(text: &str) -> Vec<u64> {
+        let token_hashes: Vec<u64> = text.split(' ').map(token_hash).collect();
+        shingle_hashes(&token_hashes, 2)
+    }
+
+    #[test]
+    fn test_shingle_hashes() {
+        assert_eq!(shingles("a b c").len(), 2);
+        assert_eq!(shingle_hashes(&[token_hash("a")], 3).len(), 1);
+        assert!(shingle_hashes(&[], 3).is_empty());
+        assert_ne!(shingles("a b"), shingles("b a"));
+    }
+
+    #[test]
+    fn test_signature_similarity() {
+        let text = "the quick brown fox jumps over the lazy dog while the cat sleeps on the mat";
+        let near_duplicate =
+            "the quick brown fox jumps over the lazy dog while the cat naps on the mat";
+        let other = "stock markets rallied today as investors cheered the central bank decision";
+        let simhash_similarity = |left: &str, right: &str| {
+            signature_similarity(&[simhash(&shingles(left))], &[simhash(&shingles(right))])
+        };
+        assert_eq!(simhash_similarity(text, text), 1.0);
+        assert!(simhash_similarity(text, near_duplicate) > simhash_similarity(text, other));
+        let minhash_similarity = |left: &str, right: &str| {
+            signature_similarity(
+                &minhash(&shingles(left), 64),
+                &minhash(&shingles(right), 64),
+            )
+        };
+        assert_eq!(minhash_similarity(text, text), 1.0);
+        assert!(minhash_similarity(text, near_duplicate) > 0.5);
+        assert!(minhash_similarity(text, other) < 0.2);
+        assert_eq!(signature_similarity(&[], &[1]), 0.0);
+    }
+}
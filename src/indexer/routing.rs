@@ -0,0 +1,167 @@
+//! Partitioning of documents, performed by the index writer when it has a
+//! [tenant field](crate::indexer::IndexWriterOptions), or when
+//! [`IndexSettings::routing_field`](crate::IndexSettings::routing_field) is set.
+//!
+//! Each segment produced by the indexer threads then only contains documents of a single
+//! partition, which it is tagged with (see
+//! [`SegmentMeta::partition()`](crate::index::SegmentMeta::partition)). Merges only happen between
+//! segments of the same partition.
+//! * With a tenant field, each value of the field, i.e. each tenant, is a partition of its own.
+//! * With a routing field, the value of the field, i.e. the routing value, is hashed to one of
+//!   `num_partitions` partitions. Unlike tenants, a partition holds the documents of many routing
+//!   values, which makes the routing field suitable for indexes with a very large number of
+//!   tenants.
+//!
+//! Documents without a value for the field, or with several of them, go to segments which are
+//! not tagged.
+//!
+//! Queries wrapped in a [`TenantQuery`](crate::query::TenantQuery), or in a
+//! [`RoutingQuery`](crate::query::RoutingQuery), skip the segments of the other partitions
+//! altogether.
+use std::hash::Hasher;
+
+use fnv::FnvHasher;
+use serde::{Deserialize, Serialize};
+
+use crate::index::{PartitionKey, SegmentPartition};
+use crate::indexer::index_writer::is_raw_text_field;
+use crate::schema::document::{Document, Value};
+use crate::schema::Field;
+use crate::{Index, TantivyError};
+
+/// Defines how documents are routed to partitions.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct RoutingSettings {
+    /// Name of the field holding the routing value of the documents.
+    ///
+    /// The field is required to be a text field indexed with the `raw` tokenizer.
+    pub field: String,
+    /// Number of partitions the routing values are hashed to.
+    pub num_partitions: u32,
+}
+
+impl RoutingSettings {
+    /// Creates settings routing documents to `num_partitions` partitions, given the value of
+    /// `field`.
+    pub fn new(field: &str, num_partitions: u32) -> RoutingSettings {
+        RoutingSettings {
+            field: field.to_string(),
+            num_partitions,
+        }
+    }
+
+    /// Returns the partition of a routing value.
+    ///
+    /// The hash function is stable, so that partitions remain valid across versions.
+    pub fn partition(&self, routing_value: &str) -> u32 {
+        hash_partition(routing_value, self.num_partitions)
+    }
+}
+
+pub(crate) fn hash_partition(routing_value: &str, num_partitions: u32) -> u32 {
+    let mut hasher = FnvHasher::default();
+    hasher.write(routing_value.as_bytes());
+    (hasher.finish() % u64::from(num_partitions.max(1))) as u32
+}
+
+/// Computes the partition of the documents written by the indexer threads.
+pub(crate) struct Partitioner {
+    field: Field,
+    field_name: String,
+    // `None` if each value of the field is a partition of its own.
+    num_partitions_opt: Option<u32>,
+}
+
+impl Partitioner {
+    /// Returns the partitioner of an index writer, given its tenant field and the routing field
+    /// of the index, after checking they are valid.
+    pub fn for_index(
+        index: &Index,
+        tenant_field_opt: Option<Field>,
+    ) -> crate::Result<Option<Partitioner>> {
+        let schema = index.schema();
+        let routing_settings_opt = index.settings().routing_field.as_ref();
+        let (field, num_partitions_opt) = match (tenant_field_opt, routing_settings_opt) {
+            (None, None) => return Ok(None),
+            (Some(_), Some(_)) => {
+                return Err(TantivyError::InvalidArgument(
+                    "An index writer cannot have a tenant field for an index with a routing field."
+                        .into(),
+                ));
+            }
+            (Some(tenant_field), None) => (tenant_field, None),
+            (None, Some(routing_settings)) => {
+                if routing_settings.num_partitions == 0 {
+                    let err_msg = "The number of routing partitions needs to be at least 1, got 0"
+                        .to_string();
+                    return Err(TantivyError::InvalidArgument(err_msg.into()));
+                }
+                let routing_field = schema.get_field(&routing_settings.field)?;
+                (routing_field, Some(routing_settings.num_partitions))
+            }
+        };
+        let field_entry = schema.get_field_entry(field);
+        if !is_raw_text_field(field_entry) {
+            let field_kind = if num_partitions_opt.is_some() {
+                "routing"
+            } else {
+                "tenant"
+            };
+            let err_msg = format!(
+                "The {field_kind} field `{}` needs to be a text field indexed with the `raw` \
+                 tokenizer.",
+                field_entry.name()
+            );
+            return Err(TantivyError::SchemaError(err_msg));
+        }
+        Ok(Some(Partitioner {
+            field,
+            field_name: field_entry.name().to_string(),
+            num_partitions_opt,
+        }))
+    }
+
+    /// Returns the partition of a document, or `None` if the document does not have exactly one
+    /// value for the field.
+    pub fn partition<D: Document>(&self, document: &D) -> Option<SegmentPartition> {
+        let mut values = document
+            .iter_fields_and_values()
+            .filter(|(field, _)| *field == self.field)
+            .map(|(_, value)| value.as_str());
+        let value = values.next()??;
+        if values.next().is_some() {
+            return None;
+        }
+        let key = match self.num_partitions_opt {
+            Some(num_partitions) => PartitionKey::Hash {
+                partition: hash_partition(value, num_partitions),
+                num_partitions,
+            },
+            None => PartitionKey::Value(value.to_string()),
+        };
+        Some(SegmentPartition {
+            field: self.field_name.clone(),
+            key,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoutingSettings;
+
+    #[test]
+    fn test_routing_partition() {
+        let routing_settings = RoutingSettings::new("tenant", 8);
+        let partitions: Vec<u32> = (0..100)
+            .map(|i| routing_settings.partition(&format!("tenant-{i}")))
+            .collect();
+        assert!(partitions.iter().all(|&partition| partition < 8));
+        assert!((0..8).all(|partition| partitions.contains(&partition)));
+        assert_eq!(
+            routing_settings.partition("tenant-0"),
+            routing_settings.partition("tenant-0")
+        );
+        assert_eq!(RoutingSettings::new("tenant", 1).partition("acme"), 0);
+    }
+}
@@ -171,7 +171,7 @@ impl SegmentManager {
             let error_msg = "Merge operation sent for segments that are not all uncommitted or \
                              committed."
                 .to_string();
-            return Err(TantivyError::InvalidArgument(error_msg));
+            return Err(TantivyError::InvalidArgument(error_msg.into()));
         }
 
         Ok(segment_entries)
@@ -197,7 +197,7 @@ impl SegmentManager {
                 crate::TantivyError::InvalidArgument(
                     "The segments that were merged could not be found in the SegmentManager. This \
                      is not necessarily a bug, and can happen after a rollback for instance."
-                        .to_string(),
+                        .into(),
                 )
             })?;
 
@@ -1,6 +1,6 @@
 use std::any::Any;
 use std::borrow::BorrowMut;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io::Write;
 use std::ops::Deref;
 use std::path::PathBuf;
@@ -13,10 +13,14 @@ use super::segment_manager::SegmentManager;
 use crate::core::META_FILEPATH;
 use crate::directory::{Directory, DirectoryClone, GarbageCollectionResult};
 use crate::fastfield::AliveBitSet;
-use crate::index::{Index, IndexMeta, IndexSettings, Segment, SegmentId, SegmentMeta};
+use crate::index::{
+    Index, IndexMeta, IndexSettings, Segment, SegmentId, SegmentMeta, SegmentPartition,
+};
 use crate::indexer::delete_queue::DeleteCursor;
+use crate::indexer::field_rebuild::rebuild_field;
 use crate::indexer::index_writer::advance_deletes;
 use crate::indexer::merge_operation::MergeOperationInventory;
+use crate::indexer::merge_stats::{record_merge_stats, MergeStats, MergeStatsRecorder};
 use crate::indexer::merger::IndexMerger;
 use crate::indexer::segment_manager::SegmentsStatus;
 use crate::indexer::stamper::Stamper;
@@ -24,6 +28,7 @@ use crate::indexer::{
     DefaultMergePolicy, MergeCandidate, MergeOperation, MergePolicy, SegmentEntry,
     SegmentSerializer,
 };
+use crate::schema::Field;
 use crate::{FutureResult, Opstamp, TantivyError};
 
 const PANIC_CAUGHT: &str = "Panic caught in merge thread";
@@ -120,15 +125,45 @@ fn merge(
     let segment_serializer = SegmentSerializer::for_segment(merged_segment.clone())?;
 
     let num_docs = merger.write(segment_serializer)?;
+    let decay_reference_timestamp = merger.decay_reference_timestamp();
 
     let merged_segment_id = merged_segment.id();
 
-    let segment_meta = index.new_segment_meta(merged_segment_id, num_docs);
+    // The merged segment belongs to a partition only if all of the merged segments do.
+    let partition = segment_entries[0].meta().partition();
+    let common_partition = segment_entries
+        .iter()
+        .all(|segment_entry| segment_entry.meta().partition() == partition)
+        .then(|| partition.cloned())
+        .flatten();
+    let segment_meta = index
+        .new_segment_meta(merged_segment_id, num_docs)
+        .with_partition(common_partition)
+        .with_decay_reference_timestamp(decay_reference_timestamp);
     Ok(Some(SegmentEntry::new(segment_meta, delete_cursor, None)))
 }
 
+/// Groups segments by partition, removing the groups that consist of a single segment without
+/// deletes as there is nothing to merge there.
+fn group_segments_by_partition(segments: Vec<SegmentMeta>) -> Vec<Vec<SegmentMeta>> {
+    let mut segments_per_partition: HashMap<Option<SegmentPartition>, Vec<SegmentMeta>> =
+        HashMap::new();
+    for segment in segments {
+        segments_per_partition
+            .entry(segment.partition().cloned())
+            .or_default()
+            .push(segment);
+    }
+    segments_per_partition
+        .into_values()
+        .filter(|segments| segments.len() > 1 || segments[0].num_deleted_docs() > 0)
+        .collect()
+}
+
 /// Advanced: Merges a list of segments from different indices in a new index.
 ///
+/// This shrinks many small indexes into one, e.g. to undo a [`split_index()`](super::split_index).
+///
 /// Returns `TantivyError` if the indices list is empty or their
 /// schemas don't match.
 ///
@@ -138,7 +173,6 @@ fn merge(
 /// This function does NOT check or take the `IndexWriter` is running. It is not
 /// meant to work if you have an `IndexWriter` running for the origin indices, or
 /// the destination `Index`.
-#[doc(hidden)]
 pub fn merge_indices<T: Into<Box<dyn Directory>>>(
     indices: &[Index],
     output_directory: T,
@@ -146,7 +180,7 @@ pub fn merge_indices<T: Into<Box<dyn Directory>>>(
     if indices.is_empty() {
         // If there are no indices to merge, there is no need to do anything.
         return Err(crate::TantivyError::InvalidArgument(
-            "No indices given to merge".to_string(),
+            "No indices given to merge".into(),
         ));
     }
 
@@ -159,7 +193,7 @@ pub fn merge_indices<T: Into<Box<dyn Directory>>>(
         .any(|index| index.settings() != &target_settings)
     {
         return Err(crate::TantivyError::InvalidArgument(
-            "Attempt to merge indices with different index_settings".to_string(),
+            "Attempt to merge indices with different index_settings".into(),
         ));
     }
 
@@ -194,7 +228,7 @@ pub fn merge_filtered_segments<T: Into<Box<dyn Directory>>>(
     if segments.is_empty() {
         // If there are no indices to merge, there is no need to do anything.
         return Err(crate::TantivyError::InvalidArgument(
-            "No segments given to merge".to_string(),
+            "No segments given to merge".into(),
         ));
     }
 
@@ -207,7 +241,7 @@ pub fn merge_filtered_segments<T: Into<Box<dyn Directory>>>(
         .any(|index| index.schema() != target_schema)
     {
         return Err(crate::TantivyError::InvalidArgument(
-            "Attempt to merge different schema indices".to_string(),
+            "Attempt to merge different schema indices".into(),
         ));
     }
 
@@ -223,7 +257,9 @@ pub fn merge_filtered_segments<T: Into<Box<dyn Directory>>>(
     let segment_serializer = SegmentSerializer::for_segment(merged_segment)?;
     let num_docs = merger.write(segment_serializer)?;
 
-    let segment_meta = merged_index.new_segment_meta(merged_segment_id, num_docs);
+    let segment_meta = merged_index
+        .new_segment_meta(merged_segment_id, num_docs)
+        .with_decay_reference_timestamp(merger.decay_reference_timestamp());
 
     let stats = format!(
         "Segments Merge: [{}]",
@@ -487,6 +523,40 @@ impl SegmentUpdater {
     pub fn start_merge(
         &self,
         merge_operation: MergeOperation,
+    ) -> FutureResult<Option<SegmentMeta>> {
+        self.start_segment_rewrite(merge_operation, merge)
+    }
+
+    /// Starts rebuilding `field` in the single segment of `merge_operation`, within
+    /// `memory_budget`.
+    ///
+    /// The rebuilt segment replaces the original one, as the result of a merge would.
+    pub(crate) fn start_field_rebuild(
+        &self,
+        merge_operation: MergeOperation,
+        field: Field,
+        memory_budget: usize,
+    ) -> FutureResult<Option<SegmentMeta>> {
+        self.start_segment_rewrite(
+            merge_operation,
+            move |index, segment_entries, target_opstamp| {
+                let segment_entry = segment_entries
+                    .into_iter()
+                    .next()
+                    .expect("A field rebuild applies to one segment.");
+                rebuild_field(index, segment_entry, target_opstamp, field, memory_budget)
+            },
+        )
+    }
+
+    /// Rewrites the segments of `merge_operation` into at most one segment, in the merge
+    /// thread pool, and replaces them with the result once done.
+    fn start_segment_rewrite(
+        &self,
+        merge_operation: MergeOperation,
+        rewrite: impl FnOnce(&Index, Vec<SegmentEntry>, Opstamp) -> crate::Result<Option<SegmentEntry>>
+            + Send
+            + 'static,
     ) -> FutureResult<Option<SegmentMeta>> {
         assert!(
             !merge_operation.segment_ids().is_empty(),
@@ -509,6 +579,12 @@ impl SegmentUpdater {
         };
 
         info!("Starting merge  - {:?}", merge_operation.segment_ids());
+        let merge_stats_recorder = MergeStatsRecorder::start(
+            segment_entries
+                .iter()
+                .map(|segment_entry| segment_entry.meta().clone())
+                .collect(),
+        );
 
         let (scheduled_result, merging_future_send) =
             FutureResult::create("Merge operation failed.");
@@ -519,7 +595,7 @@ impl SegmentUpdater {
             // as well as which segment is currently in merge and therefore should not be
             // candidate for another merge.
             let merge_panic_res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                merge(
+                rewrite(
                     &segment_updater.index,
                     segment_entries,
                     merge_operation.target_opstamp(),
@@ -547,7 +623,17 @@ impl SegmentUpdater {
             };
             match merge_res {
                 Ok(after_merge_segment_entry) => {
-                    let res = segment_updater.end_merge(merge_operation, after_merge_segment_entry);
+                    let merge_stats = after_merge_segment_entry.as_ref().and_then(|entry| {
+                        merge_stats_recorder
+                            .finish(&segment_updater.index, entry.meta())
+                            .map_err(|err| warn!("Failed to measure merge: {err:?}"))
+                            .ok()
+                    });
+                    let res = segment_updater.end_merge(
+                        merge_operation,
+                        after_merge_segment_entry,
+                        merge_stats,
+                    );
                     let _send_result = merging_future_send.send(res);
                 }
                 Err(merge_error) => {
@@ -574,35 +660,47 @@ impl SegmentUpdater {
     }
 
     fn consider_merge_options(&self) {
-        let (mut committed_segments, mut uncommitted_segments) = self.get_mergeable_segments();
-        if committed_segments.len() == 1 && committed_segments[0].num_deleted_docs() == 0 {
-            committed_segments.clear();
-        }
-        if uncommitted_segments.len() == 1 && uncommitted_segments[0].num_deleted_docs() == 0 {
-            uncommitted_segments.clear();
-        }
+        let (committed_segments, uncommitted_segments) = self.get_mergeable_segments();
 
         // Committed segments cannot be merged with uncommitted_segments.
         // We therefore consider merges using these two sets of segments independently.
+        //
+        // Segments of different partitions, e.g. of different tenants, are never merged together
+        // either.
         let merge_policy = self.get_merge_policy();
 
         let current_opstamp = self.stamper.stamp();
-        let mut merge_candidates: Vec<MergeOperation> = merge_policy
-            .compute_merge_candidates(&uncommitted_segments)
-            .into_iter()
-            .map(|merge_candidate| {
-                MergeOperation::new(&self.merge_operations, current_opstamp, merge_candidate.0)
-            })
-            .collect();
+        let mut merge_candidates: Vec<MergeOperation> = Vec::new();
+        for uncommitted_segments in group_segments_by_partition(uncommitted_segments) {
+            merge_candidates.extend(
+                merge_policy
+                    .compute_merge_candidates(&uncommitted_segments)
+                    .into_iter()
+                    .map(|merge_candidate| {
+                        MergeOperation::new(
+                            &self.merge_operations,
+                            current_opstamp,
+                            merge_candidate.0,
+                        )
+                    }),
+            );
+        }
 
         let commit_opstamp = self.load_meta().opstamp;
-        let committed_merge_candidates = merge_policy
-            .compute_merge_candidates(&committed_segments)
-            .into_iter()
-            .map(|merge_candidate: MergeCandidate| {
-                MergeOperation::new(&self.merge_operations, commit_opstamp, merge_candidate.0)
-            });
-        merge_candidates.extend(committed_merge_candidates);
+        for committed_segments in group_segments_by_partition(committed_segments) {
+            merge_candidates.extend(
+                merge_policy
+                    .compute_merge_candidates(&committed_segments)
+                    .into_iter()
+                    .map(|merge_candidate: MergeCandidate| {
+                        MergeOperation::new(
+                            &self.merge_operations,
+                            commit_opstamp,
+                            merge_candidate.0,
+                        )
+                    }),
+            );
+        }
 
         for merge_operation in merge_candidates {
             // If a merge cannot be started this is not a fatal error.
@@ -616,6 +714,7 @@ impl SegmentUpdater {
         &self,
         merge_operation: MergeOperation,
         mut after_merge_segment_entry: Option<SegmentEntry>,
+        merge_stats: Option<MergeStats>,
     ) -> crate::Result<Option<SegmentMeta>> {
         let segment_updater = self.clone();
         let after_merge_segment_meta = after_merge_segment_entry
@@ -669,6 +768,16 @@ impl SegmentUpdater {
                         .save_metas(previous_metas.opstamp, previous_metas.payload.clone())?;
                 }
 
+                if let Some(merge_stats) = merge_stats {
+                    // The statistics are informative: failing to record them does not fail the
+                    // merge.
+                    if let Err(err) =
+                        record_merge_stats(segment_updater.index.directory(), merge_stats)
+                    {
+                        warn!("Failed to record merge statistics: {err:?}");
+                    }
+                }
+
                 segment_updater.consider_merge_options();
             } // we drop all possible handle to a now useless `SegmentMeta`.
 
@@ -1,21 +1,32 @@
+use std::sync::Arc;
+
 use columnar::MonotonicallyMappableToU64;
 use common::JsonPathWriter;
 use itertools::Itertools;
 use tokenizer_api::BoxTokenStream;
 
+use super::field_presence::{field_presence_field, is_presence_recorded};
+use super::ingest_metadata::{next_ingest_timestamp, IngestMetadataFields};
+use super::near_duplicates::NearDuplicateSignatureWriter;
 use super::operation::AddOperation;
 use crate::fastfield::FastFieldsWriter;
 use crate::fieldnorm::{FieldNormReaders, FieldNormsWriter};
 use crate::index::{Segment, SegmentComponent};
 use crate::indexer::segment_serializer::SegmentSerializer;
+use crate::indexer::MemoryUsage;
 use crate::json_utils::{index_json_value, IndexingPositionsPerPath};
 use crate::postings::{
     compute_table_memory_size, serialize_postings, IndexingContext, IndexingPosition,
     PerFieldPostingsWriter, PostingsWriter,
 };
 use crate::schema::document::{Document, Value};
-use crate::schema::{FieldEntry, FieldType, Schema, Term, DATE_TIME_PRECISION_INDEXED};
-use crate::tokenizer::{FacetTokenizer, PreTokenizedStream, TextAnalyzer, Tokenizer};
+use crate::schema::{
+    Field, FieldEntry, FieldType, Schema, Term, Type, DATE_TIME_PRECISION_INDEXED,
+};
+use crate::tokenizer::{
+    FacetTokenizer, LanguageDetectingAnalyzer, LanguageDetector, PreTokenizedStream, TextAnalyzer,
+    Tokenizer,
+};
 use crate::{DocId, Opstamp, TantivyError};
 
 /// Computes the initial size of the hash table.
@@ -32,13 +43,38 @@ fn compute_initial_table_size(per_thread_memory_budget: usize) -> crate::Result<
         .take_while(|capacity| compute_table_memory_size(*capacity) < table_memory_upper_bound)
         .last()
         .ok_or_else(|| {
-            crate::TantivyError::InvalidArgument(format!(
-                "per thread memory budget (={per_thread_memory_budget}) is too small. Raise the \
-                 memory budget or lower the number of threads."
-            ))
+            crate::TantivyError::InvalidArgument(
+                format!(
+                    "per thread memory budget (={per_thread_memory_budget}) is too small. Raise \
+                     the memory budget or lower the number of threads."
+                )
+                .into(),
+            )
         })
 }
 
+/// Returns the language detector of a [`LanguageDetectingAnalyzer`] along with the field in
+/// which it records the detected language, if any.
+fn language_detector(
+    schema: &Schema,
+    text_analyzer: &TextAnalyzer,
+) -> crate::Result<Option<(Arc<LanguageDetector>, Field)>> {
+    let Some(analyzer) = text_analyzer.downcast_ref::<LanguageDetectingAnalyzer>() else {
+        return Ok(None);
+    };
+    let Some(language_field_name) = analyzer.language_field() else {
+        return Ok(None);
+    };
+    let language_field = schema.get_field(language_field_name)?;
+    let field_entry = schema.get_field_entry(language_field);
+    if !matches!(field_entry.field_type(), FieldType::Str(_)) || !field_entry.is_fast() {
+        return Err(TantivyError::SchemaError(format!(
+            "Language field {language_field_name:?} is not a fast text field"
+        )));
+    }
+    Ok(Some((analyzer.detector().clone(), language_field)))
+}
+
 /// A `SegmentWriter` is in charge of creating segment index from a
 /// set of documents.
 ///
@@ -55,6 +91,12 @@ pub struct SegmentWriter {
     pub(crate) json_positions_per_path: IndexingPositionsPerPath,
     pub(crate) doc_opstamps: Vec<Opstamp>,
     per_field_text_analyzers: Vec<TextAnalyzer>,
+    /// For the fields tokenized by a [`LanguageDetectingAnalyzer`] with a language field, the
+    /// language detector and the field in which the detected language is recorded.
+    per_field_language_detectors: Vec<Option<(Arc<LanguageDetector>, Field)>>,
+    ingest_metadata_fields: Option<IngestMetadataFields>,
+    near_duplicate_signature_writer: Option<NearDuplicateSignatureWriter>,
+    field_presence_field: Option<Field>,
     term_buffer: Term,
     schema: Schema,
 }
@@ -73,6 +115,23 @@ impl SegmentWriter {
         let schema = segment.schema();
         let tokenizer_manager = segment.index().tokenizers().clone();
         let tokenizer_manager_fast_field = segment.index().fast_field_tokenizer().clone();
+        let ingest_metadata_fields = if segment.index().settings().ingest_metadata {
+            Some(IngestMetadataFields::for_schema(&schema)?)
+        } else {
+            None
+        };
+        let near_duplicate_signature_writer = segment
+            .index()
+            .settings()
+            .near_duplicate_signature
+            .as_ref()
+            .map(|settings| NearDuplicateSignatureWriter::for_schema(&schema, settings))
+            .transpose()?;
+        let field_presence_field = if segment.index().settings().field_presence {
+            Some(field_presence_field(&schema)?)
+        } else {
+            None
+        };
         let table_size = compute_initial_table_size(memory_budget_in_bytes)?;
         let segment_serializer = SegmentSerializer::for_segment(segment)?;
         let per_field_postings_writers = PerFieldPostingsWriter::for_schema(&schema);
@@ -98,6 +157,10 @@ impl SegmentWriter {
                 })
             })
             .collect::<Result<Vec<_>, _>>()?;
+        let per_field_language_detectors = per_field_text_analyzers
+            .iter()
+            .map(|text_analyzer| language_detector(&schema, text_analyzer))
+            .collect::<crate::Result<Vec<_>>>()?;
         Ok(Self {
             max_doc: 0,
             ctx: IndexingContext::new(table_size),
@@ -112,6 +175,10 @@ impl SegmentWriter {
             )?,
             doc_opstamps: Vec::with_capacity(1_000),
             per_field_text_analyzers,
+            per_field_language_detectors,
+            ingest_metadata_fields,
+            near_duplicate_signature_writer,
+            field_presence_field,
             term_buffer: Term::with_capacity(16),
             schema,
         })
@@ -137,10 +204,17 @@ impl SegmentWriter {
     /// Returns an estimation of the current memory usage of the segment writer.
     /// If the mem usage exceeds the `memory_budget`, the segment be serialized.
     pub fn mem_usage(&self) -> usize {
-        self.ctx.mem_usage()
-            + self.fieldnorms_writer.mem_usage()
-            + self.fast_field_writers.mem_usage()
-            + self.segment_serializer.mem_usage()
+        self.memory_usage().total()
+    }
+
+    /// Returns the memory usage of the segment writer, broken down by component.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            postings: self.ctx.mem_usage(),
+            store: self.segment_serializer.mem_usage(),
+            fast_fields: self.fast_field_writers.mem_usage(),
+            fieldnorms: self.fieldnorms_writer.mem_usage(),
+        }
     }
 
     fn index_document<D: Document>(&mut self, doc: &D) -> crate::Result<()> {
@@ -152,6 +226,7 @@ impl SegmentWriter {
             .sorted_by_key(|(field, _)| *field)
             .chunk_by(|(field, _)| *field);
 
+        let mut present_fields = Vec::new();
         for (field, field_values) in &vals_grouped_by_field {
             let values = field_values.map(|el| el.1);
 
@@ -166,6 +241,9 @@ impl SegmentWriter {
             if !field_entry.is_indexed() {
                 continue;
             }
+            if self.field_presence_field.is_some() && is_presence_recorded(field_entry) {
+                present_fields.push(field);
+            }
 
             let (term_buffer, ctx) = (&mut self.term_buffer, &mut self.ctx);
             let postings_writer: &mut dyn PostingsWriter =
@@ -196,6 +274,17 @@ impl SegmentWriter {
                         let value = value.as_value();
 
                         let mut token_stream = if let Some(text) = value.as_str() {
+                            if let Some((detector, language_field)) =
+                                &self.per_field_language_detectors[field.field_id() as usize]
+                            {
+                                if let Some(language) = detector.detect(text) {
+                                    self.fast_field_writers.record_str(
+                                        doc_id,
+                                        *language_field,
+                                        language.iso_code(),
+                                    );
+                                }
+                            }
                             let text_analyzer =
                                 &mut self.per_field_text_analyzers[field.field_id() as usize];
                             text_analyzer.token_stream(text)
@@ -337,6 +426,32 @@ impl SegmentWriter {
                         self.fieldnorms_writer.record(doc_id, field, num_vals);
                     }
                 }
+                FieldType::Uuid(_) => {
+                    let mut num_vals = 0;
+                    for value in values {
+                        let value = value.as_value();
+
+                        num_vals += 1;
+                        let uuid = value.as_uuid().ok_or_else(make_schema_error)?;
+                        term_buffer.set_uuid(uuid);
+                        postings_writer.subscribe(doc_id, 0u32, term_buffer, ctx);
+                    }
+                    if field_entry.has_fieldnorms() {
+                        self.fieldnorms_writer.record(doc_id, field, num_vals);
+                    }
+                }
+            }
+        }
+        if let Some(field_presence_field) = self.field_presence_field {
+            let postings_writer = self
+                .per_field_postings_writers
+                .get_for_field_mut(field_presence_field);
+            for field in present_fields {
+                self.term_buffer
+                    .clear_with_field_and_type(Type::Str, field_presence_field);
+                self.term_buffer
+                    .append_bytes(self.schema.get_field_name(field).as_bytes());
+                postings_writer.subscribe(doc_id, 0u32, &self.term_buffer, &mut self.ctx);
             }
         }
         Ok(())
@@ -352,6 +467,25 @@ impl SegmentWriter {
         let AddOperation { document, opstamp } = add_operation;
         self.doc_opstamps.push(opstamp);
         self.fast_field_writers.add_document(&document)?;
+        if let Some(ingest_metadata_fields) = self.ingest_metadata_fields {
+            self.fast_field_writers.record_datetime(
+                self.max_doc,
+                ingest_metadata_fields.ingest_timestamp,
+                next_ingest_timestamp(),
+            );
+            self.fast_field_writers.record_u64(
+                self.max_doc,
+                ingest_metadata_fields.opstamp,
+                opstamp,
+            );
+        }
+        if let Some(signature_writer) = &mut self.near_duplicate_signature_writer {
+            let signature_field = signature_writer.signature_field();
+            for value in signature_writer.signature(&document, &mut self.per_field_text_analyzers) {
+                self.fast_field_writers
+                    .record_u64(self.max_doc, signature_field, value);
+            }
+        }
         self.index_document(&document)?;
         let doc_writer = self.segment_serializer.get_store_writer();
         doc_writer.store(&document, &self.schema)?;
@@ -911,6 +1045,31 @@ mod tests {
         assert_eq!(subsub_columns.len(), 1);
     }
 
+    #[test]
+    fn test_json_uuid() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let json_field = schema_builder.add_json_field("json", STRING | FAST);
+        let schema = schema_builder.build();
+        let uuid = uuid::Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+        let json_val = OwnedValue::Object(vec![("id".to_string(), OwnedValue::Uuid(uuid))]);
+        let index = Index::create_in_ram(schema.clone());
+        let mut writer: IndexWriter = index.writer_for_tests()?;
+        writer.add_document(doc!(json_field => json_val))?;
+        writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        // Uuids are indexed as their string representation.
+        let query = QueryParser::for_index(&index, vec![json_field])
+            .parse_query("json.id:\"67e55044-10b1-426f-9247-bb680e5fe0c8\"")?;
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        let segment_reader = searcher.segment_reader(0u32);
+        let column = segment_reader.fast_fields().str("json.id")?.unwrap();
+        let mut uuid_str = String::new();
+        column.ord_to_str(0, &mut uuid_str)?;
+        assert_eq!(uuid_str, uuid.to_string());
+        Ok(())
+    }
+
     #[test]
     fn test_json_term_with_numeric_merge_panic_regression_bug_2283() {
         // https://github.com/quickwit-oss/tantivy/issues/2283
@@ -2,6 +2,7 @@ use std::marker::PhantomData;
 
 use crate::indexer::operation::AddOperation;
 use crate::indexer::segment_updater::save_metas;
+use crate::indexer::writer_fields::WriterFields;
 use crate::indexer::SegmentWriter;
 use crate::schema::document::Document;
 use crate::{Directory, Index, IndexMeta, Opstamp, Segment, TantivyDocument};
@@ -9,6 +10,7 @@ use crate::{Directory, Index, IndexMeta, Opstamp, Segment, TantivyDocument};
 #[doc(hidden)]
 pub struct SingleSegmentIndexWriter<D: Document = TantivyDocument> {
     segment_writer: SegmentWriter,
+    writer_fields: WriterFields,
     segment: Segment,
     opstamp: Opstamp,
     _phantom: PhantomData<D>,
@@ -18,8 +20,10 @@ impl<D: Document> SingleSegmentIndexWriter<D> {
     pub fn new(index: Index, mem_budget: usize) -> crate::Result<Self> {
         let segment = index.new_segment();
         let segment_writer = SegmentWriter::for_segment(mem_budget, segment.clone())?;
+        let writer_fields = WriterFields::for_schema(&index.schema(), index.settings())?;
         Ok(Self {
             segment_writer,
+            writer_fields,
             segment,
             opstamp: 0,
             _phantom: PhantomData,
@@ -31,6 +35,7 @@ impl<D: Document> SingleSegmentIndexWriter<D> {
     }
 
     pub fn add_document(&mut self, document: D) -> crate::Result<()> {
+        self.writer_fields.check_document(&document)?;
         let opstamp = self.opstamp;
         self.opstamp += 1;
         self.segment_writer
@@ -0,0 +1,190 @@
+//! Fields populated by the index writer, given the [`IndexSettings`]:
+//! - the [`ingest_metadata`](super::ingest_metadata) fields,
+//! - the [`field_presence`](super::field_presence) field,
+//! - the [`near_duplicates`](super::near_duplicates) signature field.
+//!
+//! These fields are added to the schema upon index creation, unless the schema already defines
+//! them with the expected type. Documents must not contain any value for them:
+//! [`IndexWriter::add_document()`](crate::IndexWriter::add_document) and
+//! [`IndexWriter::run()`](crate::IndexWriter::run) reject such documents with a
+//! [`TantivyError::SchemaError`], and the index writer remains usable.
+use super::field_presence::{field_presence_field, field_presence_field_entry};
+use super::ingest_metadata::{ingest_metadata_field_entries, IngestMetadataFields};
+use super::near_duplicates::{signature_field_entry, NearDuplicateSignatureWriter};
+use crate::index::IndexSettings;
+use crate::schema::document::Document;
+use crate::schema::{Field, FieldEntry, Schema};
+use crate::TantivyError;
+
+/// Returns the schema with the fields populated by the index writer appended, unless they are
+/// already defined.
+pub(crate) fn with_writer_fields(
+    schema: Schema,
+    settings: &IndexSettings,
+) -> crate::Result<Schema> {
+    let mut field_entries: Vec<FieldEntry> = Vec::new();
+    if settings.ingest_metadata {
+        field_entries.extend(ingest_metadata_field_entries());
+    }
+    if settings.field_presence {
+        field_entries.push(field_presence_field_entry());
+    }
+    if let Some(signature_settings) = &settings.near_duplicate_signature {
+        field_entries.push(signature_field_entry(signature_settings));
+    }
+    field_entries.retain(|field_entry| schema.get_field(field_entry.name()).is_err());
+    let schema = if field_entries.is_empty() {
+        schema
+    } else {
+        let mut schema_builder = Schema::builder();
+        for (_, field_entry) in schema.fields() {
+            schema_builder.add_field(field_entry.clone());
+        }
+        for field_entry in field_entries {
+            schema_builder.add_field(field_entry);
+        }
+        schema_builder.build()
+    };
+    WriterFields::for_schema(&schema, settings)?;
+    Ok(schema)
+}
+
+/// The fields of a schema which are populated by the index writer.
+#[derive(Clone)]
+pub(crate) struct WriterFields {
+    schema: Schema,
+    fields: Vec<Field>,
+}
+
+impl WriterFields {
+    /// Returns the fields populated by the index writer given the settings, or an error if they
+    /// are missing or do not have the expected type.
+    pub fn for_schema(schema: &Schema, settings: &IndexSettings) -> crate::Result<WriterFields> {
+        let mut fields = Vec::new();
+        if settings.ingest_metadata {
+            let ingest_metadata_fields = IngestMetadataFields::for_schema(schema)?;
+            fields.push(ingest_metadata_fields.ingest_timestamp);
+            fields.push(ingest_metadata_fields.opstamp);
+        }
+        if settings.field_presence {
+            fields.push(field_presence_field(schema)?);
+        }
+        if let Some(signature_settings) = &settings.near_duplicate_signature {
+            let signature_writer =
+                NearDuplicateSignatureWriter::for_schema(schema, signature_settings)?;
+            fields.push(signature_writer.signature_field());
+        }
+        Ok(WriterFields {
+            schema: schema.clone(),
+            fields,
+        })
+    }
+
+    /// Returns an error if the document has a value for one of the fields.
+    pub fn check_document<D: Document>(&self, document: &D) -> crate::Result<()> {
+        if self.fields.is_empty() {
+            return Ok(());
+        }
+        if let Some((field, _)) = document
+            .iter_fields_and_values()
+            .find(|(field, _)| self.fields.contains(field))
+        {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is populated by the index writer",
+                self.schema.get_field_name(field)
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::indexer::field_presence::FIELD_PRESENCE_FIELD_NAME;
+    use crate::indexer::ingest_metadata::{INGEST_TIMESTAMP_FIELD_NAME, OPSTAMP_FIELD_NAME};
+    use crate::indexer::near_duplicates::{NearDuplicateSignatureSettings, SIMHASH_FIELD_NAME};
+    use crate::indexer::UserOperation;
+    use crate::schema::{Schema, FAST, STRING, TEXT};
+    use crate::{DateTime, Index, IndexSettings, IndexWriter, TantivyDocument, TantivyError};
+
+    fn writer_fields_settings() -> IndexSettings {
+        IndexSettings {
+            ingest_metadata: true,
+            field_presence: true,
+            near_duplicate_signature: Some(NearDuplicateSignatureSettings::for_fields(&["text"])),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_writer_fields_reject_client_values() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(writer_fields_settings())
+            .create_in_ram()?;
+        let schema = index.schema();
+        let ingest_timestamp = schema.get_field(INGEST_TIMESTAMP_FIELD_NAME)?;
+        let opstamp = schema.get_field(OPSTAMP_FIELD_NAME)?;
+        let field_presence = schema.get_field(FIELD_PRESENCE_FIELD_NAME)?;
+        let simhash = schema.get_field(SIMHASH_FIELD_NAME)?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        let client_documents: Vec<TantivyDocument> = vec![
+            doc!(text => "a", ingest_timestamp => DateTime::from_timestamp_secs(0)),
+            doc!(text => "a", opstamp => 3u64),
+            doc!(text => "a", field_presence => "text"),
+            doc!(text => "a", simhash => 3u64),
+        ];
+        for client_document in client_documents {
+            assert!(matches!(
+                index_writer.add_document(client_document.clone()),
+                Err(TantivyError::SchemaError(_))
+            ));
+            // The operations of the batch are not applied either.
+            let operations = vec![
+                UserOperation::Add(doc!(text => "b")),
+                UserOperation::Delete(crate::Term::from_field_text(text, "a")),
+                UserOperation::Add(client_document),
+            ];
+            assert!(matches!(
+                index_writer.run(operations),
+                Err(TantivyError::SchemaError(_))
+            ));
+        }
+        index_writer.add_document(doc!(text => "a"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_writer_fields_existing_fields() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("text", TEXT);
+        schema_builder.add_u64_field(OPSTAMP_FIELD_NAME, FAST);
+        schema_builder.add_text_field(FIELD_PRESENCE_FIELD_NAME, STRING);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(writer_fields_settings())
+            .create_in_ram()?;
+        assert_eq!(index.schema().num_fields(), 5);
+
+        for field_name in [
+            OPSTAMP_FIELD_NAME,
+            FIELD_PRESENCE_FIELD_NAME,
+            SIMHASH_FIELD_NAME,
+        ] {
+            let mut schema_builder = Schema::builder();
+            schema_builder.add_text_field("text", TEXT);
+            schema_builder.add_text_field(field_name, TEXT);
+            let index_res = Index::builder()
+                .schema(schema_builder.build())
+                .settings(writer_fields_settings())
+                .create_in_ram();
+            assert!(matches!(index_res, Err(TantivyError::SchemaError(_))));
+        }
+        Ok(())
+    }
+}
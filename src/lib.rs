@@ -165,8 +165,10 @@ mod macros;
 mod future_result;
 
 // Re-exports
+pub use columnar;
 pub use common::DateTime;
-pub use {columnar, query_grammar, time};
+pub use query_grammar;
+pub use time;
 
 pub use crate::error::TantivyError;
 pub use crate::future_result::FutureResult;
@@ -183,11 +185,28 @@ pub mod indexer;
 pub mod error;
 pub mod tokenizer;
 
+#[cfg(feature = "aggregation")]
 pub mod aggregation;
+/// Without the `aggregation` feature, only the errors of the aggregations are exposed, so that
+/// [`TantivyError`] is the same regardless of the enabled features.
+#[cfg(not(feature = "aggregation"))]
+pub mod aggregation {
+    mod error;
+    pub use self::error::AggregationError;
+
+    /// Errors of the bucket aggregations.
+    pub mod bucket {
+        pub use super::error::DateHistogramParseError;
+    }
+}
+#[cfg(feature = "clustering")]
+pub mod clustering;
 pub mod collector;
 pub mod directory;
 pub mod fastfield;
 pub mod fieldnorm;
+#[cfg(feature = "arbitrary")]
+pub mod fuzzing;
 pub mod index;
 pub mod positions;
 pub mod postings;
@@ -198,6 +217,8 @@ pub mod schema;
 pub mod space_usage;
 pub mod store;
 pub mod termdict;
+#[cfg(any(all(test, feature = "query-parser"), feature = "test-utils"))]
+pub mod test_utils;
 
 mod docset;
 mod reader;
@@ -205,7 +226,11 @@ mod reader;
 #[cfg(test)]
 mod compat_tests;
 
-pub use self::reader::{IndexReader, IndexReaderBuilder, ReloadPolicy, Warmer};
+pub use self::reader::{
+    AdmissionPolicy, IndexReader, IndexReaderBuilder, ReloadPolicy, SearchPermit, SearcherHandle,
+    SegmentCache, Warmer,
+};
+#[cfg(feature = "snippet")]
 pub mod snippet;
 
 use std::fmt;
@@ -218,7 +243,9 @@ use serde::{Deserialize, Serialize};
 pub use self::docset::{DocSet, COLLECT_BLOCK_BUFFER_LEN, TERMINATED};
 #[doc(hidden)]
 pub use crate::core::json_utils;
-pub use crate::core::{Executor, Searcher, SearcherGeneration};
+pub use crate::core::{
+    Executor, FeatureMatrix, JsonPathSuggestion, ScopedSearcher, Searcher, SearcherGeneration,
+};
 pub use crate::directory::Directory;
 pub use crate::index::{
     Index, IndexBuilder, IndexMeta, IndexSettings, InvertedIndexReader, Order, Segment,
@@ -228,7 +255,12 @@ pub use crate::indexer::{IndexWriter, SingleSegmentIndexWriter};
 pub use crate::schema::{Document, TantivyDocument, Term};
 
 /// Index format version.
-pub const INDEX_FORMAT_VERSION: u32 = 7;
+///
+/// Version 8 adds the bitmap encoding of posting lists
+/// (see [`PostingsCodec`](crate::schema::PostingsCodec)), and version 9 the positions
+/// checkpoints (see
+/// [`TextFieldIndexing::set_positions_checkpoint_interval()`](crate::schema::TextFieldIndexing::set_positions_checkpoint_interval)).
+pub const INDEX_FORMAT_VERSION: u32 = 9;
 /// Oldest index format version this tantivy version can read.
 pub const INDEX_FORMAT_OLDEST_SUPPORTED_VERSION: u32 = 4;
 
@@ -28,6 +28,18 @@
 //! * *VIntPosDeltas* := *VIntPosDelta*^(*P* % 128).
 //!
 //! The skip widths encoded separately makes it easy and fast to rapidly skip over n positions.
+//!
+//! Positions being delta encoded, reading the positions of a term within a document requires
+//! decoding all of them from the first one. For fields storing
+//! [positions checkpoints](crate::schema::TextFieldIndexing::set_positions_checkpoint_interval),
+//! the positions of a term are preceded by the absolute value of every *I*-th position of the
+//! term within a document, so that the positions after a given position can be read directly:
+//! * *CheckpointedPositions* := *NumCheckpoints* (*OffsetDelta* *Position*)^*NumCheckpoints*
+//!   *Positions*
+//! * *NumCheckpoints* := number of checkpoints encoded as a variable byte integer.
+//! * *OffsetDelta* := offset of the checkpoint position within the positions of the term, minus the
+//!   offset of the previous checkpoint, encoded as a variable byte integer.
+//! * *Position* := absolute value of the checkpoint position encoded as a variable byte integer.
 mod reader;
 mod serializer;
 
@@ -99,6 +111,29 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_position_checkpoints() -> crate::Result<()> {
+        let mut positions_buffer = vec![];
+        let mut serializer =
+            PositionSerializer::new(&mut positions_buffer).with_checkpoint_interval(2);
+        // A document with the positions 1, 3, 4, 9, 10 then a document with the positions 5, 6.
+        serializer.write_positions_delta(&[1u32, 2u32, 1u32, 5u32, 1u32]);
+        serializer.write_positions_delta(&[5u32, 1u32]);
+        serializer.close_term()?;
+        serializer.close()?;
+        let positions_data = OwnedBytes::new(positions_buffer);
+        let mut position_reader = PositionReader::open_with_checkpoints(positions_data)?;
+        assert_eq!(position_reader.checkpoint_before(0..5, 3), None);
+        assert_eq!(position_reader.checkpoint_before(0..5, 4), Some((2, 4)));
+        assert_eq!(position_reader.checkpoint_before(0..5, 9), Some((2, 4)));
+        assert_eq!(position_reader.checkpoint_before(0..5, 100), Some((4, 10)));
+        assert_eq!(position_reader.checkpoint_before(5..7, 100), None);
+        let mut buf = [0u32; 7];
+        position_reader.read(0, &mut buf);
+        assert_eq!(buf, [1u32, 2u32, 1u32, 5u32, 1u32, 5u32, 1u32]);
+        Ok(())
+    }
+
     #[test]
     fn test_empty_position() -> crate::Result<()> {
         let mut positions_buffer = vec![];
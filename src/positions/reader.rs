@@ -1,4 +1,5 @@
 use std::io;
+use std::ops::Range;
 
 use common::{BinarySerializable, VInt};
 
@@ -36,6 +37,9 @@ pub struct PositionReader {
     // These are just copies used for .reset().
     original_bit_widths: OwnedBytes,
     original_positions: OwnedBytes,
+
+    // (offset, position) of the checkpoints of the term, sorted by offset.
+    checkpoints: Vec<(u64, u32)>,
 }
 
 impl PositionReader {
@@ -51,9 +55,49 @@ impl PositionReader {
             anchor_offset: 0u64,
             original_bit_widths: bit_widths,
             original_positions: positions,
+            checkpoints: Vec::new(),
         })
     }
 
+    /// Open and reads the term positions encoded into the positions_data owned bytes, preceded by
+    /// the checkpoints of the term.
+    ///
+    /// See [`PositionSerializer::with_checkpoint_interval()`](
+    /// crate::positions::PositionSerializer::with_checkpoint_interval).
+    pub fn open_with_checkpoints(mut positions_data: OwnedBytes) -> io::Result<PositionReader> {
+        let num_checkpoints = VInt::deserialize(&mut positions_data)?.0 as usize;
+        let mut checkpoints = Vec::with_capacity(num_checkpoints);
+        let mut offset = 0u64;
+        for _ in 0..num_checkpoints {
+            offset += VInt::deserialize(&mut positions_data)?.0;
+            let position = VInt::deserialize(&mut positions_data)?.0 as u32;
+            checkpoints.push((offset, position));
+        }
+        let mut position_reader = PositionReader::open(positions_data)?;
+        position_reader.checkpoints = checkpoints;
+        Ok(position_reader)
+    }
+
+    /// Returns the `(offset, position)` of the last checkpoint within `offsets` whose position is
+    /// lower than or equal to `max_position`, if any.
+    ///
+    /// `offsets` are expected to be the offsets of the positions of the term within a document, so
+    /// that the positions of its checkpoints are increasing.
+    pub fn checkpoint_before(&self, offsets: Range<u64>, max_position: u32) -> Option<(u64, u32)> {
+        let start = self
+            .checkpoints
+            .partition_point(|&(offset, _)| offset < offsets.start);
+        let end = self
+            .checkpoints
+            .partition_point(|&(offset, _)| offset < offsets.end);
+        let doc_checkpoints = &self.checkpoints[start..end];
+        let num_checkpoints_before =
+            doc_checkpoints.partition_point(|&(_, position)| position <= max_position);
+        num_checkpoints_before
+            .checked_sub(1)
+            .map(|checkpoint_ord| doc_checkpoints[checkpoint_ord])
+    }
+
     fn reset(&mut self) {
         self.positions = self.original_positions.clone();
         self.bit_widths = self.original_bit_widths.clone();
@@ -15,6 +15,11 @@ pub struct PositionSerializer<W: io::Write> {
     positions_buffer: Vec<u8>,
     block: Vec<u32>,
     bit_widths: Vec<u8>,
+    checkpoint_interval_opt: Option<u32>,
+    // Number of positions written for the current term.
+    num_positions: u64,
+    // (offset, position) of the checkpoints of the current term.
+    checkpoints: Vec<(u64, u32)>,
 }
 
 impl<W: io::Write> PositionSerializer<W> {
@@ -26,9 +31,23 @@ impl<W: io::Write> PositionSerializer<W> {
             positions_buffer: Vec::with_capacity(128_000),
             block: Vec::with_capacity(128),
             bit_widths: Vec::new(),
+            checkpoint_interval_opt: None,
+            num_positions: 0u64,
+            checkpoints: Vec::new(),
         }
     }
 
+    /// Stores a checkpoint every `checkpoint_interval` positions of a term within a document.
+    ///
+    /// See [`TextFieldIndexing::set_positions_checkpoint_interval()`](
+    /// crate::schema::TextFieldIndexing::set_positions_checkpoint_interval).
+    #[must_use]
+    pub fn with_checkpoint_interval(mut self, checkpoint_interval: u32) -> PositionSerializer<W> {
+        assert!(checkpoint_interval > 0);
+        self.checkpoint_interval_opt = Some(checkpoint_interval);
+        self
+    }
+
     /// Returns the number of bytes written in the positions write object
     /// at this point.
     /// When called before writing the positions of a term, this value is used as
@@ -43,8 +62,19 @@ impl<W: io::Write> PositionSerializer<W> {
         COMPRESSION_BLOCK_SIZE - self.block.len()
     }
 
-    /// Writes all of the given positions delta.
+    /// Writes the positions delta of a term within a document.
     pub fn write_positions_delta(&mut self, mut positions_delta: &[u32]) {
+        if let Some(checkpoint_interval) = self.checkpoint_interval_opt {
+            let mut position = 0u32;
+            for (i, &position_delta) in positions_delta.iter().enumerate() {
+                position += position_delta;
+                if i > 0 && i % checkpoint_interval as usize == 0 {
+                    self.checkpoints
+                        .push((self.num_positions + i as u64, position));
+                }
+            }
+        }
+        self.num_positions += positions_delta.len() as u64;
         while !positions_delta.is_empty() {
             let remaining_block_len = self.remaining_block_len();
             let num_to_write = remaining_block_len.min(positions_delta.len());
@@ -78,6 +108,17 @@ impl<W: io::Write> PositionSerializer<W> {
     /// Close the positions for the current term.
     pub fn close_term(&mut self) -> io::Result<()> {
         self.flush_block();
+        if self.checkpoint_interval_opt.is_some() {
+            VInt(self.checkpoints.len() as u64).serialize(&mut self.positions_wrt)?;
+            let mut previous_offset = 0u64;
+            for &(offset, position) in &self.checkpoints {
+                VInt(offset - previous_offset).serialize(&mut self.positions_wrt)?;
+                VInt(position as u64).serialize(&mut self.positions_wrt)?;
+                previous_offset = offset;
+            }
+            self.checkpoints.clear();
+        }
+        self.num_positions = 0;
         VInt(self.bit_widths.len() as u64).serialize(&mut self.positions_wrt)?;
         self.positions_wrt.write_all(&self.bit_widths[..])?;
         self.positions_wrt.write_all(&self.positions_buffer)?;
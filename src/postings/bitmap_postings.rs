@@ -0,0 +1,329 @@
+//! Posting lists of the fields using the [bitmap postings codec](crate::schema::PostingsCodec).
+//!
+//! With this codec, each posting list starts with a one byte tag:
+//! - `BLOCK_ENCODED`: the regular block encoding follows.
+//! - `BITSET_ENCODED`: the doc ids follow, serialized as a [`BitSet`].
+//!
+//! A term is encoded as a bitset if it is dense enough for the bitset not to be much larger
+//! than its block encoding.
+use std::io::{self, Write};
+
+use common::{BinarySerializable, BitSet, OwnedBytes, TinySet};
+
+use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
+use crate::schema::PostingsCodec;
+use crate::{DocId, TERMINATED};
+
+const BLOCK_ENCODED: u8 = 0;
+const BITSET_ENCODED: u8 = 1;
+
+/// Terms matching less than a document in `MAX_BITSET_SPARSITY` (up to their last document)
+/// keep the block encoding.
+const MAX_BITSET_SPARSITY: u64 = 8;
+
+/// Serializes the posting list of a term using the bitmap postings codec.
+///
+/// `block_encoded_postings` is only called if the term is not encoded as a bitset.
+pub(crate) fn serialize_bitmap_postings<W: Write>(
+    doc_ids: &[DocId],
+    output: &mut W,
+    block_encoded_postings: impl FnOnce(&mut W) -> io::Result<()>,
+) -> io::Result<()> {
+    let Some(&last_doc) = doc_ids.last() else {
+        BLOCK_ENCODED.serialize(output)?;
+        return block_encoded_postings(output);
+    };
+    let num_slots = last_doc as u64 + 1;
+    if (doc_ids.len() as u64) * MAX_BITSET_SPARSITY < num_slots {
+        BLOCK_ENCODED.serialize(output)?;
+        return block_encoded_postings(output);
+    }
+    BITSET_ENCODED.serialize(output)?;
+    let mut bitset = BitSet::with_max_value(last_doc + 1);
+    for &doc_id in doc_ids {
+        bitset.insert(doc_id);
+    }
+    bitset.serialize(output)
+}
+
+/// Splits off the tag of a posting list.
+///
+/// Returns the bitset if the posting list is encoded as such, along with the block encoded
+/// posting list (empty in the former case).
+pub(crate) fn split_into_bitmap_and_block_postings(
+    postings_codec: PostingsCodec,
+    mut bytes: OwnedBytes,
+) -> io::Result<(Option<BitmapPostings>, OwnedBytes)> {
+    if postings_codec == PostingsCodec::Block {
+        return Ok((None, bytes));
+    }
+    match u8::deserialize(&mut bytes)? {
+        BLOCK_ENCODED => Ok((None, bytes)),
+        BITSET_ENCODED => Ok((Some(BitmapPostings::open(bytes)?), OwnedBytes::empty())),
+        tag => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unknown posting list encoding: {tag}."),
+        )),
+    }
+}
+
+/// Cursor over a posting list encoded as a bitset.
+///
+/// Doc ids are decoded by blocks of `COMPRESSION_BLOCK_SIZE` documents, just like block encoded
+/// posting lists. Seeking does not require decoding the skipped documents.
+#[derive(Clone)]
+pub(crate) struct BitmapPostings {
+    tinysets: OwnedBytes,
+    // Doc ids of the current block are greater or equal to `block_start`.
+    block_start: DocId,
+    // All of the doc ids lower than `block_end` are in the current block, or in the previous
+    // ones. Equal to `block_start` as long as the block has not been decoded.
+    block_end: DocId,
+}
+
+impl BitmapPostings {
+    fn open(bytes: OwnedBytes) -> io::Result<BitmapPostings> {
+        if bytes.len() < 4 || (bytes.len() - 4) % 8 != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Invalid bitset posting list length.",
+            ));
+        }
+        let (_max_value, tinysets) = bytes.split(4);
+        Ok(BitmapPostings {
+            tinysets,
+            block_start: 0,
+            block_end: 0,
+        })
+    }
+
+    fn num_buckets(&self) -> usize {
+        self.tinysets.len() / 8
+    }
+
+    fn tinyset(&self, bucket: usize) -> TinySet {
+        let bytes: [u8; 8] = self.tinysets[bucket * 8..][..8].try_into().unwrap();
+        TinySet::deserialize(bytes)
+    }
+
+    /// Moves to the next block.
+    pub fn advance(&mut self) {
+        self.block_start = self.block_end;
+    }
+
+    /// Moves to a block starting with the first doc id greater or equal to `target`, unless the
+    /// current block already contains it.
+    ///
+    /// Returns true if the current block changed.
+    pub fn seek(&mut self, target: DocId) -> bool {
+        if target < self.block_end {
+            return false;
+        }
+        self.block_start = target;
+        self.block_end = target;
+        true
+    }
+
+    /// Decodes the doc ids of the current block into `output`, padding it with `TERMINATED`.
+    ///
+    /// Returns the number of doc ids in the block.
+    pub fn decode_block(&mut self, output: &mut [DocId; COMPRESSION_BLOCK_SIZE]) -> usize {
+        let mut len = 0;
+        let mut bucket = (self.block_start / 64) as usize;
+        if bucket < self.num_buckets() {
+            let mut tinyset = self
+                .tinyset(bucket)
+                .intersect(TinySet::range_greater_or_equal(self.block_start % 64));
+            'decode: loop {
+                while let Some(lowest) = tinyset.pop_lowest() {
+                    output[len] = bucket as DocId * 64 + lowest;
+                    len += 1;
+                    if len == COMPRESSION_BLOCK_SIZE {
+                        break 'decode;
+                    }
+                }
+                bucket += 1;
+                if bucket == self.num_buckets() {
+                    break;
+                }
+                tinyset = self.tinyset(bucket);
+            }
+        }
+        self.block_end = if len == COMPRESSION_BLOCK_SIZE {
+            output[len - 1] + 1
+        } else {
+            TERMINATED
+        };
+        output[len..].fill(TERMINATED);
+        len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::OwnedBytes;
+
+    use super::{serialize_bitmap_postings, split_into_bitmap_and_block_postings};
+    use crate::collector::{Count, TopDocs};
+    use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
+    use crate::query::{BooleanQuery, Occur, Query, TermQuery};
+    use crate::schema::{
+        IndexRecordOption, NumericOptions, PostingsCodec, Schema, TextFieldIndexing, TextOptions,
+    };
+    use crate::{Index, IndexWriter, Term, TERMINATED};
+
+    #[test]
+    fn test_bitmap_postings_sparse_terms_are_block_encoded() {
+        let mut buffer = Vec::new();
+        serialize_bitmap_postings(&[3, 1_000], &mut buffer, |output| {
+            output.extend_from_slice(b"block");
+            Ok(())
+        })
+        .unwrap();
+        let (bitmap_postings, block_postings) =
+            split_into_bitmap_and_block_postings(PostingsCodec::Bitmap, OwnedBytes::new(buffer))
+                .unwrap();
+        assert!(bitmap_postings.is_none());
+        assert_eq!(block_postings.as_slice(), b"block");
+    }
+
+    #[test]
+    fn test_bitmap_postings_decode_and_seek() {
+        let doc_ids: Vec<u32> = (0..1_000).filter(|doc| doc % 3 != 0).collect();
+        let mut buffer = Vec::new();
+        serialize_bitmap_postings(&doc_ids, &mut buffer, |_| unreachable!()).unwrap();
+        let mut bitmap_postings =
+            split_into_bitmap_and_block_postings(PostingsCodec::Bitmap, OwnedBytes::new(buffer))
+                .unwrap()
+                .0
+                .unwrap();
+        let mut seeked_bitmap_postings = bitmap_postings.clone();
+        let mut output = [0u32; COMPRESSION_BLOCK_SIZE];
+        let mut decoded = Vec::new();
+        loop {
+            let len = bitmap_postings.decode_block(&mut output);
+            decoded.extend_from_slice(&output[..len]);
+            if len < COMPRESSION_BLOCK_SIZE {
+                assert!(output[len..].iter().all(|&doc| doc == TERMINATED));
+                break;
+            }
+            bitmap_postings.advance();
+        }
+        assert_eq!(decoded, doc_ids);
+
+        assert!(seeked_bitmap_postings.seek(500));
+        assert_eq!(
+            seeked_bitmap_postings.decode_block(&mut output),
+            COMPRESSION_BLOCK_SIZE
+        );
+        assert_eq!(output[0], 500);
+        assert!(!seeked_bitmap_postings.seek(output[COMPRESSION_BLOCK_SIZE - 1]));
+        assert!(seeked_bitmap_postings.seek(998));
+        assert_eq!(seeked_bitmap_postings.decode_block(&mut output), 1);
+        assert_eq!(output[0], 998);
+        assert!(!seeked_bitmap_postings.seek(5_000));
+    }
+
+    #[test]
+    fn test_bitmap_postings_codec() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let status = schema_builder.add_text_field(
+            "status",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("raw")
+                    .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                    .set_postings_codec(PostingsCodec::Bitmap),
+            ),
+        );
+        let tenant = schema_builder.add_u64_field(
+            "tenant",
+            NumericOptions::default()
+                .set_indexed()
+                .set_postings_codec(PostingsCodec::Bitmap),
+        );
+        let schema = schema_builder.build();
+        let schema_json = serde_json::to_string(&schema).unwrap();
+        assert!(schema_json.contains(r#""postings_codec":"bitmap""#));
+        assert_eq!(
+            serde_json::from_str::<Schema>(&schema_json).unwrap(),
+            schema
+        );
+        assert_eq!(
+            schema
+                .get_field_entry(status)
+                .field_type()
+                .index_record_option(),
+            Some(IndexRecordOption::Basic)
+        );
+
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for doc_id in 0..2_000u64 {
+            let mut doc = doc!(tenant => doc_id % 3);
+            if doc_id % 2 == 0 {
+                doc.add_text(status, "active");
+            }
+            if doc_id % 300 == 0 {
+                doc.add_text(status, "archived");
+            }
+            index_writer.add_document(doc)?;
+            if doc_id == 1_000 {
+                index_writer.commit()?;
+            }
+        }
+        index_writer.commit()?;
+
+        let status_query = |status_value: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(status, status_value),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let tenant_query = |tenant_id: u64| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_u64(tenant, tenant_id),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let expected_count = |status_filter: fn(u64) -> bool, tenant_id: u64| {
+            (0..2_000u64)
+                .filter(|&doc_id| status_filter(doc_id) && doc_id % 3 == tenant_id)
+                .count()
+        };
+        let check_counts = || -> crate::Result<()> {
+            let searcher = index.reader()?.searcher();
+            assert_eq!(searcher.search(&status_query("active"), &Count)?, 1_000);
+            assert_eq!(searcher.search(&status_query("archived"), &Count)?, 7);
+            for tenant_id in 0..3 {
+                let active_query = BooleanQuery::new(vec![
+                    (Occur::Must, status_query("active")),
+                    (Occur::Must, tenant_query(tenant_id)),
+                ]);
+                assert_eq!(
+                    searcher.search(&active_query, &Count)?,
+                    expected_count(|doc_id| doc_id % 2 == 0, tenant_id)
+                );
+                let archived_query = BooleanQuery::new(vec![
+                    (Occur::Must, status_query("archived")),
+                    (Occur::Must, tenant_query(tenant_id)),
+                ]);
+                assert_eq!(
+                    searcher.search(&archived_query, &Count)?,
+                    expected_count(|doc_id| doc_id % 300 == 0, tenant_id)
+                );
+            }
+            let top_docs = searcher.search(&status_query("active"), &TopDocs::with_limit(3))?;
+            assert_eq!(top_docs.len(), 3);
+            Ok(())
+        };
+        check_counts()?;
+
+        let segment_ids = index.searchable_segment_ids()?;
+        assert_eq!(segment_ids.len(), 2);
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        check_counts()?;
+        Ok(())
+    }
+}
@@ -4,10 +4,11 @@ use common::VInt;
 
 use crate::directory::{FileSlice, OwnedBytes};
 use crate::fieldnorm::FieldNormReader;
+use crate::postings::bitmap_postings::{split_into_bitmap_and_block_postings, BitmapPostings};
 use crate::postings::compression::{BlockDecoder, VIntDecoder, COMPRESSION_BLOCK_SIZE};
 use crate::postings::{BlockInfo, FreqReadingOption, SkipReader};
 use crate::query::Bm25Weight;
-use crate::schema::IndexRecordOption;
+use crate::schema::{IndexRecordOption, PostingsCodec};
 use crate::{DocId, Score, TERMINATED};
 
 fn max_score<I: Iterator<Item = Score>>(mut it: I) -> Option<Score> {
@@ -31,6 +32,9 @@ pub struct BlockSegmentPostings {
     doc_freq: u32,
     data: OwnedBytes,
     skip_reader: SkipReader,
+    postings_codec: PostingsCodec,
+    // Set if the posting list is encoded as a bitset, in which case the skip reader is unused.
+    bitmap_postings: Option<BitmapPostings>,
 }
 
 fn decode_bitpacked_block(
@@ -83,6 +87,12 @@ fn split_into_skips_and_postings(
         return Ok((None, bytes));
     }
     let skip_len = VInt::deserialize_u64(&mut bytes)? as usize;
+    if skip_len > bytes.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "The skip list length exceeds the postings data.",
+        ));
+    }
     let (skip_data, postings_data) = bytes.split(skip_len);
     Ok((Some(skip_data), postings_data))
 }
@@ -90,6 +100,7 @@ fn split_into_skips_and_postings(
 impl BlockSegmentPostings {
     /// Opens a `BlockSegmentPostings`.
     /// `doc_freq` is the number of documents in the posting list.
+    /// `postings_codec` is the codec of the field, as defined in the schema.
     /// `record_option` represents the amount of data available according to the schema.
     /// `requested_option` is the amount of data requested by the user.
     /// If for instance, we do not request for term frequencies, this function will not decompress
@@ -97,11 +108,18 @@ impl BlockSegmentPostings {
     pub(crate) fn open(
         doc_freq: u32,
         data: FileSlice,
+        postings_codec: PostingsCodec,
         mut record_option: IndexRecordOption,
         requested_option: IndexRecordOption,
     ) -> io::Result<BlockSegmentPostings> {
         let bytes = data.read_bytes()?;
-        let (skip_data_opt, postings_data) = split_into_skips_and_postings(doc_freq, bytes)?;
+        let (bitmap_postings, bytes) = split_into_bitmap_and_block_postings(postings_codec, bytes)?;
+        let (skip_data_opt, postings_data) = if bitmap_postings.is_some() {
+            // The skip reader then behaves as if all of the docs were in a single block.
+            (None, bytes)
+        } else {
+            split_into_skips_and_postings(doc_freq, bytes)?
+        };
         let skip_reader = match skip_data_opt {
             Some(skip_data) => {
                 let block_count = doc_freq as usize / COMPRESSION_BLOCK_SIZE;
@@ -116,6 +134,9 @@ impl BlockSegmentPostings {
                 }
                 SkipReader::new(skip_data, doc_freq, record_option)
             }
+            None if bitmap_postings.is_some() => {
+                SkipReader::new(OwnedBytes::empty(), 0, record_option)
+            }
             None => SkipReader::new(OwnedBytes::empty(), doc_freq, record_option),
         };
 
@@ -134,6 +155,8 @@ impl BlockSegmentPostings {
             doc_freq,
             data: postings_data,
             skip_reader,
+            postings_codec,
+            bitmap_postings,
         };
         block_segment_postings.load_block();
         Ok(block_segment_postings)
@@ -152,6 +175,10 @@ impl BlockSegmentPostings {
         if let Some(score) = self.block_max_score_cache {
             return score;
         }
+        if self.bitmap_postings.is_some() {
+            // Bitsets do not carry any block max information.
+            return bm25_weight.max_score();
+        }
         if let Some(skip_reader_max_score) = self.skip_reader.block_max_score(bm25_weight) {
             // if we are on a full block, the skip reader should have the block max information
             // for us
@@ -193,12 +220,20 @@ impl BlockSegmentPostings {
     //
     // This does not reset the positions list.
     pub(crate) fn reset(&mut self, doc_freq: u32, postings_data: OwnedBytes) -> io::Result<()> {
-        let (skip_data_opt, postings_data) =
-            split_into_skips_and_postings(doc_freq, postings_data)?;
+        let (bitmap_postings, postings_data) =
+            split_into_bitmap_and_block_postings(self.postings_codec, postings_data)?;
+        self.bitmap_postings = bitmap_postings;
+        let (skip_data_opt, postings_data) = if self.bitmap_postings.is_some() {
+            (None, postings_data)
+        } else {
+            split_into_skips_and_postings(doc_freq, postings_data)?
+        };
         self.data = postings_data;
         self.block_max_score_cache = None;
         self.block_loaded = false;
-        if let Some(skip_data) = skip_data_opt {
+        if self.bitmap_postings.is_some() {
+            self.skip_reader.reset(OwnedBytes::empty(), 0);
+        } else if let Some(skip_data) = skip_data_opt {
             self.skip_reader.reset(skip_data, doc_freq);
         } else {
             self.skip_reader.reset(OwnedBytes::empty(), doc_freq);
@@ -291,6 +326,12 @@ impl BlockSegmentPostings {
     /// If all docs are smaller than target, the block loaded may be empty,
     /// or be the last an incomplete VInt block.
     pub(crate) fn shallow_seek(&mut self, target_doc: DocId) {
+        if let Some(bitmap_postings) = self.bitmap_postings.as_mut() {
+            if bitmap_postings.seek(target_doc) {
+                self.block_loaded = false;
+            }
+            return;
+        }
         if self.skip_reader.seek(target_doc) {
             self.block_max_score_cache = None;
             self.block_loaded = false;
@@ -306,6 +347,12 @@ impl BlockSegmentPostings {
         if self.block_is_loaded() {
             return;
         }
+        if let Some(bitmap_postings) = self.bitmap_postings.as_mut() {
+            self.doc_decoder.output_len =
+                bitmap_postings.decode_block(self.doc_decoder.output_mut());
+            self.block_loaded = true;
+            return;
+        }
         match self.skip_reader.block_info() {
             BlockInfo::BitPacked {
                 doc_num_bits,
@@ -353,7 +400,11 @@ impl BlockSegmentPostings {
 
     /// Advance to the next block.
     pub fn advance(&mut self) {
-        self.skip_reader.advance();
+        if let Some(bitmap_postings) = self.bitmap_postings.as_mut() {
+            bitmap_postings.advance();
+        } else {
+            self.skip_reader.advance();
+        }
         self.block_loaded = false;
         self.block_max_score_cache = None;
         self.load_block();
@@ -370,6 +421,8 @@ impl BlockSegmentPostings {
             doc_freq: 0,
             data: OwnedBytes::empty(),
             skip_reader: SkipReader::new(OwnedBytes::empty(), 0, IndexRecordOption::Basic),
+            postings_codec: PostingsCodec::Block,
+            bitmap_postings: None,
         }
     }
 
@@ -95,6 +95,12 @@ impl BlockDecoder {
         }
     }
 
+    /// Returns the output array, to decode a block without going through the decompression
+    /// methods. `output_len` needs to be set accordingly.
+    pub(crate) fn output_mut(&mut self) -> &mut [u32; COMPRESSION_BLOCK_SIZE] {
+        &mut self.output
+    }
+
     /// Decompress block of sorted integers.
     ///
     /// `strict_delta` depends on what encoding was used. Older version of tantivy never use strict
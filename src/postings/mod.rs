@@ -1,5 +1,6 @@
 //! Postings module (also called inverted index)
 
+mod bitmap_postings;
 mod block_search;
 
 pub(crate) use self::block_search::branchless_binary_search;
@@ -151,6 +152,55 @@ pub(crate) mod tests {
         Ok(())
     }
 
+    #[test]
+    pub fn test_read_postings_many() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer = index.writer_for_tests()?;
+        for i in 0..1_000 {
+            let text = if i % 3 == 0 { "abc be abc" } else { "cd be" };
+            index_writer.add_document(doc!(title => text))?;
+        }
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let inverted_index = searcher.segment_reader(0u32).inverted_index(title)?;
+        let terms: Vec<Term> = ["cd", "missing", "abc", "be", "abc"]
+            .iter()
+            .map(|text| Term::from_field_text(title, text))
+            .collect();
+        let postings_many =
+            inverted_index.read_postings_many(&terms, IndexRecordOption::WithFreqsAndPositions)?;
+        assert_eq!(postings_many.len(), terms.len());
+        assert!(postings_many[1].is_none());
+        let mut positions = Vec::new();
+        let mut expected_positions = Vec::new();
+        for (term, postings) in terms.iter().zip(postings_many) {
+            let Some(mut postings) = postings else {
+                continue;
+            };
+            let mut expected = inverted_index
+                .read_postings(term, IndexRecordOption::WithFreqsAndPositions)?
+                .unwrap();
+            assert_eq!(postings.doc_freq(), expected.doc_freq());
+            loop {
+                assert_eq!(postings.doc(), expected.doc());
+                if postings.doc() == TERMINATED {
+                    break;
+                }
+                assert_eq!(postings.term_freq(), expected.term_freq());
+                postings.positions(&mut positions);
+                expected.positions(&mut expected_positions);
+                assert_eq!(positions, expected_positions);
+                postings.advance();
+                expected.advance();
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     pub fn test_index_max_length_token() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
@@ -31,6 +31,30 @@ pub trait Postings: DocSet + 'static {
     fn positions(&mut self, output: &mut Vec<u32>) {
         self.positions_with_offset(0u32, output);
     }
+
+    /// Returns the positions greater than or equal to `min_position`, offsetted with a given
+    /// value.
+    /// Data will be appended to the output.
+    ///
+    /// Implementations may skip the positions lower than `min_position` without decoding them,
+    /// e.g. using [positions checkpoints](
+    /// crate::schema::TextFieldIndexing::set_positions_checkpoint_interval).
+    fn append_positions_with_offset_from(
+        &mut self,
+        offset: u32,
+        min_position: u32,
+        output: &mut Vec<u32>,
+    ) {
+        let prev_len = output.len();
+        self.append_positions_with_offset(offset, output);
+        remove_positions_before(output, prev_len, offset.saturating_add(min_position));
+    }
+}
+
+/// Removes the positions of `output[start..]` lower than `min_position`.
+pub(crate) fn remove_positions_before(output: &mut Vec<u32>, start: usize, min_position: u32) {
+    let num_positions_before = output[start..].partition_point(|&position| position < min_position);
+    output.drain(start..start + num_positions_before);
 }
 
 impl Postings for Box<dyn Postings> {
@@ -41,4 +65,13 @@ impl Postings for Box<dyn Postings> {
     fn append_positions_with_offset(&mut self, offset: u32, output: &mut Vec<u32>) {
         (**self).append_positions_with_offset(offset, output);
     }
+
+    fn append_positions_with_offset_from(
+        &mut self,
+        offset: u32,
+        min_position: u32,
+        output: &mut Vec<u32>,
+    ) {
+        (**self).append_positions_with_offset_from(offset, min_position, output);
+    }
 }
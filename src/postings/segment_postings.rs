@@ -4,6 +4,7 @@ use crate::docset::DocSet;
 use crate::fastfield::AliveBitSet;
 use crate::positions::PositionReader;
 use crate::postings::compression::COMPRESSION_BLOCK_SIZE;
+use crate::postings::postings::remove_positions_before;
 use crate::postings::{branchless_binary_search, BlockSegmentPostings, Postings};
 use crate::{DocId, TERMINATED};
 
@@ -66,11 +67,16 @@ impl SegmentPostings {
     pub fn create_from_docs(docs: &[u32]) -> SegmentPostings {
         use crate::directory::FileSlice;
         use crate::postings::serializer::PostingsSerializer;
-        use crate::schema::IndexRecordOption;
+        use crate::schema::{IndexRecordOption, PostingsCodec};
         let mut buffer = Vec::new();
         {
-            let mut postings_serializer =
-                PostingsSerializer::new(&mut buffer, 0.0, IndexRecordOption::Basic, None);
+            let mut postings_serializer = PostingsSerializer::new(
+                &mut buffer,
+                0.0,
+                IndexRecordOption::Basic,
+                PostingsCodec::Block,
+                None,
+            );
             postings_serializer.new_term(docs.len() as u32, false);
             for &doc in docs {
                 postings_serializer.write_doc(doc, 1u32);
@@ -82,6 +88,7 @@ impl SegmentPostings {
         let block_segment_postings = BlockSegmentPostings::open(
             docs.len() as u32,
             FileSlice::from(buffer),
+            PostingsCodec::Block,
             IndexRecordOption::Basic,
             IndexRecordOption::Basic,
         )
@@ -98,7 +105,7 @@ impl SegmentPostings {
         use crate::directory::FileSlice;
         use crate::fieldnorm::FieldNormReader;
         use crate::postings::serializer::PostingsSerializer;
-        use crate::schema::IndexRecordOption;
+        use crate::schema::{IndexRecordOption, PostingsCodec};
         use crate::Score;
         let mut buffer: Vec<u8> = Vec::new();
         let fieldnorm_reader = fieldnorms.map(FieldNormReader::for_test);
@@ -118,6 +125,7 @@ impl SegmentPostings {
             &mut buffer,
             average_field_norm,
             IndexRecordOption::WithFreqs,
+            PostingsCodec::Block,
             fieldnorm_reader,
         );
         postings_serializer.new_term(doc_and_tfs.len() as u32, true);
@@ -130,6 +138,7 @@ impl SegmentPostings {
         let block_segment_postings = BlockSegmentPostings::open(
             doc_and_tfs.len() as u32,
             FileSlice::from(buffer),
+            PostingsCodec::Block,
             IndexRecordOption::WithFreqs,
             IndexRecordOption::WithFreqs,
         )
@@ -152,6 +161,22 @@ impl SegmentPostings {
             position_reader,
         }
     }
+
+    /// Returns the offset of the first position of the current document within the positions of
+    /// the term, or `None` if positions are not available.
+    fn positions_read_offset(&self) -> Option<u64> {
+        self.position_reader.as_ref()?;
+        debug_assert!(
+            !self.block_cursor.freqs().is_empty(),
+            "No positions available"
+        );
+        let read_offset = self.block_cursor.position_offset()
+            + (self.block_cursor.freqs()[..self.cur]
+                .iter()
+                .cloned()
+                .sum::<u32>() as u64);
+        Some(read_offset)
+    }
 }
 
 impl DocSet for SegmentPostings {
@@ -240,16 +265,11 @@ impl Postings for SegmentPostings {
     fn append_positions_with_offset(&mut self, offset: u32, output: &mut Vec<u32>) {
         let term_freq = self.term_freq();
         let prev_len = output.len();
-        if let Some(position_reader) = self.position_reader.as_mut() {
-            debug_assert!(
-                !self.block_cursor.freqs().is_empty(),
-                "No positions available"
-            );
-            let read_offset = self.block_cursor.position_offset()
-                + (self.block_cursor.freqs()[..self.cur]
-                    .iter()
-                    .cloned()
-                    .sum::<u32>() as u64);
+        if let Some(read_offset) = self.positions_read_offset() {
+            let position_reader = self
+                .position_reader
+                .as_mut()
+                .expect("Positions are available.");
             // TODO: instead of zeroing the output, we could use MaybeUninit or similar.
             output.resize(prev_len + term_freq as usize, 0u32);
             position_reader.read(read_offset, &mut output[prev_len..]);
@@ -260,6 +280,43 @@ impl Postings for SegmentPostings {
             }
         }
     }
+
+    fn append_positions_with_offset_from(
+        &mut self,
+        offset: u32,
+        min_position: u32,
+        output: &mut Vec<u32>,
+    ) {
+        let checkpoint_opt = self.positions_read_offset().and_then(|read_offset| {
+            let end_offset = read_offset + self.term_freq() as u64;
+            let position_reader = self.position_reader.as_ref()?;
+            let checkpoint =
+                position_reader.checkpoint_before(read_offset..end_offset, min_position)?;
+            Some((checkpoint, end_offset))
+        });
+        let Some(((checkpoint_offset, checkpoint_position), end_offset)) = checkpoint_opt else {
+            let prev_len = output.len();
+            self.append_positions_with_offset(offset, output);
+            remove_positions_before(output, prev_len, offset.saturating_add(min_position));
+            return;
+        };
+        let position_reader = self
+            .position_reader
+            .as_mut()
+            .expect("Positions are available.");
+        // The positions are read from the checkpoint, whose delta is replaced by its absolute
+        // value.
+        let prev_len = output.len();
+        output.resize(prev_len + (end_offset - checkpoint_offset) as usize, 0u32);
+        position_reader.read(checkpoint_offset, &mut output[prev_len..]);
+        let mut cum = offset + checkpoint_position;
+        output[prev_len] = cum;
+        for output_mut in output[prev_len + 1..].iter_mut() {
+            cum += *output_mut;
+            *output_mut = cum;
+        }
+        remove_positions_before(output, prev_len, offset.saturating_add(min_position));
+    }
 }
 
 #[cfg(test)]
@@ -8,10 +8,12 @@ use crate::directory::{CompositeWrite, WritePtr};
 use crate::fieldnorm::FieldNormReader;
 use crate::index::Segment;
 use crate::positions::PositionSerializer;
+use crate::postings::bitmap_postings::serialize_bitmap_postings;
 use crate::postings::compression::{BlockEncoder, VIntEncoder, COMPRESSION_BLOCK_SIZE};
 use crate::postings::skip::SkipSerializer;
 use crate::query::Bm25Weight;
-use crate::schema::{Field, FieldEntry, FieldType, IndexRecordOption, Schema};
+use crate::schema::{Field, FieldEntry, FieldType, IndexRecordOption, PostingsCodec, Schema};
+use crate::termdict::bloom_filter::BloomFilterBuilder;
 use crate::termdict::TermDictionaryBuilder;
 use crate::{DocId, Score};
 
@@ -50,6 +52,7 @@ pub struct InvertedIndexSerializer {
     terms_write: CompositeWrite<WritePtr>,
     postings_write: CompositeWrite<WritePtr>,
     positions_write: CompositeWrite<WritePtr>,
+    bloom_filters: Vec<(Field, BloomFilterBuilder)>,
     schema: Schema,
 }
 
@@ -61,6 +64,7 @@ impl InvertedIndexSerializer {
             terms_write: CompositeWrite::wrap(segment.open_write(Terms)?),
             postings_write: CompositeWrite::wrap(segment.open_write(Postings)?),
             positions_write: CompositeWrite::wrap(segment.open_write(Positions)?),
+            bloom_filters: Vec::new(),
             schema: segment.schema(),
         };
         Ok(inv_index_serializer)
@@ -81,18 +85,32 @@ impl InvertedIndexSerializer {
         let postings_write = self.postings_write.for_field(field);
         let positions_write = self.positions_write.for_field(field);
         let field_type: FieldType = (*field_entry.field_type()).clone();
+        let bloom_filter_builder_opt = if field_type.has_bloom_filter() {
+            self.bloom_filters
+                .push((field, BloomFilterBuilder::default()));
+            self.bloom_filters
+                .last_mut()
+                .map(|(_, bloom_filter_builder)| bloom_filter_builder)
+        } else {
+            None
+        };
         FieldSerializer::create(
             &field_type,
             total_num_tokens,
             term_dictionary_write,
             postings_write,
             positions_write,
+            bloom_filter_builder_opt,
             fieldnorm_reader,
         )
     }
 
     /// Closes the serializer.
-    pub fn close(self) -> io::Result<()> {
+    pub fn close(mut self) -> io::Result<()> {
+        // The bloom filter of the terms of a field follows its term dictionary.
+        for (field, bloom_filter_builder) in &self.bloom_filters {
+            bloom_filter_builder.serialize(self.terms_write.for_field_with_idx(*field, 1))?;
+        }
         self.terms_write.close()?;
         self.postings_write.close()?;
         self.positions_write.close()?;
@@ -106,6 +124,7 @@ pub struct FieldSerializer<'a> {
     term_dictionary_builder: TermDictionaryBuilder<&'a mut CountingWriter<WritePtr>>,
     postings_serializer: PostingsSerializer<&'a mut CountingWriter<WritePtr>>,
     positions_serializer_opt: Option<PositionSerializer<&'a mut CountingWriter<WritePtr>>>,
+    bloom_filter_builder_opt: Option<&'a mut BloomFilterBuilder>,
     current_term_info: TermInfo,
     term_open: bool,
 }
@@ -117,6 +136,7 @@ impl<'a> FieldSerializer<'a> {
         term_dictionary_write: &'a mut CountingWriter<WritePtr>,
         postings_write: &'a mut CountingWriter<WritePtr>,
         positions_write: &'a mut CountingWriter<WritePtr>,
+        bloom_filter_builder_opt: Option<&'a mut BloomFilterBuilder>,
         fieldnorm_reader: Option<FieldNormReader>,
     ) -> io::Result<FieldSerializer<'a>> {
         total_num_tokens.serialize(postings_write)?;
@@ -132,10 +152,17 @@ impl<'a> FieldSerializer<'a> {
             postings_write,
             average_fieldnorm,
             index_record_option,
+            field_type.postings_codec(),
             fieldnorm_reader,
         );
         let positions_serializer_opt = if index_record_option.has_positions() {
-            Some(PositionSerializer::new(positions_write))
+            let positions_serializer = PositionSerializer::new(positions_write);
+            Some(match field_type.positions_checkpoint_interval() {
+                Some(checkpoint_interval) => {
+                    positions_serializer.with_checkpoint_interval(checkpoint_interval)
+                }
+                None => positions_serializer,
+            })
         } else {
             None
         };
@@ -144,6 +171,7 @@ impl<'a> FieldSerializer<'a> {
             term_dictionary_builder,
             postings_serializer,
             positions_serializer_opt,
+            bloom_filter_builder_opt,
             current_term_info: TermInfo::default(),
             term_open: false,
         })
@@ -182,6 +210,9 @@ impl<'a> FieldSerializer<'a> {
         self.postings_serializer.clear();
         self.current_term_info = self.current_term_info();
         self.term_dictionary_builder.insert_key(term)?;
+        if let Some(bloom_filter_builder) = self.bloom_filter_builder_opt.as_mut() {
+            bloom_filter_builder.insert(term);
+        }
         self.postings_serializer
             .new_term(term_doc_freq, record_term_freq);
         Ok(())
@@ -304,6 +335,10 @@ pub struct PostingsSerializer<W: Write> {
     mode: IndexRecordOption,
     fieldnorm_reader: Option<FieldNormReader>,
 
+    postings_codec: PostingsCodec,
+    // Doc ids of the current term, only kept for the bitmap postings codec.
+    term_doc_ids: Vec<DocId>,
+
     bm25_weight: Option<Bm25Weight>,
     avg_fieldnorm: Score, /* Average number of term in the field for that segment.
                            * this value is used to compute the block wand information. */
@@ -315,6 +350,7 @@ impl<W: Write> PostingsSerializer<W> {
         write: W,
         avg_fieldnorm: Score,
         mode: IndexRecordOption,
+        postings_codec: PostingsCodec,
         fieldnorm_reader: Option<FieldNormReader>,
     ) -> PostingsSerializer<W> {
         PostingsSerializer {
@@ -330,6 +366,8 @@ impl<W: Write> PostingsSerializer<W> {
             mode,
 
             fieldnorm_reader,
+            postings_codec,
+            term_doc_ids: Vec::new(),
             bm25_weight: None,
             avg_fieldnorm,
             term_has_freq: false,
@@ -416,6 +454,9 @@ impl<W: Write> PostingsSerializer<W> {
     }
 
     pub fn write_doc(&mut self, doc_id: DocId, term_freq: u32) {
+        if self.postings_codec == PostingsCodec::Bitmap {
+            self.term_doc_ids.push(doc_id);
+        }
         self.block.append_doc(doc_id, term_freq);
         if self.block.is_full() {
             self.write_block();
@@ -449,12 +490,24 @@ impl<W: Write> PostingsSerializer<W> {
             }
             self.block.clear();
         }
-        if doc_freq >= COMPRESSION_BLOCK_SIZE as u32 {
-            let skip_data = self.skip_write.data();
-            VInt(skip_data.len() as u64).serialize(&mut self.output_write)?;
-            self.output_write.write_all(skip_data)?;
+        let skip_data = self.skip_write.data();
+        let postings_data = &self.postings_write[..];
+        let write_block_encoded_postings = |output_write: &mut CountingWriter<W>| {
+            if doc_freq >= COMPRESSION_BLOCK_SIZE as u32 {
+                VInt(skip_data.len() as u64).serialize(output_write)?;
+                output_write.write_all(skip_data)?;
+            }
+            output_write.write_all(postings_data)
+        };
+        match self.postings_codec {
+            PostingsCodec::Block => write_block_encoded_postings(&mut self.output_write)?,
+            PostingsCodec::Bitmap => serialize_bitmap_postings(
+                &self.term_doc_ids,
+                &mut self.output_write,
+                write_block_encoded_postings,
+            )?,
         }
-        self.output_write.write_all(&self.postings_write[..])?;
+        self.term_doc_ids.clear();
         self.skip_write.clear();
         self.postings_write.clear();
         self.bm25_weight = None;
@@ -473,6 +526,7 @@ impl<W: Write> PostingsSerializer<W> {
 
     fn clear(&mut self) {
         self.block.clear();
+        self.term_doc_ids.clear();
         self.last_doc_id_encoded = 0;
     }
 }
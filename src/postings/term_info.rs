@@ -37,6 +37,10 @@ impl FixedSize for TermInfo {
     const SIZE_IN_BYTES: usize = 3 * u32::SIZE_IN_BYTES + 2 * u64::SIZE_IN_BYTES;
 }
 
+fn invalid_term_info_range() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "Term info range overflows")
+}
+
 impl BinarySerializable for TermInfo {
     fn serialize<W: io::Write + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
         self.doc_freq.serialize(writer)?;
@@ -51,10 +55,14 @@ impl BinarySerializable for TermInfo {
         let doc_freq = u32::deserialize(reader)?;
         let postings_start_offset = u64::deserialize(reader)? as usize;
         let postings_num_bytes = u32::deserialize(reader)? as usize;
-        let postings_end_offset = postings_start_offset + postings_num_bytes;
+        let postings_end_offset = postings_start_offset
+            .checked_add(postings_num_bytes)
+            .ok_or_else(invalid_term_info_range)?;
         let positions_start_offset = u64::deserialize(reader)? as usize;
         let positions_num_bytes = u32::deserialize(reader)? as usize;
-        let positions_end_offset = positions_start_offset + positions_num_bytes;
+        let positions_end_offset = positions_start_offset
+            .checked_add(positions_num_bytes)
+            .ok_or_else(invalid_term_info_range)?;
         Ok(TermInfo {
             doc_freq,
             postings_range: postings_start_offset..postings_end_offset,
@@ -116,7 +116,7 @@ where
             Ok(Explanation::new("AutomatonScorer", 1.0))
         } else {
             Err(TantivyError::InvalidArgument(
-                "Document does not exist".to_string(),
+                "Document does not exist".into(),
             ))
         }
     }
@@ -0,0 +1,187 @@
+use super::{BitSetDocSet, ConstScorer, EmptyScorer};
+use crate::docset::DocSet;
+use crate::index::SegmentReader;
+use crate::query::explanation::does_not_match;
+use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::schema::Type;
+use crate::{DocId, Score, TantivyError};
+
+/// Query matching the documents having a given value in a bool fast field, e.g. `is_public`.
+///
+/// The matching documents of a segment are read from the column as a dense bitset
+/// (see [`FastFieldReaders::bool_bitset()`](crate::fastfield::FastFieldReaders::bool_bitset)),
+/// which makes the query a cheap filter to intersect with other queries.
+///
+/// All of the matched documents get the score 1.0.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::BoolFieldQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let is_public = schema_builder.add_bool_field("is_public", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(is_public => true))?;
+/// index_writer.add_document(doc!(is_public => false))?;
+/// index_writer.add_document(doc!(is_public => true))?;
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = BoolFieldQuery::new("is_public".to_string(), true);
+/// assert_eq!(searcher.search(&query, &Count)?, 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct BoolFieldQuery {
+    field_name: String,
+    value: bool,
+}
+
+impl BoolFieldQuery {
+    /// Creates a query matching the documents having `value` in the bool fast field
+    /// `field_name`.
+    ///
+    /// Executing the search with this query returns an error if the field does not exist, or is
+    /// not a bool fast field.
+    pub fn new(field_name: String, value: bool) -> BoolFieldQuery {
+        BoolFieldQuery { field_name, value }
+    }
+}
+
+impl Query for BoolFieldQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let schema = enable_scoring.schema();
+        let field = schema.get_field(&self.field_name)?;
+        let field_type = schema.get_field_entry(field).field_type();
+        if field_type.value_type() != Type::Bool || !field_type.is_fast() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} is not a bool fast field.",
+                self.field_name
+            )));
+        }
+        Ok(Box::new(BoolFieldWeight {
+            field_name: self.field_name.clone(),
+            value: self.value,
+        }))
+    }
+}
+
+struct BoolFieldWeight {
+    field_name: String,
+    value: bool,
+}
+
+impl Weight for BoolFieldWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let doc_bitset = reader
+            .fast_fields()
+            .bool_bitset(&self.field_name, self.value)?;
+        if doc_bitset.len() == 0 {
+            return Ok(Box::new(EmptyScorer));
+        }
+        Ok(Box::new(ConstScorer::new(
+            BitSetDocSet::from(doc_bitset),
+            boost,
+        )))
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        let doc_bitset = reader
+            .fast_fields()
+            .bool_bitset(&self.field_name, self.value)?;
+        if let Some(alive_bitset) = reader.alive_bitset() {
+            return Ok(BitSetDocSet::from(doc_bitset).count(alive_bitset));
+        }
+        Ok(doc_bitset.len() as u32)
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("BoolFieldQuery", 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BoolFieldQuery;
+    use crate::collector::{Count, DocSetCollector};
+    use crate::query::{BooleanQuery, Occur, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, STRING};
+    use crate::{DocAddress, Index, IndexWriter, Term};
+
+    #[test]
+    fn test_bool_field_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let is_public = schema_builder.add_bool_field("is_public", FAST);
+        let is_deleted = schema_builder.add_bool_field("is_deleted", FAST | INDEXED);
+        schema_builder.add_bool_field("not_fast", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "0", is_public => true, is_deleted => false))?;
+        index_writer.add_document(doc!(id => "1", is_public => false, is_deleted => false))?;
+        index_writer.add_document(doc!(id => "2", is_public => true, is_deleted => true))?;
+        index_writer.add_document(doc!(id => "3", is_deleted => false))?;
+        index_writer.add_document(doc!(id => "4", is_public => true, is_public => false))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let docs = |query: &dyn Query| -> crate::Result<Vec<u32>> {
+            let mut docs: Vec<u32> = searcher
+                .search(query, &DocSetCollector)?
+                .into_iter()
+                .map(|doc_address| doc_address.doc_id)
+                .collect();
+            docs.sort();
+            Ok(docs)
+        };
+        let public_query = BoolFieldQuery::new("is_public".to_string(), true);
+        assert_eq!(docs(&public_query)?, vec![0, 2, 4]);
+        let private_query = BoolFieldQuery::new("is_public".to_string(), false);
+        assert_eq!(docs(&private_query)?, vec![1, 4]);
+        let public_not_deleted = BooleanQuery::new(vec![
+            (
+                Occur::Must,
+                Box::new(public_query.clone()) as Box<dyn Query>,
+            ),
+            (
+                Occur::Must,
+                Box::new(BoolFieldQuery::new("is_deleted".to_string(), false)),
+            ),
+        ]);
+        assert_eq!(docs(&public_not_deleted)?, vec![0]);
+        assert_eq!(searcher.search(&public_query, &Count)?, 3);
+
+        let explanation = public_query.explain(&searcher, DocAddress::new(0, 0))?;
+        assert_eq!(explanation.value(), 1.0);
+        assert!(public_query
+            .explain(&searcher, DocAddress::new(0, 1))
+            .is_err());
+
+        assert!(searcher
+            .search(&BoolFieldQuery::new("not_fast".to_string(), true), &Count)
+            .is_err());
+        assert!(searcher
+            .search(&BoolFieldQuery::new("id".to_string(), true), &Count)
+            .is_err());
+
+        index_writer.delete_term(Term::from_field_text(id, "0"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.search(&public_query, &Count)?, 2);
+        let term_query = TermQuery::new(
+            Term::from_field_bool(is_deleted, true),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&term_query, &Count)?, 1);
+        Ok(())
+    }
+}
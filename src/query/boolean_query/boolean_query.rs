@@ -1,6 +1,7 @@
 use super::boolean_weight::BooleanWeight;
-use crate::query::{EnableScoring, Occur, Query, SumCombiner, TermQuery, Weight};
-use crate::schema::{IndexRecordOption, Term};
+use crate::query::{EnableScoring, Occur, Query, SumCombiner, TermQuery, TermSetQuery, Weight};
+use crate::schema::{IndexRecordOption, Schema, Term};
+use crate::TantivyError;
 
 /// The boolean query returns a set of documents
 /// that matches the Boolean combination of constituent subqueries.
@@ -155,6 +156,21 @@ impl From<Vec<(Occur, Box<dyn Query>)>> for BooleanQuery {
 
 impl Query for BooleanQuery {
     fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let max_clauses_opt = enable_scoring
+            .searcher()
+            .and_then(|searcher| searcher.index().settings().max_boolean_clauses);
+        if let Some(max_clauses) = max_clauses_opt {
+            if self.subqueries.len() > max_clauses {
+                let grouped_query = self.group_term_clauses(enable_scoring.schema());
+                if grouped_query.subqueries.len() > max_clauses {
+                    return Err(TantivyError::TooManyClauses {
+                        num_clauses: grouped_query.subqueries.len(),
+                        max_clauses,
+                    });
+                }
+                return grouped_query.weight(enable_scoring);
+            }
+        }
         let sub_weights = self
             .subqueries
             .iter()
@@ -257,6 +273,36 @@ impl BooleanQuery {
     pub fn clauses(&self) -> &[(Occur, Box<dyn Query>)] {
         &self.subqueries[..]
     }
+
+    /// Groups the `Should` clauses matching a single term of an indexed field into a single
+    /// [`TermSetQuery`] clause.
+    ///
+    /// This does not change the matching documents, as long as at most one `Should` clause is
+    /// required to match.
+    fn group_term_clauses(&self, schema: &Schema) -> BooleanQuery {
+        if self.minimum_number_should_match > 1 {
+            return self.clone();
+        }
+        let mut terms = Vec::new();
+        let mut subqueries = Vec::new();
+        for (occur, subquery) in &self.subqueries {
+            if *occur == Occur::Should {
+                if let Some(term_query) = subquery.downcast_ref::<TermQuery>() {
+                    let term = term_query.term();
+                    if schema.get_field_entry(term.field()).is_indexed() {
+                        terms.push(term.clone());
+                        continue;
+                    }
+                }
+            }
+            subqueries.push((*occur, subquery.box_clone()));
+        }
+        if terms.len() <= 1 {
+            return self.clone();
+        }
+        subqueries.push((Occur::Should, Box::new(TermSetQuery::new(terms))));
+        BooleanQuery::with_minimum_required_clauses(subqueries, self.minimum_number_should_match)
+    }
 }
 
 #[cfg(test)]
@@ -311,4 +311,78 @@ mod tests {
         assert_nearly_equals!(explanation.value(), std::f32::consts::LN_2);
         Ok(())
     }
+
+    #[test]
+    pub fn test_max_boolean_clauses() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", STRING);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(crate::IndexSettings {
+                max_boolean_clauses: Some(3),
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..10 {
+            index_writer.add_document(doc!(text => format!("{i}")))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let term_query = |text_value: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(text, text_value),
+                IndexRecordOption::Basic,
+            ))
+        };
+
+        // The term clauses are grouped into a single term set clause.
+        let clauses = (0..8)
+            .map(|i| (Occur::Should, term_query(&i.to_string())))
+            .collect();
+        let query = BooleanQuery::new(clauses);
+        assert_eq!(searcher.search(&query, &crate::collector::Count)?, 8);
+        let top_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+        assert_eq!(top_docs.len(), 8);
+        let clauses = (0..8)
+            .map(|i| (Occur::Should, term_query(&i.to_string())))
+            .chain([(Occur::MustNot, term_query("3"))])
+            .collect();
+        let query = BooleanQuery::new(clauses);
+        assert_eq!(searcher.search(&query, &crate::collector::Count)?, 7);
+
+        // Queries within the limit are left untouched.
+        let query = BooleanQuery::new(vec![
+            (Occur::Should, term_query("0")),
+            (Occur::Should, term_query("1")),
+        ]);
+        assert_eq!(searcher.search(&query, &crate::collector::Count)?, 2);
+
+        // Clauses which are not term clauses cannot be grouped.
+        let clauses = (0..4)
+            .map(|i| {
+                let query: Box<dyn Query> = Box::new(BooleanQuery::new(vec![(
+                    Occur::Must,
+                    term_query(&i.to_string()),
+                )]));
+                (Occur::Should, query)
+            })
+            .collect();
+        let err = searcher
+            .search(&BooleanQuery::new(clauses), &crate::collector::Count)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            crate::TantivyError::TooManyClauses {
+                num_clauses: 4,
+                max_clauses: 3
+            }
+        ));
+        assert_eq!(err.code().as_str(), "too_many_clauses");
+        // Neither can the clauses of queries requiring several should clauses to match.
+        let clauses = (0..4).map(|i| term_query(&i.to_string())).collect();
+        let query = BooleanQuery::union_with_minimum_required_clauses(clauses, 2);
+        assert!(searcher.search(&query, &crate::collector::Count).is_err());
+        Ok(())
+    }
 }
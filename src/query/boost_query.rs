@@ -20,6 +20,14 @@ impl BoostQuery {
     pub fn new(query: Box<dyn Query>, boost: Score) -> BoostQuery {
         BoostQuery { query, boost }
     }
+
+    pub(crate) fn query(&self) -> &dyn Query {
+        self.query.as_ref()
+    }
+
+    pub(crate) fn boost(&self) -> Score {
+        self.boost
+    }
 }
 
 impl Clone for BoostQuery {
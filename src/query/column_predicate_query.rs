@@ -0,0 +1,354 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::collector::SegmentColumns;
+use crate::docset::{DocSet, COLLECT_BLOCK_BUFFER_LEN, TERMINATED};
+use crate::query::explanation::does_not_match;
+use crate::query::{ConstScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::{DocId, Score, SegmentReader};
+
+/// A `SegmentColumnPredicate` filters the documents of a segment, a block of documents at a time.
+///
+/// It is the segment local version of the [`ColumnPredicate`].
+pub trait SegmentColumnPredicate: Send + 'static {
+    /// Pushes the documents of `docs` satisfying the predicate to `matching_docs`, in increasing
+    /// order.
+    ///
+    /// `matching_docs` is empty when the method is called. Since the whole block is handed at
+    /// once, the predicate can short-circuit: e.g. the values of a second column only need to be
+    /// fetched for the documents passing the condition on a first column, and not at all if none
+    /// of them do.
+    fn filter_block(&mut self, docs: &[DocId], matching_docs: &mut Vec<DocId>);
+}
+
+/// `ColumnPredicate` is a predicate on the fast field values of a document, evaluated by a
+/// [`ColumnPredicateQuery`].
+pub trait ColumnPredicate: Send + Sync + 'static {
+    /// Type of the associated [`SegmentColumnPredicate`].
+    type Child: SegmentColumnPredicate;
+
+    /// Builds the predicate of a segment, opening the columns it needs.
+    fn segment_predicate(&self, segment_columns: &SegmentColumns) -> crate::Result<Self::Child>;
+}
+
+impl<F, TSegmentPredicate> ColumnPredicate for F
+where
+    F: 'static + Send + Sync + Fn(&SegmentColumns) -> crate::Result<TSegmentPredicate>,
+    TSegmentPredicate: SegmentColumnPredicate,
+{
+    type Child = TSegmentPredicate;
+
+    fn segment_predicate(&self, segment_columns: &SegmentColumns) -> crate::Result<Self::Child> {
+        (self)(segment_columns)
+    }
+}
+
+impl<F> SegmentColumnPredicate for F
+where F: 'static + Send + FnMut(&[DocId], &mut Vec<DocId>)
+{
+    fn filter_block(&mut self, docs: &[DocId], matching_docs: &mut Vec<DocId>) {
+        (self)(docs, matching_docs)
+    }
+}
+
+/// Query matching the documents whose fast field values satisfy an arbitrary predicate, e.g.
+/// `a + b > c`, without having to index the result of the predicate in a field.
+///
+/// The predicate is evaluated on blocks of consecutive documents, so that the values of the
+/// columns are fetched in batch. When the query is intersected with other queries, only the
+/// blocks of the documents they match are evaluated.
+///
+/// All of the matched documents get the score 1.0.
+///
+/// ```rust
+/// use tantivy::collector::{Count, SegmentColumns};
+/// use tantivy::query::ColumnPredicateQuery;
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, DocId, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let a = schema_builder.add_u64_field("a", FAST);
+/// let b = schema_builder.add_u64_field("b", FAST);
+/// let c = schema_builder.add_u64_field("c", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(a => 1u64, b => 2u64, c => 4u64))?;
+/// index_writer.add_document(doc!(a => 3u64, b => 2u64, c => 4u64))?;
+/// index_writer.commit()?;
+///
+/// // a + b > c
+/// let query = ColumnPredicateQuery::new(|segment_columns: &SegmentColumns| {
+///     // Columns are opened once per segment.
+///     let mut a = segment_columns.u64("a")?;
+///     let mut b = segment_columns.u64("b")?;
+///     let mut c = segment_columns.u64("c")?;
+///     Ok(move |docs: &[DocId], matching_docs: &mut Vec<DocId>| {
+///         let a_values = a.fetch(docs).to_vec();
+///         let b_values = b.fetch(docs).to_vec();
+///         let c_values = c.fetch(docs);
+///         for i in 0..docs.len() {
+///             if let (Some(a), Some(b), Some(c)) = (a_values[i], b_values[i], c_values[i]) {
+///                 if a + b > c {
+///                     matching_docs.push(docs[i]);
+///                 }
+///             }
+///         }
+///     })
+/// });
+/// let searcher = index.reader()?.searcher();
+/// assert_eq!(searcher.search(&query, &Count)?, 1);
+/// # Ok(())
+/// # }
+/// ```
+pub struct ColumnPredicateQuery<P> {
+    predicate: Arc<P>,
+}
+
+impl<P: ColumnPredicate> ColumnPredicateQuery<P> {
+    /// Creates a query matching the documents satisfying `predicate`.
+    pub fn new(predicate: P) -> ColumnPredicateQuery<P> {
+        ColumnPredicateQuery {
+            predicate: Arc::new(predicate),
+        }
+    }
+}
+
+impl<P> Clone for ColumnPredicateQuery<P> {
+    fn clone(&self) -> Self {
+        ColumnPredicateQuery {
+            predicate: self.predicate.clone(),
+        }
+    }
+}
+
+impl<P> fmt::Debug for ColumnPredicateQuery<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ColumnPredicateQuery")
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P: ColumnPredicate> Query for ColumnPredicateQuery<P> {
+    fn weight(&self, _enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        Ok(Box::new(ColumnPredicateWeight {
+            predicate: self.predicate.clone(),
+        }))
+    }
+}
+
+struct ColumnPredicateWeight<P> {
+    predicate: Arc<P>,
+}
+
+impl<P: ColumnPredicate> Weight for ColumnPredicateWeight<P> {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let segment_predicate = self
+            .predicate
+            .segment_predicate(&SegmentColumns::new(reader))?;
+        let docset = ColumnPredicateDocSet::new(segment_predicate, reader.max_doc());
+        Ok(Box::new(ConstScorer::new(docset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("ColumnPredicateQuery", 1.0))
+    }
+}
+
+/// DocSet of the documents satisfying a predicate, evaluated a block of documents at a time.
+struct ColumnPredicateDocSet<P> {
+    segment_predicate: P,
+    max_doc: DocId,
+    // First document of the next block to evaluate.
+    next_block_start: DocId,
+    block_docs: Vec<DocId>,
+    // Documents of the current block satisfying the predicate.
+    matching_docs: Vec<DocId>,
+    cursor: usize,
+}
+
+impl<P: SegmentColumnPredicate> ColumnPredicateDocSet<P> {
+    fn new(segment_predicate: P, max_doc: DocId) -> ColumnPredicateDocSet<P> {
+        let mut docset = ColumnPredicateDocSet {
+            segment_predicate,
+            max_doc,
+            next_block_start: 0,
+            block_docs: Vec::with_capacity(COLLECT_BLOCK_BUFFER_LEN),
+            matching_docs: Vec::with_capacity(COLLECT_BLOCK_BUFFER_LEN),
+            cursor: 0,
+        };
+        docset.load_block(0);
+        docset
+    }
+
+    /// Evaluates the blocks starting at `block_start` until one of them has a matching document,
+    /// or all of the documents are evaluated.
+    fn load_block(&mut self, mut block_start: DocId) {
+        self.matching_docs.clear();
+        self.cursor = 0;
+        while block_start < self.max_doc {
+            let block_end = (block_start + COLLECT_BLOCK_BUFFER_LEN as DocId).min(self.max_doc);
+            self.block_docs.clear();
+            self.block_docs.extend(block_start..block_end);
+            self.segment_predicate
+                .filter_block(&self.block_docs, &mut self.matching_docs);
+            debug_assert!(self.matching_docs.windows(2).all(|docs| docs[0] < docs[1]));
+            block_start = block_end;
+            if !self.matching_docs.is_empty() {
+                break;
+            }
+        }
+        self.next_block_start = block_start;
+    }
+}
+
+impl<P: SegmentColumnPredicate> DocSet for ColumnPredicateDocSet<P> {
+    fn advance(&mut self) -> DocId {
+        self.cursor += 1;
+        if self.cursor >= self.matching_docs.len() {
+            self.load_block(self.next_block_start);
+        }
+        self.doc()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if self.doc() >= target {
+            return self.doc();
+        }
+        if target < self.next_block_start {
+            // The target is within the current block.
+            self.cursor += self.matching_docs[self.cursor..].partition_point(|&doc| doc < target);
+            if self.cursor < self.matching_docs.len() {
+                return self.doc();
+            }
+        }
+        // The blocks before the target are not evaluated.
+        self.load_block(target.max(self.next_block_start));
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.matching_docs
+            .get(self.cursor)
+            .copied()
+            .unwrap_or(TERMINATED)
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.max_doc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::{ColumnPredicateDocSet, ColumnPredicateQuery};
+    use crate::collector::{Count, DocSetCollector, SegmentColumns};
+    use crate::docset::DocSet;
+    use crate::query::{BooleanQuery, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, FAST, STRING};
+    use crate::{DocAddress, DocId, Index, IndexWriter, Term};
+
+    #[test]
+    fn test_column_predicate_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let a = schema_builder.add_u64_field("a", FAST);
+        let b = schema_builder.add_i64_field("b", FAST);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..1_000u64 {
+            let mut doc = doc!(a => i);
+            if i % 3 != 1 {
+                doc.add_i64(b, -(i as i64) / 2);
+            }
+            if i % 100 == 0 {
+                doc.add_text(tag, "hundred");
+            }
+            index_writer.add_document(doc)?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        // a + b > 400, i.e. i - i / 2 > 400, for the documents having a value for b.
+        let query = ColumnPredicateQuery::new(|segment_columns: &SegmentColumns| {
+            let mut a = segment_columns.u64("a")?;
+            let mut b = segment_columns.i64("b")?;
+            let mut candidates = Vec::new();
+            Ok(move |docs: &[DocId], matching_docs: &mut Vec<DocId>| {
+                // b is only fetched for the documents passing the condition on a.
+                candidates.clear();
+                candidates.extend(
+                    docs.iter()
+                        .zip(a.fetch(docs))
+                        .filter(|(_, a)| a.is_some_and(|a| a > 400))
+                        .map(|(&doc, _)| doc),
+                );
+                for (&doc, b) in candidates.iter().zip(b.fetch(&candidates)) {
+                    let a = doc as i64;
+                    if b.is_some_and(|b| a + b > 400) {
+                        matching_docs.push(doc);
+                    }
+                }
+            })
+        });
+        let expected: BTreeSet<u32> = (0..1_000u32)
+            .filter(|i| i % 3 != 1 && *i as i64 - (*i as i64) / 2 > 400)
+            .collect();
+        let matching_docs: BTreeSet<u32> = searcher
+            .search(&query, &DocSetCollector)?
+            .into_iter()
+            .map(|doc_address| doc_address.doc_id)
+            .collect();
+        assert_eq!(matching_docs, expected);
+
+        let hundreds = TermQuery::new(
+            Term::from_field_text(tag, "hundred"),
+            IndexRecordOption::Basic,
+        );
+        let intersection =
+            BooleanQuery::intersection(vec![Box::new(hundreds), Box::new(query.clone())]);
+        // Only the document 900 matches both queries.
+        assert_eq!(searcher.search(&intersection, &Count)?, 1);
+        assert!(query.explain(&searcher, DocAddress::new(0, 803)).is_ok());
+        assert!(query.explain(&searcher, DocAddress::new(0, 800)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_column_predicate_query_seek_skips_blocks() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let a = schema_builder.add_u64_field("a", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for i in 0..10_000u64 {
+            index_writer.add_document(doc!(a => i))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let num_evaluated = Arc::new(AtomicUsize::new(0));
+        let segment_predicate = {
+            let num_evaluated = num_evaluated.clone();
+            move |docs: &[DocId], matching_docs: &mut Vec<DocId>| {
+                num_evaluated.fetch_add(docs.len(), Ordering::Relaxed);
+                matching_docs.extend(docs.iter().filter(|&&doc| doc % 7 == 0));
+            }
+        };
+        let mut docset =
+            ColumnPredicateDocSet::new(segment_predicate, searcher.segment_reader(0).max_doc());
+        assert_eq!(docset.doc(), 0);
+        assert_eq!(docset.advance(), 7);
+        assert_eq!(docset.seek(50), 56);
+        assert_eq!(docset.seek(5_000), 5_005);
+        assert_eq!(docset.seek(9_999), crate::TERMINATED);
+        assert!(num_evaluated.load(Ordering::Relaxed) < 200);
+        Ok(())
+    }
+}
@@ -19,6 +19,10 @@ impl ConstScoreQuery {
     pub fn new(query: Box<dyn Query>, score: Score) -> ConstScoreQuery {
         ConstScoreQuery { query, score }
     }
+
+    pub(crate) fn query(&self) -> &dyn Query {
+        self.query.as_ref()
+    }
 }
 
 impl Clone for ConstScoreQuery {
@@ -71,9 +75,9 @@ impl Weight for ConstWeight {
     fn explain(&self, reader: &SegmentReader, doc: u32) -> crate::Result<Explanation> {
         let mut scorer = self.scorer(reader, 1.0)?;
         if scorer.seek(doc) != doc {
-            return Err(TantivyError::InvalidArgument(format!(
-                "Document #({doc}) does not match"
-            )));
+            return Err(TantivyError::InvalidArgument(
+                format!("Document #({doc}) does not match").into(),
+            ));
         }
         let mut explanation = Explanation::new("Const", self.score);
         let underlying_explanation = self.weight.explain(reader, doc)?;
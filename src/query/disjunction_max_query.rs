@@ -127,4 +127,12 @@ impl DisjunctionMaxQuery {
     pub fn new(disjuncts: Vec<Box<dyn Query>>) -> DisjunctionMaxQuery {
         DisjunctionMaxQuery::with_tie_breaker(disjuncts, 0.0)
     }
+
+    pub(crate) fn disjuncts(&self) -> &[Box<dyn Query>] {
+        &self.disjuncts
+    }
+
+    pub(crate) fn tie_breaker(&self) -> Score {
+        self.tie_breaker
+    }
 }
@@ -1,13 +1,17 @@
 use core::fmt::Debug;
 
 use columnar::{ColumnIndex, DynamicColumn};
+use common::json_path_writer::{JSON_END_OF_PATH, JSON_PATH_SEGMENT_SEP};
+use common::BitSet;
 
-use super::{ConstScorer, EmptyScorer};
+use super::phrase_prefix_query::prefix_end;
+use super::{BitSetDocSet, ConstScorer, EmptyScorer};
 use crate::docset::{DocSet, TERMINATED};
 use crate::index::SegmentReader;
+use crate::indexer::field_presence::field_presence_term;
 use crate::query::explanation::does_not_match;
 use crate::query::{EnableScoring, Explanation, Query, Scorer, Weight};
-use crate::schema::Type;
+use crate::schema::{Field, FieldType, IndexRecordOption, Term, Type};
 use crate::{DocId, Score, TantivyError};
 
 /// Query that matches all documents with a non-null value in the specified
@@ -28,6 +32,13 @@ use crate::{DocId, Score, TantivyError};
 /// `myfield.mysubfield` will match the document. If it is set to false, only
 /// `myfield.mysubfield` will match it.
 ///
+/// Fast fields are answered from the null index of their columns. Fields that are indexed but
+/// not fast are answered from the inverted index, by collecting the postings of all of the terms
+/// of the field (or of the JSON path). In that case, a document for which the tokenizer did not
+/// emit any token (e.g. an empty string) is not matched, unless the index records the presence
+/// of fields (see [`IndexSettings::field_presence`](crate::IndexSettings::field_presence)): the
+/// query is then answered from the postings of a single term.
+///
 /// All of the matched documents get the score 1.0.
 #[derive(Clone, Debug)]
 pub struct ExistsQuery {
@@ -40,7 +51,7 @@ impl ExistsQuery {
     ///
     /// This query matches all documents with at least one non-null value in the specified field.
     /// This constructor never fails, but executing the search with this query will return an
-    /// error if the specified field doesn't exists or is not a fast field.
+    /// error if the specified field doesn't exists or is neither fast nor indexed.
     #[deprecated]
     pub fn new_exists_query(field: String) -> ExistsQuery {
         ExistsQuery {
@@ -56,8 +67,8 @@ impl ExistsQuery {
     /// non-null values in any JSON subpath will also be matched.
     ///
     /// This constructor never fails, but executing the search with this query will
-    /// return an error if the specified field doesn't exists or is not a fast
-    /// field.
+    /// return an error if the specified field doesn't exists or is neither fast
+    /// nor indexed.
     pub fn new(field: String, json_subpaths: bool) -> Self {
         Self {
             field_name: field,
@@ -69,24 +80,70 @@ impl ExistsQuery {
 impl Query for ExistsQuery {
     fn weight(&self, enable_scoring: EnableScoring) -> crate::Result<Box<dyn Weight>> {
         let schema = enable_scoring.schema();
-        let Some((field, _path)) = schema.find_field(&self.field_name) else {
+        let Some((field, json_path)) = schema.find_field(&self.field_name) else {
             return Err(TantivyError::FieldNotFound(self.field_name.clone()));
         };
         let field_type = schema.get_field_entry(field).field_type();
-        if !field_type.is_fast() {
+        if field_type.is_fast() {
+            return Ok(Box::new(ExistsWeight {
+                field_name: self.field_name.clone(),
+                field_type: field_type.value_type(),
+                json_subpaths: self.json_subpaths,
+            }));
+        }
+        if !field_type.is_indexed() {
             return Err(TantivyError::SchemaError(format!(
-                "Field {} is not a fast field.",
+                "Field {} is neither a fast field nor an indexed field.",
                 self.field_name
             )));
         }
-        Ok(Box::new(ExistsWeight {
-            field_name: self.field_name.clone(),
-            field_type: field_type.value_type(),
-            json_subpaths: self.json_subpaths,
+        if let Some(field_presence_term) = field_presence_term(schema, field) {
+            return Ok(Box::new(FieldPresenceExistsWeight {
+                field_presence_term,
+            }));
+        }
+        let term_prefixes = match field_type {
+            FieldType::JsonObject(json_options) => json_path_prefixes(
+                field,
+                json_path,
+                json_options.is_expand_dots_enabled(),
+                self.json_subpaths,
+            ),
+            // All of the terms of the field.
+            _ => vec![Vec::new()],
+        };
+        Ok(Box::new(InvertedIndexExistsWeight {
+            field,
+            term_prefixes,
         }))
     }
 }
 
+/// Returns the prefixes of the terms of a JSON field having a value at `json_path`, or in one of
+/// its subpaths if `json_subpaths` is set.
+fn json_path_prefixes(
+    field: Field,
+    json_path: &str,
+    expand_dots: bool,
+    json_subpaths: bool,
+) -> Vec<Vec<u8>> {
+    if json_path.is_empty() && json_subpaths {
+        return vec![Vec::new()];
+    }
+    let term = Term::from_field_json_path(field, json_path, expand_dots);
+    // Ends with `JSON_END_OF_PATH`.
+    let exact_path_prefix = term.serialized_value_bytes().to_vec();
+    if !json_subpaths {
+        return vec![exact_path_prefix];
+    }
+    let mut subpath_prefix = exact_path_prefix.clone();
+    if let Some(last_byte) = subpath_prefix.last_mut() {
+        debug_assert_eq!(*last_byte, JSON_END_OF_PATH);
+        *last_byte = JSON_PATH_SEGMENT_SEP;
+    }
+    vec![exact_path_prefix, subpath_prefix]
+}
+
 /// Weight associated with the `ExistsQuery` query.
 pub struct ExistsWeight {
     field_name: String,
@@ -131,6 +188,87 @@ impl Weight for ExistsWeight {
     }
 }
 
+/// Weight of the `ExistsQuery` on a field which is indexed, but not fast.
+struct InvertedIndexExistsWeight {
+    field: Field,
+    // A document matches if the field contains a term starting with one of these prefixes.
+    // An empty prefix matches all of the terms of the field.
+    term_prefixes: Vec<Vec<u8>>,
+}
+
+impl Weight for InvertedIndexExistsWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let inverted_index = reader.inverted_index(self.field)?;
+        let term_dict = inverted_index.terms();
+        let mut doc_bitset = BitSet::with_max_value(reader.max_doc());
+        for term_prefix in &self.term_prefixes {
+            let mut term_stream_builder = term_dict.range().ge(term_prefix);
+            if let Some(end) = prefix_end(term_prefix) {
+                term_stream_builder = term_stream_builder.lt(&end);
+            }
+            let mut term_stream = term_stream_builder.into_stream()?;
+            while term_stream.advance() {
+                let mut block_segment_postings = inverted_index.read_block_postings_from_terminfo(
+                    term_stream.value(),
+                    IndexRecordOption::Basic,
+                )?;
+                loop {
+                    let docs = block_segment_postings.docs();
+                    if docs.is_empty() {
+                        break;
+                    }
+                    for &doc in docs {
+                        doc_bitset.insert(doc);
+                    }
+                    block_segment_postings.advance();
+                }
+            }
+        }
+        let docset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(docset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("ExistsQuery", 1.0))
+    }
+}
+
+/// Weight of the `ExistsQuery` on a field whose presence is recorded in the field presence field.
+struct FieldPresenceExistsWeight {
+    field_presence_term: Term,
+}
+
+impl Weight for FieldPresenceExistsWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let inverted_index = reader.inverted_index(self.field_presence_term.field())?;
+        match inverted_index.read_postings(&self.field_presence_term, IndexRecordOption::Basic)? {
+            Some(postings) => Ok(Box::new(ConstScorer::new(postings, boost))),
+            None => Ok(Box::new(EmptyScorer)),
+        }
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        if reader.has_deletes() {
+            let mut scorer = self.scorer(reader, 1.0)?;
+            return Ok(scorer.count(reader.alive_bitset().unwrap()));
+        }
+        let inverted_index = reader.inverted_index(self.field_presence_term.field())?;
+        Ok(inverted_index.doc_freq(&self.field_presence_term)?)
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("ExistsQuery", 1.0))
+    }
+}
+
 pub(crate) struct ExistsDocSet {
     columns: Vec<DynamicColumn>,
     doc: DocId,
@@ -195,7 +333,7 @@ mod tests {
     use crate::collector::Count;
     use crate::query::exist_query::ExistsQuery;
     use crate::query::{BooleanQuery, RangeQuery};
-    use crate::schema::{Facet, FacetOptions, Schema, FAST, INDEXED, STRING, TEXT};
+    use crate::schema::{Facet, FacetOptions, Schema, FAST, INDEXED, STORED, STRING, TEXT};
     use crate::{Index, Searcher, Term};
 
     #[test]
@@ -339,17 +477,76 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_exists_query_inverted_index() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let num = schema_builder.add_u64_field("num", INDEXED);
+        let json = schema_builder.add_json_field("json", TEXT);
+        let schema = schema_builder.build();
+
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer = index.writer_for_tests()?;
+            for i in 0u64..100u64 {
+                if i % 2 == 0 {
+                    index_writer.add_document(doc!(
+                        text => "hello",
+                        json => json!({"all": i, "even": {"nested": true}}),
+                    ))?;
+                } else if i % 5 == 0 {
+                    index_writer.add_document(doc!(
+                        num => i,
+                        json => json!({"all": i.to_string(), "odd": "yes", "evenly": 1}),
+                    ))?;
+                } else {
+                    index_writer.add_document(doc!(
+                        json => json!({"all": i.to_string(), "odd": "yes"}),
+                    ))?;
+                }
+            }
+            index_writer.commit()?;
+        }
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+
+        assert_eq!(count_existing_fields(&searcher, "text", false)?, 50);
+        assert_eq!(count_existing_fields(&searcher, "num", false)?, 10);
+        assert_eq!(count_existing_fields(&searcher, "json.all", false)?, 100);
+        assert_eq!(count_existing_fields(&searcher, "json.odd", false)?, 50);
+        // `json.even` only has values in a subpath, and `json.evenly` must not be matched.
+        assert_eq!(count_existing_fields(&searcher, "json.even", false)?, 0);
+        assert_eq!(count_existing_fields(&searcher, "json.even", true)?, 50);
+        assert_eq!(
+            count_existing_fields(&searcher, "json.even.nested", false)?,
+            50
+        );
+        assert_eq!(count_existing_fields(&searcher, "json.evenly", false)?, 10);
+        assert_eq!(count_existing_fields(&searcher, "json", false)?, 0);
+        assert_eq!(count_existing_fields(&searcher, "json", true)?, 100);
+        assert_eq!(count_existing_fields(&searcher, "json.absent", true)?, 0);
+
+        // exercise seek
+        let query = BooleanQuery::intersection(vec![
+            Box::new(ExistsQuery::new("num".to_string(), false)),
+            Box::new(ExistsQuery::new("json.odd".to_string(), false)),
+        ]);
+        assert_eq!(searcher.search(&query, &Count)?, 10);
+
+        Ok(())
+    }
+
     #[test]
     fn test_exists_query_unsupported_types() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
-        let not_fast = schema_builder.add_text_field("not_fast", TEXT);
+        let stored_only = schema_builder.add_text_field("stored_only", STORED);
         let schema = schema_builder.build();
 
         let index = Index::create_in_ram(schema);
         {
             let mut index_writer = index.writer_for_tests()?;
             index_writer.add_document(doc!(
-                not_fast => "slow",
+                stored_only => "slow",
             ))?;
             index_writer.commit()?;
         }
@@ -358,10 +555,10 @@ mod tests {
 
         assert_eq!(
             searcher
-                .search(&ExistsQuery::new("not_fast".to_string(), false), &Count)
+                .search(&ExistsQuery::new("stored_only".to_string(), false), &Count)
                 .unwrap_err()
                 .to_string(),
-            "Schema error: 'Field not_fast is not a fast field.'"
+            "Schema error: 'Field stored_only is neither a fast field nor an indexed field.'"
         );
 
         assert_does_not_exist(&searcher, "does_not_exists", false);
@@ -6,7 +6,7 @@ use serde::Serialize;
 use crate::{DocId, Score, TantivyError};
 
 pub(crate) fn does_not_match(doc: DocId) -> TantivyError {
-    TantivyError::InvalidArgument(format!("Document #({doc}) does not match"))
+    TantivyError::InvalidArgument(format!("Document #({doc}) does not match").into())
 }
 
 /// Object describing the score of a given document.
@@ -1,4 +1,4 @@
-use levenshtein_automata::{Distance, LevenshteinAutomatonBuilder, DFA};
+use levenshtein_automata::{Distance, LevenshteinAutomatonBuilder, DFA, SINK_STATE};
 use once_cell::sync::OnceCell;
 use tantivy_fst::Automaton;
 
@@ -23,7 +23,7 @@ impl Automaton for DfaWrapper {
     }
 
     fn can_match(&self, state: &u32) -> bool {
-        *state != levenshtein_automata::SINK_STATE
+        *state != SINK_STATE
     }
 
     fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
@@ -31,6 +31,63 @@ impl Automaton for DfaWrapper {
     }
 }
 
+/// State of a [`FuzzyAutomaton`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum FuzzyState {
+    /// Number of bytes of the exact prefix matched so far.
+    Prefix(usize),
+    /// State of the Levenshtein automaton, matching the rest of the term.
+    Dfa(u32),
+}
+
+/// Matches the terms starting with an exact prefix, followed by a suffix accepted by a Levenshtein
+/// automaton.
+pub(crate) struct FuzzyAutomaton {
+    exact_prefix: Vec<u8>,
+    dfa: DfaWrapper,
+}
+
+impl Automaton for FuzzyAutomaton {
+    type State = FuzzyState;
+
+    fn start(&self) -> FuzzyState {
+        if self.exact_prefix.is_empty() {
+            FuzzyState::Dfa(self.dfa.start())
+        } else {
+            FuzzyState::Prefix(0)
+        }
+    }
+
+    fn is_match(&self, state: &FuzzyState) -> bool {
+        match state {
+            FuzzyState::Prefix(_) => false,
+            FuzzyState::Dfa(dfa_state) => self.dfa.is_match(dfa_state),
+        }
+    }
+
+    fn can_match(&self, state: &FuzzyState) -> bool {
+        match state {
+            FuzzyState::Prefix(_) => true,
+            FuzzyState::Dfa(dfa_state) => self.dfa.can_match(dfa_state),
+        }
+    }
+
+    fn accept(&self, state: &FuzzyState, byte: u8) -> FuzzyState {
+        match *state {
+            FuzzyState::Prefix(num_matched_bytes) => {
+                if self.exact_prefix[num_matched_bytes] != byte {
+                    FuzzyState::Dfa(SINK_STATE)
+                } else if num_matched_bytes + 1 == self.exact_prefix.len() {
+                    FuzzyState::Dfa(self.dfa.start())
+                } else {
+                    FuzzyState::Prefix(num_matched_bytes + 1)
+                }
+            }
+            FuzzyState::Dfa(dfa_state) => FuzzyState::Dfa(self.dfa.accept(&dfa_state, byte)),
+        }
+    }
+}
+
 /// A Fuzzy Query matches all of the documents
 /// containing a specific term that is within
 /// Levenshtein distance
@@ -86,6 +143,8 @@ pub struct FuzzyTermQuery {
     transposition_cost_one: bool,
     /// is a starts with query
     prefix: bool,
+    /// Number of leading characters which have to match exactly
+    prefix_length: usize,
 }
 
 impl FuzzyTermQuery {
@@ -96,6 +155,7 @@ impl FuzzyTermQuery {
             distance,
             transposition_cost_one,
             prefix: false,
+            prefix_length: 0,
         }
     }
 
@@ -106,10 +166,24 @@ impl FuzzyTermQuery {
             distance,
             transposition_cost_one,
             prefix: true,
+            prefix_length: 0,
         }
     }
 
-    fn specialized_weight(&self) -> crate::Result<AutomatonWeight<DfaWrapper>> {
+    /// Sets the number of leading characters of the term which have to match exactly.
+    ///
+    /// Typos rarely occur in the first characters of a word. Not fuzzing them massively
+    /// reduces the number of terms matched by the query, and therefore its cost.
+    ///
+    /// By default, the whole term is fuzzy.
+    #[must_use]
+    pub fn with_prefix_length(mut self, prefix_length: usize) -> FuzzyTermQuery {
+        self.prefix_length = prefix_length;
+        self
+    }
+
+    /// Returns the automaton matching the terms within the Levenshtein distance of the term text.
+    pub(crate) fn automaton(&self) -> crate::Result<FuzzyAutomaton> {
         static AUTOMATON_BUILDER: [[OnceCell<LevenshteinAutomatonBuilder>; 2]; 3] = [
             [OnceCell::new(), OnceCell::new()],
             [OnceCell::new(), OnceCell::new()],
@@ -119,11 +193,14 @@ impl FuzzyTermQuery {
         let automaton_builder = AUTOMATON_BUILDER
             .get(self.distance as usize)
             .ok_or_else(|| {
-                InvalidArgument(format!(
-                    "Levenshtein distance of {} is not allowed. Choose a value less than {}",
-                    self.distance,
-                    AUTOMATON_BUILDER.len()
-                ))
+                InvalidArgument(
+                    format!(
+                        "Levenshtein distance of {} is not allowed. Choose a value less than {}",
+                        self.distance,
+                        AUTOMATON_BUILDER.len()
+                    )
+                    .into(),
+                )
             })?
             .get(self.transposition_cost_one as usize)
             .unwrap()
@@ -136,40 +213,49 @@ impl FuzzyTermQuery {
         let term_text = if term_value.typ() == Type::Json {
             if let Some(json_path_type) = term_value.json_path_type() {
                 if json_path_type != Type::Str {
-                    return Err(InvalidArgument(format!(
-                        "The fuzzy term query requires a string path type for a json term. Found \
-                         {json_path_type:?}"
-                    )));
+                    return Err(InvalidArgument(
+                        format!(
+                            "The fuzzy term query requires a string path type for a json term. \
+                             Found {json_path_type:?}"
+                        )
+                        .into(),
+                    ));
                 }
             }
 
             std::str::from_utf8(self.term.serialized_value_bytes()).map_err(|_| {
-                InvalidArgument(
-                    "Failed to convert json term value bytes to utf8 string.".to_string(),
-                )
+                InvalidArgument("Failed to convert json term value bytes to utf8 string.".into())
             })?
         } else {
             term_value.as_str().ok_or_else(|| {
-                InvalidArgument("The fuzzy term query requires a string term.".to_string())
+                InvalidArgument("The fuzzy term query requires a string term.".into())
             })?
         };
-        let automaton = if self.prefix {
-            automaton_builder.build_prefix_dfa(term_text)
+        let (exact_prefix, fuzzy_suffix) = match term_text.char_indices().nth(self.prefix_length) {
+            Some((prefix_num_bytes, _)) => term_text.split_at(prefix_num_bytes),
+            None => (term_text, ""),
+        };
+        let dfa = if self.prefix {
+            automaton_builder.build_prefix_dfa(fuzzy_suffix)
         } else {
-            automaton_builder.build_dfa(term_text)
+            automaton_builder.build_dfa(fuzzy_suffix)
         };
+        Ok(FuzzyAutomaton {
+            exact_prefix: exact_prefix.as_bytes().to_vec(),
+            dfa: DfaWrapper(dfa),
+        })
+    }
 
-        if let Some((json_path_bytes, _)) = term_value.as_json() {
+    pub(crate) fn specialized_weight(&self) -> crate::Result<AutomatonWeight<FuzzyAutomaton>> {
+        let automaton = self.automaton()?;
+        if let Some((json_path_bytes, _)) = self.term.value().as_json() {
             Ok(AutomatonWeight::new_for_json_path(
                 self.term.field(),
-                DfaWrapper(automaton),
+                automaton,
                 json_path_bytes,
             ))
         } else {
-            Ok(AutomatonWeight::new(
-                self.term.field(),
-                DfaWrapper(automaton),
-            ))
+            Ok(AutomatonWeight::new(self.term.field(), automaton))
         }
     }
 }
@@ -319,6 +405,36 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    pub fn test_fuzzy_term_prefix_length() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let country_field = schema_builder.add_text_field("country", TEXT);
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(country_field => "japan"))?;
+        index_writer.add_document(doc!(country_field => "lapan"))?;
+        index_writer.add_document(doc!(country_field => "lapin"))?;
+        index_writer.commit()?;
+        let reader = index.reader()?;
+        let searcher = reader.searcher();
+        let count = |text: &str, distance: u8, prefix_length: usize| {
+            let term = Term::from_field_text(country_field, text);
+            let query = FuzzyTermQuery::new(term, distance, true).with_prefix_length(prefix_length);
+            searcher.search(&query, &Count)
+        };
+        assert_eq!(count("lapon", 2, 0)?, 3);
+        // The first character of "japan" differs.
+        assert_eq!(count("lapon", 2, 1)?, 2);
+        assert_eq!(count("lapon", 2, 4)?, 0);
+        assert_eq!(count("lapan", 1, 5)?, 1);
+        assert_eq!(count("lapan", 1, 10)?, 1);
+        let term = Term::from_field_text(country_field, "lapi");
+        let prefix_query = FuzzyTermQuery::new_prefix(term, 1, true).with_prefix_length(3);
+        assert_eq!(searcher.search(&prefix_query, &Count)?, 2);
+        Ok(())
+    }
+
     #[test]
     pub fn test_fuzzy_term_transposition_cost_one() -> crate::Result<()> {
         let mut schema_builder = Schema::builder();
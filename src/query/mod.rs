@@ -2,8 +2,10 @@ mod all_query;
 mod automaton_weight;
 mod bitset;
 mod bm25;
+mod bool_field_query;
 mod boolean_query;
 mod boost_query;
+mod column_predicate_query;
 mod const_score_query;
 mod disjunction;
 mod disjunction_max_query;
@@ -14,15 +16,22 @@ mod explanation;
 mod fuzzy_query;
 mod intersection;
 mod more_like_this;
+pub(crate) mod non_match_explanation;
 mod phrase_prefix_query;
 mod phrase_query;
 mod query;
+#[cfg(feature = "query-parser")]
+pub mod query_dsl;
+#[cfg(feature = "query-parser")]
 mod query_parser;
 mod range_query;
 mod regex_query;
+mod relaxing_search;
 mod reqopt_scorer;
+mod routing_query;
 mod scorer;
 mod set_query;
+mod tenant_query;
 mod term_query;
 mod union;
 mod weight;
@@ -37,8 +46,12 @@ pub use self::all_query::{AllQuery, AllScorer, AllWeight};
 pub use self::automaton_weight::AutomatonWeight;
 pub use self::bitset::BitSetDocSet;
 pub use self::bm25::{Bm25StatisticsProvider, Bm25Weight};
+pub use self::bool_field_query::BoolFieldQuery;
 pub use self::boolean_query::{BooleanQuery, BooleanWeight};
 pub use self::boost_query::{BoostQuery, BoostWeight};
+pub use self::column_predicate_query::{
+    ColumnPredicate, ColumnPredicateQuery, SegmentColumnPredicate,
+};
 pub use self::const_score_query::{ConstScoreQuery, ConstScorer};
 pub use self::disjunction_max_query::DisjunctionMaxQuery;
 pub use self::empty_query::{EmptyQuery, EmptyScorer, EmptyWeight};
@@ -50,17 +63,23 @@ pub(crate) use self::fuzzy_query::DfaWrapper;
 pub use self::fuzzy_query::FuzzyTermQuery;
 pub use self::intersection::{intersect_scorers, Intersection};
 pub use self::more_like_this::{MoreLikeThisQuery, MoreLikeThisQueryBuilder};
+pub use self::non_match_explanation::{NonMatchExplanation, NonMatchReason};
 pub use self::phrase_prefix_query::PhrasePrefixQuery;
+pub use self::phrase_query::fuzzy_phrase_query::FuzzyPhraseQuery;
 pub use self::phrase_query::regex_phrase_query::{wildcard_query_to_regex_str, RegexPhraseQuery};
 pub use self::phrase_query::PhraseQuery;
 pub use self::query::{EnableScoring, Query, QueryClone};
-pub use self::query_parser::{QueryParser, QueryParserError};
+#[cfg(feature = "query-parser")]
+pub use self::query_parser::{QueryParser, QueryParserError, QuerySyntaxExtension};
 pub use self::range_query::*;
 pub use self::regex_query::RegexQuery;
+pub use self::relaxing_search::{Relaxation, RelaxedSearchResult, RelaxingSearch};
 pub use self::reqopt_scorer::RequiredOptionalScorer;
+pub use self::routing_query::RoutingQuery;
 pub use self::score_combiner::{DisjunctionMaxCombiner, ScoreCombiner, SumCombiner};
 pub use self::scorer::Scorer;
 pub use self::set_query::TermSetQuery;
+pub use self::tenant_query::TenantQuery;
 pub use self::term_query::TermQuery;
 pub use self::union::BufferedUnionScorer;
 #[cfg(test)]
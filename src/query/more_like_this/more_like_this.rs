@@ -146,7 +146,7 @@ impl MoreLikeThis {
             return Err(TantivyError::InvalidArgument(
                 "Cannot create more like this query on empty field values. The document may not \
                  have stored fields"
-                    .to_string(),
+                    .into(),
             ));
         }
         let mut field_to_term_freq_map = HashMap::new();
@@ -181,7 +181,7 @@ impl MoreLikeThis {
                     .iter()
                     .map(|value| {
                         value.as_facet().ok_or_else(|| {
-                            TantivyError::InvalidArgument("invalid field value".to_string())
+                            TantivyError::InvalidArgument("invalid field value".into())
                         })
                     })
                     .collect::<Result<Vec<_>>>()?;
@@ -227,9 +227,9 @@ impl MoreLikeThis {
             }
             FieldType::U64(_) => {
                 for value in values {
-                    let val = value.as_u64().ok_or_else(|| {
-                        TantivyError::InvalidArgument("invalid value".to_string())
-                    })?;
+                    let val = value
+                        .as_u64()
+                        .ok_or_else(|| TantivyError::InvalidArgument("invalid value".into()))?;
                     if !self.is_noise_word(val.to_string()) {
                         let term = Term::from_field_u64(field, val);
                         *term_frequencies.entry(term).or_insert(0) += 1;
@@ -238,18 +238,18 @@ impl MoreLikeThis {
             }
             FieldType::Date(_) => {
                 for value in values {
-                    let timestamp = value.as_datetime().ok_or_else(|| {
-                        TantivyError::InvalidArgument("invalid value".to_string())
-                    })?;
+                    let timestamp = value
+                        .as_datetime()
+                        .ok_or_else(|| TantivyError::InvalidArgument("invalid value".into()))?;
                     let term = Term::from_field_date_for_search(field, timestamp);
                     *term_frequencies.entry(term).or_insert(0) += 1;
                 }
             }
             FieldType::I64(_) => {
                 for value in values {
-                    let val = value.as_i64().ok_or_else(|| {
-                        TantivyError::InvalidArgument("invalid value".to_string())
-                    })?;
+                    let val = value
+                        .as_i64()
+                        .ok_or_else(|| TantivyError::InvalidArgument("invalid value".into()))?;
                     if !self.is_noise_word(val.to_string()) {
                         let term = Term::from_field_i64(field, val);
                         *term_frequencies.entry(term).or_insert(0) += 1;
@@ -258,9 +258,9 @@ impl MoreLikeThis {
             }
             FieldType::F64(_) => {
                 for value in values {
-                    let val = value.as_f64().ok_or_else(|| {
-                        TantivyError::InvalidArgument("invalid value".to_string())
-                    })?;
+                    let val = value
+                        .as_f64()
+                        .ok_or_else(|| TantivyError::InvalidArgument("invalid value".into()))?;
                     if !self.is_noise_word(val.to_string()) {
                         let term = Term::from_field_f64(field, val);
                         *term_frequencies.entry(term).or_insert(0) += 1;
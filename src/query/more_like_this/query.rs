@@ -49,7 +49,7 @@ impl Query for MoreLikeThisQuery {
             EnableScoring::Enabled { searcher, .. } => searcher,
             EnableScoring::Disabled { .. } => {
                 let err = "MoreLikeThisQuery requires to enable scoring.".to_string();
-                return Err(crate::TantivyError::InvalidArgument(err));
+                return Err(crate::TantivyError::InvalidArgument(err.into()));
             }
         };
         match &self.target {
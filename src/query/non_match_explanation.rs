@@ -0,0 +1,297 @@
+use serde::Serialize;
+
+use crate::query::{
+    BooleanQuery, BoostQuery, ConstScoreQuery, DisjunctionMaxQuery, EnableScoring, Occur, Query,
+    RangeQuery, TermQuery,
+};
+use crate::{DocAddress, DocSet, Searcher};
+
+/// Why a query, or one of its clauses, does not match a document.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NonMatchReason {
+    /// The document is deleted.
+    Deleted,
+    /// The document does not contain the term of the term query.
+    TermAbsent,
+    /// The document has no value of the field within the bounds of the range query.
+    OutOfRange,
+    /// The document matches a `MUST_NOT` clause.
+    Excluded,
+    /// Some of the required clauses of the boolean query do not match the document, or some of
+    /// its excluded clauses do.
+    RequiredClauses,
+    /// Too few of the optional clauses of the boolean query match the document.
+    NotEnoughOptionalClauses {
+        /// Number of optional clauses matching the document.
+        num_matching: usize,
+        /// Number of optional clauses required to match.
+        minimum: usize,
+    },
+    /// None of the disjuncts of the disjunction max query match the document.
+    NoMatchingDisjunct,
+    /// The query does not match the document, for a reason specific to the type of query.
+    NoMatch,
+}
+
+/// Explains why a query does not match a document, as returned by
+/// [`Searcher::explain_non_match()`].
+///
+/// The explanation is organized in a tree following the query: the details of a boolean query
+/// are the clauses which rejected the document, down to the leaf queries, e.g. a term query on
+/// a term the document does not contain.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct NonMatchExplanation {
+    /// Debug representation of the query, or clause, which does not match.
+    pub query: String,
+    /// Why the query does not match.
+    pub reason: NonMatchReason,
+    /// The clauses responsible for the non match.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<NonMatchExplanation>,
+}
+
+impl NonMatchExplanation {
+    fn new(query: &dyn Query, reason: NonMatchReason) -> NonMatchExplanation {
+        NonMatchExplanation {
+            query: format!("{query:?}"),
+            reason,
+            details: Vec::new(),
+        }
+    }
+
+    /// Returns an indented json representation of the explanation tree for debug usage.
+    pub fn to_pretty_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+fn matches(query: &dyn Query, searcher: &Searcher, doc_address: DocAddress) -> crate::Result<bool> {
+    let weight = query.weight(EnableScoring::disabled_from_searcher(searcher))?;
+    let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+    let mut scorer = weight.scorer(segment_reader, 1.0)?;
+    Ok(scorer.seek(doc_address.doc_id) == doc_address.doc_id)
+}
+
+/// Explains why a query which does not match the document rejects it.
+fn explain_query(
+    query: &dyn Query,
+    searcher: &Searcher,
+    doc_address: DocAddress,
+) -> crate::Result<NonMatchExplanation> {
+    if let Some(boolean_query) = query.downcast_ref::<BooleanQuery>() {
+        return explain_boolean_query(boolean_query, searcher, doc_address);
+    }
+    if let Some(boost_query) = query.downcast_ref::<BoostQuery>() {
+        return explain_query(boost_query.query(), searcher, doc_address);
+    }
+    if let Some(const_score_query) = query.downcast_ref::<ConstScoreQuery>() {
+        return explain_query(const_score_query.query(), searcher, doc_address);
+    }
+    if let Some(disjunction_max_query) = query.downcast_ref::<DisjunctionMaxQuery>() {
+        let mut explanation = NonMatchExplanation::new(query, NonMatchReason::NoMatchingDisjunct);
+        for disjunct in disjunction_max_query.disjuncts() {
+            let disjunct_explanation = explain_query(disjunct.as_ref(), searcher, doc_address)?;
+            explanation.details.push(disjunct_explanation);
+        }
+        return Ok(explanation);
+    }
+    let reason = if query.is::<TermQuery>() {
+        NonMatchReason::TermAbsent
+    } else if query.is::<RangeQuery>() {
+        NonMatchReason::OutOfRange
+    } else {
+        NonMatchReason::NoMatch
+    };
+    Ok(NonMatchExplanation::new(query, reason))
+}
+
+fn explain_boolean_query(
+    boolean_query: &BooleanQuery,
+    searcher: &Searcher,
+    doc_address: DocAddress,
+) -> crate::Result<NonMatchExplanation> {
+    let mut required_details = Vec::new();
+    let mut optional_details = Vec::new();
+    let mut num_optional_matching = 0;
+    let mut has_positive_clause = false;
+    for (occur, subquery) in boolean_query.clauses() {
+        let subquery = subquery.as_ref();
+        let is_matching = matches(subquery, searcher, doc_address)?;
+        match occur {
+            Occur::Must => {
+                has_positive_clause = true;
+                if !is_matching {
+                    required_details.push(explain_query(subquery, searcher, doc_address)?);
+                }
+            }
+            Occur::MustNot => {
+                if is_matching {
+                    required_details
+                        .push(NonMatchExplanation::new(subquery, NonMatchReason::Excluded));
+                }
+            }
+            Occur::Should => {
+                has_positive_clause = true;
+                if is_matching {
+                    num_optional_matching += 1;
+                } else {
+                    optional_details.push(explain_query(subquery, searcher, doc_address)?);
+                }
+            }
+        }
+    }
+    let has_required_clause = boolean_query
+        .clauses()
+        .iter()
+        .any(|(occur, _)| *occur == Occur::Must);
+    // Optional clauses are required if there are no required clauses.
+    let minimum_optional = if has_required_clause {
+        boolean_query.get_minimum_number_should_match()
+    } else {
+        boolean_query.get_minimum_number_should_match().max(1)
+    };
+    let (reason, details) = if !required_details.is_empty() {
+        (NonMatchReason::RequiredClauses, required_details)
+    } else if !has_positive_clause {
+        // A boolean query with only excluded clauses matches no document.
+        (NonMatchReason::NoMatch, Vec::new())
+    } else {
+        let reason = NonMatchReason::NotEnoughOptionalClauses {
+            num_matching: num_optional_matching,
+            minimum: minimum_optional,
+        };
+        (reason, optional_details)
+    };
+    Ok(NonMatchExplanation {
+        query: format!("{boolean_query:?}"),
+        reason,
+        details,
+    })
+}
+
+/// Explains why the query does not match the document, or returns `None` if it does match.
+pub(crate) fn explain_non_match(
+    searcher: &Searcher,
+    query: &dyn Query,
+    doc_address: DocAddress,
+) -> crate::Result<Option<NonMatchExplanation>> {
+    let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+    if segment_reader.is_deleted(doc_address.doc_id) {
+        return Ok(Some(NonMatchExplanation::new(
+            query,
+            NonMatchReason::Deleted,
+        )));
+    }
+    if matches(query, searcher, doc_address)? {
+        return Ok(None);
+    }
+    explain_query(query, searcher, doc_address).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::{NonMatchExplanation, NonMatchReason};
+    use crate::query::{
+        BooleanQuery, BoostQuery, DisjunctionMaxQuery, Occur, Query, RangeQuery, TermQuery,
+    };
+    use crate::schema::{IndexRecordOption, Schema, FAST, INDEXED, STRING};
+    use crate::{DocAddress, Index, IndexWriter, Term};
+
+    #[test]
+    fn test_explain_non_match() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let owner = schema_builder.add_text_field("owner", STRING);
+        let group = schema_builder.add_text_field("group", STRING);
+        let size = schema_builder.add_u64_field("size", INDEXED | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(owner => "alice", group => "admin", size => 10u64))?;
+        index_writer.add_document(doc!(owner => "bob", group => "staff", size => 100u64))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let alice = DocAddress::new(0, 0);
+        let bob = DocAddress::new(0, 1);
+
+        let term_query = |field, text: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(field, text),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let small_size: Box<dyn Query> = Box::new(RangeQuery::new(
+            Bound::Unbounded,
+            Bound::Excluded(Term::from_field_u64(size, 50)),
+        ));
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, small_size.box_clone()),
+            (
+                Occur::Must,
+                Box::new(BoostQuery::new(term_query(owner, "alice"), 2.0)),
+            ),
+            (Occur::MustNot, term_query(group, "staff")),
+        ]);
+        assert_eq!(searcher.explain_non_match(&query, alice)?, None);
+        let explanation = searcher.explain_non_match(&query, bob)?.unwrap();
+        assert_eq!(explanation.reason, NonMatchReason::RequiredClauses);
+        let reasons: Vec<&NonMatchReason> = explanation
+            .details
+            .iter()
+            .map(|detail| &detail.reason)
+            .collect();
+        assert_eq!(
+            reasons,
+            [
+                &NonMatchReason::OutOfRange,
+                &NonMatchReason::TermAbsent,
+                &NonMatchReason::Excluded
+            ]
+        );
+        assert_eq!(
+            explanation.details[1],
+            NonMatchExplanation {
+                query: format!("{:?}", term_query(owner, "alice")),
+                reason: NonMatchReason::TermAbsent,
+                details: Vec::new(),
+            }
+        );
+        assert!(explanation.to_pretty_json().contains("\"excluded\""));
+
+        // Optional clauses.
+        let query = BooleanQuery::with_minimum_required_clauses(
+            vec![
+                (Occur::Should, term_query(owner, "bob")),
+                (Occur::Should, term_query(group, "admin")),
+                (Occur::Should, small_size),
+            ],
+            2,
+        );
+        assert_eq!(searcher.explain_non_match(&query, alice)?, None);
+        let explanation = searcher.explain_non_match(&query, bob)?.unwrap();
+        assert_eq!(
+            explanation.reason,
+            NonMatchReason::NotEnoughOptionalClauses {
+                num_matching: 1,
+                minimum: 2
+            }
+        );
+        assert_eq!(explanation.details.len(), 2);
+
+        let query =
+            DisjunctionMaxQuery::new(vec![term_query(owner, "carol"), term_query(group, "guest")]);
+        let explanation = searcher.explain_non_match(&query, alice)?.unwrap();
+        assert_eq!(explanation.reason, NonMatchReason::NoMatchingDisjunct);
+        assert_eq!(explanation.details.len(), 2);
+
+        index_writer.delete_term(Term::from_field_text(owner, "alice"));
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let explanation = searcher
+            .explain_non_match(&term_query(owner, "alice"), alice)?
+            .unwrap();
+        assert_eq!(explanation.reason, NonMatchReason::Deleted);
+        Ok(())
+    }
+}
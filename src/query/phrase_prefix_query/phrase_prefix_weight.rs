@@ -52,15 +52,21 @@ impl PhrasePrefixWeight {
             .as_ref()
             .map(|similarity_weight| similarity_weight.boost_by(boost));
         let fieldnorm_reader = self.fieldnorm_reader(reader)?;
-        let mut term_postings_list = Vec::new();
-        for &(offset, ref term) in &self.phrase_terms {
-            if let Some(postings) = reader
-                .inverted_index(term.field())?
-                .read_postings(term, IndexRecordOption::WithFreqsAndPositions)?
-            {
+        let mut term_postings_list = Vec::with_capacity(self.phrase_terms.len());
+        if let Some((_, first_term)) = self.phrase_terms.first() {
+            let terms: Vec<Term> = self
+                .phrase_terms
+                .iter()
+                .map(|(_, term)| term.clone())
+                .collect();
+            let postings_list = reader
+                .inverted_index(first_term.field())?
+                .read_postings_many(&terms, IndexRecordOption::WithFreqsAndPositions)?;
+            for (&(offset, _), postings_opt) in self.phrase_terms.iter().zip(postings_list) {
+                let Some(postings) = postings_opt else {
+                    return Ok(None);
+                };
                 term_postings_list.push((offset, postings));
-            } else {
-                return Ok(None);
             }
         }
 
@@ -85,23 +91,16 @@ impl PhrasePrefixWeight {
 
         let mut stream = stream.into_stream()?;
 
-        let mut suffixes = Vec::with_capacity(self.max_expansions as usize);
-        let mut new_term = self.prefix.1.clone();
-        while stream.advance() && (suffixes.len() as u32) < self.max_expansions {
-            new_term.clear_with_type(new_term.typ());
-            new_term.append_bytes(stream.key());
-            if reader.has_deletes() {
-                if let Some(postings) =
-                    inv_index.read_postings(&new_term, IndexRecordOption::WithFreqsAndPositions)?
-                {
-                    suffixes.push(postings);
-                }
-            } else if let Some(postings) =
-                inv_index.read_postings(&new_term, IndexRecordOption::WithFreqsAndPositions)?
-            {
-                suffixes.push(postings);
-            }
+        // The expansions are neighbours in the dictionary, so that their postings are read
+        // together.
+        let mut suffix_term_infos = Vec::with_capacity(self.max_expansions as usize);
+        while stream.advance() && (suffix_term_infos.len() as u32) < self.max_expansions {
+            suffix_term_infos.push(stream.value().clone());
         }
+        let suffixes = inv_index.read_postings_many_from_terminfos(
+            &suffix_term_infos,
+            IndexRecordOption::WithFreqsAndPositions,
+        )?;
 
         Ok(Some(PhrasePrefixScorer::new(
             term_postings_list,
@@ -0,0 +1,180 @@
+use super::fuzzy_phrase_weight::FuzzyPhraseWeight;
+use crate::query::bm25::Bm25Weight;
+use crate::query::{EnableScoring, FuzzyTermQuery, Query, Weight};
+use crate::schema::{Field, IndexRecordOption, Term, Type};
+
+/// `FuzzyPhraseQuery` matches a sequence of terms, each of them within a given Levenshtein
+/// distance.
+///
+/// For instance, the fuzzy phrase query for `"part tme"`, with a distance of 1 for `tme`, will
+/// match the sentence:
+///
+/// **Alan just got a part time job.**
+///
+/// A distance of 0 requires the term to match exactly.
+///
+/// [Slop](FuzzyPhraseQuery::set_slop) allows leniency in term proximity
+/// for some performance trade-off.
+///
+/// Using a `FuzzyPhraseQuery` on a field requires positions
+/// to be indexed for this field.
+#[derive(Clone, Debug)]
+pub struct FuzzyPhraseQuery {
+    field: Field,
+    phrase_terms: Vec<(usize, Term, u8)>,
+    slop: u32,
+    max_expansions: u32,
+    transposition_cost_one: bool,
+    prefix_length: usize,
+}
+
+impl FuzzyPhraseQuery {
+    /// Creates a new `FuzzyPhraseQuery` given a list of terms, along with their Levenshtein
+    /// distance.
+    ///
+    /// There must be at least two terms, and all terms
+    /// must belong to the same field.
+    ///
+    /// Offset for each term will be same as index in the Vector
+    pub fn new(terms: Vec<(Term, u8)>) -> FuzzyPhraseQuery {
+        let terms_with_offset = terms
+            .into_iter()
+            .enumerate()
+            .map(|(offset, (term, distance))| (offset, term, distance))
+            .collect();
+        FuzzyPhraseQuery::new_with_offset(terms_with_offset)
+    }
+
+    /// Creates a new `FuzzyPhraseQuery` given a list of terms, their offsets and their
+    /// Levenshtein distance.
+    ///
+    /// Can be used to provide custom offset for each term.
+    pub fn new_with_offset(mut terms: Vec<(usize, Term, u8)>) -> FuzzyPhraseQuery {
+        assert!(
+            terms.len() > 1,
+            "A phrase query is required to have strictly more than one term."
+        );
+        terms.sort_by_key(|&(offset, _, _)| offset);
+        let field = terms[0].1.field();
+        assert!(
+            terms[1..].iter().all(|(_, term, _)| term.field() == field),
+            "All terms from a phrase query must belong to the same field"
+        );
+        FuzzyPhraseQuery {
+            field,
+            phrase_terms: terms,
+            slop: 0,
+            max_expansions: 1 << 14,
+            transposition_cost_one: true,
+            prefix_length: 0,
+        }
+    }
+
+    /// Slop allowed for the phrase.
+    ///
+    /// The query will match if its terms are separated by `slop` terms at most.
+    /// See [`PhraseQuery::set_slop`](crate::query::PhraseQuery::set_slop).
+    ///
+    /// By default the slop is 0 meaning query terms need to be adjacent.
+    pub fn set_slop(&mut self, value: u32) {
+        self.slop = value;
+    }
+
+    /// Sets the max expansions a fuzzy term can match. The limit will be over all terms.
+    /// After the limit is hit an error will be returned.
+    pub fn set_max_expansions(&mut self, value: u32) {
+        self.max_expansions = value;
+    }
+
+    /// Sets whether a transposition costs 1 or 2. By default, it costs 1.
+    pub fn set_transposition_cost_one(&mut self, transposition_cost_one: bool) {
+        self.transposition_cost_one = transposition_cost_one;
+    }
+
+    /// Sets the number of leading characters of every term which have to match exactly.
+    ///
+    /// See [`FuzzyTermQuery::with_prefix_length`].
+    pub fn set_prefix_length(&mut self, prefix_length: usize) {
+        self.prefix_length = prefix_length;
+    }
+
+    /// The [`Field`] this `FuzzyPhraseQuery` is targeting.
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    /// `Term`s in the phrase without the associated offsets and distances.
+    pub fn phrase_terms(&self) -> Vec<Term> {
+        self.phrase_terms
+            .iter()
+            .map(|(_, term, _)| term.clone())
+            .collect::<Vec<Term>>()
+    }
+
+    /// Returns the [`FuzzyPhraseWeight`] for the given phrase query given a specific `searcher`.
+    ///
+    /// This function is the same as [`Query::weight()`] except it returns
+    /// a specialized type [`FuzzyPhraseWeight`] instead of a Boxed trait.
+    pub(crate) fn fuzzy_phrase_weight(
+        &self,
+        enable_scoring: EnableScoring<'_>,
+    ) -> crate::Result<FuzzyPhraseWeight> {
+        let schema = enable_scoring.schema();
+        let field_entry = schema.get_field_entry(self.field);
+        let field_type = field_entry.field_type().value_type();
+        if field_type != Type::Str {
+            return Err(crate::TantivyError::SchemaError(format!(
+                "FuzzyPhraseQuery can only be used with a field of type text, but got \
+                 {field_type:?}"
+            )));
+        }
+        let has_positions = field_entry
+            .field_type()
+            .get_index_record_option()
+            .map(IndexRecordOption::has_positions)
+            .unwrap_or(false);
+        if !has_positions {
+            let field_name = field_entry.name();
+            return Err(crate::TantivyError::SchemaError(format!(
+                "Applied phrase query on field {field_name:?}, which does not have positions \
+                 indexed"
+            )));
+        }
+        let terms = self.phrase_terms();
+        let bm25_weight_opt = match enable_scoring {
+            EnableScoring::Enabled {
+                statistics_provider,
+                ..
+            } => Some(Bm25Weight::for_terms(statistics_provider, &terms)?),
+            EnableScoring::Disabled { .. } => None,
+        };
+        let fuzzy_terms = self
+            .phrase_terms
+            .iter()
+            .map(|(offset, term, distance)| {
+                let automaton_weight =
+                    FuzzyTermQuery::new(term.clone(), *distance, self.transposition_cost_one)
+                        .with_prefix_length(self.prefix_length)
+                        .specialized_weight()?;
+                Ok((*offset, automaton_weight))
+            })
+            .collect::<crate::Result<Vec<_>>>()?;
+        Ok(FuzzyPhraseWeight::new(
+            self.field,
+            fuzzy_terms,
+            bm25_weight_opt,
+            self.max_expansions,
+            self.slop,
+        ))
+    }
+}
+
+impl Query for FuzzyPhraseQuery {
+    /// Create the weight associated with a query.
+    ///
+    /// See [`Weight`].
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let phrase_weight = self.fuzzy_phrase_weight(enable_scoring)?;
+        Ok(Box::new(phrase_weight))
+    }
+}
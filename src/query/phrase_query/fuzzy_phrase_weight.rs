@@ -0,0 +1,181 @@
+use super::regex_phrase_weight::RegexPhraseWeight;
+use super::PhraseScorer;
+use crate::fieldnorm::FieldNormReader;
+use crate::index::SegmentReader;
+use crate::postings::Postings;
+use crate::query::bm25::Bm25Weight;
+use crate::query::explanation::does_not_match;
+use crate::query::fuzzy_query::FuzzyAutomaton;
+use crate::query::union::SimpleUnion;
+use crate::query::{AutomatonWeight, EmptyScorer, Explanation, Scorer, Weight};
+use crate::schema::Field;
+use crate::{DocId, DocSet, Score};
+
+type UnionType = SimpleUnion<Box<dyn Postings + 'static>>;
+
+/// The `FuzzyPhraseWeight` is the weight associated to a fuzzy phrase query.
+///
+/// The terms matched by the automaton of each position are unioned the same way as for the
+/// [`RegexPhraseWeight`].
+pub struct FuzzyPhraseWeight {
+    field: Field,
+    phrase_terms: Vec<(usize, AutomatonWeight<FuzzyAutomaton>)>,
+    similarity_weight_opt: Option<Bm25Weight>,
+    slop: u32,
+    max_expansions: u32,
+}
+
+impl FuzzyPhraseWeight {
+    /// Creates a new phrase weight.
+    /// If `similarity_weight_opt` is None, then scoring is disabled
+    pub fn new(
+        field: Field,
+        phrase_terms: Vec<(usize, AutomatonWeight<FuzzyAutomaton>)>,
+        similarity_weight_opt: Option<Bm25Weight>,
+        max_expansions: u32,
+        slop: u32,
+    ) -> FuzzyPhraseWeight {
+        FuzzyPhraseWeight {
+            field,
+            phrase_terms,
+            similarity_weight_opt,
+            slop,
+            max_expansions,
+        }
+    }
+
+    fn fieldnorm_reader(&self, reader: &SegmentReader) -> crate::Result<FieldNormReader> {
+        if self.similarity_weight_opt.is_some() {
+            if let Some(fieldnorm_reader) = reader.fieldnorms_readers().get_field(self.field)? {
+                return Ok(fieldnorm_reader);
+            }
+        }
+        Ok(FieldNormReader::constant(reader.max_doc(), 1))
+    }
+
+    pub(crate) fn phrase_scorer(
+        &self,
+        reader: &SegmentReader,
+        boost: Score,
+    ) -> crate::Result<Option<PhraseScorer<UnionType>>> {
+        let similarity_weight_opt = self
+            .similarity_weight_opt
+            .as_ref()
+            .map(|similarity_weight| similarity_weight.boost_by(boost));
+        let fieldnorm_reader = self.fieldnorm_reader(reader)?;
+        let mut posting_lists = Vec::new();
+        let inverted_index = reader.inverted_index(self.field)?;
+        let mut num_terms = 0;
+        for (offset, automaton_weight) in &self.phrase_terms {
+            let term_infos = automaton_weight.get_match_term_infos(reader)?;
+            // If term_infos is empty, the phrase can not match any documents.
+            if term_infos.is_empty() {
+                return Ok(None);
+            }
+            num_terms += term_infos.len();
+            if num_terms > self.max_expansions as usize {
+                return Err(crate::TantivyError::InvalidArgument(
+                    format!("Phrase query exceeded max expansions {num_terms}").into(),
+                ));
+            }
+            let union =
+                RegexPhraseWeight::get_union_from_term_infos(&term_infos, reader, &inverted_index)?;
+            posting_lists.push((*offset, union));
+        }
+
+        Ok(Some(PhraseScorer::new(
+            posting_lists,
+            similarity_weight_opt,
+            fieldnorm_reader,
+            self.slop,
+        )))
+    }
+}
+
+impl Weight for FuzzyPhraseWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        if let Some(scorer) = self.phrase_scorer(reader, boost)? {
+            Ok(Box::new(scorer))
+        } else {
+            Ok(Box::new(EmptyScorer))
+        }
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let Some(mut scorer) = self.phrase_scorer(reader, 1.0)? else {
+            return Err(does_not_match(doc));
+        };
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        let fieldnorm_reader = self.fieldnorm_reader(reader)?;
+        let fieldnorm_id = fieldnorm_reader.fieldnorm_id(doc);
+        let phrase_count = scorer.phrase_count();
+        let mut explanation = Explanation::new("Phrase Scorer", scorer.score());
+        if let Some(similarity_weight) = self.similarity_weight_opt.as_ref() {
+            explanation.add_detail(similarity_weight.explain(fieldnorm_id, phrase_count));
+        }
+        Ok(explanation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::create_index;
+    use crate::collector::Count;
+    use crate::docset::TERMINATED;
+    use crate::query::{EnableScoring, FuzzyPhraseQuery};
+    use crate::schema::Term;
+    use crate::DocSet;
+
+    #[test]
+    pub fn test_fuzzy_phrase() -> crate::Result<()> {
+        let index = create_index(&[
+            "alan just got a part time job",
+            "a time part",
+            "part tame",
+            "part timer",
+        ])?;
+        let schema = index.schema();
+        let text_field = schema.get_field("text").unwrap();
+        let searcher = index.reader()?.searcher();
+        let phrase_query = FuzzyPhraseQuery::new(vec![
+            (Term::from_field_text(text_field, "part"), 0),
+            (Term::from_field_text(text_field, "tme"), 1),
+        ]);
+        let enable_scoring = EnableScoring::enabled_from_searcher(&searcher);
+        let phrase_weight = phrase_query.fuzzy_phrase_weight(enable_scoring)?;
+        let mut phrase_scorer = phrase_weight
+            .phrase_scorer(searcher.segment_reader(0u32), 1.0)?
+            .unwrap();
+        assert_eq!(phrase_scorer.doc(), 0);
+        assert_eq!(phrase_scorer.advance(), 2);
+        assert_eq!(phrase_scorer.advance(), TERMINATED);
+
+        let mut phrase_query = FuzzyPhraseQuery::new(vec![
+            (Term::from_field_text(text_field, "prt"), 1),
+            (Term::from_field_text(text_field, "time"), 2),
+        ]);
+        assert_eq!(searcher.search(&phrase_query, &Count)?, 3);
+        // "prt" does not match "part" if its first two characters are required to match exactly.
+        phrase_query.set_prefix_length(2);
+        assert_eq!(searcher.search(&phrase_query, &Count)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_fuzzy_phrase_max_expansions() -> crate::Result<()> {
+        let index = create_index(&["aa ab", "ac ad"])?;
+        let schema = index.schema();
+        let text_field = schema.get_field("text").unwrap();
+        let searcher = index.reader()?.searcher();
+        let mut phrase_query = FuzzyPhraseQuery::new(vec![
+            (Term::from_field_text(text_field, "a"), 1),
+            (Term::from_field_text(text_field, "a"), 1),
+        ]);
+        assert_eq!(searcher.search(&phrase_query, &Count)?, 2);
+        phrase_query.set_max_expansions(3);
+        assert!(searcher.search(&phrase_query, &Count).is_err());
+        Ok(())
+    }
+}
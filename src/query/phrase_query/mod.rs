@@ -1,3 +1,5 @@
+pub mod fuzzy_phrase_query;
+mod fuzzy_phrase_weight;
 mod phrase_query;
 mod phrase_scorer;
 mod phrase_weight;
@@ -396,4 +398,63 @@ pub(crate) mod tests {
         assert_eq!(&matching_docs(r#"arr.text:"elliot smith""#), &[2]);
         Ok(())
     }
+
+    #[test]
+    pub fn test_phrase_query_with_positions_checkpoints() -> crate::Result<()> {
+        use crate::collector::TopDocs;
+        use crate::schema::{IndexRecordOption, TextFieldIndexing, TextOptions};
+
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", TEXT);
+        let checkpointed_options = TextOptions::default().set_indexing_options(
+            TextFieldIndexing::default()
+                .set_index_option(IndexRecordOption::WithFreqsAndPositions)
+                .set_positions_checkpoint_interval(4),
+        );
+        let checkpointed_field =
+            schema_builder.add_text_field("checkpointed", checkpointed_options);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            let words = ["a", "b", "c", "d", "e"];
+            let mut seed = 7u64;
+            for num_words in [3, 2_000, 50, 5_000] {
+                let text: Vec<&str> = (0..num_words)
+                    .map(|_| {
+                        seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+                        words[(seed >> 33) as usize % words.len()]
+                    })
+                    .collect();
+                let text = text.join(" ");
+                index_writer
+                    .add_document(doc!(text_field => text.clone(), checkpointed_field => text))?;
+            }
+            index_writer.commit()?;
+        }
+        let searcher = index.reader()?.searcher();
+        let search = |field, texts: &[&str], slop: u32| {
+            let terms: Vec<Term> = texts
+                .iter()
+                .map(|text| Term::from_field_text(field, text))
+                .collect();
+            let mut phrase_query = PhraseQuery::new(terms);
+            phrase_query.set_slop(slop);
+            searcher
+                .search(&phrase_query, &TopDocs::with_limit(10))
+                .unwrap()
+        };
+        for texts in [
+            &["a", "b"][..],
+            &["e", "a", "c"],
+            &["d", "d", "d", "d"],
+            &["a", "b", "c", "d", "e"],
+        ] {
+            for slop in [0, 1, 3] {
+                let expected = search(text_field, texts, slop);
+                assert!(!expected.is_empty());
+                assert_eq!(search(checkpointed_field, texts, slop), expected);
+            }
+        }
+        Ok(())
+    }
 }
@@ -94,6 +94,14 @@ impl PhraseQuery {
             .collect::<Vec<Term>>()
     }
 
+    pub(crate) fn phrase_terms_with_offsets(&self) -> &[(usize, Term)] {
+        &self.phrase_terms
+    }
+
+    pub(crate) fn slop(&self) -> u32 {
+        self.slop
+    }
+
     /// Returns the [`PhraseWeight`] for the given phrase query given a specific `searcher`.
     ///
     /// This function is the same as [`Query::weight()`] except it returns
@@ -23,6 +23,16 @@ impl<TPostings: Postings> PostingsWithOffset<TPostings> {
     pub fn positions(&mut self, output: &mut Vec<u32>) {
         self.postings.positions_with_offset(self.offset, output)
     }
+
+    /// Returns the offsetted positions greater than or equal to `min_position`.
+    pub fn positions_from(&mut self, min_position: u32, output: &mut Vec<u32>) {
+        output.clear();
+        self.postings.append_positions_with_offset_from(
+            self.offset,
+            min_position.saturating_sub(self.offset),
+            output,
+        )
+    }
 }
 
 impl<TPostings: Postings> DocSet for PostingsWithOffset<TPostings> {
@@ -469,9 +479,10 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
         }
         for i in 1..self.num_terms - 1 {
             {
+                let min_position = self.min_right_position();
                 self.intersection_docset
                     .docset_mut_specialized(i)
-                    .positions(&mut self.right_positions);
+                    .positions_from(min_position, &mut self.right_positions);
             }
             if self.has_slop() {
                 if self.num_terms > 2 {
@@ -499,9 +510,23 @@ impl<TPostings: Postings> PhraseScorer<TPostings> {
                 return;
             }
         }
+        let min_position = self.min_right_position();
         self.intersection_docset
             .docset_mut_specialized(self.num_terms - 1)
-            .positions(&mut self.right_positions);
+            .positions_from(min_position, &mut self.right_positions);
+    }
+
+    /// Returns the lowest position of the next term which can match one of the left positions.
+    ///
+    /// Lower positions are skipped, without decoding them if the field stores positions
+    /// checkpoints.
+    fn min_right_position(&self) -> u32 {
+        self.left_positions
+            .iter()
+            .min()
+            .map_or(0, |&min_left_position| {
+                min_left_position.saturating_sub(self.slop)
+            })
     }
 
     fn has_slop(&self) -> bool {
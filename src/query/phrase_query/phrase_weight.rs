@@ -49,16 +49,21 @@ impl PhraseWeight {
             .as_ref()
             .map(|similarity_weight| similarity_weight.boost_by(boost));
         let fieldnorm_reader = self.fieldnorm_reader(reader)?;
-        let mut term_postings_list = Vec::new();
-        for &(offset, ref term) in &self.phrase_terms {
-            if let Some(postings) = reader
-                .inverted_index(term.field())?
-                .read_postings(term, IndexRecordOption::WithFreqsAndPositions)?
-            {
-                term_postings_list.push((offset, postings));
-            } else {
+        let field = self.phrase_terms[0].1.field();
+        let terms: Vec<Term> = self
+            .phrase_terms
+            .iter()
+            .map(|(_, term)| term.clone())
+            .collect();
+        let postings_list = reader
+            .inverted_index(field)?
+            .read_postings_many(&terms, IndexRecordOption::WithFreqsAndPositions)?;
+        let mut term_postings_list = Vec::with_capacity(postings_list.len());
+        for (&(offset, _), postings_opt) in self.phrase_terms.iter().zip(postings_list) {
+            let Some(postings) = postings_opt else {
                 return Ok(None);
-            }
+            };
+            term_postings_list.push((offset, postings));
         }
         Ok(Some(PhraseScorer::new(
             term_postings_list,
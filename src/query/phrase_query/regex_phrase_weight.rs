@@ -68,8 +68,9 @@ impl RegexPhraseWeight {
         let inverted_index = reader.inverted_index(self.field)?;
         let mut num_terms = 0;
         for &(offset, ref term) in &self.phrase_terms {
-            let regex = Regex::new(term)
-                .map_err(|e| crate::TantivyError::InvalidArgument(format!("Invalid regex: {e}")))?;
+            let regex = Regex::new(term).map_err(|e| {
+                crate::TantivyError::InvalidArgument(format!("Invalid regex: {e}").into())
+            })?;
 
             let automaton: AutomatonWeight<Regex> =
                 AutomatonWeight::new(self.field, Arc::new(regex));
@@ -80,10 +81,9 @@ impl RegexPhraseWeight {
             }
             num_terms += term_infos.len();
             if num_terms > self.max_expansions as usize {
-                return Err(crate::TantivyError::InvalidArgument(format!(
-                    "Phrase query exceeded max expansions {}",
-                    num_terms
-                )));
+                return Err(crate::TantivyError::InvalidArgument(
+                    format!("Phrase query exceeded max expansions {}", num_terms).into(),
+                ));
             }
             let union = Self::get_union_from_term_infos(&term_infos, reader, &inverted_index)?;
 
@@ -0,0 +1,1033 @@
+//! Elasticsearch compatible query DSL.
+//!
+//! [`QueryDsl`] deserializes a subset of the
+//! [Elasticsearch query DSL](https://www.elastic.co/guide/en/elasticsearch/reference/current/query-dsl.html),
+//! the same way the [`aggregation`](crate::aggregation) module mirrors the Elasticsearch
+//! aggregation requests. It is then converted into a tantivy [`Query`] against a [`Schema`].
+//!
+//! The supported queries are `match_all`, `bool`, `term`, `terms`, `match`, `match_phrase`,
+//! `range`, `exists` and `query_string`. All of them accept a `boost` parameter.
+//!
+//! ```
+//! use tantivy::collector::Count;
+//! use tantivy::query::query_dsl::QueryDsl;
+//! use tantivy::schema::{Schema, FAST, STRING, TEXT};
+//! use tantivy::{doc, Index, IndexWriter};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let title = schema_builder.add_text_field("title", TEXT);
+//! let brand = schema_builder.add_text_field("brand", STRING);
+//! let price = schema_builder.add_u64_field("price", FAST);
+//! let index = Index::create_in_ram(schema_builder.build());
+//! let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+//! index_writer.add_document(doc!(title => "Blue running shoes", brand => "acme", price => 80u64))?;
+//! index_writer.add_document(doc!(title => "Red running shoes", brand => "acme", price => 120u64))?;
+//! index_writer.add_document(doc!(title => "Blue hat", brand => "hatco", price => 20u64))?;
+//! index_writer.commit()?;
+//!
+//! let query_dsl: QueryDsl = serde_json::from_str(
+//!     r#"{
+//!         "bool": {
+//!             "must": { "match": { "title": "running shoes" } },
+//!             "filter": [
+//!                 { "term": { "brand": "acme" } },
+//!                 { "range": { "price": { "lt": 100 } } }
+//!             ]
+//!         }
+//!     }"#,
+//! )
+//! .unwrap();
+//! let query = query_dsl.to_query_for_index(&index).unwrap();
+//! let searcher = index.reader()?.searcher();
+//! assert_eq!(searcher.search(&query, &Count)?, 1);
+//! # Ok(())
+//! # }
+//! ```
+use std::collections::BTreeMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+use query_grammar::{Delimiter, UserInputAst, UserInputBound, UserInputLeaf, UserInputLiteral};
+use serde::de::{self, MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::query::{
+    AllQuery, BooleanQuery, BoostQuery, ConstScoreQuery, EmptyQuery, ExistsQuery, Occur, Query,
+    QueryParser, QueryParserError, TermQuery, TermSetQuery,
+};
+use crate::schema::{Field, FieldType, IndexRecordOption, Schema, Term};
+use crate::tokenizer::TokenizerManager;
+use crate::{Index, Score};
+
+/// A query of the Elasticsearch query DSL.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryDsl {
+    /// Matches all of the documents.
+    MatchAll(MatchAllQueryDsl),
+    /// Combines queries with boolean clauses.
+    Bool(Box<BoolQueryDsl>),
+    /// Matches the documents containing an exact term.
+    Term(FieldQueryDsl<TermQueryParams>),
+    /// Matches the documents containing one of several exact terms.
+    Terms(TermsQueryDsl),
+    /// Matches the documents containing any, or all, of the terms of an analyzed text.
+    Match(FieldQueryDsl<MatchQueryParams>),
+    /// Matches the documents containing an analyzed phrase.
+    MatchPhrase(FieldQueryDsl<MatchPhraseQueryParams>),
+    /// Matches the documents with a value in a range.
+    Range(FieldQueryDsl<RangeQueryParams>),
+    /// Matches the documents with a value in a fast field.
+    Exists(ExistsQueryDsl),
+    /// Matches the documents of a query written with the tantivy
+    /// [query parser](crate::query::QueryParser) syntax.
+    QueryString(QueryStringQueryDsl),
+}
+
+/// A scalar value of a query, like the value of a `term` query or a bound of a `range` query.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum QueryDslValue {
+    /// Boolean value
+    Bool(bool),
+    /// `i64` value
+    I64(i64),
+    /// `u64` value
+    U64(u64),
+    /// `f64` value
+    F64(f64),
+    /// String value, also used for dates, ip addresses, etc.
+    Str(String),
+}
+
+impl fmt::Display for QueryDslValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QueryDslValue::Bool(val) => val.fmt(f),
+            QueryDslValue::I64(val) => val.fmt(f),
+            QueryDslValue::U64(val) => val.fmt(f),
+            QueryDslValue::F64(val) => val.fmt(f),
+            QueryDslValue::Str(val) => val.fmt(f),
+        }
+    }
+}
+
+/// `match_all` query, e.g. `{ "match_all": {} }`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MatchAllQueryDsl {
+    /// Boost applied to the score of the query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<Score>,
+}
+
+/// `bool` query.
+///
+/// Each clause accepts either a single query or an array of queries.
+///
+/// ```json
+/// {
+///     "bool": {
+///         "must": { "match": { "title": "shoes" } },
+///         "filter": [{ "term": { "brand": "acme" } }],
+///         "should": [{ "term": { "color": "blue" } }],
+///         "must_not": [{ "range": { "price": { "gt": 100 } } }]
+///     }
+/// }
+/// ```
+///
+/// `filter` clauses are required, but do not contribute to the score. As in Elasticsearch, a bool
+/// query without any `must`, `filter` or `should` clause matches all of the documents that are
+/// not excluded by its `must_not` clauses.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BoolQueryDsl {
+    /// Queries the documents are required to match.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub must: Vec<QueryDsl>,
+    /// Queries the documents are required to match, without contributing to the score.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub filter: Vec<QueryDsl>,
+    /// Queries the documents should match.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub should: Vec<QueryDsl>,
+    /// Queries the documents are required not to match.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub must_not: Vec<QueryDsl>,
+    /// Minimum number of `should` clauses the documents are required to match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum_should_match: Option<usize>,
+    /// Boost applied to the score of the query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<Score>,
+}
+
+/// A query on a single field, written as an object with the field as its only key, e.g.
+/// `{ "title": { "query": "shoes" } }`.
+///
+/// Except for ranges, the parameters can also be written as a single value, e.g.
+/// `{ "title": "shoes" }`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldQueryDsl<T> {
+    /// Name of the field, possibly including a json path.
+    pub field: String,
+    /// Parameters of the query.
+    pub params: T,
+}
+
+impl<T: Serialize> Serialize for FieldQueryDsl<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(&self.field, &self.params)?;
+        map.end()
+    }
+}
+
+impl<'de, T> Deserialize<'de> for FieldQueryDsl<T>
+where
+    T: Deserialize<'de> + TryFrom<QueryDslValue>,
+    T::Error: fmt::Display,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        struct FieldQueryVisitor<T>(PhantomData<T>);
+
+        impl<'de, T> Visitor<'de> for FieldQueryVisitor<T>
+        where
+            T: Deserialize<'de> + TryFrom<QueryDslValue>,
+            T::Error: fmt::Display,
+        {
+            type Value = FieldQueryDsl<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an object with a single field")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<FieldQueryDsl<T>, A::Error>
+            where A: MapAccess<'de> {
+                let (field, value_or_params) = map
+                    .next_entry::<String, ValueOrParams<T>>()?
+                    .ok_or_else(|| {
+                        de::Error::custom("expected a field, but got an empty object")
+                    })?;
+                let params = match value_or_params {
+                    ValueOrParams::Value(value) => T::try_from(value).map_err(de::Error::custom)?,
+                    ValueOrParams::Params(params) => params,
+                };
+                if map.next_key::<String>()?.is_some() {
+                    return Err(de::Error::custom(format!(
+                        "expected a single field, but got several fields along with {field:?}"
+                    )));
+                }
+                Ok(FieldQueryDsl { field, params })
+            }
+        }
+
+        deserializer.deserialize_map(FieldQueryVisitor(PhantomData))
+    }
+}
+
+/// Parameters of a `term` query, e.g. `{ "term": { "brand": { "value": "acme" } } }`, or
+/// `{ "term": { "brand": "acme" } }` for short.
+///
+/// The value is not analyzed, but it is normalized with the tokenizer of text fields, which is
+/// required to produce a single token.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TermQueryParams {
+    /// The value of the term.
+    pub value: QueryDslValue,
+    /// Boost applied to the score of the query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<Score>,
+}
+
+/// `terms` query, e.g. `{ "terms": { "brand": ["acme", "hatco"] } }`.
+///
+/// The values are handled as in [`TermQueryParams`]. The matched documents get a constant score.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TermsQueryDsl {
+    /// Name of the field, possibly including a json path.
+    pub field: String,
+    /// The values of the terms.
+    pub values: Vec<QueryDslValue>,
+    /// Boost applied to the score of the query.
+    pub boost: Option<Score>,
+}
+
+impl Serialize for TermsQueryDsl {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry(&self.field, &self.values)?;
+        if let Some(boost) = self.boost {
+            map.serialize_entry("boost", &boost)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for TermsQueryDsl {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let mut map: BTreeMap<String, serde_json::Value> = BTreeMap::deserialize(deserializer)?;
+        let boost = map
+            .remove("boost")
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(de::Error::custom)?;
+        if map.len() != 1 {
+            return Err(de::Error::custom(format!(
+                "expected a single field in terms query, but got {:?}",
+                map.keys().collect::<Vec<_>>()
+            )));
+        }
+        let (field, values) = map.into_iter().next().unwrap();
+        let values = serde_json::from_value(values).map_err(de::Error::custom)?;
+        Ok(TermsQueryDsl {
+            field,
+            values,
+            boost,
+        })
+    }
+}
+
+/// Whether all of the terms of a `match` query are required.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MatchOperator {
+    /// Documents are required to match any of the terms.
+    #[default]
+    #[serde(rename = "or", alias = "OR")]
+    Or,
+    /// Documents are required to match all of the terms.
+    #[serde(rename = "and", alias = "AND")]
+    And,
+}
+
+/// Parameters of a `match` query, e.g.
+/// `{ "match": { "title": { "query": "running shoes", "operator": "and" } } }`, or
+/// `{ "match": { "title": "running shoes" } }` for short.
+///
+/// The query is analyzed with the tokenizer of the field, and each resulting token becomes a
+/// term. On other types of fields, the query is handled as a single term.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MatchQueryParams {
+    /// The text to analyze.
+    pub query: QueryDslValue,
+    /// Whether all of the terms are required. Defaults to `or`.
+    #[serde(default)]
+    pub operator: MatchOperator,
+    /// Minimum number of terms the documents are required to match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minimum_should_match: Option<usize>,
+    /// Boost applied to the score of the query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<Score>,
+}
+
+/// Parameters of a `match_phrase` query, e.g.
+/// `{ "match_phrase": { "title": { "query": "running shoes", "slop": 1 } } }`, or
+/// `{ "match_phrase": { "title": "running shoes" } }` for short.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MatchPhraseQueryParams {
+    /// The phrase to analyze.
+    pub query: String,
+    /// Maximum number of positions the terms of the phrase can be moved by.
+    #[serde(default)]
+    pub slop: u32,
+    /// Boost applied to the score of the query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<Score>,
+}
+
+/// Parameters of a `range` query, e.g. `{ "range": { "price": { "gte": 10, "lt": 100 } } }`.
+///
+/// Dates are expected in the RFC 3339 format.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RangeQueryParams {
+    /// Exclusive lower bound.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gt: Option<QueryDslValue>,
+    /// Inclusive lower bound.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gte: Option<QueryDslValue>,
+    /// Exclusive upper bound.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lt: Option<QueryDslValue>,
+    /// Inclusive upper bound.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lte: Option<QueryDslValue>,
+    /// Boost applied to the score of the query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<Score>,
+}
+
+/// `exists` query, e.g. `{ "exists": { "field": "price" } }`.
+///
+/// The field is required to be a fast field. On json fields, documents with a value in any of
+/// the subpaths of the field are matched.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExistsQueryDsl {
+    /// Name of the field, possibly including a json path.
+    pub field: String,
+    /// Boost applied to the score of the query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<Score>,
+}
+
+/// `query_string` query, e.g.
+/// `{ "query_string": { "query": "title:shoes AND price:[10 TO 100]" } }`.
+///
+/// The query is parsed with the tantivy [query parser](crate::query::QueryParser), whose syntax
+/// is close to the Lucene syntax used by Elasticsearch.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QueryStringQueryDsl {
+    /// The query to parse.
+    pub query: String,
+    /// Field searched by the terms of the query without a field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_field: Option<String>,
+    /// Fields searched by the terms of the query without a field, along with `default_field`.
+    ///
+    /// If neither `default_field` nor `fields` is set, all of the indexed text fields are
+    /// searched.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    /// Whether terms of the query are combined as a conjunction or a disjunction by default.
+    #[serde(default)]
+    pub default_operator: MatchOperator,
+    /// Boost applied to the score of the query.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub boost: Option<Score>,
+}
+
+/// Parameters of a field query, which can also be written as a single value.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ValueOrParams<T> {
+    Value(QueryDslValue),
+    Params(T),
+}
+
+impl From<QueryDslValue> for TermQueryParams {
+    fn from(value: QueryDslValue) -> Self {
+        TermQueryParams { value, boost: None }
+    }
+}
+
+impl From<QueryDslValue> for MatchQueryParams {
+    fn from(query: QueryDslValue) -> Self {
+        MatchQueryParams {
+            query,
+            operator: MatchOperator::default(),
+            minimum_should_match: None,
+            boost: None,
+        }
+    }
+}
+
+impl From<QueryDslValue> for MatchPhraseQueryParams {
+    fn from(query: QueryDslValue) -> Self {
+        MatchPhraseQueryParams {
+            query: query.to_string(),
+            slop: 0,
+            boost: None,
+        }
+    }
+}
+
+impl TryFrom<QueryDslValue> for RangeQueryParams {
+    type Error = String;
+
+    fn try_from(value: QueryDslValue) -> Result<Self, String> {
+        Err(format!(
+            "expected an object with the bounds of the range, but got {value}"
+        ))
+    }
+}
+
+fn deserialize_one_or_many<'de, D>(deserializer: D) -> Result<Vec<QueryDsl>, D::Error>
+where D: Deserializer<'de> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(QueryDsl),
+        Many(Vec<QueryDsl>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(query) => vec![query],
+        OneOrMany::Many(queries) => queries,
+    })
+}
+
+impl QueryDsl {
+    /// Converts the query into a tantivy [`Query`].
+    ///
+    /// Fields are resolved against `schema`, and texts are analyzed with the tokenizers of
+    /// `tokenizer_manager`.
+    pub fn to_query(
+        &self,
+        schema: &Schema,
+        tokenizer_manager: &TokenizerManager,
+    ) -> Result<Box<dyn Query>, QueryParserError> {
+        let query_builder = QueryBuilder {
+            schema,
+            tokenizer_manager,
+            query_parser: QueryParser::new(schema.clone(), Vec::new(), tokenizer_manager.clone()),
+        };
+        query_builder.build(self)
+    }
+
+    /// Converts the query into a tantivy [`Query`], given the schema and the tokenizers of
+    /// `index`.
+    pub fn to_query_for_index(&self, index: &Index) -> Result<Box<dyn Query>, QueryParserError> {
+        self.to_query(&index.schema(), index.tokenizers())
+    }
+}
+
+struct QueryBuilder<'a> {
+    schema: &'a Schema,
+    tokenizer_manager: &'a TokenizerManager,
+    query_parser: QueryParser,
+}
+
+impl QueryBuilder<'_> {
+    fn build(&self, query_dsl: &QueryDsl) -> Result<Box<dyn Query>, QueryParserError> {
+        let (query, boost): (Box<dyn Query>, Option<Score>) = match query_dsl {
+            QueryDsl::MatchAll(match_all) => (Box::new(AllQuery), match_all.boost),
+            QueryDsl::Bool(bool_query) => (self.build_bool(bool_query)?, bool_query.boost),
+            QueryDsl::Term(FieldQueryDsl { field, params }) => {
+                let (field, json_path) = self.resolve_field(field)?;
+                let term = self.query_parser.compute_boundary_term(
+                    field,
+                    json_path,
+                    &params.value.to_string(),
+                )?;
+                (
+                    Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs)),
+                    params.boost,
+                )
+            }
+            QueryDsl::Terms(terms_query) => {
+                let (field, json_path) = self.resolve_field(&terms_query.field)?;
+                let terms = terms_query
+                    .values
+                    .iter()
+                    .map(|value| {
+                        self.query_parser.compute_boundary_term(
+                            field,
+                            json_path,
+                            &value.to_string(),
+                        )
+                    })
+                    .collect::<Result<Vec<Term>, QueryParserError>>()?;
+                (Box::new(TermSetQuery::new(terms)), terms_query.boost)
+            }
+            QueryDsl::Match(FieldQueryDsl { field, params }) => {
+                (self.build_match(field, params)?, params.boost)
+            }
+            QueryDsl::MatchPhrase(FieldQueryDsl { field, params }) => {
+                let literal = UserInputLiteral {
+                    field_name: Some(field.clone()),
+                    phrase: params.query.clone(),
+                    delimiter: Delimiter::DoubleQuotes,
+                    slop: params.slop,
+                    prefix: false,
+                };
+                let query =
+                    self.query_parser
+                        .build_query_from_user_input_ast(UserInputAst::from(
+                            UserInputLeaf::Literal(literal),
+                        ))?;
+                (query, params.boost)
+            }
+            QueryDsl::Range(FieldQueryDsl { field, params }) => {
+                let to_bound = |inclusive: &Option<QueryDslValue>,
+                                exclusive: &Option<QueryDslValue>| {
+                    match (inclusive, exclusive) {
+                        (_, Some(value)) => UserInputBound::Exclusive(value.to_string()),
+                        (Some(value), None) => UserInputBound::Inclusive(value.to_string()),
+                        (None, None) => UserInputBound::Unbounded,
+                    }
+                };
+                let range = UserInputLeaf::Range {
+                    field: Some(field.clone()),
+                    lower: to_bound(&params.gte, &params.gt),
+                    upper: to_bound(&params.lte, &params.lt),
+                };
+                let query = self
+                    .query_parser
+                    .build_query_from_user_input_ast(UserInputAst::from(range))?;
+                (query, params.boost)
+            }
+            QueryDsl::Exists(exists_query) => {
+                self.resolve_field(&exists_query.field)?;
+                (
+                    Box::new(ExistsQuery::new(exists_query.field.clone(), true)),
+                    exists_query.boost,
+                )
+            }
+            QueryDsl::QueryString(query_string) => {
+                (self.build_query_string(query_string)?, query_string.boost)
+            }
+        };
+        Ok(match boost {
+            Some(boost) => Box::new(BoostQuery::new(query, boost)),
+            None => query,
+        })
+    }
+
+    fn resolve_field<'b>(&self, full_path: &'b str) -> Result<(Field, &'b str), QueryParserError> {
+        self.query_parser
+            .split_full_path(full_path)
+            .ok_or_else(|| QueryParserError::FieldDoesNotExist(full_path.to_string()))
+    }
+
+    fn build_bool(&self, bool_query: &BoolQueryDsl) -> Result<Box<dyn Query>, QueryParserError> {
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for query_dsl in &bool_query.must {
+            clauses.push((Occur::Must, self.build(query_dsl)?));
+        }
+        for query_dsl in &bool_query.filter {
+            let query = ConstScoreQuery::new(self.build(query_dsl)?, 0.0);
+            clauses.push((Occur::Must, Box::new(query)));
+        }
+        for query_dsl in &bool_query.should {
+            clauses.push((Occur::Should, self.build(query_dsl)?));
+        }
+        if clauses.is_empty() {
+            clauses.push((Occur::Must, Box::new(AllQuery)));
+        }
+        for query_dsl in &bool_query.must_not {
+            clauses.push((Occur::MustNot, self.build(query_dsl)?));
+        }
+        // As in Elasticsearch, at least one `should` clause is required to match if there are
+        // no other required clauses.
+        let minimum_should_match = bool_query.minimum_should_match.unwrap_or(
+            if bool_query.must.is_empty()
+                && bool_query.filter.is_empty()
+                && !bool_query.should.is_empty()
+            {
+                1
+            } else {
+                0
+            },
+        );
+        Ok(Box::new(BooleanQuery::with_minimum_required_clauses(
+            clauses,
+            minimum_should_match,
+        )))
+    }
+
+    fn build_match(
+        &self,
+        full_path: &str,
+        params: &MatchQueryParams,
+    ) -> Result<Box<dyn Query>, QueryParserError> {
+        let (field, json_path) = self.resolve_field(full_path)?;
+        let field_entry = self.schema.get_field_entry(field);
+        let text = params.query.to_string();
+        let text_indexing_options = match (field_entry.field_type(), &params.query) {
+            (FieldType::Str(text_options), _) => text_options.get_indexing_options(),
+            (FieldType::JsonObject(json_options), QueryDslValue::Str(_)) => {
+                json_options.get_text_indexing_options()
+            }
+            _ => None,
+        };
+        let terms: Vec<Term> = if let Some(text_indexing_options) = text_indexing_options {
+            let mut text_analyzer = self
+                .tokenizer_manager
+                .get(text_indexing_options.tokenizer())
+                .ok_or_else(|| QueryParserError::UnknownTokenizer {
+                    field: field_entry.name().to_string(),
+                    tokenizer: text_indexing_options.tokenizer().to_string(),
+                })?;
+            let mut terms = Vec::new();
+            let mut token_stream = text_analyzer.token_stream(&text);
+            token_stream.process(&mut |token| {
+                let term = match field_entry.field_type() {
+                    FieldType::JsonObject(json_options) => {
+                        let mut term = Term::from_field_json_path(
+                            field,
+                            json_path,
+                            json_options.is_expand_dots_enabled(),
+                        );
+                        term.append_type_and_str(&token.text);
+                        term
+                    }
+                    _ => Term::from_field_text(field, &token.text),
+                };
+                terms.push(term);
+            });
+            terms
+        } else {
+            vec![self
+                .query_parser
+                .compute_boundary_term(field, json_path, &text)?]
+        };
+        let occur = match params.operator {
+            MatchOperator::Or => Occur::Should,
+            MatchOperator::And => Occur::Must,
+        };
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = terms
+            .into_iter()
+            .map(|term| {
+                let query: Box<dyn Query> =
+                    Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs));
+                (occur, query)
+            })
+            .collect();
+        Ok(match (clauses.len(), params.minimum_should_match) {
+            (0, _) => Box::new(EmptyQuery),
+            (1, None) => clauses.pop().unwrap().1,
+            (_, None) => Box::new(BooleanQuery::new(clauses)),
+            (_, Some(minimum_should_match)) => Box::new(
+                BooleanQuery::with_minimum_required_clauses(clauses, minimum_should_match),
+            ),
+        })
+    }
+
+    fn build_query_string(
+        &self,
+        query_string: &QueryStringQueryDsl,
+    ) -> Result<Box<dyn Query>, QueryParserError> {
+        let mut default_fields: Vec<Field> = query_string
+            .default_field
+            .iter()
+            .chain(&query_string.fields)
+            .map(|field_name| {
+                self.schema
+                    .get_field(field_name)
+                    .map_err(|_| QueryParserError::FieldDoesNotExist(field_name.clone()))
+            })
+            .collect::<Result<_, _>>()?;
+        if default_fields.is_empty() {
+            default_fields = self
+                .schema
+                .fields()
+                .filter(|(_, field_entry)| {
+                    matches!(field_entry.field_type(), FieldType::Str(_))
+                        && field_entry.is_indexed()
+                })
+                .map(|(field, _)| field)
+                .collect();
+        }
+        let mut query_parser = QueryParser::new(
+            self.schema.clone(),
+            default_fields,
+            self.tokenizer_manager.clone(),
+        );
+        if query_string.default_operator == MatchOperator::And {
+            query_parser.set_conjunction_by_default();
+        }
+        query_parser.parse_query(&query_string.query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::collector::Count;
+    use crate::schema::{FAST, INDEXED, STORED, STRING, TEXT};
+    use crate::{Index, IndexWriter};
+
+    fn count(index: &Index, query_dsl: serde_json::Value) -> Result<usize, QueryParserError> {
+        let query_dsl: QueryDsl = serde_json::from_value(query_dsl).unwrap();
+        let query = query_dsl.to_query_for_index(index)?;
+        let searcher = index.reader().unwrap().searcher();
+        Ok(searcher.search(&query, &Count).unwrap())
+    }
+
+    #[test]
+    fn test_query_dsl_deserialize() {
+        let short: QueryDsl =
+            serde_json::from_value(json!({ "term": { "brand": "acme" } })).unwrap();
+        let long: QueryDsl =
+            serde_json::from_value(json!({ "term": { "brand": { "value": "acme" } } })).unwrap();
+        assert_eq!(short, long);
+        assert_eq!(
+            short,
+            QueryDsl::Term(FieldQueryDsl {
+                field: "brand".to_string(),
+                params: TermQueryParams {
+                    value: QueryDslValue::Str("acme".to_string()),
+                    boost: None,
+                },
+            })
+        );
+
+        let bool_query: QueryDsl = serde_json::from_value(json!({
+            "bool": {
+                "must": { "match": { "title": "shoes" } },
+                "should": [
+                    { "match_phrase": { "title": { "query": "red shoes", "slop": 1 } } },
+                    { "terms": { "brand": ["acme", "hatco"], "boost": 2.0 } }
+                ],
+                "must_not": { "range": { "price": { "gte": 10, "lt": 20.5 } } },
+                "minimum_should_match": 1
+            }
+        }))
+        .unwrap();
+        let QueryDsl::Bool(bool_query_dsl) = &bool_query else {
+            panic!("expected a bool query");
+        };
+        assert_eq!(bool_query_dsl.must.len(), 1);
+        assert_eq!(bool_query_dsl.should.len(), 2);
+        assert_eq!(bool_query_dsl.must_not.len(), 1);
+        assert_eq!(
+            bool_query_dsl.should[1],
+            QueryDsl::Terms(TermsQueryDsl {
+                field: "brand".to_string(),
+                values: vec![
+                    QueryDslValue::Str("acme".to_string()),
+                    QueryDslValue::Str("hatco".to_string())
+                ],
+                boost: Some(2.0),
+            })
+        );
+        assert_eq!(
+            bool_query_dsl.must_not[0],
+            QueryDsl::Range(FieldQueryDsl {
+                field: "price".to_string(),
+                params: RangeQueryParams {
+                    gte: Some(QueryDslValue::I64(10)),
+                    lt: Some(QueryDslValue::F64(20.5)),
+                    ..Default::default()
+                },
+            })
+        );
+        let serialized = serde_json::to_value(&bool_query).unwrap();
+        let deserialized: QueryDsl = serde_json::from_value(serialized).unwrap();
+        assert_eq!(deserialized, bool_query);
+
+        for invalid in [
+            json!({ "fuzzy": { "title": "shoes" } }),
+            json!({ "term": { "brand": "acme", "title": "shoes" } }),
+            json!({ "term": {} }),
+            json!({ "range": { "price": 10 } }),
+            json!({ "match": { "title": { "query": "shoes", "fuzziness": 2 } } }),
+            json!({ "terms": { "brand": ["acme"], "title": ["shoes"] } }),
+        ] {
+            assert!(
+                serde_json::from_value::<QueryDsl>(invalid.clone()).is_err(),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_query_dsl_search() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let brand = schema_builder.add_text_field("brand", STRING);
+        let price = schema_builder.add_u64_field("price", INDEXED | FAST);
+        let attributes = schema_builder.add_json_field("attributes", TEXT | STORED | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(
+            title => "Blue running shoes",
+            brand => "acme",
+            price => 80u64,
+            attributes => json!({ "color": "light blue" }),
+        ))?;
+        index_writer.add_document(doc!(
+            title => "Red running shoes",
+            brand => "acme",
+            price => 120u64,
+        ))?;
+        index_writer.add_document(doc!(
+            title => "Blue hat",
+            brand => "Hat Co",
+            price => 20u64,
+            attributes => json!({ "color": "dark red" }),
+        ))?;
+        index_writer.commit()?;
+
+        assert_eq!(count(&index, json!({ "match_all": {} }))?, 3);
+        assert_eq!(count(&index, json!({ "term": { "brand": "acme" } }))?, 2);
+        assert_eq!(count(&index, json!({ "term": { "brand": "Hat Co" } }))?, 1);
+        assert_eq!(count(&index, json!({ "term": { "price": 20 } }))?, 1);
+        assert_eq!(
+            count(&index, json!({ "terms": { "brand": ["acme", "Hat Co"] } }))?,
+            3
+        );
+        assert_eq!(
+            count(&index, json!({ "match": { "title": "blue shoes" } }))?,
+            3
+        );
+        assert_eq!(
+            count(
+                &index,
+                json!({ "match": { "title": { "query": "blue shoes", "operator": "and" } } })
+            )?,
+            1
+        );
+        assert_eq!(
+            count(
+                &index,
+                json!({ "match": { "title": { "query": "red blue hat", "minimum_should_match": 2 } } })
+            )?,
+            1
+        );
+        assert_eq!(count(&index, json!({ "match": { "title": "the" } }))?, 0);
+        assert_eq!(count(&index, json!({ "match": { "price": 80 } }))?, 1);
+        assert_eq!(
+            count(&index, json!({ "match": { "attributes.color": "blue" } }))?,
+            1
+        );
+        assert_eq!(
+            count(
+                &index,
+                json!({ "match_phrase": { "title": "running shoes" } })
+            )?,
+            2
+        );
+        assert_eq!(
+            count(&index, json!({ "match_phrase": { "title": "blue shoes" } }))?,
+            0
+        );
+        assert_eq!(
+            count(
+                &index,
+                json!({ "match_phrase": { "title": { "query": "blue shoes", "slop": 1 } } })
+            )?,
+            1
+        );
+        assert_eq!(
+            count(
+                &index,
+                json!({ "range": { "price": { "gte": 20, "lt": 120 } } })
+            )?,
+            2
+        );
+        assert_eq!(
+            count(&index, json!({ "range": { "price": { "gt": 20 } } }))?,
+            2
+        );
+        assert_eq!(
+            count(&index, json!({ "exists": { "field": "attributes" } }))?,
+            2
+        );
+        assert_eq!(
+            count(
+                &index,
+                json!({ "query_string": { "query": "blue AND price:[0 TO 50]" } })
+            )?,
+            1
+        );
+        assert_eq!(
+            count(
+                &index,
+                json!({ "query_string": { "query": "blue shoes", "default_operator": "AND" } })
+            )?,
+            1
+        );
+        assert_eq!(
+            count(
+                &index,
+                json!({
+                    "bool": {
+                        "must": { "match": { "title": "running shoes" } },
+                        "filter": [
+                            { "term": { "brand": "acme" } },
+                            { "range": { "price": { "lt": 100 } } }
+                        ]
+                    }
+                })
+            )?,
+            1
+        );
+        assert_eq!(
+            count(
+                &index,
+                json!({ "bool": { "must_not": { "term": { "brand": "acme" } } } })
+            )?,
+            1
+        );
+        assert_eq!(
+            count(
+                &index,
+                json!({
+                    "bool": {
+                        "should": [{ "term": { "brand": "acme" } }],
+                        "must_not": { "match": { "title": "red" } }
+                    }
+                })
+            )?,
+            1
+        );
+        assert_eq!(count(&index, json!({ "bool": {} }))?, 3);
+
+        assert_eq!(
+            count(&index, json!({ "term": { "missing": "acme" } })).unwrap_err(),
+            QueryParserError::FieldDoesNotExist("missing".to_string())
+        );
+        assert!(count(&index, json!({ "term": { "price": "cheap" } })).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_dsl_scores() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let brand = schema_builder.add_text_field("brand", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "shoes", brand => "acme"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let score = |query_dsl: serde_json::Value| {
+            let query_dsl: QueryDsl = serde_json::from_value(query_dsl).unwrap();
+            let query = query_dsl.to_query_for_index(&index).unwrap();
+            let top_docs = searcher
+                .search(&query, &crate::collector::TopDocs::with_limit(1))
+                .unwrap();
+            top_docs[0].0
+        };
+        let match_score = score(json!({ "match": { "title": "shoes" } }));
+        assert!(match_score > 0.0);
+        assert_eq!(
+            score(json!({ "match": { "title": { "query": "shoes", "boost": 2.0 } } })),
+            2.0 * match_score
+        );
+        assert_eq!(
+            score(json!({
+                "bool": {
+                    "must": { "match": { "title": "shoes" } },
+                    "filter": { "term": { "brand": "acme" } }
+                }
+            })),
+            match_score
+        );
+        assert_eq!(score(json!({ "match_all": { "boost": 3.0 } })), 3.0);
+        Ok(())
+    }
+}
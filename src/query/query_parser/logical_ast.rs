@@ -1,13 +1,17 @@
 use std::fmt;
 use std::ops::Bound;
 
-use crate::query::Occur;
+use crate::query::{Occur, Query};
 use crate::schema::Term;
 use crate::Score;
 
-#[derive(Clone)]
 pub enum LogicalLiteral {
     Term(Term),
+    FuzzyTerm {
+        term: Term,
+        distance: u8,
+        prefix_length: usize,
+    },
     Phrase {
         terms: Vec<(usize, Term)>,
         slop: u32,
@@ -20,13 +24,23 @@ pub enum LogicalLiteral {
     Set {
         elements: Vec<Term>,
     },
+    /// Matches the documents having a value for the given field (or JSON path).
+    Exists {
+        full_path: String,
+    },
     All,
+    /// Query built by a syntax extension.
+    Query(Box<dyn Query>),
 }
 
 pub enum LogicalAst {
     Clause(Vec<(Occur, LogicalAst)>),
     Leaf(Box<LogicalLiteral>),
     Boost(Box<LogicalAst>, Score),
+    /// Gives the given constant score to the documents matching the ast.
+    ConstScore(Box<LogicalAst>, Score),
+    /// Disjunction max of the given asts, with the given tie breaker.
+    DisjunctionMax(Vec<LogicalAst>, Score),
 }
 
 impl LogicalAst {
@@ -62,7 +76,11 @@ impl LogicalAst {
 
                 LogicalAst::Clause(new_clauses)
             }
-            LogicalAst::Leaf(_) | LogicalAst::Boost(_, _) => self,
+            LogicalAst::DisjunctionMax(disjuncts, tie_breaker) => LogicalAst::DisjunctionMax(
+                disjuncts.into_iter().map(LogicalAst::simplify).collect(),
+                tie_breaker,
+            ),
+            LogicalAst::Leaf(_) | LogicalAst::Boost(_, _) | LogicalAst::ConstScore(_, _) => self,
         }
     }
 }
@@ -92,6 +110,17 @@ impl fmt::Debug for LogicalAst {
                 Ok(())
             }
             LogicalAst::Boost(ref ast, boost) => write!(formatter, "{ast:?}^{boost}"),
+            LogicalAst::ConstScore(ref ast, score) => write!(formatter, "{ast:?}^={score}"),
+            LogicalAst::DisjunctionMax(ref disjuncts, tie_breaker) => {
+                write!(formatter, "DisjunctionMax(")?;
+                for (i, disjunct) in disjuncts.iter().enumerate() {
+                    if i > 0 {
+                        write!(formatter, " | ")?;
+                    }
+                    write!(formatter, "{disjunct:?}")?;
+                }
+                write!(formatter, ")~{tie_breaker}")
+            }
             LogicalAst::Leaf(ref literal) => write!(formatter, "{literal:?}"),
         }
     }
@@ -107,6 +136,9 @@ impl fmt::Debug for LogicalLiteral {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         match *self {
             LogicalLiteral::Term(ref term) => write!(formatter, "{term:?}"),
+            LogicalLiteral::FuzzyTerm {
+                ref term, distance, ..
+            } => write!(formatter, "{term:?}~{distance}"),
             LogicalLiteral::Phrase {
                 ref terms,
                 slop,
@@ -146,7 +178,9 @@ impl fmt::Debug for LogicalLiteral {
                 }
                 write!(formatter, "]")
             }
+            LogicalLiteral::Exists { ref full_path } => write!(formatter, "$exists({full_path:?})"),
             LogicalLiteral::All => write!(formatter, "*"),
+            LogicalLiteral::Query(ref query) => write!(formatter, "{query:?}"),
         }
     }
 }
@@ -1,4 +1,6 @@
 mod query_parser;
+mod syntax_extension;
 
 pub mod logical_ast;
 pub use self::query_parser::{QueryParser, QueryParserError};
+pub use self::syntax_extension::QuerySyntaxExtension;
@@ -2,20 +2,24 @@ use std::net::{AddrParseError, IpAddr};
 use std::num::{ParseFloatError, ParseIntError};
 use std::ops::Bound;
 use std::str::{FromStr, ParseBoolError};
+use std::sync::Arc;
 
 use base64::engine::general_purpose::STANDARD as BASE64;
 use base64::Engine;
 use itertools::Itertools;
-use query_grammar::{UserInputAst, UserInputBound, UserInputLeaf, UserInputLiteral};
+use query_grammar::{Delimiter, UserInputAst, UserInputBound, UserInputLeaf, UserInputLiteral};
 use rustc_hash::FxHashMap;
+use uuid::Uuid;
 
 use super::logical_ast::*;
+use super::QuerySyntaxExtension;
 use crate::index::Index;
 use crate::json_utils::convert_to_fast_value_and_append_to_json_term;
 use crate::query::range_query::{is_type_valid_for_fastfield_range_query, RangeQuery};
 use crate::query::{
-    AllQuery, BooleanQuery, BoostQuery, EmptyQuery, FuzzyTermQuery, Occur, PhrasePrefixQuery,
-    PhraseQuery, Query, TermQuery, TermSetQuery,
+    AllQuery, BooleanQuery, BoostQuery, ConstScoreQuery, DisjunctionMaxQuery, EmptyQuery,
+    ExistsQuery, FuzzyTermQuery, Occur, PhrasePrefixQuery, PhraseQuery, Query, TermQuery,
+    TermSetQuery,
 };
 use crate::schema::{
     Facet, FacetParseError, Field, FieldType, IndexRecordOption, IntoIpv6Addr, JsonObjectOptions,
@@ -102,6 +106,9 @@ pub enum QueryParserError {
     /// The format for the ip field is invalid.
     #[error("The ip field is malformed: {0}")]
     IpFormatError(#[from] AddrParseError),
+    /// The format for the uuid field is invalid.
+    #[error("The uuid field is malformed: {0}")]
+    UuidFormatError(#[from] uuid::Error),
 }
 
 /// Recursively remove empty clause from the AST
@@ -122,6 +129,15 @@ fn trim_ast(logical_ast: LogicalAst) -> Option<LogicalAst> {
                 Some(LogicalAst::Clause(trimmed_children))
             }
         }
+        LogicalAst::DisjunctionMax(disjuncts, tie_breaker) => {
+            let mut trimmed_disjuncts: Vec<LogicalAst> =
+                disjuncts.into_iter().flat_map(trim_ast).collect();
+            match trimmed_disjuncts.len() {
+                0 => None,
+                1 => trimmed_disjuncts.pop(),
+                _ => Some(LogicalAst::DisjunctionMax(trimmed_disjuncts, tie_breaker)),
+            }
+        }
         _ => Some(logical_ast),
     }
 }
@@ -175,16 +191,27 @@ fn trim_ast(logical_ast: LogicalAst) -> Option<LogicalAst> {
 ///   `title: IN [a b cd]` will match documents where `title` is either `a`, `b` or `cd`, but do so
 ///   more efficiently than the alternative query `title:a OR title:b OR title:c` does.
 ///
+/// * exists terms: `title:*` or `_exists_:title` will match documents having at least one value for
+///   `title`. On a JSON field, values in any subpath are taken into account.
+///
 /// * date values: The query parser supports rfc3339 formatted dates. For example
 ///   `"2002-10-02T15:00:00.05Z"` or `some_date_field:[2002-10-02T15:00:00Z TO
 ///   2002-10-02T18:00:00Z}`
 ///
 /// * all docs query: A plain `*` will match all documents in the index.
 ///
+/// * syntax extensions: clauses `name:(...)` can be handed over to a [`QuerySyntaxExtension`]
+///   registered under `name`, see
+///   [`register_syntax_extension(...)`](QueryParser::register_syntax_extension).
+///
 /// Parts of the queries can be boosted by appending `^boostfactor`.
 /// For instance, `"SRE"^2.0 OR devops^0.4` will boost documents containing `SRE` instead of
 /// devops. Negative boosts are not allowed.
 ///
+/// Appending `^=score` instead gives a constant score to the matching documents, regardless of
+/// how well they match. For instance, `(title:wolf OR body:wolf)^=2.0` scores all of the
+/// documents mentioning `wolf` with `2.0`.
+///
 /// It is also possible to define a boost for a some specific field, at the query parser level.
 /// (See [`set_field_boost(...)`](QueryParser::set_field_boost)). Typically you may want to boost a
 /// title field.
@@ -198,16 +225,29 @@ fn trim_ast(logical_ast: LogicalAst) -> Option<LogicalAst> {
 /// Phrase terms also support the `*` prefix operator which switches the phrase's matching
 /// to consider all documents which contain the last term as a prefix, e.g. `"big bad wo"*` will
 /// match `"big bad wolf"`.
+///
+/// Unquoted terms support the `~` fuzzy operator, which matches the terms within the given
+/// Levenshtein distance (1 or 2) using a [`FuzzyTermQuery`], e.g. `wolf~1` also matches the
+/// documents mentioning `golf`. See
+/// [`set_fuzzy_prefix_length(...)`](QueryParser::set_fuzzy_prefix_length) to keep the first
+/// characters of the terms exact.
 #[derive(Clone)]
 pub struct QueryParser {
     schema: Schema,
     default_fields: Vec<Field>,
     conjunction_by_default: bool,
+    disjunction_max_tie_breaker: Option<Score>,
     tokenizer_manager: TokenizerManager,
     boost: FxHashMap<Field, Score>,
     fuzzy: FxHashMap<Field, Fuzzy>,
+    fuzzy_prefix_length: usize,
+    syntax_extensions: FxHashMap<String, Arc<dyn QuerySyntaxExtension>>,
+    copy_to_fields: Vec<(Vec<Field>, Field)>,
 }
 
+/// Maximum Levenshtein distance of the `~` fuzzy operator.
+const MAX_FUZZY_DISTANCE: u32 = 2;
+
 #[derive(Clone)]
 struct Fuzzy {
     prefix: bool,
@@ -217,8 +257,10 @@ struct Fuzzy {
 
 fn all_negative(ast: &LogicalAst) -> bool {
     match ast {
-        LogicalAst::Leaf(_) => false,
-        LogicalAst::Boost(ref child_ast, _) => all_negative(child_ast),
+        LogicalAst::Leaf(_) | LogicalAst::DisjunctionMax(..) => false,
+        LogicalAst::Boost(ref child_ast, _) | LogicalAst::ConstScore(ref child_ast, _) => {
+            all_negative(child_ast)
+        }
         LogicalAst::Clause(children) => children
             .iter()
             .all(|(ref occur, child)| (*occur == Occur::MustNot) || all_negative(child)),
@@ -228,8 +270,10 @@ fn all_negative(ast: &LogicalAst) -> bool {
 // Make an all-negative ast into a normal ast. Must not be used on an already okay ast.
 fn make_non_negative(ast: &mut LogicalAst) {
     match ast {
-        LogicalAst::Leaf(_) => (),
-        LogicalAst::Boost(ref mut child_ast, _) => make_non_negative(child_ast),
+        LogicalAst::Leaf(_) | LogicalAst::DisjunctionMax(..) => (),
+        LogicalAst::Boost(ref mut child_ast, _) | LogicalAst::ConstScore(ref mut child_ast, _) => {
+            make_non_negative(child_ast)
+        }
         LogicalAst::Clause(children) => children.push((Occur::Should, LogicalLiteral::All.into())),
     }
 }
@@ -258,8 +302,12 @@ impl QueryParser {
             default_fields,
             tokenizer_manager,
             conjunction_by_default: false,
+            disjunction_max_tie_breaker: None,
             boost: Default::default(),
             fuzzy: Default::default(),
+            fuzzy_prefix_length: 0,
+            syntax_extensions: Default::default(),
+            copy_to_fields: Vec::new(),
         }
     }
 
@@ -285,6 +333,21 @@ impl QueryParser {
         self.conjunction_by_default = true;
     }
 
+    /// Combines the default fields using a disjunction max rather than a boolean sum.
+    ///
+    /// By default, assuming the default fields are `title` and `body`, the unfielded
+    /// term `tax` is interpreted as `title:tax OR body:tax`, and the score of a
+    /// document is the sum of the scores obtained on each field.
+    /// After calling `.set_default_fields_disjunction_max(tie_breaker)`, the score
+    /// of a document is the maximum of its per-field scores, plus `tie_breaker` times
+    /// the sum of the other matching fields' scores.
+    /// (See [`DisjunctionMaxQuery`](crate::query::DisjunctionMaxQuery)).
+    ///
+    /// Terms targeting a specific field are not affected.
+    pub fn set_default_fields_disjunction_max(&mut self, tie_breaker: Score) {
+        self.disjunction_max_tie_breaker = Some(tie_breaker);
+    }
+
     /// Sets a boost for a specific field.
     ///
     /// The parse query will automatically boost this field.
@@ -320,6 +383,90 @@ impl QueryParser {
         );
     }
 
+    /// Sets the number of leading characters which have to match exactly in the fuzzy terms
+    /// written with the `~` operator, e.g. `wolf~1`.
+    ///
+    /// See [`FuzzyTermQuery::with_prefix_length`]. By default, the whole term is fuzzy.
+    pub fn set_fuzzy_prefix_length(&mut self, prefix_length: usize) {
+        self.fuzzy_prefix_length = prefix_length;
+    }
+
+    /// Declares that `copy_to_field` indexes the text of all of the `fields`, e.g. because the
+    /// values of the `fields` are copied into it when documents are built.
+    ///
+    /// Terms searched over the default fields are then searched once in `copy_to_field`, rather
+    /// than once in each of the `fields`, which saves the traversal of their postings in each
+    /// field. The terms are scored against `copy_to_field`.
+    ///
+    /// This only happens if all of the `fields` are default fields, and if they share the
+    /// tokenizer, the indexing options and the boost of `copy_to_field`, without fuzzy term
+    /// queries.
+    pub fn set_copy_to_field(&mut self, fields: &[Field], copy_to_field: Field) {
+        self.copy_to_fields.push((fields.to_vec(), copy_to_field));
+    }
+
+    /// Returns true if the terms of `field` can be searched in `copy_to_field` instead.
+    fn shares_postings(&self, field: Field, copy_to_field: Field) -> bool {
+        let text_indexing = |field: Field| match self.schema.get_field_entry(field).field_type() {
+            FieldType::Str(text_options) => text_options.get_indexing_options().cloned(),
+            _ => None,
+        };
+        let (Some(indexing), Some(copy_to_indexing)) =
+            (text_indexing(field), text_indexing(copy_to_field))
+        else {
+            return false;
+        };
+        indexing.tokenizer() == copy_to_indexing.tokenizer()
+            && indexing.index_option() == copy_to_indexing.index_option()
+            && self.field_boost(field) == self.field_boost(copy_to_field)
+            && !self.fuzzy.contains_key(&field)
+    }
+
+    /// Returns the default fields, where the fields copied to a common field are replaced by
+    /// this field.
+    fn default_fields_with_copy_to(&self) -> Vec<Field> {
+        let mut default_fields = self.default_fields.clone();
+        for (fields, copy_to_field) in &self.copy_to_fields {
+            let can_search_copy_to_field = !fields.is_empty()
+                && fields.iter().all(|field| {
+                    default_fields.contains(field) && self.shares_postings(*field, *copy_to_field)
+                });
+            if !can_search_copy_to_field {
+                continue;
+            }
+            let mut copy_to_field_opt = Some(*copy_to_field);
+            default_fields = default_fields
+                .into_iter()
+                .filter_map(|field| {
+                    if fields.contains(&field) || field == *copy_to_field {
+                        copy_to_field_opt.take()
+                    } else {
+                        Some(field)
+                    }
+                })
+                .collect();
+        }
+        default_fields
+    }
+
+    /// Registers a query syntax extension, which builds the queries of the clauses `name:(...)`.
+    ///
+    /// The text within the parentheses of these clauses is handed over to the extension as is,
+    /// e.g. `geo:(48.85, 2.35, 10km)`, instead of being parsed as a group of clauses targeting
+    /// the field `name`. See [`QuerySyntaxExtension`].
+    pub fn register_syntax_extension(
+        &mut self,
+        name: impl ToString,
+        extension: impl QuerySyntaxExtension,
+    ) {
+        self.syntax_extensions
+            .insert(name.to_string(), Arc::new(extension));
+    }
+
+    fn syntax_extension_names(&self) -> Vec<String> {
+        self.syntax_extensions.keys().cloned().collect()
+    }
+
     /// Parse a query
     ///
     /// Note that `parse_query` returns an error if the input
@@ -371,8 +518,9 @@ impl QueryParser {
 
     /// Parse the user query into an AST.
     fn parse_query_to_logical_ast(&self, query: &str) -> Result<LogicalAst, QueryParserError> {
-        let user_input_ast = query_grammar::parse_query(query)
-            .map_err(|_| QueryParserError::SyntaxError(query.to_string()))?;
+        let user_input_ast =
+            query_grammar::parse_query_with_extensions(query, &self.syntax_extension_names())
+                .map_err(|_| QueryParserError::SyntaxError(query.to_string()))?;
         let (ast, mut err) = self.compute_logical_ast_lenient(user_input_ast);
         if !err.is_empty() {
             return Err(err.swap_remove(0));
@@ -385,7 +533,10 @@ impl QueryParser {
         &self,
         query: &str,
     ) -> (LogicalAst, Vec<QueryParserError>) {
-        let (user_input_ast, errors) = query_grammar::parse_query_lenient(query);
+        let (user_input_ast, errors) = query_grammar::parse_query_lenient_with_extensions(
+            query,
+            &self.syntax_extension_names(),
+        );
         let mut errors: Vec<_> = errors
             .into_iter()
             .map(|error| {
@@ -417,7 +568,7 @@ impl QueryParser {
         (ast, err)
     }
 
-    fn compute_boundary_term(
+    pub(crate) fn compute_boundary_term(
         &self,
         field: Field,
         json_path: &str,
@@ -522,6 +673,10 @@ impl QueryParser {
                 let ip_v6 = IpAddr::from_str(phrase)?.into_ipv6_addr();
                 Ok(Term::from_field_ip_addr(field, ip_v6))
             }
+            FieldType::Uuid(_) => {
+                let uuid = Uuid::parse_str(phrase)?;
+                Ok(Term::from_field_uuid(field, uuid))
+            }
         }
     }
 
@@ -622,6 +777,11 @@ impl QueryParser {
                 let term = Term::from_field_ip_addr(field, ip_v6);
                 Ok(vec![LogicalLiteral::Term(term)])
             }
+            FieldType::Uuid(_) => {
+                let uuid = Uuid::parse_str(phrase)?;
+                let term = Term::from_field_uuid(field, uuid);
+                Ok(vec![LogicalLiteral::Term(term)])
+            }
         }
     }
 
@@ -672,6 +832,13 @@ impl QueryParser {
                 let (ast, errors) = self.compute_logical_ast_with_occur_lenient(*ast);
                 (ast.boost(boost as Score), errors)
             }
+            UserInputAst::ConstScore(ast, score) => {
+                let (ast, errors) = self.compute_logical_ast_with_occur_lenient(*ast);
+                (
+                    LogicalAst::ConstScore(Box::new(ast), score as Score),
+                    errors,
+                )
+            }
             UserInputAst::Leaf(leaf) => {
                 let (ast, errors) = self.compute_logical_ast_from_leaf_lenient(*leaf);
                 // if the error is not recoverable, replace it with an empty clause. We will end up
@@ -729,9 +896,9 @@ impl QueryParser {
                 return Err(QueryParserError::NoDefaultFieldDeclared);
             }
             return Ok(self
-                .default_fields
-                .iter()
-                .map(|default_field| (*default_field, "", literal.phrase.as_str()))
+                .default_fields_with_copy_to()
+                .into_iter()
+                .map(|default_field| (default_field, "", literal.phrase.as_str()))
                 .collect::<Vec<(Field, &str, &str)>>());
         };
         if let Some((field, path)) = self.split_full_path(full_path) {
@@ -748,6 +915,40 @@ impl QueryParser {
         Ok(triplets)
     }
 
+    /// Turns a text term written as `term~distance` into a fuzzy term.
+    ///
+    /// Other literals are returned unchanged: unquoted literals made of several tokens, e.g.
+    /// `big-wolf~1`, are phrases, for which `~` is the slop.
+    fn fuzzy_literal(
+        &self,
+        literal: LogicalLiteral,
+        distance: u32,
+    ) -> Result<LogicalLiteral, QueryParserError> {
+        let LogicalLiteral::Term(term) = literal else {
+            return Ok(literal);
+        };
+        let term_value = term.value();
+        let is_text = match term_value.typ() {
+            Type::Str => true,
+            Type::Json => term_value.json_path_type() == Some(Type::Str),
+            _ => false,
+        };
+        if !is_text {
+            return Ok(LogicalLiteral::Term(term));
+        }
+        if distance > MAX_FUZZY_DISTANCE {
+            return Err(QueryParserError::UnsupportedQuery(format!(
+                "Fuzzy distance of {distance} is not supported, the maximum is                  \
+                 {MAX_FUZZY_DISTANCE}."
+            )));
+        }
+        Ok(LogicalLiteral::FuzzyTerm {
+            term,
+            distance: distance as u8,
+            prefix_length: self.fuzzy_prefix_length,
+        })
+    }
+
     fn compute_logical_ast_from_leaf_lenient(
         &self,
         leaf: UserInputLeaf,
@@ -773,6 +974,17 @@ impl QueryParser {
                         }
                     };
                     for ast in unboosted_asts {
+                        let ast = if literal.delimiter == Delimiter::None && literal.slop > 0 {
+                            match self.fuzzy_literal(ast, literal.slop) {
+                                Ok(ast) => ast,
+                                Err(e) => {
+                                    errors.push(e);
+                                    continue;
+                                }
+                            }
+                        } else {
+                            ast
+                        };
                         // Apply some field specific boost defined at the query parser level.
                         let boost = self.field_boost(field);
                         asts.push(LogicalAst::Leaf(Box::new(ast)).boost(boost));
@@ -780,6 +992,10 @@ impl QueryParser {
                 }
                 let result_ast: LogicalAst = if asts.len() == 1 {
                     asts.into_iter().next().unwrap()
+                } else if let (None, Some(tie_breaker)) =
+                    (&literal.field_name, self.disjunction_max_tie_breaker)
+                {
+                    LogicalAst::DisjunctionMax(asts, tie_breaker)
                 } else {
                     LogicalAst::Clause(asts.into_iter().map(|ast| (Occur::Should, ast)).collect())
                 };
@@ -848,12 +1064,27 @@ impl QueryParser {
                 let logical_ast = LogicalAst::Leaf(Box::new(LogicalLiteral::Set { elements }));
                 (Some(logical_ast), errors)
             }
-            UserInputLeaf::Exists { .. } => (
-                None,
-                vec![QueryParserError::UnsupportedQuery(
-                    "Range query need to target a specific field.".to_string(),
-                )],
-            ),
+            UserInputLeaf::Exists { field: full_path } => {
+                let (field, _json_path) = try_tuple!(self
+                    .split_full_path(&full_path)
+                    .ok_or_else(|| QueryParserError::FieldDoesNotExist(full_path.clone())));
+                let field_type = self.schema.get_field_entry(field).field_type();
+                if !field_type.is_indexed() && !field_type.is_fast() {
+                    return (None, vec![QueryParserError::FieldNotIndexed(full_path)]);
+                }
+                let logical_ast = LogicalAst::Leaf(Box::new(LogicalLiteral::Exists { full_path }));
+                (Some(logical_ast), Vec::new())
+            }
+            UserInputLeaf::Extension { name, content } => {
+                let extension = try_tuple!(self.syntax_extensions.get(&name).ok_or_else(|| {
+                    QueryParserError::UnsupportedQuery(format!(
+                        "Unknown query syntax extension: {name:?}"
+                    ))
+                }));
+                let query = try_tuple!(extension.build_query(&content, self));
+                let logical_ast = LogicalAst::Leaf(Box::new(LogicalLiteral::Query(query)));
+                (Some(logical_ast), Vec::new())
+            }
         }
     }
 }
@@ -882,6 +1113,11 @@ fn convert_literal_to_query(
                 Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs))
             }
         }
+        LogicalLiteral::FuzzyTerm {
+            term,
+            distance,
+            prefix_length,
+        } => Box::new(FuzzyTermQuery::new(term, distance, true).with_prefix_length(prefix_length)),
         LogicalLiteral::Phrase {
             terms,
             slop,
@@ -895,7 +1131,9 @@ fn convert_literal_to_query(
         }
         LogicalLiteral::Range { lower, upper } => Box::new(RangeQuery::new(lower, upper)),
         LogicalLiteral::Set { elements, .. } => Box::new(TermSetQuery::new(elements)),
+        LogicalLiteral::Exists { full_path } => Box::new(ExistsQuery::new(full_path, true)),
         LogicalLiteral::All => Box::new(AllQuery),
+        LogicalLiteral::Query(query) => query,
     }
 }
 
@@ -1019,6 +1257,20 @@ fn convert_to_query(fuzzy: &FxHashMap<Field, Fuzzy>, logical_ast: LogicalAst) ->
             let boosted_query = BoostQuery::new(query, boost);
             Box::new(boosted_query)
         }
+        Some(LogicalAst::ConstScore(ast, score)) => {
+            let query = convert_to_query(fuzzy, *ast);
+            Box::new(ConstScoreQuery::new(query, score))
+        }
+        Some(LogicalAst::DisjunctionMax(disjuncts, tie_breaker)) => {
+            let disjunct_queries = disjuncts
+                .into_iter()
+                .map(|disjunct| convert_to_query(fuzzy, disjunct))
+                .collect::<Vec<_>>();
+            Box::new(DisjunctionMaxQuery::with_tie_breaker(
+                disjunct_queries,
+                tie_breaker,
+            ))
+        }
         None => Box::new(EmptyQuery),
     }
 }
@@ -1029,7 +1281,8 @@ mod test {
 
     use super::super::logical_ast::*;
     use super::{QueryParser, QueryParserError};
-    use crate::query::Query;
+    use crate::collector::TopDocs;
+    use crate::query::{BoostQuery, Query, TermSetQuery};
     use crate::schema::{
         FacetOptions, Field, IndexRecordOption, Schema, Term, TextFieldIndexing, TextOptions, FAST,
         INDEXED, STORED, STRING, TEXT,
@@ -1037,7 +1290,7 @@ mod test {
     use crate::tokenizer::{
         LowerCaser, SimpleTokenizer, StopWordFilter, TextAnalyzer, TokenizerManager,
     };
-    use crate::Index;
+    use crate::{assert_nearly_equals, DocAddress, Index, IndexWriter, Score};
 
     fn make_schema() -> Schema {
         let mut schema_builder = Schema::builder();
@@ -1157,6 +1410,70 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_parse_query_with_const_score() {
+        let query = make_query_parser().parse_query("text:hello^=3").unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            r#"Const(score=3, query=TermQuery(Term(field=1, type=Str, "hello")))"#
+        );
+        test_parse_query_to_logical_ast_helper(
+            "(title:a OR text:b)^=0.5",
+            r#"(Term(field=0, type=Str, "a") Term(field=1, type=Str, "b"))^=0.5"#,
+            false,
+        );
+    }
+
+    #[test]
+    pub fn test_query_parser_boost_and_const_score_scores() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(title => "the big bad wolf"))?;
+        index_writer.add_document(doc!(title => "a bad day"))?;
+        index_writer.add_document(doc!(title => "the big wolf is back"))?;
+        index_writer.add_document(doc!(title => "nothing to see"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query_parser = QueryParser::for_index(&index, vec![title]);
+        let scores = |query: &str| -> crate::Result<Vec<(DocAddress, Score)>> {
+            let query = query_parser.parse_query(query).unwrap();
+            let mut scores: Vec<(DocAddress, Score)> = searcher
+                .search(&query, &TopDocs::with_limit(10))?
+                .into_iter()
+                .map(|(score, doc_address)| (doc_address, score))
+                .collect();
+            scores.sort_by_key(|(doc_address, _)| *doc_address);
+            Ok(scores)
+        };
+
+        // The boost applies to all of the nested clauses, phrases included.
+        let unboosted_scores = scores(r#"title:"big wolf" OR title:bad"#)?;
+        let boosted_scores = scores(r#"(title:"big wolf" OR title:bad)^2.5"#)?;
+        assert_eq!(unboosted_scores.len(), 3);
+        assert_eq!(boosted_scores.len(), 3);
+        for ((doc, score), (boosted_doc, boosted_score)) in
+            unboosted_scores.iter().zip(&boosted_scores)
+        {
+            assert_eq!(doc, boosted_doc);
+            assert_nearly_equals!(score * 2.5, *boosted_score);
+        }
+
+        let const_scores = scores(r#"(title:"big wolf" OR title:bad)^=3"#)?;
+        assert_eq!(const_scores.len(), 3);
+        assert!(const_scores.iter().all(|(_, score)| *score == 3.0));
+
+        // Each clause gets its own constant score.
+        let clause_const_scores = scores(r#"title:wolf^=3 OR title:day^=1"#)?;
+        let const_score_values: Vec<Score> = clause_const_scores
+            .iter()
+            .map(|(_, score)| *score)
+            .collect();
+        assert_eq!(const_score_values, vec![3.0, 1.0, 3.0]);
+        Ok(())
+    }
+
     #[test]
     pub fn test_parse_nonindexed_field_yields_error() {
         let query_parser = make_query_parser();
@@ -1482,6 +1799,26 @@ mod test {
             false,
         );
     }
+
+    #[test]
+    pub fn test_parse_query_to_ast_disjunction_max() {
+        let mut query_parser = make_query_parser();
+        query_parser.set_default_fields_disjunction_max(0.1);
+        let query = query_parser
+            .parse_query_to_logical_ast("title:a b")
+            .unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            r#"(Term(field=0, type=Str, "a") DisjunctionMax(Term(field=0, type=Str, "b") | Term(field=1, type=Str, "b"))~0.1)"#
+        );
+        let query = query_parser.parse_query("b").unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            "DisjunctionMaxQuery { disjuncts: [TermQuery(Term(field=0, type=Str, \"b\")), \
+             TermQuery(Term(field=1, type=Str, \"b\"))], tie_breaker: 0.1 }"
+        );
+    }
+
     #[test]
     pub fn test_parse_query_all_query() {
         let logical_ast = parse_query_to_logical_ast("*", false).unwrap();
@@ -1884,6 +2221,31 @@ mod test {
         );
     }
 
+    #[test]
+    pub fn test_exists_query() {
+        test_parse_query_to_logical_ast_helper("title:*", r#"$exists("title")"#, false);
+        test_parse_query_to_logical_ast_helper("_exists_:title", r#"$exists("title")"#, false);
+        test_parse_query_to_logical_ast_helper(
+            "_exists_:json.a.b",
+            r#"$exists("json.a.b")"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper("u64_ff:*", r#"$exists("u64_ff")"#, false);
+        test_parse_query_to_logical_ast_helper(
+            "a -_exists_:title",
+            r#"(Term(field=0, type=Str, "a") Term(field=1, type=Str, "a") -$exists("title"))"#,
+            false,
+        );
+        assert_eq!(
+            parse_query_to_logical_ast("_exists_:missing", false).unwrap_err(),
+            QueryParserError::FieldDoesNotExist("missing".to_string())
+        );
+        assert_eq!(
+            parse_query_to_logical_ast("notindexed_text:*", false).unwrap_err(),
+            QueryParserError::FieldNotIndexed("notindexed_text".to_string())
+        );
+    }
+
     #[test]
     pub fn test_set_field_fuzzy() {
         {
@@ -1898,8 +2260,8 @@ mod test {
             assert_eq!(
                 format!("{query:?}"),
                 "BooleanQuery { subqueries: [(Should, FuzzyTermQuery { term: Term(field=0, \
-                 type=Str, \"abc\"), distance: 1, transposition_cost_one: true, prefix: false }), \
-                 (Should, TermQuery(Term(field=1, type=Str, \"abc\")))], \
+                 type=Str, \"abc\"), distance: 1, transposition_cost_one: true, prefix: false, \
+                 prefix_length: 0 }), (Should, TermQuery(Term(field=1, type=Str, \"abc\")))], \
                  minimum_number_should_match: 1 }"
             );
         }
@@ -1917,9 +2279,183 @@ mod test {
                 format!("{query:?}"),
                 "BooleanQuery { subqueries: [(Should, TermQuery(Term(field=0, type=Str, \
                  \"abc\"))), (Should, FuzzyTermQuery { term: Term(field=1, type=Str, \"abc\"), \
-                 distance: 2, transposition_cost_one: false, prefix: true })], \
+                 distance: 2, transposition_cost_one: false, prefix: true, prefix_length: 0 })], \
                  minimum_number_should_match: 1 }"
             );
         }
     }
+
+    #[test]
+    pub fn test_fuzzy_operator() {
+        test_parse_query_to_logical_ast_helper(
+            "title:wolf~1",
+            r#"Term(field=0, type=Str, "wolf")~1"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "json.color:red~2",
+            r#"Term(field=14, type=Json, path=color, type=Str, "red")~2"#,
+            false,
+        );
+        // Quoted terms and numbers are not fuzzy.
+        test_parse_query_to_logical_ast_helper(
+            "title:\"wolf\"~1",
+            r#"Term(field=0, type=Str, "wolf")"#,
+            false,
+        );
+        test_parse_query_to_logical_ast_helper(
+            "unsigned:3~1",
+            r#"Term(field=3, type=U64, 3)"#,
+            false,
+        );
+        // Unquoted literals made of several tokens are phrases.
+        test_parse_query_to_logical_ast_helper(
+            "title:big-wolf~1",
+            r#""[(0, Term(field=0, type=Str, "big")), (1, Term(field=0, type=Str, "wolf"))]"~1"#,
+            false,
+        );
+        assert!(matches!(
+            parse_query_to_logical_ast("title:wolf~3", false),
+            Err(QueryParserError::UnsupportedQuery(_))
+        ));
+
+        let mut query_parser = make_query_parser();
+        query_parser.set_fuzzy_prefix_length(2);
+        let query = query_parser.parse_query("title:wolf~1").unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            "FuzzyTermQuery { term: Term(field=0, type=Str, \"wolf\"), distance: 1, \
+             transposition_cost_one: true, prefix: false, prefix_length: 2 }"
+        );
+    }
+
+    #[test]
+    pub fn test_copy_to_field() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", TEXT);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let all = schema_builder.add_text_field("all", TEXT);
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let schema = schema_builder.build();
+        let make_query_parser = |default_fields: Vec<Field>| {
+            let mut query_parser =
+                QueryParser::new(schema.clone(), default_fields, TokenizerManager::default());
+            query_parser.set_copy_to_field(&[title, body], all);
+            query_parser
+        };
+        let parse = |query_parser: &QueryParser, query: &str| {
+            format!(
+                "{:?}",
+                query_parser.parse_query_to_logical_ast(query).unwrap()
+            )
+        };
+
+        let query_parser = make_query_parser(vec![title, body, tag]);
+        assert_eq!(
+            parse(&query_parser, "Hello"),
+            r#"(Term(field=2, type=Str, "hello") Term(field=3, type=Str, "Hello"))"#
+        );
+        // Terms targeting a field are left untouched.
+        assert_eq!(
+            parse(&query_parser, "title:hello"),
+            r#"Term(field=0, type=Str, "hello")"#
+        );
+        let query_parser = make_query_parser(vec![title, body, all]);
+        assert_eq!(
+            parse(&query_parser, "hello"),
+            r#"Term(field=2, type=Str, "hello")"#
+        );
+
+        // The copy-to field is only searched if all of the fields copied to it are.
+        let query_parser = make_query_parser(vec![title, tag]);
+        assert_eq!(
+            parse(&query_parser, "hello"),
+            r#"(Term(field=0, type=Str, "hello") Term(field=3, type=Str, "hello"))"#
+        );
+        // Fields which are scored differently than the copy-to field are searched separately.
+        let mut query_parser = make_query_parser(vec![title, body]);
+        query_parser.set_field_boost(title, 2.0);
+        assert_eq!(
+            parse(&query_parser, "hello"),
+            r#"(Term(field=0, type=Str, "hello")^2 Term(field=1, type=Str, "hello"))"#
+        );
+        let mut query_parser = make_query_parser(vec![title, tag]);
+        query_parser.set_copy_to_field(&[title, tag], all);
+        assert_eq!(
+            parse(&query_parser, "hello"),
+            r#"(Term(field=0, type=Str, "hello") Term(field=3, type=Str, "hello"))"#
+        );
+    }
+
+    #[test]
+    pub fn test_syntax_extension() {
+        let mut query_parser = make_query_parser();
+        query_parser.register_syntax_extension(
+            "ids",
+            |content: &str,
+             query_parser: &QueryParser|
+             -> Result<Box<dyn Query>, QueryParserError> {
+                let title = query_parser.schema.get_field("title").unwrap();
+                let terms = content
+                    .split(',')
+                    .map(|id| Term::from_field_text(title, id.trim()));
+                Ok(Box::new(TermSetQuery::new(terms)))
+            },
+        );
+        query_parser.register_syntax_extension(
+            "boosted",
+            |content: &str,
+             query_parser: &QueryParser|
+             -> Result<Box<dyn Query>, QueryParserError> {
+                let (query, boost) = content.rsplit_once(';').ok_or_else(|| {
+                    QueryParserError::UnsupportedQuery(format!("Missing boost: {content:?}"))
+                })?;
+                let boost: Score = boost.trim().parse()?;
+                Ok(Box::new(BoostQuery::new(
+                    query_parser.parse_query(query)?,
+                    boost,
+                )))
+            },
+        );
+
+        let query = query_parser.parse_query("ids:(a, b)").unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            r#"TermSetQuery { terms_map: {Field(0): [Term(field=0, type=Str, "a"), Term(field=0, type=Str, "b")]} }"#
+        );
+        let query = query_parser
+            .parse_query(r#"+boosted:(title:a AND text:"b)"; 2) -ids:(c)"#)
+            .unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            "BooleanQuery { subqueries: [(Must, Boost(query=BooleanQuery { subqueries: [(Must, \
+             TermQuery(Term(field=0, type=Str, \"a\"))), (Must, TermQuery(Term(field=1, type=Str, \
+             \"b\")))], minimum_number_should_match: 0 }, boost=2)), (MustNot, TermSetQuery { \
+             terms_map: {Field(0): [Term(field=0, type=Str, \"c\")]} })], \
+             minimum_number_should_match: 0 }"
+        );
+        assert!(matches!(
+            query_parser.parse_query("boosted:(a)"),
+            Err(QueryParserError::UnsupportedQuery(_))
+        ));
+        assert!(matches!(
+            query_parser.parse_query("ids:(a, b"),
+            Err(QueryParserError::SyntaxError(_))
+        ));
+
+        // Names which are not registered remain field names.
+        let query = query_parser.parse_query("title:(a b)").unwrap();
+        assert_eq!(
+            format!("{query:?}"),
+            query_parser
+                .parse_query("title:a title:b")
+                .map(|query| format!("{query:?}"))
+                .unwrap()
+        );
+        // Registering an extension does not change the queries of the other parsers.
+        assert!(matches!(
+            make_query_parser().parse_query("ids:(a, b)"),
+            Err(QueryParserError::FieldDoesNotExist(_))
+        ));
+    }
 }
@@ -0,0 +1,34 @@
+use super::{QueryParser, QueryParserError};
+use crate::query::Query;
+
+/// A query syntax, registered with
+/// [`QueryParser::register_syntax_extension()`](QueryParser::register_syntax_extension).
+///
+/// The query parser hands the clauses `name:(...)` of the name the extension is registered with
+/// over to the extension, which builds their query from the text within the parentheses. This
+/// text is not parsed beforehand, so that it can follow any syntax, as long as its parentheses are
+/// balanced. Parentheses within double quotes or escaped with `\` are not taken into account.
+///
+/// Closures taking the text and the query parser are query syntax extensions.
+pub trait QuerySyntaxExtension: Send + Sync + 'static {
+    /// Builds the query of a clause, given the text within its parentheses.
+    ///
+    /// `query_parser` is the parser the clause is found by, e.g. to parse sub-queries.
+    fn build_query(
+        &self,
+        content: &str,
+        query_parser: &QueryParser,
+    ) -> Result<Box<dyn Query>, QueryParserError>;
+}
+
+impl<F> QuerySyntaxExtension for F
+where F: Fn(&str, &QueryParser) -> Result<Box<dyn Query>, QueryParserError> + Send + Sync + 'static
+{
+    fn build_query(
+        &self,
+        content: &str,
+        query_parser: &QueryParser,
+    ) -> Result<Box<dyn Query>, QueryParserError> {
+        (self)(content, query_parser)
+    }
+}
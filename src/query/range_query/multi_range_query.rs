@@ -0,0 +1,627 @@
+use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::net::Ipv6Addr;
+use std::ops::{Bound, RangeInclusive};
+
+use columnar::{Column, ColumnType, MonotonicallyMappableToU64};
+use common::bounds::BoundsRange;
+use common::BitSet;
+
+use super::range_query_fastfield::{bound_range_inclusive_u128, bound_to_value_range};
+use crate::query::explanation::does_not_match;
+use crate::query::{BitSetDocSet, ConstScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use crate::schema::{Field, IndexRecordOption, Term, Type};
+use crate::{DocId, Score, SegmentReader, TantivyError};
+
+/// Number of docs whose values are checked in a batch, when scanning a fast field.
+const SCAN_BATCH_NUM_DOCS: u32 = 65_536;
+
+/// `MultiRangeQuery` matches all documents that have at least one value within any of a set of
+/// ranges.
+///
+/// This is equivalent to a boolean union of [`RangeQuery`](crate::query::RangeQuery), but the
+/// ranges are evaluated in a single pass: over the fast field if the field is fast, or over the
+/// term dictionary otherwise.
+///
+/// Matched documents all get a constant `Score` of one. Supported fields are `u64`, `i64`, `f64`,
+/// date and ip address fields.
+///
+/// # Example
+///
+/// ```rust
+/// use std::ops::Bound;
+///
+/// use tantivy::collector::Count;
+/// use tantivy::query::MultiRangeQuery;
+/// use tantivy::schema::{Schema, FAST, INDEXED};
+/// use tantivy::{doc, Index, IndexWriter, Term};
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let year_field = schema_builder.add_u64_field("year", INDEXED | FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 20_000_000)?;
+/// for year in 1950u64..2017u64 {
+///     index_writer.add_document(doc!(year_field => year))?;
+/// }
+/// index_writer.commit()?;
+///
+/// let searcher = index.reader()?.searcher();
+/// let year_range = |start: u64, end: u64| {
+///     (
+///         Bound::Included(Term::from_field_u64(year_field, start)),
+///         Bound::Excluded(Term::from_field_u64(year_field, end)),
+///     )
+/// };
+/// let sixties_and_nineties =
+///     MultiRangeQuery::new(year_field, vec![year_range(1960, 1970), year_range(1990, 2000)]);
+/// assert_eq!(searcher.search(&sixties_and_nineties, &Count)?, 20);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MultiRangeQuery {
+    field: Field,
+    ranges: Vec<BoundsRange<Term>>,
+}
+
+impl MultiRangeQuery {
+    /// Creates a new `MultiRangeQuery` from the bounds of its ranges.
+    ///
+    /// All of the terms must belong to `field`. Ranges are allowed to overlap.
+    pub fn new(field: Field, ranges: Vec<(Bound<Term>, Bound<Term>)>) -> MultiRangeQuery {
+        let ranges: Vec<BoundsRange<Term>> = ranges
+            .into_iter()
+            .map(|(lower_bound, upper_bound)| BoundsRange::new(lower_bound, upper_bound))
+            .collect();
+        assert!(
+            ranges
+                .iter()
+                .flat_map(|range| [&range.lower_bound, &range.upper_bound])
+                .all(|bound| match bound {
+                    Bound::Included(term) | Bound::Excluded(term) => term.field() == field,
+                    Bound::Unbounded => true,
+                }),
+            "All terms from a multi range query must belong to the same field"
+        );
+        MultiRangeQuery { field, ranges }
+    }
+
+    /// Field to search over
+    pub fn field(&self) -> Field {
+        self.field
+    }
+}
+
+impl Query for MultiRangeQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let schema = enable_scoring.schema();
+        let field_entry = schema.get_field_entry(self.field);
+        let value_type = field_entry.field_type().value_type();
+        if !matches!(
+            value_type,
+            Type::U64 | Type::I64 | Type::F64 | Type::Date | Type::IpAddr
+        ) {
+            return Err(TantivyError::InvalidArgument(
+                format!(
+                    "MultiRangeQuery is only supported on numeric, date and ip address fields, \
+                     but {:?} is of type {value_type:?}",
+                    field_entry.name()
+                )
+                .into(),
+            ));
+        }
+        if !field_entry.is_fast() && !field_entry.is_indexed() {
+            return Err(TantivyError::SchemaError(format!(
+                "Field {:?} needs to be fast or indexed",
+                field_entry.name()
+            )));
+        }
+        Ok(Box::new(MultiRangeWeight {
+            field: self.field,
+            value_type,
+            ranges: self.ranges.clone(),
+            use_fast_field: field_entry.is_fast(),
+        }))
+    }
+}
+
+struct MultiRangeWeight {
+    field: Field,
+    value_type: Type,
+    ranges: Vec<BoundsRange<Term>>,
+    use_fast_field: bool,
+}
+
+impl MultiRangeWeight {
+    fn scan_fast_field(
+        &self,
+        reader: &SegmentReader,
+        doc_bitset: &mut BitSet,
+    ) -> crate::Result<()> {
+        let field_name = reader.schema().get_field_name(self.field);
+        if self.value_type == Type::IpAddr {
+            let Some(column): Option<Column<Ipv6Addr>> =
+                reader.fast_fields().column_opt(field_name)?
+            else {
+                return Ok(());
+            };
+            let ranges = self
+                .ranges
+                .iter()
+                .map(|range| {
+                    let range = range.map_bound_res(term_to_ip_addr)?;
+                    Ok(bound_range_inclusive_u128(
+                        &range.lower_bound,
+                        &range.upper_bound,
+                        column.min_value(),
+                        column.max_value(),
+                    ))
+                })
+                .collect::<crate::Result<Vec<_>>>()?;
+            scan_column(&column, ranges, doc_bitset);
+        } else {
+            let Some((column, _)) = reader.fast_fields().u64_lenient_for_type(
+                Some(&[
+                    ColumnType::U64,
+                    ColumnType::I64,
+                    ColumnType::F64,
+                    ColumnType::DateTime,
+                ]),
+                field_name,
+            )?
+            else {
+                return Ok(());
+            };
+            let mut ranges = Vec::with_capacity(self.ranges.len());
+            for range in &self.ranges {
+                let range = range.map_bound_res(term_to_u64)?;
+                if let Some(value_range) = bound_to_value_range(
+                    &range.lower_bound,
+                    &range.upper_bound,
+                    column.min_value(),
+                    column.max_value(),
+                ) {
+                    ranges.push(value_range);
+                }
+            }
+            scan_column(&column, ranges, doc_bitset);
+        }
+        Ok(())
+    }
+
+    fn scan_term_dictionary(
+        &self,
+        reader: &SegmentReader,
+        doc_bitset: &mut BitSet,
+    ) -> crate::Result<()> {
+        let inverted_index = reader.inverted_index(self.field)?;
+        let term_dict = inverted_index.terms();
+        let value_bytes = |term: &Term| term.serialized_value_bytes().to_owned();
+        let ranges: Vec<BoundsRange<Vec<u8>>> = self
+            .ranges
+            .iter()
+            .map(|range| range.map_bound(value_bytes))
+            .collect();
+        // The term dictionary is read front to back, and each term is streamed at most once.
+        for range in &merge_bounds_ranges(ranges) {
+            let mut term_stream_builder = term_dict.range();
+            term_stream_builder = match &range.lower_bound {
+                Bound::Included(term_val) => term_stream_builder.ge(term_val),
+                Bound::Excluded(term_val) => term_stream_builder.gt(term_val),
+                Bound::Unbounded => term_stream_builder,
+            };
+            term_stream_builder = match &range.upper_bound {
+                Bound::Included(term_val) => term_stream_builder.le(term_val),
+                Bound::Excluded(term_val) => term_stream_builder.lt(term_val),
+                Bound::Unbounded => term_stream_builder,
+            };
+            let mut term_stream = term_stream_builder.into_stream()?;
+            while term_stream.advance() {
+                let mut block_segment_postings = inverted_index.read_block_postings_from_terminfo(
+                    term_stream.value(),
+                    IndexRecordOption::Basic,
+                )?;
+                loop {
+                    let docs = block_segment_postings.docs();
+                    if docs.is_empty() {
+                        break;
+                    }
+                    for &doc in docs {
+                        doc_bitset.insert(doc);
+                    }
+                    block_segment_postings.advance();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Weight for MultiRangeWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        let mut doc_bitset = BitSet::with_max_value(reader.max_doc());
+        if self.use_fast_field {
+            self.scan_fast_field(reader, &mut doc_bitset)?;
+        } else {
+            self.scan_term_dictionary(reader, &mut doc_bitset)?;
+        }
+        let doc_bitset = BitSetDocSet::from(doc_bitset);
+        Ok(Box::new(ConstScorer::new(doc_bitset, boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        Ok(Explanation::new("MultiRangeQuery", 1.0))
+    }
+}
+
+fn term_to_u64(term: &Term) -> crate::Result<u64> {
+    let value = term.value();
+    if let Some(val) = value.as_u64() {
+        Ok(val)
+    } else if let Some(val) = value.as_i64() {
+        Ok(val.to_u64())
+    } else if let Some(val) = value.as_f64() {
+        Ok(val.to_u64())
+    } else if let Some(val) = value.as_date() {
+        Ok(val.to_u64())
+    } else {
+        Err(TantivyError::InvalidArgument(
+            format!("Expected term with u64, i64, f64 or date, but got {term:?}").into(),
+        ))
+    }
+}
+
+fn term_to_ip_addr(term: &Term) -> crate::Result<Ipv6Addr> {
+    term.value()
+        .as_ip_addr()
+        .ok_or_else(|| TantivyError::InvalidArgument("Expected ip address".into()))
+}
+
+/// Sorts the ranges and merges the overlapping ones.
+fn merge_ranges<T: PartialOrd + Copy>(
+    mut ranges: Vec<RangeInclusive<T>>,
+) -> Vec<RangeInclusive<T>> {
+    ranges.retain(|range| !range.is_empty());
+    ranges.sort_by(|left, right| left.start().partial_cmp(right.start()).unwrap());
+    let mut merged_ranges: Vec<RangeInclusive<T>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        if let Some(last_range) = merged_ranges.last_mut() {
+            if range.start() <= last_range.end() {
+                if range.end() > last_range.end() {
+                    *last_range = *last_range.start()..=*range.end();
+                }
+                continue;
+            }
+        }
+        merged_ranges.push(range);
+    }
+    merged_ranges
+}
+
+/// Orders lower bounds by the first value they accept.
+fn cmp_lower_bounds<T: Ord>(left: &Bound<T>, right: &Bound<T>) -> Ordering {
+    match (left, right) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Unbounded, _) => Ordering::Less,
+        (_, Bound::Unbounded) => Ordering::Greater,
+        (Bound::Included(left), Bound::Excluded(right)) if left == right => Ordering::Less,
+        (Bound::Excluded(left), Bound::Included(right)) if left == right => Ordering::Greater,
+        (
+            Bound::Included(left) | Bound::Excluded(left),
+            Bound::Included(right) | Bound::Excluded(right),
+        ) => left.cmp(right),
+    }
+}
+
+/// Orders upper bounds by the last value they accept.
+fn cmp_upper_bounds<T: Ord>(left: &Bound<T>, right: &Bound<T>) -> Ordering {
+    match (left, right) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Unbounded, _) => Ordering::Greater,
+        (_, Bound::Unbounded) => Ordering::Less,
+        (Bound::Included(left), Bound::Excluded(right)) if left == right => Ordering::Greater,
+        (Bound::Excluded(left), Bound::Included(right)) if left == right => Ordering::Less,
+        (
+            Bound::Included(left) | Bound::Excluded(left),
+            Bound::Included(right) | Bound::Excluded(right),
+        ) => left.cmp(right),
+    }
+}
+
+/// Returns true if a range ending at `upper_bound` and a range starting at `lower_bound` leave
+/// no gap between them.
+fn is_contiguous<T: Ord>(upper_bound: &Bound<T>, lower_bound: &Bound<T>) -> bool {
+    match (upper_bound, lower_bound) {
+        (Bound::Unbounded, _) | (_, Bound::Unbounded) => true,
+        (Bound::Excluded(upper), Bound::Excluded(lower)) => lower < upper,
+        (
+            Bound::Included(upper) | Bound::Excluded(upper),
+            Bound::Included(lower) | Bound::Excluded(lower),
+        ) => lower <= upper,
+    }
+}
+
+/// Sorts the ranges and merges the overlapping or adjacent ones.
+fn merge_bounds_ranges<T: Ord>(mut ranges: Vec<BoundsRange<T>>) -> Vec<BoundsRange<T>> {
+    ranges.retain(|range| match (&range.lower_bound, &range.upper_bound) {
+        (Bound::Included(lower), Bound::Included(upper)) => lower <= upper,
+        (
+            Bound::Included(lower) | Bound::Excluded(lower),
+            Bound::Included(upper) | Bound::Excluded(upper),
+        ) => lower < upper,
+        _ => true,
+    });
+    ranges.sort_by(|left, right| cmp_lower_bounds(&left.lower_bound, &right.lower_bound));
+    let mut merged_ranges: Vec<BoundsRange<T>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        if let Some(last_range) = merged_ranges.last_mut() {
+            if is_contiguous(&last_range.upper_bound, &range.lower_bound) {
+                if cmp_upper_bounds(&range.upper_bound, &last_range.upper_bound)
+                    == Ordering::Greater
+                {
+                    last_range.upper_bound = range.upper_bound;
+                }
+                continue;
+            }
+        }
+        merged_ranges.push(range);
+    }
+    merged_ranges
+}
+
+/// Adds the docs with a value within any of the ranges to the bitset.
+///
+/// The column is scanned once, for the values between the start of the first range and the end
+/// of the last range. The values of these docs are then checked against the ranges.
+fn scan_column<T: PartialOrd + Copy + Debug + Send + Sync + 'static>(
+    column: &Column<T>,
+    ranges: Vec<RangeInclusive<T>>,
+    doc_bitset: &mut BitSet,
+) {
+    let ranges = merge_ranges(ranges);
+    let (Some(first_range), Some(last_range)) = (ranges.first(), ranges.last()) else {
+        return;
+    };
+    let value_range = *first_range.start()..=*last_range.end();
+    let is_in_ranges = |val: T| {
+        let range_pos = ranges.partition_point(|range| *range.end() < val);
+        ranges
+            .get(range_pos)
+            .map(|range| range.contains(&val))
+            .unwrap_or(false)
+    };
+    let num_docs = column.num_docs();
+    let mut candidate_docs = Vec::new();
+    let mut batch_start = 0;
+    while batch_start < num_docs {
+        let batch_end = batch_start
+            .saturating_add(SCAN_BATCH_NUM_DOCS)
+            .min(num_docs);
+        candidate_docs.clear();
+        column.get_docids_for_value_range(
+            value_range.clone(),
+            batch_start..batch_end,
+            &mut candidate_docs,
+        );
+        for &doc in &candidate_docs {
+            if ranges.len() == 1 || column.values_for_doc(doc).any(is_in_ranges) {
+                doc_bitset.insert(doc);
+            }
+        }
+        batch_start = batch_end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv6Addr;
+    use std::ops::{Bound, RangeInclusive};
+
+    use common::bounds::BoundsRange;
+
+    use super::{merge_bounds_ranges, merge_ranges, MultiRangeQuery};
+    use crate::collector::{Count, DocSetCollector};
+    use crate::query::{BooleanQuery, Query, RangeQuery};
+    use crate::schema::{Schema, FAST, INDEXED};
+    use crate::{DateTime, Index, IndexWriter, Term};
+
+    #[test]
+    fn test_merge_ranges() {
+        assert_eq!(
+            merge_ranges(vec![
+                10..=20,
+                0..=5,
+                15..=30,
+                4..=6,
+                40..=50,
+                RangeInclusive::new(8, 7)
+            ]),
+            vec![0..=6, 10..=30, 40..=50]
+        );
+        assert!(merge_ranges::<u64>(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_merge_bounds_ranges() {
+        let merge = |bounds: Vec<(Bound<u64>, Bound<u64>)>| {
+            let ranges = bounds
+                .into_iter()
+                .map(|(lower_bound, upper_bound)| BoundsRange::new(lower_bound, upper_bound))
+                .collect();
+            merge_bounds_ranges(ranges)
+                .into_iter()
+                .map(|range| (range.lower_bound, range.upper_bound))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            merge(vec![
+                (Bound::Included(10), Bound::Excluded(20)),
+                (Bound::Unbounded, Bound::Included(5)),
+                (Bound::Unbounded, Bound::Excluded(3)),
+                (Bound::Excluded(5), Bound::Excluded(7)),
+                (Bound::Included(20), Bound::Included(30)),
+                (Bound::Excluded(30), Bound::Included(35)),
+                (Bound::Excluded(40), Bound::Unbounded),
+                (Bound::Excluded(50), Bound::Unbounded),
+                (Bound::Included(8), Bound::Excluded(8)),
+            ]),
+            vec![
+                (Bound::Unbounded, Bound::Excluded(7)),
+                (Bound::Included(10), Bound::Included(35)),
+                (Bound::Excluded(40), Bound::Unbounded),
+            ]
+        );
+        // Ranges ending and starting on the same excluded value leave a gap.
+        let bounds = vec![
+            (Bound::Unbounded, Bound::Excluded(5)),
+            (Bound::Excluded(5), Bound::Unbounded),
+        ];
+        assert_eq!(merge(bounds.clone()), bounds);
+        assert_eq!(
+            merge(vec![
+                (Bound::Unbounded, Bound::Unbounded),
+                (Bound::Unbounded, Bound::Unbounded),
+            ]),
+            vec![(Bound::Unbounded, Bound::Unbounded)]
+        );
+        assert!(merge_bounds_ranges::<u64>(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_multi_range_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let fast_field = schema_builder.add_i64_field("fast", FAST);
+        let indexed_field = schema_builder.add_i64_field("indexed", INDEXED);
+        let date_field = schema_builder.add_date_field("date", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for val in -50i64..50 {
+            index_writer.add_document(doc!(
+                fast_field => val,
+                fast_field => val + 1000,
+                indexed_field => val,
+                date_field => DateTime::from_timestamp_secs(val),
+            ))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+
+        let ranges = |field, bounds: &[(Bound<i64>, Bound<i64>)]| {
+            bounds
+                .iter()
+                .map(|(lower_bound, upper_bound)| {
+                    let to_term = |val: &i64| Term::from_field_i64(field, *val);
+                    (
+                        common::bounds::map_bound(lower_bound, to_term),
+                        common::bounds::map_bound(upper_bound, to_term),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let bounds = [
+            (Bound::Unbounded, Bound::Excluded(-40)),
+            (Bound::Included(-10), Bound::Included(10)),
+            (Bound::Excluded(5), Bound::Excluded(15)),
+            (Bound::Included(45), Bound::Excluded(100)),
+        ];
+        // 10 + 25 + 5
+        for field in [fast_field, indexed_field] {
+            let multi_range_query = MultiRangeQuery::new(field, ranges(field, &bounds));
+            assert_eq!(searcher.search(&multi_range_query, &Count)?, 40);
+            let boolean_query = BooleanQuery::union(
+                ranges(field, &bounds)
+                    .into_iter()
+                    .map(|(lower_bound, upper_bound)| {
+                        Box::new(RangeQuery::new(lower_bound, upper_bound)) as Box<dyn Query>
+                    })
+                    .collect(),
+            );
+            assert_eq!(
+                searcher.search(&multi_range_query, &DocSetCollector)?,
+                searcher.search(&boolean_query, &DocSetCollector)?
+            );
+        }
+        // The second values of the multivalued field.
+        let multi_range_query = MultiRangeQuery::new(
+            fast_field,
+            ranges(
+                fast_field,
+                &[
+                    (Bound::Included(-45), Bound::Excluded(-40)),
+                    (Bound::Included(1040), Bound::Unbounded),
+                ],
+            ),
+        );
+        assert_eq!(searcher.search(&multi_range_query, &Count)?, 15);
+
+        let date_range = |start: i64, end: i64| {
+            (
+                Bound::Included(Term::from_field_date(
+                    date_field,
+                    DateTime::from_timestamp_secs(start),
+                )),
+                Bound::Excluded(Term::from_field_date(
+                    date_field,
+                    DateTime::from_timestamp_secs(end),
+                )),
+            )
+        };
+        let multi_range_query =
+            MultiRangeQuery::new(date_field, vec![date_range(-50, -45), date_range(0, 3)]);
+        assert_eq!(searcher.search(&multi_range_query, &Count)?, 8);
+
+        let empty_query = MultiRangeQuery::new(fast_field, Vec::new());
+        assert_eq!(searcher.search(&empty_query, &Count)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_range_query_ip() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let ip_field = schema_builder.add_ip_addr_field("ip", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for val in 0u32..20 {
+            let ip = Ipv6Addr::from(u128::from(val));
+            index_writer.add_document(doc!(ip_field => ip))?;
+        }
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let ip_range = |start: u32, end: u32| {
+            (
+                Bound::Included(Term::from_field_ip_addr(
+                    ip_field,
+                    Ipv6Addr::from(u128::from(start)),
+                )),
+                Bound::Included(Term::from_field_ip_addr(
+                    ip_field,
+                    Ipv6Addr::from(u128::from(end)),
+                )),
+            )
+        };
+        let multi_range_query =
+            MultiRangeQuery::new(ip_field, vec![ip_range(2, 4), ip_range(10, 11)]);
+        assert_eq!(searcher.search(&multi_range_query, &Count)?, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_range_query_unsupported_field() {
+        let mut schema_builder = Schema::builder();
+        let text_field = schema_builder.add_text_field("text", crate::schema::STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let searcher = index.reader().unwrap().searcher();
+        let multi_range_query = MultiRangeQuery::new(
+            text_field,
+            vec![(
+                Bound::Included(Term::from_field_text(text_field, "a")),
+                Bound::Unbounded,
+            )],
+        );
+        assert!(searcher.search(&multi_range_query, &Count).is_err());
+    }
+}
@@ -110,7 +110,7 @@ impl Query for RangeQuery {
         } else {
             if field_type.is_json() {
                 return Err(crate::TantivyError::InvalidArgument(
-                    "RangeQuery on JSON is only supported for fast fields currently".to_string(),
+                    "RangeQuery on JSON is only supported for fast fields currently".into(),
                 ));
             }
             Ok(Box::new(InvertedIndexRangeWeight::new(
@@ -262,6 +262,7 @@ mod tests {
     use std::str::FromStr;
 
     use rand::seq::SliceRandom;
+    use uuid::Uuid;
 
     use super::RangeQuery;
     use crate::collector::{Count, TopDocs};
@@ -269,7 +270,7 @@ mod tests {
     use crate::query::range_query::range_query::InvertedIndexRangeQuery;
     use crate::query::QueryParser;
     use crate::schema::{
-        Field, IntoIpv6Addr, Schema, TantivyDocument, FAST, INDEXED, STORED, TEXT,
+        Field, IntoIpv6Addr, Schema, TantivyDocument, Value, FAST, INDEXED, STORED, TEXT,
     };
     use crate::{Index, IndexWriter, Term};
 
@@ -660,4 +661,73 @@ mod tests {
             0
         );
     }
+
+    #[test]
+    fn search_uuid_range_test_posting_list() {
+        search_uuid_range_test_opt(false);
+    }
+
+    #[test]
+    fn search_uuid_range_test() {
+        search_uuid_range_test_opt(true);
+    }
+
+    fn search_uuid_range_test_opt(with_fast_field: bool) {
+        let mut schema_builder = Schema::builder();
+        let id_field = if with_fast_field {
+            schema_builder.add_uuid_field("id", INDEXED | STORED | FAST)
+        } else {
+            schema_builder.add_uuid_field("id", INDEXED | STORED)
+        };
+        let schema = schema_builder.build();
+        let index = Index::create_in_ram(schema);
+        {
+            let mut index_writer: IndexWriter = index.writer_for_tests().unwrap();
+            for i in 0..10u128 {
+                index_writer
+                    .add_document(doc!(id_field => Uuid::from_u128(i << 64 | 7)))
+                    .unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let searcher = index.reader().unwrap().searcher();
+        let get_num_hits = |text: &str| {
+            let query = QueryParser::for_index(&index, vec![])
+                .parse_query(text)
+                .unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+
+        assert_eq!(get_num_hits("id:00000000-0000-0003-0000-000000000007"), 1);
+        assert_eq!(get_num_hits("id:00000000-0000-0003-0000-000000000008"), 0);
+        let query = QueryParser::for_index(&index, vec![])
+            .parse_query("id:00000000-0000-0003-0000-000000000007")
+            .unwrap();
+        let (_, doc_address) = searcher.search(&query, &TopDocs::with_limit(1)).unwrap()[0];
+        let doc: TantivyDocument = searcher.doc(doc_address).unwrap();
+        assert_eq!(
+            doc.get_first(id_field).and_then(|value| value.as_uuid()),
+            Some(Uuid::from_u128(3 << 64 | 7))
+        );
+        assert_eq!(
+            get_num_hits(
+                "id:[00000000-0000-0002-0000-000000000007 TO 00000000-0000-0004-0000-000000000007]"
+            ),
+            3
+        );
+        assert_eq!(
+            get_num_hits(
+                "id:{00000000-0000-0002-0000-000000000007 TO 00000000-0000-0004-0000-000000000007}"
+            ),
+            1
+        );
+        assert_eq!(
+            get_num_hits("id:[00000000-0000-0008-0000-000000000000 TO *]"),
+            2
+        );
+        assert_eq!(
+            get_num_hits("id:[* TO 00000000-0000-0000-ffff-ffffffffffff]"),
+            1
+        );
+    }
 }
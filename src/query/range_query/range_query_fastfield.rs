@@ -10,6 +10,7 @@ use columnar::{
     StrColumn,
 };
 use common::bounds::{BoundsRange, TransformBound};
+use uuid::Uuid;
 
 use super::fast_field_range_doc_set::RangeDocSet;
 use crate::query::{
@@ -128,17 +129,20 @@ impl Weight for FastFieldRangeWeight {
                         BoundsRange::new(bounds.lower_bound, bounds.upper_bound),
                     )
                 }
-                Type::Bool | Type::Facet | Type::Bytes | Type::Json | Type::IpAddr => {
-                    Err(crate::TantivyError::InvalidArgument(format!(
-                        "unsupported value bytes type in json term value_bytes {:?}",
-                        term_value.typ()
-                    )))
+                Type::Bool | Type::Facet | Type::Bytes | Type::Json | Type::IpAddr | Type::Uuid => {
+                    Err(crate::TantivyError::InvalidArgument(
+                        format!(
+                            "unsupported value bytes type in json term value_bytes {:?}",
+                            term_value.typ()
+                        )
+                        .into(),
+                    ))
                 }
             }
         } else if field_type.is_ip_addr() {
             let parse_ip_from_bytes = |term: &Term| {
                 term.value().as_ip_addr().ok_or_else(|| {
-                    crate::TantivyError::InvalidArgument("Expected ip address".to_string())
+                    crate::TantivyError::InvalidArgument("Expected ip address".into())
                 })
             };
             let bounds: BoundsRange<Ipv6Addr> = self.bounds.map_bound_res(parse_ip_from_bytes)?;
@@ -148,7 +152,7 @@ impl Weight for FastFieldRangeWeight {
             else {
                 return Ok(Box::new(EmptyScorer));
             };
-            let value_range = bound_range_inclusive_ip(
+            let value_range = bound_range_inclusive_u128(
                 &bounds.lower_bound,
                 &bounds.upper_bound,
                 ip_addr_column.min_value(),
@@ -156,6 +160,27 @@ impl Weight for FastFieldRangeWeight {
             );
             let docset = RangeDocSet::new(value_range, ip_addr_column);
             Ok(Box::new(ConstScorer::new(docset, boost)))
+        } else if field_type.is_uuid() {
+            let parse_uuid_from_bytes = |term: &Term| {
+                term.value()
+                    .as_uuid()
+                    .ok_or_else(|| crate::TantivyError::InvalidArgument("Expected uuid".into()))
+            };
+            let bounds: BoundsRange<Uuid> = self.bounds.map_bound_res(parse_uuid_from_bytes)?;
+
+            let Some(uuid_column): Option<Column<Uuid>> =
+                reader.fast_fields().column_opt(&field_name)?
+            else {
+                return Ok(Box::new(EmptyScorer));
+            };
+            let value_range = bound_range_inclusive_u128(
+                &bounds.lower_bound,
+                &bounds.upper_bound,
+                uuid_column.min_value(),
+                uuid_column.max_value(),
+            );
+            let docset = RangeDocSet::new(value_range, uuid_column);
+            Ok(Box::new(ConstScorer::new(docset, boost)))
         } else if field_type.is_str() {
             let Some(str_dict_column): Option<StrColumn> = reader.fast_fields().str(&field_name)?
             else {
@@ -192,10 +217,13 @@ impl Weight for FastFieldRangeWeight {
                 } else if let Some(val) = value.as_date() {
                     val.to_u64()
                 } else {
-                    return Err(TantivyError::InvalidArgument(format!(
-                        "Expected term with u64, i64, f64 or date, but got {:?}",
-                        term
-                    )));
+                    return Err(TantivyError::InvalidArgument(
+                        format!(
+                            "Expected term with u64, i64, f64 or date, but got {:?}",
+                            term
+                        )
+                        .into(),
+                    ));
                 };
                 Ok(val)
             })?;
@@ -224,9 +252,9 @@ impl Weight for FastFieldRangeWeight {
     fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
         let mut scorer = self.scorer(reader, 1.0)?;
         if scorer.seek(doc) != doc {
-            return Err(TantivyError::InvalidArgument(format!(
-                "Document #({doc}) does not match"
-            )));
+            return Err(TantivyError::InvalidArgument(
+                format!("Document #({doc}) does not match").into(),
+            ));
         }
         let explanation = Explanation::new("Const", scorer.score());
 
@@ -421,26 +449,26 @@ fn search_on_u64_ff(
 pub(crate) fn maps_to_u64_fastfield(typ: Type) -> bool {
     match typ {
         Type::U64 | Type::I64 | Type::F64 | Type::Bool | Type::Date => true,
-        Type::IpAddr => false,
+        Type::IpAddr | Type::Uuid => false,
         Type::Str | Type::Facet | Type::Bytes | Type::Json => false,
     }
 }
 
-fn bound_range_inclusive_ip(
-    lower_bound: &Bound<Ipv6Addr>,
-    upper_bound: &Bound<Ipv6Addr>,
-    min_value: Ipv6Addr,
-    max_value: Ipv6Addr,
-) -> RangeInclusive<Ipv6Addr> {
+pub(super) fn bound_range_inclusive_u128<T: MonotonicallyMappableToU128>(
+    lower_bound: &Bound<T>,
+    upper_bound: &Bound<T>,
+    min_value: T,
+    max_value: T,
+) -> RangeInclusive<T> {
     let start_value = match lower_bound {
-        Bound::Included(ip_addr) => *ip_addr,
-        Bound::Excluded(ip_addr) => Ipv6Addr::from(ip_addr.to_u128() + 1),
+        Bound::Included(val) => *val,
+        Bound::Excluded(val) => T::from_u128(val.to_u128() + 1),
         Bound::Unbounded => min_value,
     };
 
     let end_value = match upper_bound {
-        Bound::Included(ip_addr) => *ip_addr,
-        Bound::Excluded(ip_addr) => Ipv6Addr::from(ip_addr.to_u128() - 1),
+        Bound::Included(val) => *val,
+        Bound::Excluded(val) => T::from_u128(val.to_u128() - 1),
         Bound::Unbounded => max_value,
     };
     start_value..=end_value
@@ -448,7 +476,7 @@ fn bound_range_inclusive_ip(
 
 // Returns None, if the range cannot be converted to a inclusive range (which equals to a empty
 // range).
-fn bound_to_value_range<T: MonotonicallyMappableToU64>(
+pub(super) fn bound_to_value_range<T: MonotonicallyMappableToU64>(
     lower_bound: &Bound<T>,
     upper_bound: &Bound<T>,
     min_value: T,
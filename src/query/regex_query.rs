@@ -62,8 +62,9 @@ pub struct RegexQuery {
 impl RegexQuery {
     /// Creates a new RegexQuery from a given pattern
     pub fn from_pattern(regex_pattern: &str, field: Field) -> crate::Result<Self> {
-        let regex = Regex::new(regex_pattern)
-            .map_err(|err| TantivyError::InvalidArgument(format!("RegexQueryError: {err}")))?;
+        let regex = Regex::new(regex_pattern).map_err(|err| {
+            TantivyError::InvalidArgument(format!("RegexQueryError: {err}").into())
+        })?;
         Ok(RegexQuery::from_regex(regex, field))
     }
 
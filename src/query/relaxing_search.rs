@@ -0,0 +1,329 @@
+use crate::collector::{Collector, Count};
+use crate::query::{
+    BooleanQuery, BoostQuery, DisjunctionMaxQuery, FuzzyPhraseQuery, FuzzyTermQuery, Occur,
+    PhraseQuery, Query, TermQuery,
+};
+use crate::schema::Type;
+use crate::Searcher;
+
+/// A transformation making a query match more documents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Relaxation {
+    /// Optional clauses stop restricting the matches: they are dropped from the boolean queries
+    /// which have required clauses, and a single one of them is required to match in the other
+    /// boolean queries.
+    DropOptionalClauses,
+    /// Text terms and phrases match the terms within the given Levenshtein distance.
+    Fuzzy {
+        /// Levenshtein distance, at most 2.
+        distance: u8,
+    },
+    /// Required clauses become optional: boolean queries match documents matching any of their
+    /// clauses, e.g. `a AND b` becomes `a OR b`.
+    Disjunction,
+}
+
+impl Relaxation {
+    /// Returns the relaxed query.
+    ///
+    /// The relaxation is applied to the clauses of boolean, boost and disjunction max queries,
+    /// other queries are left unchanged.
+    pub fn relax(&self, query: &dyn Query) -> Box<dyn Query> {
+        if let Some(boolean_query) = query.downcast_ref::<BooleanQuery>() {
+            return Box::new(self.relax_boolean_query(boolean_query));
+        }
+        if let Some(boost_query) = query.downcast_ref::<BoostQuery>() {
+            return Box::new(BoostQuery::new(
+                self.relax(boost_query.query()),
+                boost_query.boost(),
+            ));
+        }
+        if let Some(disjunction_max_query) = query.downcast_ref::<DisjunctionMaxQuery>() {
+            let disjuncts = disjunction_max_query
+                .disjuncts()
+                .iter()
+                .map(|disjunct| self.relax(disjunct.as_ref()))
+                .collect();
+            return Box::new(DisjunctionMaxQuery::with_tie_breaker(
+                disjuncts,
+                disjunction_max_query.tie_breaker(),
+            ));
+        }
+        if let Relaxation::Fuzzy { distance } = *self {
+            if let Some(term_query) = query.downcast_ref::<TermQuery>() {
+                if term_query.term().typ() == Type::Str {
+                    return Box::new(FuzzyTermQuery::new(
+                        term_query.term().clone(),
+                        distance,
+                        true,
+                    ));
+                }
+            }
+            if let Some(phrase_query) = query.downcast_ref::<PhraseQuery>() {
+                let fuzzy_terms = phrase_query
+                    .phrase_terms_with_offsets()
+                    .iter()
+                    .map(|(offset, term)| (*offset, term.clone(), distance))
+                    .collect();
+                let mut fuzzy_phrase_query = FuzzyPhraseQuery::new_with_offset(fuzzy_terms);
+                fuzzy_phrase_query.set_slop(phrase_query.slop());
+                return Box::new(fuzzy_phrase_query);
+            }
+        }
+        query.box_clone()
+    }
+
+    fn relax_boolean_query(&self, boolean_query: &BooleanQuery) -> BooleanQuery {
+        let clauses = boolean_query
+            .clauses()
+            .iter()
+            .map(|(occur, subquery)| (*occur, self.relax(subquery.as_ref())));
+        match self {
+            Relaxation::DropOptionalClauses => {
+                let has_required_clause = boolean_query
+                    .clauses()
+                    .iter()
+                    .any(|(occur, _)| *occur == Occur::Must);
+                if has_required_clause {
+                    let clauses = clauses
+                        .filter(|(occur, _)| *occur != Occur::Should)
+                        .collect();
+                    BooleanQuery::new(clauses)
+                } else {
+                    let minimum_number_should_match =
+                        boolean_query.get_minimum_number_should_match().min(1);
+                    BooleanQuery::with_minimum_required_clauses(
+                        clauses.collect(),
+                        minimum_number_should_match,
+                    )
+                }
+            }
+            Relaxation::Fuzzy { .. } => BooleanQuery::with_minimum_required_clauses(
+                clauses.collect(),
+                boolean_query.get_minimum_number_should_match(),
+            ),
+            Relaxation::Disjunction => {
+                let clauses = clauses
+                    .map(|(occur, subquery)| {
+                        let occur = if occur == Occur::Must {
+                            Occur::Should
+                        } else {
+                            occur
+                        };
+                        (occur, subquery)
+                    })
+                    .collect();
+                BooleanQuery::new(clauses)
+            }
+        }
+    }
+}
+
+/// The result of a [`RelaxingSearch`].
+#[derive(Debug)]
+pub struct RelaxedSearchResult<TFruit> {
+    /// Number of relaxations applied to the query to produce the results.
+    ///
+    /// 0 means the original query reached the minimum number of hits.
+    pub relaxation_level: usize,
+    /// Number of documents matching the relaxed query.
+    pub num_hits: usize,
+    /// Fruit of the collector, for the relaxed query.
+    pub fruit: TFruit,
+}
+
+/// `RelaxingSearch` runs progressively looser formulations of a query until a minimum number of
+/// hits is reached.
+///
+/// The formulation of level `n` is the original query, to which the first `n` relaxations were
+/// applied. If none of the formulations reaches the minimum number of hits, the results of the
+/// loosest one are returned.
+///
+/// ```rust
+/// use tantivy::collector::TopDocs;
+/// use tantivy::query::{QueryParser, Relaxation, RelaxingSearch};
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{doc, Index, IndexWriter};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(title => "The Name of the Wind"))?;
+/// index_writer.add_document(doc!(title => "The Wise Man's Fear"))?;
+/// index_writer.commit()?;
+/// let searcher = index.reader()?.searcher();
+///
+/// let mut query_parser = QueryParser::for_index(&index, vec![title]);
+/// query_parser.set_conjunction_by_default();
+/// let query = query_parser.parse_query("wise wind")?;
+/// let mut relaxing_search = RelaxingSearch::new(query, 2);
+/// relaxing_search.add_relaxation(Relaxation::Fuzzy { distance: 1 });
+/// relaxing_search.add_relaxation(Relaxation::Disjunction);
+/// let result = relaxing_search.search(&searcher, &TopDocs::with_limit(10))?;
+/// assert_eq!(result.relaxation_level, 2);
+/// assert_eq!(result.fruit.len(), 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RelaxingSearch {
+    query: Box<dyn Query>,
+    relaxations: Vec<Relaxation>,
+    min_num_hits: usize,
+}
+
+impl RelaxingSearch {
+    /// Creates a search of `query`, to be relaxed until at least `min_num_hits` documents match.
+    pub fn new(query: Box<dyn Query>, min_num_hits: usize) -> RelaxingSearch {
+        RelaxingSearch {
+            query,
+            relaxations: Vec::new(),
+            min_num_hits,
+        }
+    }
+
+    /// Appends a relaxation, applied on top of the previous ones.
+    pub fn add_relaxation(&mut self, relaxation: Relaxation) {
+        self.relaxations.push(relaxation);
+    }
+
+    /// Returns the formulations of the query, from the original query to the loosest one.
+    pub fn queries(&self) -> Vec<Box<dyn Query>> {
+        let mut queries = vec![self.query.box_clone()];
+        for relaxation in &self.relaxations {
+            let relaxed_query = relaxation.relax(queries[queries.len() - 1].as_ref());
+            queries.push(relaxed_query);
+        }
+        queries
+    }
+
+    /// Counts the hits of the formulations of the query until one of them matches at least the
+    /// minimum number of hits, and collects the hits of this formulation.
+    pub fn search<C: Collector>(
+        &self,
+        searcher: &Searcher,
+        collector: &C,
+    ) -> crate::Result<RelaxedSearchResult<C::Fruit>> {
+        let queries = self.queries();
+        let loosest_level = queries.len() - 1;
+        for (relaxation_level, query) in queries.iter().enumerate() {
+            let num_hits = searcher.search(query.as_ref(), &Count)?;
+            if num_hits >= self.min_num_hits || relaxation_level == loosest_level {
+                let fruit = searcher.search(query.as_ref(), collector)?;
+                return Ok(RelaxedSearchResult {
+                    relaxation_level,
+                    num_hits,
+                    fruit,
+                });
+            }
+        }
+        unreachable!("There is at least one formulation of the query.")
+    }
+}
+
+#[cfg(all(test, feature = "query-parser"))]
+mod tests {
+    use super::{Relaxation, RelaxingSearch};
+    use crate::collector::Count;
+    use crate::query::{BooleanQuery, Occur, Query, QueryParser, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, TEXT};
+    use crate::{Index, IndexWriter, Term};
+
+    fn create_index() -> crate::Result<Index> {
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(text => "red apple pie"))?;
+        index_writer.add_document(doc!(text => "green apple"))?;
+        index_writer.add_document(doc!(text => "red cherry pie"))?;
+        index_writer.commit()?;
+        Ok(index)
+    }
+
+    #[test]
+    fn test_relaxations() -> crate::Result<()> {
+        let index = create_index()?;
+        let text = index.schema().get_field("text")?;
+        let searcher = index.reader()?.searcher();
+        let count = |query: &dyn Query| searcher.search(query, &Count).unwrap();
+        let mut query_parser = QueryParser::for_index(&index, vec![text]);
+        query_parser.set_conjunction_by_default();
+
+        let query = query_parser.parse_query("red aple")?;
+        assert_eq!(count(query.as_ref()), 0);
+        let fuzzy_query = Relaxation::Fuzzy { distance: 1 }.relax(query.as_ref());
+        assert_eq!(count(fuzzy_query.as_ref()), 1);
+        let disjunction_query = Relaxation::Disjunction.relax(query.as_ref());
+        assert_eq!(count(disjunction_query.as_ref()), 2);
+
+        let phrase_query = query_parser.parse_query("\"red aple pie\"")?;
+        assert_eq!(count(phrase_query.as_ref()), 0);
+        let fuzzy_phrase_query = Relaxation::Fuzzy { distance: 1 }.relax(phrase_query.as_ref());
+        assert_eq!(count(fuzzy_phrase_query.as_ref()), 1);
+
+        let term_query = |text_value: &str| -> Box<dyn Query> {
+            Box::new(TermQuery::new(
+                Term::from_field_text(text, text_value),
+                IndexRecordOption::Basic,
+            ))
+        };
+        let optional_query = BooleanQuery::with_minimum_required_clauses(
+            vec![
+                (Occur::Should, term_query("red")),
+                (Occur::Should, term_query("apple")),
+                (Occur::Should, term_query("pie")),
+            ],
+            3,
+        );
+        assert_eq!(count(&optional_query), 1);
+        let relaxed_query = Relaxation::DropOptionalClauses.relax(&optional_query);
+        assert_eq!(count(relaxed_query.as_ref()), 3);
+        let required_query = BooleanQuery::with_minimum_required_clauses(
+            vec![
+                (Occur::Must, term_query("apple")),
+                (Occur::Should, term_query("red")),
+                (Occur::Should, term_query("pie")),
+            ],
+            2,
+        );
+        assert_eq!(count(&required_query), 1);
+        let relaxed_query = Relaxation::DropOptionalClauses.relax(&required_query);
+        assert_eq!(count(relaxed_query.as_ref()), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_relaxing_search() -> crate::Result<()> {
+        let index = create_index()?;
+        let text = index.schema().get_field("text")?;
+        let searcher = index.reader()?.searcher();
+        let mut query_parser = QueryParser::for_index(&index, vec![text]);
+        query_parser.set_conjunction_by_default();
+        let query = query_parser.parse_query("red apple")?;
+
+        let mut relaxing_search = RelaxingSearch::new(query.box_clone(), 1);
+        relaxing_search.add_relaxation(Relaxation::Disjunction);
+        let result = relaxing_search.search(&searcher, &Count)?;
+        assert_eq!(result.relaxation_level, 0);
+        assert_eq!(result.num_hits, 1);
+        assert_eq!(result.fruit, 1);
+
+        let mut relaxing_search = RelaxingSearch::new(query.box_clone(), 2);
+        relaxing_search.add_relaxation(Relaxation::Fuzzy { distance: 1 });
+        relaxing_search.add_relaxation(Relaxation::Disjunction);
+        assert_eq!(relaxing_search.queries().len(), 3);
+        let result = relaxing_search.search(&searcher, &Count)?;
+        assert_eq!(result.relaxation_level, 2);
+        assert_eq!(result.num_hits, 3);
+
+        // The loosest formulation is returned if the minimum number of hits is never reached.
+        let relaxing_search = RelaxingSearch::new(query, 10);
+        let result = relaxing_search.search(&searcher, &Count)?;
+        assert_eq!(result.relaxation_level, 0);
+        assert_eq!(result.num_hits, 1);
+        Ok(())
+    }
+}
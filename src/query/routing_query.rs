@@ -0,0 +1,176 @@
+use std::fmt;
+
+use crate::query::{EnableScoring, Query, TenantQuery, Weight};
+use crate::{TantivyError, Term};
+
+/// `RoutingQuery` restricts a query to the documents of a single routing value.
+///
+/// It is meant to be used on indexes with a
+/// [`routing_field`](crate::IndexSettings::routing_field) (see
+/// [`indexer::routing`](crate::indexer::routing)). It is a [`TenantQuery`] on the routing
+/// field: segments tagged with another partition than the one of the routing value are skipped
+/// without being searched.
+///
+/// The other segments are filtered on the routing term, as a partition holds the documents of
+/// many routing values. The score of the matched documents is the score of the underlying query.
+pub struct RoutingQuery {
+    routing_value: String,
+    query: Box<dyn Query>,
+}
+
+impl RoutingQuery {
+    /// Creates a new `RoutingQuery` restricting `query` to the documents of `routing_value`.
+    pub fn new(routing_value: &str, query: Box<dyn Query>) -> RoutingQuery {
+        RoutingQuery {
+            routing_value: routing_value.to_string(),
+            query,
+        }
+    }
+
+    /// Returns the routing value the query is restricted to.
+    pub fn routing_value(&self) -> &str {
+        &self.routing_value
+    }
+}
+
+impl Clone for RoutingQuery {
+    fn clone(&self) -> Self {
+        RoutingQuery {
+            routing_value: self.routing_value.clone(),
+            query: self.query.box_clone(),
+        }
+    }
+}
+
+impl fmt::Debug for RoutingQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Routing(routing_value={:?}, query={:?})",
+            self.routing_value, self.query
+        )
+    }
+}
+
+impl Query for RoutingQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let searcher = enable_scoring.searcher().ok_or_else(|| {
+            TantivyError::InvalidArgument(
+                "RoutingQuery requires a searcher to resolve the routing settings".into(),
+            )
+        })?;
+        let routing_settings = searcher
+            .index()
+            .settings()
+            .routing_field
+            .as_ref()
+            .ok_or_else(|| {
+                TantivyError::InvalidArgument(
+                    "RoutingQuery requires the index to have a routing field".into(),
+                )
+            })?;
+        let routing_field = searcher.schema().get_field(&routing_settings.field)?;
+        TenantQuery::new(routing_field, &self.routing_value, self.query.box_clone())
+            .weight(enable_scoring)
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoutingQuery;
+    use crate::collector::{Count, TopDocs};
+    use crate::index::PartitionKey;
+    use crate::indexer::routing::RoutingSettings;
+    use crate::indexer::NoMergePolicy;
+    use crate::query::{AllQuery, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, STRING, TEXT};
+    use crate::{Index, IndexSettings, IndexWriter, Term};
+
+    #[test]
+    fn test_routing_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let routing_settings = RoutingSettings::new("tenant", 4);
+        let index = Index::builder()
+            .schema(schema_builder.build())
+            .settings(IndexSettings {
+                routing_field: Some(routing_settings.clone()),
+                ..Default::default()
+            })
+            .create_in_ram()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        for i in 0..20 {
+            let tenant_name = format!("tenant-{i}");
+            index_writer.add_document(doc!(tenant => tenant_name.as_str(), body => "hello"))?;
+            index_writer.add_document(doc!(tenant => tenant_name.as_str(), body => "world"))?;
+        }
+        index_writer.add_document(doc!(body => "hello"))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let segment_partition =
+            |segment_ord: u32| match &searcher.segment_reader(segment_ord).partition()?.key {
+                PartitionKey::Hash { partition, .. } => Some(*partition),
+                PartitionKey::Value(_) => panic!("expected a hash partition"),
+            };
+        let mut partitions: Vec<Option<u32>> = (0..searcher.segment_readers().len() as u32)
+            .map(segment_partition)
+            .collect();
+        partitions.sort();
+        assert_eq!(partitions, vec![None, Some(0), Some(1), Some(2), Some(3)]);
+
+        let hello = TermQuery::new(
+            Term::from_field_text(body, "hello"),
+            IndexRecordOption::Basic,
+        );
+        let count = |routing_value: &str, query: Box<dyn Query>| {
+            let routing_query = RoutingQuery::new(routing_value, query);
+            let top_docs = searcher
+                .search(&routing_query, &TopDocs::with_limit(10))
+                .unwrap();
+            let count = searcher.search(&routing_query, &Count).unwrap();
+            assert_eq!(top_docs.len(), count);
+            let partition = routing_settings.partition(routing_value);
+            assert!(top_docs.iter().all(|(_, doc_address)| {
+                segment_partition(doc_address.segment_ord) == Some(partition)
+            }));
+            count
+        };
+        assert_eq!(count("tenant-3", Box::new(AllQuery)), 2);
+        assert_eq!(count("tenant-3", Box::new(hello.clone())), 1);
+        assert_eq!(count("tenant-17", Box::new(hello)), 1);
+        assert_eq!(count("umbrella", Box::new(AllQuery)), 0);
+
+        let query = RoutingQuery::new("tenant-3", Box::new(AllQuery));
+        let (_, doc_address) = searcher.search(&query, &TopDocs::with_limit(1))?[0];
+        assert_eq!(query.explain(&searcher, doc_address)?.value(), 1.0);
+        let other_tenant_query = TermQuery::new(
+            Term::from_field_text(tenant, "tenant-4"),
+            IndexRecordOption::Basic,
+        );
+        let (_, other_doc_address) =
+            searcher.search(&other_tenant_query, &TopDocs::with_limit(1))?[0];
+        assert!(query.explain(&searcher, other_doc_address).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_routing_query_requires_routing_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tenant => "acme"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = RoutingQuery::new("acme", Box::new(AllQuery));
+        assert!(searcher.search(&query, &Count).is_err());
+        Ok(())
+    }
+}
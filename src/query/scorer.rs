@@ -7,6 +7,9 @@ use crate::Score;
 
 /// Scored set of documents matching a query within a specific segment.
 ///
+/// A scorer is created and consumed by the thread searching its segment, so that it is neither
+/// required to be `Send` nor `Sync`.
+///
 /// See [`Query`](crate::query::Query).
 pub trait Scorer: downcast_rs::Downcast + DocSet + 'static {
     /// Returns the score.
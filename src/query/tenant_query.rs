@@ -0,0 +1,318 @@
+use std::fmt;
+
+use crate::index::PartitionKey;
+use crate::indexer::routing::hash_partition;
+use crate::query::explanation::does_not_match;
+use crate::query::weight::for_each_pruning_scorer;
+use crate::query::{
+    intersect_scorers, ConstScorer, EmptyScorer, EnableScoring, Explanation, Query, Scorer,
+    TermQuery, Weight,
+};
+use crate::schema::{Field, IndexRecordOption};
+use crate::{DocId, Score, SegmentReader, Term};
+
+/// `TenantQuery` restricts a query to the documents of a single tenant.
+///
+/// It is meant to be used on indexes written with a tenant field
+/// (see [`IndexWriterOptions`](crate::indexer::IndexWriterOptions)): segments tagged with
+/// another tenant are skipped without being searched, and segments tagged with the
+/// requested tenant are searched without any extra filtering.
+///
+/// Segments hash partitioned on the tenant field (see
+/// [`indexer::routing`](crate::indexer::routing)) are skipped if the tenant hashes to another
+/// partition, and filtered on the tenant term otherwise.
+///
+/// Segments that are not tagged, for instance because they were written before the
+/// tenant field was configured, or tagged on another field than the tenant field of the query,
+/// are filtered on the tenant term.
+/// The score of the matched documents is the score of the underlying query.
+pub struct TenantQuery {
+    tenant_field: Field,
+    tenant: String,
+    query: Box<dyn Query>,
+}
+
+impl TenantQuery {
+    /// Creates a new `TenantQuery` restricting `query` to the documents of `tenant`.
+    pub fn new(tenant_field: Field, tenant: &str, query: Box<dyn Query>) -> TenantQuery {
+        TenantQuery {
+            tenant_field,
+            tenant: tenant.to_string(),
+            query,
+        }
+    }
+
+    /// Returns the tenant the query is restricted to.
+    pub fn tenant(&self) -> &str {
+        &self.tenant
+    }
+}
+
+impl Clone for TenantQuery {
+    fn clone(&self) -> Self {
+        TenantQuery {
+            tenant_field: self.tenant_field,
+            tenant: self.tenant.clone(),
+            query: self.query.box_clone(),
+        }
+    }
+}
+
+impl fmt::Debug for TenantQuery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Tenant(tenant={:?}, query={:?})",
+            self.tenant, self.query
+        )
+    }
+}
+
+impl Query for TenantQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> crate::Result<Box<dyn Weight>> {
+        let weight = self.query.weight(enable_scoring)?;
+        let tenant_term = Term::from_field_text(self.tenant_field, &self.tenant);
+        let filter_scoring = match enable_scoring.searcher() {
+            Some(searcher) => EnableScoring::disabled_from_searcher(searcher),
+            None => EnableScoring::disabled_from_schema(enable_scoring.schema()),
+        };
+        let filter_weight =
+            TermQuery::new(tenant_term, IndexRecordOption::Basic).weight(filter_scoring)?;
+        Ok(Box::new(TenantWeight {
+            tenant_field_name: enable_scoring
+                .schema()
+                .get_field_name(self.tenant_field)
+                .to_string(),
+            tenant: self.tenant.clone(),
+            weight,
+            filter_weight,
+        }))
+    }
+
+    fn query_terms<'a>(&'a self, visitor: &mut dyn FnMut(&'a Term, bool)) {
+        self.query.query_terms(visitor)
+    }
+}
+
+/// How a segment relates to the tenant of a [`TenantQuery`].
+enum SegmentTenant {
+    /// The segment only contains documents of the tenant.
+    Matching,
+    /// The segment does not contain any document of the tenant.
+    Other,
+    /// The segment may contain documents of the tenant and of other tenants.
+    Unknown,
+}
+
+/// Weight associated to the `TenantQuery`.
+struct TenantWeight {
+    tenant_field_name: String,
+    tenant: String,
+    weight: Box<dyn Weight>,
+    filter_weight: Box<dyn Weight>,
+}
+
+impl TenantWeight {
+    fn segment_tenant(&self, reader: &SegmentReader) -> SegmentTenant {
+        let Some(partition) = reader.partition() else {
+            return SegmentTenant::Unknown;
+        };
+        if partition.field != self.tenant_field_name {
+            return SegmentTenant::Unknown;
+        }
+        match &partition.key {
+            PartitionKey::Value(tenant) if *tenant == self.tenant => SegmentTenant::Matching,
+            PartitionKey::Value(_) => SegmentTenant::Other,
+            PartitionKey::Hash {
+                partition,
+                num_partitions,
+            } => {
+                // A hash partition holds the documents of many tenants.
+                if hash_partition(&self.tenant, *num_partitions) == *partition {
+                    SegmentTenant::Unknown
+                } else {
+                    SegmentTenant::Other
+                }
+            }
+        }
+    }
+}
+
+impl Weight for TenantWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> crate::Result<Box<dyn Scorer>> {
+        match self.segment_tenant(reader) {
+            SegmentTenant::Matching => self.weight.scorer(reader, boost),
+            SegmentTenant::Other => Ok(Box::new(EmptyScorer)),
+            SegmentTenant::Unknown => {
+                let scorer = self.weight.scorer(reader, boost)?;
+                let filter_scorer = self.filter_weight.scorer(reader, 1.0)?;
+                Ok(intersect_scorers(vec![
+                    scorer,
+                    Box::new(ConstScorer::new(filter_scorer, 0.0)),
+                ]))
+            }
+        }
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> crate::Result<Explanation> {
+        let mut scorer = self.scorer(reader, 1.0)?;
+        if scorer.doc() > doc || scorer.seek(doc) != doc {
+            return Err(does_not_match(doc));
+        }
+        self.weight.explain(reader, doc)
+    }
+
+    fn count(&self, reader: &SegmentReader) -> crate::Result<u32> {
+        match self.segment_tenant(reader) {
+            SegmentTenant::Matching => self.weight.count(reader),
+            SegmentTenant::Other => Ok(0),
+            SegmentTenant::Unknown => {
+                let mut scorer = self.scorer(reader, 1.0)?;
+                if let Some(alive_bitset) = reader.alive_bitset() {
+                    Ok(scorer.count(alive_bitset))
+                } else {
+                    Ok(scorer.count_including_deleted())
+                }
+            }
+        }
+    }
+
+    fn for_each_pruning(
+        &self,
+        threshold: Score,
+        reader: &SegmentReader,
+        callback: &mut dyn FnMut(DocId, Score) -> Score,
+    ) -> crate::Result<()> {
+        match self.segment_tenant(reader) {
+            SegmentTenant::Matching => self.weight.for_each_pruning(threshold, reader, callback),
+            SegmentTenant::Other => Ok(()),
+            SegmentTenant::Unknown => {
+                let mut scorer = self.scorer(reader, 1.0)?;
+                for_each_pruning_scorer(scorer.as_mut(), threshold, callback);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TenantQuery;
+    use crate::collector::{Count, TopDocs};
+    use crate::index::PartitionKey;
+    use crate::indexer::{IndexWriterOptions, NoMergePolicy};
+    use crate::query::{AllQuery, Query, TermQuery};
+    use crate::schema::{IndexRecordOption, Schema, STRING, TEXT};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_tenant_query() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = Index::create_in_ram(schema_builder.build());
+        {
+            // Untagged segment, written without a tenant field.
+            let mut index_writer: IndexWriter = index.writer_for_tests()?;
+            index_writer.add_document(doc!(tenant => "acme", body => "hello"))?;
+            index_writer.add_document(doc!(tenant => "initech", body => "hello"))?;
+            index_writer.commit()?;
+        }
+        let options = IndexWriterOptions::builder()
+            .num_worker_threads(1)
+            .tenant_field(tenant)
+            .build();
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(tenant => "acme", body => "hello world"))?;
+        index_writer.add_document(doc!(tenant => "initech", body => "hello"))?;
+        index_writer.add_document(doc!(tenant => "acme", body => "world"))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let mut tenants: Vec<Option<&str>> = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| {
+                let partition = segment_reader.partition()?;
+                assert_eq!(partition.field, "tenant");
+                match &partition.key {
+                    PartitionKey::Value(tenant) => Some(tenant.as_str()),
+                    PartitionKey::Hash { .. } => panic!("expected a tenant partition"),
+                }
+            })
+            .collect();
+        tenants.sort();
+        assert_eq!(tenants, vec![None, Some("acme"), Some("initech")]);
+
+        let hello = TermQuery::new(
+            Term::from_field_text(body, "hello"),
+            IndexRecordOption::Basic,
+        );
+        let count = |tenant_name: &str, query: Box<dyn Query>| {
+            let tenant_query = TenantQuery::new(tenant, tenant_name, query);
+            let top_docs = searcher
+                .search(&tenant_query, &TopDocs::with_limit(10))
+                .unwrap();
+            let count = searcher.search(&tenant_query, &Count).unwrap();
+            assert_eq!(top_docs.len(), count);
+            count
+        };
+        assert_eq!(count("acme", Box::new(AllQuery)), 3);
+        assert_eq!(count("acme", Box::new(hello.clone())), 2);
+        assert_eq!(count("initech", Box::new(hello)), 2);
+        assert_eq!(count("umbrella", Box::new(AllQuery)), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tenant_query_other_tenant_field() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let owner = schema_builder.add_text_field("owner", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let options = IndexWriterOptions::builder()
+            .num_worker_threads(1)
+            .tenant_field(tenant)
+            .build();
+        let mut index_writer: IndexWriter = index.writer_with_options(options)?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(tenant => "acme", owner => "initech"))?;
+        index_writer.add_document(doc!(tenant => "initech", owner => "acme"))?;
+        index_writer.add_document(doc!(tenant => "initech", owner => "initech"))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        // Segments are tagged on `tenant`: the tag does not tell anything about `owner`, so that
+        // the segments are filtered on the owner term rather than being skipped or fully matched.
+        let count = |field, tenant_name: &str| {
+            let tenant_query = TenantQuery::new(field, tenant_name, Box::new(AllQuery));
+            searcher.search(&tenant_query, &Count).unwrap()
+        };
+        assert_eq!(count(owner, "acme"), 1);
+        assert_eq!(count(owner, "initech"), 2);
+        assert_eq!(count(tenant, "acme"), 1);
+        assert_eq!(count(tenant, "initech"), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tenant_query_explain() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let tenant = schema_builder.add_text_field("tenant", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(tenant => "acme"))?;
+        index_writer.add_document(doc!(tenant => "initech"))?;
+        index_writer.commit()?;
+        let searcher = index.reader()?.searcher();
+        let query = TenantQuery::new(tenant, "acme", Box::new(AllQuery));
+        let explanation = query.explain(&searcher, crate::DocAddress::new(0, 0))?;
+        assert_eq!(explanation.value(), 1.0);
+        assert!(query
+            .explain(&searcher, crate::DocAddress::new(0, 1))
+            .is_err());
+        Ok(())
+    }
+}
@@ -62,6 +62,9 @@ pub(crate) fn for_each_pruning_scorer<TScorer: Scorer + ?Sized>(
 /// A Weight is the specialization of a `Query`
 /// for a given set of segments.
 ///
+/// Weights are `Send` and `Sync`: the weight of a query is built once, and shared by the
+/// threads searching the different segments.
+///
 /// See [`Query`](crate::query::Query).
 pub trait Weight: Send + Sync + 'static {
     /// Returns the scorer for the given segment.
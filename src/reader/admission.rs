@@ -0,0 +1,315 @@
+use std::ops::Deref;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::{Searcher, TantivyError};
+
+/// Limits the searches running concurrently on an [`IndexReader`](crate::IndexReader), so that
+/// bursts of traffic are queued or shed instead of slowing down all of the searches.
+///
+/// Searches are admitted with [`IndexReader::admit()`](crate::IndexReader::admit). A search is
+/// admitted right away if:
+/// - fewer than `max_concurrent_searches` searches are running,
+/// - the memory estimates of the running searches, including its own, fit in the memory budget, if
+///   any,
+/// - and no other search is queued.
+///
+/// Otherwise, it waits in the queue for up to the queue timeout. If the queue is full, or the
+/// timeout elapses, it is rejected with [`TantivyError::Overloaded`], which can be retried later.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use tantivy::collector::Count;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::Schema;
+/// use tantivy::{AdmissionPolicy, Index, TantivyError};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let index = Index::create_in_ram(Schema::builder().build());
+/// let reader = index
+///     .reader_builder()
+///     .admission_policy(
+///         AdmissionPolicy::new(1)
+///             .max_queued_searches(0)
+///             .queue_timeout(Duration::from_millis(10)),
+///     )
+///     .try_into()?;
+///
+/// let permit = reader.admit()?;
+/// assert!(matches!(reader.admit(), Err(TantivyError::Overloaded(_))));
+/// assert_eq!(permit.search(&AllQuery, &Count)?, 0);
+/// drop(permit);
+/// assert!(reader.admit().is_ok());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct AdmissionPolicy {
+    max_concurrent_searches: usize,
+    max_queued_searches: usize,
+    queue_timeout: Duration,
+    memory_budget: Option<u64>,
+}
+
+impl AdmissionPolicy {
+    /// Creates a policy running at most `max_concurrent_searches` searches at once, typically
+    /// the number of cores dedicated to searching.
+    ///
+    /// By default, as many searches may be queued, for up to 100ms, and there is no memory
+    /// budget.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_concurrent_searches` is 0.
+    pub fn new(max_concurrent_searches: usize) -> AdmissionPolicy {
+        assert!(
+            max_concurrent_searches > 0,
+            "At least one search must be allowed to run."
+        );
+        AdmissionPolicy {
+            max_concurrent_searches,
+            max_queued_searches: max_concurrent_searches,
+            queue_timeout: Duration::from_millis(100),
+            memory_budget: None,
+        }
+    }
+
+    /// Sets the number of searches which may wait for a running search to complete. Searches
+    /// are rejected right away once the queue is full.
+    #[must_use]
+    pub fn max_queued_searches(mut self, max_queued_searches: usize) -> AdmissionPolicy {
+        self.max_queued_searches = max_queued_searches;
+        self
+    }
+
+    /// Sets how long a search may wait in the queue before being rejected.
+    #[must_use]
+    pub fn queue_timeout(mut self, queue_timeout: Duration) -> AdmissionPolicy {
+        self.queue_timeout = queue_timeout;
+        self
+    }
+
+    /// Sets the budget, in bytes, of the memory estimates of the running searches.
+    ///
+    /// See [`IndexReader::admit_with_memory()`](crate::IndexReader::admit_with_memory). A search
+    /// exceeding the budget on its own is only admitted when no other search is running.
+    #[must_use]
+    pub fn memory_budget(mut self, memory_budget: u64) -> AdmissionPolicy {
+        self.memory_budget = Some(memory_budget);
+        self
+    }
+}
+
+#[derive(Default)]
+struct AdmissionState {
+    num_running: usize,
+    num_queued: usize,
+    memory_in_use: u64,
+}
+
+impl AdmissionState {
+    fn has_room_for(&self, policy: &AdmissionPolicy, memory: u64) -> bool {
+        if self.num_running == 0 {
+            return true;
+        }
+        self.num_running < policy.max_concurrent_searches
+            && policy
+                .memory_budget
+                .map_or(true, |budget| self.memory_in_use + memory <= budget)
+    }
+}
+
+/// Tracks the searches admitted by an [`AdmissionPolicy`].
+pub(crate) struct AdmissionController {
+    policy: AdmissionPolicy,
+    state: Mutex<AdmissionState>,
+    // Notified whenever a search completes.
+    search_completed: Condvar,
+}
+
+impl AdmissionController {
+    pub fn new(policy: AdmissionPolicy) -> AdmissionController {
+        AdmissionController {
+            policy,
+            state: Mutex::default(),
+            search_completed: Condvar::new(),
+        }
+    }
+
+    /// Waits until a search with the given memory estimate can run.
+    pub fn admit(self: &Arc<Self>, memory: u64) -> crate::Result<Admission> {
+        let mut state = self.state.lock()?;
+        // Searches are not admitted ahead of the queued ones.
+        if state.num_queued > 0 || !state.has_room_for(&self.policy, memory) {
+            if state.num_queued >= self.policy.max_queued_searches {
+                return Err(TantivyError::Overloaded(format!(
+                    "{} searches are running and {} are queued",
+                    state.num_running, state.num_queued
+                )));
+            }
+            let deadline = Instant::now() + self.policy.queue_timeout;
+            state.num_queued += 1;
+            while !state.has_room_for(&self.policy, memory) {
+                let now = Instant::now();
+                if now >= deadline {
+                    state.num_queued -= 1;
+                    return Err(TantivyError::Overloaded(format!(
+                        "The search was not admitted within {:?}",
+                        self.policy.queue_timeout
+                    )));
+                }
+                state = self.search_completed.wait_timeout(state, deadline - now)?.0;
+            }
+            state.num_queued -= 1;
+        }
+        state.num_running += 1;
+        state.memory_in_use += memory;
+        Ok(Admission {
+            controller: self.clone(),
+            memory,
+        })
+    }
+}
+
+/// A search admitted by an [`AdmissionController`], released on drop.
+pub(crate) struct Admission {
+    controller: Arc<AdmissionController>,
+    memory: u64,
+}
+
+impl Drop for Admission {
+    fn drop(&mut self) {
+        let mut state = self
+            .controller
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.num_running -= 1;
+        state.memory_in_use -= self.memory;
+        self.controller.search_completed.notify_all();
+    }
+}
+
+/// A [`Searcher`] admitted by the [`AdmissionPolicy`] of the reader.
+///
+/// The search counts as running until the permit is dropped. See
+/// [`IndexReader::admit()`](crate::IndexReader::admit).
+pub struct SearchPermit {
+    searcher: Searcher,
+    _admission: Option<Admission>,
+}
+
+impl SearchPermit {
+    pub(crate) fn new(searcher: Searcher, admission: Option<Admission>) -> SearchPermit {
+        SearchPermit {
+            searcher,
+            _admission: admission,
+        }
+    }
+
+    /// Returns the admitted searcher.
+    pub fn searcher(&self) -> &Searcher {
+        &self.searcher
+    }
+}
+
+impl Deref for SearchPermit {
+    type Target = Searcher;
+
+    fn deref(&self) -> &Searcher {
+        &self.searcher
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use super::AdmissionPolicy;
+    use crate::collector::Count;
+    use crate::query::AllQuery;
+    use crate::schema::Schema;
+    use crate::{Index, IndexReader, TantivyError};
+
+    fn reader_with_policy(admission_policy: AdmissionPolicy) -> crate::Result<IndexReader> {
+        let index = Index::create_in_ram(Schema::builder().build());
+        index
+            .reader_builder()
+            .admission_policy(admission_policy)
+            .try_into()
+    }
+
+    #[test]
+    fn test_admission_without_policy() -> crate::Result<()> {
+        let index = Index::create_in_ram(Schema::builder().build());
+        let reader = index.reader()?;
+        let permits: Vec<_> = (0..100)
+            .map(|_| reader.admit())
+            .collect::<crate::Result<_>>()?;
+        assert_eq!(permits[0].search(&AllQuery, &Count)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_admission_sheds_when_queue_is_full() -> crate::Result<()> {
+        let reader = reader_with_policy(AdmissionPolicy::new(2).max_queued_searches(0))?;
+        let first = reader.admit()?;
+        let _second = reader.admit()?;
+        let err = reader.admit().err().unwrap();
+        assert!(matches!(err, TantivyError::Overloaded(_)));
+        assert!(err.code().is_retryable());
+        drop(first);
+        reader.admit()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_admission_queue_timeout() -> crate::Result<()> {
+        let queue_timeout = Duration::from_millis(20);
+        let reader = reader_with_policy(AdmissionPolicy::new(1).queue_timeout(queue_timeout))?;
+        let _permit = reader.admit()?;
+        let start = Instant::now();
+        assert!(matches!(reader.admit(), Err(TantivyError::Overloaded(_))));
+        assert!(start.elapsed() >= queue_timeout);
+        Ok(())
+    }
+
+    #[test]
+    fn test_admission_queued_search_runs_once_released() -> crate::Result<()> {
+        let reader =
+            reader_with_policy(AdmissionPolicy::new(1).queue_timeout(Duration::from_secs(10)))?;
+        let permit = reader.admit()?;
+        let queued_reader = reader.clone();
+        let queued_search =
+            thread::spawn(move || queued_reader.admit().map(|permit| permit.num_docs()));
+        thread::sleep(Duration::from_millis(20));
+        drop(permit);
+        assert_eq!(queued_search.join().unwrap()?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_admission_memory_budget() -> crate::Result<()> {
+        let reader = reader_with_policy(
+            AdmissionPolicy::new(10)
+                .max_queued_searches(0)
+                .memory_budget(100),
+        )?;
+        let first = reader.admit_with_memory(60)?;
+        assert!(matches!(
+            reader.admit_with_memory(60),
+            Err(TantivyError::Overloaded(_))
+        ));
+        let second = reader.admit_with_memory(40)?;
+        drop(first);
+        drop(second);
+        // A search exceeding the budget runs alone.
+        let large = reader.admit_with_memory(1_000)?;
+        assert!(reader.admit_with_memory(0).is_err());
+        drop(large);
+        reader.admit_with_memory(0)?;
+        Ok(())
+    }
+}
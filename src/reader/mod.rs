@@ -1,16 +1,27 @@
+mod admission;
+mod searcher_handle;
+mod segment_cache;
 mod warming;
 
 use std::sync::atomic::AtomicU64;
-use std::sync::{atomic, Arc, Weak};
+use std::sync::{atomic, Arc, Condvar, Mutex, Weak};
+use std::time::{Duration, Instant};
 
+pub use admission::{AdmissionPolicy, SearchPermit};
 use arc_swap::ArcSwap;
+pub use searcher_handle::SearcherHandle;
+pub use segment_cache::SegmentCache;
 pub use warming::Warmer;
 
+use self::admission::AdmissionController;
+use self::searcher_handle::PinnedSearchers;
 use self::warming::WarmingState;
 use crate::core::searcher::{SearcherGeneration, SearcherInner};
 use crate::directory::{Directory, WatchCallback, WatchHandle, META_LOCK};
 use crate::store::DOCSTORE_CACHE_CAPACITY;
-use crate::{Index, Inventory, Searcher, SegmentReader, TrackedObject};
+use crate::{
+    Executor, Index, Inventory, Opstamp, Searcher, SegmentReader, TantivyError, TrackedObject,
+};
 
 /// Defines when a new version of the index should be reloaded.
 ///
@@ -35,15 +46,21 @@ pub enum ReloadPolicy {
 /// It makes it possible to configure:
 /// - [`ReloadPolicy`] defining when new index versions are detected
 /// - [`Warmer`] implementations
-/// - number of warming threads, for parallelizing warming work
+/// - number of warming threads, for parallelizing warming work, or the [`Executor`] running the
+///   warmers
+/// - whether the reader may spawn threads of its own
 /// - The cache size of the underlying doc store readers.
+/// - the [`AdmissionPolicy`] limiting the concurrent searches
 #[derive(Clone)]
 pub struct IndexReaderBuilder {
     reload_policy: ReloadPolicy,
     index: Index,
     warmers: Vec<Weak<dyn Warmer>>,
     num_warming_threads: usize,
+    warming_executor: Option<Executor>,
+    single_thread: bool,
     doc_store_cache_num_blocks: usize,
+    admission_policy: Option<AdmissionPolicy>,
 }
 
 impl IndexReaderBuilder {
@@ -54,7 +71,10 @@ impl IndexReaderBuilder {
             index,
             warmers: Vec::new(),
             num_warming_threads: 1,
+            warming_executor: None,
+            single_thread: false,
             doc_store_cache_num_blocks: DOCSTORE_CACHE_CAPACITY,
+            admission_policy: None,
         }
     }
 
@@ -64,9 +84,24 @@ impl IndexReaderBuilder {
     /// to open different segment readers. It may take hundreds of milliseconds
     /// of time and it may return an error.
     pub fn try_into(self) -> crate::Result<IndexReader> {
+        if self.single_thread && !matches!(self.reload_policy, ReloadPolicy::Manual) {
+            return Err(TantivyError::InvalidArgument(
+                "A single thread reader requires the `Manual` reload policy.".into(),
+            ));
+        }
+        let warming_executor = if self.single_thread {
+            Some(
+                self.warming_executor
+                    .unwrap_or_else(Executor::single_thread),
+            )
+        } else {
+            self.warming_executor
+        };
         let searcher_generation_inventory = Inventory::default();
         let warming_state = WarmingState::new(
             self.num_warming_threads,
+            warming_executor,
+            !self.single_thread,
             self.warmers,
             searcher_generation_inventory.clone(),
         )?;
@@ -101,7 +136,10 @@ impl IndexReaderBuilder {
         };
         Ok(IndexReader {
             inner: inner_reader_arc,
-            _watch_handle_opt: watch_handle_opt,
+            watch_handle_opt,
+            admission_controller_opt: self
+                .admission_policy
+                .map(|policy| Arc::new(AdmissionController::new(policy))),
         })
     }
 
@@ -142,6 +180,48 @@ impl IndexReaderBuilder {
         self.num_warming_threads = num_warming_threads;
         self
     }
+
+    /// Sets the [`Executor`] running the warmers.
+    ///
+    /// By default, a thread pool of [`num_warming_threads`](Self::num_warming_threads) threads
+    /// is created upon each warming, if there is more than one warming thread. An executor
+    /// wrapping an existing thread pool avoids that.
+    #[must_use]
+    pub fn warming_executor(mut self, warming_executor: Executor) -> IndexReaderBuilder {
+        self.warming_executor = Some(warming_executor);
+        self
+    }
+
+    /// Makes the reader run without any thread of its own, e.g. for embedding it in a plugin, a
+    /// serverless function, or another runtime.
+    ///
+    /// All of the work of the reader then happens in the threads calling it:
+    /// - the index is not watched for new commits. The reload policy is set to
+    ///   [`ReloadPolicy::Manual`], and building the reader fails if another policy is set
+    ///   afterwards.
+    /// - the warmers run in the thread reloading the reader, one after the other, unless a
+    ///   [`warming_executor`](Self::warming_executor) is set.
+    /// - the garbage collection of the warmers happens upon reload, instead of in a background
+    ///   thread.
+    ///
+    /// Searches run on the [search executor](Index::search_executor) of the index, which is
+    /// single threaded by default.
+    #[must_use]
+    pub fn single_thread(mut self) -> IndexReaderBuilder {
+        self.single_thread = true;
+        self.reload_policy = ReloadPolicy::Manual;
+        self
+    }
+
+    /// Sets the [`AdmissionPolicy`] limiting the searches admitted with
+    /// [`IndexReader::admit()`].
+    ///
+    /// By default, all of the searches are admitted right away.
+    #[must_use]
+    pub fn admission_policy(mut self, admission_policy: AdmissionPolicy) -> IndexReaderBuilder {
+        self.admission_policy = Some(admission_policy);
+        self
+    }
 }
 
 impl TryInto<IndexReader> for IndexReaderBuilder {
@@ -157,6 +237,9 @@ struct InnerIndexReader {
     index: Index,
     warming_state: WarmingState,
     searcher: arc_swap::ArcSwap<SearcherInner>,
+    // Notified every time a new searcher is published.
+    reload_condvar: (Mutex<()>, Condvar),
+    pinned_searchers: PinnedSearchers,
     searcher_generation_counter: Arc<AtomicU64>,
     searcher_generation_inventory: Inventory<SearcherGeneration>,
 }
@@ -184,23 +267,27 @@ impl InnerIndexReader {
             index,
             warming_state,
             searcher: ArcSwap::from(searcher),
+            reload_condvar: (Mutex::new(()), Condvar::new()),
+            pinned_searchers: PinnedSearchers::default(),
             searcher_generation_counter,
             searcher_generation_inventory,
         })
     }
-    /// Opens the freshest segments [`SegmentReader`].
+    /// Opens the freshest segments [`SegmentReader`], and returns them along with the opstamp of
+    /// their commit.
     ///
     /// This function acquires a lock to prevent GC from removing files
     /// as we are opening our index.
-    fn open_segment_readers(index: &Index) -> crate::Result<Vec<SegmentReader>> {
+    fn open_segment_readers(index: &Index) -> crate::Result<(Opstamp, Vec<SegmentReader>)> {
         // Prevents segment files from getting deleted while we are in the process of opening them
         let _meta_lock = index.directory().acquire_lock(&META_LOCK)?;
-        let searchable_segments = index.searchable_segments()?;
-        let segment_readers = searchable_segments
-            .iter()
-            .map(SegmentReader::open)
+        let index_meta = index.load_metas()?;
+        let segment_readers = index_meta
+            .segments
+            .into_iter()
+            .map(|segment_meta| SegmentReader::open(&index.segment(segment_meta)))
             .collect::<crate::Result<_>>()?;
-        Ok(segment_readers)
+        Ok((index_meta.opstamp, segment_readers))
     }
 
     fn track_segment_readers_in_inventory(
@@ -221,7 +308,7 @@ impl InnerIndexReader {
         searcher_generation_counter: &Arc<AtomicU64>,
         searcher_generation_inventory: &Inventory<SearcherGeneration>,
     ) -> crate::Result<Arc<SearcherInner>> {
-        let segment_readers = Self::open_segment_readers(index)?;
+        let (opstamp, segment_readers) = Self::open_segment_readers(index)?;
         let searcher_generation = Self::track_segment_readers_in_inventory(
             &segment_readers,
             searcher_generation_counter,
@@ -232,6 +319,7 @@ impl InnerIndexReader {
         let searcher = Arc::new(SearcherInner::new(
             schema,
             index.clone(),
+            opstamp,
             segment_readers,
             searcher_generation,
             doc_store_cache_num_blocks,
@@ -251,6 +339,9 @@ impl InnerIndexReader {
         )?;
 
         self.searcher.store(searcher);
+        let (lock, condvar) = &self.reload_condvar;
+        let _guard = lock.lock().map_err(|_| TantivyError::Poisoned)?;
+        condvar.notify_all();
 
         Ok(())
     }
@@ -258,6 +349,40 @@ impl InnerIndexReader {
     fn searcher(&self) -> Searcher {
         self.searcher.load().clone().into()
     }
+
+    fn wait_for(
+        &self,
+        opstamp: Opstamp,
+        timeout: Duration,
+        reload_if_stale: bool,
+    ) -> crate::Result<Option<Searcher>> {
+        let deadline = Instant::now() + timeout;
+        if reload_if_stale && self.searcher().opstamp() < opstamp {
+            self.reload()?;
+        }
+        let (lock, condvar) = &self.reload_condvar;
+        let mut guard = lock.lock().map_err(|_| TantivyError::Poisoned)?;
+        loop {
+            // Searchers are published before the condvar is notified, so that checking the
+            // searcher while holding the lock cannot miss a reload.
+            let searcher = self.searcher();
+            if searcher.opstamp() >= opstamp {
+                return Ok(Some(searcher));
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(None);
+            }
+            guard = condvar
+                .wait_timeout(guard, deadline - now)
+                .map_err(|_| TantivyError::Poisoned)?
+                .0;
+        }
+    }
+
+    fn add_warmer(&self, warmer: Weak<dyn Warmer>) -> crate::Result<()> {
+        self.warming_state.add_warmer(warmer, || self.searcher())
+    }
 }
 
 /// `IndexReader` is your entry point to read and search the index.
@@ -265,11 +390,15 @@ impl InnerIndexReader {
 /// It controls when a new version of the index should be loaded and lends
 /// you instances of `Searcher` for the last loaded version.
 ///
-/// `IndexReader` just wraps an `Arc`.
+/// `IndexReader` just wraps an `Arc`. It is `Send` and `Sync`, and is meant to be shared by
+/// all of the threads searching the index.
 #[derive(Clone)]
 pub struct IndexReader {
     inner: Arc<InnerIndexReader>,
-    _watch_handle_opt: Option<WatchHandle>,
+    // `None` if the reload policy is `Manual`.
+    watch_handle_opt: Option<WatchHandle>,
+    // `None` if no admission policy was set.
+    admission_controller_opt: Option<Arc<AdmissionController>>,
 }
 
 impl IndexReader {
@@ -301,4 +430,152 @@ impl IndexReader {
     pub fn searcher(&self) -> Searcher {
         self.inner.searcher()
     }
+
+    /// Waits until the [`AdmissionPolicy`] of the reader admits a new search, and returns a
+    /// permit holding the current searcher.
+    ///
+    /// The search counts as running until the permit is dropped. Searches run with
+    /// [`IndexReader::searcher()`] are not accounted for.
+    ///
+    /// Returns [`TantivyError::Overloaded`] if the search is rejected, in which case it may be
+    /// retried later. Without an admission policy, the search is always admitted right away.
+    pub fn admit(&self) -> crate::Result<SearchPermit> {
+        self.admit_with_memory(0)
+    }
+
+    /// Same as [`IndexReader::admit()`], for a search expected to use up to `memory` bytes,
+    /// accounted for in the [memory budget](AdmissionPolicy::memory_budget) of the policy.
+    pub fn admit_with_memory(&self, memory: u64) -> crate::Result<SearchPermit> {
+        let admission = self
+            .admission_controller_opt
+            .as_ref()
+            .map(|admission_controller| admission_controller.admit(memory))
+            .transpose()?;
+        Ok(SearchPermit::new(self.searcher(), admission))
+    }
+
+    /// Returns a handle pinning the current searcher.
+    ///
+    /// Pinning the current searcher again returns a handle with the same id. See
+    /// [`SearcherHandle`].
+    pub fn pin_searcher(&self) -> SearcherHandle {
+        self.inner.pinned_searchers.pin(self.searcher())
+    }
+
+    /// Returns the searcher pinned by the handle with the given id, if one of the clones of the
+    /// handle is still alive.
+    pub fn pinned_searcher(&self, id: u64) -> Option<SearcherHandle> {
+        self.inner.pinned_searchers.get(id)
+    }
+
+    /// Waits until a searcher including the commit with the given opstamp is available, and
+    /// returns it.
+    ///
+    /// `opstamp` is the one returned by [`IndexWriter::commit()`](crate::IndexWriter::commit), so
+    /// that an application can read its own writes: searching the returned searcher is
+    /// guaranteed to see the changes of the commit, or of a later one.
+    ///
+    /// Returns `None` if no such searcher was available before `timeout`.
+    ///
+    /// With the [`ReloadPolicy::Manual`] reload policy, the reader is reloaded once if its
+    /// searcher is stale. It then waits for [`IndexReader::reload()`] to be called.
+    pub fn wait_for(&self, opstamp: Opstamp, timeout: Duration) -> crate::Result<Option<Searcher>> {
+        self.inner
+            .wait_for(opstamp, timeout, self.watch_handle_opt.is_none())
+    }
+
+    /// Registers a [`Warmer`], in addition to the ones set with
+    /// [`IndexReaderBuilder::warmers()`].
+    ///
+    /// The warmer is invoked right away with the current searcher, and then upon every reload.
+    /// A searcher being published concurrently to this call may not be warmed by it.
+    pub fn add_warmer(&self, warmer: Weak<dyn Warmer>) -> crate::Result<()> {
+        self.inner.add_warmer(warmer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{IndexReader, IndexReaderBuilder};
+    use crate::collector::Count;
+    use crate::query::{AllQuery, Weight};
+    use crate::schema::{Schema, STRING};
+    use crate::{Index, IndexWriter, ReloadPolicy, Searcher, SegmentReader, TantivyError};
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Index>();
+        assert_send_sync::<IndexReaderBuilder>();
+        assert_send_sync::<IndexReader>();
+        assert_send_sync::<Searcher>();
+        assert_send_sync::<SegmentReader>();
+        assert_send_sync::<Box<dyn Weight>>();
+    }
+
+    #[test]
+    fn test_single_thread_reader() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index.reader_builder().single_thread().try_into()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.commit()?;
+        assert_eq!(reader.searcher().search(&AllQuery, &Count)?, 0);
+        reader.reload()?;
+        assert_eq!(reader.searcher().search(&AllQuery, &Count)?, 1);
+
+        let reader_res = index
+            .reader_builder()
+            .single_thread()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into();
+        assert!(matches!(reader_res, Err(TantivyError::InvalidArgument(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_wait_for_manual_reload() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        let commit_opstamp = index_writer.commit()?;
+        assert!(reader.searcher().opstamp() < commit_opstamp);
+        let searcher = reader.wait_for(commit_opstamp, Duration::ZERO)?.unwrap();
+        assert_eq!(searcher.opstamp(), commit_opstamp);
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 1);
+        assert!(reader
+            .wait_for(commit_opstamp + 1, Duration::from_millis(10))?
+            .is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wait_for_reload_on_commit() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommitWithDelay)
+            .try_into()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        let commit_opstamp = index_writer.commit()?;
+        let searcher = reader
+            .wait_for(commit_opstamp, Duration::from_secs(10))?
+            .unwrap();
+        assert!(searcher.opstamp() >= commit_opstamp);
+        assert_eq!(searcher.search(&AllQuery, &Count)?, 1);
+        Ok(())
+    }
 }
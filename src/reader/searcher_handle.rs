@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::Searcher;
+
+/// `SearcherHandle` pins a [`Searcher`] in the [`IndexReader`](crate::IndexReader) which created
+/// it.
+///
+/// It makes it possible to run several passes against the exact same set of segments, even if
+/// the reader is reloaded in between: e.g. an aggregation, and then the retrieval of the pages of
+/// the matching documents, without mixing documents of different generations.
+///
+/// Cloning a handle is cheap. The searcher stays pinned as long as one of the clones is alive,
+/// and can meanwhile be retrieved from its [`id`](SearcherHandle::id) with
+/// [`IndexReader::pinned_searcher()`](crate::IndexReader::pinned_searcher), e.g. when the id is
+/// carried by a pagination cursor.
+///
+/// ```rust
+/// use tantivy::collector::Count;
+/// use tantivy::query::AllQuery;
+/// use tantivy::schema::{Schema, STRING};
+/// use tantivy::{doc, Index, IndexWriter, ReloadPolicy};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let id = schema_builder.add_text_field("id", STRING);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(id => "a"))?;
+/// index_writer.commit()?;
+/// let reader = index
+///     .reader_builder()
+///     .reload_policy(ReloadPolicy::Manual)
+///     .try_into()?;
+///
+/// let handle = reader.pin_searcher();
+/// assert_eq!(handle.search(&AllQuery, &Count)?, 1);
+///
+/// index_writer.add_document(doc!(id => "b"))?;
+/// index_writer.commit()?;
+/// reader.reload()?;
+///
+/// let pinned = reader.pinned_searcher(handle.id()).unwrap();
+/// assert_eq!(pinned.search(&AllQuery, &Count)?, 1);
+/// assert_eq!(reader.searcher().search(&AllQuery, &Count)?, 2);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SearcherHandle(Arc<Searcher>);
+
+impl SearcherHandle {
+    /// Returns the id of the handle, which is the generation id of its searcher.
+    pub fn id(&self) -> u64 {
+        self.0.generation().generation_id()
+    }
+
+    /// Returns the pinned searcher.
+    pub fn searcher(&self) -> &Searcher {
+        &self.0
+    }
+}
+
+impl Deref for SearcherHandle {
+    type Target = Searcher;
+
+    fn deref(&self) -> &Searcher {
+        &self.0
+    }
+}
+
+/// Searchers pinned by a reader, by generation id.
+#[derive(Default)]
+pub(crate) struct PinnedSearchers {
+    searchers: Mutex<HashMap<u64, Weak<Searcher>>>,
+}
+
+impl PinnedSearchers {
+    pub fn pin(&self, searcher: Searcher) -> SearcherHandle {
+        let mut searchers = self.searchers.lock().unwrap();
+        let generation_id = searcher.generation().generation_id();
+        if let Some(pinned_searcher) = searchers.get(&generation_id).and_then(Weak::upgrade) {
+            return SearcherHandle(pinned_searcher);
+        }
+        // Searchers whose handles were all dropped are no longer pinned.
+        searchers.retain(|_, pinned_searcher| pinned_searcher.strong_count() > 0);
+        let pinned_searcher = Arc::new(searcher);
+        searchers.insert(generation_id, Arc::downgrade(&pinned_searcher));
+        SearcherHandle(pinned_searcher)
+    }
+
+    pub fn get(&self, id: u64) -> Option<SearcherHandle> {
+        let searchers = self.searchers.lock().unwrap();
+        let pinned_searcher = searchers.get(&id)?.upgrade()?;
+        Some(SearcherHandle(pinned_searcher))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::collector::Count;
+    use crate::query::AllQuery;
+    use crate::schema::{Schema, STRING};
+    use crate::{Index, IndexWriter, ReloadPolicy};
+
+    #[test]
+    fn test_pinned_searcher() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_text_field("id", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(id => "a"))?;
+        index_writer.commit()?;
+        reader.reload()?;
+
+        let handle = reader.pin_searcher();
+        assert_eq!(reader.pin_searcher().id(), handle.id());
+        index_writer.add_document(doc!(id => "b"))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        let new_handle = reader.pin_searcher();
+        assert_ne!(new_handle.id(), handle.id());
+
+        let pinned = reader.pinned_searcher(handle.id()).unwrap();
+        assert_eq!(
+            pinned.segment_readers().len(),
+            handle.segment_readers().len()
+        );
+        assert_eq!(pinned.search(&AllQuery, &Count)?, 1);
+        assert_eq!(new_handle.search(&AllQuery, &Count)?, 2);
+
+        let handle_id = handle.id();
+        drop(handle);
+        drop(pinned);
+        assert!(reader.pinned_searcher(handle_id).is_none());
+        assert!(reader.pinned_searcher(new_handle.id()).is_some());
+        Ok(())
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use super::Warmer;
+use crate::core::searcher::SearcherGeneration;
+use crate::index::SegmentId;
+use crate::{Searcher, SegmentReader};
+
+type BuildFn<T> = dyn Fn(&SegmentReader) -> crate::Result<T> + Send + Sync;
+
+/// `SegmentCache` holds a value per segment, e.g. a join table or a filter built from the
+/// segment.
+///
+/// It is a [`Warmer`]: once registered with an [`IndexReader`](crate::IndexReader), the values of
+/// the newly visible segments are built before the searchers including them are published, and
+/// the values of the segments which are no longer part of any live searcher, e.g. because they
+/// were merged away, are evicted.
+///
+/// Values are keyed by [`SegmentId`]: they are not rebuilt when deletes are applied to a segment.
+///
+/// ```rust
+/// use std::sync::{Arc, Weak};
+///
+/// use tantivy::schema::{Schema, FAST};
+/// use tantivy::{doc, Index, IndexWriter, SegmentCache, Warmer};
+///
+/// # fn main() -> tantivy::Result<()> {
+/// let mut schema_builder = Schema::builder();
+/// let price = schema_builder.add_u64_field("price", FAST);
+/// let index = Index::create_in_ram(schema_builder.build());
+/// let mut index_writer: IndexWriter = index.writer(15_000_000)?;
+/// index_writer.add_document(doc!(price => 3u64))?;
+/// index_writer.add_document(doc!(price => 5u64))?;
+/// index_writer.commit()?;
+///
+/// // The maximum price of each segment.
+/// let max_prices = Arc::new(SegmentCache::new(|segment_reader| {
+///     Ok(segment_reader.fast_fields().u64("price")?.max_value())
+/// }));
+/// let reader = index
+///     .reader_builder()
+///     .warmers(vec![Arc::downgrade(&max_prices) as Weak<dyn Warmer>])
+///     .try_into()?;
+/// let searcher = reader.searcher();
+/// let max_price = max_prices.get(searcher.segment_reader(0)).unwrap();
+/// assert_eq!(*max_price, 5);
+/// # Ok(())
+/// # }
+/// ```
+pub struct SegmentCache<T> {
+    build: Box<BuildFn<T>>,
+    values: RwLock<HashMap<SegmentId, Arc<T>>>,
+}
+
+impl<T: Send + Sync> fmt::Debug for SegmentCache<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SegmentCache")
+            .field("num_segments", &self.len())
+            .finish()
+    }
+}
+
+impl<T: Send + Sync> SegmentCache<T> {
+    /// Creates a cache, building the value of a segment with the given function.
+    pub fn new(
+        build: impl Fn(&SegmentReader) -> crate::Result<T> + Send + Sync + 'static,
+    ) -> SegmentCache<T> {
+        SegmentCache {
+            build: Box::new(build),
+            values: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the value of a segment, or `None` if it has not been built yet.
+    ///
+    /// The value of every segment of a [`Searcher`] is available if the cache is registered as a
+    /// warmer of the reader which created it.
+    pub fn get(&self, segment_reader: &SegmentReader) -> Option<Arc<T>> {
+        self.values
+            .read()
+            .unwrap()
+            .get(&segment_reader.segment_id())
+            .cloned()
+    }
+
+    /// Returns the value of a segment, building it if needed.
+    pub fn get_or_build(&self, segment_reader: &SegmentReader) -> crate::Result<Arc<T>> {
+        if let Some(value) = self.get(segment_reader) {
+            return Ok(value);
+        }
+        let value = Arc::new((self.build)(segment_reader)?);
+        Ok(self
+            .values
+            .write()
+            .unwrap()
+            .entry(segment_reader.segment_id())
+            .or_insert(value)
+            .clone())
+    }
+
+    /// Returns the number of segments with a value.
+    pub fn len(&self) -> usize {
+        self.values.read().unwrap().len()
+    }
+
+    /// Returns true if no segment has a value.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Send + Sync> Warmer for SegmentCache<T> {
+    fn warm(&self, searcher: &Searcher) -> crate::Result<()> {
+        for segment_reader in searcher.segment_readers() {
+            self.get_or_build(segment_reader)?;
+        }
+        Ok(())
+    }
+
+    fn garbage_collect(&self, live_generations: &[&SearcherGeneration]) {
+        let live_segment_ids: HashSet<SegmentId> = live_generations
+            .iter()
+            .flat_map(|searcher_generation| searcher_generation.segments().keys().copied())
+            .collect();
+        self.values
+            .write()
+            .unwrap()
+            .retain(|segment_id, _| live_segment_ids.contains(segment_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Weak};
+
+    use super::SegmentCache;
+    use crate::indexer::NoMergePolicy;
+    use crate::schema::{Schema, FAST};
+    use crate::{Index, IndexWriter, ReloadPolicy, Warmer};
+
+    #[test]
+    fn test_segment_cache() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let val = schema_builder.add_u64_field("val", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        index_writer.add_document(doc!(val => 1u64))?;
+        index_writer.commit()?;
+        index_writer.add_document(doc!(val => 2u64))?;
+        index_writer.commit()?;
+
+        let num_builds = Arc::new(AtomicUsize::new(0));
+        let num_builds_clone = num_builds.clone();
+        let cache = Arc::new(SegmentCache::new(move |segment_reader| {
+            num_builds_clone.fetch_add(1, Ordering::SeqCst);
+            Ok(segment_reader.fast_fields().u64("val")?.max_value())
+        }));
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .warmers(vec![Arc::downgrade(&cache) as Weak<dyn Warmer>])
+            .try_into()?;
+        let searcher = reader.searcher();
+        assert_eq!(cache.len(), 2);
+        let mut values: Vec<u64> = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| *cache.get(segment_reader).unwrap())
+            .collect();
+        values.sort();
+        assert_eq!(values, vec![1, 2]);
+
+        // Only the new segment is built upon reload.
+        index_writer.add_document(doc!(val => 3u64))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        assert_eq!(num_builds.load(Ordering::SeqCst), 3);
+        assert_eq!(cache.len(), 3);
+
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        index_writer.wait_merging_threads()?;
+        reader.reload()?;
+        let merged_searcher = reader.searcher();
+        assert_eq!(merged_searcher.segment_readers().len(), 1);
+        assert_eq!(*cache.get(merged_searcher.segment_reader(0)).unwrap(), 3);
+
+        // The merged segments are still referenced by `searcher`.
+        let live_generations = [searcher.generation(), merged_searcher.generation()];
+        cache.garbage_collect(&live_generations);
+        assert_eq!(cache.len(), 3);
+        cache.garbage_collect(&[merged_searcher.generation()]);
+        assert_eq!(cache.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_segment_cache_added_to_reader() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let val = schema_builder.add_u64_field("val", FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(val => 1u64))?;
+        index_writer.commit()?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+
+        let cache = Arc::new(SegmentCache::new(|segment_reader| {
+            Ok(segment_reader.num_docs())
+        }));
+        reader.add_warmer(Arc::downgrade(&cache) as Weak<dyn Warmer>)?;
+        assert_eq!(*cache.get(reader.searcher().segment_reader(0)).unwrap(), 1);
+
+        index_writer.add_document(doc!(val => 2u64))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert!(searcher
+            .segment_readers()
+            .iter()
+            .all(|segment_reader| cache.get(segment_reader).is_some()));
+
+        // Dropped warmers are not invoked anymore.
+        drop(cache);
+        index_writer.add_document(doc!(val => 3u64))?;
+        index_writer.commit()?;
+        reader.reload()?;
+        Ok(())
+    }
+}
@@ -24,13 +24,22 @@ pub trait Warmer: Sync + Send {
 pub(crate) struct WarmingState(Arc<Mutex<WarmingStateInner>>);
 
 impl WarmingState {
+    /// If `executor` is set, it runs the warmers. Otherwise, a thread pool of
+    /// `num_warming_threads` threads is created for each warming.
+    ///
+    /// If `background_gc` is false, [`Warmer::garbage_collect`] is called upon warming rather than
+    /// in a background thread.
     pub fn new(
         num_warming_threads: usize,
+        executor: Option<Executor>,
+        background_gc: bool,
         warmers: Vec<Weak<dyn Warmer>>,
         searcher_generation_inventory: Inventory<SearcherGeneration>,
     ) -> crate::Result<Self> {
         Ok(Self(Arc::new(Mutex::new(WarmingStateInner {
             num_warming_threads,
+            executor,
+            background_gc,
             warmers,
             gc_thread: None,
             warmed_generation_ids: Default::default(),
@@ -42,7 +51,7 @@ impl WarmingState {
     /// warmers.
     ///
     /// A background GC thread for [`Warmer::garbage_collect`] calls is uniquely created if there
-    /// are active warmers.
+    /// are active warmers, unless the background GC is disabled.
     pub fn warm_new_searcher_generation(&self, searcher: &Searcher) -> crate::Result<()> {
         self.0
             .lock()
@@ -50,6 +59,18 @@ impl WarmingState {
             .warm_new_searcher_generation(searcher, &self.0)
     }
 
+    /// Start tracking a new warmer, and [`Warmer::warm`] it with the current [`Searcher`].
+    pub fn add_warmer(
+        &self,
+        warmer: Weak<dyn Warmer>,
+        current_searcher: impl FnOnce() -> Searcher,
+    ) -> crate::Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .add_warmer(warmer, current_searcher(), &self.0)
+    }
+
     #[cfg(test)]
     fn gc_maybe(&self) -> bool {
         self.0.lock().unwrap().gc_maybe()
@@ -58,6 +79,8 @@ impl WarmingState {
 
 struct WarmingStateInner {
     num_warming_threads: usize,
+    executor: Option<Executor>,
+    background_gc: bool,
     warmers: Vec<Weak<dyn Warmer>>,
     gc_thread: Option<JoinHandle<()>>,
     // Contains all generations that have been warmed up.
@@ -82,14 +105,33 @@ impl WarmingStateInner {
         if warmers.is_empty() {
             return Ok(());
         }
-        self.start_gc_thread_maybe(this)?;
+        self.gc(this)?;
         self.warmed_generation_ids
             .insert(searcher.generation().generation_id());
-        warming_executor(self.num_warming_threads.min(warmers.len()))?
-            .map(|warmer| warmer.warm(searcher), warmers.into_iter())?;
+        match &self.executor {
+            Some(executor) => executor.map(|warmer| warmer.warm(searcher), warmers.into_iter())?,
+            None => warming_executor(self.num_warming_threads.min(warmers.len()))?
+                .map(|warmer| warmer.warm(searcher), warmers.into_iter())?,
+        };
         Ok(())
     }
 
+    fn add_warmer(
+        &mut self,
+        warmer: Weak<dyn Warmer>,
+        searcher: Searcher,
+        this: &Arc<Mutex<Self>>,
+    ) -> crate::Result<()> {
+        let Some(strong_warmer) = warmer.upgrade() else {
+            return Ok(());
+        };
+        self.warmers.push(warmer);
+        self.gc(this)?;
+        self.warmed_generation_ids
+            .insert(searcher.generation().generation_id());
+        strong_warmer.warm(&searcher)
+    }
+
     /// Attempt to upgrade the weak `Warmer` references, pruning those which cannot be upgraded.
     /// Return the strong references.
     fn pruned_warmers(&mut self) -> Vec<Arc<dyn Warmer>> {
@@ -128,6 +170,17 @@ impl WarmingStateInner {
         true
     }
 
+    /// Garbage collects the warmers right away if the background GC is disabled, and otherwise
+    /// makes sure the background GC thread is running.
+    fn gc(&mut self, this: &Arc<Mutex<Self>>) -> crate::Result<()> {
+        if self.background_gc {
+            self.start_gc_thread_maybe(this)?;
+        } else {
+            self.gc_maybe();
+        }
+        Ok(())
+    }
+
     /// Start GC thread if one has not already been started.
     fn start_gc_thread_maybe(&mut self, this: &Arc<Mutex<Self>>) -> crate::Result<bool> {
         if self.gc_thread.is_some() {
@@ -342,4 +395,54 @@ mod tests {
     fn warming_four_threads() -> crate::Result<()> {
         test_warming(4)
     }
+
+    #[test]
+    fn warming_without_background_gc() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_u64_field("pk", INDEXED);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut writer = index.writer_for_tests()?;
+        writer.add_document(doc!(field => 1u64))?;
+        writer.commit()?;
+
+        let warmer = Arc::new(TestWarmer::default());
+        let reader = index
+            .reader_builder()
+            .single_thread()
+            .num_warming_threads(4)
+            .warmers(vec![Arc::downgrade(&warmer) as Weak<dyn Warmer>])
+            .try_into()?;
+        let first_searcher = reader.searcher();
+        warmer.verify(1, 0, segment_ids(&first_searcher));
+        assert!(reader
+            .inner
+            .warming_state
+            .0
+            .lock()
+            .unwrap()
+            .gc_thread
+            .is_none());
+
+        writer.add_document(doc!(field => 2u64))?;
+        writer.commit()?;
+        reader.reload()?;
+        drop(first_searcher);
+        warmer.verify(2, 0, segment_ids(&reader.searcher()));
+
+        // The generation of the first searcher is collected upon the next reload.
+        writer.add_document(doc!(field => 3u64))?;
+        writer.commit()?;
+        reader.reload()?;
+        assert_eq!(warmer.warm_calls(), 3);
+        assert_eq!(warmer.gc_calls(), 1);
+        assert!(reader
+            .inner
+            .warming_state
+            .0
+            .lock()
+            .unwrap()
+            .gc_thread
+            .is_none());
+        Ok(())
+    }
 }
@@ -17,6 +17,7 @@ use std::sync::Arc;
 
 use columnar::MonotonicallyMappableToU128;
 use common::{u64_to_f64, BinarySerializable, DateTime, VInt};
+use uuid::Uuid;
 
 use super::se::BinaryObjectSerializer;
 use super::{OwnedValue, Value};
@@ -123,6 +124,9 @@ pub trait ValueDeserializer<'de> {
     /// Attempts to deserialize an IP address value from the deserializer.
     fn deserialize_ip_address(self) -> Result<Ipv6Addr, DeserializeError>;
 
+    /// Attempts to deserialize a uuid value from the deserializer.
+    fn deserialize_uuid(self) -> Result<Uuid, DeserializeError>;
+
     /// Attempts to deserialize a bool value from the deserializer.
     fn deserialize_bool(self) -> Result<bool, DeserializeError>;
 
@@ -155,6 +159,8 @@ pub enum ValueType {
     Bytes,
     /// A IP address value.
     IpAddr,
+    /// A uuid value.
+    Uuid,
     /// A boolean value.
     Bool,
     /// A pre-tokenized string value.
@@ -225,6 +231,12 @@ pub trait ValueVisitor {
         Err(DeserializeError::UnsupportedType(ValueType::IpAddr))
     }
 
+    #[inline]
+    /// Called when the deserializer visits a uuid value.
+    fn visit_uuid(&self, _val: Uuid) -> Result<Self::Value, DeserializeError> {
+        Err(DeserializeError::UnsupportedType(ValueType::Uuid))
+    }
+
     #[inline]
     /// Called when the deserializer visits a facet value.
     fn visit_facet(&self, _val: Facet) -> Result<Self::Value, DeserializeError> {
@@ -285,6 +297,12 @@ pub trait ObjectAccess<'de> {
     fn next_entry<V: ValueDeserialize>(&mut self) -> Result<Option<(String, V)>, DeserializeError>;
 }
 
+/// Upper bound of the size hints of the binary deserializers.
+///
+/// Lengths are read from the doc store, and are used to pre-allocate entries: a corrupted
+/// length must not translate into a huge allocation.
+const MAX_SIZE_HINT: usize = 1 << 10;
+
 /// The document deserializer used to read the tantivy documents serialized with
 /// `BinarySerializable`.
 ///
@@ -296,6 +314,7 @@ pub struct BinaryDocumentDeserializer<'de, R> {
     length: usize,
     position: usize,
     doc_store_version: DocStoreVersion,
+    fields: Option<&'de [Field]>,
     reader: &'de mut R,
 }
 
@@ -313,15 +332,46 @@ where R: Read
             length: length.val() as usize,
             position: 0,
             doc_store_version,
+            fields: None,
             reader,
         })
     }
 
+    /// Restricts the deserializer to the values of the given fields.
+    ///
+    /// The values of the other fields are skipped without being decoded,
+    /// unless the document was written with a doc store version older than `V3`,
+    /// in which values are not prefixed with their length.
+    pub(crate) fn with_fields(mut self, fields: &'de [Field]) -> Self {
+        self.fields = Some(fields);
+        self
+    }
+
     /// Returns true if the deserializer has deserialized all the entries
     /// within the document.
     fn is_complete(&self) -> bool {
         self.position >= self.length
     }
+
+    fn skip_value(&mut self, value_num_bytes: Option<u64>) -> Result<(), DeserializeError> {
+        if let Some(value_num_bytes) = value_num_bytes {
+            let num_bytes_skipped = io::copy(
+                &mut self.reader.by_ref().take(value_num_bytes),
+                &mut io::sink(),
+            )?;
+            if num_bytes_skipped != value_num_bytes {
+                return Err(DeserializeError::from(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Reached the end of the document while skipping a value",
+                )));
+            }
+        } else {
+            let deserializer =
+                BinaryValueDeserializer::from_reader(self.reader, self.doc_store_version)?;
+            OwnedValue::deserialize(deserializer)?;
+        }
+        Ok(())
+    }
 }
 
 impl<'de, R> DocumentDeserializer<'de> for BinaryDocumentDeserializer<'de, R>
@@ -329,22 +379,36 @@ where R: Read
 {
     #[inline]
     fn size_hint(&self) -> usize {
-        self.length
+        self.length.min(MAX_SIZE_HINT)
     }
 
     fn next_field<V: ValueDeserialize>(&mut self) -> Result<Option<(Field, V)>, DeserializeError> {
-        if self.is_complete() {
-            return Ok(None);
-        }
+        loop {
+            if self.is_complete() {
+                return Ok(None);
+            }
 
-        let field = Field::deserialize(self.reader).map_err(DeserializeError::from)?;
-        let deserializer =
-            BinaryValueDeserializer::from_reader(self.reader, self.doc_store_version)?;
-        let value = V::deserialize(deserializer)?;
+            let field = Field::deserialize(self.reader).map_err(DeserializeError::from)?;
+            let value_num_bytes = if self.doc_store_version >= DocStoreVersion::V3 {
+                Some(VInt::deserialize(self.reader)?.val())
+            } else {
+                None
+            };
+            self.position += 1;
+
+            if let Some(fields) = self.fields {
+                if !fields.contains(&field) {
+                    self.skip_value(value_num_bytes)?;
+                    continue;
+                }
+            }
 
-        self.position += 1;
+            let deserializer =
+                BinaryValueDeserializer::from_reader(self.reader, self.doc_store_version)?;
+            let value = V::deserialize(deserializer)?;
 
-        Ok(Some((field, value)))
+            return Ok(Some((field, value)));
+        }
     }
 }
 
@@ -391,6 +455,7 @@ where R: Read
                 }
             }
             type_codes::IP_CODE => ValueType::IpAddr,
+            type_codes::UUID_CODE => ValueType::Uuid,
             type_codes::NULL_CODE => ValueType::Null,
             type_codes::ARRAY_CODE => ValueType::Array,
             type_codes::OBJECT_CODE => ValueType::Object,
@@ -460,7 +525,7 @@ where R: Read
                 let timestamp_micros = <i64 as BinarySerializable>::deserialize(self.reader)?;
                 Ok(DateTime::from_timestamp_micros(timestamp_micros))
             }
-            DocStoreVersion::V2 => {
+            DocStoreVersion::V2 | DocStoreVersion::V3 => {
                 let timestamp_nanos = <i64 as BinarySerializable>::deserialize(self.reader)?;
                 Ok(DateTime::from_timestamp_nanos(timestamp_nanos))
             }
@@ -484,6 +549,13 @@ where R: Read
             .map_err(DeserializeError::from)
     }
 
+    fn deserialize_uuid(self) -> Result<Uuid, DeserializeError> {
+        self.validate_type(ValueType::Uuid)?;
+        <u128 as BinarySerializable>::deserialize(self.reader)
+            .map(Uuid::from_u128)
+            .map_err(DeserializeError::from)
+    }
+
     fn deserialize_bool(self) -> Result<bool, DeserializeError> {
         self.validate_type(ValueType::Bool)?;
         <bool as BinarySerializable>::deserialize(self.reader).map_err(DeserializeError::from)
@@ -531,6 +603,10 @@ where R: Read
                 let val = self.deserialize_ip_address()?;
                 visitor.visit_ip_address(val)
             }
+            ValueType::Uuid => {
+                let val = self.deserialize_uuid()?;
+                visitor.visit_uuid(val)
+            }
             ValueType::Bool => {
                 let val = self.deserialize_bool()?;
                 visitor.visit_bool(val)
@@ -613,7 +689,7 @@ where R: Read
 {
     #[inline]
     fn size_hint(&self) -> usize {
-        self.length
+        self.length.min(MAX_SIZE_HINT)
     }
 
     fn next_element<V: ValueDeserialize>(&mut self) -> Result<Option<V>, DeserializeError> {
@@ -735,6 +811,14 @@ impl ValueDeserialize for Ipv6Addr {
     }
 }
 
+impl ValueDeserialize for Uuid {
+    #[inline]
+    fn deserialize<'de, D>(deserializer: D) -> Result<Self, DeserializeError>
+    where D: ValueDeserializer<'de> {
+        deserializer.deserialize_uuid()
+    }
+}
+
 impl ValueDeserialize for Facet {
     #[inline]
     fn deserialize<'de, D>(deserializer: D) -> Result<Self, DeserializeError>
@@ -1155,4 +1239,59 @@ mod tests {
             OwnedValue::Object(expected_object.into_iter().collect())
         );
     }
+
+    #[test]
+    fn test_document_deserialize_fields() {
+        use crate::schema::document::BinaryDocumentSerializer;
+        use crate::schema::{Schema, STORED};
+
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", STORED);
+        let body = schema_builder.add_text_field("body", STORED);
+        let tags = schema_builder.add_json_field("tags", STORED);
+        let schema = schema_builder.build();
+        let mut tags_object = BTreeMap::new();
+        tags_object.insert("color".to_string(), OwnedValue::Str("red".to_string()));
+        let mut document = BTreeMap::new();
+        document.insert(id, OwnedValue::U64(7));
+        document.insert(body, OwnedValue::Str("a long body".to_string()));
+        document.insert(tags, OwnedValue::Object(tags_object.into_iter().collect()));
+
+        let mut doc_bytes = Vec::new();
+        BinaryDocumentSerializer::new(&mut doc_bytes, &schema)
+            .serialize_doc(&document)
+            .unwrap();
+        let deserialize_fields = |doc_bytes: &[u8], version, fields: &[Field]| {
+            let mut cursor = Cursor::new(doc_bytes);
+            let deserializer = BinaryDocumentDeserializer::from_reader(&mut cursor, version)
+                .unwrap()
+                .with_fields(fields);
+            BTreeMap::<Field, OwnedValue>::deserialize(deserializer).unwrap()
+        };
+        let expected: BTreeMap<Field, OwnedValue> =
+            [(id, OwnedValue::U64(7))].into_iter().collect();
+        assert_eq!(
+            deserialize_fields(&doc_bytes, DOC_STORE_VERSION, &[id]),
+            expected
+        );
+        assert_eq!(
+            deserialize_fields(&doc_bytes, DOC_STORE_VERSION, &[id, body, tags]),
+            document
+        );
+
+        // Values written by older versions of the doc store are not prefixed with their
+        // length, and need to be decoded to be skipped.
+        let mut legacy_doc_bytes = Vec::new();
+        VInt(document.len() as u64)
+            .serialize(&mut legacy_doc_bytes)
+            .unwrap();
+        for (field, value) in &document {
+            field.serialize(&mut legacy_doc_bytes).unwrap();
+            legacy_doc_bytes.extend_from_slice(&serialize_owned_value(value.as_value()));
+        }
+        assert_eq!(
+            deserialize_fields(&legacy_doc_bytes, DocStoreVersion::V2, &[id]),
+            expected
+        );
+    }
 }
@@ -5,8 +5,10 @@ use std::net::Ipv6Addr;
 use columnar::MonotonicallyMappableToU128;
 use common::{read_u32_vint_no_advance, serialize_vint_u32, BinarySerializable, DateTime, VInt};
 use serde_json::Map;
+use uuid::Uuid;
 pub use CompactDoc as TantivyDocument;
 
+use super::json_stream::DuplicateKeys;
 use super::{ReferenceValue, ReferenceValueLeaf, Value};
 use crate::schema::document::{
     DeserializeError, Document, DocumentDeserialize, DocumentDeserializer,
@@ -93,6 +95,11 @@ impl CompactDoc {
         self.add_leaf_field_value(field, value);
     }
 
+    /// Add a uuid field.
+    pub fn add_uuid(&mut self, field: Field, value: Uuid) {
+        self.add_leaf_field_value(field, value);
+    }
+
     /// Add a i64 field
     pub fn add_i64(&mut self, field: Field, value: i64) {
         self.add_leaf_field_value(field, value);
@@ -156,6 +163,14 @@ impl CompactDoc {
         self.field_values.push(field_value);
     }
 
+    /// Removes the values of the given field from the document.
+    ///
+    /// The data of the removed values stays in `node_data`.
+    pub(crate) fn remove_field_values(&mut self, field: Field) {
+        self.field_values
+            .retain(|field_value| Field::from_field_id(field_value.field as u32) != field);
+    }
+
     /// field_values accessor
     pub fn field_values(&self) -> impl Iterator<Item = (Field, CompactDocValue<'_>)> {
         self.field_values.iter().map(|field_val| {
@@ -195,10 +210,24 @@ impl CompactDoc {
     }
 
     /// Build a document object from a json-object.
+    ///
+    /// If a key appears several times in the json-object, its last occurrence wins, as in a
+    /// `serde_json::Map`.
     pub fn parse_json(schema: &Schema, doc_json: &str) -> Result<Self, DocParsingError> {
-        let json_obj: Map<String, serde_json::Value> =
-            serde_json::from_str(doc_json).map_err(|_| DocParsingError::invalid_json(doc_json))?;
-        Self::from_json_object(schema, json_obj)
+        super::json_stream::parse_json_document(
+            schema,
+            doc_json.as_bytes(),
+            DuplicateKeys::LastWins,
+        )
+    }
+
+    /// Build a document object from the bytes of a json-object.
+    ///
+    /// The document is built while the JSON is parsed, without any intermediate
+    /// `serde_json::Value`. If a key appears several times in the json-object, the values of all
+    /// of its occurrences are added to the document.
+    pub fn parse_json_bytes(schema: &Schema, doc_json: &[u8]) -> Result<Self, DocParsingError> {
+        super::json_stream::parse_json_document(schema, doc_json, DuplicateKeys::KeepAll)
     }
 
     /// Build a document object from a json-object.
@@ -253,6 +282,7 @@ impl CompactDoc {
                 write_into(&mut self.node_data, date.into_timestamp_nanos())
             }
             ReferenceValueLeaf::IpAddr(num) => write_into(&mut self.node_data, num.to_u128()),
+            ReferenceValueLeaf::Uuid(uuid) => write_into(&mut self.node_data, uuid.as_u128()),
             ReferenceValueLeaf::PreTokStr(pre_tok) => write_into(&mut self.node_data, *pre_tok),
         };
         ValueAddr { type_id, val_addr }
@@ -382,6 +412,12 @@ impl DocumentDeserialize for CompactDoc {
         // TODO: Deserializing into OwnedValue is wasteful. The deserializer should be able to work
         // on slices and referenced data.
         while let Some((field, value)) = deserializer.next_field::<OwnedValue>()? {
+            if field.field_id() > u32::from(u16::MAX) {
+                return Err(DeserializeError::custom(format!(
+                    "Field id {} exceeds the maximum number of fields",
+                    field.field_id()
+                )));
+            }
             doc.add_field_value(field, &value);
         }
         Ok(doc)
@@ -458,6 +494,11 @@ impl<'a> CompactDocValue<'a> {
                 .read_from::<u128>(addr)
                 .map(|num| ReferenceValueLeaf::IpAddr(Ipv6Addr::from_u128(num)))
                 .map(Into::into),
+            ValueType::Uuid => self
+                .container
+                .read_from::<u128>(addr)
+                .map(|num| ReferenceValueLeaf::Uuid(Uuid::from_u128(num)))
+                .map(Into::into),
             ValueType::PreTokStr => self
                 .container
                 .read_from::<PreTokenizedString>(addr)
@@ -542,6 +583,8 @@ pub enum ValueType {
     Object = 11,
     /// Pre-tokenized str type,
     Array = 12,
+    /// Uuid
+    Uuid = 13,
 }
 
 impl BinarySerializable for ValueType {
@@ -552,7 +595,7 @@ impl BinarySerializable for ValueType {
 
     fn deserialize<R: Read>(reader: &mut R) -> io::Result<Self> {
         let num = u8::deserialize(reader)?;
-        let type_id = if (0..=12).contains(&num) {
+        let type_id = if (0..=13).contains(&num) {
             unsafe { std::mem::transmute::<u8, ValueType>(num) }
         } else {
             return Err(io::Error::new(
@@ -584,6 +627,7 @@ impl<'a> From<&ReferenceValueLeaf<'a>> for ValueType {
             ReferenceValueLeaf::Bool(_) => ValueType::Bool,
             ReferenceValueLeaf::Date(_) => ValueType::Date,
             ReferenceValueLeaf::IpAddr(_) => ValueType::IpAddr,
+            ReferenceValueLeaf::Uuid(_) => ValueType::Uuid,
             ReferenceValueLeaf::PreTokStr(_) => ValueType::PreTokStr,
             ReferenceValueLeaf::Facet(_) => ValueType::Facet,
             ReferenceValueLeaf::Bytes(_) => ValueType::Bytes,
@@ -709,8 +753,13 @@ pub enum DocParsingError {
 
 impl DocParsingError {
     /// Builds a NotJson DocParsingError
-    fn invalid_json(invalid_json: &str) -> Self {
-        let sample = invalid_json.chars().take(20).collect();
+    pub(super) fn invalid_json(invalid_json: &[u8]) -> Self {
+        // 20 chars take at most 80 bytes.
+        let sample_bytes = &invalid_json[..invalid_json.len().min(80)];
+        let sample = String::from_utf8_lossy(sample_bytes)
+            .chars()
+            .take(20)
+            .collect();
         DocParsingError::InvalidJson(sample)
     }
 }
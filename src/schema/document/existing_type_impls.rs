@@ -12,6 +12,7 @@ use common::DateTime;
 use serde_json::Number;
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
+use uuid::Uuid;
 
 use super::facet::Facet;
 use super::ReferenceValueLeaf;
@@ -185,6 +186,14 @@ impl<'a> Value<'a> for &'a Ipv6Addr {
         ReferenceValue::Leaf(ReferenceValueLeaf::IpAddr(**self))
     }
 }
+impl<'a> Value<'a> for &'a Uuid {
+    type ArrayIter = Empty<&'a Uuid>;
+    type ObjectIter = Empty<(&'a str, &'a Uuid)>;
+    #[inline]
+    fn as_value(&self) -> ReferenceValue<'a, Self> {
+        ReferenceValue::Leaf(ReferenceValueLeaf::Uuid(**self))
+    }
+}
 impl<'a> Value<'a> for &'a PreTokenizedString {
     type ArrayIter = Empty<&'a PreTokenizedString>;
     type ObjectIter = Empty<(&'a str, &'a PreTokenizedString)>;
@@ -0,0 +1,491 @@
+//! Parsing of JSON documents straight into a [`TantivyDocument`].
+//!
+//! The JSON bytes are pulled through serde visitors, and the values of the fields are converted
+//! as they are read, without building an intermediate `serde_json::Value` for the document or
+//! for its JSON fields.
+//!
+//! The result is the same as parsing the document into a `serde_json::Map`, and then calling
+//! [`TantivyDocument::from_json_object()`]. Keys appearing several times in the top-level object
+//! can also keep the values of all of their occurrences (see [`DuplicateKeys`]).
+use std::fmt;
+
+use serde::de::value::{MapAccessDeserializer, SeqAccessDeserializer};
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use serde_json::{Number, Value as JsonValue};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+use super::existing_type_impls::can_be_rfc3339_date_time;
+use super::{DocParsingError, OwnedValue, TantivyDocument};
+use crate::schema::field_type::ValueParsingError;
+use crate::schema::{Field, FieldType, Schema};
+use crate::DateTime;
+
+/// How the values of a key appearing several times in the top-level object are handled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DuplicateKeys {
+    /// Only the values of the last occurrence of the key are kept, as in a `serde_json::Map`.
+    LastWins,
+    /// The values of all of the occurrences of the key are added to the document.
+    KeepAll,
+}
+
+pub(super) fn parse_json_document(
+    schema: &Schema,
+    doc_json: &[u8],
+    duplicate_keys: DuplicateKeys,
+) -> Result<TantivyDocument, DocParsingError> {
+    let mut document = TantivyDocument::default();
+    let mut value_errors = Vec::new();
+    let mut deserializer = serde_json::Deserializer::from_slice(doc_json);
+    deserializer
+        .deserialize_map(DocumentVisitor {
+            schema,
+            duplicate_keys,
+            document: &mut document,
+            value_errors: &mut value_errors,
+        })
+        .and_then(|()| deserializer.end())
+        .map_err(|_| DocParsingError::invalid_json(doc_json))?;
+    // Invalid JSON takes precedence over invalid values, hence the values are only checked once
+    // the whole document is parsed.
+    if let Some((_, value_error)) = value_errors.into_iter().next() {
+        return Err(value_error);
+    }
+    Ok(document)
+}
+
+/// Visits the top-level object of a document.
+struct DocumentVisitor<'a> {
+    schema: &'a Schema,
+    duplicate_keys: DuplicateKeys,
+    document: &'a mut TantivyDocument,
+    // The errors of the values which are kept in the document, along with their field.
+    value_errors: &'a mut Vec<(Field, DocParsingError)>,
+}
+
+impl<'de> Visitor<'de> for DocumentVisitor<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a json object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(field_opt) = map.next_key_seed(FieldSeed(self.schema))? {
+            // Once a value is invalid, the following values are not converted, unless a later
+            // occurrence of the key of the invalid value may replace it.
+            let skip_values =
+                self.duplicate_keys == DuplicateKeys::KeepAll && !self.value_errors.is_empty();
+            let Some(field) = field_opt.filter(|_| !skip_values) else {
+                map.next_value::<IgnoredAny>()?;
+                continue;
+            };
+            if self.duplicate_keys == DuplicateKeys::LastWins {
+                self.document.remove_field_values(field);
+                self.value_errors
+                    .retain(|(error_field, _)| *error_field != field);
+            }
+            let field_type = self.schema.get_field_entry(field).field_type();
+            let values_seed = FieldValuesSeed {
+                field,
+                field_type,
+                document: &mut *self.document,
+            };
+            if let Err(value_error) = map.next_value_seed(values_seed)? {
+                self.value_errors.push((
+                    field,
+                    DocParsingError::ValueError(
+                        self.schema.get_field_name(field).to_string(),
+                        value_error,
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Resolves a key of the top-level object to a field of the schema, if any.
+struct FieldSeed<'a>(&'a Schema);
+
+impl<'de> DeserializeSeed<'de> for FieldSeed<'_> {
+    type Value = Option<Field>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Option<Field>, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FieldSeed<'_> {
+    type Value = Option<Field>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a field name")
+    }
+
+    fn visit_str<E>(self, field_name: &str) -> Result<Option<Field>, E> {
+        Ok(self.0.get_field(field_name).ok())
+    }
+}
+
+/// Adds the value, or the array of values, of a field to the document.
+struct FieldValuesSeed<'a> {
+    field: Field,
+    field_type: &'a FieldType,
+    document: &'a mut TantivyDocument,
+}
+
+impl<'a> FieldValuesSeed<'a> {
+    fn add_value(
+        self,
+        value_res: Result<OwnedValue, ValueParsingError>,
+    ) -> Result<(), ValueParsingError> {
+        self.document.add_field_value(self.field, &value_res?);
+        Ok(())
+    }
+
+    fn value_visitor(&self) -> FieldValueVisitor<'a> {
+        FieldValueVisitor(self.field_type)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for FieldValuesSeed<'_> {
+    type Value = Result<(), ValueParsingError>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FieldValuesSeed<'_> {
+    type Value = Result<(), ValueParsingError>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a value or an array of values")
+    }
+
+    fn visit_bool<E>(self, val: bool) -> Result<Self::Value, E>
+    where E: serde::de::Error {
+        let value_res = self.value_visitor().visit_bool(val);
+        Ok(self.add_value(value_res?))
+    }
+
+    fn visit_i64<E>(self, val: i64) -> Result<Self::Value, E>
+    where E: serde::de::Error {
+        let value_res = self.value_visitor().visit_i64(val);
+        Ok(self.add_value(value_res?))
+    }
+
+    fn visit_u64<E>(self, val: u64) -> Result<Self::Value, E>
+    where E: serde::de::Error {
+        let value_res = self.value_visitor().visit_u64(val);
+        Ok(self.add_value(value_res?))
+    }
+
+    fn visit_f64<E>(self, val: f64) -> Result<Self::Value, E>
+    where E: serde::de::Error {
+        let value_res = self.value_visitor().visit_f64(val);
+        Ok(self.add_value(value_res?))
+    }
+
+    fn visit_str<E>(self, val: &str) -> Result<Self::Value, E>
+    where E: serde::de::Error {
+        let value_res = self.value_visitor().visit_str(val);
+        Ok(self.add_value(value_res?))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where E: serde::de::Error {
+        let value_res = self.value_visitor().visit_unit();
+        Ok(self.add_value(value_res?))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        let value_res = self.value_visitor().visit_map(map);
+        Ok(self.add_value(value_res?))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        while let Some(value_res) = seq.next_element_seed(self.value_visitor())? {
+            match value_res {
+                Ok(value) => self.document.add_field_value(self.field, &value),
+                Err(value_error) => {
+                    while seq.next_element::<IgnoredAny>()?.is_some() {}
+                    return Ok(Err(value_error));
+                }
+            }
+        }
+        Ok(Ok(()))
+    }
+}
+
+/// Converts a value of a field, as [`FieldType::value_from_json()`] does.
+///
+/// Only the values of JSON fields, and the pre-tokenized strings, are compound values. Other
+/// compound values are errors, which are built from the `serde_json::Value` of the offending
+/// value.
+#[derive(Clone, Copy)]
+struct FieldValueVisitor<'a>(&'a FieldType);
+
+impl FieldValueVisitor<'_> {
+    fn convert(self, json: JsonValue) -> Result<OwnedValue, ValueParsingError> {
+        self.0.value_from_json(json)
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for FieldValueVisitor<'_> {
+    type Value = Result<OwnedValue, ValueParsingError>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FieldValueVisitor<'_> {
+    type Value = Result<OwnedValue, ValueParsingError>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a value")
+    }
+
+    fn visit_bool<E>(self, val: bool) -> Result<Self::Value, E> {
+        Ok(self.convert(JsonValue::Bool(val)))
+    }
+
+    fn visit_i64<E>(self, val: i64) -> Result<Self::Value, E> {
+        Ok(self.convert(JsonValue::Number(val.into())))
+    }
+
+    fn visit_u64<E>(self, val: u64) -> Result<Self::Value, E> {
+        Ok(self.convert(JsonValue::Number(val.into())))
+    }
+
+    fn visit_f64<E>(self, val: f64) -> Result<Self::Value, E> {
+        // JSON numbers are always finite.
+        let json = Number::from_f64(val).map_or(JsonValue::Null, JsonValue::Number);
+        Ok(self.convert(json))
+    }
+
+    fn visit_str<E>(self, val: &str) -> Result<Self::Value, E> {
+        Ok(self.convert(JsonValue::String(val.to_string())))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(self.convert(JsonValue::Null))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        if let FieldType::JsonObject(_) = self.0 {
+            return JsonFieldValueVisitor.visit_map(map).map(Ok);
+        }
+        let json_map = serde_json::Map::deserialize(MapAccessDeserializer::new(map))?;
+        Ok(self.convert(JsonValue::Object(json_map)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        let json_items = Vec::<JsonValue>::deserialize(SeqAccessDeserializer::new(seq))?;
+        Ok(self.convert(JsonValue::Array(json_items)))
+    }
+}
+
+/// Converts a value within a JSON field, as `OwnedValue::from(serde_json::Value)` does.
+#[derive(Clone, Copy)]
+struct JsonFieldValueVisitor;
+
+impl<'de> DeserializeSeed<'de> for JsonFieldValueVisitor {
+    type Value = OwnedValue;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<OwnedValue, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for JsonFieldValueVisitor {
+    type Value = OwnedValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a json value")
+    }
+
+    fn visit_bool<E>(self, val: bool) -> Result<OwnedValue, E> {
+        Ok(OwnedValue::Bool(val))
+    }
+
+    fn visit_i64<E>(self, val: i64) -> Result<OwnedValue, E> {
+        Ok(OwnedValue::I64(val))
+    }
+
+    fn visit_u64<E>(self, val: u64) -> Result<OwnedValue, E> {
+        // Integers are i64 whenever possible.
+        match i64::try_from(val) {
+            Ok(val) => Ok(OwnedValue::I64(val)),
+            Err(_) => Ok(OwnedValue::U64(val)),
+        }
+    }
+
+    fn visit_f64<E>(self, val: f64) -> Result<OwnedValue, E> {
+        Ok(OwnedValue::F64(val))
+    }
+
+    fn visit_str<E>(self, text: &str) -> Result<OwnedValue, E> {
+        if can_be_rfc3339_date_time(text) {
+            if let Ok(dt) = OffsetDateTime::parse(text, &Rfc3339) {
+                let dt_utc = dt.to_offset(time::UtcOffset::UTC);
+                return Ok(OwnedValue::Date(DateTime::from_utc(dt_utc)));
+            }
+        }
+        Ok(OwnedValue::Str(text.to_string()))
+    }
+
+    fn visit_unit<E>(self) -> Result<OwnedValue, E> {
+        Ok(OwnedValue::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<OwnedValue, A::Error> {
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+        while let Some(element) = seq.next_element_seed(self)? {
+            elements.push(element);
+        }
+        Ok(OwnedValue::Array(elements))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<OwnedValue, A::Error> {
+        let mut object: Vec<(String, OwnedValue)> = Vec::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let value = map.next_value_seed(self)?;
+            object.push((key, value));
+        }
+        // Like in a `serde_json::Map`, the keys are sorted, and the last occurrence of a key
+        // wins.
+        object.reverse();
+        object.sort_by(|(left, _), (right, _)| left.cmp(right));
+        object.dedup_by(|(right, _), (left, _)| left == right);
+        Ok(OwnedValue::Object(object))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::field_type::ValueParsingError;
+    use crate::schema::{DocParsingError, Schema, Value, FAST, INDEXED, STORED, STRING, TEXT};
+    use crate::{Document, TantivyDocument};
+
+    fn parse_with_json_value(schema: &Schema, doc_json: &str) -> TantivyDocument {
+        let json_obj = serde_json::from_str(doc_json).unwrap();
+        TantivyDocument::from_json_object(schema, json_obj).unwrap()
+    }
+
+    #[test]
+    fn test_parse_json_bytes_same_as_json_value() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", TEXT | STORED);
+        schema_builder.add_u64_field("count", INDEXED | STORED);
+        schema_builder.add_f64_field("score", FAST | STORED);
+        schema_builder.add_date_field("timestamp", STORED);
+        schema_builder.add_json_field("attributes", STORED);
+        schema_builder.add_text_field("tokens", STRING | STORED);
+        let schema = schema_builder.build();
+        let doc_json = r#"{
+            "title": ["hello", "happy tax payer"],
+            "unknown": {"nested": [1, 2, {"a": null}]},
+            "count": 4,
+            "score": 80,
+            "timestamp": "2023-03-01T10:00:00Z",
+            "tokens": {
+                "text": "hello",
+                "tokens": [{"offset_from": 0, "offset_to": 5, "position": 0, "text": "hello",
+                            "position_length": 1}]
+            },
+            "attributes": [
+                {"b": 1, "a": -1, "c": 18446744073709551615, "b": "2023-03-01T10:00:00Z"},
+                {"nested": {"values": [1.5, true, null, "text", ["deep"]]}}
+            ]
+        }"#;
+        let document = TantivyDocument::parse_json_bytes(&schema, doc_json.as_bytes()).unwrap();
+        let expected_document = parse_with_json_value(&schema, doc_json);
+        assert_eq!(
+            document.to_json(&schema),
+            expected_document.to_json(&schema)
+        );
+    }
+
+    #[test]
+    fn test_parse_json_duplicate_keys() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", STORED);
+        let count = schema_builder.add_u64_field("count", STORED);
+        let schema = schema_builder.build();
+        let doc_json = r#"{"title": ["a", "b"], "count": -1, "title": "c", "count": 2}"#;
+        let titles = |document: &TantivyDocument| -> Vec<String> {
+            document
+                .get_all(title)
+                .map(|value| value.as_str().unwrap().to_string())
+                .collect()
+        };
+
+        // The last occurrence of a key wins, as when parsing into a `serde_json::Map`.
+        let document = TantivyDocument::parse_json(&schema, doc_json).unwrap();
+        assert_eq!(titles(&document), ["c"]);
+        assert_eq!(document.get_first(count).unwrap().as_u64(), Some(2));
+        assert_eq!(
+            document.to_json(&schema),
+            parse_with_json_value(&schema, doc_json).to_json(&schema)
+        );
+
+        // The values of all of the occurrences are kept, and the invalid count is reported.
+        let document_res = TantivyDocument::parse_json_bytes(&schema, doc_json.as_bytes());
+        assert!(matches!(
+            document_res,
+            Err(DocParsingError::ValueError(field_name, _)) if field_name == "count"
+        ));
+        let doc_json = r#"{"title": ["a", "b"], "title": "c"}"#;
+        let document = TantivyDocument::parse_json_bytes(&schema, doc_json.as_bytes()).unwrap();
+        assert_eq!(titles(&document), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_parse_json_bytes_errors() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("count", INDEXED);
+        schema_builder.add_json_field("attributes", STORED);
+        let schema = schema_builder.build();
+        let parse =
+            |doc_json: &str| TantivyDocument::parse_json_bytes(&schema, doc_json.as_bytes());
+        assert!(matches!(
+            parse(r#"{"count": [1, -1, 2]}"#),
+            Err(DocParsingError::ValueError(
+                field_name,
+                ValueParsingError::OverflowError { .. }
+            )) if field_name == "count"
+        ));
+        assert!(matches!(
+            parse(r#"{"count": [[1]]}"#),
+            Err(DocParsingError::ValueError(
+                _,
+                ValueParsingError::TypeError { .. }
+            ))
+        ));
+        assert!(matches!(
+            parse(r#"{"attributes": "text"}"#),
+            Err(DocParsingError::ValueError(
+                _,
+                ValueParsingError::TypeError { .. }
+            ))
+        ));
+        // Invalid JSON takes precedence over invalid values.
+        assert!(matches!(
+            parse(r#"{"count": -1,}"#),
+            Err(DocParsingError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            parse(r#"{"count": 1} trailing"#),
+            Err(DocParsingError::InvalidJson(_))
+        ));
+        assert!(matches!(
+            parse(r#"[{"count": 1}]"#),
+            Err(DocParsingError::InvalidJson(_))
+        ));
+    }
+}
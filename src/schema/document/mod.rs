@@ -160,6 +160,7 @@
 mod de;
 mod default_document;
 mod existing_type_impls;
+mod json_stream;
 mod owned_value;
 mod se;
 mod value;
@@ -268,6 +269,7 @@ pub(crate) mod type_codes {
     pub const NULL_CODE: u8 = 11;
     pub const ARRAY_CODE: u8 = 12;
     pub const OBJECT_CODE: u8 = 13;
+    pub const UUID_CODE: u8 = 14;
 
     // Extended type codes
     pub const TOK_STR_EXT_CODE: u8 = 0;
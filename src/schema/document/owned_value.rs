@@ -7,6 +7,7 @@ use base64::Engine;
 use serde::de::{MapAccess, SeqAccess};
 use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
+use uuid::Uuid;
 
 use super::existing_type_impls::can_be_rfc3339_date_time;
 use super::ReferenceValueLeaf;
@@ -49,6 +50,8 @@ pub enum OwnedValue {
     Object(Vec<(String, Self)>),
     /// IpV6 Address. Internally there is no IpV4, it needs to be converted to `Ipv6Addr`.
     IpAddr(Ipv6Addr),
+    /// Uuid
+    Uuid(Uuid),
 }
 
 impl AsRef<OwnedValue> for OwnedValue {
@@ -75,6 +78,7 @@ impl<'a> Value<'a> for &'a OwnedValue {
             OwnedValue::Facet(val) => ReferenceValueLeaf::Facet(val.encoded_str()).into(),
             OwnedValue::Bytes(val) => ReferenceValueLeaf::Bytes(val).into(),
             OwnedValue::IpAddr(val) => ReferenceValueLeaf::IpAddr(*val).into(),
+            OwnedValue::Uuid(val) => ReferenceValueLeaf::Uuid(*val).into(),
             OwnedValue::Array(array) => ReferenceValue::Array(array.iter()),
             OwnedValue::Object(object) => ReferenceValue::Object(ObjectMapIter(object.iter())),
         }
@@ -121,6 +125,10 @@ impl ValueDeserialize for OwnedValue {
                 Ok(OwnedValue::IpAddr(val))
             }
 
+            fn visit_uuid(&self, val: Uuid) -> Result<Self::Value, DeserializeError> {
+                Ok(OwnedValue::Uuid(val))
+            }
+
             fn visit_facet(&self, val: Facet) -> Result<Self::Value, DeserializeError> {
                 Ok(OwnedValue::Facet(val))
             }
@@ -197,6 +205,7 @@ impl serde::Serialize for OwnedValue {
                     ip_v6.serialize(serializer)
                 }
             }
+            OwnedValue::Uuid(ref uuid) => uuid.serialize(serializer),
             OwnedValue::Array(ref array) => array.serialize(serializer),
         }
     }
@@ -283,6 +292,7 @@ impl<'a, V: Value<'a>> From<ReferenceValue<'a, V>> for OwnedValue {
                 }
                 ReferenceValueLeaf::Bytes(val) => OwnedValue::Bytes(val.to_vec()),
                 ReferenceValueLeaf::IpAddr(val) => OwnedValue::IpAddr(val),
+                ReferenceValueLeaf::Uuid(val) => OwnedValue::Uuid(val),
                 ReferenceValueLeaf::Bool(val) => OwnedValue::Bool(val),
                 ReferenceValueLeaf::PreTokStr(val) => OwnedValue::PreTokStr(*val.clone()),
             },
@@ -309,6 +319,12 @@ impl From<Ipv6Addr> for OwnedValue {
     }
 }
 
+impl From<Uuid> for OwnedValue {
+    fn from(v: Uuid) -> OwnedValue {
+        OwnedValue::Uuid(v)
+    }
+}
+
 impl From<u64> for OwnedValue {
     fn from(v: u64) -> OwnedValue {
         OwnedValue::U64(v)
@@ -35,11 +35,17 @@ where W: Write
         let num_field_values = stored_field_values().count();
         let mut actual_length = 0;
 
+        // Values are serialized in a buffer first, in order to prefix them with their length.
+        // This makes it possible to skip the values of the fields that are not requested when
+        // reading a document.
+        let mut value_buffer = Vec::new();
+
         VInt(num_field_values as u64).serialize(self.writer)?;
         for (field, value_access) in stored_field_values() {
             field.serialize(self.writer)?;
 
-            let mut serializer = BinaryValueSerializer::new(self.writer);
+            value_buffer.clear();
+            let mut serializer = BinaryValueSerializer::new(&mut value_buffer);
             match value_access.as_value() {
                 ReferenceValue::Leaf(ReferenceValueLeaf::PreTokStr(pre_tokenized_text)) => {
                     serializer.serialize_value(ReferenceValue::Leaf::<&'_ OwnedValue>(
@@ -50,6 +56,8 @@ where W: Write
                     serializer.serialize_value(value_access.as_value())?;
                 }
             }
+            VInt(value_buffer.len() as u64).serialize(self.writer)?;
+            self.writer.write_all(&value_buffer)?;
 
             actual_length += 1;
         }
@@ -129,6 +137,9 @@ where W: Write
                 ReferenceValueLeaf::IpAddr(val) => {
                     self.serialize_with_type_code(type_codes::IP_CODE, &val.to_u128())
                 }
+                ReferenceValueLeaf::Uuid(val) => {
+                    self.serialize_with_type_code(type_codes::UUID_CODE, &val.as_u128())
+                }
                 ReferenceValueLeaf::Bool(val) => {
                     self.serialize_with_type_code(type_codes::BOOL_CODE, &val)
                 }
@@ -676,6 +687,13 @@ mod tests {
         writer
     }
 
+    /// Writes the expected binary representation of a field value, prefixed with its length.
+    fn write_field_value(writer: &mut Vec<u8>, field: Field, value: Vec<u8>) {
+        field.serialize(writer).unwrap();
+        VInt(value.len() as u64).serialize(writer).unwrap();
+        writer.extend_from_slice(&value);
+    }
+
     /// A helper macro for generating the expected binary representation of the document.
     macro_rules! expected_doc_data {
         (length $len:expr) => {{
@@ -688,8 +706,9 @@ mod tests {
 
             VInt($len as u64).serialize(&mut writer).unwrap();
             $(
-                $field_id.serialize(&mut writer).unwrap();
-                $value.serialize(&mut writer).unwrap();
+                let mut value = Vec::new();
+                $value.serialize(&mut value).unwrap();
+                write_field_value(&mut writer, $field_id, value);
             )*
 
             writer
@@ -709,11 +728,16 @@ mod tests {
 
         let result = serialize_doc(&document, &schema);
         let mut expected = expected_doc_data!(length document.len());
-        name.serialize(&mut expected).unwrap();
-        expected
-            .extend_from_slice(&binary_repr!(type_codes::TEXT_CODE => String::from("ChillFish8")));
-        age.serialize(&mut expected).unwrap();
-        expected.extend_from_slice(&binary_repr!(type_codes::U64_CODE => 20u64));
+        write_field_value(
+            &mut expected,
+            name,
+            binary_repr!(type_codes::TEXT_CODE => String::from("ChillFish8")),
+        );
+        write_field_value(
+            &mut expected,
+            age,
+            binary_repr!(type_codes::U64_CODE => 20u64),
+        );
         assert_eq!(
             result, expected,
             "Expected serialized document to match the binary representation"
@@ -731,9 +755,11 @@ mod tests {
 
         let result = serialize_doc(&document, &schema);
         let mut expected = expected_doc_data!(length 1);
-        name.serialize(&mut expected).unwrap();
-        expected
-            .extend_from_slice(&binary_repr!(type_codes::TEXT_CODE => String::from("ChillFish8")));
+        write_field_value(
+            &mut expected,
+            name,
+            binary_repr!(type_codes::TEXT_CODE => String::from("ChillFish8")),
+        );
         assert_eq!(
             result, expected,
             "Expected serialized document to match the binary representation"
@@ -2,6 +2,7 @@ use std::fmt::Debug;
 use std::net::Ipv6Addr;
 
 use common::DateTime;
+use uuid::Uuid;
 
 use crate::tokenizer::PreTokenizedString;
 
@@ -63,6 +64,12 @@ pub trait Value<'a>: Send + Sync + Debug {
         self.as_leaf().and_then(|leaf| leaf.as_ip_addr())
     }
 
+    #[inline]
+    /// If the Value is a uuid, returns the associated uuid. Returns None otherwise.
+    fn as_uuid(&self) -> Option<Uuid> {
+        self.as_leaf().and_then(|leaf| leaf.as_uuid())
+    }
+
     #[inline]
     /// If the Value is a bool, returns the associated bool. Returns None otherwise.
     fn as_bool(&self) -> Option<bool> {
@@ -132,6 +139,8 @@ pub enum ReferenceValueLeaf<'a> {
     Bytes(&'a [u8]),
     /// IpV6 Address. Internally there is no IpV4, it needs to be converted to `Ipv6Addr`.
     IpAddr(Ipv6Addr),
+    /// Uuid
+    Uuid(Uuid),
     /// Bool value
     Bool(bool),
     /// Pre-tokenized str type,
@@ -194,6 +203,13 @@ impl From<Ipv6Addr> for ReferenceValueLeaf<'_> {
     }
 }
 
+impl From<Uuid> for ReferenceValueLeaf<'_> {
+    #[inline]
+    fn from(value: Uuid) -> Self {
+        ReferenceValueLeaf::Uuid(value)
+    }
+}
+
 impl From<PreTokenizedString> for ReferenceValueLeaf<'_> {
     #[inline]
     fn from(val: PreTokenizedString) -> Self {
@@ -216,6 +232,7 @@ impl<'a, T: Value<'a> + ?Sized> From<ReferenceValueLeaf<'a>> for ReferenceValue<
             ReferenceValueLeaf::IpAddr(val) => {
                 ReferenceValue::Leaf(ReferenceValueLeaf::IpAddr(val))
             }
+            ReferenceValueLeaf::Uuid(val) => ReferenceValue::Leaf(ReferenceValueLeaf::Uuid(val)),
             ReferenceValueLeaf::Bool(val) => ReferenceValue::Leaf(ReferenceValueLeaf::Bool(val)),
             ReferenceValueLeaf::PreTokStr(val) => {
                 ReferenceValue::Leaf(ReferenceValueLeaf::PreTokStr(val))
@@ -291,6 +308,16 @@ impl<'a> ReferenceValueLeaf<'a> {
         }
     }
 
+    #[inline]
+    /// If the Value is a uuid, returns the associated uuid. Returns None otherwise.
+    pub fn as_uuid(&self) -> Option<Uuid> {
+        if let Self::Uuid(val) = self {
+            Some(*val)
+        } else {
+            None
+        }
+    }
+
     #[inline]
     /// If the Value is a bool, returns the associated bool. Returns None otherwise.
     pub fn as_bool(&self) -> Option<bool> {
@@ -411,6 +438,12 @@ where V: Value<'a>
         self.as_leaf().and_then(|leaf| leaf.as_ip_addr())
     }
 
+    #[inline]
+    /// If the Value is a uuid, returns the associated uuid. Returns None otherwise.
+    pub fn as_uuid(&self) -> Option<Uuid> {
+        self.as_leaf().and_then(|leaf| leaf.as_uuid())
+    }
+
     #[inline]
     /// If the Value is a bool, returns the associated bool. Returns None otherwise.
     pub fn as_bool(&self) -> Option<bool> {
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::ip_options::IpAddrOptions;
+use super::uuid_options::UuidOptions;
 use crate::schema::bytes_options::BytesOptions;
 use crate::schema::{
     is_valid_field_name, DateOptions, FacetOptions, FieldType, JsonObjectOptions, NumericOptions,
@@ -65,6 +66,11 @@ impl FieldEntry {
         Self::new(field_name, FieldType::IpAddr(ip_options))
     }
 
+    /// Creates a new uuid field entry.
+    pub fn new_uuid(field_name: String, uuid_options: UuidOptions) -> FieldEntry {
+        Self::new(field_name, FieldType::Uuid(uuid_options))
+    }
+
     /// Creates a field entry for a facet.
     pub fn new_facet(field_name: String, facet_options: FacetOptions) -> FieldEntry {
         Self::new(field_name, FieldType::Facet(facet_options))
@@ -129,6 +135,7 @@ impl FieldEntry {
             FieldType::Bytes(ref options) => options.is_stored(),
             FieldType::JsonObject(ref options) => options.is_stored(),
             FieldType::IpAddr(ref options) => options.is_stored(),
+            FieldType::Uuid(ref options) => options.is_stored(),
         }
     }
 }
@@ -7,14 +7,16 @@ use columnar::{ColumnType, NumericalType};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use thiserror::Error;
+use uuid::Uuid;
 
 use super::ip_options::IpAddrOptions;
+use super::uuid_options::UuidOptions;
 use super::IntoIpv6Addr;
 use crate::schema::bytes_options::BytesOptions;
 use crate::schema::facet_options::FacetOptions;
 use crate::schema::{
     DateOptions, Facet, IndexRecordOption, JsonObjectOptions, NumericOptions, OwnedValue,
-    TextFieldIndexing, TextOptions,
+    PostingsCodec, TextFieldIndexing, TextOptions,
 };
 use crate::time::format_description::well_known::Rfc3339;
 use crate::time::OffsetDateTime;
@@ -71,6 +73,8 @@ pub enum Type {
     Json = b'j',
     /// IpAddr
     IpAddr = b'p',
+    /// Uuid
+    Uuid = b'g',
 }
 
 impl From<ColumnType> for Type {
@@ -84,11 +88,12 @@ impl From<ColumnType> for Type {
             ColumnType::DateTime => Type::Date,
             ColumnType::Bytes => Type::Bytes,
             ColumnType::IpAddr => Type::IpAddr,
+            ColumnType::Uuid => Type::Uuid,
         }
     }
 }
 
-const ALL_TYPES: [Type; 10] = [
+const ALL_TYPES: [Type; 11] = [
     Type::Str,
     Type::U64,
     Type::I64,
@@ -99,6 +104,7 @@ const ALL_TYPES: [Type; 10] = [
     Type::Bytes,
     Type::Json,
     Type::IpAddr,
+    Type::Uuid,
 ];
 
 impl Type {
@@ -139,6 +145,7 @@ impl Type {
             Type::Bytes => "Bytes",
             Type::Json => "Json",
             Type::IpAddr => "IpAddr",
+            Type::Uuid => "Uuid",
         }
     }
 
@@ -157,6 +164,7 @@ impl Type {
             b'b' => Some(Type::Bytes),
             b'j' => Some(Type::Json),
             b'p' => Some(Type::IpAddr),
+            b'g' => Some(Type::Uuid),
             _ => None,
         }
     }
@@ -189,6 +197,8 @@ pub enum FieldType {
     JsonObject(JsonObjectOptions),
     /// IpAddr field
     IpAddr(IpAddrOptions),
+    /// Uuid field
+    Uuid(UuidOptions),
 }
 
 impl FieldType {
@@ -205,6 +215,7 @@ impl FieldType {
             FieldType::Bytes(_) => Type::Bytes,
             FieldType::JsonObject(_) => Type::Json,
             FieldType::IpAddr(_) => Type::IpAddr,
+            FieldType::Uuid(_) => Type::Uuid,
         }
     }
 
@@ -218,6 +229,11 @@ impl FieldType {
         matches!(self, FieldType::IpAddr(_))
     }
 
+    /// returns true if this is a uuid field
+    pub fn is_uuid(&self) -> bool {
+        matches!(self, FieldType::Uuid(_))
+    }
+
     /// returns true if this is an str field
     pub fn is_str(&self) -> bool {
         matches!(self, FieldType::Str(_))
@@ -241,6 +257,7 @@ impl FieldType {
             FieldType::Bytes(ref bytes_options) => bytes_options.is_indexed(),
             FieldType::JsonObject(ref json_object_options) => json_object_options.is_indexed(),
             FieldType::IpAddr(ref ip_addr_options) => ip_addr_options.is_indexed(),
+            FieldType::Uuid(ref uuid_options) => uuid_options.is_indexed(),
         }
     }
 
@@ -265,6 +282,60 @@ impl FieldType {
         }
     }
 
+    /// Returns how the posting lists of the field are encoded.
+    pub fn postings_codec(&self) -> PostingsCodec {
+        match self {
+            FieldType::Str(text_options) => text_options
+                .get_indexing_options()
+                .map(TextFieldIndexing::postings_codec)
+                .unwrap_or_default(),
+            FieldType::JsonObject(json_object_options) => json_object_options
+                .get_text_indexing_options()
+                .map(TextFieldIndexing::postings_codec)
+                .unwrap_or_default(),
+            FieldType::U64(numeric_options)
+            | FieldType::I64(numeric_options)
+            | FieldType::F64(numeric_options)
+            | FieldType::Bool(numeric_options) => numeric_options.postings_codec(),
+            _ => PostingsCodec::Block,
+        }
+    }
+
+    /// Returns true if a bloom filter of the terms of the field is stored in each segment.
+    pub fn has_bloom_filter(&self) -> bool {
+        match self {
+            FieldType::Str(text_options) => text_options
+                .get_indexing_options()
+                .is_some_and(TextFieldIndexing::bloom_filter),
+            FieldType::JsonObject(json_object_options) => json_object_options
+                .get_text_indexing_options()
+                .is_some_and(TextFieldIndexing::bloom_filter),
+            FieldType::U64(numeric_options)
+            | FieldType::I64(numeric_options)
+            | FieldType::F64(numeric_options)
+            | FieldType::Bool(numeric_options) => numeric_options.bloom_filter(),
+            _ => false,
+        }
+    }
+
+    /// Returns the interval of the positions checkpoints of the field, if it stores positions
+    /// checkpoints.
+    ///
+    /// See [`TextFieldIndexing::set_positions_checkpoint_interval()`].
+    pub fn positions_checkpoint_interval(&self) -> Option<u32> {
+        let text_indexing_options = match self {
+            FieldType::Str(text_options) => text_options.get_indexing_options(),
+            FieldType::JsonObject(json_object_options) => {
+                json_object_options.get_text_indexing_options()
+            }
+            _ => None,
+        }?;
+        if !text_indexing_options.index_option().has_positions() {
+            return None;
+        }
+        text_indexing_options.positions_checkpoint_interval()
+    }
+
     /// returns true if the field is fast.
     pub fn is_fast(&self) -> bool {
         match *self {
@@ -276,6 +347,7 @@ impl FieldType {
             | FieldType::Bool(ref int_options) => int_options.is_fast(),
             FieldType::Date(ref date_options) => date_options.is_fast(),
             FieldType::IpAddr(ref ip_addr_options) => ip_addr_options.is_fast(),
+            FieldType::Uuid(ref uuid_options) => uuid_options.is_fast(),
             FieldType::Facet(_) => true,
             FieldType::JsonObject(ref json_object_options) => json_object_options.is_fast(),
         }
@@ -297,6 +369,7 @@ impl FieldType {
             FieldType::Bytes(ref bytes_options) => bytes_options.fieldnorms(),
             FieldType::JsonObject(ref _json_object_options) => false,
             FieldType::IpAddr(ref ip_addr_options) => ip_addr_options.fieldnorms(),
+            FieldType::Uuid(ref uuid_options) => uuid_options.fieldnorms(),
         }
     }
 
@@ -348,6 +421,13 @@ impl FieldType {
                     None
                 }
             }
+            FieldType::Uuid(ref uuid_options) => {
+                if uuid_options.is_indexed() {
+                    Some(IndexRecordOption::Basic)
+                } else {
+                    None
+                }
+            }
         }
     }
 
@@ -449,6 +529,15 @@ impl FieldType {
 
                         Ok(OwnedValue::IpAddr(ip_addr.into_ipv6_addr()))
                     }
+                    FieldType::Uuid(_) => {
+                        let uuid = Uuid::parse_str(&field_text).map_err(|err| {
+                            ValueParsingError::ParseError {
+                                error: err.to_string(),
+                                json: JsonValue::String(field_text),
+                            }
+                        })?;
+                        Ok(OwnedValue::Uuid(uuid))
+                    }
                 }
             }
             JsonValue::Number(field_val_num) => match self {
@@ -508,6 +597,10 @@ impl FieldType {
                     expected: "a string with an ip addr",
                     json: JsonValue::Number(field_val_num),
                 }),
+                FieldType::Uuid(_) => Err(ValueParsingError::TypeError {
+                    expected: "a string with a uuid",
+                    json: JsonValue::Number(field_val_num),
+                }),
             },
             JsonValue::Object(json_map) => match self {
                 FieldType::Str(_) => {
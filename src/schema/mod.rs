@@ -124,7 +124,9 @@ mod ip_options;
 mod json_object_options;
 mod named_field_document;
 mod numeric_options;
+mod postings_codec;
 mod text_options;
+mod uuid_options;
 
 use columnar::ColumnType;
 
@@ -143,9 +145,11 @@ pub use self::ip_options::{IntoIpv6Addr, IpAddrOptions};
 pub use self::json_object_options::JsonObjectOptions;
 pub use self::named_field_document::NamedFieldDocument;
 pub use self::numeric_options::NumericOptions;
+pub use self::postings_codec::PostingsCodec;
 pub use self::schema::{Schema, SchemaBuilder};
 pub use self::term::{Term, ValueBytes};
 pub use self::text_options::{TextFieldIndexing, TextOptions, STRING, TEXT};
+pub use self::uuid_options::UuidOptions;
 
 /// Validator for a potential `field_name`.
 /// Returns true if the name can be use for a field name.
@@ -167,6 +171,7 @@ pub(crate) fn value_type_to_column_type(typ: Type) -> Option<ColumnType> {
         Type::Facet => Some(ColumnType::Str),
         Type::Bytes => Some(ColumnType::Bytes),
         Type::IpAddr => Some(ColumnType::IpAddr),
+        Type::Uuid => Some(ColumnType::Uuid),
         Type::Json => None,
     }
 }
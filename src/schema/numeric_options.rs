@@ -1,9 +1,11 @@
 use std::ops::BitOr;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
 use super::flags::CoerceFlag;
 use crate::schema::flags::{FastFlag, IndexedFlag, SchemaFlagList, StoredFlag};
+use crate::schema::PostingsCodec;
 
 /// Define how an `u64`, `i64`, or `f64` field should be handled by tantivy.
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
@@ -16,6 +18,12 @@ pub struct NumericOptions {
     stored: bool,
     #[serde(skip_serializing_if = "is_false")]
     coerce: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decay_half_life_secs: Option<u64>,
+    #[serde(skip_serializing_if = "PostingsCodec::is_block")]
+    postings_codec: PostingsCodec,
+    #[serde(skip_serializing_if = "is_false")]
+    bloom_filter: bool,
 }
 
 fn is_false(val: &bool) -> bool {
@@ -37,6 +45,12 @@ struct NumericOptionsDeser {
     stored: bool,
     #[serde(default)]
     coerce: bool,
+    #[serde(default)]
+    decay_half_life_secs: Option<u64>,
+    #[serde(default)]
+    postings_codec: PostingsCodec,
+    #[serde(default)]
+    bloom_filter: bool,
 }
 
 impl From<NumericOptionsDeser> for NumericOptions {
@@ -47,6 +61,9 @@ impl From<NumericOptionsDeser> for NumericOptions {
             fast: deser.fast,
             stored: deser.stored,
             coerce: deser.coerce,
+            decay_half_life_secs: deser.decay_half_life_secs,
+            postings_codec: deser.postings_codec,
+            bloom_filter: deser.bloom_filter,
         }
     }
 }
@@ -82,6 +99,69 @@ impl NumericOptions {
         self.coerce
     }
 
+    /// Returns the half-life of the values, if the field is a decayed counter.
+    ///
+    /// See [`NumericOptions::set_decay_half_life()`].
+    #[inline]
+    pub fn decay_half_life(&self) -> Option<Duration> {
+        self.decay_half_life_secs.map(Duration::from_secs)
+    }
+
+    /// Makes the field an exponentially decayed counter, whose values halve every
+    /// `half_life`.
+    ///
+    /// This only applies to fast `f64` fields. The values of a segment are relative to
+    /// its [decay reference timestamp](crate::index::SegmentMeta::decay_reference_timestamp):
+    /// when segments get merged, their values are decayed to the time of the merge. Use
+    /// [`FastFieldReaders::decayed_f64()`](crate::fastfield::FastFieldReaders::decayed_f64) to
+    /// read the values decayed to a given time.
+    ///
+    /// The half-life is truncated to a whole number of seconds, and needs to be at least a
+    /// second.
+    #[must_use]
+    pub fn set_decay_half_life(mut self, half_life: Duration) -> Self {
+        assert!(
+            half_life.as_secs() > 0,
+            "The decay half-life needs to be at least a second."
+        );
+        self.decay_half_life_secs = Some(half_life.as_secs());
+        self
+    }
+
+    /// Returns how the posting lists of the field are encoded.
+    #[inline]
+    pub fn postings_codec(&self) -> PostingsCodec {
+        self.postings_codec
+    }
+
+    /// Sets how the posting lists of the field are encoded.
+    ///
+    /// This has no effect if the field is not indexed. See [`PostingsCodec`] for more detail.
+    #[must_use]
+    pub fn set_postings_codec(mut self, postings_codec: PostingsCodec) -> Self {
+        self.postings_codec = postings_codec;
+        self
+    }
+
+    /// Returns true if a bloom filter of the terms of the field is stored in each segment.
+    #[inline]
+    pub fn bloom_filter(&self) -> bool {
+        self.bloom_filter && self.indexed
+    }
+
+    /// Stores a bloom filter of the terms of the field in each segment.
+    ///
+    /// The filter is consulted before looking a term up in the term dictionary, so that the
+    /// segments which do not contain the term are skipped cheaply. This is worth it for
+    /// fields mostly looked up by value, e.g. primary keys.
+    ///
+    /// This has no effect if the field is not indexed.
+    #[must_use]
+    pub fn set_bloom_filter(mut self, bloom_filter: bool) -> Self {
+        self.bloom_filter = bloom_filter;
+        self
+    }
+
     /// Try to coerce values if they are not a number. Defaults to false.
     #[must_use]
     pub fn set_coerce(mut self) -> Self {
@@ -145,6 +225,9 @@ impl From<CoerceFlag> for NumericOptions {
             stored: false,
             fast: false,
             coerce: true,
+            decay_half_life_secs: None,
+            postings_codec: PostingsCodec::Block,
+            bloom_filter: false,
         }
     }
 }
@@ -157,6 +240,9 @@ impl From<FastFlag> for NumericOptions {
             stored: false,
             fast: true,
             coerce: false,
+            decay_half_life_secs: None,
+            postings_codec: PostingsCodec::Block,
+            bloom_filter: false,
         }
     }
 }
@@ -169,6 +255,9 @@ impl From<StoredFlag> for NumericOptions {
             stored: true,
             fast: false,
             coerce: false,
+            decay_half_life_secs: None,
+            postings_codec: PostingsCodec::Block,
+            bloom_filter: false,
         }
     }
 }
@@ -181,6 +270,9 @@ impl From<IndexedFlag> for NumericOptions {
             stored: false,
             fast: false,
             coerce: false,
+            decay_half_life_secs: None,
+            postings_codec: PostingsCodec::Block,
+            bloom_filter: false,
         }
     }
 }
@@ -196,6 +288,13 @@ impl<T: Into<NumericOptions>> BitOr<T> for NumericOptions {
             stored: self.stored | other.stored,
             fast: self.fast | other.fast,
             coerce: self.coerce | other.coerce,
+            decay_half_life_secs: self.decay_half_life_secs.or(other.decay_half_life_secs),
+            postings_codec: if self.postings_codec.is_block() {
+                other.postings_codec
+            } else {
+                self.postings_codec
+            },
+            bloom_filter: self.bloom_filter | other.bloom_filter,
         }
     }
 }
@@ -230,6 +329,9 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                decay_half_life_secs: None,
+                postings_codec: PostingsCodec::Block,
+                bloom_filter: false,
             }
         );
     }
@@ -249,6 +351,9 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                decay_half_life_secs: None,
+                postings_codec: PostingsCodec::Block,
+                bloom_filter: false,
             }
         );
     }
@@ -269,6 +374,9 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                decay_half_life_secs: None,
+                postings_codec: PostingsCodec::Block,
+                bloom_filter: false,
             }
         );
     }
@@ -290,6 +398,9 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: false,
+                decay_half_life_secs: None,
+                postings_codec: PostingsCodec::Block,
+                bloom_filter: false,
             }
         );
     }
@@ -312,6 +423,9 @@ mod tests {
                 fast: false,
                 stored: false,
                 coerce: true,
+                decay_half_life_secs: None,
+                postings_codec: PostingsCodec::Block,
+                bloom_filter: false,
             }
         );
     }
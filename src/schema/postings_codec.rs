@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// `PostingsCodec` defines how the posting lists of an indexed field are encoded.
+///
+/// It is set in the schema, either through
+/// [`TextFieldIndexing::set_postings_codec()`](crate::schema::TextFieldIndexing::set_postings_codec)
+/// or [`NumericOptions::set_postings_codec()`](crate::schema::NumericOptions::set_postings_codec).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum PostingsCodec {
+    /// Doc ids and term frequencies are delta-encoded and bitpacked in blocks of 128 documents.
+    ///
+    /// This is the most compact encoding, and it supports term frequencies and positions.
+    #[serde(rename = "block")]
+    #[default]
+    Block,
+    /// The posting list of a term matching a large fraction of the segment is encoded as a bitset
+    /// over the doc ids of the segment. The other terms keep the block encoding.
+    ///
+    /// Seeking through a bitset does not require any decoding, which makes intersecting filters
+    /// on low-cardinality fields like `status` or `tenant_id` much faster.
+    ///
+    /// Term frequencies and positions are not recorded: the field is indexed as with
+    /// [`IndexRecordOption::Basic`](crate::schema::IndexRecordOption::Basic).
+    #[serde(rename = "bitmap")]
+    Bitmap,
+}
+
+impl PostingsCodec {
+    pub(crate) fn is_block(&self) -> bool {
+        *self == PostingsCodec::Block
+    }
+}
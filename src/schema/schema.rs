@@ -139,6 +139,22 @@ impl SchemaBuilder {
         self.add_field(field_entry)
     }
 
+    /// Adds a uuid field.
+    /// Returns the associated field handle.
+    ///
+    /// # Panics
+    ///
+    /// Panics when field already exists.
+    pub fn add_uuid_field<T: Into<UuidOptions>>(
+        &mut self,
+        field_name_str: &str,
+        field_options: T,
+    ) -> Field {
+        let field_name = String::from(field_name_str);
+        let field_entry = FieldEntry::new_uuid(field_name, field_options.into());
+        self.add_field(field_entry)
+    }
+
     /// Adds a new text field.
     /// Returns the associated field handle
     ///
@@ -627,6 +643,31 @@ mod tests {
         assert_eq!(value["ip"][0], "192.168.0.1");
     }
 
+    #[test]
+    pub fn test_document_to_uuid_json() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_uuid_field("id", FAST | STORED);
+        let schema = schema_builder.build();
+
+        let doc_json = r#"{
+                "id": "67E55044-10B1-426F-9247-BB680E5FE0C8"
+        }"#;
+        let doc = TantivyDocument::parse_json(&schema, doc_json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&doc.to_json(&schema)).unwrap();
+        assert_eq!(value["id"][0], "67e55044-10b1-426f-9247-bb680e5fe0c8");
+
+        let doc_json = r#"{
+                "id": "not-a-uuid"
+        }"#;
+        assert_matches!(
+            TantivyDocument::parse_json(&schema, doc_json),
+            Err(DocParsingError::ValueError(
+                _,
+                ValueParsingError::ParseError { .. }
+            ))
+        );
+    }
+
     #[test]
     pub fn test_document_from_nameddoc() {
         let mut schema_builder = Schema::builder();
@@ -5,6 +5,7 @@ use std::{fmt, str};
 use columnar::MonotonicallyMappableToU128;
 use common::json_path_writer::{JSON_END_OF_PATH, JSON_PATH_SEGMENT_SEP_STR};
 use common::JsonPathWriter;
+use uuid::Uuid;
 
 use super::date_time_options::DATE_TIME_PRECISION_INDEXED;
 use super::{Field, Schema};
@@ -121,6 +122,13 @@ impl Term {
         term
     }
 
+    /// Builds a term given a field, and a `Uuid`-value
+    pub fn from_field_uuid(field: Field, uuid: Uuid) -> Term {
+        let mut term = Self::with_type_and_field(Type::Uuid, field);
+        term.set_uuid(uuid);
+        term
+    }
+
     /// Builds a term given a field, and a `u64`-value
     pub fn from_field_u64(field: Field, val: u64) -> Term {
         Term::from_fast_value(field, &val)
@@ -244,6 +252,11 @@ impl Term {
         self.set_bytes(val.to_u128().to_be_bytes().as_ref());
     }
 
+    /// Sets a `Uuid` value in the term.
+    pub fn set_uuid(&mut self, val: Uuid) {
+        self.set_bytes(val.as_bytes());
+    }
+
     /// Sets the value of a `Bytes` field.
     pub fn set_bytes(&mut self, bytes: &[u8]) {
         self.truncate_value_bytes(0);
@@ -459,6 +472,14 @@ where B: AsRef<[u8]>
         Some(Ipv6Addr::from_u128(ip_u128))
     }
 
+    /// Returns a `Uuid` value from the term.
+    pub fn as_uuid(&self) -> Option<Uuid> {
+        if self.typ() != Type::Uuid {
+            return None;
+        }
+        Uuid::from_slice(self.raw_value_bytes_payload()).ok()
+    }
+
     /// Returns the json path type.
     ///
     /// Returns `None` if the value is not JSON.
@@ -561,6 +582,9 @@ where B: AsRef<[u8]>
             Type::IpAddr => {
                 write_opt(f, self.as_ip_addr())?;
             }
+            Type::Uuid => {
+                write_opt(f, self.as_uuid())?;
+            }
         }
         Ok(())
     }
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 
 use super::flags::{CoerceFlag, FastFlag};
 use crate::schema::flags::{SchemaFlagList, StoredFlag};
-use crate::schema::IndexRecordOption;
+use crate::schema::{IndexRecordOption, PostingsCodec};
 
 /// Define how a text field should be handled by tantivy.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize, Default)]
@@ -202,6 +202,15 @@ pub struct TextFieldIndexing {
     fieldnorms: bool,
     #[serde(default)]
     tokenizer: TokenizerName,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "PostingsCodec::is_block")]
+    postings_codec: PostingsCodec,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    bloom_filter: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    positions_checkpoint_interval: Option<u32>,
 }
 
 pub(crate) fn default_fieldnorms() -> bool {
@@ -214,6 +223,9 @@ impl Default for TextFieldIndexing {
             tokenizer: TokenizerName::default(),
             record: IndexRecordOption::default(),
             fieldnorms: default_fieldnorms(),
+            postings_codec: PostingsCodec::Block,
+            bloom_filter: false,
+            positions_checkpoint_interval: None,
         }
     }
 }
@@ -254,9 +266,72 @@ impl TextFieldIndexing {
 
     /// Returns the indexing options associated with this field.
     ///
+    /// This is always [`IndexRecordOption::Basic`] if the field uses the
+    /// [bitmap postings codec](PostingsCodec::Bitmap).
+    ///
     /// See [`IndexRecordOption`] for more detail.
     pub fn index_option(&self) -> IndexRecordOption {
-        self.record
+        match self.postings_codec {
+            PostingsCodec::Block => self.record,
+            PostingsCodec::Bitmap => IndexRecordOption::Basic,
+        }
+    }
+
+    /// Sets how the posting lists of the field are encoded.
+    ///
+    /// See [`PostingsCodec`] for more detail.
+    #[must_use]
+    pub fn set_postings_codec(mut self, postings_codec: PostingsCodec) -> TextFieldIndexing {
+        self.postings_codec = postings_codec;
+        self
+    }
+
+    /// Returns how the posting lists of the field are encoded.
+    pub fn postings_codec(&self) -> PostingsCodec {
+        self.postings_codec
+    }
+
+    /// Stores a bloom filter of the terms of the field in each segment.
+    ///
+    /// The filter is consulted before looking a term up in the term dictionary, so that the
+    /// segments which do not contain the term are skipped cheaply. This is worth it for
+    /// fields mostly looked up by value, e.g. identifiers.
+    #[must_use]
+    pub fn set_bloom_filter(mut self, bloom_filter: bool) -> TextFieldIndexing {
+        self.bloom_filter = bloom_filter;
+        self
+    }
+
+    /// Returns true if a bloom filter of the terms of the field is stored in each segment.
+    pub fn bloom_filter(&self) -> bool {
+        self.bloom_filter
+    }
+
+    /// Stores the absolute value of every `interval`-th position of a term within a document.
+    ///
+    /// Positions are delta encoded, so that phrase queries otherwise decode all of the positions
+    /// of a term within a document, from the first one. Checkpoints let them skip to the
+    /// positions where the phrase may start, which bounds the latency of phrase queries on long
+    /// documents, e.g. books or transcripts, at the cost of a slightly larger positions file.
+    ///
+    /// This has no effect if positions are not indexed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is 0.
+    #[must_use]
+    pub fn set_positions_checkpoint_interval(mut self, interval: u32) -> TextFieldIndexing {
+        assert!(
+            interval > 0,
+            "The positions checkpoint interval must be positive."
+        );
+        self.positions_checkpoint_interval = Some(interval);
+        self
+    }
+
+    /// Returns the interval of the positions checkpoints, if positions checkpoints are stored.
+    pub fn positions_checkpoint_interval(&self) -> Option<u32> {
+        self.positions_checkpoint_interval
     }
 }
 
@@ -266,6 +341,9 @@ pub const STRING: TextOptions = TextOptions {
         tokenizer: TokenizerName::from_static(NO_TOKENIZER_NAME),
         fieldnorms: true,
         record: IndexRecordOption::Basic,
+        postings_codec: PostingsCodec::Block,
+        bloom_filter: false,
+        positions_checkpoint_interval: None,
     }),
     stored: false,
     fast: FastFieldTextOptions::IsEnabled(false),
@@ -278,6 +356,9 @@ pub const TEXT: TextOptions = TextOptions {
         tokenizer: TokenizerName::from_static(DEFAULT_TOKENIZER_NAME),
         fieldnorms: true,
         record: IndexRecordOption::WithFreqsAndPositions,
+        postings_codec: PostingsCodec::Block,
+        bloom_filter: false,
+        positions_checkpoint_interval: None,
     }),
     stored: false,
     coerce: false,
@@ -0,0 +1,142 @@
+use std::ops::BitOr;
+
+use serde::{Deserialize, Serialize};
+
+use super::flags::{FastFlag, IndexedFlag, SchemaFlagList, StoredFlag};
+
+/// Define how a uuid field should be handled by tantivy.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub struct UuidOptions {
+    fast: bool,
+    stored: bool,
+    indexed: bool,
+    fieldnorms: bool,
+}
+
+impl UuidOptions {
+    /// Returns true iff the value is a fast field.
+    #[inline]
+    pub fn is_fast(&self) -> bool {
+        self.fast
+    }
+
+    /// Returns `true` if the uuid should be stored in the doc store.
+    #[inline]
+    pub fn is_stored(&self) -> bool {
+        self.stored
+    }
+
+    /// Returns true iff the value is indexed and therefore searchable.
+    #[inline]
+    pub fn is_indexed(&self) -> bool {
+        self.indexed
+    }
+
+    /// Returns true if and only if the value is normed.
+    #[inline]
+    pub fn fieldnorms(&self) -> bool {
+        self.fieldnorms
+    }
+
+    /// Set the field as normed.
+    ///
+    /// Setting a uuid as normed will generate
+    /// the fieldnorm data for it.
+    #[must_use]
+    pub fn set_fieldnorms(mut self) -> Self {
+        self.fieldnorms = true;
+        self
+    }
+
+    /// Sets the field as stored
+    #[must_use]
+    pub fn set_stored(mut self) -> Self {
+        self.stored = true;
+        self
+    }
+
+    /// Set the field as indexed.
+    ///
+    /// Setting a uuid as indexed will generate
+    /// a posting list for each value taken by the uuid.
+    ///
+    /// This is required for the field to be searchable.
+    #[must_use]
+    pub fn set_indexed(mut self) -> Self {
+        self.indexed = true;
+        self
+    }
+
+    /// Set the field as a fast field.
+    ///
+    /// Fast fields are designed for random access.
+    #[must_use]
+    pub fn set_fast(mut self) -> Self {
+        self.fast = true;
+        self
+    }
+}
+
+impl From<()> for UuidOptions {
+    fn from(_: ()) -> UuidOptions {
+        UuidOptions::default()
+    }
+}
+
+impl From<FastFlag> for UuidOptions {
+    fn from(_: FastFlag) -> Self {
+        UuidOptions {
+            fieldnorms: false,
+            indexed: false,
+            stored: false,
+            fast: true,
+        }
+    }
+}
+
+impl From<StoredFlag> for UuidOptions {
+    fn from(_: StoredFlag) -> Self {
+        UuidOptions {
+            fieldnorms: false,
+            indexed: false,
+            stored: true,
+            fast: false,
+        }
+    }
+}
+
+impl From<IndexedFlag> for UuidOptions {
+    fn from(_: IndexedFlag) -> Self {
+        UuidOptions {
+            fieldnorms: true,
+            indexed: true,
+            stored: false,
+            fast: false,
+        }
+    }
+}
+
+impl<T: Into<UuidOptions>> BitOr<T> for UuidOptions {
+    type Output = UuidOptions;
+
+    fn bitor(self, other: T) -> UuidOptions {
+        let other = other.into();
+        UuidOptions {
+            fieldnorms: self.fieldnorms | other.fieldnorms,
+            indexed: self.indexed | other.indexed,
+            stored: self.stored | other.stored,
+            fast: self.fast | other.fast,
+        }
+    }
+}
+
+impl<Head, Tail> From<SchemaFlagList<Head, Tail>> for UuidOptions
+where
+    Head: Clone,
+    Tail: Clone,
+    Self: BitOr<Output = Self> + From<Head> + From<Tail>,
+{
+    fn from(head_tail: SchemaFlagList<Head, Tail>) -> Self {
+        Self::from(head_tail.head) | Self::from(head_tail.tail)
+    }
+}
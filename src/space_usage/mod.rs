@@ -229,6 +229,11 @@ impl PerFieldSpaceUsage {
         }
     }
 
+    /// Space usage of the given field, if it appears in the represented file
+    pub fn field_usage(&self, field: Field) -> Option<&FieldUsage> {
+        self.fields.get(&field)
+    }
+
     /// Per field space usage
     pub fn fields(&self) -> impl Iterator<Item = (&Field, &FieldUsage)> {
         self.fields.iter()
@@ -3,6 +3,12 @@ use std::mem;
 
 use lz4_flex::{compress_into, decompress_into};
 
+/// Upper bound of the compression ratio of lz4.
+///
+/// Blocks announcing a larger uncompressed size are necessarily corrupted, and are
+/// rejected before allocating their decompression buffer.
+const MAX_LZ4_COMPRESSION_RATIO: usize = 255;
+
 #[inline]
 #[expect(clippy::uninit_vec)]
 pub fn compress(uncompressed: &[u8], compressed: &mut Vec<u8>) -> io::Result<()> {
@@ -33,6 +39,12 @@ pub fn decompress(compressed: &[u8], decompressed: &mut Vec<u8>) -> io::Result<(
         .try_into()
         .unwrap();
     let uncompressed_size = u32::from_le_bytes(*uncompressed_size_bytes) as usize;
+    if uncompressed_size > compressed.len().saturating_mul(MAX_LZ4_COMPRESSION_RATIO) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "doc store block uncompressed size is too large, data corruption".to_string(),
+        ));
+    }
     decompressed.reserve(uncompressed_size);
     unsafe {
         decompressed.set_len(uncompressed_size);
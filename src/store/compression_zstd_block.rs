@@ -3,6 +3,13 @@ use std::io;
 use zstd::bulk::{compress_to_buffer, decompress_to_buffer};
 use zstd::DEFAULT_COMPRESSION_LEVEL;
 
+/// Upper bound of the compression ratio of the blocks we accept to decompress.
+///
+/// zstd can compress a run of identical bytes with an arbitrarily large ratio, but
+/// doc store blocks are small enough to never reach it. Blocks announcing a larger
+/// uncompressed size are rejected before allocating their decompression buffer.
+const MAX_ZSTD_COMPRESSION_RATIO: usize = 1 << 15;
+
 #[inline]
 pub fn compress(
     uncompressed: &[u8],
@@ -37,6 +44,12 @@ pub fn decompress(compressed: &[u8], decompressed: &mut Vec<u8>) -> io::Result<(
             .try_into()
             .unwrap(),
     ) as usize;
+    if uncompressed_size > compressed.len().saturating_mul(MAX_ZSTD_COMPRESSION_RATIO) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "doc store block uncompressed size is too large, data corruption".to_string(),
+        ));
+    }
 
     decompressed.clear();
     decompressed.resize(uncompressed_size, 0);
@@ -28,9 +28,13 @@ impl BinarySerializable for DocStoreFooter {
     fn deserialize<R: io::Read>(reader: &mut R) -> io::Result<Self> {
         let doc_store_version = DocStoreVersion::deserialize(reader)?;
         if doc_store_version > DOC_STORE_VERSION {
-            panic!(
-                "actual doc store version: {doc_store_version}, max_supported: {DOC_STORE_VERSION}"
-            );
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "actual doc store version: {doc_store_version}, max_supported: \
+                     {DOC_STORE_VERSION}"
+                ),
+            ));
         }
         let offset = u64::deserialize(reader)?;
         let compressor_id = u8::deserialize(reader)?;
@@ -39,7 +43,7 @@ impl BinarySerializable for DocStoreFooter {
         Ok(DocStoreFooter {
             offset,
             doc_store_version,
-            decompressor: Decompressor::from_id(compressor_id),
+            decompressor: Decompressor::from_id(compressor_id)?,
         })
     }
 }
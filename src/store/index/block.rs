@@ -1,7 +1,7 @@
 use std::io;
 use std::ops::Range;
 
-use common::{read_u32_vint, VInt};
+use common::{BinarySerializable, VInt};
 
 use crate::store::index::{Checkpoint, CHECKPOINT_PERIOD};
 use crate::DocId;
@@ -85,26 +85,47 @@ impl CheckpointBlock {
             return Err(io::Error::new(io::ErrorKind::UnexpectedEof, ""));
         }
         self.checkpoints.clear();
-        let len = read_u32_vint(data);
+        let len = read_u32(data)?;
         if len == 0 {
             return Ok(());
         }
-        let mut doc = read_u32_vint(data);
+        // Each checkpoint takes at least two bytes.
+        if len as usize > data.len() / 2 {
+            return Err(corrupted_block_error());
+        }
+        let mut doc = read_u32(data)?;
         let mut start_offset = VInt::deserialize_u64(data)? as usize;
         for _ in 0..len {
-            let num_docs = read_u32_vint(data);
-            let block_num_bytes = read_u32_vint(data) as usize;
+            let num_docs = read_u32(data)?;
+            let block_num_bytes = read_u32(data)? as usize;
+            let end_doc = doc
+                .checked_add(num_docs)
+                .ok_or_else(corrupted_block_error)?;
+            let end_offset = start_offset
+                .checked_add(block_num_bytes)
+                .ok_or_else(corrupted_block_error)?;
             self.checkpoints.push(Checkpoint {
-                doc_range: doc..doc + num_docs,
-                byte_range: start_offset..start_offset + block_num_bytes,
+                doc_range: doc..end_doc,
+                byte_range: start_offset..end_offset,
             });
-            doc += num_docs;
-            start_offset += block_num_bytes;
+            doc = end_doc;
+            start_offset = end_offset;
         }
         Ok(())
     }
 }
 
+fn read_u32(data: &mut &[u8]) -> io::Result<u32> {
+    u32::try_from(VInt::deserialize(data)?.0).map_err(|_| corrupted_block_error())
+}
+
+fn corrupted_block_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "doc store checkpoint block is corrupted",
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use std::io;
@@ -55,7 +55,7 @@ mod tests {
         let mut output: Vec<u8> = Vec::new();
         let skip_index_builder: SkipIndexBuilder = SkipIndexBuilder::new();
         skip_index_builder.serialize_into(&mut output)?;
-        let skip_index: SkipIndex = SkipIndex::open(OwnedBytes::new(output));
+        let skip_index: SkipIndex = SkipIndex::open(OwnedBytes::new(output))?;
         let mut skip_cursor = skip_index.checkpoints();
         assert!(skip_cursor.next().is_none());
         Ok(())
@@ -71,7 +71,7 @@ mod tests {
         };
         skip_index_builder.insert(checkpoint.clone());
         skip_index_builder.serialize_into(&mut output)?;
-        let skip_index: SkipIndex = SkipIndex::open(OwnedBytes::new(output));
+        let skip_index: SkipIndex = SkipIndex::open(OwnedBytes::new(output))?;
         let mut skip_cursor = skip_index.checkpoints();
         assert_eq!(skip_cursor.next(), Some(checkpoint));
         assert_eq!(skip_cursor.next(), None);
@@ -110,7 +110,7 @@ mod tests {
         }
         skip_index_builder.serialize_into(&mut output)?;
 
-        let skip_index: SkipIndex = SkipIndex::open(OwnedBytes::new(output));
+        let skip_index: SkipIndex = SkipIndex::open(OwnedBytes::new(output))?;
         assert_eq!(
             &skip_index.checkpoints().collect::<Vec<_>>()[..],
             &checkpoints[..]
@@ -169,7 +169,7 @@ mod tests {
         }
         skip_index_builder.serialize_into(&mut output)?;
         assert_eq!(output.len(), 4035);
-        let resulting_checkpoints: Vec<Checkpoint> = SkipIndex::open(OwnedBytes::new(output))
+        let resulting_checkpoints: Vec<Checkpoint> = SkipIndex::open(OwnedBytes::new(output))?
             .checkpoints()
             .collect();
         assert_eq!(&resulting_checkpoints, &checkpoints);
@@ -237,7 +237,7 @@ mod tests {
              }
              let mut buffer = Vec::new();
              skip_index_builder.serialize_into(&mut buffer).unwrap();
-             let skip_index = SkipIndex::open(OwnedBytes::new(buffer));
+             let skip_index = SkipIndex::open(OwnedBytes::new(buffer)).unwrap();
              let iter_checkpoints: Vec<Checkpoint> = skip_index.checkpoints().collect();
              assert_eq!(&checkpoints[..], &iter_checkpoints[..]);
              test_skip_index_aux(skip_index, &checkpoints[..]);
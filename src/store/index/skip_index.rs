@@ -1,3 +1,5 @@
+use std::io;
+
 use common::{BinarySerializable, VInt};
 
 use crate::directory::OwnedBytes;
@@ -41,7 +43,8 @@ impl Layer {
     fn cursor_at_offset(&self, start_offset: usize) -> impl Iterator<Item = Checkpoint> + '_ {
         let data = &self.data.as_slice();
         LayerCursor {
-            remaining: &data[start_offset..],
+            // Offsets are read from the index, they may be out of bounds if it is corrupted.
+            remaining: data.get(start_offset..).unwrap_or(&[]),
             block: CheckpointBlock::default(),
             cursor: 0,
         }
@@ -58,22 +61,27 @@ pub struct SkipIndex {
 }
 
 impl SkipIndex {
-    pub fn open(mut data: OwnedBytes) -> SkipIndex {
-        let offsets: Vec<u64> = Vec::<VInt>::deserialize(&mut data)
-            .unwrap()
+    pub fn open(mut data: OwnedBytes) -> io::Result<SkipIndex> {
+        let offsets: Vec<u64> = Vec::<VInt>::deserialize(&mut data)?
             .into_iter()
             .map(|el| el.0)
             .collect();
         let mut start_offset = 0;
         let mut layers = Vec::new();
         for end_offset in offsets {
+            if end_offset < start_offset || end_offset > data.len() as u64 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("doc store skip index layer offset {end_offset} is out of bounds"),
+                ));
+            }
             let layer = Layer {
                 data: data.slice(start_offset as usize..end_offset as usize),
             };
             layers.push(layer);
             start_offset = end_offset;
         }
-        SkipIndex { layers }
+        Ok(SkipIndex { layers })
     }
 
     pub(crate) fn checkpoints(&self) -> impl Iterator<Item = Checkpoint> + '_ {
@@ -44,7 +44,7 @@ pub use self::writer::StoreWriter;
 mod store_compressor;
 
 /// Doc store version in footer to handle format changes.
-pub(crate) const DOC_STORE_VERSION: DocStoreVersion = DocStoreVersion::V2;
+pub(crate) const DOC_STORE_VERSION: DocStoreVersion = DocStoreVersion::V3;
 
 #[cfg(feature = "lz4-compression")]
 mod compression_lz4_block;
@@ -58,7 +58,7 @@ pub(crate) mod tests {
     use std::path::Path;
 
     use super::*;
-    use crate::directory::{Directory, RamDirectory, WritePtr};
+    use crate::directory::{Directory, FileSlice, RamDirectory, WritePtr};
     use crate::fastfield::AliveBitSet;
     use crate::schema::{
         self, Schema, TantivyDocument, TextFieldIndexing, TextOptions, Value, STORED, TEXT,
@@ -215,6 +215,59 @@ pub(crate) mod tests {
         )
     }
 
+    fn exercise_store(store: &StoreReader, num_docs: u32) {
+        for doc in store.iter::<TantivyDocument>(None) {
+            let _ = doc;
+        }
+        for doc_id in 0..num_docs + 1 {
+            let _ = store.get::<TantivyDocument>(doc_id);
+        }
+    }
+
+    fn test_store_open_untrusted_corrupted(compressor: Compressor) -> crate::Result<()> {
+        let num_docs = 10;
+        let path = Path::new("store");
+        let directory = RamDirectory::create();
+        let store_wrt = directory.open_write(path)?;
+        write_lorem_ipsum_store(store_wrt, num_docs as usize, compressor, 1_000, false);
+        let bytes = directory.atomic_read(path)?;
+        let store = StoreReader::open_untrusted(FileSlice::from(bytes.clone()), 1)?;
+        assert_eq!(
+            store.iter::<TantivyDocument>(None).count(),
+            num_docs as usize
+        );
+        exercise_store(&store, num_docs);
+
+        for len in 0..bytes.len() {
+            if let Ok(store) =
+                StoreReader::open_untrusted(FileSlice::from(bytes[..len].to_vec()), 1)
+            {
+                exercise_store(&store, num_docs);
+            }
+        }
+        for pos in 0..bytes.len() {
+            for mask in [0x01, 0xff] {
+                let mut corrupted = bytes.clone();
+                corrupted[pos] ^= mask;
+                if let Ok(store) = StoreReader::open_untrusted(FileSlice::from(corrupted), 1) {
+                    exercise_store(&store, num_docs);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_open_untrusted_corrupted_no_compression() -> crate::Result<()> {
+        test_store_open_untrusted_corrupted(Compressor::None)
+    }
+
+    #[cfg(feature = "lz4-compression")]
+    #[test]
+    fn test_store_open_untrusted_corrupted_lz4() -> crate::Result<()> {
+        test_store_open_untrusted_corrupted(Compressor::Lz4)
+    }
+
     #[test]
     fn test_store_with_delete() -> crate::Result<()> {
         let mut schema_builder = schema::Schema::builder();
@@ -6,7 +6,7 @@ use std::ops::{AddAssign, Range};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
-use common::{BinarySerializable, OwnedBytes};
+use common::{BinarySerializable, HasLen, OwnedBytes};
 use lru::LruCache;
 
 use super::footer::DocStoreFooter;
@@ -16,6 +16,7 @@ use crate::directory::FileSlice;
 use crate::error::DataCorruption;
 use crate::fastfield::AliveBitSet;
 use crate::schema::document::{BinaryDocumentDeserializer, DocumentDeserialize};
+use crate::schema::Field;
 use crate::space_usage::StoreSpaceUsage;
 use crate::store::index::Checkpoint;
 use crate::DocId;
@@ -31,12 +32,15 @@ type Block = OwnedBytes;
 pub(crate) enum DocStoreVersion {
     V1 = 1,
     V2 = 2,
+    /// The values of the fields are prefixed with their length.
+    V3 = 3,
 }
 impl Display for DocStoreVersion {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             DocStoreVersion::V1 => write!(f, "V1"),
             DocStoreVersion::V2 => write!(f, "V2"),
+            DocStoreVersion::V3 => write!(f, "V3"),
         }
     }
 }
@@ -49,6 +53,7 @@ impl BinarySerializable for DocStoreVersion {
         Ok(match u32::deserialize(reader)? {
             1 => DocStoreVersion::V1,
             2 => DocStoreVersion::V2,
+            3 => DocStoreVersion::V3,
             v => {
                 return Err(io::Error::new(
                     io::ErrorKind::InvalidData,
@@ -157,11 +162,21 @@ impl StoreReader {
     pub fn open(store_file: FileSlice, cache_num_blocks: usize) -> io::Result<StoreReader> {
         let (footer, data_and_offset) = DocStoreFooter::extract_footer(store_file)?;
 
+        if footer.offset > data_and_offset.len() as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "doc store index offset {} exceeds the file length {}",
+                    footer.offset,
+                    data_and_offset.len()
+                ),
+            ));
+        }
         let (data_file, offset_index_file) = data_and_offset.split(footer.offset as usize);
         let index_data = offset_index_file.read_bytes()?;
         let space_usage =
             StoreSpaceUsage::new(data_file.num_bytes(), offset_index_file.num_bytes());
-        let skip_index = SkipIndex::open(index_data);
+        let skip_index = SkipIndex::open(index_data)?;
         Ok(StoreReader {
             decompressor: footer.decompressor,
             doc_store_version: footer.doc_store_version,
@@ -177,6 +192,38 @@ impl StoreReader {
         })
     }
 
+    /// Opens a store reader on a file coming from an untrusted source.
+    ///
+    /// On top of what [`StoreReader::open`] does, this walks through the whole skip index
+    /// and checks that its checkpoints cover contiguous ranges of documents starting at 0,
+    /// and point to blocks within the file. Reading documents then returns an error rather
+    /// than panicking, whatever the content of the file.
+    pub fn open_untrusted(
+        store_file: FileSlice,
+        cache_num_blocks: usize,
+    ) -> io::Result<StoreReader> {
+        let store_reader = StoreReader::open(store_file, cache_num_blocks)?;
+        store_reader.validate_checkpoints()?;
+        Ok(store_reader)
+    }
+
+    fn validate_checkpoints(&self) -> io::Result<()> {
+        let mut previous_checkpoint = Checkpoint::default();
+        for checkpoint in self.block_checkpoints() {
+            if !checkpoint.follows(&previous_checkpoint)
+                || checkpoint.doc_range.is_empty()
+                || checkpoint.byte_range.end > self.data.len()
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("doc store checkpoint {checkpoint:?} is invalid"),
+                ));
+            }
+            previous_checkpoint = checkpoint;
+        }
+        Ok(())
+    }
+
     pub(crate) fn block_checkpoints(&self) -> impl Iterator<Item = Checkpoint> + '_ {
         self.skip_index.checkpoints()
     }
@@ -185,6 +232,10 @@ impl StoreReader {
         self.decompressor
     }
 
+    pub(crate) fn doc_store_version(&self) -> DocStoreVersion {
+        self.doc_store_version
+    }
+
     /// Returns the cache hit and miss statistics of the store reader.
     pub(crate) fn cache_stats(&self) -> CacheStats {
         self.cache.stats()
@@ -196,7 +247,7 @@ impl StoreReader {
     /// Advanced API. In most cases use [`get`](Self::get).
     fn block_checkpoint(&self, doc_id: DocId) -> crate::Result<Checkpoint> {
         self.skip_index.seek(doc_id).ok_or_else(|| {
-            crate::TantivyError::InvalidArgument(format!("Failed to lookup Doc #{doc_id}."))
+            crate::TantivyError::InvalidArgument(format!("Failed to lookup Doc #{doc_id}.").into())
         })
     }
 
@@ -204,8 +255,19 @@ impl StoreReader {
         self.data.read_bytes()
     }
 
+    fn compressed_block_slice(&self, checkpoint: &Checkpoint) -> io::Result<FileSlice> {
+        let byte_range = checkpoint.byte_range.clone();
+        if byte_range.start > byte_range.end || byte_range.end > self.data.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("doc store block {byte_range:?} is out of bounds"),
+            ));
+        }
+        Ok(self.data.slice(byte_range))
+    }
+
     fn get_compressed_block(&self, checkpoint: &Checkpoint) -> io::Result<OwnedBytes> {
-        self.data.slice(checkpoint.byte_range.clone()).read_bytes()
+        self.compressed_block_slice(checkpoint)?.read_bytes()
     }
 
     /// Loads and decompresses a block.
@@ -245,6 +307,25 @@ impl StoreReader {
         D::deserialize(deserializer).map_err(crate::TantivyError::from)
     }
 
+    /// Reads the values of the given fields of a document.
+    ///
+    /// The values of the other fields are skipped without being decoded, which makes
+    /// this cheaper than [`get`](Self::get) to retrieve a few small fields of documents with
+    /// large stored fields. The block containing the document still needs to be decompressed.
+    pub fn get_fields<D: DocumentDeserialize>(
+        &self,
+        doc_id: DocId,
+        fields: &[Field],
+    ) -> crate::Result<D> {
+        let mut doc_bytes = self.get_document_bytes(doc_id)?;
+
+        let deserializer =
+            BinaryDocumentDeserializer::from_reader(&mut doc_bytes, self.doc_store_version)
+                .map_err(crate::TantivyError::from)?
+                .with_fields(fields);
+        D::deserialize(deserializer).map_err(crate::TantivyError::from)
+    }
+
     /// Returns raw bytes of a given document.
     ///
     /// Calling `.get(doc)` is relatively costly as it requires
@@ -265,7 +346,13 @@ impl StoreReader {
         doc_id: DocId,
         checkpoint: &Checkpoint,
     ) -> crate::Result<OwnedBytes> {
-        let doc_pos = doc_id - checkpoint.doc_range.start;
+        let doc_pos = doc_id
+            .checked_sub(checkpoint.doc_range.start)
+            .ok_or_else(|| {
+                DataCorruption::comment_only(format!(
+                    "doc store checkpoint {checkpoint:?} does not contain Doc #{doc_id}"
+                ))
+            })?;
 
         let range = block_read_index(&block, doc_pos)?;
         Ok(block.slice(range))
@@ -312,7 +399,10 @@ impl StoreReader {
                 // the outer variables
 
                 // check move to next checkpoint
-                if doc_id >= curr_checkpoint.as_ref().unwrap().doc_range.end {
+                if curr_checkpoint
+                    .as_ref()
+                    .is_some_and(|checkpoint| doc_id >= checkpoint.doc_range.end)
+                {
                     curr_checkpoint = checkpoint_block_iter.next();
                     curr_block = curr_checkpoint
                         .as_ref()
@@ -356,7 +446,12 @@ fn block_read_index(block: &[u8], doc_pos: u32) -> crate::Result<Range<usize>> {
     let doc_pos = doc_pos as usize;
     let size_of_u32 = std::mem::size_of::<u32>();
 
-    let index_len_pos = block.len() - size_of_u32;
+    let corrupted_index = || DataCorruption::comment_only("doc store block index is corrupted");
+
+    let index_len_pos = block
+        .len()
+        .checked_sub(size_of_u32)
+        .ok_or_else(corrupted_index)?;
     let index_len = u32::deserialize(&mut &block[index_len_pos..])? as usize;
 
     if doc_pos > index_len {
@@ -365,12 +460,18 @@ fn block_read_index(block: &[u8], doc_pos: u32) -> crate::Result<Range<usize>> {
         ));
     }
 
-    let index_start = block.len() - (index_len + 1) * size_of_u32;
+    let index_start = (index_len + 1)
+        .checked_mul(size_of_u32)
+        .and_then(|index_num_bytes| block.len().checked_sub(index_num_bytes))
+        .ok_or_else(corrupted_index)?;
     let index = &block[index_start..index_start + index_len * size_of_u32];
 
     let start_offset = u32::deserialize(&mut &index[doc_pos * size_of_u32..])? as usize;
     let end_offset = u32::deserialize(&mut &index[(doc_pos + 1) * size_of_u32..])
         .unwrap_or(index_start as u32) as usize;
+    if start_offset > end_offset || end_offset > index_start {
+        return Err(corrupted_index().into());
+    }
     Ok(start_offset..end_offset)
 }
 
@@ -392,8 +493,7 @@ impl StoreReader {
         }
 
         let compressed_block = self
-            .data
-            .slice(checkpoint.byte_range.clone())
+            .compressed_block_slice(checkpoint)?
             .read_bytes_async()
             .await?;
 
@@ -456,6 +556,30 @@ mod tests {
     #[test]
     fn test_doc_store_version_ord() {
         assert!(DocStoreVersion::V1 < DocStoreVersion::V2);
+        assert!(DocStoreVersion::V2 < DocStoreVersion::V3);
+    }
+
+    #[test]
+    fn test_store_get_fields() -> crate::Result<()> {
+        let directory = RamDirectory::create();
+        let path = Path::new("store");
+        let writer = directory.open_write(path)?;
+        let schema = write_lorem_ipsum_store(writer, 10, Compressor::default(), BLOCK_SIZE, true);
+        let body = schema.get_field("body").unwrap();
+        let title = schema.get_field("title").unwrap();
+        let store_file = directory.open_read(path)?;
+        let store = StoreReader::open(store_file, DOCSTORE_CACHE_CAPACITY)?;
+
+        let doc: TantivyDocument = store.get_fields(3, &[title])?;
+        assert_eq!(doc.len(), 1);
+        assert_eq!(get_text_field(&doc, &title), Some("Doc 3"));
+
+        let doc: TantivyDocument = store.get_fields(3, &[body, title])?;
+        assert_eq!(doc, store.get::<TantivyDocument>(3)?);
+
+        let doc: TantivyDocument = store.get_fields(3, &[])?;
+        assert_eq!(doc.len(), 0);
+        Ok(())
     }
 
     #[test]
@@ -497,7 +621,7 @@ mod tests {
         assert_eq!(store.cache_stats().cache_hits, 1);
         assert_eq!(store.cache_stats().cache_misses, 2);
 
-        assert_eq!(store.cache.peek_lru(), Some(11207));
+        assert_eq!(store.cache.peek_lru(), Some(11337));
 
         Ok(())
     }
@@ -0,0 +1,205 @@
+//! Bloom filters of the terms of a field.
+//!
+//! For the fields with [bloom filters](crate::schema::TextFieldIndexing::set_bloom_filter),
+//! the term dictionary composite file holds, next to the dictionary of the field, a bloom filter
+//! of its terms. Looking a term up first checks the filter, so that segments which do not
+//! contain the term are skipped without seeking in their dictionary.
+//!
+//! The filter uses 10 bits per term and 7 hash functions, for a false positive rate of about
+//! 1%. It is serialized as:
+//! - the number of hash functions, as a `u8`,
+//! - the number of 64 bits words of the filter, as a `u64`,
+//! - the words of the filter, in little endian.
+use std::hash::Hasher;
+use std::io::{self, Write};
+
+use common::{BinarySerializable, OwnedBytes};
+use fnv::FnvHasher;
+
+const BITS_PER_KEY: u64 = 10;
+const NUM_HASHES: u8 = 7;
+
+fn hash_key(key: &[u8]) -> u64 {
+    let mut hasher = FnvHasher::default();
+    hasher.write(key);
+    // FNV mixes the last bytes poorly, which double hashing would amplify.
+    mix64(hasher.finish())
+}
+
+/// Bits of the filter a key hash maps to, given the number of bits of the filter.
+fn bit_positions(hash: u64, num_hashes: u8, num_bits: u64) -> impl Iterator<Item = u64> {
+    let delta = mix64(hash ^ 0x9e37_79b9_7f4a_7c15) | 1;
+    (0..u64::from(num_hashes)).map(move |i| hash.wrapping_add(i.wrapping_mul(delta)) % num_bits)
+}
+
+/// Finalizer of MurmurHash3.
+fn mix64(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33)
+}
+
+/// Builds the bloom filter of the terms of a field, as they get serialized.
+#[derive(Default)]
+pub(crate) struct BloomFilterBuilder {
+    key_hashes: Vec<u64>,
+}
+
+impl BloomFilterBuilder {
+    pub fn insert(&mut self, key: &[u8]) {
+        self.key_hashes.push(hash_key(key));
+    }
+
+    pub fn serialize<W: Write>(&self, wrt: &mut W) -> io::Result<()> {
+        let num_words = (self.key_hashes.len() as u64 * BITS_PER_KEY)
+            .div_ceil(64)
+            .max(1);
+        let num_bits = num_words * 64;
+        let mut words = vec![0u64; num_words as usize];
+        for &hash in &self.key_hashes {
+            for bit in bit_positions(hash, NUM_HASHES, num_bits) {
+                words[(bit / 64) as usize] |= 1u64 << (bit % 64);
+            }
+        }
+        NUM_HASHES.serialize(wrt)?;
+        num_words.serialize(wrt)?;
+        for word in words {
+            wrt.write_all(&word.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// Bloom filter of the terms of a field in a segment.
+#[derive(Clone)]
+pub(crate) struct BloomFilter {
+    num_hashes: u8,
+    words: OwnedBytes,
+}
+
+impl BloomFilter {
+    pub fn open(mut data: OwnedBytes) -> io::Result<BloomFilter> {
+        let num_hashes = u8::deserialize(&mut data)?;
+        let num_words = u64::deserialize(&mut data)?;
+        if num_hashes == 0 || num_words == 0 || data.len() as u64 != num_words.saturating_mul(8) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "The bloom filter is corrupted.",
+            ));
+        }
+        Ok(BloomFilter {
+            num_hashes,
+            words: data,
+        })
+    }
+
+    /// Returns false if the key is definitely not part of the filter.
+    pub fn may_contain(&self, key: &[u8]) -> bool {
+        let words = self.words.as_slice();
+        let num_bits = words.len() as u64 * 8;
+        bit_positions(hash_key(key), self.num_hashes, num_bits).all(|bit| {
+            let byte = words[(bit / 8) as usize];
+            byte & (1u8 << (bit % 8)) != 0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::OwnedBytes;
+
+    use super::{BloomFilter, BloomFilterBuilder};
+    use crate::collector::Count;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, NumericOptions, Schema, INDEXED, STRING};
+    use crate::{Index, IndexWriter, Term};
+
+    #[test]
+    fn test_bloom_filter() {
+        let mut builder = BloomFilterBuilder::default();
+        for i in 0..10_000u32 {
+            builder.insert(format!("key-{i}").as_bytes());
+        }
+        let mut buffer = Vec::new();
+        builder.serialize(&mut buffer).unwrap();
+        let bloom_filter = BloomFilter::open(OwnedBytes::new(buffer)).unwrap();
+        for i in 0..10_000u32 {
+            assert!(bloom_filter.may_contain(format!("key-{i}").as_bytes()));
+        }
+        let num_false_positives = (10_000..20_000u32)
+            .filter(|i| bloom_filter.may_contain(format!("key-{i}").as_bytes()))
+            .count();
+        assert!(num_false_positives < 300, "{num_false_positives}");
+    }
+
+    #[test]
+    fn test_bloom_filter_empty() {
+        let mut buffer = Vec::new();
+        BloomFilterBuilder::default()
+            .serialize(&mut buffer)
+            .unwrap();
+        let bloom_filter = BloomFilter::open(OwnedBytes::new(buffer)).unwrap();
+        assert!(!bloom_filter.may_contain(b"key"));
+        assert!(BloomFilter::open(OwnedBytes::new(vec![7u8, 1u8])).is_err());
+    }
+
+    #[test]
+    fn test_segment_bloom_filter() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder
+            .add_u64_field("id", NumericOptions::from(INDEXED).set_bloom_filter(true));
+        let tag = schema_builder.add_text_field("tag", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        for segment in 0..2u64 {
+            for i in 0..100u64 {
+                index_writer.add_document(doc!(id => segment * 100 + i, tag => "a"))?;
+            }
+            index_writer.commit()?;
+        }
+
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+        let id_term = |i: u64| Term::from_field_u64(id, i);
+        let mut num_skipped = 0;
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(id)?;
+            for i in 0..200u64 {
+                let has_term = inverted_index
+                    .terms()
+                    .get(id_term(i).serialized_value_bytes())?
+                    .is_some();
+                if has_term {
+                    assert!(inverted_index.may_contain_term(&id_term(i)));
+                } else if !inverted_index.may_contain_term(&id_term(i)) {
+                    num_skipped += 1;
+                }
+            }
+            // Fields without bloom filters always need a lookup.
+            let tag_inverted_index = segment_reader.inverted_index(tag)?;
+            assert!(tag_inverted_index.may_contain_term(&Term::from_field_text(tag, "b")));
+        }
+        assert!(num_skipped > 180, "{num_skipped}");
+        for i in [0, 99, 100, 199] {
+            let query = TermQuery::new(id_term(i), IndexRecordOption::Basic);
+            assert_eq!(searcher.search(&query, &Count)?, 1);
+        }
+
+        // Merged segments have the bloom filter of all of the terms.
+        let segment_ids = index.searchable_segment_ids()?;
+        index_writer.merge(&segment_ids).wait()?;
+        let reader = index.reader()?;
+        reader.reload()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 1);
+        let inverted_index = searcher.segment_reader(0).inverted_index(id)?;
+        assert!((0..200).all(|i| inverted_index.may_contain_term(&id_term(i))));
+        let num_skipped = (200..400)
+            .filter(|&i| !inverted_index.may_contain_term(&id_term(i)))
+            .count();
+        assert!(num_skipped > 180, "{num_skipped}");
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use std::cmp;
 use std::io::{self, Read, Write};
 
 use byteorder::{ByteOrder, LittleEndian};
-use common::{BinarySerializable, FixedSize};
+use common::{BinarySerializable, FixedSize, HasLen};
 use tantivy_bitpacker::{compute_num_bits, BitPacker};
 
 use crate::directory::{FileSlice, OwnedBytes};
@@ -37,6 +37,12 @@ impl BinarySerializable for TermInfoBlockMeta {
         let ref_term_info = TermInfo::deserialize(reader)?;
         let mut buffer = [0u8; 3];
         reader.read_exact(&mut buffer)?;
+        if buffer.iter().any(|&num_bits| num_bits > 56) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Term info block uses more than 56 bits per value",
+            ));
+        }
         Ok(TermInfoBlockMeta {
             offset,
             ref_term_info,
@@ -57,6 +63,17 @@ impl TermInfoBlockMeta {
         self.doc_freq_nbits + self.postings_offset_nbits + self.positions_offset_nbits
     }
 
+    /// Returns true if the term infos of a block of `num_terms` terms, followed by its end
+    /// offsets, fit in the `len` bytes of term infos.
+    fn fits_in(&self, num_terms: usize, len: usize) -> bool {
+        let num_bits = (num_terms as u64 - 1) * self.num_bits() as u64
+            + self.postings_offset_nbits as u64
+            + self.positions_offset_nbits as u64;
+        self.offset
+            .checked_add(num_bits.div_ceil(8))
+            .is_some_and(|end| end <= len as u64)
+    }
+
     // Here inner_offset is the offset within the block, WITHOUT the first term_info.
     // In other word, term_info #1,#2,#3 gets inner_offset 0,1,2... While term_info #0
     // is encoded without bitpacking.
@@ -73,15 +90,37 @@ impl TermInfoBlockMeta {
 
         let doc_freq_addr = positions_start_addr + self.positions_offset_nbits as usize;
 
-        let postings_start_offset = self.ref_term_info.postings_range.start
-            + extract_bits(data, posting_start_addr, self.postings_offset_nbits) as usize;
-        let postings_end_offset = self.ref_term_info.postings_range.start
-            + extract_bits(data, posting_end_addr, self.postings_offset_nbits) as usize;
-
-        let positions_start_offset = self.ref_term_info.positions_range.start
-            + extract_bits(data, positions_start_addr, self.positions_offset_nbits) as usize;
-        let positions_end_offset = self.ref_term_info.positions_range.start
-            + extract_bits(data, positions_end_addr, self.positions_offset_nbits) as usize;
+        // Offsets of corrupted data may overflow. The resulting ranges are checked when
+        // the postings are opened.
+        let postings_start_offset =
+            self.ref_term_info
+                .postings_range
+                .start
+                .wrapping_add(
+                    extract_bits(data, posting_start_addr, self.postings_offset_nbits) as usize,
+                );
+        let postings_end_offset =
+            self.ref_term_info
+                .postings_range
+                .start
+                .wrapping_add(
+                    extract_bits(data, posting_end_addr, self.postings_offset_nbits) as usize,
+                );
+
+        let positions_start_offset =
+            self.ref_term_info
+                .positions_range
+                .start
+                .wrapping_add(
+                    extract_bits(data, positions_start_addr, self.positions_offset_nbits) as usize,
+                );
+        let positions_end_offset =
+            self.ref_term_info
+                .positions_range
+                .start
+                .wrapping_add(
+                    extract_bits(data, positions_end_addr, self.positions_offset_nbits) as usize,
+                );
 
         let doc_freq = extract_bits(data, doc_freq_addr, self.doc_freq_nbits) as u32;
 
@@ -123,15 +162,40 @@ fn extract_bits(data: &[u8], addr_bits: usize, num_bits: u8) -> u64 {
 
 impl TermInfoStore {
     pub fn open(term_info_store_file: FileSlice) -> io::Result<TermInfoStore> {
+        let invalid_data = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+        if term_info_store_file.len() < 16 {
+            return Err(invalid_data("Term info store is truncated"));
+        }
         let (len_slice, main_slice) = term_info_store_file.split(16);
         let mut bytes = len_slice.read_bytes()?;
         let len = u64::deserialize(&mut bytes)? as usize;
         let num_terms = u64::deserialize(&mut bytes)? as usize;
+        if len > main_slice.len() {
+            return Err(invalid_data("Term info store block metas are truncated"));
+        }
+        let num_blocks = num_terms.div_ceil(BLOCK_LEN);
+        if num_blocks
+            .checked_mul(TermInfoBlockMeta::SIZE_IN_BYTES)
+            .map_or(true, |block_metas_len| block_metas_len > len)
+        {
+            return Err(invalid_data(
+                "Term info store has fewer block metas than terms",
+            ));
+        }
         let (block_meta_file, term_info_file) = main_slice.split(len);
+        let block_meta_bytes = block_meta_file.read_bytes()?;
         let term_info_bytes = term_info_file.read_bytes()?;
+        let mut block_metas: &[u8] = block_meta_bytes.as_slice();
+        for block_id in 0..num_blocks {
+            let block_meta = TermInfoBlockMeta::deserialize(&mut block_metas)?;
+            let num_terms_in_block = cmp::min(num_terms - block_id * BLOCK_LEN, BLOCK_LEN);
+            if !block_meta.fits_in(num_terms_in_block, term_info_bytes.len()) {
+                return Err(invalid_data("Term info store term infos are truncated"));
+            }
+        }
         Ok(TermInfoStore {
             num_terms,
-            block_meta_bytes: block_meta_file.read_bytes()?,
+            block_meta_bytes,
             term_info_bytes,
         })
     }
@@ -140,6 +204,7 @@ impl TermInfoStore {
         let block_id = (term_ord as usize) / BLOCK_LEN;
         let buffer = self.block_meta_bytes.as_slice();
         let mut block_data: &[u8] = &buffer[block_id * TermInfoBlockMeta::SIZE_IN_BYTES..];
+        // `open` checked every block meta, and the term infos they point to.
         let term_info_block_data = TermInfoBlockMeta::deserialize(&mut block_data)
             .expect("Failed to deserialize terminfoblockmeta");
         let inner_offset = (term_ord as usize) % BLOCK_LEN;
@@ -364,4 +429,27 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_open_truncated() -> crate::Result<()> {
+        let mut store_writer = TermInfoStoreWriter::new();
+        for i in 0usize..300usize {
+            store_writer.write_term_info(&TermInfo {
+                doc_freq: i as u32,
+                postings_range: i * 10..(i + 1) * 10,
+                positions_range: i * 30..(i + 1) * 30,
+            })?;
+        }
+        let mut buffer = Vec::new();
+        store_writer.serialize(&mut buffer)?;
+        for len in 0..buffer.len() {
+            let file_slice = FileSlice::from(buffer[..len].to_vec());
+            assert!(TermInfoStore::open(file_slice).is_err(), "len {len}");
+        }
+        let term_info_store = TermInfoStore::open(FileSlice::from(buffer))?;
+        for term_ord in 0..300 {
+            term_info_store.get(term_ord);
+        }
+        Ok(())
+    }
 }
@@ -28,6 +28,8 @@ mod sstable_termdict;
 #[cfg(feature = "quickwit")]
 use sstable_termdict as termdict;
 
+pub(crate) mod bloom_filter;
+mod numeric_term_streamer;
 #[cfg(test)]
 mod tests;
 
@@ -35,11 +37,13 @@ mod tests;
 pub type TermOrdinal = u64;
 
 use std::io;
+use std::ops::{Bound, RangeBounds};
 
 use common::file_slice::FileSlice;
 use common::BinarySerializable;
 use tantivy_fst::Automaton;
 
+pub use self::numeric_term_streamer::{NumericTermStreamer, NumericTermValue};
 use self::termdict::{
     TermDictionary as InnerTermDict, TermDictionaryBuilder as InnerTermDictBuilder,
     TermStreamerBuilder,
@@ -144,12 +148,62 @@ impl TermDictionary {
         self.0.get(key)
     }
 
+    /// Lookups the values corresponding to several keys at once.
+    ///
+    /// The keys are looked up once each, in sorted order, so that the lookups of neighbouring
+    /// keys hit the same blocks of the dictionary. The values are returned in the order of the
+    /// keys.
+    pub fn get_many<K: AsRef<[u8]>>(&self, keys: &[K]) -> io::Result<Vec<Option<TermInfo>>> {
+        let mut key_ords: Vec<usize> = (0..keys.len()).collect();
+        key_ords.sort_by(|&left, &right| keys[left].as_ref().cmp(keys[right].as_ref()));
+        let mut term_infos = vec![None; keys.len()];
+        let mut previous_key_ord: Option<usize> = None;
+        for key_ord in key_ords {
+            let key = keys[key_ord].as_ref();
+            term_infos[key_ord] = match previous_key_ord {
+                Some(previous_key_ord) if keys[previous_key_ord].as_ref() == key => {
+                    term_infos[previous_key_ord].clone()
+                }
+                _ => self.get(key)?,
+            };
+            previous_key_ord = Some(key_ord);
+        }
+        Ok(term_infos)
+    }
+
     /// Returns a range builder, to stream all of the terms
     /// within an interval.
     pub fn range(&self) -> TermStreamerBuilder<'_> {
         self.0.range()
     }
 
+    /// Streams the terms of a numeric, date, bool or ip address field whose value is within the
+    /// given range, in increasing order.
+    ///
+    /// The range is translated into bounds on the encoded terms, so that only the terms within
+    /// the range are visited, and the values are decoded from the keys of the dictionary.
+    ///
+    /// The dictionary is expected to be the dictionary of a field of the type of `T`. Note that
+    /// date terms are truncated to
+    /// [`DATE_TIME_PRECISION_INDEXED`](crate::schema::DATE_TIME_PRECISION_INDEXED).
+    pub fn stream_range<T: NumericTermValue>(
+        &self,
+        range: impl RangeBounds<T>,
+    ) -> io::Result<NumericTermStreamer<'_, T>> {
+        let mut term_stream_builder = self.range();
+        term_stream_builder = match range.start_bound() {
+            Bound::Included(lower) => term_stream_builder.ge(lower.to_term_bytes()),
+            Bound::Excluded(lower) => term_stream_builder.gt(lower.to_term_bytes()),
+            Bound::Unbounded => term_stream_builder,
+        };
+        term_stream_builder = match range.end_bound() {
+            Bound::Included(upper) => term_stream_builder.le(upper.to_term_bytes()),
+            Bound::Excluded(upper) => term_stream_builder.lt(upper.to_term_bytes()),
+            Bound::Unbounded => term_stream_builder,
+        };
+        Ok(NumericTermStreamer::new(term_stream_builder.into_stream()?))
+    }
+
     /// A stream of all the sorted terms.
     pub fn stream(&self) -> io::Result<TermStreamer<'_>> {
         self.0.stream()
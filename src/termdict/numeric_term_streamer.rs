@@ -0,0 +1,102 @@
+use std::net::Ipv6Addr;
+
+use columnar::MonotonicallyMappableToU128;
+
+use super::{TermOrdinal, TermStreamer};
+use crate::fastfield::FastValue;
+use crate::postings::TermInfo;
+
+/// Value of an indexed numeric, date, bool or ip address field.
+///
+/// These values are encoded in the term dictionary as the big endian bytes of an order
+/// preserving mapping to `u64` (or `u128` for ip addresses), so that the order of the terms in
+/// the dictionary is the order of the values.
+pub trait NumericTermValue: Copy + Sized {
+    /// Encoded bytes of the value.
+    type Bytes: AsRef<[u8]>;
+
+    /// Encodes the value as it is stored in the term dictionary.
+    fn to_term_bytes(self) -> Self::Bytes;
+
+    /// Decodes a key of the term dictionary.
+    ///
+    /// Returns `None` if the key does not have the length of an encoded value.
+    fn from_term_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+impl<T: FastValue> NumericTermValue for T {
+    type Bytes = [u8; 8];
+
+    fn to_term_bytes(self) -> [u8; 8] {
+        self.to_u64().to_be_bytes()
+    }
+
+    fn from_term_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: [u8; 8] = bytes.try_into().ok()?;
+        Some(T::from_u64(u64::from_be_bytes(bytes)))
+    }
+}
+
+impl NumericTermValue for Ipv6Addr {
+    type Bytes = [u8; 16];
+
+    fn to_term_bytes(self) -> [u8; 16] {
+        self.to_u128().to_be_bytes()
+    }
+
+    fn from_term_bytes(bytes: &[u8]) -> Option<Self> {
+        let bytes: [u8; 16] = bytes.try_into().ok()?;
+        Some(Ipv6Addr::from_u128(u128::from_be_bytes(bytes)))
+    }
+}
+
+/// Streams the terms of a numeric field within a range of values, decoding the values on the
+/// fly.
+///
+/// See [`TermDictionary::stream_range()`](super::TermDictionary::stream_range).
+pub struct NumericTermStreamer<'a, T> {
+    streamer: TermStreamer<'a>,
+    value: Option<T>,
+}
+
+impl<'a, T: NumericTermValue> NumericTermStreamer<'a, T> {
+    pub(super) fn new(streamer: TermStreamer<'a>) -> Self {
+        NumericTermStreamer {
+            streamer,
+            value: None,
+        }
+    }
+
+    /// Advances the stream to the next term.
+    ///
+    /// Returns false once the end of the range is reached.
+    pub fn advance(&mut self) -> bool {
+        while self.streamer.advance() {
+            // Keys which are not encoded values cannot be part of a numeric field.
+            if let Some(value) = T::from_term_bytes(self.streamer.key()) {
+                self.value = Some(value);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Returns the value of the current term.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `.advance()` has never returned true.
+    pub fn value(&self) -> T {
+        self.value.expect("advance() must be called before value()")
+    }
+
+    /// Returns the `TermOrdinal` of the current term.
+    pub fn term_ord(&self) -> TermOrdinal {
+        self.streamer.term_ord()
+    }
+
+    /// Returns the `TermInfo` of the current term.
+    pub fn term_info(&self) -> &TermInfo {
+        self.streamer.value()
+    }
+}
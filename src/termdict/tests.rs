@@ -1,7 +1,9 @@
+use std::net::Ipv6Addr;
+use std::ops::Bound;
 use std::path::PathBuf;
 use std::{io, str};
 
-use super::{TermDictionary, TermDictionaryBuilder, TermStreamer};
+use super::{NumericTermValue, TermDictionary, TermDictionaryBuilder, TermStreamer};
 use crate::directory::{Directory, FileSlice, RamDirectory, TerminatingWrite};
 use crate::postings::TermInfo;
 
@@ -92,6 +94,34 @@ fn test_term_dictionary_simple() -> crate::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_term_dictionary_get_many() -> crate::Result<()> {
+    let directory = RamDirectory::create();
+    let path = PathBuf::from("TermDictionary");
+    {
+        let write = directory.open_write(&path)?;
+        let mut term_dictionary_builder = TermDictionaryBuilder::create(write)?;
+        for i in 0u64..1_000u64 {
+            let key = format!("key{i:0>4}");
+            term_dictionary_builder.insert(key.as_bytes(), &make_term_info(i))?;
+        }
+        term_dictionary_builder.finish()?.terminate()?;
+    }
+    let file = directory.open_read(&path)?;
+    let term_dict: TermDictionary = TermDictionary::open(file)?;
+    let keys = ["key0900", "missing", "key0003", "key0900", "key0500", "key"];
+    let term_infos = term_dict.get_many(&keys)?;
+    assert_eq!(term_infos.len(), keys.len());
+    for (key, term_info) in keys.iter().zip(&term_infos) {
+        assert_eq!(term_info, &term_dict.get(key)?);
+    }
+    assert_eq!(term_infos[0], Some(make_term_info(900)));
+    assert_eq!(term_infos[1], None);
+    assert_eq!(term_infos[2], Some(make_term_info(3)));
+    assert!(term_dict.get_many::<&str>(&[])?.is_empty());
+    Ok(())
+}
+
 #[test]
 fn test_term_dictionary_stream() -> crate::Result<()> {
     let ids: Vec<_> = (0u32..10_000u32)
@@ -429,3 +459,66 @@ fn test_automaton_search() -> crate::Result<()> {
     assert!(!range.advance());
     Ok(())
 }
+
+fn collect_numeric_range<T: NumericTermValue>(
+    term_dict: &TermDictionary,
+    range: impl std::ops::RangeBounds<T>,
+) -> io::Result<Vec<(T, u64)>> {
+    let mut values = Vec::new();
+    let mut stream = term_dict.stream_range(range)?;
+    while stream.advance() {
+        assert_eq!(stream.term_info(), &make_term_info(stream.term_ord()));
+        values.push((stream.value(), stream.term_ord()));
+    }
+    Ok(values)
+}
+
+#[test]
+fn test_stream_numeric_range() -> crate::Result<()> {
+    let values: Vec<i64> = (-50..50).map(|val| val * 3).collect();
+    let directory = RamDirectory::create();
+    let path = PathBuf::from("TermDictionary");
+    {
+        let write = directory.open_write(&path)?;
+        let mut term_dictionary_builder = TermDictionaryBuilder::create(write)?;
+        for (term_ord, val) in values.iter().enumerate() {
+            term_dictionary_builder
+                .insert(val.to_term_bytes(), &make_term_info(term_ord as u64))?;
+        }
+        term_dictionary_builder.finish()?.terminate()?;
+    }
+    let term_dict = TermDictionary::open(directory.open_read(&path)?)?;
+
+    let in_range = collect_numeric_range(&term_dict, -4i64..=6)?;
+    assert_eq!(in_range, vec![(-3, 49), (0, 50), (3, 51), (6, 52)]);
+    let in_range = collect_numeric_range(&term_dict, -3i64..6)?;
+    assert_eq!(in_range, vec![(-3, 49), (0, 50), (3, 51)]);
+    let in_range = collect_numeric_range(&term_dict, (Bound::Excluded(144i64), Bound::Unbounded))?;
+    assert_eq!(in_range, vec![(147, 99)]);
+    assert_eq!(collect_numeric_range::<i64>(&term_dict, ..)?.len(), 100);
+    assert!(collect_numeric_range(&term_dict, 1i64..3)?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_stream_ip_addr_range() -> crate::Result<()> {
+    let ip_addrs: Vec<Ipv6Addr> = (0u16..20)
+        .map(|i| Ipv6Addr::new(0, 0, 0, 0, 0, 0xffff, 0xc00a, i * 0x100))
+        .collect();
+    let directory = RamDirectory::create();
+    let path = PathBuf::from("TermDictionary");
+    {
+        let write = directory.open_write(&path)?;
+        let mut term_dictionary_builder = TermDictionaryBuilder::create(write)?;
+        for (term_ord, ip_addr) in ip_addrs.iter().enumerate() {
+            term_dictionary_builder
+                .insert(ip_addr.to_term_bytes(), &make_term_info(term_ord as u64))?;
+        }
+        term_dictionary_builder.finish()?.terminate()?;
+    }
+    let term_dict = TermDictionary::open(directory.open_read(&path)?)?;
+
+    let in_range = collect_numeric_range(&term_dict, ip_addrs[3]..ip_addrs[5])?;
+    assert_eq!(in_range, vec![(ip_addrs[3], 3), (ip_addrs[4], 4)]);
+    Ok(())
+}
@@ -0,0 +1,377 @@
+//! Relevance test harness.
+//!
+//! This module makes it possible to catch relevance regressions by indexing a fixture corpus,
+//! running a suite of golden queries against it, and comparing the ranked results with
+//! snapshots recorded earlier.
+//!
+//! It is only available with the `test-utils` feature.
+//!
+//! ```rust
+//! use tantivy::schema::{Schema, STORED, STRING, TEXT};
+//! use tantivy::test_utils::{index_fixture, RelevanceHarness};
+//!
+//! # fn main() -> tantivy::Result<()> {
+//! let mut schema_builder = Schema::builder();
+//! let id = schema_builder.add_text_field("id", STRING | STORED);
+//! let title = schema_builder.add_text_field("title", TEXT);
+//! let index = index_fixture(
+//!     schema_builder.build(),
+//!     [
+//!         r#"{"id": "frankenstein", "title": "Frankenstein, or the Modern Prometheus"}"#,
+//!         r#"{"id": "prometheus", "title": "Prometheus Unbound"}"#,
+//!     ],
+//! )?;
+//! let harness = RelevanceHarness::new(&index, id, vec![title])?;
+//! // Snapshots are typically recorded once, serialized to a file and checked in.
+//! let golden_queries = harness.snapshot(&["prometheus"], 10)?;
+//! harness.assert_golden(&golden_queries)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::collector::TopDocs;
+use crate::query::QueryParser;
+use crate::schema::{Field, Schema, Value};
+use crate::{DocAddress, Index, IndexWriter, Score, Searcher, TantivyDocument, TantivyError};
+
+/// Default tolerance used when comparing scores.
+pub const DEFAULT_SCORE_TOLERANCE: Score = 1e-4;
+
+/// Memory budget of the fixture index writer, large enough for the segment not to be flushed
+/// before the commit.
+const FIXTURE_MEMORY_BUDGET_NUM_BYTES: usize = 1 << 30;
+
+/// Creates an index in RAM out of a fixture corpus, given as one JSON object per document.
+///
+/// Documents are indexed by a single thread, in order, and committed in a single segment,
+/// so that document ids, and therefore the ordering of ties, are deterministic.
+///
+/// Returns an error if the corpus does not fit in a single segment.
+pub fn index_fixture<'a>(
+    schema: Schema,
+    json_docs: impl IntoIterator<Item = &'a str>,
+) -> crate::Result<Index> {
+    let index = Index::create_in_ram(schema.clone());
+    let mut index_writer: IndexWriter =
+        index.writer_with_num_threads(1, FIXTURE_MEMORY_BUDGET_NUM_BYTES)?;
+    for json_doc in json_docs {
+        let doc = TantivyDocument::parse_json(&schema, json_doc)?;
+        index_writer.add_document(doc)?;
+    }
+    index_writer.commit()?;
+    let num_segments = index.searchable_segment_ids()?.len();
+    if num_segments > 1 {
+        return Err(TantivyError::InvalidArgument(
+            format!("The fixture corpus was indexed in {num_segments} segments instead of one.")
+                .into(),
+        ));
+    }
+    Ok(index)
+}
+
+/// A hit expected in the results of a [`GoldenQuery`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExpectedHit {
+    /// Identifier of the document, as found in the id field of the harness.
+    pub id: String,
+    /// Expected score of the document.
+    ///
+    /// If `None`, only the rank of the document is checked.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<Score>,
+}
+
+/// A query along with its expected ranked results.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GoldenQuery {
+    /// The query, in the query parser syntax.
+    pub query: String,
+    /// The expected hits, best first.
+    ///
+    /// The query is expected to return exactly these hits, in this order.
+    pub hits: Vec<ExpectedHit>,
+}
+
+/// A mismatch between the results of a golden query and its snapshot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RelevanceMismatch {
+    /// The query.
+    pub query: String,
+    /// The expected hits.
+    pub expected: Vec<ExpectedHit>,
+    /// The actual hits.
+    pub actual: Vec<ExpectedHit>,
+}
+
+impl fmt::Display for RelevanceMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "query `{}`:", self.query)?;
+        let num_rows = self.expected.len().max(self.actual.len());
+        for rank in 0..num_rows {
+            let expected = self.expected.get(rank).map(format_hit).unwrap_or_default();
+            let actual = self.actual.get(rank).map(format_hit).unwrap_or_default();
+            let marker = if expected == actual { ' ' } else { '!' };
+            writeln!(
+                f,
+                "{marker} #{rank:<3} expected {expected:<30} got {actual}"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn format_hit(hit: &ExpectedHit) -> String {
+    match hit.score {
+        Some(score) => format!("{} ({score})", hit.id),
+        None => hit.id.clone(),
+    }
+}
+
+/// Runs golden queries against an index and compares their results with snapshots.
+pub struct RelevanceHarness {
+    searcher: Searcher,
+    query_parser: QueryParser,
+    id_field: Field,
+    score_tolerance: Score,
+}
+
+impl RelevanceHarness {
+    /// Creates a new harness.
+    ///
+    /// `id_field` is a stored field identifying documents in snapshots, and
+    /// `default_fields` are the fields queries search by default.
+    pub fn new(
+        index: &Index,
+        id_field: Field,
+        default_fields: Vec<Field>,
+    ) -> crate::Result<RelevanceHarness> {
+        let searcher = index.reader()?.searcher();
+        Ok(RelevanceHarness {
+            searcher,
+            query_parser: QueryParser::for_index(index, default_fields),
+            id_field,
+            score_tolerance: DEFAULT_SCORE_TOLERANCE,
+        })
+    }
+
+    /// Sets the tolerance used when comparing scores.
+    ///
+    /// Defaults to [`DEFAULT_SCORE_TOLERANCE`].
+    pub fn score_tolerance(mut self, score_tolerance: Score) -> RelevanceHarness {
+        self.score_tolerance = score_tolerance;
+        self
+    }
+
+    /// Runs a query and returns its top `limit` hits.
+    pub fn search(&self, query: &str, limit: usize) -> crate::Result<Vec<ExpectedHit>> {
+        let query = self.query_parser.parse_query(query)?;
+        let top_docs = self.searcher.search(&query, &TopDocs::with_limit(limit))?;
+        top_docs
+            .into_iter()
+            .map(|(score, doc_address)| {
+                Ok(ExpectedHit {
+                    id: self.doc_id(doc_address)?,
+                    score: Some(score),
+                })
+            })
+            .collect()
+    }
+
+    /// Records the current results of the given queries, to be used as golden queries.
+    pub fn snapshot(&self, queries: &[&str], limit: usize) -> crate::Result<Vec<GoldenQuery>> {
+        queries
+            .iter()
+            .map(|query| {
+                Ok(GoldenQuery {
+                    query: query.to_string(),
+                    hits: self.search(query, limit)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Runs the golden queries, and returns the list of queries whose results
+    /// do not match their snapshot.
+    pub fn run(&self, golden_queries: &[GoldenQuery]) -> crate::Result<Vec<RelevanceMismatch>> {
+        let mut mismatches = Vec::new();
+        for golden_query in golden_queries {
+            // One extra hit is fetched, to detect results that should not be there.
+            let actual = self.search(&golden_query.query, golden_query.hits.len() + 1)?;
+            if !self.matches(&golden_query.hits, &actual) {
+                mismatches.push(RelevanceMismatch {
+                    query: golden_query.query.clone(),
+                    expected: golden_query.hits.clone(),
+                    actual,
+                });
+            }
+        }
+        Ok(mismatches)
+    }
+
+    /// Runs the golden queries, and panics with a readable report if the results of
+    /// any of them do not match their snapshot.
+    ///
+    /// Returns an error if the queries could not be run.
+    pub fn assert_golden(&self, golden_queries: &[GoldenQuery]) -> crate::Result<()> {
+        let mismatches = self.run(golden_queries)?;
+        if !mismatches.is_empty() {
+            let report: String = mismatches
+                .iter()
+                .map(|mismatch| mismatch.to_string())
+                .collect();
+            panic!(
+                "{} of {} golden queries do not match their snapshot.\n{report}",
+                mismatches.len(),
+                golden_queries.len()
+            );
+        }
+        Ok(())
+    }
+
+    fn matches(&self, expected: &[ExpectedHit], actual: &[ExpectedHit]) -> bool {
+        expected.len() == actual.len()
+            && expected.iter().zip(actual).all(|(expected, actual)| {
+                expected.id == actual.id
+                    && match (expected.score, actual.score) {
+                        (Some(expected_score), Some(actual_score)) => {
+                            (expected_score - actual_score).abs() <= self.score_tolerance
+                        }
+                        (None, _) => true,
+                        (Some(_), None) => false,
+                    }
+            })
+    }
+
+    fn doc_id(&self, doc_address: DocAddress) -> crate::Result<String> {
+        let doc: TantivyDocument = self.searcher.doc(doc_address)?;
+        let value = doc.get_first(self.id_field).ok_or_else(|| {
+            TantivyError::InvalidArgument(
+                format!("Document {doc_address:?} has no stored value for the id field.").into(),
+            )
+        })?;
+        if let Some(text) = value.as_str() {
+            Ok(text.to_string())
+        } else if let Some(val) = value.as_u64() {
+            Ok(val.to_string())
+        } else if let Some(val) = value.as_i64() {
+            Ok(val.to_string())
+        } else {
+            Err(TantivyError::InvalidArgument(
+                "The id field needs to be a text or an integer field.".into(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{index_fixture, ExpectedHit, GoldenQuery, RelevanceHarness};
+    use crate::schema::{Schema, INDEXED, STORED, TEXT};
+    use crate::Index;
+
+    fn fixture() -> crate::Result<(Index, RelevanceHarness)> {
+        let mut schema_builder = Schema::builder();
+        let id = schema_builder.add_u64_field("id", INDEXED | STORED);
+        let body = schema_builder.add_text_field("body", TEXT);
+        let index = index_fixture(
+            schema_builder.build(),
+            [
+                r#"{"id": 1, "body": "the old man and the sea"}"#,
+                r#"{"id": 2, "body": "the sea, the sea"}"#,
+                r#"{"id": 3, "body": "twenty thousand leagues under the seas"}"#,
+            ],
+        )?;
+        let harness = RelevanceHarness::new(&index, id, vec![body])?;
+        Ok((index, harness))
+    }
+
+    fn hit(id: &str) -> ExpectedHit {
+        ExpectedHit {
+            id: id.to_string(),
+            score: None,
+        }
+    }
+
+    #[test]
+    fn test_relevance_harness_snapshot_roundtrip() -> crate::Result<()> {
+        let (_index, harness) = fixture()?;
+        let golden_queries = harness.snapshot(&["sea", "old OR leagues", "whale"], 10)?;
+        assert_eq!(golden_queries[0].hits.len(), 2);
+        assert_eq!(golden_queries[0].hits[0].id, "2");
+        assert!(golden_queries[2].hits.is_empty());
+        let json = serde_json::to_string(&golden_queries)?;
+        let golden_queries: Vec<GoldenQuery> = serde_json::from_str(&json)?;
+        assert!(harness.run(&golden_queries)?.is_empty());
+        harness.assert_golden(&golden_queries)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_relevance_harness_mismatches() -> crate::Result<()> {
+        let (_index, harness) = fixture()?;
+        let mut golden_queries = harness.snapshot(&["sea"], 10)?;
+        golden_queries[0].hits[0].score = golden_queries[0].hits[0].score.map(|score| score + 0.1);
+        golden_queries.push(GoldenQuery {
+            query: "sea".to_string(),
+            hits: vec![hit("1"), hit("2")],
+        });
+        golden_queries.push(GoldenQuery {
+            query: "sea".to_string(),
+            hits: vec![hit("2")],
+        });
+        golden_queries.push(GoldenQuery {
+            query: "sea".to_string(),
+            hits: vec![hit("2"), hit("1")],
+        });
+        let mismatches = harness.run(&golden_queries)?;
+        assert_eq!(mismatches.len(), 3);
+        assert_eq!(mismatches[2].actual.len(), 2);
+        assert!(mismatches[0].to_string().contains("query `sea`"));
+
+        let harness = harness.score_tolerance(0.2);
+        assert_eq!(harness.run(&golden_queries)?.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "1 of 1 golden queries do not match")]
+    fn test_relevance_harness_assert_golden() {
+        let (_index, harness) = fixture().unwrap();
+        harness
+            .assert_golden(&[GoldenQuery {
+                query: "sea".to_string(),
+                hits: vec![hit("3")],
+            }])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_relevance_harness_assert_golden_invalid_query() -> crate::Result<()> {
+        let (_index, harness) = fixture()?;
+        let golden_queries = [GoldenQuery {
+            query: "body:(".to_string(),
+            hits: Vec::new(),
+        }];
+        assert!(harness.assert_golden(&golden_queries).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_index_fixture_single_segment() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("body", TEXT);
+        let json_docs: Vec<String> = (0..50_000)
+            .map(|i| format!(r#"{{"body": "document {i} alpha{i} beta{i} gamma{i}"}}"#))
+            .collect();
+        let index = index_fixture(schema_builder.build(), json_docs.iter().map(String::as_str))?;
+        assert_eq!(index.searchable_segment_ids()?.len(), 1);
+        let searcher = index.reader()?.searcher();
+        assert_eq!(searcher.num_docs(), 50_000);
+        Ok(())
+    }
+}
@@ -0,0 +1,552 @@
+//! Language detection, and analyzer routing based on the detected language.
+//!
+//! # Example
+//!
+//! ```rust
+//! use tantivy::schema::{Schema, TextFieldIndexing, TextOptions, FAST, STRING};
+//! use tantivy::tokenizer::*;
+//! use tantivy::Index;
+//!
+//! let mut schema_builder = Schema::builder();
+//! let text_options = TextOptions::default()
+//!     .set_indexing_options(TextFieldIndexing::default().set_tokenizer("multilingual"));
+//! schema_builder.add_text_field("body", text_options);
+//! schema_builder.add_text_field("lang", STRING | FAST);
+//! let index = Index::create_in_ram(schema_builder.build());
+//!
+//! let tokenizers = index.tokenizers();
+//! for (language, tokenizer_name) in [(Language::French, "fr_stem"), (Language::German, "de_stem")] {
+//!     let analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+//!         .filter(RemoveLongFilter::limit(40))
+//!         .filter(LowerCaser)
+//!         .filter(Stemmer::new(language))
+//!         .build();
+//!     tokenizers.register(tokenizer_name, analyzer);
+//! }
+//! let multilingual = LanguageDetectingAnalyzer::builder(tokenizers, "default")?
+//!     .route(Language::English, "en_stem")?
+//!     .route(Language::French, "fr_stem")?
+//!     .route(Language::German, "de_stem")?
+//!     .language_field("lang")
+//!     .build();
+//! tokenizers.register("multilingual", multilingual);
+//! # Ok::<(), tantivy::TantivyError>(())
+//! ```
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use rustc_hash::FxHashMap;
+
+use super::{BoxTokenStream, Language, TextAnalyzer, Tokenizer, TokenizerManager};
+use crate::TantivyError;
+
+type Trigram = [char; 3];
+
+/// Number of trigrams kept in the profile of a language.
+const MAX_PROFILE_LEN: usize = 400;
+/// Only the beginning of long texts is used to detect their language.
+const MAX_DETECTION_CHARS: usize = 2_048;
+/// Texts with fewer trigrams are considered too short to detect their language.
+const MIN_DETECTION_TRIGRAMS: usize = 8;
+/// Minimum cosine similarity between a text and the profile of its language.
+const MIN_SIMILARITY: f32 = 0.05;
+
+/// Sample texts used to build the profiles of the default [`LanguageDetector`].
+const BUILTIN_SAMPLES: &[(Language, &str)] = &[
+    (
+        Language::English,
+        "All human beings are born free and equal in dignity and rights. They are endowed with \
+         reason and conscience and should act towards one another in a spirit of brotherhood. \
+         Everyone has the right to life, liberty and security of person. The children are playing \
+         in the garden with their friends while the cat is sleeping near the window.",
+    ),
+    (
+        Language::French,
+        "Tous les êtres humains naissent libres et égaux en dignité et en droits. Ils sont doués \
+         de raison et de conscience et doivent agir les uns envers les autres dans un esprit de \
+         fraternité. Tout individu a droit à la vie, à la liberté et à la sûreté de sa personne. \
+         Les enfants jouent dans le jardin avec leurs amis pendant que le chat dort près de la \
+         fenêtre.",
+    ),
+    (
+        Language::German,
+        "Alle Menschen sind frei und gleich an Würde und Rechten geboren. Sie sind mit Vernunft \
+         und Gewissen begabt und sollen einander im Geist der Brüderlichkeit begegnen. Jeder hat \
+         das Recht auf Leben, Freiheit und Sicherheit der Person. Die Kinder spielen mit ihren \
+         Freunden im Garten, während die Katze neben dem Fenster schläft.",
+    ),
+    (
+        Language::Spanish,
+        "Todos los seres humanos nacen libres e iguales en dignidad y derechos y, dotados como \
+         están de razón y conciencia, deben comportarse fraternalmente los unos con los otros. \
+         Todo individuo tiene derecho a la vida, a la libertad y a la seguridad de su persona. \
+         Los niños juegan en el jardín con sus amigos mientras el gato duerme cerca de la ventana.",
+    ),
+    (
+        Language::Italian,
+        "Tutti gli esseri umani nascono liberi ed eguali in dignità e diritti. Essi sono dotati \
+         di ragione e di coscienza e devono agire gli uni verso gli altri in spirito di \
+         fratellanza. Ogni individuo ha diritto alla vita, alla libertà ed alla sicurezza della \
+         propria persona. I bambini giocano in giardino con i loro amici mentre il gatto dorme \
+         vicino alla finestra.",
+    ),
+    (
+        Language::Portuguese,
+        "Todos os seres humanos nascem livres e iguais em dignidade e em direitos. Dotados de \
+         razão e de consciência, devem agir uns para com os outros em espírito de fraternidade. \
+         Todo o indivíduo tem direito à vida, à liberdade e à segurança pessoal. As crianças \
+         brincam no jardim com os seus amigos enquanto o gato dorme perto da janela.",
+    ),
+    (
+        Language::Dutch,
+        "Alle mensen worden vrij en gelijk in waardigheid en rechten geboren. Zij zijn begiftigd \
+         met verstand en geweten, en behoren zich jegens elkander in een geest van broederschap \
+         te gedragen. Een ieder heeft het recht op leven, vrijheid en onschendbaarheid van zijn \
+         persoon. De kinderen spelen met hun vrienden in de tuin terwijl de kat naast het raam \
+         slaapt.",
+    ),
+    (
+        Language::Swedish,
+        "Alla människor är födda fria och lika i värde och rättigheter. De har utrustats med \
+         förnuft och samvete och bör handla gentemot varandra i en anda av broderskap. Var och en \
+         har rätt till liv, frihet och personlig säkerhet. Barnen leker med sina vänner i \
+         trädgården medan katten sover bredvid fönstret.",
+    ),
+    (
+        Language::Russian,
+        "Все люди рождаются свободными и равными в своем достоинстве и правах. Они наделены \
+         разумом и совестью и должны поступать в отношении друг друга в духе братства. Каждый \
+         человек имеет право на жизнь, на свободу и на личную неприкосновенность. Дети играют в \
+         саду со своими друзьями, пока кошка спит у окна.",
+    ),
+];
+
+static DEFAULT_DETECTOR: Lazy<Arc<LanguageDetector>> = Lazy::new(|| {
+    let mut detector = LanguageDetector::empty();
+    for &(language, sample_text) in BUILTIN_SAMPLES {
+        detector.add_language(language, sample_text);
+    }
+    Arc::new(detector)
+});
+
+/// Calls `callback` on the character trigrams of the words of `text`.
+///
+/// Words are lowercased and padded with a space on both sides, so that trigrams also capture
+/// the beginning and the end of words.
+fn for_each_trigram(text: &str, mut callback: impl FnMut(Trigram)) {
+    let mut window = [' '; 3];
+    let mut word_len = 0;
+    for c in text.chars().take(MAX_DETECTION_CHARS) {
+        if c.is_alphabetic() {
+            window = [window[1], window[2], c.to_lowercase().next().unwrap_or(c)];
+            word_len += 1;
+            if word_len >= 2 {
+                callback(window);
+            }
+        } else if word_len > 0 {
+            callback([window[1], window[2], ' ']);
+            window = [' '; 3];
+            word_len = 0;
+        }
+    }
+    if word_len > 0 {
+        callback([window[1], window[2], ' ']);
+    }
+}
+
+fn count_trigrams(text: &str) -> FxHashMap<Trigram, u32> {
+    let mut counts: FxHashMap<Trigram, u32> = FxHashMap::default();
+    for_each_trigram(text, |trigram| *counts.entry(trigram).or_default() += 1);
+    counts
+}
+
+fn norm(counts: impl Iterator<Item = f32>) -> f32 {
+    counts.map(|count| count * count).sum::<f32>().sqrt()
+}
+
+/// Detects the language of a text by comparing its character trigrams with the trigram
+/// profiles of the known languages.
+///
+/// The default detector knows English, French, German, Spanish, Italian, Portuguese, Dutch,
+/// Swedish and Russian. Its profiles are built from short sample texts, and can be extended
+/// or replaced with [`LanguageDetector::add_language()`].
+#[derive(Clone, Debug)]
+pub struct LanguageDetector {
+    /// Normalized trigram frequencies of each language.
+    profiles: Vec<(Language, FxHashMap<Trigram, f32>)>,
+}
+
+impl Default for LanguageDetector {
+    fn default() -> Self {
+        LanguageDetector::clone(&DEFAULT_DETECTOR)
+    }
+}
+
+impl LanguageDetector {
+    /// Creates a detector that does not know any language.
+    pub fn empty() -> LanguageDetector {
+        LanguageDetector {
+            profiles: Vec::new(),
+        }
+    }
+
+    /// Builds the profile of `language` out of a sample text.
+    ///
+    /// The sample text should be representative of the language, and at least a few sentences
+    /// long. The previous profile of the language, if any, is replaced.
+    pub fn add_language(&mut self, language: Language, sample_text: &str) {
+        let mut trigram_counts: Vec<(Trigram, u32)> =
+            count_trigrams(sample_text).into_iter().collect();
+        trigram_counts.sort_by(|left, right| right.1.cmp(&left.1).then(left.0.cmp(&right.0)));
+        trigram_counts.truncate(MAX_PROFILE_LEN);
+        let profile_norm = norm(trigram_counts.iter().map(|(_, count)| *count as f32));
+        let profile: FxHashMap<Trigram, f32> = trigram_counts
+            .into_iter()
+            .map(|(trigram, count)| (trigram, count as f32 / profile_norm))
+            .collect();
+        self.profiles
+            .retain(|(known_language, _)| *known_language != language);
+        self.profiles.push((language, profile));
+    }
+
+    /// Returns the languages known by the detector.
+    pub fn languages(&self) -> impl Iterator<Item = Language> + '_ {
+        self.profiles.iter().map(|(language, _)| *language)
+    }
+
+    /// Returns the language of the text, or `None` if the text is too short or does not
+    /// resemble any of the known languages.
+    pub fn detect(&self, text: &str) -> Option<Language> {
+        let trigram_counts = count_trigrams(text);
+        let num_trigrams: u32 = trigram_counts.values().sum();
+        if (num_trigrams as usize) < MIN_DETECTION_TRIGRAMS {
+            return None;
+        }
+        let text_norm = norm(trigram_counts.values().map(|&count| count as f32));
+        let (language, similarity) = self
+            .profiles
+            .iter()
+            .map(|(language, profile)| {
+                let dot_product: f32 = trigram_counts
+                    .iter()
+                    .filter_map(|(trigram, &count)| {
+                        profile
+                            .get(trigram)
+                            .map(|frequency| count as f32 * frequency)
+                    })
+                    .sum();
+                (*language, dot_product / text_norm)
+            })
+            .max_by(|left, right| left.1.total_cmp(&right.1))?;
+        if similarity < MIN_SIMILARITY {
+            return None;
+        }
+        Some(language)
+    }
+}
+
+/// `Tokenizer` that detects the language of each text, and delegates its tokenization to the
+/// analyzer registered for this language.
+///
+/// Texts whose language cannot be detected, or has no registered analyzer, are handled by a
+/// fallback analyzer. Since queries are usually too short for their language to be detected,
+/// they are typically processed by the fallback analyzer.
+///
+/// If a language field is set, the ISO 639-1 code of the detected language (see
+/// [`Language::iso_code()`]) is recorded, upon indexing, in this field. The language field
+/// needs to be a fast text field. This only works if the analyzer is registered as is in the
+/// [`TokenizerManager`], without any additional filter.
+#[derive(Clone)]
+pub struct LanguageDetectingAnalyzer {
+    detector: Arc<LanguageDetector>,
+    analyzers: Vec<(Language, TextAnalyzer)>,
+    fallback: TextAnalyzer,
+    language_field: Option<String>,
+}
+
+impl LanguageDetectingAnalyzer {
+    /// Creates a builder for a `LanguageDetectingAnalyzer`, resolving analyzers by name in the
+    /// given `TokenizerManager`.
+    ///
+    /// Returns an error if no analyzer named `fallback_tokenizer_name` is registered.
+    pub fn builder(
+        tokenizer_manager: &TokenizerManager,
+        fallback_tokenizer_name: &str,
+    ) -> crate::Result<LanguageDetectingAnalyzerBuilder> {
+        let fallback = get_tokenizer(tokenizer_manager, fallback_tokenizer_name)?;
+        Ok(LanguageDetectingAnalyzerBuilder {
+            tokenizer_manager: tokenizer_manager.clone(),
+            analyzer: LanguageDetectingAnalyzer {
+                detector: DEFAULT_DETECTOR.clone(),
+                analyzers: Vec::new(),
+                fallback,
+                language_field: None,
+            },
+        })
+    }
+
+    /// Returns the language detector.
+    pub fn detector(&self) -> &Arc<LanguageDetector> {
+        &self.detector
+    }
+
+    /// Returns the name of the field in which the detected language is recorded.
+    pub fn language_field(&self) -> Option<&str> {
+        self.language_field.as_deref()
+    }
+
+    fn analyzer_mut(&mut self, language: Option<Language>) -> &mut TextAnalyzer {
+        let Some(language) = language else {
+            return &mut self.fallback;
+        };
+        match self
+            .analyzers
+            .iter_mut()
+            .find(|(analyzer_language, _)| *analyzer_language == language)
+        {
+            Some((_, analyzer)) => analyzer,
+            None => &mut self.fallback,
+        }
+    }
+}
+
+fn get_tokenizer(
+    tokenizer_manager: &TokenizerManager,
+    tokenizer_name: &str,
+) -> crate::Result<TextAnalyzer> {
+    tokenizer_manager.get(tokenizer_name).ok_or_else(|| {
+        TantivyError::InvalidArgument(format!("Tokenizer {tokenizer_name:?} not found").into())
+    })
+}
+
+impl Tokenizer for LanguageDetectingAnalyzer {
+    type TokenStream<'a> = BoxTokenStream<'a>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> BoxTokenStream<'a> {
+        let language = self.detector.detect(text);
+        self.analyzer_mut(language).token_stream(text)
+    }
+}
+
+/// Builder for a [`LanguageDetectingAnalyzer`].
+pub struct LanguageDetectingAnalyzerBuilder {
+    tokenizer_manager: TokenizerManager,
+    analyzer: LanguageDetectingAnalyzer,
+}
+
+impl LanguageDetectingAnalyzerBuilder {
+    /// Uses a custom language detector, instead of the default one.
+    pub fn detector(mut self, detector: LanguageDetector) -> Self {
+        self.analyzer.detector = Arc::new(detector);
+        self
+    }
+
+    /// Routes the texts detected as `language` to the analyzer registered as `tokenizer_name`.
+    ///
+    /// Returns an error if no such analyzer is registered.
+    pub fn route(mut self, language: Language, tokenizer_name: &str) -> crate::Result<Self> {
+        let analyzer = get_tokenizer(&self.tokenizer_manager, tokenizer_name)?;
+        self.analyzer
+            .analyzers
+            .retain(|(analyzer_language, _)| *analyzer_language != language);
+        self.analyzer.analyzers.push((language, analyzer));
+        Ok(self)
+    }
+
+    /// Records the detected language of the indexed texts in the given field.
+    pub fn language_field(mut self, field_name: &str) -> Self {
+        self.analyzer.language_field = Some(field_name.to_string());
+        self
+    }
+
+    /// Builds the `LanguageDetectingAnalyzer`.
+    pub fn build(self) -> LanguageDetectingAnalyzer {
+        self.analyzer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LanguageDetectingAnalyzer, LanguageDetector};
+    use crate::collector::Count;
+    use crate::indexer::SegmentWriter;
+    use crate::query::TermQuery;
+    use crate::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions, FAST, STRING};
+    use crate::tokenizer::{
+        Language, LowerCaser, SimpleTokenizer, Stemmer, TextAnalyzer, TokenizerManager,
+    };
+    use crate::{Index, IndexWriter, TantivyError, Term};
+
+    fn tokens(analyzer: &mut TextAnalyzer, text: &str) -> Vec<String> {
+        let mut token_stream = analyzer.token_stream(text);
+        let mut tokens = Vec::new();
+        while let Some(token) = token_stream.next() {
+            tokens.push(token.text.clone());
+        }
+        tokens
+    }
+
+    fn multilingual_analyzer(tokenizer_manager: &TokenizerManager) -> LanguageDetectingAnalyzer {
+        tokenizer_manager.register(
+            "fr_stem",
+            TextAnalyzer::builder(SimpleTokenizer::default())
+                .filter(LowerCaser)
+                .filter(Stemmer::new(Language::French))
+                .build(),
+        );
+        LanguageDetectingAnalyzer::builder(tokenizer_manager, "default")
+            .unwrap()
+            .route(Language::English, "en_stem")
+            .unwrap()
+            .route(Language::French, "fr_stem")
+            .unwrap()
+            .language_field("lang")
+            .build()
+    }
+
+    #[test]
+    fn test_language_detector() {
+        let detector = LanguageDetector::default();
+        assert_eq!(detector.languages().count(), 9);
+        let detect = |text: &str| detector.detect(text);
+        assert_eq!(
+            detect("The weather was nice, so we decided to walk to the station."),
+            Some(Language::English)
+        );
+        assert_eq!(
+            detect("Il faisait beau, alors nous avons décidé de marcher jusqu'à la gare."),
+            Some(Language::French)
+        );
+        assert_eq!(
+            detect("Das Wetter war schön, also haben wir beschlossen, zum Bahnhof zu laufen."),
+            Some(Language::German)
+        );
+        assert_eq!(
+            detect("Hacía buen tiempo, así que decidimos caminar hasta la estación."),
+            Some(Language::Spanish)
+        );
+        assert_eq!(
+            detect("Погода была хорошая, поэтому мы решили пойти на вокзал пешком."),
+            Some(Language::Russian)
+        );
+        // Too short
+        assert_eq!(detect("wolf"), None);
+        assert_eq!(detect("1234 5678 !!!"), None);
+    }
+
+    #[test]
+    fn test_language_detector_custom_profiles() {
+        let mut detector = LanguageDetector::empty();
+        assert_eq!(detector.detect("the cat is sleeping near the window"), None);
+        detector.add_language(Language::Finnish, "kissa nukkuu ikkunan vieressä");
+        detector.add_language(Language::English, "the cat is sleeping near the window");
+        assert_eq!(
+            detector.detect("the dog is sleeping near the door"),
+            Some(Language::English)
+        );
+        assert_eq!(
+            detector.detect("koira nukkuu oven vieressä"),
+            Some(Language::Finnish)
+        );
+    }
+
+    #[test]
+    fn test_language_detecting_analyzer_routing() {
+        let tokenizer_manager = TokenizerManager::default();
+        let mut analyzer = TextAnalyzer::from(multilingual_analyzer(&tokenizer_manager));
+        assert_eq!(
+            tokens(&mut analyzer, "The children are playing with their friends"),
+            vec!["the", "children", "are", "play", "with", "their", "friend"]
+        );
+        assert_eq!(
+            tokens(&mut analyzer, "Les enfants jouent avec leurs amis"),
+            vec!["le", "enfant", "jouent", "avec", "leur", "amis"]
+        );
+        // German is detected, but has no dedicated analyzer.
+        assert_eq!(
+            tokens(&mut analyzer, "Die Kinder spielen mit ihren Freunden"),
+            vec!["die", "kinder", "spielen", "mit", "ihren", "freunden"]
+        );
+        assert!(
+            LanguageDetectingAnalyzer::builder(&tokenizer_manager, "default")
+                .unwrap()
+                .route(Language::German, "de_stem")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_language_detecting_analyzer_records_language() -> crate::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field(
+            "body",
+            TextOptions::default().set_indexing_options(
+                TextFieldIndexing::default()
+                    .set_tokenizer("multilingual")
+                    .set_index_option(IndexRecordOption::Basic),
+            ),
+        );
+        let lang = schema_builder.add_text_field("lang", STRING | FAST);
+        let index = Index::create_in_ram(schema_builder.build());
+        let multilingual = multilingual_analyzer(index.tokenizers());
+        index.tokenizers().register("multilingual", multilingual);
+
+        let mut index_writer: IndexWriter = index.writer_for_tests()?;
+        index_writer.add_document(doc!(body => "The children are playing with their friends"))?;
+        index_writer.add_document(doc!(body => "Les enfants jouent avec leurs amis"))?;
+        index_writer.add_document(doc!(body => "wolf"))?;
+        index_writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let segment_reader = searcher.segment_reader(0);
+        let lang_column = segment_reader.fast_fields().str("lang")?.unwrap();
+        let languages: Vec<Option<String>> = (0..3)
+            .map(|doc| {
+                lang_column.term_ords(doc).next().map(|term_ord| {
+                    let mut language = String::new();
+                    lang_column.ord_to_str(term_ord, &mut language).unwrap();
+                    language
+                })
+            })
+            .collect();
+        assert_eq!(
+            languages,
+            vec![Some("en".to_string()), Some("fr".to_string()), None]
+        );
+        // The language field is only recorded as a fast field.
+        let query = TermQuery::new(Term::from_field_text(lang, "fr"), IndexRecordOption::Basic);
+        assert_eq!(searcher.search(&query, &Count)?, 0);
+        let query = TermQuery::new(
+            Term::from_field_text(body, "enfant"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_language_detecting_analyzer_invalid_language_field() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field(
+            "body",
+            TextOptions::default()
+                .set_indexing_options(TextFieldIndexing::default().set_tokenizer("multilingual")),
+        );
+        schema_builder.add_text_field("lang", STRING);
+        let index = Index::create_in_ram(schema_builder.build());
+        let multilingual = multilingual_analyzer(index.tokenizers());
+        index.tokenizers().register("multilingual", multilingual);
+        assert!(matches!(
+            SegmentWriter::for_segment(15_000_000, index.new_segment()),
+            Err(TantivyError::SchemaError(_))
+        ));
+    }
+
+    #[test]
+    fn test_language_iso_code() {
+        for language in Language::ALL {
+            assert_eq!(Language::from_iso_code(language.iso_code()), Some(language));
+        }
+        assert_eq!(Language::from_iso_code("xx"), None);
+    }
+}
@@ -125,11 +125,13 @@ mod alphanum_only;
 mod ascii_folding_filter;
 mod empty_tokenizer;
 mod facet_tokenizer;
+mod language_detecting_analyzer;
 mod lower_caser;
 mod ngram_tokenizer;
 mod raw_tokenizer;
 mod regex_tokenizer;
 mod remove_long;
+mod shingle_filter;
 mod simple_tokenizer;
 mod split_compound_words;
 mod stemmer;
@@ -144,11 +146,15 @@ pub use tokenizer_api::{BoxTokenStream, Token, TokenFilter, TokenStream, Tokeniz
 pub use self::alphanum_only::AlphaNumOnlyFilter;
 pub use self::ascii_folding_filter::AsciiFoldingFilter;
 pub use self::facet_tokenizer::FacetTokenizer;
+pub use self::language_detecting_analyzer::{
+    LanguageDetectingAnalyzer, LanguageDetectingAnalyzerBuilder, LanguageDetector,
+};
 pub use self::lower_caser::LowerCaser;
 pub use self::ngram_tokenizer::NgramTokenizer;
 pub use self::raw_tokenizer::RawTokenizer;
 pub use self::regex_tokenizer::RegexTokenizer;
 pub use self::remove_long::RemoveLongFilter;
+pub use self::shingle_filter::ShingleFilter;
 pub use self::simple_tokenizer::{SimpleTokenStream, SimpleTokenizer};
 pub use self::split_compound_words::SplitCompoundWords;
 pub use self::stemmer::{Language, Stemmer};
@@ -100,12 +100,12 @@ impl NgramTokenizer {
     ) -> crate::Result<NgramTokenizer> {
         if min_gram == 0 {
             return Err(TantivyError::InvalidArgument(
-                "min_gram must be greater than 0".to_string(),
+                "min_gram must be greater than 0".into(),
             ));
         }
         if min_gram > max_gram {
             return Err(TantivyError::InvalidArgument(
-                "min_gram must not be greater than max_gram".to_string(),
+                "min_gram must not be greater than max_gram".into(),
             ));
         }
         Ok(NgramTokenizer {
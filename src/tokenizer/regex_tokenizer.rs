@@ -56,7 +56,7 @@ impl RegexTokenizer {
     /// Creates a new RegexTokenizer.
     pub fn new(regex_pattern: &str) -> crate::Result<RegexTokenizer> {
         Regex::new(regex_pattern)
-            .map_err(|_| TantivyError::InvalidArgument(regex_pattern.to_owned()))
+            .map_err(|_| TantivyError::InvalidArgument(regex_pattern.into()))
             .map(|regex| Self {
                 regex,
                 token: Token::default(),
@@ -0,0 +1,291 @@
+use std::collections::VecDeque;
+
+use super::{Token, TokenFilter, TokenStream, Tokenizer};
+use crate::TantivyError;
+
+/// A [`TokenFilter`] that emits shingles, i.e. word n-grams, built out of
+/// consecutive tokens of the underlying token stream.
+///
+/// Each shingle is positioned at the position of its first token,
+/// spans from the `offset_from` of its first token to the `offset_to` of its last token,
+/// and its `position_length` is the number of tokens it is made of.
+///
+/// When unigrams are output, each original token is emitted right before the shingles
+/// starting at the same position.
+///
+/// Shingles are useful to build autocomplete fields, or to boost documents
+/// containing a phrase without running a phrase query.
+///
+/// ```rust
+/// use tantivy::tokenizer::*;
+///
+/// let mut tokenizer = TextAnalyzer::builder(SimpleTokenizer::default())
+///     .filter(ShingleFilter::new(2, 3).unwrap().output_unigrams(false))
+///     .build();
+///
+/// let mut stream = tokenizer.token_stream("please divide this");
+/// {
+///     let token = stream.next().unwrap();
+///     assert_eq!(token.text, "please divide");
+///     assert_eq!(token.position, 0);
+///     assert_eq!(token.offset_from, 0);
+///     assert_eq!(token.offset_to, 13);
+/// }
+/// assert_eq!(stream.next().unwrap().text, "please divide this");
+/// {
+///     let token = stream.next().unwrap();
+///     assert_eq!(token.text, "divide this");
+///     assert_eq!(token.position, 1);
+/// }
+/// assert!(stream.next().is_none());
+/// ```
+#[derive(Clone)]
+pub struct ShingleFilter {
+    min_shingle_size: usize,
+    max_shingle_size: usize,
+    output_unigrams: bool,
+    token_separator: String,
+}
+
+impl Default for ShingleFilter {
+    /// Creates a filter emitting bigrams as well as the original tokens.
+    fn default() -> ShingleFilter {
+        ShingleFilter {
+            min_shingle_size: 2,
+            max_shingle_size: 2,
+            output_unigrams: true,
+            token_separator: " ".to_string(),
+        }
+    }
+}
+
+impl ShingleFilter {
+    /// Creates a `ShingleFilter` emitting shingles of `min_shingle_size` to
+    /// `max_shingle_size` tokens (inclusive), as well as the original tokens.
+    ///
+    /// # Errors
+    /// `min_shingle_size` needs to be at least 2, and cannot be greater than
+    /// `max_shingle_size`.
+    pub fn new(min_shingle_size: usize, max_shingle_size: usize) -> crate::Result<ShingleFilter> {
+        if min_shingle_size < 2 {
+            return Err(TantivyError::InvalidArgument(
+                "min_shingle_size must be at least 2".into(),
+            ));
+        }
+        if min_shingle_size > max_shingle_size {
+            return Err(TantivyError::InvalidArgument(
+                "min_shingle_size must not be greater than max_shingle_size".into(),
+            ));
+        }
+        Ok(ShingleFilter {
+            min_shingle_size,
+            max_shingle_size,
+            ..ShingleFilter::default()
+        })
+    }
+
+    /// Sets whether the original tokens should be emitted alongside the shingles.
+    ///
+    /// Defaults to `true`.
+    pub fn output_unigrams(mut self, output_unigrams: bool) -> ShingleFilter {
+        self.output_unigrams = output_unigrams;
+        self
+    }
+
+    /// Sets the string inserted between the tokens of a shingle.
+    ///
+    /// Defaults to a single space.
+    pub fn token_separator(mut self, token_separator: &str) -> ShingleFilter {
+        self.token_separator = token_separator.to_string();
+        self
+    }
+}
+
+impl TokenFilter for ShingleFilter {
+    type Tokenizer<T: Tokenizer> = ShingleFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> ShingleFilterWrapper<T> {
+        ShingleFilterWrapper {
+            filter: self,
+            inner: tokenizer,
+            window: VecDeque::new(),
+            pending: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ShingleFilterWrapper<T> {
+    filter: ShingleFilter,
+    inner: T,
+    window: VecDeque<Token>,
+    pending: Vec<Token>,
+}
+
+impl<T: Tokenizer> Tokenizer for ShingleFilterWrapper<T> {
+    type TokenStream<'a> = ShingleFilterStream<'a, T::TokenStream<'a>>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> Self::TokenStream<'a> {
+        self.window.clear();
+        self.pending.clear();
+        ShingleFilterStream {
+            filter: &self.filter,
+            tail: self.inner.token_stream(text),
+            window: &mut self.window,
+            pending: &mut self.pending,
+            token: Token::default(),
+        }
+    }
+}
+
+pub struct ShingleFilterStream<'a, T> {
+    filter: &'a ShingleFilter,
+    tail: T,
+    // Tokens of the underlying stream, starting at the position of the
+    // next shingles to emit.
+    window: &'a mut VecDeque<Token>,
+    // Tokens to emit, in reverse order.
+    pending: &'a mut Vec<Token>,
+    token: Token,
+}
+
+impl<T: TokenStream> ShingleFilterStream<'_, T> {
+    fn fill_window(&mut self) {
+        while self.window.len() < self.filter.max_shingle_size && self.tail.advance() {
+            self.window.push_back(self.tail.token().clone());
+        }
+    }
+
+    // Fills `self.pending` with the tokens starting at the first
+    // token of the window, in reverse order.
+    fn push_shingles(&mut self) {
+        let max_shingle_size = self.filter.max_shingle_size.min(self.window.len());
+        for shingle_size in (self.filter.min_shingle_size..=max_shingle_size).rev() {
+            let first = &self.window[0];
+            let last = &self.window[shingle_size - 1];
+            let mut text = String::with_capacity(
+                self.window
+                    .iter()
+                    .take(shingle_size)
+                    .map(|token| token.text.len() + self.filter.token_separator.len())
+                    .sum(),
+            );
+            for (i, token) in self.window.iter().take(shingle_size).enumerate() {
+                if i > 0 {
+                    text.push_str(&self.filter.token_separator);
+                }
+                text.push_str(&token.text);
+            }
+            self.pending.push(Token {
+                offset_from: first.offset_from,
+                offset_to: last.offset_to,
+                position: first.position,
+                text,
+                position_length: shingle_size,
+            });
+        }
+        if self.filter.output_unigrams {
+            self.pending.push(self.window[0].clone());
+        }
+    }
+}
+
+impl<T: TokenStream> TokenStream for ShingleFilterStream<'_, T> {
+    fn advance(&mut self) -> bool {
+        loop {
+            if let Some(token) = self.pending.pop() {
+                self.token = token;
+                return true;
+            }
+            self.fill_window();
+            if self.window.is_empty() {
+                return false;
+            }
+            self.push_shingles();
+            self.window.pop_front();
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShingleFilter;
+    use crate::tokenizer::tests::assert_token;
+    use crate::tokenizer::{SimpleTokenizer, TextAnalyzer, Token};
+
+    fn token_stream_helper(text: &str, shingle_filter: ShingleFilter) -> Vec<Token> {
+        let mut a = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(shingle_filter)
+            .build();
+        let mut token_stream = a.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        let mut add_token = |token: &Token| {
+            tokens.push(token.clone());
+        };
+        token_stream.process(&mut add_token);
+        tokens
+    }
+
+    #[test]
+    fn test_shingle_filter_default() {
+        let tokens = token_stream_helper("the quick fox", ShingleFilter::default());
+        assert_eq!(tokens.len(), 5);
+        assert_token(&tokens[0], 0, "the", 0, 3);
+        assert_token(&tokens[1], 0, "the quick", 0, 9);
+        assert_token(&tokens[2], 1, "quick", 4, 9);
+        assert_token(&tokens[3], 1, "quick fox", 4, 13);
+        assert_token(&tokens[4], 2, "fox", 10, 13);
+        assert_eq!(tokens[1].position_length, 2);
+        assert_eq!(tokens[2].position_length, 1);
+    }
+
+    #[test]
+    fn test_shingle_filter_min_max() {
+        let tokens = token_stream_helper(
+            "a b c d",
+            ShingleFilter::new(2, 3)
+                .unwrap()
+                .output_unigrams(false)
+                .token_separator("_"),
+        );
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(texts, &["a_b", "a_b_c", "b_c", "b_c_d", "c_d"]);
+        assert_token(&tokens[3], 1, "b_c_d", 2, 7);
+        assert_eq!(tokens[3].position_length, 3);
+    }
+
+    #[test]
+    fn test_shingle_filter_too_few_tokens() {
+        let shingle_filter = ShingleFilter::new(3, 3).unwrap().output_unigrams(false);
+        assert!(token_stream_helper("a b", shingle_filter.clone()).is_empty());
+        assert!(token_stream_helper("", shingle_filter).is_empty());
+        let tokens = token_stream_helper("a", ShingleFilter::default());
+        assert_eq!(tokens.len(), 1);
+        assert_token(&tokens[0], 0, "a", 0, 1);
+    }
+
+    #[test]
+    fn test_shingle_filter_invalid_sizes() {
+        assert!(ShingleFilter::new(1, 2).is_err());
+        assert!(ShingleFilter::new(3, 2).is_err());
+    }
+
+    #[test]
+    fn test_shingle_filter_reuse() {
+        let mut a = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(ShingleFilter::default())
+            .build();
+        assert_eq!(a.token_stream("a b").next().unwrap().text, "a");
+        let mut token_stream = a.token_stream("c d");
+        assert_eq!(token_stream.next().unwrap().text, "c");
+        assert_eq!(token_stream.next().unwrap().text, "c d");
+    }
+}
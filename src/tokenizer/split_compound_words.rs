@@ -62,9 +62,9 @@ impl SplitCompoundWords {
             .match_kind(MatchKind::LeftmostLongest)
             .build(dict)
             .map_err(|err| {
-                crate::TantivyError::InvalidArgument(format!(
-                    "Failed to build Aho-Corasick automaton from dictionary: {err}"
-                ))
+                crate::TantivyError::InvalidArgument(
+                    format!("Failed to build Aho-Corasick automaton from dictionary: {err}").into(),
+                )
             })?;
 
         Ok(Self::from_automaton(dict))
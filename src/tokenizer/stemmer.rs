@@ -31,6 +31,60 @@ pub enum Language {
 }
 
 impl Language {
+    /// All of the available languages.
+    pub const ALL: [Language; 18] = [
+        Language::Arabic,
+        Language::Danish,
+        Language::Dutch,
+        Language::English,
+        Language::Finnish,
+        Language::French,
+        Language::German,
+        Language::Greek,
+        Language::Hungarian,
+        Language::Italian,
+        Language::Norwegian,
+        Language::Portuguese,
+        Language::Romanian,
+        Language::Russian,
+        Language::Spanish,
+        Language::Swedish,
+        Language::Tamil,
+        Language::Turkish,
+    ];
+
+    /// Returns the ISO 639-1 code of the language, e.g. `"en"` for English.
+    pub fn iso_code(self) -> &'static str {
+        use self::Language::*;
+        match self {
+            Arabic => "ar",
+            Danish => "da",
+            Dutch => "nl",
+            English => "en",
+            Finnish => "fi",
+            French => "fr",
+            German => "de",
+            Greek => "el",
+            Hungarian => "hu",
+            Italian => "it",
+            Norwegian => "no",
+            Portuguese => "pt",
+            Romanian => "ro",
+            Russian => "ru",
+            Spanish => "es",
+            Swedish => "sv",
+            Tamil => "ta",
+            Turkish => "tr",
+        }
+    }
+
+    /// Returns the language associated to an ISO 639-1 code.
+    pub fn from_iso_code(iso_code: &str) -> Option<Language> {
+        Language::ALL
+            .into_iter()
+            .find(|language| language.iso_code() == iso_code)
+    }
+
     fn algorithm(self) -> Algorithm {
         use self::Language::*;
         match self {
@@ -1,5 +1,7 @@
 /// The tokenizer module contains all of the tools used to process
 /// text in `tantivy`.
+use std::any::Any;
+
 use tokenizer_api::{BoxTokenStream, TokenFilter, Tokenizer};
 
 use crate::tokenizer::empty_tokenizer::EmptyTokenizer;
@@ -35,6 +37,8 @@ pub trait BoxableTokenizer: 'static + Send + Sync {
     fn box_token_stream<'a>(&'a mut self, text: &'a str) -> BoxTokenStream<'a>;
     /// Clone this tokenizer.
     fn box_clone(&self) -> Box<dyn BoxableTokenizer>;
+    /// Returns the concrete `Tokenizer`, for downcasting.
+    fn as_any(&self) -> &dyn Any;
 }
 
 impl<T: Tokenizer> BoxableTokenizer for T {
@@ -44,6 +48,9 @@ impl<T: Tokenizer> BoxableTokenizer for T {
     fn box_clone(&self) -> Box<dyn BoxableTokenizer> {
         Box::new(self.clone())
     }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
 }
 
 impl Default for TextAnalyzer {
@@ -68,6 +75,13 @@ impl TextAnalyzer {
     pub fn token_stream<'a>(&'a mut self, text: &'a str) -> BoxTokenStream<'a> {
         self.tokenizer.token_stream(text)
     }
+
+    /// Returns the underlying tokenizer if it is of type `T`.
+    ///
+    /// This only works if no filter was appended to the tokenizer.
+    pub(crate) fn downcast_ref<T: Tokenizer>(&self) -> Option<&T> {
+        (*self.tokenizer).as_any().downcast_ref::<T>()
+    }
 }
 
 /// Builder helper for [`TextAnalyzer`]
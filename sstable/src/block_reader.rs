@@ -4,6 +4,13 @@ use std::ops::Range;
 use common::OwnedBytes;
 use zstd::bulk::Decompressor;
 
+/// Upper bound of the compression ratio of zstd.
+///
+/// The best ratio is reached by RLE blocks, encoding up to 128KB in 4 bytes.
+/// It is used to reject corrupted blocks declaring an absurd decompressed size,
+/// before allocating the buffer to decompress them.
+const MAX_ZSTD_COMPRESSION_RATIO: usize = 1 << 15;
+
 pub struct BlockReader {
     buffer: Vec<u8>,
     reader: OwnedBytes,
@@ -69,18 +76,24 @@ impl BlockReader {
             if block_len <= 1 {
                 return Ok(false);
             }
-            let compress = self.reader.read_u8();
-            let block_len = block_len - 1;
-
             if self.reader.len() < block_len {
                 return Err(io::Error::new(
                     io::ErrorKind::UnexpectedEof,
                     "failed to read block content",
                 ));
             }
+            let compress = self.reader.read_u8();
+            let block_len = block_len - 1;
+
             if compress == 1 {
                 let required_capacity =
                     Decompressor::upper_bound(&self.reader[..block_len]).unwrap_or(1024 * 1024);
+                if required_capacity > block_len.saturating_mul(MAX_ZSTD_COMPRESSION_RATIO) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "sstable block declares an impossible decompressed size",
+                    ));
+                }
                 self.buffer.reserve(required_capacity);
                 Decompressor::new()?
                     .decompress_to_buffer(&self.reader[..block_len], &mut self.buffer)?;
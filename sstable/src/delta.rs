@@ -184,15 +184,21 @@ where TValueReader: value::ValueReader
         }
     }
 
-    fn read_delta_key(&mut self) -> bool {
+    fn read_delta_key(&mut self) -> io::Result<bool> {
         let Some((keep, add)) = self.read_keep_add() else {
-            return false;
+            return Ok(false);
         };
+        if add > self.block_reader.buffer().len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "SSTable key suffix exceeds the block",
+            ));
+        }
         self.common_prefix_len = keep;
         let suffix_start = self.block_reader.offset();
         self.suffix_range = suffix_start..(suffix_start + add);
         self.block_reader.advance(add);
-        true
+        Ok(true)
     }
 
     pub fn advance(&mut self) -> io::Result<bool> {
@@ -206,9 +212,17 @@ where TValueReader: value::ValueReader
         } else {
             self.idx += 1;
         }
-        if !self.read_delta_key() {
+        if !self.read_delta_key()? {
             return Ok(false);
         }
+        if let Some(num_vals) = self.value_reader.num_vals() {
+            if self.idx >= num_vals {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "SSTable block holds less values than keys",
+                ));
+            }
+        }
         Ok(true)
     }
 
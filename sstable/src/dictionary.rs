@@ -8,7 +8,7 @@ use std::sync::Arc;
 
 use common::bounds::{transform_bound_inner_res, TransformBound};
 use common::file_slice::FileSlice;
-use common::{BinarySerializable, OwnedBytes};
+use common::{BinarySerializable, HasLen, OwnedBytes};
 use futures_util::{stream, StreamExt, TryStreamExt};
 use itertools::Itertools;
 use tantivy_fst::automaton::AlwaysMatch;
@@ -20,6 +20,13 @@ use crate::{
     BlockAddr, DeltaReader, Reader, SSTable, SSTableIndex, SSTableIndexV3, TermOrdinal, VoidSSTable,
 };
 
+fn corruption_error(msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("SSTable corruption: {msg}"),
+    )
+}
+
 /// An SSTable is a sorted map that associates sorted `&[u8]` keys
 /// to any kind of typed values.
 ///
@@ -278,11 +285,17 @@ impl<TSSTable: SSTable> Dictionary<TSSTable> {
 
     /// Opens a `TermDictionary`.
     pub fn open(term_dictionary_file: FileSlice) -> io::Result<Self> {
+        if term_dictionary_file.len() < 20 {
+            return Err(corruption_error("the file is smaller than the footer"));
+        }
         let (main_slice, footer_len_slice) = term_dictionary_file.split_from_end(20);
         let mut footer_len_bytes: OwnedBytes = footer_len_slice.read_bytes()?;
         let index_offset = u64::deserialize(&mut footer_len_bytes)?;
         let num_terms = u64::deserialize(&mut footer_len_bytes)?;
         let version = u32::deserialize(&mut footer_len_bytes)?;
+        if index_offset > main_slice.len() as u64 {
+            return Err(corruption_error("the index offset exceeds the file length"));
+        }
         let (sstable_slice, index_slice) = main_slice.split(index_offset as usize);
         let sstable_index_bytes = index_slice.read_bytes()?;
 
@@ -293,6 +306,9 @@ impl<TSSTable: SSTable> Dictionary<TSSTable> {
                 })?,
             ),
             3 => {
+                if sstable_index_bytes.len() < 8 {
+                    return Err(corruption_error("the index is smaller than its footer"));
+                }
                 let (sstable_index_bytes, mut footerv3_len_bytes) = sstable_index_bytes.rsplit(8);
                 let store_offset = u64::deserialize(&mut footerv3_len_bytes)?;
                 if store_offset != 0 {
@@ -323,6 +339,70 @@ impl<TSSTable: SSTable> Dictionary<TSSTable> {
         })
     }
 
+    /// Opens a `TermDictionary` coming from an untrusted source.
+    ///
+    /// On top of what [`Dictionary::open`] does, this decodes the whole index and all of the
+    /// blocks of the dictionary, checking that they are consistent and that the keys are sorted.
+    /// This is as costly as streaming the whole dictionary, but guarantees that the
+    /// lookups on the returned dictionary do not panic, whatever the content of the file.
+    pub fn open_untrusted(term_dictionary_file: FileSlice) -> io::Result<Self> {
+        let dictionary = Self::open(term_dictionary_file)?;
+        dictionary.validate()?;
+        Ok(dictionary)
+    }
+
+    fn validate(&self) -> io::Result<()> {
+        let block_addrs = self.sstable_index.all_blocks()?;
+        let mut key = Vec::new();
+        let mut previous_key = Vec::new();
+        let mut num_terms = 0u64;
+        let mut block_start = 0;
+        for block_addr in block_addrs {
+            if block_addr.byte_range.start != block_start
+                || block_addr.byte_range.end < block_addr.byte_range.start
+                || block_addr.byte_range.end > self.sstable_slice.len()
+            {
+                return Err(corruption_error("invalid block address in the index"));
+            }
+            block_start = block_addr.byte_range.end;
+            if block_addr.first_ordinal != num_terms {
+                return Err(corruption_error("invalid block ordinal in the index"));
+            }
+            // Keys are delta-encoded within a block only.
+            key.clear();
+            let mut delta_reader = self.sstable_delta_reader_block(block_addr)?;
+            while delta_reader.advance()? {
+                let common_prefix_len = delta_reader.common_prefix_len();
+                if common_prefix_len > key.len() {
+                    return Err(corruption_error("invalid key prefix"));
+                }
+                key.truncate(common_prefix_len);
+                key.extend_from_slice(delta_reader.suffix());
+                if num_terms > 0 && key <= previous_key {
+                    return Err(corruption_error("the keys are not sorted"));
+                }
+                previous_key.clone_from(&key);
+                num_terms += 1;
+            }
+        }
+        if num_terms != self.num_terms {
+            return Err(corruption_error(
+                "the number of terms does not match the footer",
+            ));
+        }
+        // Streams read ranges of consecutive blocks, up to the end marker following
+        // the last block.
+        let mut delta_reader = TSSTable::delta_reader(self.sstable_slice.read_bytes()?);
+        let mut num_streamed_terms = 0u64;
+        while delta_reader.advance()? {
+            num_streamed_terms += 1;
+        }
+        if num_streamed_terms != self.num_terms {
+            return Err(corruption_error("invalid end of the sstable"));
+        }
+        Ok(())
+    }
+
     /// Creates a term dictionary from the supplied bytes.
     pub fn from_bytes(owned_bytes: OwnedBytes) -> io::Result<Self> {
         Dictionary::open(FileSlice::new(Arc::new(owned_bytes)))
@@ -1055,6 +1135,76 @@ mod tests {
         }
     }
 
+    fn make_small_sstable() -> Vec<u8> {
+        let mut builder = Dictionary::<MonotonicU64SSTable>::builder(Vec::new()).unwrap();
+        builder.set_block_len(32);
+        for elem in 0..300u64 {
+            let key = format!("{:05X}", elem * 7).into_bytes();
+            builder.insert(&key, &elem).unwrap();
+        }
+        builder.finish().unwrap()
+    }
+
+    fn exercise_dictionary(dict: &Dictionary<MonotonicU64SSTable>) {
+        let mut stream = dict.stream().unwrap();
+        while stream.advance() {}
+        let mut stream = dict.range().ge("00100").lt("00200").into_stream().unwrap();
+        while stream.advance() {}
+        let mut bytes = Vec::new();
+        for ord in 0..dict.num_terms() as u64 + 2 {
+            let _ = dict.ord_to_term(ord, &mut bytes);
+            let _ = dict.term_info_from_ord(ord);
+        }
+        for key in ["", "00000", "00015", "0082D", "FFFFF"] {
+            let _ = dict.get(key);
+            let _ = dict.term_ord(key);
+            let _ = dict.term_ord_or_next(key);
+        }
+    }
+
+    #[test]
+    fn test_open_untrusted() {
+        let dict = Dictionary::<MonotonicU64SSTable>::open_untrusted(
+            common::file_slice::FileSlice::from(make_small_sstable()),
+        )
+        .unwrap();
+        assert_eq!(dict.num_terms(), 300);
+        assert!(dict.sstable_index.locate_with_ord(299) > 1);
+        assert_eq!(dict.get("00015").unwrap(), Some(3));
+        exercise_dictionary(&dict);
+
+        let empty_dict = Dictionary::<MonotonicU64SSTable>::builder(Vec::new())
+            .unwrap()
+            .finish()
+            .unwrap();
+        let dict = Dictionary::<MonotonicU64SSTable>::open_untrusted(
+            common::file_slice::FileSlice::from(empty_dict),
+        )
+        .unwrap();
+        assert_eq!(dict.num_terms(), 0);
+    }
+
+    #[test]
+    fn test_open_untrusted_corrupted() {
+        let bytes = make_small_sstable();
+        for len in 0..bytes.len() {
+            let truncated = common::file_slice::FileSlice::from(bytes[..len].to_vec());
+            assert!(Dictionary::<MonotonicU64SSTable>::open(truncated.clone()).is_err());
+            assert!(Dictionary::<MonotonicU64SSTable>::open_untrusted(truncated).is_err());
+        }
+        for pos in 0..bytes.len() {
+            for mask in [0x01, 0xff] {
+                let mut corrupted = bytes.clone();
+                corrupted[pos] ^= mask;
+                if let Ok(dict) = Dictionary::<MonotonicU64SSTable>::open_untrusted(
+                    common::file_slice::FileSlice::from(corrupted),
+                ) {
+                    exercise_dictionary(&dict);
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_prefix_edge() {
         let dict = {
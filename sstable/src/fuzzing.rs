@@ -0,0 +1,53 @@
+//! Generators of arbitrary, possibly corrupted, sstable files.
+//!
+//! This module is only available with the `arbitrary` feature.
+
+use std::collections::BTreeMap;
+
+use arbitrary::Arbitrary;
+use common::fuzzing::{corrupt, Corruption};
+
+use crate::{Dictionary, MonotonicU64SSTable};
+
+/// An arbitrary sstable file.
+///
+/// The file is obtained by serializing a valid [`Dictionary<MonotonicU64SSTable>`],
+/// and then applying the corruptions to it.
+#[derive(Arbitrary, Clone, Debug)]
+pub struct ArbitrarySSTable {
+    /// The keys of the dictionary, associated to the difference between their value
+    /// and the value of the previous key.
+    pub entries: BTreeMap<Vec<u8>, u32>,
+    /// The length of the blocks of the sstable.
+    ///
+    /// Small blocks make it possible to have indexes with many blocks.
+    pub block_len: u16,
+    /// The corruptions applied to the serialized file.
+    pub corruptions: Vec<Corruption>,
+}
+
+impl ArbitrarySSTable {
+    /// Serializes the sstable, before any corruption.
+    pub fn to_valid_bytes(&self) -> Vec<u8> {
+        let mut writer = Dictionary::<MonotonicU64SSTable>::builder(Vec::new())
+            .expect("Creating a writer in a Vec<u8> should never fail");
+        writer.set_block_len(usize::from(self.block_len).max(1));
+        let mut value = 0u64;
+        for (key, delta) in &self.entries {
+            value += u64::from(*delta);
+            writer
+                .insert(key, &value)
+                .expect("Writing in a Vec<u8> should never fail");
+        }
+        writer
+            .finish()
+            .expect("Writing in a Vec<u8> should never fail")
+    }
+
+    /// Serializes the sstable, and applies the corruptions to it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.to_valid_bytes();
+        corrupt(&mut bytes, &self.corruptions);
+        bytes
+    }
+}
@@ -6,6 +6,8 @@ use merge::ValueMerger;
 mod block_match_automaton;
 mod delta;
 mod dictionary;
+#[cfg(feature = "arbitrary")]
+pub mod fuzzing;
 pub mod merge;
 mod streamer;
 pub mod value;
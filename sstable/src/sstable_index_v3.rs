@@ -66,6 +66,22 @@ impl SSTableIndex {
         }
     }
 
+    /// Returns the [`BlockAddr`] of all of the blocks, in order.
+    ///
+    /// This decodes the whole index, checking that it is well-formed,
+    /// and is meant to validate untrusted files.
+    pub(crate) fn all_blocks(&self) -> io::Result<Vec<BlockAddr>> {
+        match self {
+            SSTableIndex::V2(v2_index) => Ok(v2_index
+                .blocks
+                .iter()
+                .map(|block_meta| block_meta.block_addr.clone())
+                .collect()),
+            SSTableIndex::V3(v3_index) => v3_index.all_blocks(),
+            SSTableIndex::V3Empty(v3_empty) => Ok(vec![v3_empty.block_addr.clone()]),
+        }
+    }
+
     pub fn get_block_for_automaton<'a>(
         &'a self,
         automaton: &'a impl Automaton,
@@ -114,6 +130,9 @@ impl SSTableIndexV3 {
         data: OwnedBytes,
         fst_length: u64,
     ) -> Result<SSTableIndexV3, SSTableDataCorruption> {
+        if fst_length > data.len() as u64 {
+            return Err(SSTableDataCorruption);
+        }
         let (fst_slice, block_addr_store_slice) = data.split(fst_length as usize);
         let fst_index = Fst::new(fst_slice)
             .map_err(|_| SSTableDataCorruption)?
@@ -160,6 +179,40 @@ impl SSTableIndexV3 {
         self.block_addr_store.binary_search_ord(ord).1
     }
 
+    /// Returns the [`BlockAddr`] of all of the blocks, in order.
+    ///
+    /// This decodes the whole index, checking that it is well-formed.
+    pub(crate) fn all_blocks(&self) -> io::Result<Vec<BlockAddr>> {
+        // The fst crate does not validate its input, and may panic when walking a corrupted
+        // fst. Walking the whole fst once visits all of its nodes, so that later lookups
+        // cannot panic if this succeeds.
+        let block_ids = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut block_ids = Vec::new();
+            let mut stream = self.fst_index.stream();
+            while let Some((_key, block_id)) = stream.next() {
+                block_ids.push(block_id);
+            }
+            block_ids
+        }))
+        .map_err(|_| corruption_error("the fst of the sstable index is corrupted"))?;
+        if block_ids
+            .iter()
+            .enumerate()
+            .any(|(expected_block_id, &block_id)| block_id != expected_block_id as u64)
+        {
+            return Err(corruption_error(
+                "the fst of the sstable index does not list the blocks in order",
+            ));
+        }
+        let block_addrs = self.block_addr_store.all_blocks()?;
+        if block_addrs.len() != block_ids.len() {
+            return Err(corruption_error(
+                "the fst and the block addresses of the sstable index do not match",
+            ));
+        }
+        Ok(block_addrs)
+    }
+
     pub(crate) fn get_block_for_automaton<'a>(
         &'a self,
         automaton: &'a impl Automaton,
@@ -421,11 +474,15 @@ impl BlockAddrBlockMetadata {
     }
 
     fn deserialize_block_addr(&self, data: &[u8], inner_offset: usize) -> Option<BlockAddr> {
+        // Arithmetic wraps instead of overflowing, so that a corrupted index yields
+        // wrong addresses rather than panics.
         if inner_offset == 0 {
-            let range_end = self.ref_block_addr.byte_range_start
-                + extract_bits(data, 0, self.range_start_nbits) as usize
-                + self.range_start_slope as usize
-                - self.range_shift as usize;
+            let range_end = self
+                .ref_block_addr
+                .byte_range_start
+                .wrapping_add(extract_bits(data, 0, self.range_start_nbits) as usize)
+                .wrapping_add(self.range_start_slope as usize)
+                .wrapping_sub(self.range_shift as usize);
             return Some(self.ref_block_addr.to_block_addr(range_end));
         }
         let inner_offset = inner_offset - 1;
@@ -442,18 +499,24 @@ impl BlockAddrBlockMetadata {
             return None;
         }
 
-        let range_start = self.ref_block_addr.byte_range_start
-            + extract_bits(data, range_start_addr, self.range_start_nbits) as usize
-            + self.range_start_slope as usize * (inner_offset + 1)
-            - self.range_shift as usize;
-        let first_ordinal = self.ref_block_addr.first_ordinal
-            + extract_bits(data, ordinal_addr, self.first_ordinal_nbits)
-            + self.first_ordinal_slope as u64 * (inner_offset + 1) as u64
-            - self.ordinal_shift as u64;
-        let range_end = self.ref_block_addr.byte_range_start
-            + extract_bits(data, range_end_addr, self.range_start_nbits) as usize
-            + self.range_start_slope as usize * (inner_offset + 2)
-            - self.range_shift as usize;
+        let range_start = self
+            .ref_block_addr
+            .byte_range_start
+            .wrapping_add(extract_bits(data, range_start_addr, self.range_start_nbits) as usize)
+            .wrapping_add(self.range_start_slope as usize * (inner_offset + 1))
+            .wrapping_sub(self.range_shift as usize);
+        let first_ordinal = self
+            .ref_block_addr
+            .first_ordinal
+            .wrapping_add(extract_bits(data, ordinal_addr, self.first_ordinal_nbits))
+            .wrapping_add(self.first_ordinal_slope as u64 * (inner_offset + 1) as u64)
+            .wrapping_sub(self.ordinal_shift as u64);
+        let range_end = self
+            .ref_block_addr
+            .byte_range_start
+            .wrapping_add(extract_bits(data, range_end_addr, self.range_start_nbits) as usize)
+            .wrapping_add(self.range_start_slope as usize * (inner_offset + 2))
+            .wrapping_sub(self.range_shift as usize);
 
         Some(BlockAddr {
             first_ordinal,
@@ -462,7 +525,7 @@ impl BlockAddrBlockMetadata {
     }
 
     fn bisect_for_ord(&self, data: &[u8], target_ord: TermOrdinal) -> (u64, BlockAddr) {
-        let inner_target_ord = target_ord - self.ref_block_addr.first_ordinal;
+        let inner_target_ord = target_ord.wrapping_sub(self.ref_block_addr.first_ordinal);
         let num_bits = self.num_bits() as usize;
         let range_start_nbits = self.range_start_nbits as usize;
         let get_ord = |index| {
@@ -470,8 +533,9 @@ impl BlockAddrBlockMetadata {
                 data,
                 num_bits * index as usize + range_start_nbits,
                 self.first_ordinal_nbits,
-            ) + self.first_ordinal_slope as u64 * (index + 1)
-                - self.ordinal_shift as u64
+            )
+            .wrapping_add(self.first_ordinal_slope as u64 * (index + 1))
+            .wrapping_sub(self.ordinal_shift as u64)
         };
 
         let inner_offset = match binary_search(self.block_len as u64, |index| {
@@ -489,6 +553,13 @@ impl BlockAddrBlockMetadata {
     }
 }
 
+fn corruption_error(msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("SSTable corruption: {msg}"),
+    )
+}
+
 // TODO move this function to tantivy_common?
 #[inline(always)]
 fn extract_bits(data: &[u8], addr_bits: usize, num_bits: u8) -> u64 {
@@ -534,6 +605,11 @@ impl BinarySerializable for BlockAddrBlockMetadata {
         reader.read_exact(&mut buffer)?;
         let first_ordinal_nbits = buffer[0];
         let range_start_nbits = buffer[1];
+        if !(1..=56).contains(&first_ordinal_nbits) || !(1..=56).contains(&range_start_nbits) {
+            return Err(corruption_error(
+                "invalid number of bits in the sstable index block metadata",
+            ));
+        }
         let block_len = u16::deserialize(reader)?;
         Ok(BlockAddrBlockMetadata {
             offset,
@@ -565,9 +641,19 @@ struct BlockAddrStore {
 
 impl BlockAddrStore {
     fn open(term_info_store_file: OwnedBytes) -> io::Result<BlockAddrStore> {
+        if term_info_store_file.len() < 8 {
+            return Err(corruption_error(
+                "the sstable block address store is truncated",
+            ));
+        }
         let (mut len_slice, main_slice) = term_info_store_file.split(8);
-        let len = u64::deserialize(&mut len_slice)? as usize;
-        let (block_meta_bytes, addr_bytes) = main_slice.split(len);
+        let len = u64::deserialize(&mut len_slice)?;
+        if len > main_slice.len() as u64 {
+            return Err(corruption_error(
+                "the sstable block address store is truncated",
+            ));
+        }
+        let (block_meta_bytes, addr_bytes) = main_slice.split(len as usize);
         Ok(BlockAddrStore {
             block_meta_bytes,
             addr_bytes,
@@ -586,11 +672,48 @@ impl BlockAddrStore {
         let inner_offset = (block_id as usize) % STORE_BLOCK_LEN;
         let block_addr_block_data = self.get_block_meta(store_block_id)?;
         block_addr_block_data.deserialize_block_addr(
-            &self.addr_bytes[block_addr_block_data.offset as usize..],
+            self.addr_bytes
+                .get(block_addr_block_data.offset as usize..)?,
             inner_offset,
         )
     }
 
+    /// Returns the [`BlockAddr`] of all of the blocks, in order.
+    fn all_blocks(&self) -> io::Result<Vec<BlockAddr>> {
+        let num_store_blocks = self.block_meta_bytes.len() / BlockAddrBlockMetadata::SIZE_IN_BYTES;
+        if num_store_blocks * BlockAddrBlockMetadata::SIZE_IN_BYTES != self.block_meta_bytes.len() {
+            return Err(corruption_error(
+                "the sstable block metadata have an invalid length",
+            ));
+        }
+        let mut block_addrs = Vec::new();
+        for store_block_id in 0..num_store_blocks {
+            let block_meta = self
+                .get_block_meta(store_block_id)
+                .ok_or_else(|| corruption_error("invalid sstable index block metadata"))?;
+            let num_blocks = block_meta.block_len as usize + 1;
+            // All of the store blocks but the last one are full, so that block ids can be
+            // mapped to store blocks.
+            let is_last = store_block_id + 1 == num_store_blocks;
+            if num_blocks > STORE_BLOCK_LEN || (!is_last && num_blocks != STORE_BLOCK_LEN) {
+                return Err(corruption_error(
+                    "invalid number of blocks in the sstable index block metadata",
+                ));
+            }
+            let data = self
+                .addr_bytes
+                .get(block_meta.offset as usize..)
+                .ok_or_else(|| corruption_error("invalid offset in the sstable index"))?;
+            for inner_offset in 0..num_blocks {
+                let block_addr = block_meta
+                    .deserialize_block_addr(data, inner_offset)
+                    .ok_or_else(|| corruption_error("the sstable index is truncated"))?;
+                block_addrs.push(block_addr);
+            }
+        }
+        Ok(block_addrs)
+    }
+
     fn binary_search_ord(&self, ord: TermOrdinal) -> (u64, BlockAddr) {
         let max_block =
             (self.block_meta_bytes.len() / BlockAddrBlockMetadata::SIZE_IN_BYTES) as u64;
@@ -1,6 +1,8 @@
 use std::io;
 
-use crate::value::{deserialize_vint_u64, ValueReader, ValueWriter};
+use crate::value::{
+    corrupted_values_error, deserialize_num_vals, deserialize_vint_u64, ValueReader, ValueWriter,
+};
 use crate::{vint, BlockAddr};
 
 #[derive(Default)]
@@ -18,7 +20,7 @@ impl ValueReader for IndexValueReader {
 
     fn load(&mut self, mut data: &[u8]) -> io::Result<usize> {
         let original_num_bytes = data.len();
-        let num_vals = deserialize_vint_u64(&mut data) as usize;
+        let num_vals = deserialize_num_vals(&mut data, 2)?;
         self.vals.clear();
         let mut first_ordinal = 0u64;
         let mut prev_start = deserialize_vint_u64(&mut data) as usize;
@@ -26,8 +28,12 @@ impl ValueReader for IndexValueReader {
             let len = deserialize_vint_u64(&mut data);
             let delta_ordinal = deserialize_vint_u64(&mut data);
 
-            first_ordinal += delta_ordinal;
-            let end = prev_start + len as usize;
+            first_ordinal = first_ordinal
+                .checked_add(delta_ordinal)
+                .ok_or_else(corrupted_values_error)?;
+            let end = prev_start
+                .checked_add(len as usize)
+                .ok_or_else(corrupted_values_error)?;
             self.vals.push(BlockAddr {
                 byte_range: prev_start..end,
                 first_ordinal,
@@ -36,6 +42,10 @@ impl ValueReader for IndexValueReader {
         }
         Ok(original_num_bytes - data.len())
     }
+
+    fn num_vals(&self) -> Option<usize> {
+        Some(self.vals.len())
+    }
 }
 
 #[derive(Default)]
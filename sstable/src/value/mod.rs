@@ -19,6 +19,14 @@ pub trait ValueReader: Default {
     ///
     /// Returns the number of bytes that were read.
     fn load(&mut self, data: &[u8]) -> io::Result<usize>;
+
+    /// Returns the number of values in the last block that was read,
+    /// or `None` if any index is valid.
+    ///
+    /// This is used to detect corrupted blocks holding less values than keys.
+    fn num_vals(&self) -> Option<usize> {
+        None
+    }
 }
 
 /// `ValueWriter` is a trait to make it possible to write blocks
@@ -50,6 +58,20 @@ fn deserialize_vint_u64(data: &mut &[u8]) -> u64 {
     val
 }
 
+/// Reads the number of values of a block, checking that the remaining data is large enough
+/// to hold them, given that each value takes at least `min_num_bytes_per_val` bytes.
+fn deserialize_num_vals(data: &mut &[u8], min_num_bytes_per_val: usize) -> io::Result<usize> {
+    let num_vals = deserialize_vint_u64(data);
+    if num_vals > (data.len() / min_num_bytes_per_val) as u64 {
+        return Err(corrupted_values_error());
+    }
+    Ok(num_vals as usize)
+}
+
+fn corrupted_values_error() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "SSTable value block corruption")
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use std::fmt;
@@ -1,7 +1,9 @@
 use std::io;
 use std::ops::Range;
 
-use crate::value::{deserialize_vint_u64, ValueReader, ValueWriter};
+use crate::value::{
+    corrupted_values_error, deserialize_num_vals, deserialize_vint_u64, ValueReader, ValueWriter,
+};
 
 /// See module comment.
 #[derive(Default)]
@@ -20,17 +22,23 @@ impl ValueReader for RangeValueReader {
     fn load(&mut self, mut data: &[u8]) -> io::Result<usize> {
         self.vals.clear();
         let original_num_bytes = data.len();
-        let len = deserialize_vint_u64(&mut data) as usize;
+        let len = deserialize_num_vals(&mut data, 1)?;
         if len != 0 {
             let mut prev_val = deserialize_vint_u64(&mut data);
             for _ in 1..len {
-                let next_val = prev_val + deserialize_vint_u64(&mut data);
+                let next_val = prev_val
+                    .checked_add(deserialize_vint_u64(&mut data))
+                    .ok_or_else(corrupted_values_error)?;
                 self.vals.push(prev_val..next_val);
                 prev_val = next_val;
             }
         }
         Ok(original_num_bytes - data.len())
     }
+
+    fn num_vals(&self) -> Option<usize> {
+        Some(self.vals.len())
+    }
 }
 
 /// Range writer. The range are required to partition the
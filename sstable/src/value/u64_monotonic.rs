@@ -1,6 +1,8 @@
 use std::io;
 
-use crate::value::{deserialize_vint_u64, ValueReader, ValueWriter};
+use crate::value::{
+    corrupted_values_error, deserialize_num_vals, deserialize_vint_u64, ValueReader, ValueWriter,
+};
 use crate::vint;
 
 #[derive(Default)]
@@ -18,17 +20,23 @@ impl ValueReader for U64MonotonicValueReader {
 
     fn load(&mut self, mut data: &[u8]) -> io::Result<usize> {
         let original_num_bytes = data.len();
-        let num_vals = deserialize_vint_u64(&mut data) as usize;
+        let num_vals = deserialize_num_vals(&mut data, 1)?;
         self.vals.clear();
         let mut prev_val = 0u64;
         for _ in 0..num_vals {
             let delta = deserialize_vint_u64(&mut data);
-            let val = prev_val + delta;
+            let val = prev_val
+                .checked_add(delta)
+                .ok_or_else(corrupted_values_error)?;
             self.vals.push(val);
             prev_val = val;
         }
         Ok(original_num_bytes - data.len())
     }
+
+    fn num_vals(&self) -> Option<usize> {
+        Some(self.vals.len())
+    }
 }
 
 #[derive(Default)]
@@ -28,7 +28,8 @@ pub fn deserialize_read(buf: &[u8]) -> (usize, u64) {
 
     for &b in buf {
         consumed += 1;
-        result |= u64::from(b % 128u8) << shift;
+        // Bits past the 64th can only come from corrupted data. They are ignored.
+        result |= u64::from(b % 128u8).checked_shl(shift as u32).unwrap_or(0);
         if b < CONTINUE_BIT {
             break;
         }
@@ -89,19 +89,13 @@ pub fn load<Item: Copy + 'static>(data: &[u8]) -> Item {
 }
 
 /// The `MemoryArena`
+///
+/// Pages are only allocated on demand, so that an empty arena does not use any memory.
+#[derive(Default)]
 pub struct MemoryArena {
     pages: Vec<Page>,
 }
 
-impl Default for MemoryArena {
-    fn default() -> MemoryArena {
-        let first_page = Page::new(0);
-        MemoryArena {
-            pages: vec![first_page],
-        }
-    }
-}
-
 impl MemoryArena {
     /// Returns an estimate in number of bytes
     /// of resident memory consumed by the `MemoryArena`.
@@ -114,7 +108,8 @@ impl MemoryArena {
 
     /// Returns the number of bytes allocated in the arena.
     pub fn len(&self) -> usize {
-        self.pages.len().saturating_sub(1) * PAGE_SIZE + self.pages.last().unwrap().len
+        self.pages.len().saturating_sub(1) * PAGE_SIZE
+            + self.pages.last().map(|page| page.len).unwrap_or(0)
     }
 
     pub fn is_empty(&self) -> bool {
@@ -181,8 +176,11 @@ impl MemoryArena {
     /// Allocates `len` bytes and returns the allocated address.
     #[inline]
     pub fn allocate_space(&mut self, len: usize) -> Addr {
-        let page_id = self.pages.len() - 1;
-        if let Some(addr) = self.get_page_mut(page_id).allocate_space(len) {
+        if let Some(addr) = self
+            .pages
+            .last_mut()
+            .and_then(|page| page.allocate_space(len))
+        {
             return addr;
         }
         self.add_page(len)
@@ -269,6 +267,16 @@ mod tests {
         assert_eq!(arena.slice(addr_b, b.len()), b);
     }
 
+    #[test]
+    fn test_arena_allocates_pages_lazily() {
+        let mut arena = MemoryArena::default();
+        assert_eq!(arena.mem_usage(), 0);
+        assert!(arena.is_empty());
+        arena.allocate_space(3);
+        assert_eq!(arena.mem_usage(), PAGE_SIZE);
+        assert_eq!(arena.len(), 3);
+    }
+
     #[test]
     fn test_arena_allocate_end_of_page() {
         let mut arena = MemoryArena::default();